@@ -0,0 +1,125 @@
+use anyhow::Context as _;
+use clap::Parser;
+
+use zksync_config::{
+    configs::chain::{CommitmentSchemeConfig, StateKeeperConfig},
+    ETHClientConfig,
+};
+use zksync_core::consistency_checker::{CommitmentAuditor, L1BatchAuditReport};
+use zksync_dal::{connection::DbVariant, ConnectionPool};
+use zksync_types::{commitment::CommitmentSchemes, L1BatchNumber};
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Recomputes L1 batch commitments from Postgres data and diffs them against the data committed on L1",
+    long_about = None
+)]
+struct Cli {
+    /// First L1 batch to audit.
+    #[arg(long = "from-l1-batch")]
+    from_l1_batch: u32,
+    /// Last L1 batch to audit (inclusive). If not specified, only the first L1 batch is audited.
+    #[arg(long = "to-l1-batch")]
+    to_l1_batch: Option<u32>,
+    /// Outputs audit reports as JSON objects (one per line), so that they are machine-readable.
+    #[arg(long)]
+    json: bool,
+}
+
+impl Cli {
+    async fn run(self, auditor: &CommitmentAuditor) -> anyhow::Result<()> {
+        let to_l1_batch = self.to_l1_batch.unwrap_or(self.from_l1_batch);
+        anyhow::ensure!(
+            self.from_l1_batch <= to_l1_batch,
+            "Invalid L1 batch range: {}..={to_l1_batch}",
+            self.from_l1_batch
+        );
+
+        let mut inconsistent_batches = vec![];
+        for number in self.from_l1_batch..=to_l1_batch {
+            let number = L1BatchNumber(number);
+            let report = auditor
+                .audit_l1_batch(number)
+                .await
+                .with_context(|| format!("failed auditing L1 batch #{number}"))?;
+            if self.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                Self::print_report(&report);
+            }
+            if !report.is_consistent() {
+                inconsistent_batches.push(number);
+            }
+        }
+
+        anyhow::ensure!(
+            inconsistent_batches.is_empty(),
+            "Inconsistent L1 batches: {inconsistent_batches:?}"
+        );
+        tracing::info!("All audited L1 batches are consistent");
+        Ok(())
+    }
+
+    fn print_report(report: &L1BatchAuditReport) {
+        let number = report.l1_batch_number;
+        match report.commit_tx_hash {
+            Some(tx_hash) => println!("L1 batch #{number} (commit tx {tx_hash:?})"),
+            None => println!("L1 batch #{number} (not committed on L1)"),
+        }
+        if report.is_consistent() {
+            println!("  consistent");
+        }
+        for mismatch in &report.stored_mismatches {
+            println!(
+                "  stored {}: recomputed {}, stored {}",
+                mismatch.component, mismatch.recomputed, mismatch.actual
+            );
+        }
+        for mismatch in &report.committed_mismatches {
+            println!(
+                "  committed {}: recomputed {}, committed {}",
+                mismatch.component, mismatch.recomputed, mismatch.actual
+            );
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let sentry_url = vlog::sentry_url_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let environment = vlog::environment_from_env();
+
+    let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
+    if let Some(sentry_url) = sentry_url {
+        builder = builder
+            .with_sentry_url(&sentry_url)
+            .context("Invalid Sentry URL")?
+            .with_sentry_environment(environment);
+    }
+    let _guard = builder.build();
+
+    let eth_client = ETHClientConfig::from_env().context("ETHClientConfig::from_env()")?;
+    let state_keeper = StateKeeperConfig::from_env().context("StateKeeperConfig::from_env()")?;
+    let commitment_scheme =
+        CommitmentSchemeConfig::from_env().context("CommitmentSchemeConfig::from_env()")?;
+    let commitment_schemes = CommitmentSchemes::try_from(&commitment_scheme)
+        .context("invalid protocol version in commitment scheme config")?;
+    let connection_pool = ConnectionPool::builder(DbVariant::Replica)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+    let auditor = CommitmentAuditor::new(
+        &eth_client.web3_url,
+        connection_pool,
+        state_keeper.l1_batch_commitment_mode,
+        commitment_schemes,
+    )?;
+
+    Cli::parse().run(&auditor).await
+}
@@ -3,12 +3,21 @@ use serde::Deserialize;
 use std::{env, time::Duration};
 use url::Url;
 
-use zksync_basic_types::{Address, L1ChainId, L2ChainId, MiniblockNumber, H256};
+use zksync_basic_types::{
+    Address, L1BatchCommitmentMode, L1ChainId, L2ChainId, MiniblockNumber, PubdataSendingMode, H256,
+};
+use zksync_config::configs::chain::CommitmentSchemeConfig;
 use zksync_contracts::BaseSystemContractsHashes;
-use zksync_core::api_server::{
-    tx_sender::TxSenderConfig, web3::state::InternalApiConfig, web3::Namespace,
+use zksync_core::{
+    api_server::{
+        tx_sender::{PaymasterPolicy, TxSenderConfig},
+        web3::state::InternalApiConfig,
+        web3::Namespace,
+    },
+    gas_tracker::PubdataDaMode,
+    token_registry::TokenPolicy,
 };
-use zksync_types::api::BridgeAddresses;
+use zksync_types::{api::BridgeAddresses, commitment::CommitmentSchemes};
 
 use zksync_web3_decl::{
     jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
@@ -126,12 +135,19 @@ pub struct OptionalENConfig {
     /// Limit for fee history block range.
     #[serde(default = "OptionalENConfig::default_fee_history_limit")]
     pub fee_history_limit: u64,
+    /// Number of latest miniblocks used to estimate fees in `eth_gasPrice` and `eth_maxPriorityFeePerGas`.
+    #[serde(default = "OptionalENConfig::default_fee_estimation_blocks")]
+    pub fee_estimation_blocks: u64,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     #[serde(default = "OptionalENConfig::default_max_batch_request_size")]
     pub max_batch_request_size: usize,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     #[serde(default = "OptionalENConfig::default_max_response_body_size_mb")]
     pub max_response_body_size_mb: usize,
+    /// Hard cap in MiBs on the serialized size of potentially large responses (`eth_getLogs`, `debug_traceBlock*` etc.).
+    /// Default is 8 MiB.
+    #[serde(default = "OptionalENConfig::default_large_response_size_limit_mb")]
+    pub large_response_size_limit_mb: usize,
 
     // Other API config settings
     /// Interval between polling DB for pubsub (in ms).
@@ -196,6 +212,13 @@ pub struct OptionalENConfig {
     /// Whether to try running EN with MultiVM.
     #[serde(default)]
     pub experimental_multivm_support: bool,
+    /// Mode in which the main node commits L1 batches. Must match the main node configuration;
+    /// otherwise, the consistency checker will report commit data mismatches.
+    #[serde(default)]
+    pub l1_batch_commitment_mode: L1BatchCommitmentMode,
+    /// Way in which the main node sends pubdata to L1. Used to estimate L1 gas spent on committing L1 batches.
+    #[serde(default)]
+    pub pubdata_sending_mode: PubdataSendingMode,
 }
 
 impl OptionalENConfig {
@@ -274,6 +297,10 @@ impl OptionalENConfig {
         1_024
     }
 
+    const fn default_fee_estimation_blocks() -> u64 {
+        20
+    }
+
     const fn default_max_batch_request_size() -> usize {
         500 // The default limit is chosen to be reasonably permissive.
     }
@@ -282,6 +309,10 @@ impl OptionalENConfig {
         10
     }
 
+    const fn default_large_response_size_limit_mb() -> usize {
+        8
+    }
+
     pub fn polling_interval(&self) -> Duration {
         Duration::from_millis(self.polling_interval)
     }
@@ -310,6 +341,10 @@ impl OptionalENConfig {
         self.merkle_tree_block_cache_size_mb * BYTES_IN_MEGABYTE
     }
 
+    pub fn pubdata_da_mode(&self) -> PubdataDaMode {
+        PubdataDaMode::new(self.l1_batch_commitment_mode, self.pubdata_sending_mode)
+    }
+
     pub fn api_namespaces(&self) -> Vec<Namespace> {
         self.api_namespaces
             .clone()
@@ -319,6 +354,10 @@ impl OptionalENConfig {
     pub fn max_response_body_size(&self) -> usize {
         self.max_response_body_size_mb * BYTES_IN_MEGABYTE
     }
+
+    pub fn large_response_size_limit(&self) -> usize {
+        self.large_response_size_limit_mb * BYTES_IN_MEGABYTE
+    }
 }
 
 /// This part of the external node config is required for its operation.
@@ -361,11 +400,13 @@ impl RequiredENConfig {
 
 /// External Node Config contains all the configuration required for the EN operation.
 /// It is split into three parts: required, optional and remote for easier navigation.
+/// Additionally, it contains the commitment scheme config, which must match the one used by the main node.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct ExternalNodeConfig {
     pub required: RequiredENConfig,
     pub optional: OptionalENConfig,
     pub remote: RemoteENConfig,
+    pub commitment_scheme: CommitmentSchemeConfig,
 }
 
 impl ExternalNodeConfig {
@@ -380,6 +421,10 @@ impl ExternalNodeConfig {
             .from_env::<OptionalENConfig>()
             .context("could not load external node config")?;
 
+        let commitment_scheme = envy::prefixed("EN_COMMITMENT_SCHEME_")
+            .from_env::<CommitmentSchemeConfig>()
+            .context("could not load commitment scheme config")?;
+
         let client = HttpClientBuilder::default()
             .build(required.main_node_url()?)
             .expect("Unable to build HTTP client for main node");
@@ -428,8 +473,15 @@ impl ExternalNodeConfig {
             remote,
             required,
             optional,
+            commitment_scheme,
         })
     }
+
+    /// Returns schemes used to hash L1 batch commitments.
+    pub fn commitment_schemes(&self) -> anyhow::Result<CommitmentSchemes> {
+        CommitmentSchemes::try_from(&self.commitment_scheme)
+            .context("invalid protocol version in commitment scheme config")
+    }
 }
 
 fn env_var<T>(name: &str) -> T
@@ -463,6 +515,11 @@ impl From<ExternalNodeConfig> for InternalApiConfig {
             l2_testnet_paymaster_addr: config.remote.l2_testnet_paymaster_addr,
             req_entities_limit: config.optional.req_entities_limit,
             fee_history_limit: config.optional.fee_history_limit,
+            fee_estimation_blocks: config.optional.fee_estimation_blocks,
+            large_response_size_limit: config.optional.large_response_size_limit(),
+            l1_batch_commitment_mode: config.optional.l1_batch_commitment_mode,
+            // Token filtering is only configured on the main node.
+            token_policy: TokenPolicy::default(),
         }
     }
 }
@@ -486,6 +543,10 @@ impl From<ExternalNodeConfig> for TxSenderConfig {
             max_allowed_l2_tx_gas_limit: u32::MAX,
             validation_computational_gas_limit: u32::MAX,
             chain_id: config.remote.l2_chain_id,
+            // Paymaster policies are enforced by the main node.
+            paymaster_policy: PaymasterPolicy::default(),
+            // Reverted transactions are rejected, if necessary, by the main node.
+            reject_reverted_txs: false,
         }
     }
 }
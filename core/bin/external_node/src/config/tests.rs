@@ -8,6 +8,7 @@ fn parsing_optional_config_from_empty_env() {
     assert_eq!(config.filters_limit, 10_000);
     assert_eq!(config.subscriptions_limit, 10_000);
     assert_eq!(config.fee_history_limit, 1_024);
+    assert_eq!(config.fee_estimation_blocks, 20);
     assert_eq!(config.polling_interval(), Duration::from_millis(200));
     assert_eq!(config.max_tx_size, 1_000_000);
     assert_eq!(
@@ -25,6 +26,7 @@ fn parsing_optional_config_from_empty_env() {
         128 * BYTES_IN_MEGABYTE
     );
     assert_eq!(config.max_response_body_size(), 10 * BYTES_IN_MEGABYTE);
+    assert_eq!(config.large_response_size_limit(), 8 * BYTES_IN_MEGABYTE);
 }
 
 #[test]
@@ -44,6 +46,7 @@ fn parsing_optional_config_from_env() {
         ("EN_MERKLE_TREE_MULTI_GET_CHUNK_SIZE", "1000"),
         ("EN_MERKLE_TREE_BLOCK_CACHE_SIZE_MB", "32"),
         ("EN_MAX_RESPONSE_BODY_SIZE_MB", "1"),
+        ("EN_LARGE_RESPONSE_SIZE_LIMIT_MB", "1"),
     ];
     let env_vars = env_vars
         .into_iter()
@@ -70,4 +73,5 @@ fn parsing_optional_config_from_env() {
         32 * BYTES_IN_MEGABYTE
     );
     assert_eq!(config.max_response_body_size(), BYTES_IN_MEGABYTE);
+    assert_eq!(config.large_response_size_limit(), BYTES_IN_MEGABYTE);
 }
@@ -58,7 +58,8 @@ async fn build_state_keeper(
         None,
         vec![en_sealer.clone().into_unconditional_batch_seal_criterion()],
         vec![en_sealer.into_miniblock_seal_criterion()],
-    );
+    )
+    .with_pubdata_da_mode(config.optional.pubdata_da_mode());
 
     // These config values are used on the main node, and depending on these values certain transactions can
     // be *rejected* (that is, not included into the block). However, external node only mirrors what the main
@@ -76,6 +77,7 @@ async fn build_state_keeper(
             max_allowed_l2_tx_gas_limit,
             save_call_traces,
             false,
+            config.optional.pubdata_da_mode(),
         ));
 
     let io = Box::new(
@@ -147,6 +149,8 @@ async fn init_tasks(
         max_l1_batches_per_iter: config.optional.max_l1_batches_per_tree_iter,
         multi_get_chunk_size: config.optional.merkle_tree_multi_get_chunk_size,
         block_cache_capacity: config.optional.merkle_tree_block_cache_size(),
+        pubdata_da_mode: config.optional.pubdata_da_mode(),
+        commitment_schemes: config.commitment_schemes()?,
     })
     .await;
     healthchecks.push(Box::new(metadata_calculator.tree_health_check()));
@@ -161,6 +165,7 @@ async fn init_tasks(
             .build()
             .await
             .context("failed to build connection pool for ConsistencyChecker")?,
+        config.optional.l1_batch_commitment_mode,
     );
 
     let batch_status_updater = BatchStatusUpdater::new(
@@ -392,6 +397,8 @@ async fn main() -> anyhow::Result<()> {
         &mut connection_pool.access_storage().await.unwrap(),
         config.remote.l2_chain_id,
         main_node_url.clone(),
+        config.commitment_schemes()?,
+        config.optional.l1_batch_commitment_mode,
     )
     .await
     .context("Performing genesis failed")?;
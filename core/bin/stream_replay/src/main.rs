@@ -0,0 +1,83 @@
+use anyhow::Context as _;
+use clap::Parser;
+
+use zksync_config::configs::{chain::NetworkConfig, StreamPublisherConfig};
+use zksync_core::stream_publisher::{connect_sink, StreamPublisher};
+use zksync_dal::{connection::DbVariant, ConnectionPool};
+use zksync_types::{L2ChainId, MiniblockNumber};
+
+#[derive(Debug, Parser)]
+#[command(
+    author = "Matter Labs",
+    version,
+    about = "Re-publishes a range of sealed miniblocks to the stream publisher topic",
+    long_about = None
+)]
+struct Cli {
+    /// First miniblock to publish.
+    #[arg(long = "from-miniblock")]
+    from_miniblock: u32,
+    /// Last miniblock to publish (inclusive). If not specified, miniblocks are published
+    /// up to the last sealed one.
+    #[arg(long = "to-miniblock")]
+    to_miniblock: Option<u32>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let log_format = vlog::log_format_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let sentry_url = vlog::sentry_url_from_env();
+    #[allow(deprecated)] // TODO (QIT-21): Use centralized configuration approach.
+    let environment = vlog::environment_from_env();
+
+    let mut builder = vlog::ObservabilityBuilder::new().with_log_format(log_format);
+    if let Some(sentry_url) = sentry_url {
+        builder = builder
+            .with_sentry_url(&sentry_url)
+            .context("Invalid Sentry URL")?
+            .with_sentry_environment(environment);
+    }
+    let _guard = builder.build();
+
+    let opts = Cli::parse();
+    let config = StreamPublisherConfig::from_env().context("StreamPublisherConfig::from_env()")?;
+    let network_config = NetworkConfig::from_env().context("NetworkConfig::from_env()")?;
+    let connection_pool = ConnectionPool::builder(DbVariant::Replica)
+        .build()
+        .await
+        .context("failed to build a connection pool")?;
+
+    let to_miniblock = match opts.to_miniblock {
+        Some(number) => MiniblockNumber(number),
+        None => connection_pool
+            .access_storage()
+            .await?
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?,
+    };
+    let from_miniblock = MiniblockNumber(opts.from_miniblock);
+    anyhow::ensure!(
+        from_miniblock <= to_miniblock,
+        "Invalid miniblock range: {from_miniblock}..={to_miniblock}"
+    );
+
+    let sink = connect_sink(&config)
+        .await
+        .context("failed connecting to stream publisher backend")?;
+    let publisher = StreamPublisher::new(
+        &config,
+        sink,
+        connection_pool,
+        L2ChainId(network_config.zksync_network_id),
+    );
+    publisher.replay(from_miniblock..=to_miniblock).await?;
+    tracing::info!(
+        "Replayed miniblocks #{from_miniblock}..=#{to_miniblock} to topic `{}`",
+        publisher.topic()
+    );
+    Ok(())
+}
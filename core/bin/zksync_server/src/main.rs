@@ -1,13 +1,15 @@
 use anyhow::Context as _;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 use std::{str::FromStr, time::Duration};
-use zksync_config::configs::chain::NetworkConfig;
+use zksync_config::configs::chain::{CommitmentSchemeConfig, NetworkConfig, StateKeeperConfig};
 
-use zksync_config::{ContractsConfig, ETHSenderConfig};
+use zksync_config::{
+    configs::MultiChainConfig, ContractsConfig, DBConfig, ETHSenderConfig, EnvVars,
+};
 use zksync_core::{
-    genesis_init, initialize_components, is_genesis_needed, setup_sigint_handler, Component,
-    Components,
+    genesis_init, initialize_components, is_genesis_needed, multi_chain::initialize_chains,
+    set_miniblock_data_foreign_keys, setup_sigint_handler, Component, Components,
 };
 use zksync_storage::RocksDB;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
@@ -25,6 +27,11 @@ struct Cli {
     /// Rebuild tree.
     #[arg(long)]
     rebuild_tree: bool,
+    /// Drop or restore foreign keys to miniblocks from miniblock data tables and exit. Keys must be dropped
+    /// before enabling parallel miniblock sealing, and should be restored after the state keeper is restarted
+    /// with parallel sealing disabled.
+    #[arg(long, value_enum)]
+    miniblock_data_foreign_keys: Option<ForeignKeysAction>,
     /// Comma-separated list of components to launch.
     #[arg(
         long,
@@ -33,6 +40,12 @@ struct Cli {
     components: ComponentsToRun,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ForeignKeysAction {
+    Drop,
+    Restore,
+}
+
 #[derive(Debug, Clone)]
 struct ComponentsToRun(Vec<Component>);
 
@@ -49,6 +62,31 @@ impl FromStr for ComponentsToRun {
     }
 }
 
+/// Performs genesis for the chain configured via `env_vars` if it's forced or required.
+async fn run_genesis_if_needed(force: bool, env_vars: &EnvVars) -> anyhow::Result<()> {
+    let db_config = DBConfig::from_env_vars(env_vars).context("DBConfig")?;
+    if force || is_genesis_needed(&db_config).await? {
+        let network = NetworkConfig::from_env_vars(env_vars).context("NetworkConfig")?;
+        let eth_sender = ETHSenderConfig::from_env_vars(env_vars).context("ETHSenderConfig")?;
+        let contracts = ContractsConfig::from_env_vars(env_vars).context("ContractsConfig")?;
+        let state_keeper =
+            StateKeeperConfig::from_env_vars(env_vars).context("StateKeeperConfig")?;
+        let commitment_schemes =
+            CommitmentSchemeConfig::from_env_vars(env_vars).context("CommitmentSchemeConfig")?;
+        genesis_init(
+            &db_config,
+            &eth_sender,
+            &network,
+            &contracts,
+            &state_keeper,
+            &commitment_schemes,
+        )
+        .await
+        .context("genesis_init")?;
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let opt = Cli::parse();
@@ -76,16 +114,36 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("No sentry URL was provided");
     }
 
-    if opt.genesis || is_genesis_needed().await {
-        let network = NetworkConfig::from_env().context("NetworkConfig")?;
-        let eth_sender = ETHSenderConfig::from_env().context("ETHSenderConfig")?;
-        let contracts = ContractsConfig::from_env().context("ContractsConfig")?;
-        genesis_init(&eth_sender, &network, &contracts)
-            .await
-            .context("genesis_init")?;
-        if opt.genesis {
-            return Ok(());
+    let multi_chain_config = MultiChainConfig::from_env().context("MultiChainConfig")?;
+    let env_vars = EnvVars::from_env();
+    if let Some(action) = opt.miniblock_data_foreign_keys {
+        let enforce = matches!(action, ForeignKeysAction::Restore);
+        if multi_chain_config.chains.is_empty() {
+            let db_config = DBConfig::from_env_vars(&env_vars).context("DBConfig")?;
+            set_miniblock_data_foreign_keys(&db_config, enforce).await?;
+        } else {
+            for chain in &multi_chain_config.chains {
+                let db_config =
+                    DBConfig::from_env_vars(&env_vars.for_chain(chain)).context("DBConfig")?;
+                set_miniblock_data_foreign_keys(&db_config, enforce)
+                    .await
+                    .with_context(|| format!("changing foreign keys failed for chain `{chain}`"))?;
+            }
         }
+        tracing::info!("Miniblock data foreign keys: {action:?} completed");
+        return Ok(());
+    }
+    if multi_chain_config.chains.is_empty() {
+        run_genesis_if_needed(opt.genesis, &env_vars).await?;
+    } else {
+        for chain in &multi_chain_config.chains {
+            run_genesis_if_needed(opt.genesis, &env_vars.for_chain(chain))
+                .await
+                .with_context(|| format!("genesis failed for chain `{chain}`"))?;
+        }
+    }
+    if opt.genesis {
+        return Ok(());
     }
 
     let components = if opt.rebuild_tree {
@@ -102,10 +160,27 @@ async fn main() -> anyhow::Result<()> {
     );
 
     // Run core actors.
-    let (core_task_handles, stop_sender, cb_receiver, health_check_handle) =
-        initialize_components(components, is_only_oneshot_witness_generator_task)
+    let (core_task_handles, stop_sender, cb_receiver, health_check_handles) =
+        if multi_chain_config.chains.is_empty() {
+            let (task_handles, stop_sender, cb_receiver, health_check_handle) =
+                initialize_components(components, is_only_oneshot_witness_generator_task)
+                    .await
+                    .context("Unable to start Core actors")?;
+            (
+                task_handles,
+                stop_sender,
+                cb_receiver,
+                vec![health_check_handle],
+            )
+        } else {
+            initialize_chains(
+                &multi_chain_config.chains,
+                components,
+                is_only_oneshot_witness_generator_task,
+            )
             .await
-            .context("Unable to start Core actors")?;
+            .context("Unable to start Core actors")?
+        };
 
     tracing::info!("Running {} core task handlers", core_task_handles.len());
     let sigint_receiver = setup_sigint_handler();
@@ -133,7 +208,9 @@ async fn main() -> anyhow::Result<()> {
         .unwrap();
     // Sleep for some time to let some components gracefully stop.
     tokio::time::sleep(Duration::from_secs(5)).await;
-    health_check_handle.stop().await;
+    for health_check_handle in health_check_handles {
+        health_check_handle.stop().await;
+    }
     tracing::info!("Stopped");
     Ok(())
 }
@@ -151,3 +151,96 @@ impl Default for PriorityOpId {
         Self(0)
     }
 }
+
+/// Mode in which L1 batches are committed to L1.
+///
+/// The mode influences both the batch commitment and the data published to L1 alongside it.
+/// In the validium mode, storage writes are not part of the auxiliary commitment output,
+/// since the state is committed to via the Merkle tree root hash only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum L1BatchCommitmentMode {
+    /// Full pubdata (storage writes, L2-to-L1 messages, published bytecodes) is posted to L1 as calldata.
+    #[default]
+    Rollup,
+    /// Storage writes are kept off-chain and are not included into the batch commitment.
+    Validium,
+}
+
+impl L1BatchCommitmentMode {
+    /// Returns `true` if storage writes are published to L1 in this mode.
+    pub fn publishes_storage_writes(self) -> bool {
+        matches!(self, Self::Rollup)
+    }
+}
+
+impl FromStr for L1BatchCommitmentMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rollup" => Ok(Self::Rollup),
+            "validium" => Ok(Self::Validium),
+            other => Err(format!(
+                "unknown L1 batch commitment mode `{other}`; expected one of `rollup`, `validium`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for L1BatchCommitmentMode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Rollup => "rollup",
+            Self::Validium => "validium",
+        })
+    }
+}
+
+/// Way in which pubdata of committed L1 batches is sent to L1.
+///
+/// Only relevant in the [`L1BatchCommitmentMode::Rollup`] mode; in the validium mode, storage writes
+/// are kept off-chain regardless of this setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PubdataSendingMode {
+    /// Pubdata is sent as a part of the commit transaction calldata.
+    #[default]
+    Calldata,
+    /// Pubdata is sent in EIP-4844 blobs attached to the commit transaction.
+    Blobs,
+}
+
+impl FromStr for PubdataSendingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "calldata" => Ok(Self::Calldata),
+            "blobs" => Ok(Self::Blobs),
+            other => Err(format!(
+                "unknown pubdata sending mode `{other}`; expected one of `calldata`, `blobs`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PubdataSendingMode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Calldata => "calldata",
+            Self::Blobs => "blobs",
+        })
+    }
+}
+
+/// Hash function used in L1 batch commitments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitmentHashFunction {
+    /// Keccak-256, as used by the zkSync Era L1 contracts.
+    #[default]
+    Keccak256,
+    /// BLAKE2s-256.
+    Blake2s256,
+}
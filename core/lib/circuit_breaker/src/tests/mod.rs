@@ -164,6 +164,10 @@ impl EthInterface for ETHDirectClientMock {
         Ok(Default::default())
     }
 
+    async fn get_blob_base_fee(&self, _component: &'static str) -> Result<U256, Error> {
+        Ok(Default::default())
+    }
+
     async fn failure_reason(&self, _tx_hash: H256) -> Result<Option<FailureInfo>, Error> {
         Ok(Default::default())
     }
@@ -5,9 +5,9 @@ use serde::Deserialize;
 use std::net::SocketAddr;
 use std::time::Duration;
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 pub use crate::configs::PrometheusConfig;
-use zksync_basic_types::H256;
+use zksync_basic_types::{Address, H256};
 
 /// API configuration.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -20,16 +20,29 @@ pub struct ApiConfig {
     pub prometheus: PrometheusConfig,
     /// Configuration options for the Health check.
     pub healthcheck: HealthCheckConfig,
+    /// Configuration options for the Firehose-compatible block stream.
+    pub firehose: FirehoseApiConfig,
+    /// Configuration options for the GraphQL API.
+    pub graphql: GraphqlApiConfig,
+    /// Configuration options for the Rosetta Data API.
+    pub rosetta: RosettaApiConfig,
 }
 
 impl ApiConfig {
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
         Ok(Self {
-            web3_json_rpc: Web3JsonRpcConfig::from_env().context("Web3JsonRpcConfig")?,
-            contract_verification: ContractVerificationApiConfig::from_env()
+            web3_json_rpc: Web3JsonRpcConfig::from_env_vars(vars).context("Web3JsonRpcConfig")?,
+            contract_verification: ContractVerificationApiConfig::from_env_vars(vars)
                 .context("ContractVerificationApiConfig")?,
-            prometheus: PrometheusConfig::from_env().context("PrometheusConfig")?,
-            healthcheck: HealthCheckConfig::from_env().context("HealthCheckConfig")?,
+            prometheus: PrometheusConfig::from_env_vars(vars).context("PrometheusConfig")?,
+            healthcheck: HealthCheckConfig::from_env_vars(vars).context("HealthCheckConfig")?,
+            firehose: FirehoseApiConfig::from_env_vars(vars).context("FirehoseApiConfig")?,
+            graphql: GraphqlApiConfig::from_env_vars(vars).context("GraphqlApiConfig")?,
+            rosetta: RosettaApiConfig::from_env_vars(vars).context("RosettaApiConfig")?,
         })
     }
 }
@@ -78,6 +91,13 @@ pub struct Web3JsonRpcConfig {
     /// This option can be tweaked down if the API server is running out of memory.
     /// If not set, the VM concurrency limit will be efficiently disabled.
     pub vm_concurrency_limit: Option<usize>,
+    /// Number of threads in the dedicated thread pool used to execute VM code in the API sandbox.
+    /// If not set, VM code is executed on the shared blocking Tokio threadpool. If set, this effectively caps
+    /// the number of concurrent VM invocations (i.e., overrides `vm_concurrency_limit` if the latter is greater).
+    pub vm_thread_pool_size: Option<usize>,
+    /// IDs of CPU cores to pin threads of the API VM thread pool to (in the round-robin order).
+    /// Ignored if `vm_thread_pool_size` is not set.
+    pub vm_thread_pool_pinned_cores: Option<Vec<usize>>,
     /// Smart contract cache size in MiBs. The default value is 128 MiB.
     pub factory_deps_cache_size_mb: Option<usize>,
     /// Initial writes cache size in MiBs. The default value is 32 MiB.
@@ -85,6 +105,11 @@ pub struct Web3JsonRpcConfig {
     /// Latest values cache size in MiBs. The default value is 128 MiB. If set to 0, the latest
     /// values cache will be disabled.
     pub latest_values_cache_size_mb: Option<usize>,
+    /// Path to store info logs of a RocksDB secondary instance opened for the state keeper cache
+    /// (`DATABASE_STATE_KEEPER_DB_PATH`). If set, latest storage values are read from the state keeper cache
+    /// instead of Postgres where possible. Requires the cache (or its replicated copy) to be accessible
+    /// from the API server.
+    pub state_keeper_secondary_db_path: Option<String>,
     /// Override value for the amount of threads used for HTTP RPC server.
     /// If not set, the value from `threads_per_server` is used.
     pub http_threads: Option<u32>,
@@ -93,19 +118,48 @@ pub struct Web3JsonRpcConfig {
     pub ws_threads: Option<u32>,
     /// Limit for fee history block range.
     pub fee_history_limit: Option<u64>,
+    /// Number of latest miniblocks used to estimate fees in `eth_gasPrice` and `eth_maxPriorityFeePerGas`.
+    /// Default is 20.
+    pub fee_estimation_blocks: Option<u64>,
     /// Maximum number of requests in a single batch JSON RPC request. Default is 500.
     pub max_batch_request_size: Option<usize>,
     /// Maximum response body size in MiBs. Default is 10 MiB.
     pub max_response_body_size_mb: Option<usize>,
+    /// Hard cap in MiBs on the serialized size of potentially large responses (`eth_getLogs`, `debug_traceBlock*` etc.)
+    /// enforced on all transports while the response is assembled. Exceeding it results in a "response too large" error
+    /// asking to narrow the query. Default is 8 MiB.
+    pub large_response_size_limit_mb: Option<usize>,
     /// Maximum number of requests per minute for the WebSocket server.
     /// The value is per active connection.
     /// Note: For HTTP, rate limiting is expected to be configured on the infra level.
     pub websocket_requests_per_minute_limit: Option<u32>,
+    /// Maximum number of cached transaction validation outcomes. Cached outcomes are invalidated once any storage slot
+    /// read during validation is modified. If not set, validation outcomes are not cached.
+    pub validation_cache_size: Option<usize>,
+    /// Paymasters allowed to sponsor transactions submitted via the API. If not set, any paymaster is allowed.
+    pub allowed_paymasters: Option<Vec<Address>>,
+    /// Maximum gas limit of a transaction sponsored by a paymaster. If not set, sponsored transactions
+    /// are only limited by the generic transaction gas limit.
+    pub max_sponsored_gas: Option<u64>,
+    /// Tokens that can be used to pay fees in the approval-based paymaster flow. If not set, any token is allowed.
+    pub allowed_paymaster_tokens: Option<Vec<Address>>,
+    /// If set, `eth_` namespace responses follow geth conventions where zkSync deviates from them:
+    /// zkSync-specific fields are omitted, and error codes / messages match the ones returned by geth.
+    #[serde(default)]
+    pub strict_geth_compatibility: bool,
+    /// If set, transactions reverting when simulated on submission are rejected by `eth_sendRawTransaction`
+    /// with the revert reason, rather than included into a block as failed transactions.
+    #[serde(default)]
+    pub reject_reverted_txs: bool,
 }
 
 impl Web3JsonRpcConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("web3_json_rpc", "API_WEB3_JSON_RPC_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("web3_json_rpc", "API_WEB3_JSON_RPC_")
     }
 
     pub fn http_bind_addr(&self) -> SocketAddr {
@@ -174,6 +228,10 @@ impl Web3JsonRpcConfig {
         self.fee_history_limit.unwrap_or(1024)
     }
 
+    pub fn fee_estimation_blocks(&self) -> u64 {
+        self.fee_estimation_blocks.unwrap_or(20)
+    }
+
     pub fn max_batch_request_size(&self) -> usize {
         // The default limit is chosen to be reasonably permissive.
         self.max_batch_request_size.unwrap_or(500)
@@ -183,6 +241,11 @@ impl Web3JsonRpcConfig {
         self.max_response_body_size_mb.unwrap_or(10) * super::BYTES_IN_MEGABYTE
     }
 
+    pub fn large_response_size_limit(&self) -> usize {
+        // The default limit is lower than the default max response body size, so that the JSON-RPC envelope fits.
+        self.large_response_size_limit_mb.unwrap_or(8) * super::BYTES_IN_MEGABYTE
+    }
+
     pub fn websocket_requests_per_minute_limit(&self) -> u32 {
         // The default limit is chosen to be reasonably permissive.
         self.websocket_requests_per_minute_limit.unwrap_or(6000)
@@ -197,7 +260,11 @@ pub struct HealthCheckConfig {
 
 impl HealthCheckConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("healthcheck", "API_HEALTHCHECK_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("healthcheck", "API_HEALTHCHECK_")
     }
 
     pub fn bind_addr(&self) -> SocketAddr {
@@ -221,7 +288,105 @@ impl ContractVerificationApiConfig {
     }
 
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("contract_verification", "API_CONTRACT_VERIFICATION_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("contract_verification", "API_CONTRACT_VERIFICATION_")
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct FirehoseApiConfig {
+    /// Port to which the block stream server is listening.
+    pub port: u16,
+    /// Interval between checks for new miniblocks when a stream follows the chain head.
+    pub poll_interval_ms: u64,
+    /// Maximum number of concurrently served block streams. Streams requested after the limit
+    /// is reached are rejected.
+    pub max_concurrent_streams: Option<usize>,
+}
+
+impl FirehoseApiConfig {
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    pub fn max_concurrent_streams(&self) -> usize {
+        self.max_concurrent_streams.unwrap_or(64)
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("firehose", "API_FIREHOSE_")
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct GraphqlApiConfig {
+    /// Port to which the GraphQL server is listening.
+    pub port: u16,
+    /// Maximum number of requests per minute served by the GraphQL server to a single client
+    /// (identified by its IP address). Must be positive. If not set, requests are not rate-limited.
+    pub requests_per_minute_limit: Option<u32>,
+    /// Maximum number of items returned in a single page. The default value is 100.
+    pub max_page_size: Option<usize>,
+    /// Maximum nesting depth of a query. The default value is 10.
+    pub max_query_depth: Option<usize>,
+    /// Maximum complexity (roughly, the number of resolved fields) of a query. The default value is 1000.
+    pub max_query_complexity: Option<usize>,
+}
+
+impl GraphqlApiConfig {
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
+    }
+
+    pub fn max_page_size(&self) -> usize {
+        self.max_page_size.unwrap_or(100)
+    }
+
+    pub fn max_query_depth(&self) -> usize {
+        self.max_query_depth.unwrap_or(10)
+    }
+
+    pub fn max_query_complexity(&self) -> usize {
+        self.max_query_complexity.unwrap_or(1_000)
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("graphql", "API_GRAPHQL_")
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct RosettaApiConfig {
+    /// Port to which the Rosetta Data API server is listening.
+    pub port: u16,
+}
+
+impl RosettaApiConfig {
+    pub fn bind_addr(&self) -> SocketAddr {
+        SocketAddr::new("0.0.0.0".parse().unwrap(), self.port)
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("rosetta", "API_ROSETTA_")
     }
 }
 
@@ -230,7 +395,7 @@ mod tests {
     use std::net::IpAddr;
 
     use super::*;
-    use crate::configs::test_utils::{hash, EnvMutex};
+    use crate::configs::test_utils::{addr, hash, EnvMutex};
 
     static MUTEX: EnvMutex = EnvMutex::new();
 
@@ -259,15 +424,29 @@ mod tests {
                 max_tx_size: 1000000,
                 vm_execution_cache_misses_limit: None,
                 vm_concurrency_limit: Some(512),
+                vm_thread_pool_size: Some(16),
+                vm_thread_pool_pinned_cores: Some(vec![2, 3]),
                 factory_deps_cache_size_mb: Some(128),
                 initial_writes_cache_size_mb: Some(32),
                 latest_values_cache_size_mb: Some(256),
+                state_keeper_secondary_db_path: Some("/db/state_keeper_secondary".to_owned()),
                 http_threads: Some(128),
                 ws_threads: Some(256),
                 fee_history_limit: Some(100),
+                fee_estimation_blocks: Some(10),
                 max_batch_request_size: Some(200),
                 max_response_body_size_mb: Some(10),
+                large_response_size_limit_mb: Some(5),
                 websocket_requests_per_minute_limit: Some(10),
+                validation_cache_size: Some(10000),
+                allowed_paymasters: Some(vec![addr("0x0000000000000000000000000000000000000001")]),
+                max_sponsored_gas: Some(10_000_000),
+                allowed_paymaster_tokens: Some(vec![
+                    addr("0x0000000000000000000000000000000000000002"),
+                    addr("0x0000000000000000000000000000000000000003"),
+                ]),
+                strict_geth_compatibility: true,
+                reject_reverted_txs: true,
             },
             contract_verification: ContractVerificationApiConfig {
                 port: 3070,
@@ -280,6 +459,19 @@ mod tests {
                 push_interval_ms: Some(100),
             },
             healthcheck: HealthCheckConfig { port: 8081 },
+            firehose: FirehoseApiConfig {
+                port: 3072,
+                poll_interval_ms: 500,
+                max_concurrent_streams: Some(32),
+            },
+            graphql: GraphqlApiConfig {
+                port: 3073,
+                requests_per_minute_limit: Some(600),
+                max_page_size: Some(50),
+                max_query_depth: Some(8),
+                max_query_complexity: Some(500),
+            },
+            rosetta: RosettaApiConfig { port: 3074 },
         }
     }
 
@@ -305,22 +497,42 @@ mod tests {
             API_WEB3_JSON_RPC_ESTIMATE_GAS_ACCEPTABLE_OVERESTIMATION=1000
             API_WEB3_JSON_RPC_MAX_TX_SIZE=1000000
             API_WEB3_JSON_RPC_VM_CONCURRENCY_LIMIT=512
+            API_WEB3_JSON_RPC_VM_THREAD_POOL_SIZE=16
+            API_WEB3_JSON_RPC_VM_THREAD_POOL_PINNED_CORES="2,3"
             API_WEB3_JSON_RPC_FACTORY_DEPS_CACHE_SIZE_MB=128
             API_WEB3_JSON_RPC_INITIAL_WRITES_CACHE_SIZE_MB=32
             API_WEB3_JSON_RPC_LATEST_VALUES_CACHE_SIZE_MB=256
+            API_WEB3_JSON_RPC_STATE_KEEPER_SECONDARY_DB_PATH="/db/state_keeper_secondary"
             API_WEB3_JSON_RPC_HTTP_THREADS=128
             API_WEB3_JSON_RPC_WS_THREADS=256
             API_WEB3_JSON_RPC_FEE_HISTORY_LIMIT=100
+            API_WEB3_JSON_RPC_FEE_ESTIMATION_BLOCKS=10
             API_WEB3_JSON_RPC_MAX_BATCH_REQUEST_SIZE=200
             API_WEB3_JSON_RPC_WEBSOCKET_REQUESTS_PER_MINUTE_LIMIT=10
+            API_WEB3_JSON_RPC_VALIDATION_CACHE_SIZE=10000
+            API_WEB3_JSON_RPC_ALLOWED_PAYMASTERS="0x0000000000000000000000000000000000000001"
+            API_WEB3_JSON_RPC_MAX_SPONSORED_GAS=10000000
+            API_WEB3_JSON_RPC_ALLOWED_PAYMASTER_TOKENS="0x0000000000000000000000000000000000000002,0x0000000000000000000000000000000000000003"
+            API_WEB3_JSON_RPC_STRICT_GETH_COMPATIBILITY=true
+            API_WEB3_JSON_RPC_REJECT_REVERTED_TXS=true
             API_CONTRACT_VERIFICATION_PORT="3070"
             API_CONTRACT_VERIFICATION_URL="http://127.0.0.1:3070"
             API_CONTRACT_VERIFICATION_THREADS_PER_SERVER=128
             API_WEB3_JSON_RPC_MAX_RESPONSE_BODY_SIZE_MB=10
+            API_WEB3_JSON_RPC_LARGE_RESPONSE_SIZE_LIMIT_MB=5
             API_PROMETHEUS_LISTENER_PORT="3312"
             API_PROMETHEUS_PUSHGATEWAY_URL="http://127.0.0.1:9091"
             API_PROMETHEUS_PUSH_INTERVAL_MS=100
             API_HEALTHCHECK_PORT=8081
+            API_FIREHOSE_PORT=3072
+            API_FIREHOSE_POLL_INTERVAL_MS=500
+            API_FIREHOSE_MAX_CONCURRENT_STREAMS=32
+            API_GRAPHQL_PORT=3073
+            API_GRAPHQL_REQUESTS_PER_MINUTE_LIMIT=600
+            API_GRAPHQL_MAX_PAGE_SIZE=50
+            API_GRAPHQL_MAX_QUERY_DEPTH=8
+            API_GRAPHQL_MAX_QUERY_COMPLEXITY=500
+            API_ROSETTA_PORT=3074
         "#;
         lock.set_env(config);
 
@@ -5,10 +5,12 @@ use serde::Deserialize;
 use std::time::Duration;
 // Local uses
 use zksync_basic_types::network::Network;
-use zksync_basic_types::{Address, H256};
+use zksync_basic_types::{
+    Address, CommitmentHashFunction, L1BatchCommitmentMode, PubdataSendingMode, H256,
+};
 use zksync_contracts::BaseSystemContractsHashes;
 
-use super::envy_load;
+use super::EnvVars;
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct ChainConfig {
@@ -22,18 +24,27 @@ pub struct ChainConfig {
     pub mempool: MempoolConfig,
     /// circuit breaker configuration
     pub circuit_breaker: CircuitBreakerConfig,
+    /// L1 batch commitment scheme configuration
+    pub commitment_scheme: CommitmentSchemeConfig,
 }
 
 impl ChainConfig {
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
         Ok(Self {
             // TODO rename `eth` to `network`
-            network: NetworkConfig::from_env().context("NetworkConfig")?,
-            state_keeper: StateKeeperConfig::from_env().context("StateKeeperConfig")?,
-            operations_manager: OperationsManagerConfig::from_env()
+            network: NetworkConfig::from_env_vars(vars).context("NetworkConfig")?,
+            state_keeper: StateKeeperConfig::from_env_vars(vars).context("StateKeeperConfig")?,
+            operations_manager: OperationsManagerConfig::from_env_vars(vars)
                 .context("OperationsManagerConfig")?,
-            mempool: MempoolConfig::from_env().context("MempoolConfig")?,
-            circuit_breaker: CircuitBreakerConfig::from_env().context("CircuitBreakerConfig")?,
+            mempool: MempoolConfig::from_env_vars(vars).context("MempoolConfig")?,
+            circuit_breaker: CircuitBreakerConfig::from_env_vars(vars)
+                .context("CircuitBreakerConfig")?,
+            commitment_scheme: CommitmentSchemeConfig::from_env_vars(vars)
+                .context("CommitmentSchemeConfig")?,
         })
     }
 }
@@ -52,7 +63,11 @@ pub struct NetworkConfig {
 
 impl NetworkConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("network", "CHAIN_ETH_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("network", "CHAIN_ETH_")
     }
 }
 
@@ -69,6 +84,12 @@ pub struct StateKeeperConfig {
     /// sealing will block until some of the miniblocks from the queue are processed.
     /// 0 means that sealing is synchronous; this is mostly useful for performance comparison, testing etc.
     pub miniblock_seal_queue_capacity: usize,
+    /// If set, independent miniblock data (storage logs, events, etc.) is persisted in parallel connections,
+    /// with the miniblock header inserted last as the atomic marker that the miniblock is sealed.
+    /// Requires foreign keys to miniblocks from miniblock data tables to be dropped beforehand
+    /// (`zksync_server --miniblock-data-foreign-keys=drop`); otherwise, the state keeper refuses to start.
+    #[serde(default)]
+    pub parallel_miniblock_sealing: bool,
 
     /// The max number of gas to spend on an L1 tx before its batch should be sealed by the gas sealer.
     pub max_single_tx_gas: u32,
@@ -109,11 +130,64 @@ pub struct StateKeeperConfig {
     /// Flag which will enable storage to cache witness_inputs during State Keeper's run.
     /// NOTE: This will slow down StateKeeper, to be used in non-production environments!
     pub upload_witness_inputs_to_gcs: bool,
+
+    /// Mode in which L1 batches are committed to L1 (i.e., whether storage writes are published as pubdata).
+    /// If not specified, the rollup mode is used.
+    #[serde(default)]
+    pub l1_batch_commitment_mode: L1BatchCommitmentMode,
+    /// Way in which pubdata is sent to L1. Only affects rollup chains; if not specified, pubdata
+    /// is sent as calldata.
+    #[serde(default)]
+    pub pubdata_sending_mode: PubdataSendingMode,
+
+    /// Number of threads in the dedicated thread pool used to run batch executors.
+    /// If not set, batch executors are run on the shared blocking Tokio threadpool.
+    pub vm_thread_pool_size: Option<usize>,
+    /// IDs of CPU cores to pin threads of the batch executor thread pool to (in the round-robin order).
+    /// Ignored if `vm_thread_pool_size` is not set.
+    pub vm_thread_pool_pinned_cores: Option<Vec<usize>>,
+    /// Maximum number of transaction execution hints passed from the API server to the state keeper
+    /// if they run in the same process. If not set, hints are not collected.
+    pub tx_execution_hints_capacity: Option<usize>,
+    /// Port of the admin HTTP server used to trigger profiling of transaction execution. Only used
+    /// if the server is built with the `profiling` feature; if not set, profiling is disabled.
+    /// The server has no authentication and only listens on the loopback interface.
+    pub profiling_admin_port: Option<u16>,
+    /// Enables the developer mode intended for local dapp development: each transaction is sealed
+    /// in its own L1 batch immediately, L1 batches are marked as executed without sending anything to L1,
+    /// and `hardhat_*` / `evm_*` methods are exposed by the Web3 API running in the same process.
+    /// Must never be enabled in production.
+    #[serde(default)]
+    pub dev_mode: bool,
+    /// URL of a remote zkSync Era node to fork the state from. Requires the dev mode. If set, storage slots
+    /// and bytecodes missing from the local state are lazily fetched from the remote node.
+    pub fork_url: Option<String>,
+    /// Number of the remote miniblock to pin the forked state at. If not set, the latest miniblock
+    /// of the remote node at the moment the node is started is used.
+    pub fork_miniblock: Option<u32>,
+
+    /// Maximum number of sealed L1 batches without Merkle tree metadata. If exceeded, the state keeper stops
+    /// accepting transactions until the tree catches up. Not limited if not set.
+    pub max_l1_batches_without_metadata: Option<u32>,
+    /// Maximum number of sealed L1 batches without a generated proof (as tracked by the proof data handler).
+    /// If exceeded, the state keeper stops accepting transactions until proofs catch up. Not limited if not set.
+    pub max_l1_batches_without_proof: Option<u32>,
+    /// Maximum number of sealed L1 batches without a confirmed commit transaction on L1. If exceeded,
+    /// the state keeper stops accepting transactions until the eth_sender catches up. Not limited if not set.
+    pub max_uncommitted_l1_batches: Option<u32>,
+    /// Maximum gas (as reported in transaction receipts) used by all transactions in a miniblock. If a transaction
+    /// would exceed it, the miniblock is sealed and the transaction is included into the next one.
+    /// Not limited if not set.
+    pub max_gas_per_miniblock: Option<u64>,
 }
 
 impl StateKeeperConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("state_keeper", "CHAIN_STATE_KEEPER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("state_keeper", "CHAIN_STATE_KEEPER_")
     }
 
     pub fn base_system_contracts_hashes(&self) -> BaseSystemContractsHashes {
@@ -132,12 +206,20 @@ pub struct OperationsManagerConfig {
 
 impl OperationsManagerConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("operations_manager", "CHAIN_OPERATIONS_MANAGER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("operations_manager", "CHAIN_OPERATIONS_MANAGER_")
     }
 
     pub fn delay_interval(&self) -> Duration {
         Duration::from_millis(self.delay_interval)
     }
+
+    pub fn tx_lifecycle_events_retention(&self) -> Duration {
+        Duration::from_secs(self.tx_lifecycle_events_retention_sec)
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -150,7 +232,11 @@ pub struct CircuitBreakerConfig {
 
 impl CircuitBreakerConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("circuit_breaker", "CHAIN_CIRCUIT_BREAKER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("circuit_breaker", "CHAIN_CIRCUIT_BREAKER_")
     }
 
     pub fn sync_interval(&self) -> Duration {
@@ -170,9 +256,17 @@ pub struct MempoolConfig {
     pub stuck_tx_timeout: u64,
     pub remove_stuck_txs: bool,
     pub delay_interval: u64,
+    /// Retention period (in seconds) of transaction lifecycle events recorded before transactions
+    /// are sealed in an L1 batch. Older events are pruned by the mempool fetcher.
+    #[serde(default = "MempoolConfig::default_tx_lifecycle_events_retention_sec")]
+    pub tx_lifecycle_events_retention_sec: u64,
 }
 
 impl MempoolConfig {
+    const fn default_tx_lifecycle_events_retention_sec() -> u64 {
+        7 * 24 * 3_600 // 1 week
+    }
+
     pub fn sync_interval(&self) -> Duration {
         Duration::from_millis(self.sync_interval_ms)
     }
@@ -186,7 +280,41 @@ impl MempoolConfig {
     }
 
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("mempool", "CHAIN_MEMPOOL_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("mempool", "CHAIN_MEMPOOL_")
+    }
+}
+
+/// Scheme used to hash L1 batch commitments. By default, commitments are computed in the same way as
+/// the zkSync Era L1 contracts do; chains with modified L1 contracts may customize the scheme starting
+/// from a certain protocol version.
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+pub struct CommitmentSchemeConfig {
+    /// Protocol version starting from which the custom scheme is used. If not set, the default scheme
+    /// is used for all protocol versions, and other params in this config are ignored.
+    #[serde(default)]
+    pub custom_since_protocol_version: Option<u16>,
+    /// Hash function applied to commitment components (pass-through data, meta parameters and auxiliary output).
+    #[serde(default)]
+    pub component_hash_function: CommitmentHashFunction,
+    /// Hash function used to combine component hashes into the commitment.
+    #[serde(default)]
+    pub commitment_hash_function: CommitmentHashFunction,
+    /// Custom fields appended to the commitment preimage after the component hashes.
+    #[serde(default)]
+    pub custom_fields: Vec<H256>,
+}
+
+impl CommitmentSchemeConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("commitment_scheme", "CHAIN_COMMITMENT_SCHEME_")
     }
 }
 
@@ -209,6 +337,7 @@ mod tests {
                 block_commit_deadline_ms: 2500,
                 miniblock_commit_deadline_ms: 1000,
                 miniblock_seal_queue_capacity: 10,
+                parallel_miniblock_sealing: true,
                 max_single_tx_gas: 1_000_000,
                 max_allowed_l2_tx_gas_limit: 2_000_000_000,
                 close_block_at_eth_params_percentage: 0.2,
@@ -226,6 +355,19 @@ mod tests {
                 virtual_blocks_interval: 1,
                 virtual_blocks_per_miniblock: 1,
                 upload_witness_inputs_to_gcs: false,
+                l1_batch_commitment_mode: L1BatchCommitmentMode::Validium,
+                pubdata_sending_mode: PubdataSendingMode::Blobs,
+                vm_thread_pool_size: Some(1),
+                vm_thread_pool_pinned_cores: Some(vec![0]),
+                tx_execution_hints_capacity: Some(1000),
+                profiling_admin_port: Some(3322),
+                dev_mode: true,
+                fork_url: Some("http://127.0.0.1:3050".to_owned()),
+                fork_miniblock: Some(1000),
+                max_l1_batches_without_metadata: Some(10),
+                max_l1_batches_without_proof: None,
+                max_uncommitted_l1_batches: Some(100),
+                max_gas_per_miniblock: Some(30_000_000),
             },
             operations_manager: OperationsManagerConfig {
                 delay_interval: 100,
@@ -237,6 +379,7 @@ mod tests {
                 stuck_tx_timeout: 10,
                 remove_stuck_txs: true,
                 delay_interval: 100,
+                tx_lifecycle_events_retention_sec: 3_600,
             },
             circuit_breaker: CircuitBreakerConfig {
                 sync_interval_ms: 1000,
@@ -244,6 +387,12 @@ mod tests {
                 http_req_retry_interval_sec: 2,
                 replication_lag_limit_sec: Some(10),
             },
+            commitment_scheme: CommitmentSchemeConfig {
+                custom_since_protocol_version: Some(17),
+                component_hash_function: CommitmentHashFunction::Blake2s256,
+                commitment_hash_function: CommitmentHashFunction::Keccak256,
+                custom_fields: vec![H256::repeat_byte(1), H256::repeat_byte(2)],
+            },
         }
     }
 
@@ -267,12 +416,25 @@ mod tests {
             CHAIN_STATE_KEEPER_BLOCK_COMMIT_DEADLINE_MS="2500"
             CHAIN_STATE_KEEPER_MINIBLOCK_COMMIT_DEADLINE_MS="1000"
             CHAIN_STATE_KEEPER_MINIBLOCK_SEAL_QUEUE_CAPACITY="10"
+            CHAIN_STATE_KEEPER_PARALLEL_MINIBLOCK_SEALING="true"
             CHAIN_STATE_KEEPER_FAIR_L2_GAS_PRICE="250000000"
             CHAIN_STATE_KEEPER_BOOTLOADER_HASH="0xfefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefe"
             CHAIN_STATE_KEEPER_DEFAULT_AA_HASH="0xfefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefefe"
             CHAIN_STATE_KEEPER_VALIDATION_COMPUTATIONAL_GAS_LIMIT="10000000"
             CHAIN_STATE_KEEPER_SAVE_CALL_TRACES="false"
             CHAIN_STATE_KEEPER_UPLOAD_WITNESS_INPUTS_TO_GCS="false"
+            CHAIN_STATE_KEEPER_L1_BATCH_COMMITMENT_MODE="validium"
+            CHAIN_STATE_KEEPER_PUBDATA_SENDING_MODE="blobs"
+            CHAIN_STATE_KEEPER_VM_THREAD_POOL_SIZE="1"
+            CHAIN_STATE_KEEPER_VM_THREAD_POOL_PINNED_CORES="0"
+            CHAIN_STATE_KEEPER_TX_EXECUTION_HINTS_CAPACITY="1000"
+            CHAIN_STATE_KEEPER_PROFILING_ADMIN_PORT="3322"
+            CHAIN_STATE_KEEPER_DEV_MODE="true"
+            CHAIN_STATE_KEEPER_FORK_URL="http://127.0.0.1:3050"
+            CHAIN_STATE_KEEPER_FORK_MINIBLOCK="1000"
+            CHAIN_STATE_KEEPER_MAX_L1_BATCHES_WITHOUT_METADATA="10"
+            CHAIN_STATE_KEEPER_MAX_UNCOMMITTED_L1_BATCHES="100"
+            CHAIN_STATE_KEEPER_MAX_GAS_PER_MINIBLOCK="30000000"
             CHAIN_OPERATIONS_MANAGER_DELAY_INTERVAL="100"
             CHAIN_MEMPOOL_SYNC_INTERVAL_MS="10"
             CHAIN_MEMPOOL_SYNC_BATCH_SIZE="1000"
@@ -280,10 +442,14 @@ mod tests {
             CHAIN_MEMPOOL_REMOVE_STUCK_TXS="true"
             CHAIN_MEMPOOL_DELAY_INTERVAL="100"
             CHAIN_MEMPOOL_CAPACITY="1000000"
+            CHAIN_MEMPOOL_TX_LIFECYCLE_EVENTS_RETENTION_SEC="3600"
             CHAIN_CIRCUIT_BREAKER_SYNC_INTERVAL_MS="1000"
             CHAIN_CIRCUIT_BREAKER_HTTP_REQ_MAX_RETRY_NUMBER="5"
             CHAIN_CIRCUIT_BREAKER_HTTP_REQ_RETRY_INTERVAL_SEC="2"
             CHAIN_CIRCUIT_BREAKER_REPLICATION_LAG_LIMIT_SEC="10"
+            CHAIN_COMMITMENT_SCHEME_CUSTOM_SINCE_PROTOCOL_VERSION="17"
+            CHAIN_COMMITMENT_SCHEME_COMPONENT_HASH_FUNCTION="blake2s256"
+            CHAIN_COMMITMENT_SCHEME_CUSTOM_FIELDS="0x0101010101010101010101010101010101010101010101010101010101010101,0x0202020202020202020202020202020202020202020202020202020202020202"
         "#;
         lock.set_env(config);
 
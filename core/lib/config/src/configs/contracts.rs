@@ -3,7 +3,7 @@ use serde::Deserialize;
 // Workspace uses
 use zksync_basic_types::{Address, H256};
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 
 /// Data about deployed contracts.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -38,7 +38,11 @@ pub struct ContractsConfig {
 
 impl ContractsConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("contracts", "CONTRACTS_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("contracts", "CONTRACTS_")
     }
 }
 
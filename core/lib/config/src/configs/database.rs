@@ -1,8 +1,9 @@
+use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 
 use std::time::Duration;
 
-use super::envy_load;
+use super::EnvVars;
 
 /// Mode of operation for the Merkle tree.
 ///
@@ -86,12 +87,27 @@ impl MerkleTreeConfig {
 /// Database configuration.
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct DBConfig {
+    /// URL of the master Postgres database.
+    pub url: Option<String>,
+    /// URL of the replica Postgres database. If not specified, the master database is used.
+    pub replica_url: Option<String>,
+    /// URL of the prover Postgres database. If not specified, the master database is used.
+    pub prover_url: Option<String>,
+    /// Maximum size of Postgres connection pools, unless the pool size is set for a specific pool.
+    pub pool_size: Option<u32>,
+    /// Postgres schema used for all database connections instead of the default `public` one.
+    /// Allows running several chains on a single database.
+    pub schema: Option<String>,
     /// Statement timeout in seconds for Postgres connections. Applies only to the replica
     /// connection pool used by the API servers.
     pub statement_timeout_sec: Option<u64>,
     /// Path to the RocksDB data directory that serves state cache.
     #[serde(default = "DBConfig::default_state_keeper_db_path")]
     pub state_keeper_db_path: String,
+    /// Whether to bulk-load the state keeper cache if it's empty instead of replaying all L1 batches.
+    /// Bulk loading is significantly faster for nodes with a long history.
+    #[serde(default)]
+    pub state_keeper_db_bulk_load: bool,
     /// Merkle tree configuration.
     #[serde(skip)]
     // ^ Filled in separately in `Self::from_env()`. We cannot use `serde(flatten)` because it
@@ -103,6 +119,12 @@ pub struct DBConfig {
     /// Time interval between performing backups.
     #[serde(default = "DBConfig::default_backup_interval_ms")]
     pub backup_interval_ms: u64,
+    /// Number of chunk column values copied per chunk by the online migrations component.
+    #[serde(default = "DBConfig::default_online_migrations_chunk_size")]
+    pub online_migrations_chunk_size: u32,
+    /// Pause between copying chunks by the online migrations component.
+    #[serde(default = "DBConfig::default_online_migrations_pause_ms")]
+    pub online_migrations_pause_ms: u64,
 }
 
 impl DBConfig {
@@ -118,13 +140,46 @@ impl DBConfig {
         60_000
     }
 
+    const fn default_online_migrations_chunk_size() -> u32 {
+        1_000
+    }
+
+    const fn default_online_migrations_pause_ms() -> u64 {
+        100
+    }
+
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
         Ok(Self {
-            merkle_tree: envy_load("database_merkle_tree", "DATABASE_MERKLE_TREE_")?,
-            ..envy_load("database", "DATABASE_")?
+            merkle_tree: vars.load("database_merkle_tree", "DATABASE_MERKLE_TREE_")?,
+            ..vars.load("database", "DATABASE_")?
         })
     }
 
+    /// Returns the master database URL.
+    pub fn master_url(&self) -> anyhow::Result<&str> {
+        self.url.as_deref().context("DATABASE_URL must be set")
+    }
+
+    /// Returns the replica database URL, falling back to the master database URL.
+    pub fn replica_url(&self) -> anyhow::Result<&str> {
+        match &self.replica_url {
+            Some(url) => Ok(url),
+            None => self.master_url(),
+        }
+    }
+
+    /// Returns the prover database URL, falling back to the master database URL.
+    pub fn prover_url(&self) -> anyhow::Result<&str> {
+        match &self.prover_url {
+            Some(url) => Ok(url),
+            None => self.master_url(),
+        }
+    }
+
     /// Returns the Postgres statement timeout.
     pub fn statement_timeout(&self) -> Option<Duration> {
         self.statement_timeout_sec.map(Duration::from_secs)
@@ -133,6 +188,10 @@ impl DBConfig {
     pub fn backup_interval(&self) -> Duration {
         Duration::from_millis(self.backup_interval_ms)
     }
+
+    pub fn online_migrations_pause(&self) -> Duration {
+        Duration::from_millis(self.online_migrations_pause_ms)
+    }
 }
 
 #[cfg(test)]
@@ -146,7 +205,12 @@ mod tests {
     fn from_env() {
         let mut lock = MUTEX.lock();
         let config = r#"
+            DATABASE_URL="postgres://postgres@localhost/zksync_local"
+            DATABASE_PROVER_URL="postgres://postgres@localhost/prover_local"
+            DATABASE_POOL_SIZE=50
+            DATABASE_SCHEMA=era
             DATABASE_STATE_KEEPER_DB_PATH="/db/state_keeper"
+            DATABASE_STATE_KEEPER_DB_BULK_LOAD=true
             DATABASE_MERKLE_TREE_BACKUP_PATH="/db/backups"
             DATABASE_MERKLE_TREE_PATH="/db/tree"
             DATABASE_MERKLE_TREE_MODE=lightweight
@@ -154,11 +218,28 @@ mod tests {
             DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER=50
             DATABASE_BACKUP_COUNT=5
             DATABASE_BACKUP_INTERVAL_MS=60000
+            DATABASE_ONLINE_MIGRATIONS_CHUNK_SIZE=500
+            DATABASE_ONLINE_MIGRATIONS_PAUSE_MS=1000
         "#;
         lock.set_env(config);
 
         let db_config = DBConfig::from_env().unwrap();
+        assert_eq!(
+            db_config.master_url().unwrap(),
+            "postgres://postgres@localhost/zksync_local"
+        );
+        assert_eq!(
+            db_config.replica_url().unwrap(),
+            "postgres://postgres@localhost/zksync_local"
+        );
+        assert_eq!(
+            db_config.prover_url().unwrap(),
+            "postgres://postgres@localhost/prover_local"
+        );
+        assert_eq!(db_config.pool_size, Some(50));
+        assert_eq!(db_config.schema.as_deref(), Some("era"));
         assert_eq!(db_config.state_keeper_db_path, "/db/state_keeper");
+        assert!(db_config.state_keeper_db_bulk_load);
         assert_eq!(db_config.merkle_tree.path, "/db/tree");
         assert_eq!(db_config.merkle_tree.backup_path, "/db/backups");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Lightweight);
@@ -166,13 +247,21 @@ mod tests {
         assert_eq!(db_config.merkle_tree.max_l1_batches_per_iter, 50);
         assert_eq!(db_config.backup_count, 5);
         assert_eq!(db_config.backup_interval().as_secs(), 60);
+        assert_eq!(db_config.online_migrations_chunk_size, 500);
+        assert_eq!(db_config.online_migrations_pause(), Duration::from_secs(1));
     }
 
     #[test]
     fn from_empty_env() {
         let mut lock = MUTEX.lock();
         lock.remove_env(&[
+            "DATABASE_URL",
+            "DATABASE_REPLICA_URL",
+            "DATABASE_PROVER_URL",
+            "DATABASE_POOL_SIZE",
+            "DATABASE_SCHEMA",
             "DATABASE_STATE_KEEPER_DB_PATH",
+            "DATABASE_STATE_KEEPER_DB_BULK_LOAD",
             "DATABASE_MERKLE_TREE_BACKUP_PATH",
             "DATABASE_MERKLE_TREE_PATH",
             "DATABASE_MERKLE_TREE_MODE",
@@ -181,10 +270,16 @@ mod tests {
             "DATABASE_MERKLE_TREE_MAX_L1_BATCHES_PER_ITER",
             "DATABASE_BACKUP_COUNT",
             "DATABASE_BACKUP_INTERVAL_MS",
+            "DATABASE_ONLINE_MIGRATIONS_CHUNK_SIZE",
+            "DATABASE_ONLINE_MIGRATIONS_PAUSE_MS",
         ]);
 
         let db_config = DBConfig::from_env().unwrap();
+        assert!(db_config.master_url().is_err());
+        assert_eq!(db_config.pool_size, None);
+        assert_eq!(db_config.schema, None);
         assert_eq!(db_config.state_keeper_db_path, "./db/state_keeper");
+        assert!(!db_config.state_keeper_db_bulk_load);
         assert_eq!(db_config.merkle_tree.path, "./db/lightweight-new");
         assert_eq!(db_config.merkle_tree.backup_path, "./db/backups");
         assert_eq!(db_config.merkle_tree.mode, MerkleTreeMode::Full);
@@ -193,6 +288,8 @@ mod tests {
         assert_eq!(db_config.merkle_tree.block_cache_size_mb, 128);
         assert_eq!(db_config.backup_count, 5);
         assert_eq!(db_config.backup_interval().as_secs(), 60);
+        assert_eq!(db_config.online_migrations_chunk_size, 1_000);
+        assert_eq!(db_config.online_migrations_pause_ms, 100);
 
         // Check that new env variable for Merkle tree path is supported
         lock.set_env("DATABASE_MERKLE_TREE_PATH=/db/tree/main");
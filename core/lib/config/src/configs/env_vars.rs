@@ -0,0 +1,150 @@
+use anyhow::Context as _;
+use serde::de::DeserializeOwned;
+
+use std::{collections::HashMap, env};
+
+use super::MultiChainConfig;
+
+/// Snapshot of environment variables that configs are loaded from.
+///
+/// Unlike the process environment, a snapshot can be amended with overrides (e.g., for a specific chain
+/// run by a multi-chain server) without affecting other code that reads the environment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvVars {
+    vars: HashMap<String, String>,
+}
+
+impl EnvVars {
+    /// Takes a snapshot of the process environment. Variables with non-UTF-8 names or values are skipped.
+    pub fn from_env() -> Self {
+        env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect()
+    }
+
+    /// Returns variables for the specified chain. Variables prefixed with [`MultiChainConfig::env_prefix()`]
+    /// for the chain override the corresponding unprefixed variables.
+    #[must_use]
+    pub fn for_chain(&self, chain: &str) -> Self {
+        let prefix = MultiChainConfig::env_prefix(chain);
+        let overrides: Vec<_> = self
+            .vars
+            .iter()
+            .filter_map(|(name, value)| {
+                let name = name.strip_prefix(&prefix)?;
+                (!name.is_empty()).then(|| (name.to_owned(), value.clone()))
+            })
+            .collect();
+
+        let mut vars = self.vars.clone();
+        vars.extend(overrides);
+        Self { vars }
+    }
+
+    /// Returns the value of the specified variable.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// Loads a config from the variables with the specified prefix.
+    pub fn load<T: DeserializeOwned>(&self, name: &str, prefix: &str) -> anyhow::Result<T> {
+        envy::prefixed(prefix)
+            .from_iter(self.vars.clone())
+            .with_context(|| format!("Cannot load config <{name}>"))
+    }
+}
+
+impl FromIterator<(String, String)> for EnvVars {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Self {
+            vars: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    fn env_vars(pairs: &[(&str, &str)]) -> EnvVars {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn applying_chain_overrides() {
+        let vars = env_vars(&[
+            ("DATABASE_URL", "postgres://localhost/zksync_local"),
+            ("DATABASE_POOL_SIZE", "50"),
+            ("ERA__DATABASE_URL", "postgres://localhost/era"),
+            ("ERA__CHAIN_ETH_ZKSYNC_NETWORK_ID", "270"),
+            ("ERA_TEST__DATABASE_URL", "postgres://localhost/era_test"),
+            ("ERA__", "ignored"),
+        ]);
+
+        let era_vars = vars.for_chain("era");
+        assert_eq!(
+            era_vars.get("DATABASE_URL"),
+            Some("postgres://localhost/era")
+        );
+        assert_eq!(era_vars.get("CHAIN_ETH_ZKSYNC_NETWORK_ID"), Some("270"));
+        assert_eq!(era_vars.get("DATABASE_POOL_SIZE"), Some("50"));
+        assert_eq!(era_vars.get(""), None);
+
+        let test_vars = vars.for_chain("era_test");
+        assert_eq!(
+            test_vars.get("DATABASE_URL"),
+            Some("postgres://localhost/era_test")
+        );
+        assert_eq!(test_vars.get("CHAIN_ETH_ZKSYNC_NETWORK_ID"), None);
+
+        assert_eq!(vars.for_chain("other"), vars);
+        // The snapshot itself must not be changed.
+        assert_eq!(
+            vars.get("DATABASE_URL"),
+            Some("postgres://localhost/zksync_local")
+        );
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct TestConfig {
+        url: String,
+        pool_size: Option<u32>,
+    }
+
+    #[test]
+    fn loading_config_from_vars() {
+        let vars = env_vars(&[
+            ("DATABASE_URL", "postgres://localhost/zksync_local"),
+            ("ERA__DATABASE_URL", "postgres://localhost/era"),
+            ("ERA__DATABASE_POOL_SIZE", "10"),
+        ]);
+
+        let config: TestConfig = vars.load("test", "DATABASE_").unwrap();
+        assert_eq!(
+            config,
+            TestConfig {
+                url: "postgres://localhost/zksync_local".to_owned(),
+                pool_size: None,
+            }
+        );
+        let config: TestConfig = vars.for_chain("era").load("test", "DATABASE_").unwrap();
+        assert_eq!(
+            config,
+            TestConfig {
+                url: "postgres://localhost/era".to_owned(),
+                pool_size: Some(10),
+            }
+        );
+
+        let err = vars
+            .load::<TestConfig>("test", "OTHER_")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Cannot load config <test>"), "{err}");
+    }
+}
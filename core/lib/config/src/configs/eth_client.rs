@@ -1,7 +1,7 @@
 // External uses
 use serde::Deserialize;
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 
 /// Configuration for the Ethereum gateways.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -14,7 +14,11 @@ pub struct ETHClientConfig {
 
 impl ETHClientConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        let config: Self = envy_load("eth_client", "ETH_CLIENT_")?;
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        let config: Self = vars.load("eth_client", "ETH_CLIENT_")?;
         if config.web3_url.find(',').is_some() {
             anyhow::bail!(
                 "Multiple web3 URLs aren't supported anymore. Provided invalid value: {}",
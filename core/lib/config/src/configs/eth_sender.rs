@@ -6,7 +6,7 @@ use serde::Deserialize;
 // Workspace uses
 use zksync_basic_types::H256;
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 
 /// Configuration for the Ethereum sender crate.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -19,9 +19,13 @@ pub struct ETHSenderConfig {
 
 impl ETHSenderConfig {
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
         Ok(Self {
-            sender: SenderConfig::from_env().context("SenderConfig")?,
-            gas_adjuster: GasAdjusterConfig::from_env().context("GasAdjusterConfig")?,
+            sender: SenderConfig::from_env_vars(vars).context("SenderConfig")?,
+            gas_adjuster: GasAdjusterConfig::from_env_vars(vars).context("GasAdjusterConfig")?,
         })
     }
 }
@@ -72,6 +76,30 @@ pub struct SenderConfig {
 
     /// The mode in which proofs are loaded, either from DB/GCS for FRI/Old proof.
     pub proof_loading_mode: ProofLoadingMode,
+
+    /// If set, costs of publishing pubdata of each commit operation as calldata and in blobs are estimated
+    /// at current L1 prices and recorded for analysis. Pubdata is still sent as calldata regardless of the estimate.
+    /// Only has effect for rollup chains.
+    #[serde(default)]
+    pub estimate_pubdata_costs: bool,
+    /// If set, L1 batches are only executed on L1 after the inclusion of their pubdata on the external DA layer
+    /// is confirmed. Only has effect for validium chains.
+    ///
+    /// The server doesn't dispatch pubdata to the DA layer itself; blob IDs and inclusion data must be recorded
+    /// by an external dispatcher. Without one, enabling this flag blocks execution of all L1 batches, hence it's
+    /// disabled by default.
+    #[serde(default)]
+    pub wait_for_da_inclusion: bool,
+    /// If set, the number of L1 batches aggregated in commit / execute operations and the aggregation deadlines
+    /// are scaled up when L1 is congested, so that fewer L1 transactions are sent. Otherwise, the static limits
+    /// above are always used.
+    #[serde(default)]
+    pub congestion_adaptive_aggregation: bool,
+    /// Ratio of the latest L1 base fee to the median base fee at which L1 is considered fully congested.
+    /// Must be greater than 1.
+    pub congestion_base_fee_ratio: Option<f64>,
+    /// Factor by which aggregation limits and deadlines are multiplied if L1 is fully congested.
+    pub congestion_max_aggregation_factor: Option<f64>,
 }
 
 impl SenderConfig {
@@ -91,8 +119,20 @@ impl SenderConfig {
             .map(|pk| pk.parse().unwrap())
     }
 
+    pub fn congestion_base_fee_ratio(&self) -> f64 {
+        self.congestion_base_fee_ratio.unwrap_or(2.0)
+    }
+
+    pub fn congestion_max_aggregation_factor(&self) -> f64 {
+        self.congestion_max_aggregation_factor.unwrap_or(4.0)
+    }
+
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("eth_sender", "ETH_SENDER_SENDER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("eth_sender", "ETH_SENDER_SENDER_")
     }
 }
 
@@ -114,6 +154,9 @@ pub struct GasAdjusterConfig {
     pub poll_period: u64,
     /// Max number of l1 gas price that is allowed to be used in state keeper.
     pub max_l1_gas_price: Option<u64>,
+    /// Price (in wei) of publishing a byte of pubdata on the external DA layer used by validium chains.
+    #[serde(default)]
+    pub external_da_price_per_pubdata_byte: u64,
 }
 
 impl GasAdjusterConfig {
@@ -127,7 +170,11 @@ impl GasAdjusterConfig {
     }
 
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("eth_sender.gas_adjuster", "ETH_SENDER_GAS_ADJUSTER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("eth_sender.gas_adjuster", "ETH_SENDER_GAS_ADJUSTER_")
     }
 }
 
@@ -159,6 +206,11 @@ mod tests {
                 l1_batch_min_age_before_execute_seconds: Some(1000),
                 max_acceptable_priority_fee_in_gwei: 100_000_000_000,
                 proof_loading_mode: ProofLoadingMode::OldProofFromDb,
+                estimate_pubdata_costs: true,
+                wait_for_da_inclusion: true,
+                congestion_adaptive_aggregation: true,
+                congestion_base_fee_ratio: Some(1.5),
+                congestion_max_aggregation_factor: None,
             },
             gas_adjuster: GasAdjusterConfig {
                 default_priority_fee_per_gas: 20000000000,
@@ -169,6 +221,7 @@ mod tests {
                 internal_enforced_l1_gas_price: None,
                 poll_period: 15,
                 max_l1_gas_price: Some(100000000),
+                external_da_price_per_pubdata_byte: 5,
             },
         }
     }
@@ -190,6 +243,7 @@ mod tests {
             ETH_SENDER_GAS_ADJUSTER_INTERNAL_L1_PRICING_MULTIPLIER="0.8"
             ETH_SENDER_GAS_ADJUSTER_POLL_PERIOD="15"
             ETH_SENDER_GAS_ADJUSTER_MAX_L1_GAS_PRICE="100000000"
+            ETH_SENDER_GAS_ADJUSTER_EXTERNAL_DA_PRICE_PER_PUBDATA_BYTE="5"
             ETH_SENDER_WAIT_FOR_PROOFS="false"
             ETH_SENDER_SENDER_AGGREGATED_PROOF_SIZES="1,5"
             ETH_SENDER_SENDER_MAX_AGGREGATED_BLOCKS_TO_COMMIT="3"
@@ -203,6 +257,10 @@ mod tests {
             ETH_SENDER_SENDER_L1_BATCH_MIN_AGE_BEFORE_EXECUTE_SECONDS="1000"
             ETH_SENDER_SENDER_MAX_ACCEPTABLE_PRIORITY_FEE_IN_GWEI="100000000000"
             ETH_SENDER_SENDER_PROOF_LOADING_MODE="OldProofFromDb"
+            ETH_SENDER_SENDER_ESTIMATE_PUBDATA_COSTS="true"
+            ETH_SENDER_SENDER_WAIT_FOR_DA_INCLUSION="true"
+            ETH_SENDER_SENDER_CONGESTION_ADAPTIVE_AGGREGATION="true"
+            ETH_SENDER_SENDER_CONGESTION_BASE_FEE_RATIO="1.5"
         "#;
         lock.set_env(config);
 
@@ -223,5 +281,7 @@ mod tests {
             config.sender.tx_poll_period(),
             Duration::from_secs(config.sender.tx_poll_period)
         );
+        assert_eq!(config.sender.congestion_base_fee_ratio(), 1.5);
+        assert_eq!(config.sender.congestion_max_aggregation_factor(), 4.0);
     }
 }
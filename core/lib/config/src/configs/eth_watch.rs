@@ -3,7 +3,7 @@ use std::time::Duration;
 // External uses
 use serde::Deserialize;
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 
 /// Configuration for the Ethereum sender crate.
 #[derive(Debug, Deserialize, Clone, PartialEq)]
@@ -18,7 +18,11 @@ pub struct ETHWatchConfig {
 
 impl ETHWatchConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("eth_watch", "ETH_WATCH_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("eth_watch", "ETH_WATCH_")
     }
 
     /// Converts `self.eth_node_poll_interval` into `Duration`.
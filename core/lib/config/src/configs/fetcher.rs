@@ -5,7 +5,7 @@ use std::time::Duration;
 use serde::Deserialize;
 // Workspace uses
 // Local uses
-use super::envy_load;
+use super::EnvVars;
 
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
 pub enum TokenListSource {
@@ -52,13 +52,15 @@ pub struct FetcherConfig {
 
 impl FetcherConfig {
     pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
         Ok(Self {
-            token_list: envy_load("token_list", "FETCHER_TOKEN_LIST_")?,
-            token_price: envy_load("token_price", "FETCHER_TOKEN_PRICE_")?,
-            token_trading_volume: envy_load(
-                "token_trading_volume",
-                "FETCHER_TOKEN_TRADING_VOLUME_",
-            )?,
+            token_list: vars.load("token_list", "FETCHER_TOKEN_LIST_")?,
+            token_price: vars.load("token_price", "FETCHER_TOKEN_PRICE_")?,
+            token_trading_volume: vars
+                .load("token_trading_volume", "FETCHER_TOKEN_TRADING_VOLUME_")?,
         })
     }
 }
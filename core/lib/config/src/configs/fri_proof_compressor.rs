@@ -26,6 +26,10 @@ pub struct FriProofCompressorConfig {
 
     // Whether to verify wrapper proof or not.
     pub verify_wrapper_proof: bool,
+
+    /// Interval between checks of circuit commitments for proven, but not executed L1 batches.
+    /// If not set, commitments are not verified.
+    pub commitment_verification_interval_ms: Option<u64>,
 }
 
 impl FriProofCompressorConfig {
@@ -59,6 +63,7 @@ mod tests {
                 "https://storage.googleapis.com/matterlabs-setup-keys-us/setup-keys/setup_2^26.key"
                     .to_string(),
             verify_wrapper_proof: false,
+            commitment_verification_interval_ms: Some(10000),
         }
     }
 
@@ -75,6 +80,7 @@ mod tests {
             FRI_PROOF_COMPRESSOR_UNIVERSAL_SETUP_PATH="keys/setup/setup_2^26.key"
             FRI_PROOF_COMPRESSOR_UNIVERSAL_SETUP_DOWNLOAD_URL="https://storage.googleapis.com/matterlabs-setup-keys-us/setup-keys/setup_2^26.key"
             FRI_PROOF_COMPRESSOR_VERIFY_WRAPPER_PROOF=false
+            FRI_PROOF_COMPRESSOR_COMMITMENT_VERIFICATION_INTERVAL_MS=10000
         "#;
         lock.set_env(config);
 
@@ -2,15 +2,17 @@
 pub use self::{
     alerts::AlertsConfig, api::ApiConfig, chain::ChainConfig,
     circuit_synthesizer::CircuitSynthesizerConfig, contract_verifier::ContractVerifierConfig,
-    contracts::ContractsConfig, database::DBConfig, eth_client::ETHClientConfig,
+    contracts::ContractsConfig, database::DBConfig, env_vars::EnvVars, eth_client::ETHClientConfig,
     eth_sender::ETHSenderConfig, eth_sender::GasAdjusterConfig, eth_watch::ETHWatchConfig,
     fetcher::FetcherConfig, fri_proof_compressor::FriProofCompressorConfig,
     fri_prover::FriProverConfig, fri_prover_gateway::FriProverGatewayConfig,
     fri_witness_generator::FriWitnessGeneratorConfig,
-    fri_witness_vector_generator::FriWitnessVectorGeneratorConfig, object_store::ObjectStoreConfig,
-    proof_data_handler::ProofDataHandlerConfig, prover::ProverConfig, prover::ProverConfigs,
-    prover_group::ProverGroupConfig, utils::PrometheusConfig,
-    witness_generator::WitnessGeneratorConfig,
+    fri_witness_vector_generator::FriWitnessVectorGeneratorConfig, multi_chain::MultiChainConfig,
+    object_store::ObjectStoreConfig, proof_data_handler::ProofDataHandlerConfig,
+    prover::ProverConfig, prover::ProverConfigs, prover_group::ProverGroupConfig,
+    stream_publisher::StreamPublisherConfig, token_registry::TokenRegistryConfig,
+    utils::PrometheusConfig, webhook_notifier::WebhookNotifierConfig,
+    withdrawal_finalizer::WithdrawalFinalizerConfig, witness_generator::WitnessGeneratorConfig,
 };
 
 use anyhow::Context as _;
@@ -23,6 +25,7 @@ pub mod circuit_synthesizer;
 pub mod contract_verifier;
 pub mod contracts;
 pub mod database;
+pub mod env_vars;
 pub mod eth_client;
 pub mod eth_sender;
 pub mod eth_watch;
@@ -34,11 +37,16 @@ pub mod fri_prover_group;
 pub mod fri_witness_generator;
 pub mod fri_witness_vector_generator;
 pub mod house_keeper;
+pub mod multi_chain;
 pub mod object_store;
 pub mod proof_data_handler;
 pub mod prover;
 pub mod prover_group;
+pub mod stream_publisher;
+pub mod token_registry;
 pub mod utils;
+pub mod webhook_notifier;
+pub mod withdrawal_finalizer;
 pub mod witness_generator;
 
 #[cfg(test)]
@@ -0,0 +1,57 @@
+use serde::Deserialize;
+
+use super::envy_load;
+
+/// Configuration for running several hyperchains in a single server process.
+///
+/// Each chain is configured with the same environment variables as a standalone server; a variable
+/// can be overridden for a specific chain by prefixing it with the uppercased chain name followed
+/// by `__` (e.g., `ERA__DATABASE_URL` or `ERA__CHAIN_ETH_ZKSYNC_NETWORK_ID`). Variables without
+/// a chain override are shared by all chains.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct MultiChainConfig {
+    /// Names of chains run by the process. If empty, the process runs a single chain configured
+    /// without overrides.
+    #[serde(default)]
+    pub chains: Vec<String>,
+}
+
+impl MultiChainConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        envy_load("multi_chain", "MULTI_CHAIN_")
+    }
+
+    /// Returns the prefix of environment variables overriding configuration for the specified chain.
+    pub fn env_prefix(chain: &str) -> String {
+        format!("{}__", chain.to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    #[test]
+    fn from_env() {
+        let mut lock = MUTEX.lock();
+        let config = r#"
+            MULTI_CHAIN_CHAINS="era,test_chain"
+        "#;
+        lock.set_env(config);
+        let actual = MultiChainConfig::from_env().unwrap();
+        assert_eq!(
+            actual,
+            MultiChainConfig {
+                chains: vec!["era".to_owned(), "test_chain".to_owned()],
+            }
+        );
+        assert_eq!(MultiChainConfig::env_prefix("test_chain"), "TEST_CHAIN__");
+
+        lock.remove_env(&["MULTI_CHAIN_CHAINS"]);
+        let actual = MultiChainConfig::from_env().unwrap();
+        assert!(actual.chains.is_empty());
+    }
+}
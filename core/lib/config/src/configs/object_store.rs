@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use super::envy_load;
+use super::{envy_load, EnvVars};
 
 #[derive(Debug, Deserialize, Eq, PartialEq, Clone, Copy)]
 pub enum ObjectStoreMode {
@@ -17,11 +17,18 @@ pub struct ObjectStoreConfig {
     pub file_backed_base_path: String,
     pub gcs_credential_file_path: String,
     pub max_retries: u16,
+    /// Prefix prepended to all object keys. Allows several chains to share a single bucket
+    /// without their objects colliding.
+    pub key_prefix: Option<String>,
 }
 
 impl ObjectStoreConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("object_store", "OBJECT_STORE_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("object_store", "OBJECT_STORE_")
     }
 
     pub fn public_from_env() -> anyhow::Result<Self> {
@@ -47,6 +54,7 @@ mod tests {
             file_backed_base_path: "artifacts".to_string(),
             gcs_credential_file_path: "/path/to/credentials.json".to_string(),
             max_retries: 5,
+            key_prefix: None,
         }
     }
 
@@ -94,4 +102,24 @@ mod tests {
         let actual = ObjectStoreConfig::prover_from_env().unwrap();
         assert_eq!(actual, expected_config("/prover_base_url"));
     }
+
+    #[test]
+    fn config_with_key_prefix_from_env() {
+        let mut lock = MUTEX.lock();
+        let config = r#"
+            OBJECT_STORE_BUCKET_BASE_URL="/base/url"
+            OBJECT_STORE_MODE="FileBacked"
+            OBJECT_STORE_FILE_BACKED_BASE_PATH="artifacts"
+            OBJECT_STORE_GCS_CREDENTIAL_FILE_PATH="/path/to/credentials.json"
+            OBJECT_STORE_MAX_RETRIES="5"
+            OBJECT_STORE_KEY_PREFIX="era"
+        "#;
+        lock.set_env(config);
+        let actual = ObjectStoreConfig::from_env().unwrap();
+        let expected = ObjectStoreConfig {
+            key_prefix: Some("era".to_owned()),
+            ..expected_config("/base/url")
+        };
+        assert_eq!(actual, expected);
+    }
 }
@@ -1,4 +1,4 @@
-use super::envy_load;
+use super::EnvVars;
 use serde::Deserialize;
 use std::time::Duration;
 
@@ -14,16 +14,28 @@ pub struct ProofDataHandlerConfig {
     pub proof_generation_timeout_in_secs: u16,
     pub protocol_version_loading_mode: ProtocolVersionLoadingMode,
     pub fri_protocol_version_id: u16,
+    /// Size of chunks in which witness inputs are served to provers. Provers can download inputs
+    /// chunk by chunk using HTTP range requests and verify each chunk against its checksum.
+    /// Must be positive.
+    pub witness_chunk_size_bytes: Option<u64>,
 }
 
 impl ProofDataHandlerConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("proof_data_handler", "PROOF_DATA_HANDLER_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("proof_data_handler", "PROOF_DATA_HANDLER_")
     }
 
     pub fn proof_generation_timeout(&self) -> Duration {
         Duration::from_secs(self.proof_generation_timeout_in_secs as u64)
     }
+
+    pub fn witness_chunk_size(&self) -> u64 {
+        self.witness_chunk_size_bytes.unwrap_or(16 * 1_024 * 1_024)
+    }
 }
 
 #[cfg(test)]
@@ -39,6 +51,7 @@ mod tests {
             proof_generation_timeout_in_secs: 18000,
             protocol_version_loading_mode: ProtocolVersionLoadingMode::FromEnvVar,
             fri_protocol_version_id: 2,
+            witness_chunk_size_bytes: Some(8_388_608),
         }
     }
 
@@ -49,6 +62,7 @@ mod tests {
             PROOF_DATA_HANDLER_HTTP_PORT="3320"
             PROOF_DATA_HANDLER_PROTOCOL_VERSION_LOADING_MODE="FromEnvVar"
             PROOF_DATA_HANDLER_FRI_PROTOCOL_VERSION_ID="2"
+            PROOF_DATA_HANDLER_WITNESS_CHUNK_SIZE_BYTES="8388608"
         "#;
         let mut lock = MUTEX.lock();
         lock.set_env(config);
@@ -0,0 +1,84 @@
+use serde::Deserialize;
+
+use std::time::Duration;
+
+use super::EnvVars;
+
+/// Message broker the streaming publisher sends sealed miniblocks to.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub enum StreamBackend {
+    /// NATS with JetStream; messages are published to subjects. Requires the server to be built
+    /// with the `nats` feature.
+    Nats,
+    /// Apache Kafka; messages are published to topics. Requires the server to be built
+    /// with the `kafka` feature.
+    Kafka,
+}
+
+/// Configuration for the publisher streaming sealed miniblocks to a message broker.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct StreamPublisherConfig {
+    pub backend: StreamBackend,
+    /// Broker URL: a NATS server URL or a comma-separated list of Kafka bootstrap servers.
+    pub url: String,
+    /// Prefix of the topic (subject) miniblocks are published to. The schema version is appended
+    /// to the prefix, e.g. `zksync.miniblocks.v1`.
+    pub topic_prefix: String,
+    /// Interval for checking newly sealed miniblocks if the publisher isn't notified about them
+    /// directly by the state keeper.
+    pub poll_interval_ms: u64,
+    /// Maximum number of miniblocks published in a single iteration before persisting progress.
+    pub max_miniblocks_per_iteration: u32,
+    /// Miniblock to start publishing from if nothing was published to the topic yet. If not set,
+    /// all miniblocks starting from the genesis are published.
+    pub first_miniblock: Option<u32>,
+}
+
+impl StreamPublisherConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("stream_publisher", "STREAM_PUBLISHER_")
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    fn expected_config() -> StreamPublisherConfig {
+        StreamPublisherConfig {
+            backend: StreamBackend::Kafka,
+            url: "127.0.0.1:9092,127.0.0.1:9093".to_owned(),
+            topic_prefix: "zksync.miniblocks".to_owned(),
+            poll_interval_ms: 1000,
+            max_miniblocks_per_iteration: 100,
+            first_miniblock: Some(1_000),
+        }
+    }
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            STREAM_PUBLISHER_BACKEND="Kafka"
+            STREAM_PUBLISHER_URL="127.0.0.1:9092,127.0.0.1:9093"
+            STREAM_PUBLISHER_TOPIC_PREFIX="zksync.miniblocks"
+            STREAM_PUBLISHER_POLL_INTERVAL_MS="1000"
+            STREAM_PUBLISHER_MAX_MINIBLOCKS_PER_ITERATION="100"
+            STREAM_PUBLISHER_FIRST_MINIBLOCK="1000"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = StreamPublisherConfig::from_env().unwrap();
+        assert_eq!(actual, expected_config());
+    }
+}
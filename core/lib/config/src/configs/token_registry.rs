@@ -0,0 +1,115 @@
+use serde::Deserialize;
+use zksync_basic_types::Address;
+
+use std::time::Duration;
+
+use super::EnvVars;
+
+/// Configuration for the token registry, which controls tokens exposed by the node and keeps their metadata
+/// up to date. All values are optional; if no values are set, all tokens are supported and fees are paid in ETH.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct TokenRegistryConfig {
+    /// L2 addresses of tokens exposed via the API and supported by the fee model. If not set, all tokens
+    /// not in the denylist are supported. ETH is always supported.
+    pub allowlist: Option<Vec<Address>>,
+    /// L2 addresses of tokens that are never exposed via the API and are not supported by the fee model.
+    pub denylist: Option<Vec<Address>>,
+    /// L2 address of the base token of the chain. If set, the fee model converts L1 gas and pubdata prices
+    /// into the base token using USD prices of ETH and the base token. In this case, `fair_l2_gas_price`
+    /// in the state keeper config must be specified in the base token as well.
+    pub base_token: Option<Address>,
+    /// Interval between refreshes of the ETH / base token conversion rate. Only used if `base_token` is set.
+    #[serde(default = "TokenRegistryConfig::default_base_token_rate_refresh_interval_ms")]
+    pub base_token_rate_refresh_interval_ms: u64,
+    /// Interval between metadata refresh iterations.
+    #[serde(default = "TokenRegistryConfig::default_metadata_refresh_interval_ms")]
+    pub metadata_refresh_interval_ms: u64,
+    /// Maximum number of tokens with metadata refreshed in a single iteration. Tokens are refreshed
+    /// starting from the least recently refreshed ones.
+    #[serde(default = "TokenRegistryConfig::default_metadata_refresh_batch_size")]
+    pub metadata_refresh_batch_size: usize,
+}
+
+impl TokenRegistryConfig {
+    const fn default_base_token_rate_refresh_interval_ms() -> u64 {
+        30_000
+    }
+
+    const fn default_metadata_refresh_interval_ms() -> u64 {
+        600_000
+    }
+
+    const fn default_metadata_refresh_batch_size() -> usize {
+        100
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("token_registry", "TOKEN_REGISTRY_")
+    }
+
+    pub fn base_token_rate_refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.base_token_rate_refresh_interval_ms)
+    }
+
+    pub fn metadata_refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.metadata_refresh_interval_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::test_utils::{addr, EnvMutex};
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    fn expected_config() -> TokenRegistryConfig {
+        TokenRegistryConfig {
+            allowlist: None,
+            denylist: Some(vec![
+                addr("0x0000000000000000000000000000000000000001"),
+                addr("0x0000000000000000000000000000000000000002"),
+            ]),
+            base_token: Some(addr("0x0000000000000000000000000000000000000003")),
+            base_token_rate_refresh_interval_ms: 10_000,
+            metadata_refresh_interval_ms: 60_000,
+            metadata_refresh_batch_size: 100,
+        }
+    }
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            TOKEN_REGISTRY_DENYLIST="0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002"
+            TOKEN_REGISTRY_BASE_TOKEN="0x0000000000000000000000000000000000000003"
+            TOKEN_REGISTRY_BASE_TOKEN_RATE_REFRESH_INTERVAL_MS="10000"
+            TOKEN_REGISTRY_METADATA_REFRESH_INTERVAL_MS="60000"
+            TOKEN_REGISTRY_METADATA_REFRESH_BATCH_SIZE="100"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = TokenRegistryConfig::from_env().unwrap();
+        assert_eq!(actual, expected_config());
+    }
+
+    #[test]
+    fn from_empty_env() {
+        let mut lock = MUTEX.lock();
+        lock.remove_env(&[
+            "TOKEN_REGISTRY_DENYLIST",
+            "TOKEN_REGISTRY_BASE_TOKEN",
+            "TOKEN_REGISTRY_BASE_TOKEN_RATE_REFRESH_INTERVAL_MS",
+            "TOKEN_REGISTRY_METADATA_REFRESH_INTERVAL_MS",
+            "TOKEN_REGISTRY_METADATA_REFRESH_BATCH_SIZE",
+        ]);
+        let actual = TokenRegistryConfig::from_env().unwrap();
+        assert_eq!(actual.allowlist, None);
+        assert_eq!(actual.base_token, None);
+        assert_eq!(actual.metadata_refresh_interval(), Duration::from_secs(600));
+        assert_eq!(actual.metadata_refresh_batch_size, 100);
+    }
+}
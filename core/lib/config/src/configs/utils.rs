@@ -2,7 +2,7 @@ use serde::Deserialize;
 
 use std::{env, time::Duration};
 
-use crate::configs::envy_load;
+use crate::configs::EnvVars;
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct PrometheusConfig {
@@ -16,7 +16,11 @@ pub struct PrometheusConfig {
 
 impl PrometheusConfig {
     pub fn from_env() -> anyhow::Result<Self> {
-        envy_load("prometheus", "API_PROMETHEUS_")
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("prometheus", "API_PROMETHEUS_")
     }
 
     pub fn push_interval(&self) -> Duration {
@@ -0,0 +1,85 @@
+use serde::Deserialize;
+
+use std::time::Duration;
+
+use super::EnvVars;
+
+/// Configuration for the webhook notifier that reports L1 batch lifecycle transitions
+/// to external HTTP endpoints.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct WebhookNotifierConfig {
+    /// Webhook endpoints. Each endpoint is a URL optionally followed by a `#` and a `+`-separated
+    /// list of events to deliver to it (e.g., `https://example.com/hook#committed+executed`).
+    /// If no events are specified, all events are delivered to the endpoint.
+    pub endpoints: Vec<String>,
+    /// Secret used to sign webhook payloads with HMAC-SHA256.
+    pub signing_secret: String,
+    /// Interval between polling the database for L1 batch status changes.
+    pub poll_interval_ms: u64,
+    /// Timeout for a single webhook delivery attempt.
+    pub request_timeout_ms: u64,
+    /// Maximum number of delivery retries for a single event before it's dropped.
+    pub max_retries: u32,
+    /// Backoff before the first retry; it's doubled after each subsequent failed attempt.
+    pub initial_retry_backoff_ms: u64,
+}
+
+impl WebhookNotifierConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("webhook_notifier", "WEBHOOK_NOTIFIER_")
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+
+    pub fn initial_retry_backoff(&self) -> Duration {
+        Duration::from_millis(self.initial_retry_backoff_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::test_utils::EnvMutex;
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    fn expected_config() -> WebhookNotifierConfig {
+        WebhookNotifierConfig {
+            endpoints: vec![
+                "http://127.0.0.1:8080/hook".to_owned(),
+                "https://example.com/hook#committed+executed".to_owned(),
+            ],
+            signing_secret: "secret".to_owned(),
+            poll_interval_ms: 1000,
+            request_timeout_ms: 5000,
+            max_retries: 5,
+            initial_retry_backoff_ms: 500,
+        }
+    }
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            WEBHOOK_NOTIFIER_ENDPOINTS="http://127.0.0.1:8080/hook,https://example.com/hook#committed+executed"
+            WEBHOOK_NOTIFIER_SIGNING_SECRET="secret"
+            WEBHOOK_NOTIFIER_POLL_INTERVAL_MS="1000"
+            WEBHOOK_NOTIFIER_REQUEST_TIMEOUT_MS="5000"
+            WEBHOOK_NOTIFIER_MAX_RETRIES="5"
+            WEBHOOK_NOTIFIER_INITIAL_RETRY_BACKOFF_MS="500"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = WebhookNotifierConfig::from_env().unwrap();
+        assert_eq!(actual, expected_config());
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Deserialize;
+use zksync_basic_types::{Address, H256};
+
+use std::time::Duration;
+
+use super::EnvVars;
+
+/// Configuration for the withdrawal finalizer that automatically finalizes withdrawals on L1
+/// once their L1 batches are executed.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct WithdrawalFinalizerConfig {
+    /// L1 receivers whose withdrawals are finalized. If not set, withdrawals for all receivers are finalized.
+    pub receivers: Option<Vec<Address>>,
+    /// L1 addresses of tokens whose withdrawals are finalized; ETH is denoted by the zero address.
+    /// If not set, withdrawals of all tokens are finalized.
+    pub tokens: Option<Vec<Address>>,
+    /// First L1 batch scanned for withdrawals on the first launch of the finalizer. If not set, only withdrawals
+    /// from L1 batches executed after the first launch are finalized.
+    pub start_from_l1_batch: Option<u32>,
+    /// Interval between polling the database and L1 for updates.
+    pub poll_interval_ms: u64,
+    /// Maximum number of L1 batches scanned for withdrawals in a single iteration.
+    pub max_l1_batches_per_iteration: u32,
+    /// Maximum number of sent finalization transactions not yet included into an L1 block.
+    pub max_txs_in_flight: u32,
+    /// Gas limit for a single finalization transaction.
+    pub finalize_tx_gas_limit: u64,
+    /// Priority fee for finalization transactions, in wei.
+    pub priority_fee_per_gas: u64,
+    /// Number of L1 blocks after which a finalization transaction not included into a block is considered stuck
+    /// and is replaced with a transaction with bumped fees.
+    pub stuck_tx_timeout_blocks: u64,
+    /// Maximum fee per gas for finalization transactions, in wei. Fees of stuck transactions are not bumped above it.
+    pub max_fee_per_gas: u64,
+}
+
+impl WithdrawalFinalizerConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_env_vars(&EnvVars::from_env())
+    }
+
+    pub fn from_env_vars(vars: &EnvVars) -> anyhow::Result<Self> {
+        vars.load("withdrawal_finalizer", "WITHDRAWAL_FINALIZER_")
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    /// Private key of the L1 account sending finalization transactions. It's separate from the operator key,
+    /// so that finalization costs are accounted for independently. The key is only loaded when required.
+    pub fn private_key(&self) -> anyhow::Result<Option<H256>> {
+        let Ok(private_key) = std::env::var("WITHDRAWAL_FINALIZER_PRIVATE_KEY") else {
+            return Ok(None);
+        };
+        let private_key = private_key.parse().map_err(|err| {
+            anyhow::anyhow!("WITHDRAWAL_FINALIZER_PRIVATE_KEY is malformed: {err}")
+        })?;
+        Ok(Some(private_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::test_utils::{addr, hash, EnvMutex};
+
+    static MUTEX: EnvMutex = EnvMutex::new();
+
+    fn expected_config() -> WithdrawalFinalizerConfig {
+        WithdrawalFinalizerConfig {
+            receivers: Some(vec![
+                addr("0x36615cf349d7f6344891b1e7ca7c72883f5dc049"),
+                addr("0xa61464658afeaf65cccaafd3a512b69a83b77618"),
+            ]),
+            tokens: None,
+            start_from_l1_batch: Some(10),
+            poll_interval_ms: 5000,
+            max_l1_batches_per_iteration: 20,
+            max_txs_in_flight: 5,
+            finalize_tx_gas_limit: 500_000,
+            priority_fee_per_gas: 1_000_000_000,
+            stuck_tx_timeout_blocks: 30,
+            max_fee_per_gas: 500_000_000_000,
+        }
+    }
+
+    #[test]
+    fn from_env() {
+        let config = r#"
+            WITHDRAWAL_FINALIZER_RECEIVERS="0x36615cf349d7f6344891b1e7ca7c72883f5dc049,0xa61464658afeaf65cccaafd3a512b69a83b77618"
+            WITHDRAWAL_FINALIZER_START_FROM_L1_BATCH="10"
+            WITHDRAWAL_FINALIZER_POLL_INTERVAL_MS="5000"
+            WITHDRAWAL_FINALIZER_MAX_L1_BATCHES_PER_ITERATION="20"
+            WITHDRAWAL_FINALIZER_MAX_TXS_IN_FLIGHT="5"
+            WITHDRAWAL_FINALIZER_FINALIZE_TX_GAS_LIMIT="500000"
+            WITHDRAWAL_FINALIZER_PRIORITY_FEE_PER_GAS="1000000000"
+            WITHDRAWAL_FINALIZER_STUCK_TX_TIMEOUT_BLOCKS="30"
+            WITHDRAWAL_FINALIZER_MAX_FEE_PER_GAS="500000000000"
+            WITHDRAWAL_FINALIZER_PRIVATE_KEY="0x27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be"
+        "#;
+        let mut lock = MUTEX.lock();
+        lock.set_env(config);
+        let actual = WithdrawalFinalizerConfig::from_env().unwrap();
+        assert_eq!(actual, expected_config());
+        assert_eq!(
+            actual.private_key().unwrap(),
+            Some(hash(
+                "27593fea79697e947890ecbecce7901b0008345e5d7259710d0dd5e500d040be"
+            ))
+        );
+
+        lock.set_env(r#"WITHDRAWAL_FINALIZER_PRIVATE_KEY="0xbad""#);
+        actual.private_key().unwrap_err();
+        lock.remove_env(&["WITHDRAWAL_FINALIZER_PRIVATE_KEY"]);
+        assert_eq!(actual.private_key().unwrap(), None);
+    }
+}
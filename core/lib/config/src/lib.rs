@@ -2,7 +2,7 @@
 
 pub use crate::configs::{
     ApiConfig, ChainConfig, ContractVerifierConfig, ContractsConfig, DBConfig, ETHClientConfig,
-    ETHSenderConfig, ETHWatchConfig, FetcherConfig, GasAdjusterConfig, ObjectStoreConfig,
+    ETHSenderConfig, ETHWatchConfig, EnvVars, FetcherConfig, GasAdjusterConfig, ObjectStoreConfig,
     ProverConfig, ProverConfigs,
 };
 
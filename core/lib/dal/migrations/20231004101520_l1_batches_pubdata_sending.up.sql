@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS l1_batches_pubdata_sending (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    pubdata_sending_mode TEXT NOT NULL,
+    pubdata_size BIGINT NOT NULL,
+    calldata_cost NUMERIC(80),
+    blobs_cost NUMERIC(80),
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
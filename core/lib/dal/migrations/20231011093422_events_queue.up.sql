@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS events_queue (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    serialized_events_queue JSONB NOT NULL
+);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS data_availability (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    -- Pointer to the L1 batch pubdata on the DA layer (e.g., a blob ID); the format is specific to the DA layer.
+    blob_id TEXT NOT NULL,
+    -- Inclusion proof / attestation returned by the DA layer; `NULL` until inclusion is confirmed.
+    inclusion_data BYTEA,
+    sent_at TIMESTAMP NOT NULL,
+    inclusion_confirmed_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS data_availability_pending_inclusion_index
+    ON data_availability (l1_batch_number) WHERE inclusion_data IS NULL;
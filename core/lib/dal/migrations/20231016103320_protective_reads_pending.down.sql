@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS l1_batches_protective_reads_pending_idx;
+ALTER TABLE l1_batches DROP COLUMN IF EXISTS protective_reads_pending;
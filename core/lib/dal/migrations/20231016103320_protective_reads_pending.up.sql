@@ -0,0 +1,3 @@
+-- Set for sealed L1 batches which protective reads are persisted asynchronously and are not persisted yet.
+ALTER TABLE l1_batches ADD COLUMN IF NOT EXISTS protective_reads_pending BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE INDEX IF NOT EXISTS l1_batches_protective_reads_pending_idx ON l1_batches (number) WHERE protective_reads_pending;
@@ -0,0 +1,7 @@
+-- Last miniblock published (and acknowledged by the broker) for each stream of the miniblock streaming publisher.
+CREATE TABLE IF NOT EXISTS stream_publisher_cursors (
+    stream TEXT PRIMARY KEY,
+    last_published_miniblock BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
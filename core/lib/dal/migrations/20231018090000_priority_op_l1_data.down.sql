@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS transactions_l1_tx_hash_idx;
+ALTER TABLE transactions DROP COLUMN IF EXISTS l1_deadline_block;
+ALTER TABLE transactions DROP COLUMN IF EXISTS l1_tx_hash;
@@ -0,0 +1,5 @@
+-- Hash of the L1 transaction that emitted a priority operation and the operation deadline from the L1 event.
+-- Not set for priority operations received before this migration.
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS l1_tx_hash BYTEA;
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS l1_deadline_block BIGINT;
+CREATE INDEX IF NOT EXISTS transactions_l1_tx_hash_idx ON transactions (l1_tx_hash) WHERE l1_tx_hash IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Hashes of non-empty nodes of the L2->L1 log Merkle tree of each L1 batch, grouped by tree level starting from leaves.
+-- Each level is stored as a concatenation of 32-byte hashes.
+CREATE TABLE IF NOT EXISTS l2_to_l1_log_trees (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    layers BYTEA[] NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
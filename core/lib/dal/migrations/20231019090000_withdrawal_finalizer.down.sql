@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS withdrawal_finalizer_cursor;
+DROP TABLE IF EXISTS finalizer_withdrawals;
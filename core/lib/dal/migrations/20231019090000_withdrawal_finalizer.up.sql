@@ -0,0 +1,31 @@
+-- Withdrawals tracked by the withdrawal finalizer, together with the accounting data for their finalization on L1.
+CREATE TABLE IF NOT EXISTS finalizer_withdrawals (
+    l1_batch_number BIGINT NOT NULL,
+    l2_message_index INT NOT NULL,
+    l2_tx_number_in_batch INT NOT NULL,
+    l2_tx_hash BYTEA NOT NULL,
+    l1_receiver BYTEA NOT NULL,
+    -- Zero address for ETH withdrawals.
+    l1_token BYTEA NOT NULL,
+    amount NUMERIC(80) NOT NULL,
+    message BYTEA NOT NULL,
+    merkle_proof BYTEA[] NOT NULL,
+    -- One of 'pending', 'sent', 'finalized', 'failed' or 'finalized_externally'.
+    status TEXT NOT NULL,
+    finalize_tx_hash BYTEA,
+    finalize_tx_nonce BIGINT,
+    gas_used BIGINT,
+    fee_paid NUMERIC(80),
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (l1_batch_number, l2_message_index)
+);
+CREATE INDEX IF NOT EXISTS finalizer_withdrawals_status_idx
+    ON finalizer_withdrawals (status) WHERE status IN ('pending', 'sent');
+
+-- Last L1 batch scanned for withdrawals by the withdrawal finalizer. Contains at most one row.
+CREATE TABLE IF NOT EXISTS withdrawal_finalizer_cursor (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_processed_l1_batch BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,3 @@
+ALTER TABLE tokens DROP COLUMN IF EXISTS metadata_refreshed_at;
+ALTER TABLE tokens DROP COLUMN IF EXISTS added_in_miniblock;
+ALTER TABLE tokens DROP COLUMN IF EXISTS bridge_address;
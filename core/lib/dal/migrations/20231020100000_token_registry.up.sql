@@ -0,0 +1,5 @@
+-- Provenance of tokens deployed by an L2 bridge. Not set for ETH and tokens added before this migration.
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS bridge_address BYTEA;
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS added_in_miniblock BIGINT;
+-- Last time token metadata was refreshed from the token contract.
+ALTER TABLE tokens ADD COLUMN IF NOT EXISTS metadata_refreshed_at TIMESTAMP;
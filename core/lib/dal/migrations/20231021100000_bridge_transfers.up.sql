@@ -0,0 +1,30 @@
+-- Deposits and withdrawals via bridges, indexed from sealed L1 batches by the bridge indexer.
+CREATE TABLE IF NOT EXISTS bridge_transfers (
+    l1_batch_number BIGINT NOT NULL REFERENCES l1_batches (number) ON DELETE CASCADE,
+    index_in_l1_batch INT NOT NULL,
+    kind TEXT NOT NULL,
+    -- Status at the time of indexing; withdrawal finalization on L1 is tracked in `finalizer_withdrawals`.
+    status TEXT NOT NULL,
+    sender BYTEA NOT NULL,
+    receiver BYTEA NOT NULL,
+    l1_token BYTEA NOT NULL,
+    l2_token BYTEA,
+    amount NUMERIC(80) NOT NULL,
+    l1_tx_hash BYTEA,
+    l2_tx_hash BYTEA NOT NULL,
+    miniblock_number BIGINT NOT NULL,
+    l2_message_index INT,
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (l1_batch_number, index_in_l1_batch)
+);
+CREATE INDEX IF NOT EXISTS bridge_transfers_sender_idx
+    ON bridge_transfers (sender, l1_batch_number DESC, index_in_l1_batch DESC);
+CREATE INDEX IF NOT EXISTS bridge_transfers_receiver_idx
+    ON bridge_transfers (receiver, l1_batch_number DESC, index_in_l1_batch DESC);
+
+-- L1 batches processed by the bridge indexer. Rows are removed together with reverted L1 batches,
+-- so that the indexer re-processes L1 batches sealed after a revert.
+CREATE TABLE IF NOT EXISTS bridge_indexed_l1_batches (
+    l1_batch_number BIGINT PRIMARY KEY REFERENCES l1_batches (number) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL
+);
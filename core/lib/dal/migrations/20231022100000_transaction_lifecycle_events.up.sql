@@ -0,0 +1,9 @@
+-- Milestones of a transaction before it is included into an L1 batch. Later milestones are derived
+-- from `l1_batches` and `eth_txs_history`. Events follow the transaction if it is replaced, and are removed
+-- together with the transaction.
+CREATE TABLE IF NOT EXISTS transaction_lifecycle_events (
+    tx_hash BYTEA NOT NULL REFERENCES transactions (hash) ON UPDATE CASCADE ON DELETE CASCADE,
+    event TEXT NOT NULL,
+    occurred_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (tx_hash, event)
+);
@@ -0,0 +1,11 @@
+-- Progress of online migrations of huge tables (see `online_migrations_dal` in the DAL crate).
+CREATE TABLE IF NOT EXISTS online_migrations (
+    name TEXT PRIMARY KEY,
+    source_table TEXT NOT NULL,
+    target_table TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    backfilled_up_to BIGINT NOT NULL,
+    backfill_end BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
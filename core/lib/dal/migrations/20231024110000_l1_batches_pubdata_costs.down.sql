@@ -0,0 +1,5 @@
+UPDATE l1_batches_pubdata_costs SET cheaper_pubdata_sending_mode = 'calldata'
+    WHERE cheaper_pubdata_sending_mode IS NULL;
+ALTER TABLE l1_batches_pubdata_costs ALTER COLUMN cheaper_pubdata_sending_mode SET NOT NULL;
+ALTER TABLE l1_batches_pubdata_costs RENAME COLUMN cheaper_pubdata_sending_mode TO pubdata_sending_mode;
+ALTER TABLE l1_batches_pubdata_costs RENAME TO l1_batches_pubdata_sending;
@@ -0,0 +1,4 @@
+-- Pubdata is always sent as calldata; the table only records estimated costs of the alternatives.
+ALTER TABLE l1_batches_pubdata_sending RENAME TO l1_batches_pubdata_costs;
+ALTER TABLE l1_batches_pubdata_costs RENAME COLUMN pubdata_sending_mode TO cheaper_pubdata_sending_mode;
+ALTER TABLE l1_batches_pubdata_costs ALTER COLUMN cheaper_pubdata_sending_mode DROP NOT NULL;
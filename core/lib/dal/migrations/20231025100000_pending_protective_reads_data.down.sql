@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS l1_batches_pending_protective_reads_idx;
+ALTER TABLE l1_batches ADD COLUMN IF NOT EXISTS protective_reads_pending BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE l1_batches SET protective_reads_pending = TRUE WHERE pending_protective_reads IS NOT NULL;
+ALTER TABLE l1_batches DROP COLUMN IF EXISTS pending_protective_reads;
+CREATE INDEX IF NOT EXISTS l1_batches_protective_reads_pending_idx ON l1_batches (number) WHERE protective_reads_pending;
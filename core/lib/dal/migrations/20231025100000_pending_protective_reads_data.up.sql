@@ -0,0 +1,8 @@
+-- Protective reads of sealed L1 batches are persisted asynchronously. To be able to persist them after the node
+-- is restarted, they are stored in a compact form together with the L1 batch (which is cheaper than inserting
+-- them as separate rows) until they are persisted.
+DROP INDEX IF EXISTS l1_batches_protective_reads_pending_idx;
+ALTER TABLE l1_batches DROP COLUMN IF EXISTS protective_reads_pending;
+ALTER TABLE l1_batches ADD COLUMN IF NOT EXISTS pending_protective_reads BYTEA;
+CREATE INDEX IF NOT EXISTS l1_batches_pending_protective_reads_idx ON l1_batches (number)
+    WHERE pending_protective_reads IS NOT NULL;
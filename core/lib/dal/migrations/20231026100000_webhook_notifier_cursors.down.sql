@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS webhook_notifier_sealed_l1_batches;
+DROP TABLE IF EXISTS webhook_notifier_cursors;
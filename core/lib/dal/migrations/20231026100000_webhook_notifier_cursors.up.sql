@@ -0,0 +1,18 @@
+-- L1 batch statuses delivered to each webhook endpoint, so that the webhook notifier can resume after a restart.
+CREATE TABLE IF NOT EXISTS webhook_notifier_cursors (
+    endpoint TEXT PRIMARY KEY,
+    committed_l1_batch BIGINT,
+    proven_l1_batch BIGINT,
+    executed_l1_batch BIGINT,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+-- Identities (hashes of the last miniblock) of sealed L1 batches delivered to each webhook endpoint.
+-- Used to detect reverts, including ones followed by re-sealing L1 batches with the same numbers.
+CREATE TABLE IF NOT EXISTS webhook_notifier_sealed_l1_batches (
+    endpoint TEXT NOT NULL REFERENCES webhook_notifier_cursors (endpoint) ON DELETE CASCADE,
+    l1_batch_number BIGINT NOT NULL,
+    l1_batch_hash BYTEA NOT NULL,
+    PRIMARY KEY (endpoint, l1_batch_number)
+);
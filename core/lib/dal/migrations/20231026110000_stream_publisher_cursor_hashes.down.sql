@@ -0,0 +1 @@
+ALTER TABLE stream_publisher_cursors DROP COLUMN IF EXISTS last_published_miniblock_hash;
@@ -0,0 +1,3 @@
+-- Hash of the last published miniblock; used to detect reverts of published miniblocks. May be NULL for cursors
+-- created before hashes were persisted.
+ALTER TABLE stream_publisher_cursors ADD COLUMN IF NOT EXISTS last_published_miniblock_hash BYTEA;
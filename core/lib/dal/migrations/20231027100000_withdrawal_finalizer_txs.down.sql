@@ -0,0 +1 @@
+DROP TABLE IF EXISTS finalizer_withdrawal_txs;
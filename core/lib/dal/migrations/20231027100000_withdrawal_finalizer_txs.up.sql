@@ -0,0 +1,23 @@
+-- Finalization transactions signed by the withdrawal finalizer. Transactions are persisted before being sent to L1,
+-- so that they are re-sent rather than re-created with another nonce after a restart. A withdrawal may have several
+-- transactions with the same nonce if the fees of a stuck transaction were bumped.
+CREATE TABLE IF NOT EXISTS finalizer_withdrawal_txs (
+    tx_hash BYTEA PRIMARY KEY,
+    l1_batch_number BIGINT NOT NULL,
+    l2_message_index INT NOT NULL,
+    nonce BIGINT NOT NULL,
+    raw_tx BYTEA NOT NULL,
+    max_fee_per_gas BIGINT NOT NULL,
+    max_priority_fee_per_gas BIGINT NOT NULL,
+    created_at_block BIGINT NOT NULL,
+    -- NULL until the transaction is successfully sent to L1.
+    sent_at_block BIGINT,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (l1_batch_number, l2_message_index)
+        REFERENCES finalizer_withdrawals (l1_batch_number, l2_message_index) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS finalizer_withdrawal_txs_withdrawal_idx
+    ON finalizer_withdrawal_txs (l1_batch_number, l2_message_index);
+CREATE INDEX IF NOT EXISTS finalizer_withdrawal_txs_unsent_idx
+    ON finalizer_withdrawal_txs (created_at_block) WHERE sent_at_block IS NULL;
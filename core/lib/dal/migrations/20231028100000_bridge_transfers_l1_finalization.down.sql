@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS bridge_transfers_initiated_withdrawals_idx;
+DROP TABLE IF EXISTS bridge_indexer_l1_cursor;
@@ -0,0 +1,12 @@
+-- Last L1 block scanned by the bridge indexer for withdrawal finalization events. Contains at most one row.
+CREATE TABLE IF NOT EXISTS bridge_indexer_l1_cursor (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_processed_l1_block BIGINT NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+-- Withdrawals are now finalized based on L1 events, so initiated withdrawals are looked up by their L1 parameters.
+CREATE INDEX IF NOT EXISTS bridge_transfers_initiated_withdrawals_idx
+    ON bridge_transfers (receiver, l1_token)
+    WHERE kind = 'withdrawal' AND status = 'initiated';
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS transaction_lifecycle_events_occurred_at_idx;
@@ -0,0 +1,3 @@
+-- Allows pruning lifecycle events past their retention period.
+CREATE INDEX IF NOT EXISTS transaction_lifecycle_events_occurred_at_idx
+    ON transaction_lifecycle_events (occurred_at);
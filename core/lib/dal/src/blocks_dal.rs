@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::{Into, TryInto},
     ops,
 };
@@ -12,15 +12,29 @@ use zksync_types::{
     aggregated_operations::AggregatedActionType,
     block::{BlockGasCount, L1BatchHeader, MiniblockHeader},
     commitment::{L1BatchMetadata, L1BatchWithMetadata},
-    L1BatchNumber, MiniblockNumber, ProtocolVersionId, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
+    AccountTreeId, Address, L1BatchNumber, LogQuery, MiniblockNumber, ProtocolVersionId,
+    PubdataSendingMode, StorageKey, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
 
+use zksync_utils::{u256_to_big_decimal, u256_to_h256};
+
 use crate::{
     instrument::InstrumentExt,
     models::storage_block::{StorageL1Batch, StorageL1BatchHeader, StorageMiniblockHeader},
     StorageProcessor,
 };
 
+/// Size of a serialized pending protective read: account address (20 bytes) followed by the storage key (32 bytes).
+const PROTECTIVE_READ_SIZE: usize = 20 + 32;
+
+/// Tables with miniblock data referencing `miniblocks`, together with the `ON DELETE` clause of the reference.
+const MINIBLOCK_DATA_FOREIGN_KEYS: [(&str, &str); 4] = [
+    ("storage_logs", ""),
+    ("factory_deps", ""),
+    ("events", ""),
+    ("l2_to_l1_logs", " ON DELETE CASCADE"),
+];
+
 #[derive(Debug)]
 pub struct BlocksDal<'a, 'c> {
     pub(crate) storage: &'a mut StorageProcessor<'c>,
@@ -238,6 +252,80 @@ impl BlocksDal<'_, '_> {
         Ok(Some(heap))
     }
 
+    /// Saves the deduplicated events queue produced by the VM for the specified L1 batch.
+    pub async fn insert_events_queue(
+        &mut self,
+        number: L1BatchNumber,
+        events_queue: &[LogQuery],
+    ) -> sqlx::Result<()> {
+        let events_queue =
+            serde_json::to_value(events_queue).expect("failed to serialize events queue");
+        sqlx::query!(
+            "INSERT INTO events_queue (l1_batch_number, serialized_events_queue) \
+            VALUES ($1, $2)",
+            number.0 as i64,
+            events_queue
+        )
+        .instrument("insert_events_queue")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Saves hashes of non-empty nodes of the L2->L1 log Merkle tree for the specified L1 batch,
+    /// grouped by tree level starting from leaves.
+    pub async fn insert_l2_to_l1_log_tree(
+        &mut self,
+        number: L1BatchNumber,
+        layers: &[&[H256]],
+    ) -> sqlx::Result<()> {
+        let layers: Vec<Vec<u8>> = layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .flat_map(|hash| hash.as_bytes())
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        sqlx::query!(
+            "INSERT INTO l2_to_l1_log_trees (l1_batch_number, layers, created_at) \
+            VALUES ($1, $2, now())",
+            number.0 as i64,
+            &layers
+        )
+        .instrument("insert_l2_to_l1_log_tree")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the deduplicated events queue for the specified L1 batch.
+    pub async fn get_events_queue(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> anyhow::Result<Option<Vec<LogQuery>>> {
+        let Some(row) = sqlx::query!(
+            "SELECT serialized_events_queue FROM events_queue WHERE l1_batch_number = $1",
+            number.0 as i64
+        )
+        .instrument("get_events_queue")
+        .report_latency()
+        .with_arg("number", &number)
+        .fetch_optional(self.storage.conn())
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let events_queue = serde_json::from_value(row.serialized_events_queue)
+            .context("invalid value for serialized_events_queue in the DB")?;
+        Ok(Some(events_queue))
+    }
+
     pub async fn set_eth_tx_id(
         &mut self,
         number_range: ops::RangeInclusive<L1BatchNumber>,
@@ -365,8 +453,8 @@ impl BlocksDal<'_, '_> {
                 number, timestamp, hash, l1_tx_count, l2_tx_count, \
                 base_fee_per_gas, l1_gas_price, l2_fair_gas_price, gas_per_pubdata_limit, \
                 bootloader_code_hash, default_aa_code_hash, protocol_version, \
-                virtual_blocks, created_at, updated_at \
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, now(), now())",
+                virtual_blocks, fair_pubdata_price, created_at, updated_at \
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, now(), now())",
             miniblock_header.number.0 as i64,
             miniblock_header.timestamp as i64,
             miniblock_header.hash.as_bytes(),
@@ -386,6 +474,9 @@ impl BlocksDal<'_, '_> {
                 .as_bytes(),
             miniblock_header.protocol_version.map(|v| v as i32),
             miniblock_header.virtual_blocks as i64,
+            miniblock_header
+                .fair_pubdata_price
+                .map(|price| price as i64),
         )
         .execute(self.storage.conn())
         .await?;
@@ -424,7 +515,7 @@ impl BlocksDal<'_, '_> {
             "SELECT number, timestamp, hash, l1_tx_count, l2_tx_count, \
                 base_fee_per_gas, l1_gas_price, l2_fair_gas_price, \
                 bootloader_code_hash, default_aa_code_hash, protocol_version, \
-                virtual_blocks
+                virtual_blocks, fair_pubdata_price
             FROM miniblocks \
             ORDER BY number DESC \
             LIMIT 1",
@@ -443,7 +534,7 @@ impl BlocksDal<'_, '_> {
             "SELECT number, timestamp, hash, l1_tx_count, l2_tx_count, \
                 base_fee_per_gas, l1_gas_price, l2_fair_gas_price, \
                 bootloader_code_hash, default_aa_code_hash, protocol_version, \
-                virtual_blocks
+                virtual_blocks, fair_pubdata_price
             FROM miniblocks \
             WHERE number = $1",
             miniblock_number.0 as i64,
@@ -468,6 +559,107 @@ impl BlocksDal<'_, '_> {
         Ok(())
     }
 
+    /// Marks protective reads of the specified sealed L1 batch as not yet persisted. Protective reads
+    /// are persisted asynchronously after the L1 batch is sealed; see [`Self::mark_protective_reads_as_persisted()`].
+    /// Until then, they are stored in a compact form together with the L1 batch, so that they can be persisted
+    /// after a node restart (see [`Self::get_pending_protective_reads()`]).
+    pub async fn mark_protective_reads_as_pending(
+        &mut self,
+        number: L1BatchNumber,
+        protective_reads: &[LogQuery],
+    ) -> sqlx::Result<()> {
+        let mut serialized_reads =
+            Vec::with_capacity(protective_reads.len() * PROTECTIVE_READ_SIZE);
+        for read in protective_reads {
+            serialized_reads.extend_from_slice(read.address.as_bytes());
+            serialized_reads.extend_from_slice(u256_to_h256(read.key).as_bytes());
+        }
+
+        sqlx::query!(
+            "UPDATE l1_batches SET pending_protective_reads = $1 WHERE number = $2",
+            &serialized_reads,
+            number.0 as i64
+        )
+        .instrument("mark_protective_reads_as_pending")
+        .with_arg("number", &number)
+        .with_arg("protective_reads.len", &protective_reads.len())
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns protective reads of the specified sealed L1 batch that are not persisted yet. Returns `None`
+    /// if the L1 batch doesn't exist or its protective reads are already persisted.
+    pub async fn get_pending_protective_reads(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> anyhow::Result<Option<Vec<StorageKey>>> {
+        let row = sqlx::query!(
+            "SELECT pending_protective_reads FROM l1_batches WHERE number = $1",
+            number.0 as i64
+        )
+        .instrument("get_pending_protective_reads")
+        .with_arg("number", &number)
+        .fetch_optional(self.storage.conn())
+        .await?;
+        let Some(serialized_reads) = row.and_then(|row| row.pending_protective_reads) else {
+            return Ok(None);
+        };
+
+        anyhow::ensure!(
+            serialized_reads.len() % PROTECTIVE_READ_SIZE == 0,
+            "pending protective reads for L1 batch #{number} have unexpected length {}",
+            serialized_reads.len()
+        );
+        let protective_reads = serialized_reads
+            .chunks_exact(PROTECTIVE_READ_SIZE)
+            .map(|chunk| {
+                let (address, key) = chunk.split_at(20);
+                StorageKey::new(
+                    AccountTreeId::new(Address::from_slice(address)),
+                    H256::from_slice(key),
+                )
+            })
+            .collect();
+        Ok(Some(protective_reads))
+    }
+
+    /// Marks protective reads of the specified L1 batch as persisted.
+    pub async fn mark_protective_reads_as_persisted(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "UPDATE l1_batches SET pending_protective_reads = NULL WHERE number = $1",
+            number.0 as i64
+        )
+        .instrument("mark_protective_reads_as_persisted")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns sealed L1 batches which protective reads are not persisted yet, in ascending order.
+    pub async fn get_l1_batches_with_pending_protective_reads(
+        &mut self,
+    ) -> sqlx::Result<Vec<L1BatchNumber>> {
+        let rows = sqlx::query!(
+            "SELECT number FROM l1_batches \
+            WHERE pending_protective_reads IS NOT NULL \
+            ORDER BY number"
+        )
+        .instrument("get_l1_batches_with_pending_protective_reads")
+        .report_latency()
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchNumber(row.number as u32))
+            .collect())
+    }
+
     pub async fn save_genesis_l1_batch_metadata(
         &mut self,
         metadata: &L1BatchMetadata,
@@ -1077,6 +1269,97 @@ impl BlocksDal<'_, '_> {
         Ok(())
     }
 
+    /// Checks foreign keys referencing `miniblocks` from the tables with miniblock data (storage logs,
+    /// factory deps, events and L2-to-L1 logs). Returns a map from the key name to whether the key exists.
+    /// Keys are identified by their conventional names (e.g., `events_miniblock_number_fkey`),
+    /// which are preserved by online migrations of the tables.
+    pub async fn get_miniblock_data_foreign_keys(&mut self) -> sqlx::Result<HashMap<String, bool>> {
+        let constraints: Vec<_> = MINIBLOCK_DATA_FOREIGN_KEYS
+            .iter()
+            .map(|(table, _)| format!("{table}_miniblock_number_fkey"))
+            .collect();
+        let rows = sqlx::query!(
+            "SELECT conname::text AS \"conname!\" FROM pg_constraint \
+            WHERE contype = 'f' AND conname::text = ANY($1) \
+            ORDER BY conname",
+            &constraints
+        )
+        .instrument("get_miniblock_data_foreign_keys")
+        .fetch_all(self.storage.conn())
+        .await?;
+        let existing: HashSet<_> = rows.into_iter().map(|row| row.conname).collect();
+        Ok(constraints
+            .into_iter()
+            .map(|constraint| {
+                let exists = existing.contains(&constraint);
+                (constraint, exists)
+            })
+            .collect())
+    }
+
+    /// Drops foreign keys referencing `miniblocks` from the tables with miniblock data. The keys must be dropped
+    /// for parallel miniblock sealing, which persists miniblock data before the miniblock header.
+    ///
+    /// This is an operator action that makes the schema differ from the one described by migrations;
+    /// it should be reverted using [`Self::restore_miniblock_data_foreign_keys()`] once parallel sealing is disabled.
+    pub async fn drop_miniblock_data_foreign_keys(&mut self) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        for (table, _) in MINIBLOCK_DATA_FOREIGN_KEYS {
+            let statement = format!(
+                "ALTER TABLE {table} DROP CONSTRAINT IF EXISTS {table}_miniblock_number_fkey"
+            );
+            sqlx::query(&statement)
+                .instrument("drop_miniblock_data_foreign_keys")
+                .with_arg("table", &table)
+                .execute(transaction.conn())
+                .await?;
+        }
+        transaction.commit().await
+    }
+
+    /// Restores foreign keys referencing `miniblocks` from the tables with miniblock data dropped
+    /// by [`Self::drop_miniblock_data_foreign_keys()`]. Data of unsealed miniblocks must be removed beforehand.
+    ///
+    /// Keys are added as `NOT VALID`, so that the tables are locked only briefly, and are validated afterwards.
+    /// Validation scans the tables, but doesn't block concurrent reads or writes. If there are rows referencing
+    /// missing miniblocks, validation fails and the restored key remains `NOT VALID`; in this case, the rows
+    /// should be investigated and the method can be called again.
+    pub async fn restore_miniblock_data_foreign_keys(&mut self) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        for (table, on_delete) in MINIBLOCK_DATA_FOREIGN_KEYS {
+            let statement = format!(
+                "DO $$ BEGIN \
+                    IF NOT EXISTS ( \
+                        SELECT 1 FROM pg_constraint \
+                        WHERE conrelid = '{table}'::regclass AND conname = '{table}_miniblock_number_fkey' \
+                    ) THEN \
+                        ALTER TABLE {table} ADD CONSTRAINT {table}_miniblock_number_fkey \
+                        FOREIGN KEY (miniblock_number) REFERENCES miniblocks (number){on_delete} NOT VALID; \
+                    END IF; \
+                END $$"
+            );
+            sqlx::query(&statement)
+                .instrument("restore_miniblock_data_foreign_keys")
+                .with_arg("table", &table)
+                .execute(transaction.conn())
+                .await?;
+        }
+        transaction.commit().await?;
+
+        // Validation is performed for each table separately, so that it's not blocked by the `NOT VALID` keys
+        // being added. Validating an already validated key is a no-op.
+        for (table, _) in MINIBLOCK_DATA_FOREIGN_KEYS {
+            let statement =
+                format!("ALTER TABLE {table} VALIDATE CONSTRAINT {table}_miniblock_number_fkey");
+            sqlx::query(&statement)
+                .instrument("validate_miniblock_data_foreign_keys")
+                .with_arg("table", &table)
+                .execute(self.storage.conn())
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Returns sum of predicted gas costs on the given L1 batch range.
     /// Panics if the sum doesn't fit into `u32`.
     pub async fn get_l1_batches_predicted_gas(
@@ -1120,6 +1403,33 @@ impl BlocksDal<'_, '_> {
         Ok(())
     }
 
+    /// Records the estimated costs of sending pubdata of the L1 batch to L1 as calldata and in blobs.
+    pub async fn save_pubdata_cost_estimate(
+        &mut self,
+        number: L1BatchNumber,
+        cheaper_mode: Option<PubdataSendingMode>,
+        pubdata_size: usize,
+        calldata_cost: U256,
+        blobs_cost: Option<U256>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "INSERT INTO l1_batches_pubdata_costs \
+            (l1_batch_number, cheaper_pubdata_sending_mode, pubdata_size, calldata_cost, blobs_cost, created_at, updated_at) \
+            VALUES ($1, $2, $3, $4, $5, now(), now()) \
+            ON CONFLICT (l1_batch_number) DO UPDATE \
+            SET cheaper_pubdata_sending_mode = $2, pubdata_size = $3, calldata_cost = $4, blobs_cost = $5, \
+            updated_at = now()",
+            number.0 as i64,
+            cheaper_mode.map(|mode| mode.to_string()),
+            pubdata_size as i64,
+            u256_to_big_decimal(calldata_cost),
+            blobs_cost.map(u256_to_big_decimal)
+        )
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_miniblock_range_of_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
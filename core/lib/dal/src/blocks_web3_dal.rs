@@ -309,6 +309,86 @@ impl BlocksWeb3Dal<'_, '_> {
             .collect())
     }
 
+    /// Returns the 0-based index (in the L1 batch) of the `nth` L2->L1 log emitted by the transaction
+    /// with the specified index in the L1 batch. Logs are filtered in Postgres, so that the entire
+    /// list of logs for the batch doesn't need to be loaded.
+    pub async fn get_l2_to_l1_log_index_for_tx(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        tx_index_in_l1_batch: u16,
+        nth: usize,
+    ) -> Result<Option<usize>, sqlx::Error> {
+        let index = sqlx::query!(
+            "SELECT (log.index - 1) AS \"index!\" \
+            FROM l1_batches, UNNEST(l2_to_l1_logs) WITH ORDINALITY AS log(data, index) \
+            WHERE l1_batches.number = $1 AND SUBSTRING(log.data FROM 3 FOR 2) = $2 \
+            ORDER BY log.index OFFSET $3 LIMIT 1",
+            l1_batch_number.0 as i64,
+            &tx_index_in_l1_batch.to_be_bytes()[..],
+            nth as i64
+        )
+        .instrument("get_l2_to_l1_log_index_for_tx")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("tx_index_in_l1_batch", &tx_index_in_l1_batch)
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|row| row.index as usize);
+        Ok(index)
+    }
+
+    /// Returns the 0-based index (in the L1 batch) of the `nth` L2->L1 log with the specified sender, key
+    /// and value. Similarly to [`Self::get_l2_to_l1_log_index_for_tx()`], logs are filtered in Postgres.
+    pub async fn get_l2_to_l1_log_index_by_content(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        sender: Address,
+        key: H256,
+        value: H256,
+        nth: usize,
+    ) -> Result<Option<usize>, sqlx::Error> {
+        // Sender, key and value are serialized contiguously after the shard ID, service flag
+        // and transaction index; see `L2ToL1Log::from_slice()`.
+        let content = [sender.as_bytes(), key.as_bytes(), value.as_bytes()].concat();
+        let index = sqlx::query!(
+            "SELECT (log.index - 1) AS \"index!\" \
+            FROM l1_batches, UNNEST(l2_to_l1_logs) WITH ORDINALITY AS log(data, index) \
+            WHERE l1_batches.number = $1 AND SUBSTRING(log.data FROM 5 FOR 84) = $2 \
+            ORDER BY log.index OFFSET $3 LIMIT 1",
+            l1_batch_number.0 as i64,
+            &content,
+            nth as i64
+        )
+        .instrument("get_l2_to_l1_log_index_by_content")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("sender", &sender)
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|row| row.index as usize);
+        Ok(index)
+    }
+
+    /// Returns hashes of non-empty nodes of the L2->L1 log Merkle tree for the specified L1 batch
+    /// grouped by tree level, or `None` if the tree was not persisted for the batch.
+    pub async fn get_l2_to_l1_log_tree(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> Result<Option<Vec<Vec<H256>>>, sqlx::Error> {
+        let raw_layers = sqlx::query!(
+            "SELECT layers FROM l2_to_l1_log_trees WHERE l1_batch_number = $1",
+            number.0 as i64
+        )
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(|row| row.layers);
+
+        Ok(raw_layers.map(|layers| {
+            layers
+                .iter()
+                .map(|layer| layer.chunks(32).map(H256::from_slice).collect())
+                .collect()
+        }))
+    }
+
     pub async fn get_l1_batch_number_of_miniblock(
         &mut self,
         miniblock_number: MiniblockNumber,
@@ -384,6 +464,31 @@ impl BlocksWeb3Dal<'_, '_> {
         .collect()
     }
 
+    /// Returns call traces for all transactions in the specified miniblock together with
+    /// the transaction hashes, ordered by the transaction index in the miniblock.
+    pub async fn get_call_traces_for_miniblock(
+        &mut self,
+        block_number: MiniblockNumber,
+    ) -> sqlx::Result<Vec<(H256, Call)>> {
+        let traces = sqlx::query_as!(
+            CallTrace,
+            "SELECT call_traces.tx_hash, call_traces.call_trace FROM call_traces \
+            INNER JOIN transactions ON transactions.hash = call_traces.tx_hash \
+            WHERE transactions.miniblock_number = $1 \
+            ORDER BY transactions.index_in_block",
+            block_number.0 as i64
+        )
+        .instrument("get_call_traces_for_miniblock")
+        .with_arg("block_number", &block_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(traces
+            .into_iter()
+            .map(|trace| (H256::from_slice(&trace.tx_hash), Call::from(trace)))
+            .collect())
+    }
+
     /// Returns `base_fee_per_gas` for miniblock range [min(newest_block - block_count + 1, 0), newest_block]
     /// in descending order of miniblock numbers.
     pub async fn get_fee_history(
@@ -407,6 +512,55 @@ impl BlocksWeb3Dal<'_, '_> {
         Ok(result)
     }
 
+    /// Returns the maximum base fee among `block_count` miniblocks ending with `newest_block`.
+    pub async fn get_max_base_fee_per_gas(
+        &mut self,
+        newest_block: MiniblockNumber,
+        block_count: u64,
+    ) -> Result<Option<U256>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT MAX(base_fee_per_gas) AS \"max_base_fee_per_gas\" FROM miniblocks \
+            WHERE number <= $1 AND number > $1 - $2",
+            newest_block.0 as i64,
+            block_count as i64
+        )
+        .instrument("get_max_base_fee_per_gas")
+        .with_arg("newest_block", &newest_block)
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(row.max_base_fee_per_gas.map(bigdecimal_to_u256))
+    }
+
+    /// Returns the `percentile` (in the `0.0..=1.0` range) of priority fees effectively paid by L2 transactions
+    /// included into `block_count` miniblocks ending with `newest_block`. Returns `None` if these miniblocks
+    /// contain no L2 transactions.
+    pub async fn get_effective_priority_fee_percentile(
+        &mut self,
+        newest_block: MiniblockNumber,
+        block_count: u64,
+        percentile: f64,
+    ) -> Result<Option<U256>, sqlx::Error> {
+        let row = sqlx::query!(
+            "SELECT percentile_disc($3) WITHIN GROUP (\
+                ORDER BY GREATEST(transactions.effective_gas_price - miniblocks.base_fee_per_gas, 0)\
+            ) AS \"priority_fee\" \
+            FROM transactions \
+            INNER JOIN miniblocks ON miniblocks.number = transactions.miniblock_number \
+            WHERE transactions.miniblock_number <= $1 AND transactions.miniblock_number > $1 - $2 \
+                AND transactions.is_priority = FALSE",
+            newest_block.0 as i64,
+            block_count as i64,
+            percentile
+        )
+        .instrument("get_effective_priority_fee_percentile")
+        .with_arg("newest_block", &newest_block)
+        .fetch_one(self.storage.conn())
+        .await?;
+
+        Ok(row.priority_fee.map(bigdecimal_to_u256))
+    }
+
     pub async fn get_block_details(
         &mut self,
         block_number: MiniblockNumber,
@@ -477,11 +631,16 @@ impl BlocksWeb3Dal<'_, '_> {
                         l1_batches.l1_gas_price,
                         l1_batches.l2_fair_gas_price,
                         l1_batches.bootloader_code_hash,
-                        l1_batches.default_aa_code_hash
+                        l1_batches.default_aa_code_hash,
+                        data_availability.blob_id as "da_blob_id?",
+                        data_availability.sent_at as "da_sent_at?",
+                        data_availability.inclusion_data as "da_inclusion_data?",
+                        data_availability.inclusion_confirmed_at as "da_inclusion_confirmed_at?"
                     FROM l1_batches
                     LEFT JOIN eth_txs_history as commit_tx ON (l1_batches.eth_commit_tx_id = commit_tx.eth_tx_id AND commit_tx.confirmed_at IS NOT NULL)
                     LEFT JOIN eth_txs_history as prove_tx ON (l1_batches.eth_prove_tx_id = prove_tx.eth_tx_id AND prove_tx.confirmed_at IS NOT NULL)
                     LEFT JOIN eth_txs_history as execute_tx ON (l1_batches.eth_execute_tx_id = execute_tx.eth_tx_id AND execute_tx.confirmed_at IS NOT NULL)
+                    LEFT JOIN data_availability ON data_availability.l1_batch_number = l1_batches.number
                     WHERE l1_batches.number = $1
                 "#,
                 l1_batch_number.0 as i64
@@ -585,7 +744,7 @@ mod tests {
     use db_test_macro::db_test;
     use zksync_contracts::BaseSystemContractsHashes;
     use zksync_types::{
-        block::{miniblock_hash, MiniblockHeader},
+        block::{miniblock_hash, L1BatchHeader, MiniblockHeader},
         MiniblockNumber, ProtocolVersion, ProtocolVersionId,
     };
 
@@ -776,6 +935,7 @@ mod tests {
             base_fee_per_gas: 100,
             l1_gas_price: 100,
             l2_fair_gas_price: 100,
+            fair_pubdata_price: None,
             base_system_contracts_hashes: BaseSystemContractsHashes::default(),
             protocol_version: Some(ProtocolVersionId::default()),
             virtual_blocks: 0,
@@ -846,4 +1006,81 @@ mod tests {
             assert_eq!(miniblock_to, expected_miniblock_to);
         }
     }
+
+    #[db_test(dal_crate)]
+    async fn getting_l2_to_l1_log_indices(connection_pool: ConnectionPool) {
+        let mut conn = connection_pool.access_test_storage().await;
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let mut header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            Address::default(),
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+        );
+        let log = L2ToL1Log {
+            shard_id: 0,
+            is_service: false,
+            tx_number_in_block: 1,
+            sender: Address::repeat_byte(1),
+            key: H256::repeat_byte(2),
+            value: H256::repeat_byte(3),
+        };
+        header.l2_to_l1_logs = vec![
+            log.clone(),
+            L2ToL1Log {
+                tx_number_in_block: 2,
+                ..log.clone()
+            },
+            L2ToL1Log {
+                tx_number_in_block: 2,
+                value: H256::repeat_byte(4),
+                ..log.clone()
+            },
+        ];
+        conn.blocks_dal()
+            .insert_l1_batch(&header, &[], Default::default())
+            .await
+            .unwrap();
+
+        let mut dal = conn.blocks_web3_dal();
+        for (tx_index, nth, expected_index) in [(1, 0, Some(0)), (2, 0, Some(1)), (2, 1, Some(2))] {
+            let index = dal
+                .get_l2_to_l1_log_index_for_tx(L1BatchNumber(1), tx_index, nth)
+                .await
+                .unwrap();
+            assert_eq!(index, expected_index, "tx_index={tx_index}, nth={nth}");
+        }
+        let missing_index = dal
+            .get_l2_to_l1_log_index_for_tx(L1BatchNumber(1), 2, 2)
+            .await
+            .unwrap();
+        assert_eq!(missing_index, None);
+
+        for (value, nth, expected_index) in [
+            (log.value, 0, Some(0)),
+            (log.value, 1, Some(1)),
+            (log.value, 2, None),
+            (H256::repeat_byte(4), 0, Some(2)),
+        ] {
+            let index = dal
+                .get_l2_to_l1_log_index_by_content(
+                    L1BatchNumber(1),
+                    log.sender,
+                    log.key,
+                    value,
+                    nth,
+                )
+                .await
+                .unwrap();
+            assert_eq!(index, expected_index, "value={value:?}, nth={nth}");
+        }
+        let missing_index = dal
+            .get_l2_to_l1_log_index_by_content(L1BatchNumber(2), log.sender, log.key, log.value, 0)
+            .await
+            .unwrap();
+        assert_eq!(missing_index, None);
+    }
 }
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use zksync_types::{
+    api::{BridgeTransfer, BridgeTransferKind, BridgeTransferStatus},
+    Address, Execute, L1BatchNumber, MiniblockNumber, H256, U256,
+};
+use zksync_utils::{bigdecimal_to_u256, u256_to_big_decimal};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+/// Transaction in an L1 batch that may correspond to a bridge transfer.
+#[derive(Debug, Clone)]
+pub struct BridgeTransferTx {
+    pub hash: H256,
+    pub index_in_l1_batch: u16,
+    pub miniblock_number: MiniblockNumber,
+    pub is_priority: bool,
+    pub initiator_address: Address,
+    pub execute: Execute,
+    /// Hash of the L1 transaction that emitted the priority operation.
+    pub l1_tx_hash: Option<H256>,
+    pub failed: bool,
+}
+
+fn kind_to_str(kind: BridgeTransferKind) -> &'static str {
+    match kind {
+        BridgeTransferKind::Deposit => "deposit",
+        BridgeTransferKind::Withdrawal => "withdrawal",
+    }
+}
+
+fn kind_from_str(s: &str) -> sqlx::Result<BridgeTransferKind> {
+    Ok(match s {
+        "deposit" => BridgeTransferKind::Deposit,
+        "withdrawal" => BridgeTransferKind::Withdrawal,
+        _ => {
+            let err = format!("unknown bridge transfer kind `{s}`");
+            return Err(sqlx::Error::Decode(err.into()));
+        }
+    })
+}
+
+fn status_to_str(status: BridgeTransferStatus) -> &'static str {
+    match status {
+        BridgeTransferStatus::Initiated => "initiated",
+        BridgeTransferStatus::Finalized => "finalized",
+        BridgeTransferStatus::Failed => "failed",
+    }
+}
+
+fn status_from_str(s: &str) -> sqlx::Result<BridgeTransferStatus> {
+    Ok(match s {
+        "initiated" => BridgeTransferStatus::Initiated,
+        "finalized" => BridgeTransferStatus::Finalized,
+        "failed" => BridgeTransferStatus::Failed,
+        _ => {
+            let err = format!("unknown bridge transfer status `{s}`");
+            return Err(sqlx::Error::Decode(err.into()));
+        }
+    })
+}
+
+/// Persists deposits and withdrawals indexed by the bridge indexer.
+#[derive(Debug)]
+pub struct BridgeTransfersDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl BridgeTransfersDal<'_, '_> {
+    /// Returns the last L1 batch processed by the indexer, or `None` if no L1 batches were processed yet.
+    pub async fn get_last_indexed_l1_batch(&mut self) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!(
+            "SELECT MAX(l1_batch_number) AS \"number\" FROM bridge_indexed_l1_batches"
+        )
+        .instrument("get_last_indexed_l1_batch")
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number as u32)))
+    }
+
+    /// Loads all priority transactions in the specified L1 batch, and L2 transactions at the specified
+    /// positions in the L1 batch.
+    pub async fn get_l1_batch_transfer_txs(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        l2_tx_indices: &[u16],
+    ) -> sqlx::Result<Vec<BridgeTransferTx>> {
+        let l2_tx_indices: Vec<_> = l2_tx_indices.iter().copied().map(i32::from).collect();
+        let rows = sqlx::query!(
+            "SELECT hash, l1_batch_tx_index AS \"l1_batch_tx_index!\", \
+                miniblock_number AS \"miniblock_number!\", is_priority, initiator_address, data, \
+                l1_tx_hash, error \
+            FROM transactions \
+            WHERE l1_batch_number = $1 AND (is_priority = TRUE OR l1_batch_tx_index = ANY($2)) \
+            ORDER BY l1_batch_tx_index",
+            l1_batch_number.0 as i64,
+            &l2_tx_indices
+        )
+        .instrument("get_l1_batch_transfer_txs")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let execute = serde_json::from_value(row.data)
+                    .map_err(|err| sqlx::Error::Decode(err.into()))?;
+                Ok(BridgeTransferTx {
+                    hash: H256::from_slice(&row.hash),
+                    index_in_l1_batch: row.l1_batch_tx_index as u16,
+                    miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                    is_priority: row.is_priority,
+                    initiator_address: Address::from_slice(&row.initiator_address),
+                    execute,
+                    l1_tx_hash: row.l1_tx_hash.as_deref().map(H256::from_slice),
+                    failed: row.error.is_some(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns L2 addresses of the specified L1 tokens. Tokens unknown on L2 are omitted.
+    pub async fn get_l2_token_addresses(
+        &mut self,
+        l1_tokens: &[Address],
+    ) -> sqlx::Result<HashMap<Address, Address>> {
+        let l1_tokens: Vec<_> = l1_tokens.iter().map(Address::as_bytes).collect();
+        let rows = sqlx::query!(
+            "SELECT l1_address, l2_address FROM tokens WHERE l1_address = ANY($1)",
+            &l1_tokens as &[&[u8]]
+        )
+        .instrument("get_l2_token_addresses")
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    Address::from_slice(&row.l1_address),
+                    Address::from_slice(&row.l2_address),
+                )
+            })
+            .collect())
+    }
+
+    /// Saves transfers found in the specified L1 batch and marks the L1 batch as processed.
+    /// Transfers must be ordered by their position in the L1 batch.
+    pub async fn insert_l1_batch_transfers(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        transfers: &[BridgeTransfer],
+    ) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        for (index, transfer) in transfers.iter().enumerate() {
+            sqlx::query!(
+                "INSERT INTO bridge_transfers \
+                    (l1_batch_number, index_in_l1_batch, kind, status, sender, receiver, l1_token, \
+                    l2_token, amount, l1_tx_hash, l2_tx_hash, miniblock_number, l2_message_index, \
+                    created_at) \
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, now())",
+                l1_batch_number.0 as i64,
+                index as i32,
+                kind_to_str(transfer.kind),
+                status_to_str(transfer.status),
+                transfer.sender.as_bytes(),
+                transfer.receiver.as_bytes(),
+                transfer.l1_token.as_bytes(),
+                transfer.l2_token.as_ref().map(Address::as_bytes),
+                u256_to_big_decimal(transfer.amount),
+                transfer.l1_tx_hash.as_ref().map(H256::as_bytes),
+                transfer.l2_tx_hash.as_bytes(),
+                transfer.miniblock_number.0 as i64,
+                transfer.l2_message_index.map(|index| index as i32)
+            )
+            .instrument("insert_l1_batch_transfers#insert")
+            .with_arg("l1_batch_number", &l1_batch_number)
+            .execute(transaction.conn())
+            .await?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO bridge_indexed_l1_batches (l1_batch_number, created_at) VALUES ($1, now())",
+            l1_batch_number.0 as i64
+        )
+        .instrument("insert_l1_batch_transfers#mark_indexed")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Returns initiated withdrawals with the specified L1 parameters as `(l1_batch_number, l2_message_index)`
+    /// tuples, starting from the oldest ones.
+    pub async fn get_initiated_withdrawals(
+        &mut self,
+        l1_receiver: Address,
+        l1_token: Address,
+        amount: U256,
+    ) -> sqlx::Result<Vec<(L1BatchNumber, u32)>> {
+        let rows = sqlx::query!(
+            "SELECT l1_batch_number, l2_message_index AS \"l2_message_index!\" \
+            FROM bridge_transfers \
+            WHERE kind = 'withdrawal' AND status = 'initiated' AND receiver = $1 AND l1_token = $2 \
+                AND amount = $3 AND l2_message_index IS NOT NULL \
+            ORDER BY l1_batch_number, index_in_l1_batch",
+            l1_receiver.as_bytes(),
+            l1_token.as_bytes(),
+            u256_to_big_decimal(amount)
+        )
+        .instrument("get_initiated_withdrawals")
+        .with_arg("l1_receiver", &l1_receiver)
+        .with_arg("l1_token", &l1_token)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    L1BatchNumber(row.l1_batch_number as u32),
+                    row.l2_message_index as u32,
+                )
+            })
+            .collect())
+    }
+
+    /// Marks a withdrawal as finalized on L1 by the specified L1 transaction.
+    pub async fn mark_withdrawal_as_finalized(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        l2_message_index: u32,
+        l1_tx_hash: H256,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "UPDATE bridge_transfers SET status = 'finalized', l1_tx_hash = $3 \
+            WHERE kind = 'withdrawal' AND l1_batch_number = $1 AND l2_message_index = $2",
+            l1_batch_number.0 as i64,
+            l2_message_index as i32,
+            l1_tx_hash.as_bytes()
+        )
+        .instrument("mark_withdrawal_as_finalized")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("l2_message_index", &l2_message_index)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the last L1 block scanned for withdrawal finalization events, or `None` if no blocks were scanned yet.
+    pub async fn get_last_processed_l1_block(&mut self) -> sqlx::Result<Option<u64>> {
+        let row = sqlx::query!("SELECT last_processed_l1_block FROM bridge_indexer_l1_cursor")
+            .instrument("get_last_processed_l1_block")
+            .fetch_optional(self.storage.conn())
+            .await?;
+        Ok(row.map(|row| row.last_processed_l1_block as u64))
+    }
+
+    /// Sets the last L1 block scanned for withdrawal finalization events.
+    pub async fn set_last_processed_l1_block(&mut self, block_number: u64) -> sqlx::Result<()> {
+        sqlx::query!(
+            "INSERT INTO bridge_indexer_l1_cursor (last_processed_l1_block, created_at, updated_at) \
+            VALUES ($1, now(), now()) \
+            ON CONFLICT (id) DO UPDATE \
+            SET last_processed_l1_block = excluded.last_processed_l1_block, updated_at = now()",
+            block_number as i64
+        )
+        .instrument("set_last_processed_l1_block")
+        .with_arg("block_number", &block_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns transfers sent or received by the specified address, starting from the most recent ones.
+    pub async fn get_transfers_for_address(
+        &mut self,
+        address: Address,
+        offset: usize,
+        limit: usize,
+    ) -> sqlx::Result<Vec<BridgeTransfer>> {
+        let rows = sqlx::query!(
+            "SELECT kind, status, sender, receiver, l1_token, l2_token, amount, l1_tx_hash, l2_tx_hash, \
+                miniblock_number, l1_batch_number, l2_message_index \
+            FROM bridge_transfers \
+            WHERE sender = $1 OR receiver = $1 \
+            ORDER BY l1_batch_number DESC, index_in_l1_batch DESC \
+            OFFSET $2 LIMIT $3",
+            address.as_bytes(),
+            offset as i64,
+            limit as i64
+        )
+        .instrument("get_transfers_for_address")
+        .with_arg("address", &address)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(BridgeTransfer {
+                    kind: kind_from_str(&row.kind)?,
+                    status: status_from_str(&row.status)?,
+                    sender: Address::from_slice(&row.sender),
+                    receiver: Address::from_slice(&row.receiver),
+                    l1_token: Address::from_slice(&row.l1_token),
+                    l2_token: row.l2_token.as_deref().map(Address::from_slice),
+                    amount: bigdecimal_to_u256(row.amount),
+                    l1_tx_hash: row.l1_tx_hash.as_deref().map(H256::from_slice),
+                    l2_tx_hash: H256::from_slice(&row.l2_tx_hash),
+                    miniblock_number: MiniblockNumber(row.miniblock_number as u32),
+                    l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+                    l2_message_index: row.l2_message_index.map(|index| index as u32),
+                })
+            })
+            .collect()
+    }
+}
@@ -6,6 +6,7 @@ use sqlx::{
 use anyhow::Context as _;
 use std::time::Duration;
 
+use zksync_config::DBConfig;
 use zksync_utils::parse_env;
 
 pub mod holder;
@@ -18,6 +19,11 @@ use crate::{
     metrics::CONNECTION_METRICS, StorageProcessor,
 };
 
+/// Default capacity of the per-connection prepared statement cache. The DAL defines several hundred
+/// distinct queries, so the `sqlx` default (100 statements) leads to hot queries being evicted
+/// and re-prepared when a connection is used by multiple components.
+pub const DEFAULT_STATEMENT_CACHE_CAPACITY: usize = 1_024;
+
 #[derive(Debug, Clone, Copy)]
 pub enum DbVariant {
     Master,
@@ -31,11 +37,21 @@ pub struct ConnectionPoolBuilder {
     db: DbVariant,
     max_size: Option<u32>,
     statement_timeout: Option<Duration>,
+    statement_cache_capacity: usize,
+    db_config: Option<DBConfig>,
 }
 
 impl ConnectionPoolBuilder {
+    /// Sets the database config used to create the pool: database URLs, the default pool size
+    /// and the Postgres schema. If not specified, URLs and the pool size are taken from env variables,
+    /// and the default schema is used.
+    pub fn set_db_config(&mut self, config: &DBConfig) -> &mut Self {
+        self.db_config = Some(config.clone());
+        self
+    }
+
     /// Sets the maximum size of the created pool. If not specified, the max pool size will be
-    /// taken from the `DATABASE_POOL_SIZE` env variable.
+    /// taken from the database config or the `DATABASE_POOL_SIZE` env variable.
     pub fn set_max_size(&mut self, max_size: Option<u32>) -> &mut Self {
         self.max_size = max_size;
         self
@@ -50,29 +66,56 @@ impl ConnectionPoolBuilder {
         self
     }
 
+    /// Sets the capacity of the prepared statement cache for each connection in the pool.
+    /// If not specified, [`DEFAULT_STATEMENT_CACHE_CAPACITY`] is used. Setting the capacity to 0
+    /// disables caching, i.e., each query will be prepared anew.
+    pub fn set_statement_cache_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.statement_cache_capacity = capacity;
+        self
+    }
+
     /// Builds a connection pool from this builder.
     pub async fn build(&self) -> anyhow::Result<ConnectionPool> {
-        let database_url = match self.db {
-            DbVariant::Master => get_master_database_url()?,
-            DbVariant::Replica => get_replica_database_url()?,
-            DbVariant::Prover => get_prover_database_url()?,
+        let database_url = if let Some(config) = &self.db_config {
+            match self.db {
+                DbVariant::Master => config.master_url()?,
+                DbVariant::Replica => config.replica_url()?,
+                DbVariant::Prover => config.prover_url()?,
+            }
+            .to_owned()
+        } else {
+            match self.db {
+                DbVariant::Master => get_master_database_url()?,
+                DbVariant::Replica => get_replica_database_url()?,
+                DbVariant::Prover => get_prover_database_url()?,
+            }
         };
         Ok(self.build_inner(&database_url).await)
     }
 
     pub async fn build_inner(&self, database_url: &str) -> ConnectionPool {
+        let config_pool_size = self.db_config.as_ref().and_then(|config| config.pool_size);
         let max_connections = self
             .max_size
+            .or(config_pool_size)
             .unwrap_or_else(|| parse_env("DATABASE_POOL_SIZE"));
+        let schema = self
+            .db_config
+            .as_ref()
+            .and_then(|config| config.schema.as_deref());
 
         let options = PgPoolOptions::new().max_connections(max_connections);
         let mut connect_options: PgConnectOptions = database_url.parse().unwrap_or_else(|err| {
             panic!("Failed parsing {:?} database URL: {}", self.db, err);
         });
+        connect_options = connect_options.statement_cache_capacity(self.statement_cache_capacity);
         if let Some(timeout) = self.statement_timeout {
             let timeout_string = format!("{}s", timeout.as_secs());
             connect_options = connect_options.options([("statement_timeout", timeout_string)]);
         }
+        if let Some(schema) = schema {
+            connect_options = connect_options.options([("search_path", schema)]);
+        }
         let pool = options
             .connect_with(connect_options)
             .await
@@ -80,10 +123,11 @@ impl ConnectionPoolBuilder {
                 panic!("Failed connecting to {:?} database: {}", self.db, err);
             });
         tracing::info!(
-            "Created pool for {db:?} database with {max_connections} max connections \
-             and {statement_timeout:?} statement timeout",
+            "Created pool for {db:?} database (schema: {schema:?}) with {max_connections} max connections, \
+             {statement_timeout:?} statement timeout and {statement_cache_capacity} statement cache capacity",
             db = self.db,
-            statement_timeout = self.statement_timeout
+            statement_timeout = self.statement_timeout,
+            statement_cache_capacity = self.statement_cache_capacity
         );
         ConnectionPool::Real(pool)
     }
@@ -102,6 +146,8 @@ impl ConnectionPool {
             db,
             max_size: None,
             statement_timeout: None,
+            statement_cache_capacity: DEFAULT_STATEMENT_CACHE_CAPACITY,
+            db_config: None,
         }
     }
 
@@ -112,6 +158,8 @@ impl ConnectionPool {
             db,
             max_size: Some(1),
             statement_timeout: None,
+            statement_cache_capacity: DEFAULT_STATEMENT_CACHE_CAPACITY,
+            db_config: None,
         }
     }
 
@@ -237,4 +285,27 @@ mod tests {
             sqlx::Error::Database(db_err) if db_err.message().contains("statement timeout")
         );
     }
+
+    #[tokio::test]
+    async fn setting_schema_from_db_config() {
+        let database_url = get_test_database_url().unwrap();
+        let db_config = DBConfig {
+            url: Some(database_url),
+            pool_size: Some(2),
+            schema: Some("era".to_owned()),
+            ..DBConfig::from_env().unwrap()
+        };
+        let pool = ConnectionPool::builder(DbVariant::Replica)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .unwrap();
+        // NB. We must not mutate the database below! Doing so may break other tests.
+        let mut conn = pool.access_storage().await.unwrap();
+        let search_path: String = sqlx::query_scalar("SHOW search_path")
+            .fetch_one(conn.conn())
+            .await
+            .unwrap();
+        assert_eq!(search_path, "era");
+    }
 }
@@ -1,12 +1,13 @@
 // Built-in deps
 use std::{fmt, mem, pin::Pin, sync::Arc, time::Duration};
 // External imports
-use sqlx::{Acquire, Connection, PgConnection, Postgres, Transaction};
+use sqlx::{postgres::PgConnectOptions, Acquire, Connection, PgConnection, Postgres, Transaction};
 use tokio::{
     sync::{Mutex, OwnedMutexGuard},
     time::timeout,
 };
 // Local imports
+use super::DEFAULT_STATEMENT_CACHE_CAPACITY;
 use crate::StorageProcessor;
 
 /// Self-referential struct powering [`TestPool`].
@@ -37,7 +38,12 @@ impl fmt::Debug for TestPoolInner {
 impl TestPoolInner {
     async fn new() -> Self {
         let database_url = crate::get_test_database_url().unwrap();
-        let connection = PgConnection::connect(&database_url).await.unwrap();
+        // Use the same statement cache configuration as real pools, so that DAL benchmarks are representative.
+        let connect_options = database_url
+            .parse::<PgConnectOptions>()
+            .unwrap()
+            .statement_cache_capacity(DEFAULT_STATEMENT_CACHE_CAPACITY);
+        let connection = PgConnection::connect_with(&connect_options).await.unwrap();
         let mut connection = Box::pin(connection);
 
         let transaction = Connection::begin(&mut *connection).await.unwrap();
@@ -0,0 +1,153 @@
+use anyhow::Context as _;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use std::{collections::HashSet, ops};
+
+use zksync_types::{api::DataAvailabilityDetails, L1BatchNumber};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+#[derive(Debug)]
+pub struct DataAvailabilityDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl DataAvailabilityDal<'_, '_> {
+    /// Records that pubdata of the specified L1 batch was dispatched to the DA layer under `blob_id`.
+    pub async fn insert_l1_batch_da(
+        &mut self,
+        number: L1BatchNumber,
+        blob_id: &str,
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            "INSERT INTO data_availability \
+                (l1_batch_number, blob_id, sent_at, created_at, updated_at) \
+            VALUES ($1, $2, now(), now(), now()) \
+            ON CONFLICT DO NOTHING",
+            number.0 as i64,
+            blob_id
+        )
+        .instrument("insert_l1_batch_da")
+        .with_arg("number", &number)
+        .with_arg("blob_id", &blob_id)
+        .execute(self.storage.conn())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            // The L1 batch may have been dispatched before (e.g., if the dispatcher was restarted);
+            // this is only OK if the blob ID is the same.
+            let existing = self.get_l1_batch_da(number).await?;
+            let existing_blob_id = existing.map(|details| details.blob_id);
+            anyhow::ensure!(
+                existing_blob_id.as_deref() == Some(blob_id),
+                "L1 batch #{number} is already dispatched to the DA layer with blob ID {existing_blob_id:?}, \
+                 which differs from the provided {blob_id}"
+            );
+        }
+        Ok(())
+    }
+
+    /// Saves the inclusion proof / attestation returned by the DA layer for the specified L1 batch,
+    /// marking its pubdata inclusion as confirmed.
+    pub async fn save_l1_batch_inclusion_data(
+        &mut self,
+        number: L1BatchNumber,
+        inclusion_data: &[u8],
+    ) -> anyhow::Result<()> {
+        let result = sqlx::query!(
+            "UPDATE data_availability \
+            SET inclusion_data = $1, inclusion_confirmed_at = now(), updated_at = now() \
+            WHERE l1_batch_number = $2 AND inclusion_data IS NULL",
+            inclusion_data,
+            number.0 as i64
+        )
+        .instrument("save_l1_batch_inclusion_data")
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            let existing = self.get_l1_batch_da(number).await?;
+            let existing = existing
+                .with_context(|| format!("L1 batch #{number} is not dispatched to the DA layer"))?;
+            let existing_data = existing.inclusion_data.map(|data| data.0);
+            anyhow::ensure!(
+                existing_data.as_deref() == Some(inclusion_data),
+                "L1 batch #{number} already has different inclusion data saved"
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the DA layer pointer and inclusion data for the specified L1 batch, or `None` if
+    /// the L1 batch pubdata wasn't dispatched to the DA layer.
+    pub async fn get_l1_batch_da(
+        &mut self,
+        number: L1BatchNumber,
+    ) -> sqlx::Result<Option<DataAvailabilityDetails>> {
+        let row = sqlx::query!(
+            "SELECT blob_id, sent_at, inclusion_data, inclusion_confirmed_at \
+            FROM data_availability WHERE l1_batch_number = $1",
+            number.0 as i64
+        )
+        .instrument("get_l1_batch_da")
+        .with_arg("number", &number)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| DataAvailabilityDetails {
+            blob_id: row.blob_id,
+            sent_at: DateTime::<Utc>::from_naive_utc_and_offset(row.sent_at, Utc),
+            inclusion_data: row.inclusion_data.map(Into::into),
+            inclusion_confirmed_at: row
+                .inclusion_confirmed_at
+                .map(|confirmed_at| DateTime::<Utc>::from_naive_utc_and_offset(confirmed_at, Utc)),
+        }))
+    }
+
+    /// Returns L1 batches in the specified range which pubdata inclusion on the DA layer is confirmed.
+    pub async fn get_l1_batches_with_confirmed_inclusion(
+        &mut self,
+        range: ops::RangeInclusive<L1BatchNumber>,
+    ) -> sqlx::Result<HashSet<L1BatchNumber>> {
+        let rows = sqlx::query!(
+            "SELECT l1_batch_number FROM data_availability \
+            WHERE l1_batch_number BETWEEN $1 AND $2 AND inclusion_data IS NOT NULL",
+            range.start().0 as i64,
+            range.end().0 as i64
+        )
+        .instrument("get_l1_batches_with_confirmed_inclusion")
+        .with_arg("range", &range)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L1BatchNumber(row.l1_batch_number as u32))
+            .collect())
+    }
+
+    /// Returns L1 batches dispatched to the DA layer which inclusion is not confirmed yet, in ascending order.
+    pub async fn get_l1_batches_pending_inclusion(
+        &mut self,
+        limit: usize,
+    ) -> sqlx::Result<Vec<(L1BatchNumber, String)>> {
+        let rows = sqlx::query!(
+            "SELECT l1_batch_number, blob_id FROM data_availability \
+            WHERE inclusion_data IS NULL \
+            ORDER BY l1_batch_number \
+            LIMIT $1",
+            limit as i64
+        )
+        .instrument("get_l1_batches_pending_inclusion")
+        .report_latency()
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (L1BatchNumber(row.l1_batch_number as u32), row.blob_id))
+            .collect())
+    }
+}
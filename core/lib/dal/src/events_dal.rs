@@ -4,7 +4,8 @@ use std::fmt;
 
 use crate::{models::storage_event::StorageL2ToL1Log, SqlxError, StorageProcessor};
 use zksync_types::{
-    l2_to_l1_log::L2ToL1Log, tx::IncludedTxLocation, MiniblockNumber, VmEvent, H256,
+    l2_to_l1_log::L2ToL1Log, tx::IncludedTxLocation, Address, L1BatchNumber, MiniblockNumber,
+    VmEvent, H256,
 };
 
 /// Wrapper around an optional event topic allowing to hex-format it for `COPY` instructions.
@@ -172,6 +173,38 @@ impl EventsDal<'_, '_> {
         .unwrap();
     }
 
+    /// Returns L2-to-L1 logs emitted in the specified L1 batch, in the order they were emitted.
+    /// Unlike the logs stored in the L1 batch header, these logs are persisted per miniblock when it is sealed.
+    pub async fn get_l2_to_l1_logs_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<Vec<L2ToL1Log>, SqlxError> {
+        let rows = sqlx::query!(
+            "SELECT l2_to_l1_logs.shard_id, l2_to_l1_logs.is_service, \
+                l2_to_l1_logs.tx_index_in_l1_batch, l2_to_l1_logs.sender, \
+                l2_to_l1_logs.key, l2_to_l1_logs.value \
+            FROM l2_to_l1_logs \
+            INNER JOIN miniblocks ON miniblocks.number = l2_to_l1_logs.miniblock_number \
+            WHERE miniblocks.l1_batch_number = $1 \
+            ORDER BY l2_to_l1_logs.miniblock_number, l2_to_l1_logs.log_index_in_miniblock",
+            l1_batch_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| L2ToL1Log {
+                shard_id: row.shard_id as u8,
+                is_service: row.is_service,
+                tx_number_in_block: row.tx_index_in_l1_batch as u16,
+                sender: Address::from_slice(&row.sender),
+                key: H256::from_slice(&row.key),
+                value: H256::from_slice(&row.value),
+            })
+            .collect())
+    }
+
     pub(crate) async fn l2_to_l1_logs(
         &mut self,
         tx_hash: H256,
@@ -190,6 +223,45 @@ impl EventsDal<'_, '_> {
         .fetch_all(self.storage.conn())
         .await
     }
+
+    pub(crate) async fn l2_to_l1_logs_for_miniblock(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> Result<Vec<StorageL2ToL1Log>, SqlxError> {
+        sqlx::query_as!(
+            StorageL2ToL1Log,
+            "SELECT \
+                miniblock_number, log_index_in_miniblock, log_index_in_tx, tx_hash, \
+                Null::bytea as \"block_hash\", Null::bigint as \"l1_batch_number?\", \
+                shard_id, is_service, tx_index_in_miniblock, tx_index_in_l1_batch, sender, key, value \
+            FROM l2_to_l1_logs \
+            WHERE miniblock_number = $1 \
+            ORDER BY log_index_in_miniblock ASC",
+            miniblock_number.0 as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await
+    }
+
+    pub(crate) async fn l2_to_l1_logs_for_transactions(
+        &mut self,
+        tx_hashes: &[H256],
+    ) -> Result<Vec<StorageL2ToL1Log>, SqlxError> {
+        let tx_hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        sqlx::query_as!(
+            StorageL2ToL1Log,
+            "SELECT \
+                miniblock_number, log_index_in_miniblock, log_index_in_tx, tx_hash, \
+                Null::bytea as \"block_hash\", Null::bigint as \"l1_batch_number?\", \
+                shard_id, is_service, tx_index_in_miniblock, tx_index_in_l1_batch, sender, key, value \
+            FROM l2_to_l1_logs \
+            WHERE tx_hash = ANY($1) \
+            ORDER BY miniblock_number ASC, log_index_in_miniblock ASC",
+            &tx_hashes as &[&[u8]]
+        )
+        .fetch_all(self.storage.conn())
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -197,7 +269,7 @@ mod tests {
     use super::*;
     use crate::{tests::create_miniblock_header, ConnectionPool};
     use db_test_macro::db_test;
-    use zksync_types::{Address, L1BatchNumber, ProtocolVersion};
+    use zksync_types::{api::GetLogsFilter, Address, L1BatchNumber, ProtocolVersion};
 
     fn create_vm_event(index: u8, topic_count: u8) -> VmEvent {
         assert!(topic_count <= 4);
@@ -273,6 +345,57 @@ mod tests {
         }
     }
 
+    #[db_test(dal_crate)]
+    async fn streaming_logs_stops_early(pool: ConnectionPool) {
+        let mut conn = pool.access_storage().await.unwrap();
+        conn.events_dal().rollback_events(MiniblockNumber(0)).await;
+        conn.blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        conn.blocks_dal()
+            .insert_miniblock(&create_miniblock_header(1))
+            .await
+            .unwrap();
+
+        let location = IncludedTxLocation {
+            tx_hash: H256([1; 32]),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: Address::default(),
+        };
+        let events: Vec<_> = (0..5).map(|i| create_vm_event(i, 1)).collect();
+        let all_events = vec![(location, events.iter().collect())];
+        conn.events_dal()
+            .save_events(MiniblockNumber(1), &all_events)
+            .await;
+
+        let filter = GetLogsFilter {
+            from_block: MiniblockNumber(1),
+            to_block: None,
+            addresses: vec![],
+            topics: vec![],
+        };
+        let mut seen_logs = 0;
+        let logs = conn
+            .events_web3_dal()
+            .get_logs_while(filter.clone(), 100, |_| {
+                seen_logs += 1;
+                seen_logs <= 2
+            })
+            .await
+            .unwrap();
+        assert_eq!(seen_logs, 3);
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].address, Address::repeat_byte(0));
+        assert_eq!(logs[1].address, Address::repeat_byte(1));
+
+        let logs = conn.events_web3_dal().get_logs(filter, 100).await.unwrap();
+        assert_eq!(logs.len(), 5);
+    }
+
     fn create_l2_to_l1_log(tx_number_in_block: u16, index: u8) -> L2ToL1Log {
         L2ToL1Log {
             shard_id: 0,
@@ -67,6 +67,18 @@ impl EventsWeb3Dal<'_, '_> {
         &mut self,
         filter: GetLogsFilter,
         limit: usize,
+    ) -> Result<Vec<Log>, SqlxError> {
+        self.get_logs_while(filter, limit, |_| true).await
+    }
+
+    /// Same as [`Self::get_logs()`], but streams logs from the database, passing each log to `should_continue`.
+    /// Once `should_continue` returns `false`, the remaining logs are not loaded; the log it was called on
+    /// is not included into the output.
+    pub async fn get_logs_while(
+        &mut self,
+        filter: GetLogsFilter,
+        limit: usize,
+        mut should_continue: impl FnMut(&Log) -> bool + Send,
     ) -> Result<Vec<Log>, SqlxError> {
         {
             let (where_sql, arg_index) = self.build_get_logs_where_clause(&filter);
@@ -102,14 +114,21 @@ impl EventsWeb3Dal<'_, '_> {
             }
             query = query.bind(limit as i32);
 
-            let db_logs: Vec<StorageWeb3Log> = query
+            let mut logs = vec![];
+            query
                 .instrument("get_logs")
                 .report_latency()
                 .with_arg("filter", &filter)
                 .with_arg("limit", &limit)
-                .fetch_all(self.storage.conn())
+                .fetch_while(self.storage.conn(), |db_log: StorageWeb3Log| {
+                    let log = Log::from(db_log);
+                    let should_continue = should_continue(&log);
+                    if should_continue {
+                        logs.push(log);
+                    }
+                    should_continue
+                })
                 .await?;
-            let logs = db_logs.into_iter().map(Into::into).collect();
             Ok(logs)
         }
     }
@@ -1,5 +1,6 @@
 //! DAL query instrumentation.
 
+use futures::TryStreamExt as _;
 use sqlx::{
     postgres::{PgConnection, PgQueryResult, PgRow},
     query::{Map, Query, QueryAs},
@@ -215,6 +216,26 @@ where
     pub async fn fetch_all(self, conn: &mut PgConnection) -> Result<Vec<O>, sqlx::Error> {
         self.data.fetch(self.query.fetch_all(conn)).await
     }
+
+    /// Streams rows returned by this query into `process_row` one by one. Once `process_row` returns `false`,
+    /// the remaining rows are not fetched.
+    pub async fn fetch_while(
+        self,
+        conn: &mut PgConnection,
+        mut process_row: impl FnMut(O) -> bool + Send,
+    ) -> Result<(), sqlx::Error> {
+        let Self { query, data } = self;
+        let query_future = async move {
+            let mut rows = query.fetch(conn);
+            while let Some(row) = rows.try_next().await? {
+                if !process_row(row) {
+                    break;
+                }
+            }
+            Ok(())
+        };
+        data.fetch(query_future).await
+    }
 }
 
 impl<'q, F, O, A> Instrumented<'_, Map<'q, Postgres, F, A>>
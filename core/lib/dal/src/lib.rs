@@ -14,9 +14,11 @@ pub use sqlx::types::BigDecimal;
 use crate::accounts_dal::AccountsDal;
 use crate::blocks_dal::BlocksDal;
 use crate::blocks_web3_dal::BlocksWeb3Dal;
+use crate::bridge_transfers_dal::BridgeTransfersDal;
 pub use crate::connection::ConnectionPool;
 use crate::connection::{holder::ConnectionHolder, test_pool::TestPoolLock};
 use crate::contract_verification_dal::ContractVerificationDal;
+use crate::data_availability_dal::DataAvailabilityDal;
 use crate::eth_sender_dal::EthSenderDal;
 use crate::events_dal::EventsDal;
 use crate::events_web3_dal::EventsWeb3Dal;
@@ -27,6 +29,7 @@ use crate::fri_prover_dal::FriProverDal;
 use crate::fri_scheduler_dependency_tracker_dal::FriSchedulerDependencyTrackerDal;
 use crate::fri_witness_generator_dal::FriWitnessGeneratorDal;
 use crate::gpu_prover_queue_dal::GpuProverQueueDal;
+use crate::online_migrations_dal::OnlineMigrationsDal;
 use crate::proof_generation_dal::ProofGenerationDal;
 use crate::protocol_versions_dal::ProtocolVersionsDal;
 use crate::protocol_versions_web3_dal::ProtocolVersionsWeb3Dal;
@@ -35,12 +38,16 @@ use crate::storage_dal::StorageDal;
 use crate::storage_logs_dal::StorageLogsDal;
 use crate::storage_logs_dedup_dal::StorageLogsDedupDal;
 use crate::storage_web3_dal::StorageWeb3Dal;
+use crate::stream_publisher_dal::StreamPublisherDal;
 use crate::sync_dal::SyncDal;
 use crate::system_dal::SystemDal;
 use crate::tokens_dal::TokensDal;
 use crate::tokens_web3_dal::TokensWeb3Dal;
 use crate::transactions_dal::TransactionsDal;
 use crate::transactions_web3_dal::TransactionsWeb3Dal;
+use crate::tx_lifecycle_dal::TxLifecycleDal;
+use crate::webhook_notifier_dal::WebhookNotifierDal;
+use crate::withdrawal_finalizer_dal::WithdrawalFinalizerDal;
 use crate::witness_generator_dal::WitnessGeneratorDal;
 
 #[macro_use]
@@ -48,8 +55,10 @@ mod macro_utils;
 pub mod accounts_dal;
 pub mod blocks_dal;
 pub mod blocks_web3_dal;
+pub mod bridge_transfers_dal;
 pub mod connection;
 pub mod contract_verification_dal;
+pub mod data_availability_dal;
 pub mod eth_sender_dal;
 pub mod events_dal;
 pub mod events_web3_dal;
@@ -64,6 +73,7 @@ pub mod healthcheck;
 mod instrument;
 mod metrics;
 mod models;
+pub mod online_migrations_dal;
 pub mod proof_generation_dal;
 pub mod protocol_versions_dal;
 pub mod protocol_versions_web3_dal;
@@ -72,6 +82,7 @@ pub mod storage_dal;
 pub mod storage_logs_dal;
 pub mod storage_logs_dedup_dal;
 pub mod storage_web3_dal;
+pub mod stream_publisher_dal;
 pub mod sync_dal;
 pub mod system_dal;
 pub mod time_utils;
@@ -79,6 +90,9 @@ pub mod tokens_dal;
 pub mod tokens_web3_dal;
 pub mod transactions_dal;
 pub mod transactions_web3_dal;
+pub mod tx_lifecycle_dal;
+pub mod webhook_notifier_dal;
+pub mod withdrawal_finalizer_dal;
 pub mod witness_generator_dal;
 
 #[cfg(test)]
@@ -307,4 +321,32 @@ impl<'a> StorageProcessor<'a> {
     pub fn system_dal(&mut self) -> SystemDal<'_, 'a> {
         SystemDal { storage: self }
     }
+
+    pub fn data_availability_dal(&mut self) -> DataAvailabilityDal<'_, 'a> {
+        DataAvailabilityDal { storage: self }
+    }
+
+    pub fn stream_publisher_dal(&mut self) -> StreamPublisherDal<'_, 'a> {
+        StreamPublisherDal { storage: self }
+    }
+
+    pub fn withdrawal_finalizer_dal(&mut self) -> WithdrawalFinalizerDal<'_, 'a> {
+        WithdrawalFinalizerDal { storage: self }
+    }
+
+    pub fn bridge_transfers_dal(&mut self) -> BridgeTransfersDal<'_, 'a> {
+        BridgeTransfersDal { storage: self }
+    }
+
+    pub fn tx_lifecycle_dal(&mut self) -> TxLifecycleDal<'_, 'a> {
+        TxLifecycleDal { storage: self }
+    }
+
+    pub fn online_migrations_dal(&mut self) -> OnlineMigrationsDal<'_, 'a> {
+        OnlineMigrationsDal { storage: self }
+    }
+
+    pub fn webhook_notifier_dal(&mut self) -> WebhookNotifierDal<'_, 'a> {
+        WebhookNotifierDal { storage: self }
+    }
 }
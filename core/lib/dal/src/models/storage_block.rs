@@ -417,6 +417,10 @@ pub struct StorageL1BatchDetails {
     pub l2_fair_gas_price: i64,
     pub bootloader_code_hash: Option<Vec<u8>>,
     pub default_aa_code_hash: Option<Vec<u8>>,
+    pub da_blob_id: Option<String>,
+    pub da_sent_at: Option<NaiveDateTime>,
+    pub da_inclusion_data: Option<Vec<u8>>,
+    pub da_inclusion_confirmed_at: Option<NaiveDateTime>,
 }
 
 impl From<StorageL1BatchDetails> for api::L1BatchDetails {
@@ -461,9 +465,23 @@ impl From<StorageL1BatchDetails> for api::L1BatchDetails {
                 details.default_aa_code_hash,
             ),
         };
+        // Both columns come from the same `LEFT JOIN`ed row, so they are either both present or both absent.
+        let data_availability =
+            details
+                .da_blob_id
+                .zip(details.da_sent_at)
+                .map(|(blob_id, sent_at)| api::DataAvailabilityDetails {
+                    blob_id,
+                    sent_at: DateTime::<Utc>::from_naive_utc_and_offset(sent_at, Utc),
+                    inclusion_data: details.da_inclusion_data.map(Into::into),
+                    inclusion_confirmed_at: details.da_inclusion_confirmed_at.map(|confirmed_at| {
+                        DateTime::<Utc>::from_naive_utc_and_offset(confirmed_at, Utc)
+                    }),
+                });
         api::L1BatchDetails {
             base,
             number: L1BatchNumber(details.number as u32),
+            data_availability,
         }
     }
 }
@@ -479,6 +497,7 @@ pub struct StorageMiniblockHeader {
     // L1 gas price assumed in the corresponding batch
     pub l2_fair_gas_price: i64,
     // L2 gas price assumed in the corresponding batch
+    pub fair_pubdata_price: Option<i64>,
     pub bootloader_code_hash: Option<Vec<u8>>,
     pub default_aa_code_hash: Option<Vec<u8>>,
     pub protocol_version: Option<i32>,
@@ -501,6 +520,7 @@ impl From<StorageMiniblockHeader> for MiniblockHeader {
             base_fee_per_gas: row.base_fee_per_gas.to_u64().unwrap(),
             l1_gas_price: row.l1_gas_price as u64,
             l2_fair_gas_price: row.l2_fair_gas_price as u64,
+            fair_pubdata_price: row.fair_pubdata_price.map(|price| price as u64),
             base_system_contracts_hashes: convert_base_system_contracts_hashes(
                 row.bootloader_code_hash,
                 row.default_aa_code_hash,
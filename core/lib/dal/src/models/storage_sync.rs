@@ -24,6 +24,7 @@ pub struct StorageSyncBlock {
     pub l1_gas_price: i64,
     // L2 gas price assumed in the corresponding batch
     pub l2_fair_gas_price: i64,
+    pub fair_pubdata_price: Option<i64>,
     pub bootloader_code_hash: Option<Vec<u8>>,
     pub default_aa_code_hash: Option<Vec<u8>>,
     pub fee_account_address: Option<Vec<u8>>, // May be None if the block is not yet sealed
@@ -70,6 +71,7 @@ impl StorageSyncBlock {
                 .map(|executed_at| DateTime::<Utc>::from_naive_utc_and_offset(executed_at, Utc)),
             l1_gas_price: self.l1_gas_price as u64,
             l2_fair_gas_price: self.l2_fair_gas_price as u64,
+            fair_pubdata_price: self.fair_pubdata_price.map(|price| price as u64),
             // TODO (SMA-1635): Make these filed non optional in database
             base_system_contracts_hashes: BaseSystemContractsHashes {
                 bootloader: self
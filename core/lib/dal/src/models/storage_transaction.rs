@@ -18,11 +18,11 @@ use zksync_types::{
     api::{TransactionDetails, TransactionStatus},
     fee::Fee,
     l1::{OpProcessingType, PriorityQueueType},
-    Address, Execute, L1TxCommonData, L2ChainId, L2TxCommonData, Nonce, PackedEthSignature,
-    PriorityOpId, Transaction, EIP_1559_TX_TYPE, EIP_2930_TX_TYPE, EIP_712_TX_TYPE, H160, H256,
-    PRIORITY_OPERATION_L2_TX_TYPE, PROTOCOL_UPGRADE_TX_TYPE, U256,
+    Address, Execute, L1BatchNumber, L1TxCommonData, L2ChainId, L2TxCommonData, MiniblockNumber,
+    Nonce, PackedEthSignature, PriorityOpId, Transaction, EIP_1559_TX_TYPE, EIP_2930_TX_TYPE,
+    EIP_712_TX_TYPE, H160, H256, PRIORITY_OPERATION_L2_TX_TYPE, PROTOCOL_UPGRADE_TX_TYPE, U256,
 };
-use zksync_utils::bigdecimal_to_u256;
+use zksync_utils::{bigdecimal_to_u256, h256_to_account_address};
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct StorageTransaction {
@@ -67,6 +67,9 @@ pub struct StorageTransaction {
 
     pub upgrade_id: Option<i32>,
 
+    pub l1_tx_hash: Option<Vec<u8>>,
+    pub l1_deadline_block: Option<i64>,
+
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -140,8 +143,11 @@ impl From<StorageTransaction> for L1TxCommonData {
                 .gas_per_pubdata_limit
                 .map(bigdecimal_to_u256)
                 .unwrap_or_else(|| U256::from(1u32)),
-            deadline_block: 0,
-            eth_hash: Default::default(),
+            deadline_block: tx.l1_deadline_block.unwrap_or_default() as u64,
+            eth_hash: tx
+                .l1_tx_hash
+                .map(|hash| H256::from_slice(&hash))
+                .unwrap_or_default(),
             eth_block: tx.l1_block_number.unwrap_or_default() as u64,
             canonical_tx_hash,
         }
@@ -447,6 +453,7 @@ impl From<StorageTransactionDetails> for TransactionDetails {
         );
         let gas_refunded = U256::from(tx_details.refunded_gas as u32);
         let fee = (gas_limit - gas_refunded) * effective_gas_price;
+        let is_executed = tx_details.miniblock_number.is_some();
 
         let gas_per_pubdata =
             bigdecimal_to_u256(tx_details.gas_per_pubdata_limit.unwrap_or_default());
@@ -468,6 +475,9 @@ impl From<StorageTransactionDetails> for TransactionDetails {
             is_l1_originated: tx_details.is_priority,
             status,
             fee,
+            gas_limit,
+            gas_refunded: is_executed.then_some(gas_refunded),
+            effective_gas_price,
             gas_per_pubdata,
             initiator_address,
             received_at,
@@ -478,6 +488,136 @@ impl From<StorageTransactionDetails> for TransactionDetails {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct StoragePriorityOpStatus {
+    pub hash: Vec<u8>,
+    pub l1_tx_hash: Option<Vec<u8>>,
+    pub priority_op_id: Option<i64>,
+    pub l1_block_number: Option<i32>,
+    pub l1_deadline_block: Option<i64>,
+    pub received_at: NaiveDateTime,
+    pub miniblock_number: Option<i64>,
+    pub l1_batch_number: Option<i64>,
+    pub error: Option<String>,
+    pub queue_position: i64,
+}
+
+impl From<StoragePriorityOpStatus> for api::PriorityOpStatus {
+    fn from(op: StoragePriorityOpStatus) -> Self {
+        let stage = match (op.miniblock_number, op.l1_batch_number) {
+            (None, _) => api::PriorityOpStage::Queued,
+            (Some(_), None) => api::PriorityOpStage::IncludedInMiniblock,
+            (Some(_), Some(_)) => api::PriorityOpStage::IncludedInL1Batch,
+        };
+        let is_queued = stage == api::PriorityOpStage::Queued;
+        let status = if is_queued {
+            None
+        } else if op.error.is_some() {
+            Some(U64::zero())
+        } else {
+            Some(U64::one())
+        };
+
+        Self {
+            l1_tx_hash: H256::from_slice(&op.l1_tx_hash.expect("l1_tx_hash is not set")),
+            l2_tx_hash: H256::from_slice(&op.hash),
+            priority_op_id: op.priority_op_id.expect("priority_op_id is not set") as u64,
+            l1_block_number: op.l1_block_number.unwrap_or_default() as u64,
+            expiration_block: op.l1_deadline_block.unwrap_or_default() as u64,
+            received_at: DateTime::<Utc>::from_naive_utc_and_offset(op.received_at, Utc),
+            stage,
+            queue_position: is_queued.then_some(op.queue_position as u64),
+            miniblock_number: op
+                .miniblock_number
+                .map(|number| MiniblockNumber(number as u32)),
+            l1_batch_number: op
+                .l1_batch_number
+                .map(|number| L1BatchNumber(number as u32)),
+            status,
+            revert_reason: op.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageTransactionReceipt {
+    pub tx_hash: Vec<u8>,
+    pub index_in_block: Option<i32>,
+    pub l1_batch_tx_index: Option<i32>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+    pub effective_gas_price: Option<BigDecimal>,
+    pub initiator_address: Vec<u8>,
+    pub transfer_to: Option<serde_json::Value>,
+    pub execute_contract_address: Option<serde_json::Value>,
+    pub tx_format: Option<i32>,
+    pub refunded_gas: i64,
+    pub gas_limit: Option<BigDecimal>,
+    pub block_hash: Option<Vec<u8>>,
+    pub l1_batch_number: Option<i64>,
+    pub contract_address: Option<Vec<u8>>,
+}
+
+/// Converts the receipt without logs; logs must be loaded separately.
+impl From<StorageTransactionReceipt> for api::TransactionReceipt {
+    fn from(db_row: StorageTransactionReceipt) -> Self {
+        let status = match (db_row.block_number, db_row.error) {
+            (_, Some(_)) => Some(U64::from(0)),
+            (Some(_), None) => Some(U64::from(1)),
+            // tx not executed yet
+            _ => None,
+        };
+        let tx_type = db_row.tx_format.map(U64::from).unwrap_or_default();
+        let transaction_index = db_row.index_in_block.map(U64::from).unwrap_or_default();
+
+        let block_hash = db_row.block_hash.map(|bytes| H256::from_slice(&bytes));
+        api::TransactionReceipt {
+            transaction_hash: H256::from_slice(&db_row.tx_hash),
+            transaction_index,
+            block_hash,
+            block_number: db_row.block_number.map(U64::from),
+            l1_batch_tx_index: db_row.l1_batch_tx_index.map(U64::from),
+            l1_batch_number: db_row.l1_batch_number.map(U64::from),
+            from: H160::from_slice(&db_row.initiator_address),
+            to: db_row
+                .transfer_to
+                .or(db_row.execute_contract_address)
+                .map(|addr| {
+                    serde_json::from_value::<Address>(addr)
+                        .expect("invalid address value in the database")
+                })
+                // For better compatibility with various clients, we never return null.
+                .or_else(|| Some(Address::default())),
+            cumulative_gas_used: Default::default(), // TODO: Should be actually calculated (SMA-1183).
+            gas_used: {
+                let refunded_gas: U256 = db_row.refunded_gas.into();
+                db_row.gas_limit.map(|val| {
+                    let gas_limit = bigdecimal_to_u256(val);
+                    gas_limit - refunded_gas
+                })
+            },
+            effective_gas_price: Some(
+                db_row
+                    .effective_gas_price
+                    .map(bigdecimal_to_u256)
+                    .unwrap_or_default(),
+            ),
+            gas_refunded: db_row.block_number.map(|_| U256::from(db_row.refunded_gas)),
+            contract_address: db_row
+                .contract_address
+                .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
+            logs: vec![],
+            l2_to_l1_logs: vec![],
+            status,
+            root: block_hash,
+            logs_bloom: Default::default(),
+            // Even though the Rust SDK recommends us to supply "None" for legacy transactions
+            // we always supply some number anyway to have the same behaviour as most popular RPCs
+            transaction_type: Some(tx_type),
+        }
+    }
+}
+
 pub fn web3_transaction_select_sql() -> &'static str {
     r#"
          transactions.hash as tx_hash,
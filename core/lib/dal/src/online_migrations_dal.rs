@@ -0,0 +1,621 @@
+//! Online schema migrations for huge tables (e.g., `events` or `storage_logs`), which cannot be altered
+//! by a regular migration without locking the table for a long time.
+//!
+//! An online migration copies rows of the source table into a target table with the new schema, which
+//! must be created beforehand by a regular migration. The migration proceeds in 3 stages:
+//!
+//! 1. **Dual writes.** [`OnlineMigrationsDal::start()`] installs a trigger on the source table that mirrors
+//!    inserted, updated and deleted rows into the target table, and records the range of rows to backfill.
+//! 2. **Backfill.** [`OnlineMigrationsDal::backfill_chunk()`] copies rows existing before the migration
+//!    was started in small chunks; the progress is tracked in the `online_migrations` table, so the backfill
+//!    can be resumed after a restart and run by several workers. Migrations listed in [`ONLINE_MIGRATIONS`]
+//!    are backfilled by the `online_migrations` server component (see [`run_online_migrations()`]).
+//! 3. **Switch-over.** [`OnlineMigrationsDal::switch_over()`] atomically removes the trigger and swaps
+//!    the tables: the source table is renamed to `<source_table>_retired`, and the target table takes its name.
+//!    Constraints, indexes and sequences named after the tables are renamed accordingly, sequences
+//!    of the source table are moved to the target table, and foreign keys referencing the source table
+//!    are re-created to reference the target table. The switch-over must be performed together with deploying
+//!    the code reading the new schema. The retired table should be dropped by a regular migration afterwards.
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{instrument::InstrumentExt, ConnectionPool, StorageProcessor};
+
+/// Online migrations backfilled by the `online_migrations` server component. A migration should be removed
+/// from the list once it's switched over and its retired table is dropped.
+pub const ONLINE_MIGRATIONS: &[OnlineMigration] = &[];
+
+/// Definition of an online migration. Table and column names are interpolated into SQL queries
+/// and must be plain identifiers.
+#[derive(Debug, Clone, Copy)]
+pub struct OnlineMigration {
+    /// Unique name of the migration.
+    pub name: &'static str,
+    pub source_table: &'static str,
+    pub target_table: &'static str,
+    /// Integer column of the source table used to split the backfill into chunks, e.g. `miniblock_number`.
+    /// Should be indexed.
+    pub chunk_column: &'static str,
+    /// Columns uniquely identifying a row; they must have the same names in the source and target tables,
+    /// and the target table must have a unique constraint on them.
+    pub key_columns: &'static [&'static str],
+    /// Columns of the target table together with SQL expressions computing them from a source row
+    /// aliased as `src`, e.g. `("value_len", "length(src.value)")`.
+    pub columns: &'static [(&'static str, &'static str)],
+}
+
+impl OnlineMigration {
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut identifiers = vec![
+            self.name,
+            self.source_table,
+            self.target_table,
+            self.chunk_column,
+        ];
+        identifiers.extend_from_slice(self.key_columns);
+        identifiers.extend(self.columns.iter().map(|(name, _)| *name));
+        for identifier in identifiers {
+            let is_valid = !identifier.is_empty()
+                && !identifier.starts_with(|ch: char| ch.is_ascii_digit())
+                && identifier
+                    .chars()
+                    .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_');
+            anyhow::ensure!(is_valid, "`{identifier}` is not a valid identifier");
+        }
+        anyhow::ensure!(
+            !self.key_columns.is_empty(),
+            "online migration must have key columns"
+        );
+        anyhow::ensure!(
+            !self.columns.is_empty(),
+            "online migration must have target columns"
+        );
+        Ok(())
+    }
+
+    fn trigger_name(&self) -> String {
+        format!("online_migration_{}", self.name)
+    }
+
+    fn retired_table(&self) -> String {
+        format!("{}_retired", self.source_table)
+    }
+
+    fn target_columns(&self) -> String {
+        let names: Vec<_> = self.columns.iter().map(|(name, _)| *name).collect();
+        names.join(", ")
+    }
+
+    fn column_expressions(&self) -> String {
+        let expressions: Vec<_> = self.columns.iter().map(|(_, expr)| *expr).collect();
+        expressions.join(", ")
+    }
+
+    fn dual_write_statements(&self) -> [String; 2] {
+        let trigger = self.trigger_name();
+        let keys = self.key_columns.join(", ");
+        let old_keys: Vec<_> = self
+            .key_columns
+            .iter()
+            .map(|column| format!("OLD.{column}"))
+            .collect();
+        let old_keys = old_keys.join(", ");
+        let function = format!(
+            "CREATE OR REPLACE FUNCTION {trigger}() RETURNS TRIGGER AS $$ \
+             BEGIN \
+                 IF TG_OP IN ('UPDATE', 'DELETE') THEN \
+                     DELETE FROM {target} WHERE ({keys}) = ({old_keys}); \
+                 END IF; \
+                 IF TG_OP IN ('INSERT', 'UPDATE') THEN \
+                     INSERT INTO {target} ({columns}) \
+                     SELECT {expressions} FROM (SELECT (NEW).*) AS src \
+                     ON CONFLICT ({keys}) DO NOTHING; \
+                 END IF; \
+                 RETURN NULL; \
+             END; \
+             $$ LANGUAGE plpgsql",
+            target = self.target_table,
+            columns = self.target_columns(),
+            expressions = self.column_expressions()
+        );
+        let trigger = format!(
+            "CREATE TRIGGER {trigger} AFTER INSERT OR UPDATE OR DELETE ON {source} \
+             FOR EACH ROW EXECUTE FUNCTION {trigger}()",
+            source = self.source_table
+        );
+        [function, trigger]
+    }
+
+    /// Returns statements swapping the source and target tables. Must be executed in a single DB transaction.
+    fn switch_over_statements(&self) -> Vec<String> {
+        let source = self.source_table;
+        let target = self.target_table;
+        let retired = self.retired_table();
+        let trigger = self.trigger_name();
+        vec![
+            format!("LOCK TABLE {source}, {target} IN ACCESS EXCLUSIVE MODE"),
+            format!("DROP TRIGGER {trigger} ON {source}"),
+            format!("DROP FUNCTION {trigger}()"),
+            // Sequences must survive retiring the source table, so that values generated after the switch-over
+            // don't collide with the copied ones. Sequences of the target table for the same columns are dropped.
+            format!(
+                "DO $$ \
+                 DECLARE \
+                     owned RECORD; \
+                     target_seq_name TEXT; \
+                 BEGIN \
+                     FOR owned IN \
+                         SELECT seq.oid::regclass::text AS seq_name, col.attname AS column_name \
+                         FROM pg_depend AS dep \
+                         JOIN pg_class AS seq ON seq.oid = dep.objid \
+                         JOIN pg_attribute AS col \
+                             ON col.attrelid = dep.refobjid AND col.attnum = dep.refobjsubid \
+                         WHERE dep.refobjid = '{source}'::regclass AND dep.deptype = 'a' AND seq.relkind = 'S' \
+                     LOOP \
+                         EXECUTE format('ALTER TABLE {source} ALTER COLUMN %I DROP DEFAULT', owned.column_name); \
+                         EXECUTE format('ALTER TABLE {target} ALTER COLUMN %I SET DEFAULT nextval(%L::regclass)', \
+                             owned.column_name, owned.seq_name); \
+                         FOR target_seq_name IN \
+                             SELECT seq.oid::regclass::text FROM pg_depend AS dep \
+                             JOIN pg_class AS seq ON seq.oid = dep.objid \
+                             JOIN pg_attribute AS col \
+                                 ON col.attrelid = dep.refobjid AND col.attnum = dep.refobjsubid \
+                             WHERE dep.refobjid = '{target}'::regclass AND dep.deptype = 'a' \
+                                 AND seq.relkind = 'S' AND col.attname = owned.column_name \
+                         LOOP \
+                             EXECUTE format('DROP SEQUENCE %s', target_seq_name); \
+                         END LOOP; \
+                         EXECUTE format('ALTER SEQUENCE %s OWNED BY {target}.%I', owned.seq_name, owned.column_name); \
+                     END LOOP; \
+                 END $$"
+            ),
+            // The retired table doesn't receive writes, so its foreign keys would only obstruct
+            // deleting referenced rows (e.g., on miniblock rollback).
+            format!(
+                "DO $$ \
+                 DECLARE \
+                     constraint_name TEXT; \
+                 BEGIN \
+                     FOR constraint_name IN \
+                         SELECT conname FROM pg_constraint \
+                         WHERE conrelid = '{source}'::regclass AND contype = 'f' \
+                     LOOP \
+                         EXECUTE format('ALTER TABLE {source} DROP CONSTRAINT %I', constraint_name); \
+                     END LOOP; \
+                 END $$"
+            ),
+            format!("ALTER TABLE {source} RENAME TO {retired}"),
+            Self::rename_objects_statement(&retired, source, &retired),
+            format!("ALTER TABLE {target} RENAME TO {source}"),
+            Self::rename_objects_statement(source, target, source),
+            // Foreign keys are bound to tables rather than table names, so they need to be re-created.
+            // Keys are re-created as `NOT VALID` to not scan referencing tables; the referenced rows
+            // are copied together with all other rows.
+            format!(
+                "DO $$ \
+                 DECLARE \
+                     fk RECORD; \
+                 BEGIN \
+                     FOR fk IN \
+                         SELECT conname, conrelid::regclass::text AS table_name, convalidated, \
+                             pg_get_constraintdef(oid) AS definition \
+                         FROM pg_constraint \
+                         WHERE confrelid = '{retired}'::regclass AND contype = 'f' \
+                     LOOP \
+                         EXECUTE format('ALTER TABLE %s DROP CONSTRAINT %I', fk.table_name, fk.conname); \
+                         EXECUTE format('ALTER TABLE %s ADD CONSTRAINT %I %s%s', fk.table_name, fk.conname, \
+                             replace(fk.definition, 'REFERENCES {retired}(', 'REFERENCES {source}('), \
+                             CASE WHEN fk.convalidated THEN ' NOT VALID' ELSE '' END); \
+                     END LOOP; \
+                 END $$"
+            ),
+        ]
+    }
+
+    /// Returns a statement renaming constraints, indexes and sequences of `table` named with `old_prefix`
+    /// (e.g., `{old_prefix}_pkey` or `{old_prefix}_miniblock_number_fkey` following Postgres naming conventions)
+    /// so that they use `new_prefix` instead. Other objects are not renamed. Indexes backing constraints
+    /// are renamed together with the constraints.
+    fn rename_objects_statement(table: &str, old_prefix: &str, new_prefix: &str) -> String {
+        let suffix_start = old_prefix.len() + 1;
+        format!(
+            "DO $$ \
+             DECLARE \
+                 object_name TEXT; \
+             BEGIN \
+                 FOR object_name IN \
+                     SELECT conname FROM pg_constraint \
+                     WHERE conrelid = '{table}'::regclass AND starts_with(conname, '{old_prefix}_') \
+                 LOOP \
+                     EXECUTE format('ALTER TABLE {table} RENAME CONSTRAINT %I TO %I', \
+                         object_name, '{new_prefix}' || substr(object_name, {suffix_start})); \
+                 END LOOP; \
+                 FOR object_name IN \
+                     SELECT idx.relname FROM pg_index \
+                     JOIN pg_class AS idx ON idx.oid = pg_index.indexrelid \
+                     WHERE pg_index.indrelid = '{table}'::regclass AND starts_with(idx.relname, '{old_prefix}_') \
+                         AND NOT EXISTS ( \
+                             SELECT 1 FROM pg_constraint \
+                             WHERE conrelid = '{table}'::regclass AND conindid = idx.oid \
+                         ) \
+                 LOOP \
+                     EXECUTE format('ALTER INDEX %I RENAME TO %I', \
+                         object_name, '{new_prefix}' || substr(object_name, {suffix_start})); \
+                 END LOOP; \
+                 FOR object_name IN \
+                     SELECT seq.relname FROM pg_depend AS dep \
+                     JOIN pg_class AS seq ON seq.oid = dep.objid \
+                     WHERE dep.refobjid = '{table}'::regclass AND dep.deptype = 'a' AND seq.relkind = 'S' \
+                         AND starts_with(seq.relname, '{old_prefix}_') \
+                 LOOP \
+                     EXECUTE format('ALTER SEQUENCE %I RENAME TO %I', \
+                         object_name, '{new_prefix}' || substr(object_name, {suffix_start})); \
+                 END LOOP; \
+             END $$"
+        )
+    }
+}
+
+/// Stage of an online migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnlineMigrationStage {
+    /// Writes are mirrored to the target table, and pre-existing rows are being copied.
+    Backfilling,
+    /// All pre-existing rows are copied; the migration is ready for the switch-over.
+    Backfilled,
+    /// The target table has replaced the source table.
+    SwitchedOver,
+}
+
+impl OnlineMigrationStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Backfilling => "backfilling",
+            Self::Backfilled => "backfilled",
+            Self::SwitchedOver => "switched_over",
+        }
+    }
+}
+
+impl fmt::Display for OnlineMigrationStage {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OnlineMigrationStage {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "backfilling" => Self::Backfilling,
+            "backfilled" => Self::Backfilled,
+            "switched_over" => Self::SwitchedOver,
+            _ => anyhow::bail!("unknown online migration stage: `{s}`"),
+        })
+    }
+}
+
+/// Progress of an online migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnlineMigrationProgress {
+    pub stage: OnlineMigrationStage,
+    /// Exclusive upper bound of the chunk column values copied so far.
+    pub backfilled_up_to: i64,
+    /// Exclusive upper bound of the chunk column values to copy. Rows with greater values
+    /// are written to the target table by the dual-write trigger.
+    pub backfill_end: i64,
+}
+
+#[derive(Debug)]
+pub struct OnlineMigrationsDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl OnlineMigrationsDal<'_, '_> {
+    /// Returns the progress of the migration with the specified name, or `None` if the migration
+    /// was not started.
+    pub async fn get_progress(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<OnlineMigrationProgress>> {
+        let row = sqlx::query!(
+            "SELECT stage, backfilled_up_to, backfill_end FROM online_migrations WHERE name = $1",
+            name
+        )
+        .instrument("get_progress")
+        .with_arg("name", &name)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        Ok(Some(OnlineMigrationProgress {
+            stage: row.stage.parse()?,
+            backfilled_up_to: row.backfilled_up_to,
+            backfill_end: row.backfill_end,
+        }))
+    }
+
+    /// Starts the migration by installing the dual-write trigger on the source table and recording
+    /// the range of rows to backfill. Does nothing if the migration is already started.
+    pub async fn start(
+        &mut self,
+        migration: &OnlineMigration,
+    ) -> anyhow::Result<OnlineMigrationProgress> {
+        migration.validate()?;
+        let mut transaction = self.storage.start_transaction().await?;
+        let progress = transaction
+            .online_migrations_dal()
+            .get_progress(migration.name)
+            .await?;
+        if let Some(progress) = progress {
+            return Ok(progress);
+        }
+
+        for statement in &migration.dual_write_statements() {
+            sqlx::query(statement)
+                .instrument("start#create_trigger")
+                .with_arg("name", &migration.name)
+                .execute(transaction.conn())
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed installing dual-write trigger for `{}`",
+                        migration.name
+                    )
+                })?;
+        }
+        // Creating the trigger waits for transactions writing to the source table to finish,
+        // so all rows beyond the backfill range will be written by the trigger.
+        let range_query = format!(
+            "SELECT MIN({column})::BIGINT, MAX({column})::BIGINT FROM {source}",
+            column = migration.chunk_column,
+            source = migration.source_table
+        );
+        let (min, max): (Option<i64>, Option<i64>) = sqlx::query_as(&range_query)
+            .fetch_one(transaction.conn())
+            .await?;
+        let backfill_start = min.unwrap_or(0);
+        let backfill_end = max.map_or(0, |max| max + 1);
+        let stage = if backfill_start < backfill_end {
+            OnlineMigrationStage::Backfilling
+        } else {
+            OnlineMigrationStage::Backfilled
+        };
+
+        sqlx::query!(
+            "INSERT INTO online_migrations \
+            (name, source_table, target_table, stage, backfilled_up_to, backfill_end, created_at, updated_at) \
+            VALUES ($1, $2, $3, $4, $5, $6, now(), now())",
+            migration.name,
+            migration.source_table,
+            migration.target_table,
+            stage.as_str(),
+            backfill_start,
+            backfill_end
+        )
+        .instrument("start#insert")
+        .with_arg("name", &migration.name)
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Started online migration `{}` from `{}` to `{}`; backfilling range {backfill_start}..{backfill_end}",
+            migration.name,
+            migration.source_table,
+            migration.target_table
+        );
+        Ok(OnlineMigrationProgress {
+            stage,
+            backfilled_up_to: backfill_start,
+            backfill_end,
+        })
+    }
+
+    /// Copies the next chunk of rows into the target table. Copied rows are locked until the chunk is committed,
+    /// so that rows concurrently deleted or updated in the source table are not resurrected or left stale
+    /// in the target table; other writes to the source table are not blocked. Concurrent calls
+    /// for the same migration are serialized.
+    pub async fn backfill_chunk(
+        &mut self,
+        migration: &OnlineMigration,
+        chunk_size: i64,
+    ) -> anyhow::Result<OnlineMigrationProgress> {
+        anyhow::ensure!(chunk_size > 0, "chunk size must be positive");
+        migration.validate()?;
+        let mut transaction = self.storage.start_transaction().await?;
+        let row = sqlx::query!(
+            "SELECT stage, backfilled_up_to, backfill_end FROM online_migrations \
+            WHERE name = $1 FOR UPDATE",
+            migration.name
+        )
+        .instrument("backfill_chunk#get_progress")
+        .with_arg("name", &migration.name)
+        .fetch_optional(transaction.conn())
+        .await?
+        .with_context(|| format!("online migration `{}` is not started", migration.name))?;
+
+        let mut progress = OnlineMigrationProgress {
+            stage: row.stage.parse()?,
+            backfilled_up_to: row.backfilled_up_to,
+            backfill_end: row.backfill_end,
+        };
+        if progress.stage != OnlineMigrationStage::Backfilling {
+            return Ok(progress);
+        }
+
+        let chunk_start = progress.backfilled_up_to;
+        let chunk_end = chunk_start
+            .saturating_add(chunk_size)
+            .min(progress.backfill_end);
+        // `FOR SHARE` makes concurrent deletes and updates of the copied rows wait until the chunk is committed,
+        // so that the dual-write trigger applies them to the copied rows. Conversely, rows deleted or updated
+        // by uncommitted transactions are re-checked once these transactions commit.
+        let copy_statement = format!(
+            "INSERT INTO {target} ({columns}) \
+             SELECT {expressions} FROM {source} AS src \
+             WHERE src.{chunk_column} >= $1 AND src.{chunk_column} < $2 \
+             FOR SHARE \
+             ON CONFLICT ({keys}) DO NOTHING",
+            target = migration.target_table,
+            columns = migration.target_columns(),
+            expressions = migration.column_expressions(),
+            source = migration.source_table,
+            chunk_column = migration.chunk_column,
+            keys = migration.key_columns.join(", ")
+        );
+        let copied_rows = sqlx::query(&copy_statement)
+            .bind(chunk_start)
+            .bind(chunk_end)
+            .instrument("backfill_chunk#copy")
+            .with_arg("name", &migration.name)
+            .with_arg("chunk_start", &chunk_start)
+            .with_arg("chunk_end", &chunk_end)
+            .report_latency()
+            .execute(transaction.conn())
+            .await?
+            .rows_affected();
+
+        progress.backfilled_up_to = chunk_end;
+        if chunk_end == progress.backfill_end {
+            progress.stage = OnlineMigrationStage::Backfilled;
+        }
+        sqlx::query!(
+            "UPDATE online_migrations \
+            SET stage = $2, backfilled_up_to = $3, updated_at = now() \
+            WHERE name = $1",
+            migration.name,
+            progress.stage.as_str(),
+            progress.backfilled_up_to
+        )
+        .instrument("backfill_chunk#update_progress")
+        .with_arg("name", &migration.name)
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+
+        tracing::debug!(
+            "Copied {copied_rows} rows in range {chunk_start}..{chunk_end} for online migration `{}`",
+            migration.name
+        );
+        Ok(progress)
+    }
+
+    /// Atomically replaces the source table with the target table. The migration must be fully backfilled.
+    pub async fn switch_over(&mut self, migration: &OnlineMigration) -> anyhow::Result<()> {
+        migration.validate()?;
+        let mut transaction = self.storage.start_transaction().await?;
+        let stage = sqlx::query!(
+            "SELECT stage FROM online_migrations WHERE name = $1 FOR UPDATE",
+            migration.name
+        )
+        .instrument("switch_over#get_stage")
+        .with_arg("name", &migration.name)
+        .fetch_optional(transaction.conn())
+        .await?
+        .with_context(|| format!("online migration `{}` is not started", migration.name))?
+        .stage;
+        let stage: OnlineMigrationStage = stage.parse()?;
+        anyhow::ensure!(
+            stage == OnlineMigrationStage::Backfilled,
+            "online migration `{}` cannot be switched over at stage `{stage}`",
+            migration.name
+        );
+
+        for statement in &migration.switch_over_statements() {
+            sqlx::query(statement)
+                .instrument("switch_over#swap_tables")
+                .with_arg("name", &migration.name)
+                .execute(transaction.conn())
+                .await
+                .with_context(|| format!("failed executing `{statement}`"))?;
+        }
+
+        sqlx::query!(
+            "UPDATE online_migrations SET stage = $2, updated_at = now() WHERE name = $1",
+            migration.name,
+            OnlineMigrationStage::SwitchedOver.as_str()
+        )
+        .instrument("switch_over#update_stage")
+        .with_arg("name", &migration.name)
+        .execute(transaction.conn())
+        .await?;
+        transaction.commit().await?;
+
+        tracing::info!(
+            "Switched over online migration `{}`; `{}` is retired as `{}`",
+            migration.name,
+            migration.source_table,
+            migration.retired_table()
+        );
+        Ok(())
+    }
+}
+
+/// Starts the migration (if necessary) and backfills it chunk by chunk, pausing between chunks
+/// to limit the load on the database. Returns once the migration is backfilled or a stop signal is received.
+/// The switch-over is not performed.
+pub async fn run_backfill(
+    pool: &ConnectionPool,
+    migration: &OnlineMigration,
+    chunk_size: i64,
+    pause: Duration,
+    stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<OnlineMigrationProgress> {
+    let mut storage = pool.access_storage_tagged("online_migrations").await?;
+    let mut progress = storage.online_migrations_dal().start(migration).await?;
+    while progress.stage == OnlineMigrationStage::Backfilling {
+        if *stop_receiver.borrow() {
+            tracing::info!(
+                "Stop signal received, stopping backfill for online migration `{}`",
+                migration.name
+            );
+            break;
+        }
+        progress = storage
+            .online_migrations_dal()
+            .backfill_chunk(migration, chunk_size)
+            .await?;
+        tracing::info!(
+            "Online migration `{}` progress: {progress:?}",
+            migration.name
+        );
+        tokio::time::sleep(pause).await;
+    }
+    Ok(progress)
+}
+
+/// Backfills the specified migrations one by one, pausing between chunks, and then waits for a stop signal.
+/// Switch-overs are not performed since they must be coordinated with deploying the code reading
+/// the new schemas.
+pub async fn run_online_migrations(
+    pool: ConnectionPool,
+    migrations: &[OnlineMigration],
+    chunk_size: i64,
+    pause: Duration,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    for migration in migrations {
+        let progress = run_backfill(&pool, migration, chunk_size, pause, stop_receiver.clone())
+            .await
+            .with_context(|| format!("failed backfilling online migration `{}`", migration.name))?;
+        if *stop_receiver.borrow() {
+            return Ok(());
+        }
+        tracing::info!(
+            "Online migration `{}` is at stage `{}`",
+            migration.name,
+            progress.stage
+        );
+    }
+
+    // Finished components stop the server, so the component should only finish on the stop signal.
+    stop_receiver.changed().await.ok();
+    Ok(())
+}
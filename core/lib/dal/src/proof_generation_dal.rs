@@ -109,4 +109,66 @@ impl ProofGenerationDal<'_, '_> {
         .then_some(())
         .ok_or(sqlx::Error::RowNotFound)
     }
+
+    /// Checks whether the specified L1 batch was handed out to a prover and its proof is not yet received.
+    pub async fn is_picked_by_prover(
+        &mut self,
+        block_number: L1BatchNumber,
+    ) -> Result<bool, SqlxError> {
+        let row = sqlx::query!(
+            "SELECT EXISTS ( \
+                SELECT 1 FROM proof_generation_details \
+                WHERE l1_batch_number = $1 AND status = $2 \
+             ) AS \"exists!\"",
+            block_number.0 as i64,
+            ProofGenerationJobStatus::PickedByProver.to_string(),
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.exists)
+    }
+
+    /// Returns the last L1 batch such that it and all preceding L1 batches tracked by proof generation
+    /// have a generated (or skipped) proof. Returns `None` if no L1 batches are tracked.
+    pub async fn get_last_proven_l1_batch(&mut self) -> Result<Option<L1BatchNumber>, SqlxError> {
+        let row = sqlx::query!(
+            "SELECT COALESCE( \
+                (SELECT MIN(l1_batch_number) - 1 FROM proof_generation_details \
+                 WHERE status NOT IN ($1, $2)), \
+                (SELECT MAX(l1_batch_number) FROM proof_generation_details) \
+             ) AS number",
+            ProofGenerationJobStatus::Generated.to_string(),
+            ProofGenerationJobStatus::Skipped.to_string(),
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(row.number.map(|number| L1BatchNumber(number.max(0) as u32)))
+    }
+
+    /// Returns numbers of L1 batches greater than `after` that are proven, but not yet executed on L1.
+    pub async fn get_proven_unexecuted_l1_batches(
+        &mut self,
+        after: L1BatchNumber,
+        limit: usize,
+    ) -> Vec<L1BatchNumber> {
+        sqlx::query!(
+            "SELECT proof_generation_details.l1_batch_number \
+             FROM proof_generation_details \
+             JOIN l1_batches ON l1_batches.number = proof_generation_details.l1_batch_number \
+             WHERE proof_generation_details.status = $1 \
+             AND l1_batches.eth_execute_tx_id IS NULL \
+             AND proof_generation_details.l1_batch_number > $2 \
+             ORDER BY proof_generation_details.l1_batch_number ASC \
+             LIMIT $3",
+            ProofGenerationJobStatus::Generated.to_string(),
+            after.0 as i64,
+            limit as i64,
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| L1BatchNumber(row.l1_batch_number as u32))
+        .collect()
+    }
 }
@@ -108,6 +108,32 @@ impl StorageDal<'_, '_> {
         .collect()
     }
 
+    /// Returns a chunk of factory deps from miniblocks with number less than or equal to `last_miniblock`.
+    /// Factory deps are sorted by the bytecode hash; only deps with hash greater than `after_hash` are returned.
+    pub async fn get_factory_deps_chunk(
+        &mut self,
+        last_miniblock: MiniblockNumber,
+        after_hash: Option<H256>,
+        chunk_size: usize,
+    ) -> Vec<(H256, Vec<u8>)> {
+        let after_hash = after_hash.as_ref().map_or(&[][..], H256::as_bytes);
+        sqlx::query!(
+            "SELECT bytecode_hash, bytecode FROM factory_deps \
+            WHERE miniblock_number <= $1 AND bytecode_hash > $2 \
+            ORDER BY bytecode_hash \
+            LIMIT $3",
+            last_miniblock.0 as i64,
+            after_hash,
+            chunk_size as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (H256::from_slice(&row.bytecode_hash), row.bytecode))
+        .collect()
+    }
+
     /// Returns bytecode hashes for factory deps from miniblocks with number strictly greater
     /// than `block_number`.
     pub async fn get_factory_deps_for_revert(
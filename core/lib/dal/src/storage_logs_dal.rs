@@ -3,7 +3,7 @@ use sqlx::Row;
 
 use std::{collections::HashMap, time::Instant};
 
-use crate::StorageProcessor;
+use crate::{instrument::InstrumentExt, StorageProcessor};
 use zksync_types::{
     get_code_key, AccountTreeId, Address, L1BatchNumber, MiniblockNumber, StorageKey, StorageLog,
     FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H256,
@@ -212,17 +212,19 @@ impl StorageLogsDal<'_, '_> {
 
     /// Returns latest values for all [`StorageKey`]s written to in the specified L1 batch
     /// judging by storage logs (i.e., not taking deduplication logic into account).
+    /// Repeated writes to the same slot are deduplicated on the Postgres side, so that only
+    /// the latest write for each slot is loaded.
     pub async fn get_touched_slots_for_l1_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
     ) -> HashMap<StorageKey, H256> {
         let rows = sqlx::query!(
-            "SELECT address, key, value \
+            "SELECT DISTINCT ON (hashed_key) address, key, value \
             FROM storage_logs \
             WHERE miniblock_number BETWEEN \
                 (SELECT MIN(number) FROM miniblocks WHERE l1_batch_number = $1) \
                 AND (SELECT MAX(number) FROM miniblocks WHERE l1_batch_number = $1) \
-            ORDER BY miniblock_number, operation_number",
+            ORDER BY hashed_key, miniblock_number DESC, operation_number DESC",
             l1_batch_number.0 as i64
         )
         .fetch_all(self.storage.conn())
@@ -239,6 +241,48 @@ impl StorageLogsDal<'_, '_> {
         touched_slots.collect()
     }
 
+    /// Returns a chunk of the VM state as of the end of the specified L1 batch, which must be sealed.
+    /// The state is returned as `(hashed_key, value)` entries sorted by the hashed key; only keys
+    /// with `hashed_key > after_hashed_key` are returned. Deduplication is taken into account, i.e.,
+    /// only keys present in `initial_writes` are returned.
+    ///
+    /// This method is used to bulk-load the state into a secondary storage.
+    pub async fn get_state_chunk(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        last_miniblock: MiniblockNumber,
+        after_hashed_key: Option<H256>,
+        chunk_size: usize,
+    ) -> Vec<(H256, H256)> {
+        let after_hashed_key = after_hashed_key.as_ref().map_or(&[][..], H256::as_bytes);
+        let rows = sqlx::query!(
+            "SELECT iw.hashed_key, \
+                (SELECT value FROM storage_logs \
+                WHERE hashed_key = iw.hashed_key AND miniblock_number <= $2 \
+                ORDER BY miniblock_number DESC, operation_number DESC LIMIT 1) as \"value!\" \
+            FROM initial_writes iw \
+            WHERE iw.l1_batch_number <= $1 AND iw.hashed_key > $3 \
+            ORDER BY iw.hashed_key \
+            LIMIT $4",
+            l1_batch_number.0 as i64,
+            last_miniblock.0 as i64,
+            after_hashed_key,
+            chunk_size as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+
+        rows.into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.hashed_key),
+                    H256::from_slice(&row.value),
+                )
+            })
+            .collect()
+    }
+
     /// Returns (hashed) storage keys and the corresponding values that need to be applied to a storage
     /// in order to revert it to the specified L1 batch. Deduplication is taken into account.
     pub async fn get_storage_logs_for_revert(
@@ -381,6 +425,17 @@ impl StorageLogsDal<'_, '_> {
         hashed_keys: &[H256],
         miniblock_number: MiniblockNumber,
     ) -> HashMap<H256, Option<H256>> {
+        self.try_get_storage_values(hashed_keys, miniblock_number)
+            .await
+            .unwrap()
+    }
+
+    /// Fallible version of [`Self::get_storage_values()`].
+    pub async fn try_get_storage_values(
+        &mut self,
+        hashed_keys: &[H256],
+        miniblock_number: MiniblockNumber,
+    ) -> sqlx::Result<HashMap<H256, Option<H256>>> {
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
 
         let rows = sqlx::query!(
@@ -393,16 +448,16 @@ impl StorageLogsDal<'_, '_> {
             miniblock_number.0 as i64
         )
         .fetch_all(self.storage.conn())
-        .await
-        .unwrap();
+        .await?;
 
-        rows.into_iter()
+        Ok(rows
+            .into_iter()
             .map(|row| {
                 let key = H256::from_slice(&row.hashed_key);
                 let value = row.value.map(|value| H256::from_slice(&value));
                 (key, value)
             })
-            .collect()
+            .collect())
     }
 
     /// Resolves hashed keys into storage keys ((address, key) tuples).
@@ -432,6 +487,38 @@ impl StorageLogsDal<'_, '_> {
         .collect()
     }
 
+    /// Returns `(tx_hash, key, value)` tuples for storage writes to the specified contract
+    /// in the specified miniblock, in the order of their execution.
+    pub async fn get_miniblock_logs_for_address(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+        address: Address,
+    ) -> sqlx::Result<Vec<(H256, H256, H256)>> {
+        let rows = sqlx::query!(
+            "SELECT tx_hash, key, value FROM storage_logs \
+            WHERE miniblock_number = $1 AND address = $2 \
+            ORDER BY operation_number",
+            miniblock_number.0 as i64,
+            address.as_bytes()
+        )
+        .instrument("get_miniblock_logs_for_address")
+        .with_arg("miniblock_number", &miniblock_number)
+        .with_arg("address", &address)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    H256::from_slice(&row.tx_hash),
+                    H256::from_slice(&row.key),
+                    H256::from_slice(&row.value),
+                )
+            })
+            .collect())
+    }
+
     pub async fn get_miniblock_storage_logs(
         &mut self,
         miniblock_number: MiniblockNumber,
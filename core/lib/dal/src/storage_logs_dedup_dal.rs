@@ -1,6 +1,6 @@
 use crate::StorageProcessor;
 use sqlx::types::chrono::Utc;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use zksync_types::{AccountTreeId, Address, L1BatchNumber, LogQuery, StorageKey, H256};
 use zksync_utils::u256_to_h256;
 
@@ -14,6 +14,19 @@ impl StorageLogsDedupDal<'_, '_> {
         &mut self,
         l1_batch_number: L1BatchNumber,
         read_logs: &[LogQuery],
+    ) {
+        let read_keys: Vec<_> = read_logs
+            .iter()
+            .map(|log| StorageKey::new(AccountTreeId::new(log.address), u256_to_h256(log.key)))
+            .collect();
+        self.insert_protective_read_keys(l1_batch_number, &read_keys)
+            .await;
+    }
+
+    pub async fn insert_protective_read_keys(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        read_keys: &[StorageKey],
     ) {
         let mut copy = self
             .storage
@@ -27,9 +40,9 @@ impl StorageLogsDedupDal<'_, '_> {
 
         let mut bytes: Vec<u8> = Vec::new();
         let now = Utc::now().naive_utc().to_string();
-        for log in read_logs.iter() {
-            let address_str = format!("\\\\x{}", hex::encode(log.address.0));
-            let key_str = format!("\\\\x{}", hex::encode(u256_to_h256(log.key).0));
+        for key in read_keys {
+            let address_str = format!("\\\\x{}", hex::encode(key.address().0));
+            let key_str = format!("\\\\x{}", hex::encode(key.key().0));
             let row = format!(
                 "{}|{}|{}|{}|{}\n",
                 l1_batch_number, address_str, key_str, now, now
@@ -100,6 +113,22 @@ impl StorageLogsDedupDal<'_, '_> {
             .map(|max| max as u64)
     }
 
+    /// Returns the maximum enumeration index assigned in the specified L1 batch or before it.
+    pub async fn max_enumeration_index_for_l1_batch(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Option<u64> {
+        sqlx::query!(
+            "SELECT MAX(index) as \"max?\" FROM initial_writes WHERE l1_batch_number <= $1",
+            l1_batch_number.0 as i64
+        )
+        .fetch_one(self.storage.conn())
+        .await
+        .unwrap()
+        .max
+        .map(|max| max as u64)
+    }
+
     pub async fn initial_writes_for_batch(
         &mut self,
         l1_batch_number: L1BatchNumber,
@@ -118,6 +147,43 @@ impl StorageLogsDedupDal<'_, '_> {
         .collect()
     }
 
+    /// Returns enumeration indices for the specified `hashed_keys`. Keys that were never written to
+    /// are not present in the returned map.
+    pub async fn get_enumeration_indices(&mut self, hashed_keys: &[H256]) -> HashMap<H256, u64> {
+        let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
+        sqlx::query!(
+            "SELECT hashed_key, index FROM initial_writes \
+            WHERE hashed_key = ANY($1)",
+            &hashed_keys as &[&[u8]],
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (H256::from_slice(&row.hashed_key), row.index as u64))
+        .collect()
+    }
+
+    /// Returns hashed keys for the specified enumeration `indices`. Indices that are not assigned
+    /// are not present in the returned map.
+    pub async fn get_hashed_keys_for_enumeration_indices(
+        &mut self,
+        indices: &[u64],
+    ) -> HashMap<u64, H256> {
+        let indices: Vec<_> = indices.iter().map(|&index| index as i64).collect();
+        sqlx::query!(
+            "SELECT hashed_key, index FROM initial_writes \
+            WHERE index = ANY($1)",
+            &indices,
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.index as u64, H256::from_slice(&row.hashed_key)))
+        .collect()
+    }
+
     /// Returns `hashed_keys` that are both present in the input and in `initial_writes` table.
     pub async fn filter_written_slots(&mut self, hashed_keys: &[H256]) -> HashSet<H256> {
         let hashed_keys: Vec<_> = hashed_keys.iter().map(H256::as_bytes).collect();
@@ -0,0 +1,72 @@
+use zksync_types::{MiniblockNumber, H256};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+/// Last miniblock published to a stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PublishedMiniblock {
+    pub number: MiniblockNumber,
+    /// Miniblock hash; `None` if the progress was persisted before hashes were recorded.
+    pub hash: Option<H256>,
+}
+
+/// Persists progress of the miniblock streaming publisher, so that it can resume after a restart
+/// without losing miniblocks.
+#[derive(Debug)]
+pub struct StreamPublisherDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl StreamPublisherDal<'_, '_> {
+    /// Returns the last miniblock published to the specified stream, or `None` if nothing
+    /// was published to it yet.
+    pub async fn get_last_published_miniblock(
+        &mut self,
+        stream: &str,
+    ) -> sqlx::Result<Option<PublishedMiniblock>> {
+        let row = sqlx::query!(
+            "SELECT last_published_miniblock, last_published_miniblock_hash \
+            FROM stream_publisher_cursors WHERE stream = $1",
+            stream
+        )
+        .instrument("get_last_published_miniblock")
+        .with_arg("stream", &stream)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| PublishedMiniblock {
+            number: MiniblockNumber(row.last_published_miniblock as u32),
+            hash: row
+                .last_published_miniblock_hash
+                .map(|hash| H256::from_slice(&hash)),
+        }))
+    }
+
+    /// Sets the last miniblock published to the specified stream.
+    pub async fn set_last_published_miniblock(
+        &mut self,
+        stream: &str,
+        number: MiniblockNumber,
+        hash: H256,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "INSERT INTO stream_publisher_cursors \
+                (stream, last_published_miniblock, last_published_miniblock_hash, created_at, updated_at) \
+            VALUES ($1, $2, $3, now(), now()) \
+            ON CONFLICT (stream) DO UPDATE \
+            SET last_published_miniblock = excluded.last_published_miniblock, \
+                last_published_miniblock_hash = excluded.last_published_miniblock_hash, \
+                updated_at = now()",
+            stream,
+            number.0 as i64,
+            hash.as_bytes()
+        )
+        .instrument("set_last_published_miniblock")
+        .with_arg("stream", &stream)
+        .with_arg("number", &number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+}
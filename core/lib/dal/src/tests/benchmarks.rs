@@ -0,0 +1,132 @@
+//! Latency benchmarks for hot DAL queries. The benchmarks are ignored by default;
+//! run them explicitly with `RUST_LOG=info cargo test -p zksync_dal benchmark -- --ignored`.
+
+use std::time::{Duration, Instant};
+
+use db_test_macro::db_test;
+use zksync_types::{AccountTreeId, Address, MiniblockNumber, StorageKey, StorageLog, H256};
+
+use super::{create_miniblock_header, mock_l2_transaction, mock_tx_execution_metrics};
+use crate::{connection::ConnectionPool, transactions_dal::L2TxSubmissionResult};
+
+const ITERATIONS: u32 = 200;
+
+#[derive(Debug, Default)]
+struct LatencyReport {
+    entries: Vec<(&'static str, Duration)>,
+}
+
+impl LatencyReport {
+    fn add(&mut self, query: &'static str, total_latency: Duration) {
+        self.entries.push((query, total_latency / ITERATIONS));
+    }
+
+    fn report(&self) {
+        for (query, latency) in &self.entries {
+            tracing::info!(
+                query,
+                iterations = ITERATIONS,
+                "Mean latency of DAL query: {latency:?}"
+            );
+        }
+    }
+}
+
+#[db_test(dal_crate)]
+#[ignore] // Benchmark; see the module docs
+async fn benchmark_hot_queries(connection_pool: ConnectionPool) {
+    let _guard = vlog::ObservabilityBuilder::new().build();
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    let mut report = LatencyReport::default();
+
+    // Transaction insertion.
+    let started_at = Instant::now();
+    for _ in 0..ITERATIONS {
+        let result = storage
+            .transactions_dal()
+            .insert_transaction_l2(mock_l2_transaction(), mock_tx_execution_metrics())
+            .await;
+        assert_eq!(result, L2TxSubmissionResult::Added);
+    }
+    report.add("insert_transaction_l2", started_at.elapsed());
+
+    // Miniblock headers.
+    let started_at = Instant::now();
+    for number in 1..=ITERATIONS {
+        let header = create_miniblock_header(number);
+        storage
+            .blocks_dal()
+            .insert_miniblock(&header)
+            .await
+            .unwrap();
+    }
+    report.add("insert_miniblock", started_at.elapsed());
+
+    let started_at = Instant::now();
+    for number in 1..=ITERATIONS {
+        let header = storage
+            .blocks_dal()
+            .get_miniblock_header(MiniblockNumber(number))
+            .await
+            .unwrap();
+        assert!(header.is_some());
+    }
+    report.add("get_miniblock_header", started_at.elapsed());
+
+    // Storage logs.
+    let keys: Vec<_> = (0..ITERATIONS)
+        .map(|i| {
+            StorageKey::new(
+                AccountTreeId::new(Address::repeat_byte(1)),
+                H256::from_low_u64_be(i.into()),
+            )
+        })
+        .collect();
+    let logs: Vec<_> = keys
+        .iter()
+        .map(|&key| StorageLog::new_write_log(key, H256::repeat_byte(0xff)))
+        .collect();
+    let logs = [(H256::zero(), logs)];
+    storage
+        .storage_logs_dal()
+        .insert_storage_logs(MiniblockNumber(1), &logs)
+        .await;
+    storage.storage_dal().apply_storage_logs(&logs).await;
+
+    let started_at = Instant::now();
+    for key in &keys {
+        let value = storage.storage_dal().get_by_key(key).await;
+        assert_eq!(value, Some(H256::repeat_byte(0xff)));
+    }
+    report.add("get_by_key", started_at.elapsed());
+
+    let started_at = Instant::now();
+    for key in &keys {
+        let value = storage
+            .storage_web3_dal()
+            .get_historical_value_unchecked(key, MiniblockNumber(ITERATIONS))
+            .await
+            .unwrap();
+        assert_eq!(value, H256::repeat_byte(0xff));
+    }
+    report.add("get_historical_value_unchecked", started_at.elapsed());
+
+    let started_at = Instant::now();
+    for key in &keys {
+        storage
+            .storage_web3_dal()
+            .get_l1_batch_number_for_initial_write(key)
+            .await
+            .unwrap();
+    }
+    report.add(
+        "get_l1_batch_number_for_initial_write",
+        started_at.elapsed(),
+    );
+
+    report.report();
+}
@@ -2,27 +2,36 @@ use std::fs;
 use std::time::Duration;
 
 use db_test_macro::db_test;
+use tokio::sync::watch;
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_types::{
+    api::{self, BridgeTransfer, BridgeTransferKind, BridgeTransferStatus},
     block::{miniblock_hash, L1BatchHeader, MiniblockHeader},
     fee::{Fee, TransactionExecutionMetrics},
     helpers::unix_timestamp_ms,
     l1::{L1Tx, OpProcessingType, PriorityQueueType},
     l2::L2Tx,
     proofs::AggregationRound,
+    tokens::{TokenInfo, TokenMetadata, TokenProvenance, ETHEREUM_ADDRESS},
     tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
+    withdrawals::{Withdrawal, WithdrawalStatus},
     Address, Execute, L1BatchNumber, L1BlockNumber, L1TxCommonData, L2ChainId, MiniblockNumber,
     PriorityOpId, ProtocolVersion, ProtocolVersionId, H160, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
 
 use crate::blocks_dal::BlocksDal;
 use crate::connection::ConnectionPool;
+use crate::online_migrations_dal::{run_online_migrations, OnlineMigration, OnlineMigrationStage};
 use crate::protocol_versions_dal::ProtocolVersionsDal;
 use crate::prover_dal::{GetProverJobsParams, ProverDal};
 use crate::transactions_dal::L2TxSubmissionResult;
 use crate::transactions_dal::TransactionsDal;
 use crate::transactions_web3_dal::TransactionsWeb3Dal;
+use crate::withdrawal_finalizer_dal::FinalizationTx;
 use crate::witness_generator_dal::WitnessGeneratorDal;
+use crate::StorageProcessor;
+
+mod benchmarks;
 
 const DEFAULT_GAS_PER_PUBDATA: u32 = 100;
 
@@ -40,6 +49,7 @@ pub(crate) fn create_miniblock_header(number: u32) -> MiniblockHeader {
         base_fee_per_gas: 100,
         l1_gas_price: 100,
         l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
         base_system_contracts_hashes: BaseSystemContractsHashes::default(),
         protocol_version: Some(ProtocolVersionId::default()),
         virtual_blocks: 1,
@@ -254,6 +264,65 @@ async fn remove_stuck_txs(connection_pool: ConnectionPool) {
         .unwrap();
 }
 
+#[db_test(dal_crate)]
+async fn gas_refunds_in_transaction_receipts_and_details(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    ProtocolVersionsDal { storage }
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+
+    let executed_tx = mock_l2_transaction();
+    let pending_tx = mock_l2_transaction();
+    let mut transactions_dal = TransactionsDal { storage };
+    for tx in [&executed_tx, &pending_tx] {
+        transactions_dal
+            .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+            .await;
+    }
+    BlocksDal { storage }
+        .insert_miniblock(&create_miniblock_header(1))
+        .await
+        .unwrap();
+    let mut execution_result = mock_execution_result(executed_tx.clone());
+    execution_result.refunded_gas = 1_000;
+    TransactionsDal { storage }
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &[execution_result], U256::from(1))
+        .await;
+
+    let mut transactions_web3_dal = TransactionsWeb3Dal { storage };
+    let receipt = transactions_web3_dal
+        .get_transaction_receipt(executed_tx.hash())
+        .await
+        .unwrap()
+        .expect("no receipt for executed transaction");
+    assert_eq!(receipt.gas_refunded, Some(1_000.into()));
+    assert_eq!(receipt.gas_used, Some(999_000.into()));
+    let receipt = transactions_web3_dal
+        .get_transaction_receipt(pending_tx.hash())
+        .await
+        .unwrap()
+        .expect("no receipt for pending transaction");
+    assert_eq!(receipt.gas_refunded, None);
+
+    let details = transactions_web3_dal
+        .get_transaction_details(executed_tx.hash())
+        .await
+        .unwrap()
+        .expect("no details for executed transaction");
+    assert_eq!(details.gas_limit, 1_000_000.into());
+    assert_eq!(details.gas_refunded, Some(1_000.into()));
+    // The effective gas price is capped by the base fee since the priority fee is zero.
+    assert_eq!(details.effective_gas_price, 1.into());
+    assert_eq!(details.fee, 999_000.into());
+    let details = transactions_web3_dal
+        .get_transaction_details(pending_tx.hash())
+        .await
+        .unwrap()
+        .expect("no details for pending transaction");
+    assert_eq!(details.gas_limit, 1_000_000.into());
+    assert_eq!(details.gas_refunded, None);
+}
+
 fn create_circuits() -> Vec<(&'static str, String)> {
     vec![
         ("Main VM", "1_0_Main VM_BasicCircuits.bin".to_owned()),
@@ -645,6 +714,92 @@ async fn test_move_scheduler_jobs_from_waiting_to_queued(connection_pool: Connec
     assert_eq!(l1_batch_number, job.unwrap().block_number);
 }
 
+#[db_test(dal_crate)]
+async fn data_availability_workflow(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    for number in [1, 2] {
+        let header = L1BatchHeader::new(
+            L1BatchNumber(number),
+            0,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        );
+        storage
+            .blocks_dal()
+            .insert_l1_batch(&header, &[], Default::default())
+            .await
+            .unwrap();
+    }
+
+    let mut dal = storage.data_availability_dal();
+    assert!(dal
+        .get_l1_batch_da(L1BatchNumber(1))
+        .await
+        .unwrap()
+        .is_none());
+    dal.insert_l1_batch_da(L1BatchNumber(1), "blob-1")
+        .await
+        .unwrap();
+    dal.insert_l1_batch_da(L1BatchNumber(2), "blob-2")
+        .await
+        .unwrap();
+    // Repeated dispatch with the same blob ID is idempotent, but with a different one is an error.
+    dal.insert_l1_batch_da(L1BatchNumber(1), "blob-1")
+        .await
+        .unwrap();
+    dal.insert_l1_batch_da(L1BatchNumber(1), "other")
+        .await
+        .unwrap_err();
+
+    let pending = dal.get_l1_batches_pending_inclusion(10).await.unwrap();
+    assert_eq!(
+        pending,
+        [
+            (L1BatchNumber(1), "blob-1".to_owned()),
+            (L1BatchNumber(2), "blob-2".to_owned())
+        ]
+    );
+
+    dal.save_l1_batch_inclusion_data(L1BatchNumber(2), b"proof")
+        .await
+        .unwrap();
+    dal.save_l1_batch_inclusion_data(L1BatchNumber(2), b"proof")
+        .await
+        .unwrap();
+    dal.save_l1_batch_inclusion_data(L1BatchNumber(2), b"other")
+        .await
+        .unwrap_err();
+    let included = dal
+        .get_l1_batches_with_confirmed_inclusion(L1BatchNumber(1)..=L1BatchNumber(2))
+        .await
+        .unwrap();
+    assert_eq!(included, [L1BatchNumber(2)].into());
+
+    let details = dal
+        .get_l1_batch_da(L1BatchNumber(2))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(details.blob_id, "blob-2");
+    assert_eq!(details.inclusion_data.unwrap().0, b"proof");
+    assert!(details.inclusion_confirmed_at.is_some());
+
+    let batch_details = storage
+        .blocks_web3_dal()
+        .get_l1_batch_details(L1BatchNumber(1))
+        .await
+        .unwrap()
+        .unwrap();
+    let da_details = batch_details.data_availability.unwrap();
+    assert_eq!(da_details.blob_id, "blob-1");
+    assert_eq!(da_details.inclusion_data, None);
+}
+
 fn get_default_prover_jobs_params(l1_batch_number: L1BatchNumber) -> GetProverJobsParams {
     GetProverJobsParams {
         statuses: None,
@@ -663,3 +818,833 @@ fn get_sample_proof() -> Vec<u8> {
     fs::read(format!("{}/etc/prover-test-data/proof.bin", zksync_home))
         .expect("Failed reading test proof file")
 }
+
+#[db_test(dal_crate)]
+async fn priority_op_statuses(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    let tx = mock_l1_execute();
+    let l1_tx_hash = tx.common_data.eth_hash;
+    // The same L1 transaction emits another priority operation.
+    let mut second_tx = mock_l1_execute();
+    second_tx.common_data.serial_id = PriorityOpId(2);
+    second_tx.common_data.canonical_tx_hash = H256::from_low_u64_be(2);
+    second_tx.common_data.eth_hash = l1_tx_hash;
+    for tx in [&second_tx, &tx] {
+        storage
+            .transactions_dal()
+            .insert_transaction_l1(tx.clone(), L1BlockNumber(1))
+            .await;
+    }
+
+    let statuses = storage
+        .transactions_web3_dal()
+        .get_priority_op_statuses(l1_tx_hash)
+        .await
+        .unwrap();
+    assert_eq!(statuses.len(), 2);
+    let status = &statuses[0];
+    assert_eq!(status.l2_tx_hash, tx.hash());
+    assert_eq!(status.priority_op_id, 1);
+    assert_eq!(status.l1_block_number, 1);
+    assert_eq!(status.expiration_block, 100_000);
+    assert_eq!(status.stage, api::PriorityOpStage::Queued);
+    assert_eq!(status.queue_position, Some(0));
+    assert_eq!(status.status, None);
+    let second_status = &statuses[1];
+    assert_eq!(second_status.l2_tx_hash, second_tx.hash());
+    assert_eq!(second_status.priority_op_id, 2);
+    assert_eq!(second_status.queue_position, Some(1));
+
+    let missing_statuses = storage
+        .transactions_web3_dal()
+        .get_priority_op_statuses(H256::repeat_byte(1))
+        .await
+        .unwrap();
+    assert!(missing_statuses.is_empty());
+}
+
+#[db_test(dal_crate)]
+async fn withdrawal_finalizer_workflow(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    let mut dal = storage.withdrawal_finalizer_dal();
+    assert_eq!(dal.get_last_processed_l1_batch().await.unwrap(), None);
+
+    let withdrawals: Vec<_> = (0..3)
+        .map(|i| Withdrawal {
+            l1_batch_number: L1BatchNumber(1),
+            l2_message_index: i,
+            l2_tx_number_in_batch: i as u16,
+            l2_tx_hash: H256::repeat_byte(i as u8),
+            l1_receiver: Address::repeat_byte(1),
+            l1_token: Address::zero(),
+            amount: U256::from(1_000) * (i + 1),
+            message: vec![i as u8; 56],
+            merkle_proof: vec![H256::repeat_byte(0xff); 3],
+        })
+        .collect();
+    dal.insert_withdrawals(L1BatchNumber(1), &withdrawals)
+        .await
+        .unwrap();
+    dal.insert_withdrawals(L1BatchNumber(2), &[]).await.unwrap();
+    assert_eq!(
+        dal.get_last_processed_l1_batch().await.unwrap(),
+        Some(L1BatchNumber(2))
+    );
+    assert_eq!(dal.get_pending_withdrawals(10).await.unwrap(), withdrawals);
+    assert_eq!(
+        dal.get_pending_withdrawals(1).await.unwrap(),
+        withdrawals[..1]
+    );
+
+    assert_eq!(dal.get_next_nonce().await.unwrap(), None);
+    let finalization_tx = |hash: H256, nonce: u64, max_fee_per_gas: u64| FinalizationTx {
+        hash,
+        nonce,
+        raw_tx: hash.as_bytes().to_vec(),
+        max_fee_per_gas,
+        max_priority_fee_per_gas: 1,
+        created_at_block: max_fee_per_gas,
+    };
+    let tx = finalization_tx(H256::repeat_byte(0xaa), 5, 10);
+    dal.save_finalization_tx(L1BatchNumber(1), 0, &tx)
+        .await
+        .unwrap();
+    let tx = finalization_tx(H256::repeat_byte(0xbb), 6, 10);
+    dal.save_finalization_tx(L1BatchNumber(1), 1, &tx)
+        .await
+        .unwrap();
+    assert_eq!(dal.get_next_nonce().await.unwrap(), Some(7));
+    let unsent_txs = dal.get_unsent_finalization_txs().await.unwrap();
+    let unsent_hashes: Vec<_> = unsent_txs.iter().map(|(hash, _)| *hash).collect();
+    assert_eq!(
+        unsent_hashes,
+        [H256::repeat_byte(0xaa), H256::repeat_byte(0xbb)]
+    );
+    assert_eq!(unsent_txs[0].1, H256::repeat_byte(0xaa).as_bytes());
+    dal.mark_finalization_tx_as_sent(H256::repeat_byte(0xaa), 10)
+        .await
+        .unwrap();
+    dal.mark_finalization_tx_as_sent(H256::repeat_byte(0xbb), 10)
+        .await
+        .unwrap();
+    assert!(dal.get_unsent_finalization_txs().await.unwrap().is_empty());
+
+    // Bump fees for the second withdrawal.
+    let bumped_tx = finalization_tx(H256::repeat_byte(0xcc), 6, 20);
+    dal.save_finalization_tx(L1BatchNumber(1), 1, &bumped_tx)
+        .await
+        .unwrap();
+    let unsent_txs = dal.get_unsent_finalization_txs().await.unwrap();
+    assert_eq!(unsent_txs.len(), 1);
+    assert_eq!(unsent_txs[0].0, bumped_tx.hash);
+    let withdrawal = dal.get_withdrawal(L1BatchNumber(1), 1).await.unwrap();
+    assert_eq!(withdrawal.as_ref(), Some(&withdrawals[1]));
+    dal.set_withdrawal_outcome(
+        L1BatchNumber(1),
+        2,
+        WithdrawalStatus::FinalizedExternally,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(dal.get_pending_withdrawals(10).await.unwrap().is_empty());
+    let sent = dal.get_sent_withdrawals().await.unwrap();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].l2_message_index, 0);
+    assert_eq!(sent[0].finalize_tx_hash, H256::repeat_byte(0xaa));
+    assert_eq!(sent[0].tx_hashes, [H256::repeat_byte(0xaa)]);
+    assert_eq!(sent[1].l2_message_index, 1);
+    assert_eq!(sent[1].finalize_tx_hash, bumped_tx.hash);
+    assert_eq!(sent[1].nonce, 6);
+    assert_eq!(sent[1].max_fee_per_gas, 20);
+    assert_eq!(sent[1].created_at_block, 20);
+    assert_eq!(sent[1].tx_hashes, [bumped_tx.hash, H256::repeat_byte(0xbb)]);
+
+    dal.set_withdrawal_outcome(
+        L1BatchNumber(1),
+        0,
+        WithdrawalStatus::Finalized,
+        Some(100_000),
+        Some(1_000_000.into()),
+    )
+    .await
+    .unwrap();
+    dal.set_withdrawal_outcome(
+        L1BatchNumber(1),
+        1,
+        WithdrawalStatus::Failed,
+        Some(50_000),
+        Some(500_000.into()),
+    )
+    .await
+    .unwrap();
+    assert!(dal.get_sent_withdrawals().await.unwrap().is_empty());
+    assert_eq!(dal.get_total_fee_paid().await.unwrap(), 1_500_000.into());
+}
+
+#[db_test(dal_crate)]
+async fn token_provenance_and_metadata_refresh(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    let token_info = |l2_address: Address| TokenInfo {
+        l1_address: l2_address,
+        l2_address,
+        metadata: TokenMetadata::default(l2_address),
+    };
+    let eth_token = token_info(ETHEREUM_ADDRESS);
+    let token = Address::repeat_byte(1);
+    let other_token = Address::repeat_byte(2);
+    let provenance = TokenProvenance {
+        bridge_address: Address::repeat_byte(0xbb),
+        added_in_miniblock: MiniblockNumber(5),
+    };
+    let mut dal = storage.tokens_dal();
+    dal.add_tokens(vec![eth_token], None).await;
+    dal.add_tokens(
+        vec![token_info(token), token_info(other_token)],
+        Some(provenance),
+    )
+    .await;
+
+    assert_eq!(dal.get_token_provenance(&ETHEREUM_ADDRESS).await, None);
+    assert_eq!(dal.get_token_provenance(&token).await, Some(provenance));
+    assert_eq!(
+        dal.get_tokens_for_metadata_refresh(10).await,
+        [token, other_token]
+    );
+
+    let metadata = TokenMetadata {
+        name: "Test token".to_owned(),
+        symbol: "TEST".to_owned(),
+        decimals: 6,
+    };
+    dal.refresh_token_metadata(&token, Some(&metadata)).await;
+    // Tokens that were never refreshed go first.
+    assert_eq!(dal.get_tokens_for_metadata_refresh(1).await, [other_token]);
+    dal.refresh_token_metadata(&other_token, None).await;
+    let stored_metadata = storage
+        .tokens_web3_dal()
+        .get_token_metadata(&token)
+        .await
+        .unwrap();
+    assert_eq!(stored_metadata, Some(metadata));
+    let stored_metadata = storage
+        .tokens_web3_dal()
+        .get_token_metadata(&other_token)
+        .await
+        .unwrap();
+    assert_eq!(stored_metadata, Some(TokenMetadata::default(other_token)));
+}
+
+#[db_test(dal_crate)]
+async fn bridge_transfers_workflow(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    let header = L1BatchHeader::new(
+        L1BatchNumber(1),
+        0,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    storage
+        .blocks_dal()
+        .insert_l1_batch(&header, &[], Default::default())
+        .await
+        .unwrap();
+
+    let account = Address::repeat_byte(1);
+    let deposit = BridgeTransfer {
+        kind: BridgeTransferKind::Deposit,
+        status: BridgeTransferStatus::Finalized,
+        sender: Address::repeat_byte(2),
+        receiver: account,
+        l1_token: Address::zero(),
+        l2_token: Some(Address::zero()),
+        amount: 1_000.into(),
+        l1_tx_hash: Some(H256::repeat_byte(0xaa)),
+        l2_tx_hash: H256::repeat_byte(1),
+        miniblock_number: MiniblockNumber(1),
+        l1_batch_number: L1BatchNumber(1),
+        l2_message_index: None,
+    };
+    let withdrawal = BridgeTransfer {
+        kind: BridgeTransferKind::Withdrawal,
+        status: BridgeTransferStatus::Initiated,
+        sender: account,
+        receiver: Address::repeat_byte(3),
+        l1_tx_hash: None,
+        l2_tx_hash: H256::repeat_byte(2),
+        l2_message_index: Some(0),
+        ..deposit.clone()
+    };
+
+    let mut dal = storage.bridge_transfers_dal();
+    assert_eq!(dal.get_last_indexed_l1_batch().await.unwrap(), None);
+    dal.insert_l1_batch_transfers(L1BatchNumber(1), &[deposit.clone(), withdrawal.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        dal.get_last_indexed_l1_batch().await.unwrap(),
+        Some(L1BatchNumber(1))
+    );
+
+    let transfers = dal.get_transfers_for_address(account, 0, 10).await.unwrap();
+    assert_eq!(transfers, [withdrawal.clone(), deposit.clone()]);
+    let transfers = dal.get_transfers_for_address(account, 1, 10).await.unwrap();
+    assert_eq!(transfers, [deposit.clone()]);
+    let transfers = dal
+        .get_transfers_for_address(Address::repeat_byte(2), 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(transfers, [deposit]);
+
+    // Withdrawals finalized on L1 should be reported as finalized.
+    let mut dal = storage.bridge_transfers_dal();
+    let initiated = dal
+        .get_initiated_withdrawals(withdrawal.receiver, withdrawal.l1_token, withdrawal.amount)
+        .await
+        .unwrap();
+    assert_eq!(initiated, [(L1BatchNumber(1), 0)]);
+    let initiated = dal
+        .get_initiated_withdrawals(withdrawal.receiver, withdrawal.l1_token, 1.into())
+        .await
+        .unwrap();
+    assert!(initiated.is_empty());
+
+    let finalize_tx_hash = H256::repeat_byte(0xbb);
+    dal.mark_withdrawal_as_finalized(L1BatchNumber(1), 0, finalize_tx_hash)
+        .await
+        .unwrap();
+    let initiated = dal
+        .get_initiated_withdrawals(withdrawal.receiver, withdrawal.l1_token, withdrawal.amount)
+        .await
+        .unwrap();
+    assert!(initiated.is_empty());
+    let transfers = dal.get_transfers_for_address(account, 0, 1).await.unwrap();
+    let expected_withdrawal = BridgeTransfer {
+        status: BridgeTransferStatus::Finalized,
+        l1_tx_hash: Some(finalize_tx_hash),
+        ..withdrawal
+    };
+    assert_eq!(transfers, [expected_withdrawal]);
+
+    assert_eq!(dal.get_last_processed_l1_block().await.unwrap(), None);
+    dal.set_last_processed_l1_block(100).await.unwrap();
+    dal.set_last_processed_l1_block(200).await.unwrap();
+    assert_eq!(dal.get_last_processed_l1_block().await.unwrap(), Some(200));
+
+    // Unknown values in Postgres should result in an error rather than a panic.
+    sqlx::query("UPDATE bridge_transfers SET kind = 'unknown' WHERE l2_message_index IS NULL")
+        .execute(storage.conn())
+        .await
+        .unwrap();
+    let err = storage
+        .bridge_transfers_dal()
+        .get_transfers_for_address(account, 0, 10)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unknown bridge transfer kind"),
+        "{err}"
+    );
+
+    // Transfers should be removed together with the reverted L1 batch.
+    storage
+        .blocks_dal()
+        .delete_l1_batches(L1BatchNumber(0))
+        .await
+        .unwrap();
+    let mut dal = storage.bridge_transfers_dal();
+    assert_eq!(dal.get_last_indexed_l1_batch().await.unwrap(), None);
+    assert!(dal
+        .get_transfers_for_address(account, 0, 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[db_test(dal_crate)]
+async fn transaction_journey(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    let tx = mock_l2_transaction();
+    let tx_hash = tx.hash();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await;
+
+    let mut dal = storage.tx_lifecycle_dal();
+    assert!(dal
+        .get_transaction_journey(H256::repeat_byte(1))
+        .await
+        .unwrap()
+        .is_none());
+    dal.record_validated(tx_hash).await.unwrap();
+    dal.record_entered_mempool(&[tx_hash, H256::repeat_byte(1)])
+        .await
+        .unwrap();
+    let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+    let milestones: Vec<_> = journey.milestones.iter().map(|m| m.milestone).collect();
+    assert_eq!(
+        milestones,
+        [
+            api::TransactionMilestone::Received,
+            api::TransactionMilestone::Validated,
+            api::TransactionMilestone::EnteredMempool,
+        ]
+    );
+    assert_eq!(journey.miniblock_number, None);
+
+    storage
+        .blocks_dal()
+        .insert_miniblock(&create_miniblock_header(1))
+        .await
+        .unwrap();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(
+            MiniblockNumber(1),
+            &[mock_execution_result(tx)],
+            U256::from(1),
+        )
+        .await;
+    let mut dal = storage.tx_lifecycle_dal();
+    dal.record_miniblock_inclusion(MiniblockNumber(1))
+        .await
+        .unwrap();
+    let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+    assert_eq!(journey.miniblock_number, Some(MiniblockNumber(1)));
+    assert_eq!(journey.l1_batch_number, None);
+    let last_milestone = journey.milestones.last().unwrap();
+    assert_eq!(
+        last_milestone.milestone,
+        api::TransactionMilestone::IncludedInMiniblock
+    );
+
+    // Re-validating the transaction (e.g., after it was replaced) should reset its events.
+    dal.record_validated(tx_hash).await.unwrap();
+    let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+    assert_eq!(journey.milestones.len(), 2);
+}
+
+#[db_test(dal_crate)]
+async fn revalidating_transaction(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    let tx = mock_l2_transaction();
+    let tx_hash = tx.hash();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(tx, mock_tx_execution_metrics())
+        .await;
+
+    let mut dal = storage.tx_lifecycle_dal();
+    dal.record_validated(tx_hash).await.unwrap();
+    dal.record_entered_mempool(&[tx_hash]).await.unwrap();
+    // Recording the same event again must not fail.
+    dal.record_entered_mempool(&[tx_hash]).await.unwrap();
+    let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+    assert_eq!(journey.milestones.len(), 3);
+    let validated_at = journey.milestones[1].timestamp;
+
+    dal.record_validated(tx_hash).await.unwrap();
+    let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+    let milestones: Vec<_> = journey.milestones.iter().map(|m| m.milestone).collect();
+    assert_eq!(
+        milestones,
+        [
+            api::TransactionMilestone::Received,
+            api::TransactionMilestone::Validated,
+        ]
+    );
+    assert!(journey.milestones[1].timestamp >= validated_at);
+}
+
+#[db_test(dal_crate)]
+async fn pruning_transaction_lifecycle_events(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    let mut tx_hashes = vec![];
+    for _ in 0..3 {
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx, mock_tx_execution_metrics())
+            .await;
+        storage
+            .tx_lifecycle_dal()
+            .record_validated(tx_hash)
+            .await
+            .unwrap();
+        tx_hashes.push(tx_hash);
+    }
+    storage
+        .tx_lifecycle_dal()
+        .record_entered_mempool(&tx_hashes)
+        .await
+        .unwrap();
+
+    // Make events for the first 2 transactions old.
+    let old_hashes: Vec<_> = tx_hashes[..2].iter().map(H256::as_bytes).collect();
+    sqlx::query(
+        "UPDATE transaction_lifecycle_events SET occurred_at = now() - interval '2 hours' \
+        WHERE tx_hash = ANY($1)",
+    )
+    .bind(&old_hashes)
+    .execute(storage.conn())
+    .await
+    .unwrap();
+
+    let retention = Duration::from_secs(3_600);
+    let mut dal = storage.tx_lifecycle_dal();
+    let removed_events = dal.prune_events(retention, 3).await.unwrap();
+    assert_eq!(removed_events, 3);
+    let removed_events = dal.prune_events(retention, 3).await.unwrap();
+    assert_eq!(removed_events, 1);
+    let removed_events = dal.prune_events(retention, 3).await.unwrap();
+    assert_eq!(removed_events, 0);
+
+    for (i, &tx_hash) in tx_hashes.iter().enumerate() {
+        let journey = dal.get_transaction_journey(tx_hash).await.unwrap().unwrap();
+        let expected_milestones = if i < 2 { 1 } else { 3 };
+        assert_eq!(journey.milestones.len(), expected_milestones, "{journey:?}");
+    }
+}
+
+#[db_test(dal_crate)]
+async fn recent_fee_statistics(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    storage
+        .protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    for number in 1..=2 {
+        let mut header = create_miniblock_header(number);
+        header.base_fee_per_gas = 100 * u64::from(number);
+        storage
+            .blocks_dal()
+            .insert_miniblock(&header)
+            .await
+            .unwrap();
+    }
+    let tx = mock_l2_transaction();
+    storage
+        .transactions_dal()
+        .insert_transaction_l2(tx.clone(), mock_tx_execution_metrics())
+        .await;
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(
+            MiniblockNumber(1),
+            &[mock_execution_result(tx)],
+            U256::from(100),
+        )
+        .await;
+
+    let mut dal = storage.blocks_web3_dal();
+    let max_base_fee = dal
+        .get_max_base_fee_per_gas(MiniblockNumber(2), 10)
+        .await
+        .unwrap();
+    assert_eq!(max_base_fee, Some(200.into()));
+    let max_base_fee = dal
+        .get_max_base_fee_per_gas(MiniblockNumber(1), 1)
+        .await
+        .unwrap();
+    assert_eq!(max_base_fee, Some(100.into()));
+
+    let priority_fee = dal
+        .get_effective_priority_fee_percentile(MiniblockNumber(2), 2, 0.6)
+        .await
+        .unwrap();
+    assert_eq!(priority_fee, Some(U256::zero()));
+    // The last miniblock contains no transactions.
+    let priority_fee = dal
+        .get_effective_priority_fee_percentile(MiniblockNumber(2), 1, 0.6)
+        .await
+        .unwrap();
+    assert_eq!(priority_fee, None);
+}
+
+const TEST_ONLINE_MIGRATION: OnlineMigration = OnlineMigration {
+    name: "test_values_len",
+    source_table: "online_migration_test_values",
+    target_table: "online_migration_test_values_new",
+    chunk_column: "id",
+    key_columns: &["id"],
+    columns: &[
+        ("id", "src.id"),
+        ("value", "src.value"),
+        ("value_len", "length(src.value)"),
+    ],
+};
+
+async fn execute_statements(storage: &mut StorageProcessor<'_>, statements: &[&str]) {
+    for statement in statements {
+        sqlx::query(statement)
+            .execute(storage.conn())
+            .await
+            .unwrap();
+    }
+}
+
+async fn create_online_migration_test_tables(storage: &mut StorageProcessor<'_>) {
+    execute_statements(
+        storage,
+        &[
+            "CREATE TABLE online_migration_test_values (id BIGSERIAL PRIMARY KEY, value BYTEA NOT NULL)",
+            "CREATE INDEX online_migration_test_values_value_idx ON online_migration_test_values (value)",
+            "CREATE TABLE online_migration_test_values_new \
+             (id BIGSERIAL PRIMARY KEY, value BYTEA NOT NULL, value_len INT NOT NULL)",
+            "CREATE INDEX online_migration_test_values_new_value_len_idx \
+             ON online_migration_test_values_new (value_len)",
+            "CREATE TABLE online_migration_test_refs \
+             (value_id BIGINT NOT NULL REFERENCES online_migration_test_values (id))",
+            "INSERT INTO online_migration_test_values (id, value) \
+             SELECT i, repeat('x', i)::BYTEA FROM generate_series(1, 5) AS i",
+            "SELECT setval('online_migration_test_values_id_seq', 10)",
+            "INSERT INTO online_migration_test_refs (value_id) VALUES (1)",
+        ],
+    )
+    .await;
+}
+
+async fn online_migration_test_rows(
+    storage: &mut StorageProcessor<'_>,
+) -> Vec<(i64, Vec<u8>, i32)> {
+    sqlx::query_as("SELECT id, value, value_len FROM online_migration_test_values ORDER BY id")
+        .fetch_all(storage.conn())
+        .await
+        .unwrap()
+}
+
+async fn table_constraints(storage: &mut StorageProcessor<'_>, table: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT conname::TEXT FROM pg_constraint WHERE conrelid = $1::regclass ORDER BY conname",
+    )
+    .bind(table)
+    .fetch_all(storage.conn())
+    .await
+    .unwrap()
+}
+
+async fn table_indexes(storage: &mut StorageProcessor<'_>, table: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT indexname::TEXT FROM pg_indexes WHERE tablename = $1 ORDER BY indexname",
+    )
+    .bind(table)
+    .fetch_all(storage.conn())
+    .await
+    .unwrap()
+}
+
+#[db_test(dal_crate)]
+async fn online_migration_lifecycle(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    create_online_migration_test_tables(storage).await;
+
+    let mut dal = storage.online_migrations_dal();
+    assert_eq!(
+        dal.get_progress(TEST_ONLINE_MIGRATION.name).await.unwrap(),
+        None
+    );
+    let progress = dal.start(&TEST_ONLINE_MIGRATION).await.unwrap();
+    assert_eq!(progress.stage, OnlineMigrationStage::Backfilling);
+    assert_eq!((progress.backfilled_up_to, progress.backfill_end), (1, 6));
+    // Restarting the migration is a no-op.
+    assert_eq!(dal.start(&TEST_ONLINE_MIGRATION).await.unwrap(), progress);
+    dal.switch_over(&TEST_ONLINE_MIGRATION).await.unwrap_err();
+
+    // Writes during the backfill are mirrored to the target table.
+    execute_statements(
+        storage,
+        &[
+            "INSERT INTO online_migration_test_values (id, value) VALUES (10, 'new'::BYTEA)",
+            "UPDATE online_migration_test_values SET value = 'updated'::BYTEA WHERE id = 4",
+            "DELETE FROM online_migration_test_values WHERE id = 5",
+        ],
+    )
+    .await;
+
+    let mut dal = storage.online_migrations_dal();
+    let progress = dal.backfill_chunk(&TEST_ONLINE_MIGRATION, 3).await.unwrap();
+    assert_eq!(progress.stage, OnlineMigrationStage::Backfilling);
+    assert_eq!(progress.backfilled_up_to, 4);
+    let progress = dal.backfill_chunk(&TEST_ONLINE_MIGRATION, 3).await.unwrap();
+    assert_eq!(progress.stage, OnlineMigrationStage::Backfilled);
+    assert_eq!(progress.backfilled_up_to, 6);
+    assert_eq!(
+        dal.backfill_chunk(&TEST_ONLINE_MIGRATION, 3).await.unwrap(),
+        progress
+    );
+
+    dal.switch_over(&TEST_ONLINE_MIGRATION).await.unwrap();
+    let progress = dal.get_progress(TEST_ONLINE_MIGRATION.name).await.unwrap();
+    assert_eq!(progress.unwrap().stage, OnlineMigrationStage::SwitchedOver);
+
+    let rows = online_migration_test_rows(storage).await;
+    assert_eq!(
+        rows,
+        [
+            (1, b"x".to_vec(), 1),
+            (2, b"xx".to_vec(), 2),
+            (3, b"xxx".to_vec(), 3),
+            (4, b"updated".to_vec(), 7),
+            (10, b"new".to_vec(), 3),
+        ]
+    );
+    // The dual-write trigger is removed.
+    execute_statements(
+        storage,
+        &["INSERT INTO online_migration_test_values_retired (id, value) VALUES (20, ''::BYTEA)"],
+    )
+    .await;
+    assert_eq!(online_migration_test_rows(storage).await.len(), 5);
+
+    // Constraints and indexes are named after the tables they belong to.
+    assert_eq!(
+        table_constraints(storage, "online_migration_test_values").await,
+        ["online_migration_test_values_pkey"]
+    );
+    assert_eq!(
+        table_indexes(storage, "online_migration_test_values").await,
+        [
+            "online_migration_test_values_pkey",
+            "online_migration_test_values_value_len_idx"
+        ]
+    );
+    assert_eq!(
+        table_constraints(storage, "online_migration_test_values_retired").await,
+        ["online_migration_test_values_retired_pkey"]
+    );
+    assert_eq!(
+        table_indexes(storage, "online_migration_test_values_retired").await,
+        [
+            "online_migration_test_values_retired_pkey",
+            "online_migration_test_values_retired_value_idx"
+        ]
+    );
+
+    // The foreign key references the new table.
+    let referenced_table: String = sqlx::query_scalar(
+        "SELECT confrelid::regclass::TEXT FROM pg_constraint \
+         WHERE conname = 'online_migration_test_refs_value_id_fkey'",
+    )
+    .fetch_one(storage.conn())
+    .await
+    .unwrap();
+    assert_eq!(referenced_table, "online_migration_test_values");
+    execute_statements(
+        storage,
+        &["INSERT INTO online_migration_test_refs (value_id) VALUES (10)"],
+    )
+    .await;
+    let err = sqlx::query("INSERT INTO online_migration_test_refs (value_id) VALUES (5)")
+        .execute(storage.conn())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("foreign key"), "{err}");
+}
+
+#[db_test(dal_crate)]
+async fn online_migration_keeps_sequences(connection_pool: ConnectionPool) {
+    let storage = &mut connection_pool.access_test_storage().await;
+    create_online_migration_test_tables(storage).await;
+    let mut dal = storage.online_migrations_dal();
+    dal.start(&TEST_ONLINE_MIGRATION).await.unwrap();
+    dal.backfill_chunk(&TEST_ONLINE_MIGRATION, 10)
+        .await
+        .unwrap();
+    dal.switch_over(&TEST_ONLINE_MIGRATION).await.unwrap();
+
+    let sequence: Option<String> =
+        sqlx::query_scalar("SELECT pg_get_serial_sequence('online_migration_test_values', 'id')")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap();
+    assert_eq!(
+        sequence.as_deref(),
+        Some("public.online_migration_test_values_id_seq")
+    );
+    let target_sequence: Option<String> =
+        sqlx::query_scalar("SELECT to_regclass('online_migration_test_values_new_id_seq')::TEXT")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap();
+    assert_eq!(target_sequence, None);
+
+    // Generated IDs continue the source table sequence.
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO online_migration_test_values (value, value_len) VALUES ('next'::BYTEA, 4) \
+         RETURNING id",
+    )
+    .fetch_one(storage.conn())
+    .await
+    .unwrap();
+    assert_eq!(id, 11);
+}
+
+#[db_test(dal_crate)]
+async fn running_online_migrations(connection_pool: ConnectionPool) {
+    const MIGRATIONS: &[OnlineMigration] = &[TEST_ONLINE_MIGRATION];
+
+    let mut storage = connection_pool.access_test_storage().await;
+    create_online_migration_test_tables(&mut storage).await;
+    drop(storage);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let runner = tokio::spawn(run_online_migrations(
+        connection_pool.clone(),
+        MIGRATIONS,
+        2,
+        Duration::ZERO,
+        stop_receiver,
+    ));
+    loop {
+        let progress = connection_pool
+            .access_test_storage()
+            .await
+            .online_migrations_dal()
+            .get_progress(TEST_ONLINE_MIGRATION.name)
+            .await
+            .unwrap();
+        if progress.map_or(false, |progress| {
+            progress.stage == OnlineMigrationStage::Backfilled
+        }) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // The runner doesn't switch the migration over and waits for the stop signal.
+    stop_sender.send_replace(true);
+    runner.await.unwrap().unwrap();
+    let mut storage = connection_pool.access_test_storage().await;
+    let progress = storage
+        .online_migrations_dal()
+        .get_progress(TEST_ONLINE_MIGRATION.name)
+        .await
+        .unwrap();
+    assert_eq!(progress.unwrap().stage, OnlineMigrationStage::Backfilled);
+    let copied_rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM online_migration_test_values_new")
+            .fetch_one(storage.conn())
+            .await
+            .unwrap();
+    assert_eq!(copied_rows, 5);
+}
@@ -3,7 +3,9 @@ use crate::StorageProcessor;
 use num::{rational::Ratio, BigUint};
 use sqlx::types::chrono::Utc;
 use zksync_types::{
-    tokens::{TokenInfo, TokenMarketVolume, TokenMetadata, TokenPrice},
+    tokens::{
+        TokenInfo, TokenMarketVolume, TokenMetadata, TokenPrice, TokenProvenance, ETHEREUM_ADDRESS,
+    },
     Address, MiniblockNumber, ACCOUNT_CODE_STORAGE_ADDRESS,
     FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH,
 };
@@ -18,13 +20,18 @@ pub struct TokensDal<'a, 'c> {
 }
 
 impl TokensDal<'_, '_> {
-    pub async fn add_tokens(&mut self, tokens: Vec<TokenInfo>) {
+    /// Adds tokens to the storage. `provenance` is specified for tokens deployed by an L2 bridge.
+    pub async fn add_tokens(
+        &mut self,
+        tokens: Vec<TokenInfo>,
+        provenance: Option<TokenProvenance>,
+    ) {
         {
             let mut copy = self
             .storage
             .conn()
             .copy_in_raw(
-                "COPY tokens (l1_address, l2_address, name, symbol, decimals, well_known, created_at, updated_at)
+                "COPY tokens (l1_address, l2_address, name, symbol, decimals, well_known, created_at, updated_at, bridge_address, added_in_miniblock)
                 FROM STDIN WITH (DELIMITER '|')",
             )
             .await
@@ -32,6 +39,13 @@ impl TokensDal<'_, '_> {
 
             let mut bytes: Vec<u8> = Vec::new();
             let now = Utc::now().naive_utc().to_string();
+            let (bridge_address_str, added_in_miniblock_str) = match provenance {
+                Some(provenance) => (
+                    format!("\\\\x{}", hex::encode(provenance.bridge_address.0)),
+                    provenance.added_in_miniblock.0.to_string(),
+                ),
+                None => ("\\N".to_owned(), "\\N".to_owned()),
+            };
             for TokenInfo {
                 l1_address,
                 l2_address,
@@ -46,8 +60,16 @@ impl TokensDal<'_, '_> {
                 let l1_address_str = format!("\\\\x{}", hex::encode(l1_address.0));
                 let l2_address_str = format!("\\\\x{}", hex::encode(l2_address.0));
                 let row = format!(
-                    "{}|{}|{}|{}|{}|FALSE|{}|{}\n",
-                    l1_address_str, l2_address_str, name, symbol, decimals, now, now
+                    "{}|{}|{}|{}|{}|FALSE|{}|{}|{}|{}\n",
+                    l1_address_str,
+                    l2_address_str,
+                    name,
+                    symbol,
+                    decimals,
+                    now,
+                    now,
+                    bridge_address_str,
+                    added_in_miniblock_str
                 );
                 bytes.extend_from_slice(row.as_bytes());
             }
@@ -126,6 +148,57 @@ impl TokensDal<'_, '_> {
         }
     }
 
+    /// Returns L2 addresses of up to `limit` tokens (other than ETH) with the least recently refreshed metadata.
+    pub async fn get_tokens_for_metadata_refresh(&mut self, limit: usize) -> Vec<Address> {
+        let records = sqlx::query!(
+            "SELECT l2_address FROM tokens WHERE l2_address != $1
+            ORDER BY metadata_refreshed_at ASC NULLS FIRST, l2_address LIMIT $2",
+            ETHEREUM_ADDRESS.as_bytes(),
+            limit as i64
+        )
+        .fetch_all(self.storage.conn())
+        .await
+        .unwrap();
+        records
+            .into_iter()
+            .map(|record| Address::from_slice(&record.l2_address))
+            .collect()
+    }
+
+    /// Marks metadata of the specified token as refreshed, updating it if `metadata` is provided.
+    pub async fn refresh_token_metadata(
+        &mut self,
+        l2_address: &Address,
+        metadata: Option<&TokenMetadata>,
+    ) {
+        sqlx::query!(
+            "UPDATE tokens SET name = COALESCE($2, name), symbol = COALESCE($3, symbol),
+            decimals = COALESCE($4, decimals), metadata_refreshed_at = now(), updated_at = now()
+            WHERE l2_address = $1",
+            l2_address.as_bytes(),
+            metadata.map(|metadata| metadata.name.as_str()),
+            metadata.map(|metadata| metadata.symbol.as_str()),
+            metadata.map(|metadata| i32::from(metadata.decimals))
+        )
+        .execute(self.storage.conn())
+        .await
+        .unwrap();
+    }
+
+    pub async fn get_token_provenance(&mut self, l2_address: &Address) -> Option<TokenProvenance> {
+        let record = sqlx::query!(
+            "SELECT bridge_address, added_in_miniblock FROM tokens WHERE l2_address = $1",
+            l2_address.as_bytes()
+        )
+        .fetch_optional(self.storage.conn())
+        .await
+        .unwrap()?;
+        Some(TokenProvenance {
+            bridge_address: Address::from_slice(&record.bridge_address?),
+            added_in_miniblock: MiniblockNumber(record.added_in_miniblock? as u32),
+        })
+    }
+
     pub async fn get_l1_tokens_by_volume(&mut self, min_volume: &Ratio<BigUint>) -> Vec<Address> {
         {
             let min_volume = ratio_to_big_decimal(min_volume, STORED_USD_PRICE_PRECISION);
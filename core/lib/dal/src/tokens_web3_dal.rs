@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use crate::instrument::InstrumentExt;
 use crate::models::storage_token::{StorageTokenMetadata, StorageTokenPrice};
 use crate::SqlxError;
 use crate::StorageProcessor;
@@ -121,4 +124,40 @@ impl TokensWeb3Dal<'_, '_> {
             Ok(storage_token_metadata.map(Into::into))
         }
     }
+
+    /// Batched version of [`Self::get_token_metadata()`]. Tokens missing from the tokens table
+    /// are not included in the returned map.
+    pub async fn get_tokens_metadata(
+        &mut self,
+        l2_addresses: &[Address],
+    ) -> Result<HashMap<Address, TokenMetadata>, SqlxError> {
+        let l2_addresses: Vec<_> = l2_addresses.iter().map(Address::as_bytes).collect();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                l2_address,
+                COALESCE(token_list_name, name) as "name!",
+                COALESCE(token_list_symbol, symbol) as "symbol!",
+                COALESCE(token_list_decimals, decimals) as "decimals!"
+            FROM tokens WHERE l2_address = ANY($1)
+            "#,
+            &l2_addresses as &[&[u8]],
+        )
+        .instrument("get_tokens_metadata")
+        .with_arg("l2_addresses.len", &l2_addresses.len())
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let metadata = StorageTokenMetadata {
+                    name: row.name,
+                    symbol: row.symbol,
+                    decimals: row.decimals,
+                };
+                (Address::from_slice(&row.l2_address), metadata.into())
+            })
+            .collect())
+    }
 }
@@ -68,6 +68,8 @@ impl TransactionsDal<'_, '_> {
 
             let to_mint = u256_to_big_decimal(tx.common_data.to_mint);
             let refund_recipient = tx.common_data.refund_recipient.as_bytes();
+            let l1_tx_hash = tx.common_data.eth_hash.as_bytes();
+            let l1_deadline_block = tx.common_data.deadline_block as i64;
 
             let secs = (tx.received_timestamp_ms / 1000) as i64;
             let nanosecs = ((tx.received_timestamp_ms % 1000) * 1_000_000) as u32;
@@ -99,6 +101,8 @@ impl TransactionsDal<'_, '_> {
 
                     l1_tx_mint,
                     l1_tx_refund_recipient,
+                    l1_tx_hash,
+                    l1_deadline_block,
 
                     received_at,
                     created_at,
@@ -107,7 +111,7 @@ impl TransactionsDal<'_, '_> {
                 VALUES
                     (
                         $1, TRUE, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
-                        $13, $14, $15, $16, $17, $18, now(), now()
+                        $13, $14, $15, $16, $17, $18, $19, $20, now(), now()
                     )
                 ON CONFLICT (hash) DO NOTHING
                 ",
@@ -128,6 +132,8 @@ impl TransactionsDal<'_, '_> {
                 tx_format,
                 to_mint,
                 refund_recipient,
+                l1_tx_hash,
+                l1_deadline_block,
                 received_at,
             )
             .fetch_optional(self.storage.conn())
@@ -771,6 +777,21 @@ impl TransactionsDal<'_, '_> {
         }
     }
 
+    /// Removes transactions executed in miniblocks after the specified one. Unlike [`Self::reset_transactions_state()`],
+    /// the removed transactions are not returned to the mempool. Returns the number of removed transactions.
+    pub async fn remove_txs_after_miniblock(&mut self, miniblock_number: MiniblockNumber) -> usize {
+        {
+            sqlx::query!(
+                "DELETE FROM transactions WHERE miniblock_number > $1 RETURNING hash",
+                miniblock_number.0 as i64
+            )
+            .fetch_all(self.storage.conn())
+            .await
+            .unwrap()
+            .len()
+        }
+    }
+
     pub async fn remove_stuck_txs(&mut self, stuck_tx_timeout: Duration) -> usize {
         {
             let stuck_tx_timeout = pg_interval_from_duration(stuck_tx_timeout);
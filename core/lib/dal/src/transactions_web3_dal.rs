@@ -1,5 +1,7 @@
 use sqlx::types::chrono::NaiveDateTime;
 
+use std::collections::HashMap;
+
 use zksync_types::{
     api, Address, L2ChainId, MiniblockNumber, Transaction, ACCOUNT_CODE_STORAGE_ADDRESS,
     FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH, H160, H256, U256, U64,
@@ -8,10 +10,10 @@ use zksync_utils::{bigdecimal_to_u256, h256_to_account_address};
 
 use crate::models::{
     storage_block::{bind_block_where_sql_params, web3_block_where_sql},
-    storage_event::StorageWeb3Log,
+    storage_event::{StorageL2ToL1Log, StorageWeb3Log},
     storage_transaction::{
-        extract_web3_transaction, web3_transaction_select_sql, StorageTransaction,
-        StorageTransactionDetails,
+        extract_web3_transaction, web3_transaction_select_sql, StoragePriorityOpStatus,
+        StorageTransaction, StorageTransactionDetails, StorageTransactionReceipt,
     },
 };
 use crate::{instrument::InstrumentExt, SqlxError, StorageProcessor};
@@ -26,149 +28,280 @@ impl TransactionsWeb3Dal<'_, '_> {
         &mut self,
         hash: H256,
     ) -> Result<Option<api::TransactionReceipt>, SqlxError> {
-        {
-            let receipt = sqlx::query!(
-                r#"
-                WITH sl AS (
-                    SELECT * FROM storage_logs
-                    WHERE storage_logs.address = $1 AND storage_logs.tx_hash = $2
-                    ORDER BY storage_logs.miniblock_number DESC, storage_logs.operation_number DESC
-                    LIMIT 1
-                )
-                SELECT
-                     transactions.hash as tx_hash,
-                     transactions.index_in_block as index_in_block,
-                     transactions.l1_batch_tx_index as l1_batch_tx_index,
-                     transactions.miniblock_number as block_number,
-                     transactions.error as error,
-                     transactions.effective_gas_price as effective_gas_price,
-                     transactions.initiator_address as initiator_address,
-                     transactions.data->'to' as "transfer_to?",
-                     transactions.data->'contractAddress' as "execute_contract_address?",
-                     transactions.tx_format as "tx_format?",
-                     transactions.refunded_gas as refunded_gas,
-                     transactions.gas_limit as gas_limit,
-                     miniblocks.hash as "block_hash?",
-                     miniblocks.l1_batch_number as "l1_batch_number?",
-                     sl.key as "contract_address?"
-                FROM transactions
-                LEFT JOIN miniblocks
-                    ON miniblocks.number = transactions.miniblock_number
-                LEFT JOIN sl
-                    ON sl.value != $3
-                WHERE transactions.hash = $2
-                "#,
-                ACCOUNT_CODE_STORAGE_ADDRESS.as_bytes(),
-                hash.as_bytes(),
-                FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes()
+        let receipt = sqlx::query_as!(
+            StorageTransactionReceipt,
+            r#"
+            WITH sl AS (
+                SELECT * FROM storage_logs
+                WHERE storage_logs.address = $1 AND storage_logs.tx_hash = $2
+                ORDER BY storage_logs.miniblock_number DESC, storage_logs.operation_number DESC
+                LIMIT 1
             )
-            .instrument("get_transaction_receipt")
-            .with_arg("hash", &hash)
-            .fetch_optional(self.storage.conn())
-            .await?
-            .map(|db_row| {
-                let status = match (db_row.block_number, db_row.error) {
-                    (_, Some(_)) => Some(U64::from(0)),
-                    (Some(_), None) => Some(U64::from(1)),
-                    // tx not executed yet
-                    _ => None,
-                };
-                let tx_type = db_row.tx_format.map(U64::from).unwrap_or_default();
-                let transaction_index = db_row.index_in_block.map(U64::from).unwrap_or_default();
-
-                let block_hash = db_row.block_hash.map(|bytes| H256::from_slice(&bytes));
-                api::TransactionReceipt {
-                    transaction_hash: H256::from_slice(&db_row.tx_hash),
-                    transaction_index,
-                    block_hash,
-                    block_number: db_row.block_number.map(U64::from),
-                    l1_batch_tx_index: db_row.l1_batch_tx_index.map(U64::from),
-                    l1_batch_number: db_row.l1_batch_number.map(U64::from),
-                    from: H160::from_slice(&db_row.initiator_address),
-                    to: db_row
-                        .transfer_to
-                        .or(db_row.execute_contract_address)
-                        .map(|addr| {
-                            serde_json::from_value::<Address>(addr)
-                                .expect("invalid address value in the database")
-                        })
-                        // For better compatibility with various clients, we never return null.
-                        .or_else(|| Some(Address::default())),
-                    cumulative_gas_used: Default::default(), // TODO: Should be actually calculated (SMA-1183).
-                    gas_used: {
-                        let refunded_gas: U256 = db_row.refunded_gas.into();
-                        db_row.gas_limit.map(|val| {
-                            let gas_limit = bigdecimal_to_u256(val);
-                            gas_limit - refunded_gas
-                        })
-                    },
-                    effective_gas_price: Some(
-                        db_row
-                            .effective_gas_price
-                            .map(bigdecimal_to_u256)
-                            .unwrap_or_default(),
-                    ),
-                    contract_address: db_row
-                        .contract_address
-                        .map(|addr| h256_to_account_address(&H256::from_slice(&addr))),
-                    logs: vec![],
-                    l2_to_l1_logs: vec![],
-                    status,
-                    root: block_hash,
-                    logs_bloom: Default::default(),
-                    // Even though the Rust SDK recommends us to supply "None" for legacy transactions
-                    // we always supply some number anyway to have the same behaviour as most popular RPCs
-                    transaction_type: Some(tx_type),
-                }
-            });
-            match receipt {
-                Some(mut receipt) => {
-                    let logs: Vec<_> = sqlx::query_as!(
-                        StorageWeb3Log,
-                        r#"
-                        SELECT
-                            address, topic1, topic2, topic3, topic4, value,
-                            Null::bytea as "block_hash", Null::bigint as "l1_batch_number?",
-                            miniblock_number, tx_hash, tx_index_in_block,
-                            event_index_in_block, event_index_in_tx
-                        FROM events
-                        WHERE tx_hash = $1
-                        ORDER BY miniblock_number ASC, event_index_in_block ASC
-                        "#,
-                        hash.as_bytes()
-                    )
-                    .instrument("get_transaction_receipt_events")
-                    .with_arg("hash", &hash)
-                    .fetch_all(self.storage.conn())
-                    .await?
-                    .into_iter()
-                    .map(|storage_log| {
-                        let mut log = api::Log::from(storage_log);
-                        log.block_hash = receipt.block_hash;
-                        log.l1_batch_number = receipt.l1_batch_number;
-                        log
-                    })
-                    .collect();
-
-                    receipt.logs = logs;
-
-                    let l2_to_l1_logs = self.storage.events_dal().l2_to_l1_logs(hash).await?;
-                    let l2_to_l1_logs: Vec<_> = l2_to_l1_logs
-                        .into_iter()
-                        .map(|storage_l2_to_l1_log| {
-                            let mut l2_to_l1_log = api::L2ToL1Log::from(storage_l2_to_l1_log);
-                            l2_to_l1_log.block_hash = receipt.block_hash;
-                            l2_to_l1_log.l1_batch_number = receipt.l1_batch_number;
-                            l2_to_l1_log
-                        })
-                        .collect();
-                    receipt.l2_to_l1_logs = l2_to_l1_logs;
-
-                    Ok(Some(receipt))
-                }
-                None => Ok(None),
-            }
-        }
+            SELECT
+                 transactions.hash as tx_hash,
+                 transactions.index_in_block as index_in_block,
+                 transactions.l1_batch_tx_index as l1_batch_tx_index,
+                 transactions.miniblock_number as block_number,
+                 transactions.error as error,
+                 transactions.effective_gas_price as effective_gas_price,
+                 transactions.initiator_address as initiator_address,
+                 transactions.data->'to' as "transfer_to?",
+                 transactions.data->'contractAddress' as "execute_contract_address?",
+                 transactions.tx_format as "tx_format?",
+                 transactions.refunded_gas as refunded_gas,
+                 transactions.gas_limit as gas_limit,
+                 miniblocks.hash as "block_hash?",
+                 miniblocks.l1_batch_number as "l1_batch_number?",
+                 sl.key as "contract_address?"
+            FROM transactions
+            LEFT JOIN miniblocks
+                ON miniblocks.number = transactions.miniblock_number
+            LEFT JOIN sl
+                ON sl.value != $3
+            WHERE transactions.hash = $2
+            "#,
+            ACCOUNT_CODE_STORAGE_ADDRESS.as_bytes(),
+            hash.as_bytes(),
+            FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes()
+        )
+        .instrument("get_transaction_receipt")
+        .with_arg("hash", &hash)
+        .fetch_optional(self.storage.conn())
+        .await?
+        .map(api::TransactionReceipt::from);
+
+        let Some(mut receipt) = receipt else {
+            return Ok(None);
+        };
+        let logs: Vec<_> = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                address, topic1, topic2, topic3, topic4, value,
+                Null::bytea as "block_hash", Null::bigint as "l1_batch_number?",
+                miniblock_number, tx_hash, tx_index_in_block,
+                event_index_in_block, event_index_in_tx
+            FROM events
+            WHERE tx_hash = $1
+            ORDER BY miniblock_number ASC, event_index_in_block ASC
+            "#,
+            hash.as_bytes()
+        )
+        .instrument("get_transaction_receipt_events")
+        .with_arg("hash", &hash)
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(|storage_log| {
+            let mut log = api::Log::from(storage_log);
+            log.block_hash = receipt.block_hash;
+            log.l1_batch_number = receipt.l1_batch_number;
+            log
+        })
+        .collect();
+
+        receipt.logs = logs;
+
+        let l2_to_l1_logs = self.storage.events_dal().l2_to_l1_logs(hash).await?;
+        let l2_to_l1_logs: Vec<_> = l2_to_l1_logs
+            .into_iter()
+            .map(|storage_l2_to_l1_log| {
+                let mut l2_to_l1_log = api::L2ToL1Log::from(storage_l2_to_l1_log);
+                l2_to_l1_log.block_hash = receipt.block_hash;
+                l2_to_l1_log.l1_batch_number = receipt.l1_batch_number;
+                l2_to_l1_log
+            })
+            .collect();
+        receipt.l2_to_l1_logs = l2_to_l1_logs;
+
+        Ok(Some(receipt))
+    }
+
+    /// Returns receipts (including logs) for all transactions in the specified miniblock, ordered
+    /// by the transaction index in the miniblock. Unlike [`Self::get_transaction_receipt()`], uses
+    /// a constant number of queries regardless of the number of transactions.
+    pub async fn get_miniblock_receipts(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> Result<Vec<api::TransactionReceipt>, SqlxError> {
+        let mut receipts: Vec<_> = sqlx::query_as!(
+            StorageTransactionReceipt,
+            r#"
+            SELECT
+                 transactions.hash as tx_hash,
+                 transactions.index_in_block as index_in_block,
+                 transactions.l1_batch_tx_index as l1_batch_tx_index,
+                 transactions.miniblock_number as block_number,
+                 transactions.error as error,
+                 transactions.effective_gas_price as effective_gas_price,
+                 transactions.initiator_address as initiator_address,
+                 transactions.data->'to' as "transfer_to?",
+                 transactions.data->'contractAddress' as "execute_contract_address?",
+                 transactions.tx_format as "tx_format?",
+                 transactions.refunded_gas as refunded_gas,
+                 transactions.gas_limit as gas_limit,
+                 miniblocks.hash as "block_hash?",
+                 miniblocks.l1_batch_number as "l1_batch_number?",
+                 sl.key as "contract_address?"
+            FROM transactions
+            LEFT JOIN miniblocks
+                ON miniblocks.number = transactions.miniblock_number
+            LEFT JOIN LATERAL (
+                SELECT key, value FROM storage_logs
+                WHERE storage_logs.address = $1 AND storage_logs.tx_hash = transactions.hash
+                ORDER BY storage_logs.miniblock_number DESC, storage_logs.operation_number DESC
+                LIMIT 1
+            ) sl
+                ON sl.value != $3
+            WHERE transactions.miniblock_number = $2
+            ORDER BY transactions.index_in_block
+            "#,
+            ACCOUNT_CODE_STORAGE_ADDRESS.as_bytes(),
+            miniblock_number.0 as i64,
+            FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes()
+        )
+        .instrument("get_miniblock_receipts")
+        .with_arg("miniblock_number", &miniblock_number)
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(api::TransactionReceipt::from)
+        .collect();
+
+        let logs = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                address, topic1, topic2, topic3, topic4, value,
+                Null::bytea as "block_hash", Null::bigint as "l1_batch_number?",
+                miniblock_number, tx_hash, tx_index_in_block,
+                event_index_in_block, event_index_in_tx
+            FROM events
+            WHERE miniblock_number = $1
+            ORDER BY event_index_in_block ASC
+            "#,
+            miniblock_number.0 as i64
+        )
+        .instrument("get_miniblock_receipts#events")
+        .with_arg("miniblock_number", &miniblock_number)
+        .fetch_all(self.storage.conn())
+        .await?;
+        let l2_to_l1_logs = self
+            .storage
+            .events_dal()
+            .l2_to_l1_logs_for_miniblock(miniblock_number)
+            .await?;
+        attach_receipt_logs(&mut receipts, logs, l2_to_l1_logs);
+        Ok(receipts)
+    }
+
+    /// Returns receipts (including logs) for the specified transactions. Transactions missing
+    /// from the database are skipped. Uses a constant number of queries regardless of
+    /// the number of transactions.
+    pub async fn get_transaction_receipts(
+        &mut self,
+        hashes: &[H256],
+    ) -> Result<Vec<api::TransactionReceipt>, SqlxError> {
+        let hash_bytes: Vec<_> = hashes.iter().map(H256::as_bytes).collect();
+        let mut receipts: Vec<_> = sqlx::query_as!(
+            StorageTransactionReceipt,
+            r#"
+            SELECT
+                 transactions.hash as tx_hash,
+                 transactions.index_in_block as index_in_block,
+                 transactions.l1_batch_tx_index as l1_batch_tx_index,
+                 transactions.miniblock_number as block_number,
+                 transactions.error as error,
+                 transactions.effective_gas_price as effective_gas_price,
+                 transactions.initiator_address as initiator_address,
+                 transactions.data->'to' as "transfer_to?",
+                 transactions.data->'contractAddress' as "execute_contract_address?",
+                 transactions.tx_format as "tx_format?",
+                 transactions.refunded_gas as refunded_gas,
+                 transactions.gas_limit as gas_limit,
+                 miniblocks.hash as "block_hash?",
+                 miniblocks.l1_batch_number as "l1_batch_number?",
+                 sl.key as "contract_address?"
+            FROM transactions
+            LEFT JOIN miniblocks
+                ON miniblocks.number = transactions.miniblock_number
+            LEFT JOIN LATERAL (
+                SELECT key, value FROM storage_logs
+                WHERE storage_logs.address = $1 AND storage_logs.tx_hash = transactions.hash
+                ORDER BY storage_logs.miniblock_number DESC, storage_logs.operation_number DESC
+                LIMIT 1
+            ) sl
+                ON sl.value != $3
+            WHERE transactions.hash = ANY($2)
+            "#,
+            ACCOUNT_CODE_STORAGE_ADDRESS.as_bytes(),
+            &hash_bytes as &[&[u8]],
+            FAILED_CONTRACT_DEPLOYMENT_BYTECODE_HASH.as_bytes()
+        )
+        .instrument("get_transaction_receipts")
+        .with_arg("hashes.len", &hashes.len())
+        .fetch_all(self.storage.conn())
+        .await?
+        .into_iter()
+        .map(api::TransactionReceipt::from)
+        .collect();
+
+        let logs = sqlx::query_as!(
+            StorageWeb3Log,
+            r#"
+            SELECT
+                address, topic1, topic2, topic3, topic4, value,
+                Null::bytea as "block_hash", Null::bigint as "l1_batch_number?",
+                miniblock_number, tx_hash, tx_index_in_block,
+                event_index_in_block, event_index_in_tx
+            FROM events
+            WHERE tx_hash = ANY($1)
+            ORDER BY miniblock_number ASC, event_index_in_block ASC
+            "#,
+            &hash_bytes as &[&[u8]]
+        )
+        .instrument("get_transaction_receipts#events")
+        .with_arg("hashes.len", &hashes.len())
+        .fetch_all(self.storage.conn())
+        .await?;
+        let l2_to_l1_logs = self
+            .storage
+            .events_dal()
+            .l2_to_l1_logs_for_transactions(hashes)
+            .await?;
+        attach_receipt_logs(&mut receipts, logs, l2_to_l1_logs);
+        Ok(receipts)
+    }
+
+    /// Returns transactions from the specified miniblocks ordered by the miniblock number
+    /// and the index in the miniblock.
+    pub async fn get_miniblocks_transactions(
+        &mut self,
+        miniblock_numbers: &[MiniblockNumber],
+        chain_id: L2ChainId,
+    ) -> Result<Vec<api::Transaction>, SqlxError> {
+        let numbers: Vec<_> = miniblock_numbers
+            .iter()
+            .map(|number| number.0 as i64)
+            .collect();
+        let query = format!(
+            "SELECT {}
+            FROM transactions
+            LEFT JOIN miniblocks ON miniblocks.number = transactions.miniblock_number
+            WHERE transactions.miniblock_number = ANY($1)
+            ORDER BY transactions.miniblock_number, transactions.index_in_block",
+            web3_transaction_select_sql()
+        );
+        let rows = sqlx::query(&query)
+            .bind(&numbers)
+            .instrument("get_miniblocks_transactions")
+            .with_arg("miniblock_numbers.len", &miniblock_numbers.len())
+            .fetch_all(self.storage.conn())
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| extract_web3_transaction(row, chain_id))
+            .collect())
     }
 
     pub async fn get_transaction(
@@ -254,6 +387,45 @@ impl TransactionsWeb3Dal<'_, '_> {
         }
     }
 
+    /// Returns statuses of priority operations emitted by the specified L1 transaction, ordered by
+    /// priority operation ID. A single L1 transaction may emit several operations (e.g., if it calls
+    /// the Mailbox contract multiple times).
+    pub async fn get_priority_op_statuses(
+        &mut self,
+        l1_tx_hash: H256,
+    ) -> Result<Vec<api::PriorityOpStatus>, SqlxError> {
+        let storage_statuses = sqlx::query_as!(
+            StoragePriorityOpStatus,
+            r#"
+                SELECT transactions.hash,
+                    transactions.l1_tx_hash,
+                    transactions.priority_op_id,
+                    transactions.l1_block_number,
+                    transactions.l1_deadline_block,
+                    transactions.received_at,
+                    transactions.miniblock_number,
+                    transactions.l1_batch_number,
+                    transactions.error,
+                    (
+                        SELECT COUNT(*) FROM transactions AS queued
+                        WHERE queued.is_priority = TRUE
+                            AND queued.miniblock_number IS NULL
+                            AND queued.priority_op_id < transactions.priority_op_id
+                    ) AS "queue_position!"
+                FROM transactions
+                WHERE transactions.l1_tx_hash = $1 AND transactions.is_priority = TRUE
+                ORDER BY transactions.priority_op_id
+            "#,
+            l1_tx_hash.as_bytes()
+        )
+        .instrument("get_priority_op_statuses")
+        .with_arg("l1_tx_hash", &l1_tx_hash)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(storage_statuses.into_iter().map(Into::into).collect())
+    }
+
     /// Returns hashes of txs which were received after `from_timestamp` and the time of receiving the last tx.
     pub async fn get_pending_txs_hashes_after(
         &mut self,
@@ -351,6 +523,40 @@ impl TransactionsWeb3Dal<'_, '_> {
     }
 }
 
+/// Attaches logs and L2-to-L1 logs to the corresponding receipts. Logs must be ordered
+/// by their position in the chain.
+fn attach_receipt_logs(
+    receipts: &mut [api::TransactionReceipt],
+    logs: Vec<StorageWeb3Log>,
+    l2_to_l1_logs: Vec<StorageL2ToL1Log>,
+) {
+    let receipt_indices: HashMap<_, _> = receipts
+        .iter()
+        .enumerate()
+        .map(|(i, receipt)| (receipt.transaction_hash, i))
+        .collect();
+    for storage_log in logs {
+        let mut log = api::Log::from(storage_log);
+        let Some(&i) = receipt_indices.get(&log.transaction_hash.unwrap_or_default()) else {
+            continue;
+        };
+        let receipt = &mut receipts[i];
+        log.block_hash = receipt.block_hash;
+        log.l1_batch_number = receipt.l1_batch_number;
+        receipt.logs.push(log);
+    }
+    for storage_l2_to_l1_log in l2_to_l1_logs {
+        let mut l2_to_l1_log = api::L2ToL1Log::from(storage_l2_to_l1_log);
+        let Some(&i) = receipt_indices.get(&l2_to_l1_log.transaction_hash) else {
+            continue;
+        };
+        let receipt = &mut receipts[i];
+        l2_to_l1_log.block_hash = receipt.block_hash;
+        l2_to_l1_log.l1_batch_number = receipt.l1_batch_number;
+        receipt.l2_to_l1_logs.push(l2_to_l1_log);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use db_test_macro::db_test;
@@ -479,4 +685,43 @@ mod tests {
         assert_eq!(raw_txs.len(), 1);
         assert_eq!(raw_txs[0].hash(), tx_hash);
     }
+
+    #[db_test(dal_crate)]
+    async fn getting_receipts_and_transactions_in_batch(connection_pool: ConnectionPool) {
+        let mut conn = connection_pool.access_test_storage().await;
+        conn.protocol_versions_dal()
+            .save_protocol_version_with_tx(ProtocolVersion::default())
+            .await;
+        let tx = mock_l2_transaction();
+        let tx_hash = tx.hash();
+        prepare_transaction(&mut conn, tx).await;
+
+        let receipt = conn
+            .transactions_web3_dal()
+            .get_transaction_receipt(tx_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        let receipts = conn
+            .transactions_web3_dal()
+            .get_transaction_receipts(&[tx_hash, H256::repeat_byte(1)])
+            .await
+            .unwrap();
+        assert_eq!(receipts, [receipt.clone()]);
+        let miniblock_receipts = conn
+            .transactions_web3_dal()
+            .get_miniblock_receipts(MiniblockNumber(1))
+            .await
+            .unwrap();
+        assert_eq!(miniblock_receipts, [receipt]);
+
+        let txs = conn
+            .transactions_web3_dal()
+            .get_miniblocks_transactions(&[MiniblockNumber(0), MiniblockNumber(1)], L2ChainId(270))
+            .await
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash, tx_hash);
+        assert_eq!(txs[0].block_number, Some(1.into()));
+    }
 }
@@ -0,0 +1,197 @@
+use sqlx::types::chrono::{DateTime, NaiveDateTime, Utc};
+
+use std::time::Duration;
+
+use zksync_types::{
+    api::{TransactionJourney, TransactionMilestone, TransactionMilestoneRecord},
+    L1BatchNumber, MiniblockNumber, H256,
+};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+const VALIDATED_EVENT: &str = "validated";
+const ENTERED_MEMPOOL_EVENT: &str = "entered_mempool";
+const INCLUDED_IN_MINIBLOCK_EVENT: &str = "included_in_miniblock";
+
+fn to_utc(timestamp: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::<Utc>::from_naive_utc_and_offset(timestamp, Utc)
+}
+
+/// Records milestones in the lifecycle of transactions reached before the transactions are sealed
+/// in an L1 batch, and assembles transaction journeys.
+#[derive(Debug)]
+pub struct TxLifecycleDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl TxLifecycleDal<'_, '_> {
+    /// Records that the transaction has passed validation. Events recorded for a transaction
+    /// replaced by this one are discarded. Uses a single statement, so that recording doesn't
+    /// require a DB transaction on the transaction submission path.
+    pub async fn record_validated(&mut self, tx_hash: H256) -> sqlx::Result<()> {
+        sqlx::query!(
+            "WITH stale_events AS ( \
+                DELETE FROM transaction_lifecycle_events WHERE tx_hash = $1 AND event <> $2 \
+            ) \
+            INSERT INTO transaction_lifecycle_events (tx_hash, event, occurred_at) \
+            VALUES ($1, $2, now()) \
+            ON CONFLICT (tx_hash, event) DO UPDATE SET occurred_at = excluded.occurred_at",
+            tx_hash.as_bytes(),
+            VALIDATED_EVENT
+        )
+        .instrument("record_validated")
+        .with_arg("tx_hash", &tx_hash)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Records that the specified transactions were loaded into the mempool. Only the first load
+    /// of each transaction is recorded; transactions removed from the storage in the meantime are skipped.
+    pub async fn record_entered_mempool(&mut self, tx_hashes: &[H256]) -> sqlx::Result<()> {
+        if tx_hashes.is_empty() {
+            return Ok(());
+        }
+        let tx_hashes: Vec<_> = tx_hashes.iter().map(H256::as_bytes).collect();
+        sqlx::query!(
+            "INSERT INTO transaction_lifecycle_events (tx_hash, event, occurred_at) \
+            SELECT hash, $2, now() FROM transactions WHERE hash = ANY($1) \
+            ON CONFLICT (tx_hash, event) DO NOTHING",
+            &tx_hashes as &[&[u8]],
+            ENTERED_MEMPOOL_EVENT
+        )
+        .instrument("record_entered_mempool")
+        .with_arg("tx_hashes.len", &tx_hashes.len())
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Records that all transactions in the specified miniblock were included into it. If a transaction
+    /// is re-executed after a miniblock rollback, the inclusion time is updated.
+    pub async fn record_miniblock_inclusion(
+        &mut self,
+        miniblock_number: MiniblockNumber,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "INSERT INTO transaction_lifecycle_events (tx_hash, event, occurred_at) \
+            SELECT hash, $2, now() FROM transactions WHERE miniblock_number = $1 \
+            ON CONFLICT (tx_hash, event) DO UPDATE SET occurred_at = excluded.occurred_at",
+            miniblock_number.0 as i64,
+            INCLUDED_IN_MINIBLOCK_EVENT
+        )
+        .instrument("record_miniblock_inclusion")
+        .with_arg("miniblock_number", &miniblock_number)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Removes events that occurred more than `retention` ago. At most `limit` events are removed
+    /// per call. Returns the number of removed events.
+    pub async fn prune_events(&mut self, retention: Duration, limit: usize) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM transaction_lifecycle_events \
+            WHERE (tx_hash, event) IN ( \
+                SELECT tx_hash, event FROM transaction_lifecycle_events \
+                WHERE occurred_at < now() - make_interval(secs => $1) \
+                LIMIT $2 \
+            )",
+            retention.as_secs_f64(),
+            limit as i64
+        )
+        .instrument("prune_events")
+        .with_arg("retention", &retention)
+        .with_arg("limit", &limit)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Returns milestones reached by the specified transaction, or `None` if the transaction is unknown.
+    /// Milestones reached on L1 are taken from confirmed L1 transactions for the L1 batch
+    /// containing the transaction.
+    pub async fn get_transaction_journey(
+        &mut self,
+        tx_hash: H256,
+    ) -> sqlx::Result<Option<TransactionJourney>> {
+        let Some(row) = sqlx::query!(
+            "SELECT transactions.received_at, transactions.miniblock_number, \
+                transactions.l1_batch_number, l1_batches.created_at AS \"sealed_at?\", \
+                commit_tx.confirmed_at AS \"committed_at?\", \
+                prove_tx.confirmed_at AS \"proven_at?\", \
+                execute_tx.confirmed_at AS \"executed_at?\" \
+            FROM transactions \
+            LEFT JOIN l1_batches ON l1_batches.number = transactions.l1_batch_number \
+            LEFT JOIN eth_txs_history AS commit_tx \
+                ON (l1_batches.eth_commit_tx_id = commit_tx.eth_tx_id AND commit_tx.confirmed_at IS NOT NULL) \
+            LEFT JOIN eth_txs_history AS prove_tx \
+                ON (l1_batches.eth_prove_tx_id = prove_tx.eth_tx_id AND prove_tx.confirmed_at IS NOT NULL) \
+            LEFT JOIN eth_txs_history AS execute_tx \
+                ON (l1_batches.eth_execute_tx_id = execute_tx.eth_tx_id AND execute_tx.confirmed_at IS NOT NULL) \
+            WHERE transactions.hash = $1",
+            tx_hash.as_bytes()
+        )
+        .instrument("get_transaction_journey#transaction")
+        .with_arg("tx_hash", &tx_hash)
+        .fetch_optional(self.storage.conn())
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let events = sqlx::query!(
+            "SELECT event, occurred_at FROM transaction_lifecycle_events WHERE tx_hash = $1",
+            tx_hash.as_bytes()
+        )
+        .instrument("get_transaction_journey#events")
+        .with_arg("tx_hash", &tx_hash)
+        .fetch_all(self.storage.conn())
+        .await?;
+        let event_time = |name: &str| {
+            events
+                .iter()
+                .find(|event| event.event == name)
+                .map(|event| event.occurred_at)
+        };
+        // Inclusion events are not removed on miniblock rollback, so they need to be filtered.
+        let included_at = row
+            .miniblock_number
+            .and_then(|_| event_time(INCLUDED_IN_MINIBLOCK_EVENT));
+
+        let milestones = [
+            (TransactionMilestone::Received, Some(row.received_at)),
+            (TransactionMilestone::Validated, event_time(VALIDATED_EVENT)),
+            (
+                TransactionMilestone::EnteredMempool,
+                event_time(ENTERED_MEMPOOL_EVENT),
+            ),
+            (TransactionMilestone::IncludedInMiniblock, included_at),
+            (TransactionMilestone::L1BatchSealed, row.sealed_at),
+            (TransactionMilestone::Committed, row.committed_at),
+            (TransactionMilestone::Proven, row.proven_at),
+            (TransactionMilestone::ExecutedOnL1, row.executed_at),
+        ];
+        let milestones = milestones
+            .into_iter()
+            .filter_map(|(milestone, timestamp)| {
+                Some(TransactionMilestoneRecord {
+                    milestone,
+                    timestamp: to_utc(timestamp?),
+                })
+            })
+            .collect();
+
+        Ok(Some(TransactionJourney {
+            tx_hash,
+            miniblock_number: row
+                .miniblock_number
+                .map(|number| MiniblockNumber(number as u32)),
+            l1_batch_number: row
+                .l1_batch_number
+                .map(|number| L1BatchNumber(number as u32)),
+            milestones,
+        }))
+    }
+}
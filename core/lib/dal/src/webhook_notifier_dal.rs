@@ -0,0 +1,153 @@
+use std::ops;
+
+use zksync_types::{L1BatchNumber, H256};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+/// L1 batch statuses delivered to a webhook endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebhookCursor {
+    /// Numbers and hashes of the last miniblock of sealed L1 batches delivered to the endpoint, in ascending order.
+    /// Only L1 batches starting from the last executed one are retained.
+    pub sealed_l1_batches: Vec<(L1BatchNumber, H256)>,
+    pub committed_l1_batch: Option<L1BatchNumber>,
+    pub proven_l1_batch: Option<L1BatchNumber>,
+    pub executed_l1_batch: Option<L1BatchNumber>,
+}
+
+/// Persists progress of the webhook notifier, so that it can resume after a restart without losing events.
+#[derive(Debug)]
+pub struct WebhookNotifierDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl WebhookNotifierDal<'_, '_> {
+    /// Returns the cursor for the specified endpoint, or `None` if nothing was delivered to it yet.
+    pub async fn get_cursor(&mut self, endpoint: &str) -> sqlx::Result<Option<WebhookCursor>> {
+        let row = sqlx::query!(
+            "SELECT committed_l1_batch, proven_l1_batch, executed_l1_batch \
+            FROM webhook_notifier_cursors WHERE endpoint = $1",
+            endpoint
+        )
+        .instrument("get_webhook_cursor")
+        .with_arg("endpoint", &endpoint)
+        .fetch_optional(self.storage.conn())
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let sealed_rows = sqlx::query!(
+            "SELECT l1_batch_number, l1_batch_hash FROM webhook_notifier_sealed_l1_batches \
+            WHERE endpoint = $1 ORDER BY l1_batch_number",
+            endpoint
+        )
+        .instrument("get_webhook_cursor#sealed_l1_batches")
+        .with_arg("endpoint", &endpoint)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        let to_batch_number =
+            |number: Option<i64>| number.map(|number| L1BatchNumber(number as u32));
+        Ok(Some(WebhookCursor {
+            sealed_l1_batches: sealed_rows
+                .into_iter()
+                .map(|row| {
+                    let number = L1BatchNumber(row.l1_batch_number as u32);
+                    (number, H256::from_slice(&row.l1_batch_hash))
+                })
+                .collect(),
+            committed_l1_batch: to_batch_number(row.committed_l1_batch),
+            proven_l1_batch: to_batch_number(row.proven_l1_batch),
+            executed_l1_batch: to_batch_number(row.executed_l1_batch),
+        }))
+    }
+
+    /// Saves the cursor for the specified endpoint, replacing the previously saved one.
+    pub async fn save_cursor(
+        &mut self,
+        endpoint: &str,
+        cursor: &WebhookCursor,
+    ) -> sqlx::Result<()> {
+        let from_batch_number =
+            |number: Option<L1BatchNumber>| number.map(|number| number.0 as i64);
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query!(
+            "INSERT INTO webhook_notifier_cursors \
+                (endpoint, committed_l1_batch, proven_l1_batch, executed_l1_batch, created_at, updated_at) \
+            VALUES ($1, $2, $3, $4, now(), now()) \
+            ON CONFLICT (endpoint) DO UPDATE \
+            SET committed_l1_batch = excluded.committed_l1_batch, \
+                proven_l1_batch = excluded.proven_l1_batch, \
+                executed_l1_batch = excluded.executed_l1_batch, \
+                updated_at = now()",
+            endpoint,
+            from_batch_number(cursor.committed_l1_batch),
+            from_batch_number(cursor.proven_l1_batch),
+            from_batch_number(cursor.executed_l1_batch)
+        )
+        .instrument("save_webhook_cursor")
+        .with_arg("endpoint", &endpoint)
+        .execute(transaction.conn())
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM webhook_notifier_sealed_l1_batches WHERE endpoint = $1",
+            endpoint
+        )
+        .instrument("save_webhook_cursor#delete_sealed_l1_batches")
+        .with_arg("endpoint", &endpoint)
+        .execute(transaction.conn())
+        .await?;
+
+        let (numbers, hashes): (Vec<_>, Vec<_>) = cursor
+            .sealed_l1_batches
+            .iter()
+            .map(|(number, hash)| (number.0 as i64, hash.as_bytes()))
+            .unzip();
+        sqlx::query!(
+            "INSERT INTO webhook_notifier_sealed_l1_batches (endpoint, l1_batch_number, l1_batch_hash) \
+            SELECT $1, u.l1_batch_number, u.l1_batch_hash \
+            FROM UNNEST($2::bigint[], $3::bytea[]) AS u(l1_batch_number, l1_batch_hash)",
+            endpoint,
+            &numbers,
+            &hashes as &[&[u8]]
+        )
+        .instrument("save_webhook_cursor#insert_sealed_l1_batches")
+        .with_arg("endpoint", &endpoint)
+        .with_arg("sealed_l1_batches.len", &numbers.len())
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Returns hashes of the last miniblock for each sealed L1 batch in the specified range.
+    /// These hashes identify L1 batches: if an L1 batch is reverted and then re-sealed, its hash will change.
+    pub async fn get_l1_batch_hashes(
+        &mut self,
+        numbers: ops::RangeInclusive<L1BatchNumber>,
+    ) -> sqlx::Result<Vec<(L1BatchNumber, H256)>> {
+        let rows = sqlx::query!(
+            "SELECT DISTINCT ON (l1_batch_number) l1_batch_number AS \"l1_batch_number!\", hash \
+            FROM miniblocks \
+            WHERE l1_batch_number BETWEEN $1 AND $2 \
+            ORDER BY l1_batch_number, number DESC",
+            numbers.start().0 as i64,
+            numbers.end().0 as i64
+        )
+        .instrument("get_l1_batch_hashes")
+        .with_arg("numbers", &numbers)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let number = L1BatchNumber(row.l1_batch_number as u32);
+                (number, H256::from_slice(&row.hash))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,352 @@
+use zksync_types::{
+    withdrawals::{Withdrawal, WithdrawalStatus},
+    Address, L1BatchNumber, H256, U256,
+};
+use zksync_utils::{bigdecimal_to_u256, u256_to_big_decimal};
+
+use crate::instrument::InstrumentExt;
+use crate::StorageProcessor;
+
+/// Signed finalization transaction for a withdrawal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinalizationTx {
+    pub hash: H256,
+    pub nonce: u64,
+    pub raw_tx: Vec<u8>,
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+    /// L1 block number at the time the transaction was signed.
+    pub created_at_block: u64,
+}
+
+/// Withdrawal with a finalization transaction sent to L1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentWithdrawal {
+    pub l1_batch_number: L1BatchNumber,
+    pub l2_message_index: u32,
+    /// Hash of the latest finalization transaction.
+    pub finalize_tx_hash: H256,
+    pub nonce: u64,
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+    pub created_at_block: u64,
+    /// Hashes of all finalization transactions for the withdrawal, starting from the latest one.
+    /// All transactions have the same nonce, so at most one of them can be included into an L1 block.
+    pub tx_hashes: Vec<H256>,
+}
+
+/// Persists withdrawals tracked by the withdrawal finalizer and the accounting data for their finalization.
+#[derive(Debug)]
+pub struct WithdrawalFinalizerDal<'a, 'c> {
+    pub(crate) storage: &'a mut StorageProcessor<'c>,
+}
+
+impl WithdrawalFinalizerDal<'_, '_> {
+    /// Returns the last L1 batch scanned for withdrawals, or `None` if no L1 batches were scanned yet.
+    pub async fn get_last_processed_l1_batch(&mut self) -> sqlx::Result<Option<L1BatchNumber>> {
+        let row = sqlx::query!("SELECT last_processed_l1_batch FROM withdrawal_finalizer_cursor")
+            .instrument("get_last_processed_l1_batch")
+            .fetch_optional(self.storage.conn())
+            .await?;
+        Ok(row.map(|row| L1BatchNumber(row.last_processed_l1_batch as u32)))
+    }
+
+    /// Saves withdrawals found in the specified L1 batch and marks the L1 batch as scanned.
+    pub async fn insert_withdrawals(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        withdrawals: &[Withdrawal],
+    ) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        for withdrawal in withdrawals {
+            let merkle_proof: Vec<_> = withdrawal
+                .merkle_proof
+                .iter()
+                .map(|hash| hash.as_bytes().to_vec())
+                .collect();
+            sqlx::query!(
+                "INSERT INTO finalizer_withdrawals \
+                    (l1_batch_number, l2_message_index, l2_tx_number_in_batch, l2_tx_hash, l1_receiver, \
+                    l1_token, amount, message, merkle_proof, status, created_at, updated_at) \
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'pending', now(), now()) \
+                ON CONFLICT (l1_batch_number, l2_message_index) DO NOTHING",
+                withdrawal.l1_batch_number.0 as i64,
+                withdrawal.l2_message_index as i32,
+                i32::from(withdrawal.l2_tx_number_in_batch),
+                withdrawal.l2_tx_hash.as_bytes(),
+                withdrawal.l1_receiver.as_bytes(),
+                withdrawal.l1_token.as_bytes(),
+                u256_to_big_decimal(withdrawal.amount),
+                &withdrawal.message,
+                &merkle_proof
+            )
+            .instrument("insert_withdrawals#insert")
+            .with_arg("l1_batch_number", &l1_batch_number)
+            .execute(transaction.conn())
+            .await?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO withdrawal_finalizer_cursor (id, last_processed_l1_batch, updated_at) \
+            VALUES (TRUE, $1, now()) \
+            ON CONFLICT (id) DO UPDATE \
+            SET last_processed_l1_batch = excluded.last_processed_l1_batch, updated_at = now()",
+            l1_batch_number.0 as i64
+        )
+        .instrument("insert_withdrawals#set_cursor")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Returns up to `limit` oldest withdrawals without a sent finalization transaction.
+    pub async fn get_pending_withdrawals(&mut self, limit: usize) -> sqlx::Result<Vec<Withdrawal>> {
+        let rows = sqlx::query!(
+            "SELECT l1_batch_number, l2_message_index, l2_tx_number_in_batch, l2_tx_hash, \
+                l1_receiver, l1_token, amount, message, merkle_proof \
+            FROM finalizer_withdrawals \
+            WHERE status = 'pending' \
+            ORDER BY l1_batch_number, l2_message_index \
+            LIMIT $1",
+            limit as i64
+        )
+        .instrument("get_pending_withdrawals")
+        .with_arg("limit", &limit)
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Withdrawal {
+                l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+                l2_message_index: row.l2_message_index as u32,
+                l2_tx_number_in_batch: row.l2_tx_number_in_batch as u16,
+                l2_tx_hash: H256::from_slice(&row.l2_tx_hash),
+                l1_receiver: Address::from_slice(&row.l1_receiver),
+                l1_token: Address::from_slice(&row.l1_token),
+                amount: bigdecimal_to_u256(row.amount),
+                message: row.message,
+                merkle_proof: row
+                    .merkle_proof
+                    .iter()
+                    .map(|hash| H256::from_slice(hash))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Returns the withdrawal with the specified ID, or `None` if it is not tracked.
+    pub async fn get_withdrawal(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        l2_message_index: u32,
+    ) -> sqlx::Result<Option<Withdrawal>> {
+        let row = sqlx::query!(
+            "SELECT l2_tx_number_in_batch, l2_tx_hash, l1_receiver, l1_token, amount, message, merkle_proof \
+            FROM finalizer_withdrawals \
+            WHERE l1_batch_number = $1 AND l2_message_index = $2",
+            l1_batch_number.0 as i64,
+            l2_message_index as i32
+        )
+        .instrument("get_withdrawal")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("l2_message_index", &l2_message_index)
+        .fetch_optional(self.storage.conn())
+        .await?;
+
+        Ok(row.map(|row| Withdrawal {
+            l1_batch_number,
+            l2_message_index,
+            l2_tx_number_in_batch: row.l2_tx_number_in_batch as u16,
+            l2_tx_hash: H256::from_slice(&row.l2_tx_hash),
+            l1_receiver: Address::from_slice(&row.l1_receiver),
+            l1_token: Address::from_slice(&row.l1_token),
+            amount: bigdecimal_to_u256(row.amount),
+            message: row.message,
+            merkle_proof: row
+                .merkle_proof
+                .iter()
+                .map(|hash| H256::from_slice(hash))
+                .collect(),
+        }))
+    }
+
+    /// Returns all withdrawals with a sent, but not yet confirmed finalization transaction.
+    pub async fn get_sent_withdrawals(&mut self) -> sqlx::Result<Vec<SentWithdrawal>> {
+        let rows = sqlx::query!(
+            "SELECT finalizer_withdrawals.l1_batch_number, finalizer_withdrawals.l2_message_index, \
+                latest_tx.tx_hash, latest_tx.nonce, latest_tx.max_fee_per_gas, \
+                latest_tx.max_priority_fee_per_gas, latest_tx.created_at_block, \
+                ARRAY( \
+                    SELECT txs.tx_hash FROM finalizer_withdrawal_txs AS txs \
+                    WHERE txs.l1_batch_number = finalizer_withdrawals.l1_batch_number \
+                        AND txs.l2_message_index = finalizer_withdrawals.l2_message_index \
+                    ORDER BY txs.created_at_block DESC, txs.max_fee_per_gas DESC \
+                ) AS \"tx_hashes!\" \
+            FROM finalizer_withdrawals \
+            JOIN finalizer_withdrawal_txs AS latest_tx \
+                ON latest_tx.tx_hash = finalizer_withdrawals.finalize_tx_hash \
+            WHERE finalizer_withdrawals.status = 'sent' \
+            ORDER BY finalizer_withdrawals.l1_batch_number, finalizer_withdrawals.l2_message_index"
+        )
+        .instrument("get_sent_withdrawals")
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SentWithdrawal {
+                l1_batch_number: L1BatchNumber(row.l1_batch_number as u32),
+                l2_message_index: row.l2_message_index as u32,
+                finalize_tx_hash: H256::from_slice(&row.tx_hash),
+                nonce: row.nonce as u64,
+                max_fee_per_gas: row.max_fee_per_gas as u64,
+                max_priority_fee_per_gas: row.max_priority_fee_per_gas as u64,
+                created_at_block: row.created_at_block as u64,
+                tx_hashes: row
+                    .tx_hashes
+                    .iter()
+                    .map(|hash| H256::from_slice(hash))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Returns the nonce following the nonces of all finalization transactions, or `None` if no transactions
+    /// were created yet.
+    pub async fn get_next_nonce(&mut self) -> sqlx::Result<Option<u64>> {
+        let row = sqlx::query!("SELECT MAX(nonce) AS max_nonce FROM finalizer_withdrawal_txs")
+            .instrument("get_next_nonce")
+            .fetch_one(self.storage.conn())
+            .await?;
+        Ok(row.max_nonce.map(|nonce| nonce as u64 + 1))
+    }
+
+    /// Persists a signed finalization transaction for a withdrawal and marks the withdrawal as sent. This must be done
+    /// *before* sending the transaction to L1, so that a transaction is never sent without being recorded.
+    /// If the withdrawal already has a finalization transaction (e.g., when bumping fees), the new transaction
+    /// becomes the latest one.
+    pub async fn save_finalization_tx(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        l2_message_index: u32,
+        tx: &FinalizationTx,
+    ) -> sqlx::Result<()> {
+        let mut transaction = self.storage.start_transaction().await?;
+        sqlx::query!(
+            "INSERT INTO finalizer_withdrawal_txs \
+                (tx_hash, l1_batch_number, l2_message_index, nonce, raw_tx, max_fee_per_gas, \
+                max_priority_fee_per_gas, created_at_block, created_at, updated_at) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now(), now())",
+            tx.hash.as_bytes(),
+            l1_batch_number.0 as i64,
+            l2_message_index as i32,
+            tx.nonce as i64,
+            &tx.raw_tx,
+            tx.max_fee_per_gas as i64,
+            tx.max_priority_fee_per_gas as i64,
+            tx.created_at_block as i64
+        )
+        .instrument("save_finalization_tx#insert")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("l2_message_index", &l2_message_index)
+        .with_arg("tx.hash", &tx.hash)
+        .execute(transaction.conn())
+        .await?;
+
+        sqlx::query!(
+            "UPDATE finalizer_withdrawals \
+            SET status = 'sent', finalize_tx_hash = $3, finalize_tx_nonce = $4, updated_at = now() \
+            WHERE l1_batch_number = $1 AND l2_message_index = $2",
+            l1_batch_number.0 as i64,
+            l2_message_index as i32,
+            tx.hash.as_bytes(),
+            tx.nonce as i64
+        )
+        .instrument("save_finalization_tx#update")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("l2_message_index", &l2_message_index)
+        .execute(transaction.conn())
+        .await?;
+
+        transaction.commit().await
+    }
+
+    /// Returns hashes and raw bytes of the latest finalization transactions for withdrawals in the `sent` status
+    /// that were persisted, but not successfully sent to L1, ordered by nonce.
+    pub async fn get_unsent_finalization_txs(&mut self) -> sqlx::Result<Vec<(H256, Vec<u8>)>> {
+        let rows = sqlx::query!(
+            "SELECT txs.tx_hash, txs.raw_tx FROM finalizer_withdrawal_txs AS txs \
+            JOIN finalizer_withdrawals ON finalizer_withdrawals.finalize_tx_hash = txs.tx_hash \
+            WHERE txs.sent_at_block IS NULL AND finalizer_withdrawals.status = 'sent' \
+            ORDER BY txs.nonce, txs.created_at"
+        )
+        .instrument("get_unsent_finalization_txs")
+        .fetch_all(self.storage.conn())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (H256::from_slice(&row.tx_hash), row.raw_tx))
+            .collect())
+    }
+
+    /// Records that the finalization transaction was successfully sent to L1.
+    pub async fn mark_finalization_tx_as_sent(
+        &mut self,
+        tx_hash: H256,
+        sent_at_block: u64,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "UPDATE finalizer_withdrawal_txs SET sent_at_block = $2, updated_at = now() \
+            WHERE tx_hash = $1",
+            tx_hash.as_bytes(),
+            sent_at_block as i64
+        )
+        .instrument("mark_finalization_tx_as_sent")
+        .with_arg("tx_hash", &tx_hash)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Records the outcome of a withdrawal finalization. `gas_used` and `fee_paid` must be specified
+    /// if the withdrawal was finalized by the finalizer (successfully or not).
+    pub async fn set_withdrawal_outcome(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        l2_message_index: u32,
+        status: WithdrawalStatus,
+        gas_used: Option<u64>,
+        fee_paid: Option<U256>,
+    ) -> sqlx::Result<()> {
+        sqlx::query!(
+            "UPDATE finalizer_withdrawals \
+            SET status = $3, gas_used = $4, fee_paid = $5, updated_at = now() \
+            WHERE l1_batch_number = $1 AND l2_message_index = $2",
+            l1_batch_number.0 as i64,
+            l2_message_index as i32,
+            status.as_str(),
+            gas_used.map(|gas| gas as i64),
+            fee_paid.map(u256_to_big_decimal)
+        )
+        .instrument("set_withdrawal_outcome")
+        .with_arg("l1_batch_number", &l1_batch_number)
+        .with_arg("l2_message_index", &l2_message_index)
+        .with_arg("status", &status)
+        .execute(self.storage.conn())
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the total fee paid for finalization transactions sent by the finalizer (including failed ones).
+    pub async fn get_total_fee_paid(&mut self) -> sqlx::Result<U256> {
+        let row = sqlx::query!("SELECT SUM(fee_paid) AS total FROM finalizer_withdrawals")
+            .instrument("get_total_fee_paid")
+            .fetch_one(self.storage.conn())
+            .await?;
+        Ok(row.total.map(bigdecimal_to_u256).unwrap_or_default())
+    }
+}
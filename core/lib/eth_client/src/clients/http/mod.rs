@@ -22,6 +22,7 @@ enum Method {
     BaseFeeHistory,
     #[metrics(name = "get_pending_block_base_fee_per_gas")]
     PendingBlockBaseFee,
+    BlobBaseFee,
     GetTxStatus,
     FailureReason,
     GetTx,
@@ -140,6 +140,16 @@ impl EthInterface for QueryClient {
         Ok(block.base_fee_per_gas.unwrap())
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        COUNTERS.call[&(Method::BlobBaseFee, component)].inc();
+        let latency = LATENCIES.direct[&Method::BlobBaseFee].start();
+        // `web3` doesn't support this method, so we call it directly.
+        let blob_base_fee =
+            CallFuture::new(self.web3.transport().execute("eth_blobBaseFee", vec![])).await?;
+        latency.observe();
+        Ok(blob_base_fee)
+    }
+
     async fn get_tx_status(
         &self,
         hash: H256,
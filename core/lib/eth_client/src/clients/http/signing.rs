@@ -63,6 +63,30 @@ impl PKSigningClient {
             L1ChainId(l1_chain_id),
         )
     }
+
+    /// Creates a client for an auxiliary L1 account (i.e., not the operator account) with the specified
+    /// private key. The client is bound to the zkSync diamond proxy contract.
+    pub fn from_private_key(
+        private_key: H256,
+        default_priority_fee_per_gas: U256,
+        contracts_config: &ContractsConfig,
+        eth_client: &ETHClientConfig,
+    ) -> Self {
+        let transport =
+            web3::transports::Http::new(&eth_client.web3_url).expect("Failed to create transport");
+        let address = PackedEthSignature::address_from_private_key(&private_key)
+            .expect("Failed to get address from private key");
+
+        SigningClient::new(
+            transport,
+            zksync_contract(),
+            address,
+            PrivateKeySigner::new(private_key),
+            contracts_config.diamond_proxy_addr,
+            default_priority_fee_per_gas,
+            L1ChainId(eth_client.chain_id),
+        )
+    }
 }
 
 /// Gas limit value to be used in transaction if for some reason
@@ -145,6 +169,10 @@ impl<S: EthereumSigner> EthInterface for SigningClient<S> {
             .await
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        self.query_client.get_blob_base_fee(component).await
+    }
+
     async fn get_tx_status(
         &self,
         hash: H256,
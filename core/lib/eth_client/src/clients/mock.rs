@@ -58,6 +58,7 @@ pub struct MockEthereum {
     pub block_number: AtomicU64,
     pub max_fee_per_gas: U256,
     pub base_fee_history: RwLock<Vec<u64>>,
+    pub blob_base_fee: AtomicU64,
     pub max_priority_fee_per_gas: U256,
     pub tx_statuses: RwLock<HashMap<H256, ExecutedTxStatus>>,
     pub sent_txs: RwLock<HashMap<H256, MockTx>>,
@@ -68,6 +69,10 @@ pub struct MockEthereum {
     /// This is useful for testing the cases when the transactions are executed out of order.
     pub non_ordering_confirmations: bool,
     pub multicall_address: Address,
+    /// Results returned from `call_contract_function()` for the specified function names.
+    pub contract_call_results: RwLock<HashMap<String, Token>>,
+    /// Logs returned from `logs()` regardless of the filter.
+    pub logs: RwLock<Vec<Log>>,
 }
 
 impl Default for MockEthereum {
@@ -77,6 +82,7 @@ impl Default for MockEthereum {
             max_priority_fee_per_gas: 10.into(),
             block_number: Default::default(),
             base_fee_history: Default::default(),
+            blob_base_fee: AtomicU64::new(1),
             tx_statuses: Default::default(),
             sent_txs: Default::default(),
             current_nonce: Default::default(),
@@ -84,6 +90,8 @@ impl Default for MockEthereum {
             nonces: RwLock::new([(0, 0)].into()),
             non_ordering_confirmations: false,
             multicall_address: Address::default(),
+            contract_call_results: Default::default(),
+            logs: Default::default(),
         }
     }
 }
@@ -182,6 +190,13 @@ impl MockEthereum {
         }
     }
 
+    pub fn with_blob_base_fee(self, blob_base_fee: u64) -> Self {
+        Self {
+            blob_base_fee: AtomicU64::new(blob_base_fee),
+            ..self
+        }
+    }
+
     pub fn with_non_ordering_confirmation(self, non_ordering_confirmations: bool) -> Self {
         Self {
             non_ordering_confirmations,
@@ -264,6 +279,10 @@ impl EthInterface for MockEthereum {
         ))
     }
 
+    async fn get_blob_base_fee(&self, _component: &'static str) -> Result<U256, Error> {
+        Ok(self.blob_base_fee.load(Ordering::SeqCst).into())
+    }
+
     async fn failure_reason(&self, tx_hash: H256) -> Result<Option<FailureInfo>, Error> {
         let tx_status = self.get_tx_status(tx_hash, "failure_reason").await.unwrap();
 
@@ -278,7 +297,7 @@ impl EthInterface for MockEthereum {
     #[allow(clippy::too_many_arguments)]
     async fn call_contract_function<R, A, B, P>(
         &self,
-        _func: &str,
+        func: &str,
         _params: P,
         _from: A,
         _options: Options,
@@ -309,6 +328,9 @@ impl EthInterface for MockEthereum {
             ]);
             return Ok(R::from_tokens(vec![token]).unwrap());
         }
+        if let Some(token) = self.contract_call_results.read().unwrap().get(func) {
+            return Ok(R::from_tokens(vec![token.clone()]).unwrap());
+        }
         Ok(R::from_tokens(vec![]).unwrap())
     }
 
@@ -337,15 +359,19 @@ impl EthInterface for MockEthereum {
     }
 
     async fn logs(&self, _filter: Filter, _component: &'static str) -> Result<Vec<Log>, Error> {
-        unimplemented!("Not needed right now")
+        Ok(self.logs.read().unwrap().clone())
     }
 
+    /// Returns a block with the current block number for any requested block.
     async fn block(
         &self,
         _block_id: String,
         _component: &'static str,
     ) -> Result<Option<Block<H256>>, Error> {
-        unimplemented!("Not needed right now")
+        Ok(Some(Block {
+            number: Some(self.block_number.load(Ordering::SeqCst).into()),
+            ..Block::default()
+        }))
     }
 }
 
@@ -444,6 +470,10 @@ impl<T: AsRef<MockEthereum> + Send + Sync> EthInterface for T {
             .await
     }
 
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error> {
+        self.as_ref().get_blob_base_fee(component).await
+    }
+
     async fn get_gas_price(&self, component: &'static str) -> Result<U256, Error> {
         self.as_ref().get_gas_price(component).await
     }
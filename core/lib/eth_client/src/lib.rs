@@ -63,6 +63,9 @@ pub trait EthInterface: Sync + Send {
         component: &'static str,
     ) -> Result<U256, Error>;
 
+    /// Returns the blob base fee (in wei per blob gas) for the next L1 block, as per EIP-4844.
+    async fn get_blob_base_fee(&self, component: &'static str) -> Result<U256, Error>;
+
     /// Returns the current gas price.
     async fn get_gas_price(&self, component: &'static str) -> Result<U256, Error>;
 
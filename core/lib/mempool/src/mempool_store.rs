@@ -131,11 +131,22 @@ impl MempoolStore {
                 .is_some()
     }
 
+    /// Returns `true` if the next L1 (priority) transaction is in the mempool.
+    pub fn has_next_l1_transaction(&self) -> bool {
+        self.l1_transactions.contains_key(&self.next_priority_id)
+    }
+
+    /// Returns the next L1 (priority) transaction for execution if it's in the mempool.
+    pub fn next_l1_transaction(&mut self) -> Option<Transaction> {
+        let transaction = self.l1_transactions.remove(&self.next_priority_id)?;
+        self.next_priority_id += 1;
+        Some(transaction.into())
+    }
+
     /// Returns next transaction for execution from mempool
     pub fn next_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
-        if let Some(transaction) = self.l1_transactions.remove(&self.next_priority_id) {
-            self.next_priority_id += 1;
-            return Some(transaction.into());
+        if let Some(transaction) = self.next_l1_transaction() {
+            return Some(transaction);
         }
 
         let mut removed = 0;
@@ -204,6 +215,12 @@ impl MempoolStore {
         }
     }
 
+    /// Removes all transactions from the mempool and resets the next expected priority operation ID.
+    /// Used when the node state is rolled back, so that the mempool can be re-populated from the storage.
+    pub fn reset(&mut self, next_priority_id: PriorityOpId) {
+        *self = Self::new(next_priority_id, self.capacity);
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         MempoolInfo {
             stashed_accounts: std::mem::take(&mut self.stashed_accounts),
@@ -136,6 +136,53 @@ fn l1_txns_priority_id() {
     }
 }
 
+#[test]
+fn getting_only_l1_txns() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    mempool.insert(vec![gen_l2_tx(account, Nonce(0))], HashMap::new());
+    assert!(!mempool.has_next_l1_transaction());
+    assert!(mempool.next_l1_transaction().is_none());
+
+    mempool.insert(vec![gen_l1_tx(PriorityOpId(0))], HashMap::new());
+    assert!(mempool.has_next_l1_transaction());
+    assert!(mempool.next_l1_transaction().unwrap().is_l1());
+    assert!(!mempool.has_next_l1_transaction());
+    // The L2 transaction is still in the mempool.
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account, 0)
+    );
+}
+
+#[test]
+fn resetting_mempool() {
+    let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
+    let account = Address::random();
+    mempool.insert(
+        vec![gen_l1_tx(PriorityOpId(0)), gen_l2_tx(account, Nonce(0))],
+        HashMap::new(),
+    );
+    assert!(mempool.next_transaction(&L2TxFilter::default()).is_some());
+
+    mempool.reset(PriorityOpId(0));
+    assert_eq!(mempool.stats().l2_transaction_count, 0);
+    assert_eq!(mempool.next_transaction(&L2TxFilter::default()), None);
+    // The reset mempool should accept the priority operation and the account nonce again.
+    mempool.insert(
+        vec![gen_l1_tx(PriorityOpId(0)), gen_l2_tx(account, Nonce(0))],
+        HashMap::new(),
+    );
+    assert!(mempool
+        .next_transaction(&L2TxFilter::default())
+        .unwrap()
+        .is_l1());
+    assert_eq!(
+        view(mempool.next_transaction(&L2TxFilter::default())),
+        (account, 0)
+    );
+}
+
 #[test]
 fn rejected_tx() {
     let mut mempool = MempoolStore::new(PriorityOpId(0), 100);
@@ -36,7 +36,7 @@ where
 {
     /// Creates a new Merkle tree from the supplied leaves. If `tree_size` is larger than the
     /// number of the supplied leaves, the remaining leaves are `[0_u8; LEAF_SIZE]`.
-    /// The hash function used in keccak-256.
+    /// The hash function used is keccak-256.
     ///
     /// # Panics
     ///
@@ -102,6 +102,32 @@ impl<'a, const LEAF_SIZE: usize> MiniMerkleTree<'a, LEAF_SIZE> {
         (root_hash, merkle_path)
     }
 
+    /// Computes hashes of all non-empty nodes of this tree. Unlike the tree itself, the returned layers
+    /// allow obtaining Merkle paths without rehashing the tree.
+    pub fn into_layers(self) -> MiniMerkleTreeLayers<'a, LEAF_SIZE> {
+        let depth = tree_depth_by_size(self.tree_size);
+        let mut layers = Vec::with_capacity(depth + 1);
+        let mut hashes = self.hashes;
+        for level in 0..depth {
+            let empty_hash_at_level = self.hasher.empty_subtree_hash(level);
+            let next_hashes = hashes
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&empty_hash_at_level);
+                    self.hasher.compress(&pair[0], right)
+                })
+                .collect();
+            layers.push(hashes);
+            hashes = next_hashes;
+        }
+        layers.push(hashes);
+
+        MiniMerkleTreeLayers {
+            hasher: self.hasher,
+            layers,
+        }
+    }
+
     fn compute_merkle_root_and_path(
         self,
         mut index: usize,
@@ -142,6 +168,121 @@ impl<'a, const LEAF_SIZE: usize> MiniMerkleTree<'a, LEAF_SIZE> {
     }
 }
 
+/// Hashes of all non-empty nodes of a [`MiniMerkleTree`] grouped by tree level. Allows obtaining
+/// the Merkle path for any leaf in O(log n) without rehashing the tree.
+#[derive(Debug, Clone)]
+pub struct MiniMerkleTreeLayers<'a, const LEAF_SIZE: usize> {
+    hasher: &'a dyn HashEmptySubtree<LEAF_SIZE>,
+    /// Layers starting from leaf hashes; the last layer contains the root hash only
+    /// (or is empty if the tree has no leaves).
+    layers: Vec<Box<[H256]>>,
+}
+
+impl<const LEAF_SIZE: usize> MiniMerkleTreeLayers<'static, LEAF_SIZE>
+where
+    KeccakHasher: HashEmptySubtree<LEAF_SIZE>,
+{
+    /// Restores tree layers previously obtained via [`Self::layers()`]. The hash function used is keccak-256.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layers are inconsistent, i.e., if the tree is deeper than supported,
+    /// or if the length of a layer doesn't correspond to the length of the previous layer.
+    pub fn new(layers: Vec<Vec<H256>>) -> Result<Self, InvalidLayersError> {
+        if layers.is_empty() || layers.len() > MAX_TREE_DEPTH + 1 {
+            return Err(InvalidLayersError::LayerCount(layers.len()));
+        }
+        for (level, pair) in layers.windows(2).enumerate() {
+            if pair[1].len() != (pair[0].len() + 1) / 2 {
+                return Err(InvalidLayersError::LayerLength {
+                    level: level + 1,
+                    len: pair[1].len(),
+                });
+            }
+        }
+        let root_layer_len = layers[layers.len() - 1].len();
+        if root_layer_len > 1 {
+            return Err(InvalidLayersError::RootLayer(root_layer_len));
+        }
+
+        Ok(Self {
+            hasher: &KeccakHasher,
+            layers: layers.into_iter().map(Vec::into_boxed_slice).collect(),
+        })
+    }
+}
+
+/// Error returned by [`MiniMerkleTreeLayers::new()`] if the supplied layers are inconsistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidLayersError {
+    /// The number of layers is zero or exceeds the maximum supported tree depth.
+    LayerCount(usize),
+    /// The length of a layer doesn't correspond to the length of the previous layer.
+    LayerLength {
+        /// Tree level of the layer (0 for leaf hashes).
+        level: usize,
+        /// Length of the layer.
+        len: usize,
+    },
+    /// The root layer contains more than one hash.
+    RootLayer(usize),
+}
+
+impl fmt::Display for InvalidLayersError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LayerCount(count) => write!(formatter, "invalid number of layers: {count}"),
+            Self::LayerLength { level, len } => {
+                write!(formatter, "invalid length of layer #{level}: {len}")
+            }
+            Self::RootLayer(len) => write!(formatter, "invalid root layer length: {len}"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidLayersError {}
+
+impl<const LEAF_SIZE: usize> MiniMerkleTreeLayers<'_, LEAF_SIZE> {
+    /// Returns hashes of non-empty nodes grouped by tree level, starting from leaf hashes.
+    pub fn layers(&self) -> impl Iterator<Item = &[H256]> + '_ {
+        self.layers.iter().map(|layer| &**layer)
+    }
+
+    /// Returns the number of leaves in the tree, not counting empty leaves.
+    pub fn leaf_count(&self) -> usize {
+        self.layers[0].len()
+    }
+
+    /// Returns the root hash of the tree.
+    pub fn merkle_root(&self) -> H256 {
+        // TODO (SMA-184): change constant to the real root hash of empty merkle tree.
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the root hash and the Merkle proof for a leaf with the specified 0-based `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn merkle_root_and_path(&self, mut index: usize) -> (H256, Vec<H256>) {
+        assert!(index < self.leaf_count(), "invalid tree leaf index");
+
+        let depth = self.layers.len() - 1;
+        let merkle_path = (0..depth)
+            .map(|level| {
+                let adjacent_hash = self.layers[level].get(index ^ 1).copied();
+                index /= 2;
+                adjacent_hash.unwrap_or_else(|| self.hasher.empty_subtree_hash(level))
+            })
+            .collect();
+        (self.merkle_root(), merkle_path)
+    }
+}
+
 fn tree_depth_by_size(tree_size: usize) -> usize {
     debug_assert!(tree_size.is_power_of_two());
     tree_size.trailing_zeros() as usize
@@ -175,3 +175,52 @@ fn merkle_proofs_are_valid_in_larger_tree() {
         verify_merkle_proof(&item, i, 512, &path, merkle_root);
     }
 }
+
+#[test]
+fn tree_layers_produce_same_paths() {
+    for len in [1, 2, 3, 5, 8, 13, 32] {
+        println!("checking tree with {len} items");
+        let leaves: Vec<_> = (1_u8..=len).map(|byte| [byte; 88]).collect();
+        let layers = MiniMerkleTree::new(leaves.iter().copied(), 32).into_layers();
+        assert_eq!(layers.leaf_count(), leaves.len());
+        for index in 0..leaves.len() {
+            let tree = MiniMerkleTree::new(leaves.iter().copied(), 32);
+            assert_eq!(
+                layers.merkle_root_and_path(index),
+                tree.merkle_root_and_path(index)
+            );
+        }
+
+        let tree = MiniMerkleTree::new(leaves.iter().copied(), 32);
+        assert_eq!(layers.merkle_root(), tree.merkle_root());
+        let restored_layers: Vec<_> = layers.layers().map(<[H256]>::to_vec).collect();
+        let restored_layers = MiniMerkleTreeLayers::<88>::new(restored_layers).unwrap();
+        assert_eq!(
+            restored_layers.merkle_root_and_path(0),
+            layers.merkle_root_and_path(0)
+        );
+    }
+}
+
+#[test]
+fn layers_of_empty_tree() {
+    let layers = MiniMerkleTree::new(iter::empty::<[u8; 88]>(), 32).into_layers();
+    assert_eq!(layers.leaf_count(), 0);
+    assert_eq!(layers.merkle_root(), H256::zero());
+    assert_eq!(layers.layers().count(), 6);
+}
+
+#[test]
+fn restoring_invalid_layers() {
+    let err = MiniMerkleTreeLayers::<88>::new(vec![]).unwrap_err();
+    assert_eq!(err, InvalidLayersError::LayerCount(0));
+    let err = MiniMerkleTreeLayers::<88>::new(vec![vec![]; MAX_TREE_DEPTH + 2]).unwrap_err();
+    assert_eq!(err, InvalidLayersError::LayerCount(MAX_TREE_DEPTH + 2));
+
+    let leaves = vec![H256::repeat_byte(1); 3];
+    let err =
+        MiniMerkleTreeLayers::<88>::new(vec![leaves.clone(), vec![H256::zero()]]).unwrap_err();
+    assert_eq!(err, InvalidLayersError::LayerLength { level: 1, len: 1 });
+    let err = MiniMerkleTreeLayers::<88>::new(vec![leaves]).unwrap_err();
+    assert_eq!(err, InvalidLayersError::RootLayer(3));
+}
@@ -32,6 +32,7 @@ impl GlueFrom<vm_m5::vm::VmBlockResult> for vm_latest::FinishedL1Batch {
             },
             final_execution_state: CurrentExecutionState {
                 events: value.full_result.events,
+                deduplicated_events_logs: vec![],
                 storage_log_queries: value.full_result.storage_log_queries,
                 used_contract_hashes: value.full_result.used_contract_hashes,
                 l2_to_l1_logs: value.full_result.l2_to_l1_logs,
@@ -65,6 +66,7 @@ impl GlueFrom<vm_m6::vm::VmBlockResult> for vm_latest::FinishedL1Batch {
             },
             final_execution_state: CurrentExecutionState {
                 events: value.full_result.events,
+                deduplicated_events_logs: vec![],
                 storage_log_queries: value.full_result.storage_log_queries,
                 used_contract_hashes: value.full_result.used_contract_hashes,
                 l2_to_l1_logs: value.full_result.l2_to_l1_logs,
@@ -98,6 +100,7 @@ impl GlueFrom<vm_1_3_2::vm::VmBlockResult> for vm_latest::FinishedL1Batch {
             },
             final_execution_state: CurrentExecutionState {
                 events: value.full_result.events,
+                deduplicated_events_logs: vec![],
                 storage_log_queries: value.full_result.storage_log_queries,
                 used_contract_hashes: value.full_result.used_contract_hashes,
                 l2_to_l1_logs: value.full_result.l2_to_l1_logs,
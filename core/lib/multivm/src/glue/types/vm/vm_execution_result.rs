@@ -4,6 +4,7 @@ impl GlueFrom<vm_m5::vm::VmExecutionResult> for vm_latest::CurrentExecutionState
     fn glue_from(value: vm_m5::vm::VmExecutionResult) -> Self {
         Self {
             events: value.events,
+            deduplicated_events_logs: vec![],
             storage_log_queries: value.storage_log_queries,
             used_contract_hashes: value.used_contract_hashes,
             l2_to_l1_logs: value.l2_to_l1_logs,
@@ -17,6 +18,7 @@ impl GlueFrom<vm_m6::vm::VmExecutionResult> for vm_latest::CurrentExecutionState
     fn glue_from(value: vm_m6::vm::VmExecutionResult) -> Self {
         Self {
             events: value.events,
+            deduplicated_events_logs: vec![],
             storage_log_queries: value.storage_log_queries,
             used_contract_hashes: value.used_contract_hashes,
             l2_to_l1_logs: value.l2_to_l1_logs,
@@ -30,6 +32,7 @@ impl GlueFrom<vm_1_3_2::VmExecutionResult> for vm_latest::CurrentExecutionState
     fn glue_from(value: vm_1_3_2::VmExecutionResult) -> Self {
         Self {
             events: value.events,
+            deduplicated_events_logs: vec![],
             storage_log_queries: value.storage_log_queries,
             used_contract_hashes: value.used_contract_hashes,
             l2_to_l1_logs: value.l2_to_l1_logs,
@@ -1,7 +1,10 @@
 use async_trait::async_trait;
-use tokio::{fs, io};
+use tokio::{
+    fs, io,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
 
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::Range};
 
 use crate::raw::{Bucket, ObjectStore, ObjectStoreError};
 
@@ -32,6 +35,8 @@ impl FileBackedObjectStore {
             Bucket::NodeAggregationWitnessJobsFri,
             Bucket::SchedulerWitnessJobsFri,
             Bucket::ProofsFri,
+            Bucket::ExecutionProfiles,
+            Bucket::ProofUploads,
         ] {
             let bucket_path = format!("{base_dir}/{bucket}");
             fs::create_dir_all(&bucket_path)
@@ -55,6 +60,20 @@ impl ObjectStore for FileBackedObjectStore {
         fs::read(filename).await.map_err(From::from)
     }
 
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError> {
+        let filename = self.filename(bucket, key);
+        let mut file = fs::File::open(filename).await?;
+        file.seek(io::SeekFrom::Start(range.start)).await?;
+        let mut buffer = vec![0; (range.end - range.start) as usize];
+        file.read_exact(&mut buffer).await?;
+        Ok(buffer)
+    }
+
     async fn put_raw(
         &self,
         bucket: Bucket,
@@ -94,6 +113,27 @@ mod test {
         assert_eq!(expected, bytes, "expected didn't match");
     }
 
+    #[tokio::test]
+    async fn test_get_range() {
+        let dir = TempDir::new("test-data").unwrap();
+        let path = dir.into_path().into_os_string().into_string().unwrap();
+        let object_store = FileBackedObjectStore::new(path).await;
+        object_store
+            .put_raw(Bucket::ProverJobs, "test-key.bin", vec![9, 0, 8, 9, 0, 7])
+            .await
+            .unwrap();
+        let bytes = object_store
+            .get_raw_range(Bucket::ProverJobs, "test-key.bin", 1..4)
+            .await
+            .unwrap();
+        assert_eq!(bytes, [0, 8, 9]);
+
+        let result = object_store
+            .get_raw_range(Bucket::ProverJobs, "test-key.bin", 4..10)
+            .await;
+        assert!(result.is_err(), "out-of-bounds range must not be served");
+    }
+
     #[tokio::test]
     async fn test_put() {
         let dir = TempDir::new("test-data").unwrap();
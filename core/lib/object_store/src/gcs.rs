@@ -14,7 +14,7 @@ use google_cloud_storage::{
 };
 use http::StatusCode;
 
-use std::{fmt, future::Future, time::Duration};
+use std::{fmt, future::Future, ops, time::Duration};
 
 use crate::{
     metrics::GCS_METRICS,
@@ -171,6 +171,50 @@ impl ObjectStore for GoogleCloudStorage {
         blob.map_err(ObjectStoreError::from)
     }
 
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: ops::Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError> {
+        if range.is_empty() {
+            return Ok(vec![]);
+        }
+        let fetch_latency = GCS_METRICS.start_fetch(bucket);
+        let filename = Self::filename(bucket.as_str(), key);
+        tracing::trace!(
+            "Fetching range {range:?} from GCS for key {filename} from bucket {}",
+            self.bucket_prefix
+        );
+
+        let request = GetObjectRequest {
+            bucket: self.bucket_prefix.clone(),
+            object: filename,
+            ..GetObjectRequest::default()
+        };
+        // The end of the GCS range is inclusive.
+        let expected_len = range.end - range.start;
+        let gcs_range = Range(Some(range.start), Some(range.end - 1));
+        let blob = retry(self.max_retries, || {
+            self.client.download_object(&request, &gcs_range)
+        })
+        .await;
+
+        let elapsed = fetch_latency.observe();
+        tracing::trace!(
+            "Fetched range {range:?} from GCS for key {key} from bucket {bucket} and it took: {elapsed:?}"
+        );
+        let blob = blob.map_err(ObjectStoreError::from)?;
+        if blob.len() as u64 != expected_len {
+            let error_message = format!(
+                "range {range:?} is out of bounds for key {key}: got {} bytes",
+                blob.len()
+            );
+            return Err(ObjectStoreError::Other(error_message.into()));
+        }
+        Ok(blob)
+    }
+
     async fn put_raw(
         &self,
         bucket: Bucket,
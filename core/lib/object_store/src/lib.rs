@@ -28,6 +28,7 @@ mod gcs;
 mod metrics;
 mod mock;
 mod objects;
+mod prefixed;
 mod raw;
 
 // Re-export `bincode` crate so that client binaries can conveniently use it.
@@ -3,7 +3,7 @@
 use async_trait::async_trait;
 use tokio::sync::Mutex;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use crate::raw::{Bucket, ObjectStore, ObjectStoreError};
 
@@ -25,6 +25,20 @@ impl ObjectStore for MockStore {
         })
     }
 
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError> {
+        let bytes = self.get_raw(bucket, key).await?;
+        let chunk = bytes.get(range.start as usize..range.end as usize);
+        chunk.map(<[u8]>::to_vec).ok_or_else(|| {
+            let error_message = format!("range {range:?} is out of bounds for key: {key}");
+            ObjectStoreError::Other(error_message.into())
+        })
+    }
+
     async fn put_raw(
         &self,
         bucket: Bucket,
@@ -0,0 +1,101 @@
+//! Object store wrapper namespacing keys with a fixed prefix.
+
+use async_trait::async_trait;
+
+use std::ops::Range;
+
+use crate::raw::{Bucket, ObjectStore, ObjectStoreError};
+
+/// [`ObjectStore`] prepending a fixed prefix to all keys. Used to share a single bucket
+/// among several chains.
+#[derive(Debug)]
+pub(crate) struct PrefixedObjectStore {
+    inner: Box<dyn ObjectStore>,
+    prefix: String,
+}
+
+impl PrefixedObjectStore {
+    pub fn new(inner: Box<dyn ObjectStore>, prefix: String) -> Self {
+        Self { inner, prefix }
+    }
+
+    // `/` is not used as a separator since the file-backed store does not create nested directories.
+    fn prefixed_key(&self, key: &str) -> String {
+        format!("{}_{key}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for PrefixedObjectStore {
+    async fn get_raw(&self, bucket: Bucket, key: &str) -> Result<Vec<u8>, ObjectStoreError> {
+        self.inner.get_raw(bucket, &self.prefixed_key(key)).await
+    }
+
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError> {
+        self.inner
+            .get_raw_range(bucket, &self.prefixed_key(key), range)
+            .await
+    }
+
+    async fn put_raw(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        value: Vec<u8>,
+    ) -> Result<(), ObjectStoreError> {
+        self.inner
+            .put_raw(bucket, &self.prefixed_key(key), value)
+            .await
+    }
+
+    async fn remove_raw(&self, bucket: Bucket, key: &str) -> Result<(), ObjectStoreError> {
+        self.inner.remove_raw(bucket, &self.prefixed_key(key)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::mock::MockStore;
+
+    #[tokio::test]
+    async fn prefixed_stores_do_not_collide() {
+        let shared = Arc::new(MockStore::default());
+        let era = PrefixedObjectStore::new(Box::new(Arc::clone(&shared)), "era".to_owned());
+        let other = PrefixedObjectStore::new(Box::new(Arc::clone(&shared)), "other".to_owned());
+
+        era.put_raw(Bucket::WitnessInput, "1.bin", vec![1])
+            .await
+            .unwrap();
+        other
+            .put_raw(Bucket::WitnessInput, "1.bin", vec![2])
+            .await
+            .unwrap();
+        assert_eq!(
+            era.get_raw(Bucket::WitnessInput, "1.bin").await.unwrap(),
+            [1]
+        );
+        assert_eq!(
+            other.get_raw(Bucket::WitnessInput, "1.bin").await.unwrap(),
+            [2]
+        );
+        assert_eq!(
+            shared
+                .get_raw(Bucket::WitnessInput, "era_1.bin")
+                .await
+                .unwrap(),
+            [1]
+        );
+
+        era.remove_raw(Bucket::WitnessInput, "1.bin").await.unwrap();
+        assert!(era.get_raw(Bucket::WitnessInput, "1.bin").await.is_err());
+        assert!(other.get_raw(Bucket::WitnessInput, "1.bin").await.is_ok());
+    }
+}
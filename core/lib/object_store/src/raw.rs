@@ -1,9 +1,12 @@
 use anyhow::Context as _;
 use async_trait::async_trait;
 
-use std::{error, fmt, sync::Arc};
+use std::{error, fmt, ops::Range, sync::Arc};
 
-use crate::{file::FileBackedObjectStore, gcs::GoogleCloudStorage, mock::MockStore};
+use crate::{
+    file::FileBackedObjectStore, gcs::GoogleCloudStorage, mock::MockStore,
+    prefixed::PrefixedObjectStore,
+};
 use zksync_config::configs::object_store::ObjectStoreMode;
 use zksync_config::ObjectStoreConfig;
 
@@ -21,6 +24,8 @@ pub enum Bucket {
     NodeAggregationWitnessJobsFri,
     SchedulerWitnessJobsFri,
     ProofsFri,
+    ExecutionProfiles,
+    ProofUploads,
 }
 
 impl Bucket {
@@ -36,6 +41,8 @@ impl Bucket {
             Self::NodeAggregationWitnessJobsFri => "node_aggregation_witness_jobs_fri",
             Self::SchedulerWitnessJobsFri => "scheduler_witness_jobs_fri",
             Self::ProofsFri => "proofs_fri",
+            Self::ExecutionProfiles => "execution_profiles",
+            Self::ProofUploads => "proof_uploads",
         }
     }
 }
@@ -96,6 +103,20 @@ pub trait ObjectStore: fmt::Debug + Send + Sync {
     /// Returns an error if an object with the `key` does not exist or cannot be accessed.
     async fn get_raw(&self, bucket: Bucket, key: &str) -> Result<Vec<u8>, ObjectStoreError>;
 
+    /// Fetches the specified byte `range` of the value for the given key from the given bucket.
+    /// Allows serving large objects in parts without loading them into memory in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an object with the `key` does not exist or cannot be accessed,
+    /// or if the `range` is out of bounds for the object.
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError>;
+
     /// Stores the value associating it with the key into the given bucket.
     /// If the key already exists, the value is replaced.
     ///
@@ -123,6 +144,15 @@ impl<T: ObjectStore + ?Sized> ObjectStore for Arc<T> {
         (**self).get_raw(bucket, key).await
     }
 
+    async fn get_raw_range(
+        &self,
+        bucket: Bucket,
+        key: &str,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, ObjectStoreError> {
+        (**self).get_raw_range(bucket, key, range).await
+    }
+
     async fn put_raw(
         &self,
         bucket: Bucket,
@@ -193,10 +223,17 @@ impl ObjectStoreFactory {
         }
     }
 
-    /// Creates an [`ObjectStore`].
+    /// Creates an [`ObjectStore`]. If the configuration specifies a key prefix, all keys
+    /// of the returned store are namespaced with it.
     pub async fn create_store(&self) -> Box<dyn ObjectStore> {
         match &self.origin {
-            ObjectStoreOrigin::Config(config) => Self::create_from_config(config).await,
+            ObjectStoreOrigin::Config(config) => {
+                let store = Self::create_from_config(config).await;
+                match &config.key_prefix {
+                    Some(prefix) => Box::new(PrefixedObjectStore::new(store, prefix.clone())),
+                    None => store,
+                }
+            }
             ObjectStoreOrigin::Mock(store) => Box::new(Arc::clone(store)),
         }
     }
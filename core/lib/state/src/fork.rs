@@ -0,0 +1,405 @@
+//! Storage lazily fetching state from a remote node.
+
+use anyhow::Context as _;
+
+use std::{
+    collections::HashMap,
+    fmt, mem,
+    sync::{Arc, Mutex, RwLock},
+    thread,
+    time::Duration,
+};
+
+use zksync_types::{StorageKey, StorageValue, H256};
+
+use crate::ReadStorage;
+
+/// Source of the remote state for a [`ForkStorage`]. All values must correspond to the same (pinned)
+/// block of the remote node.
+pub trait ForkSource: fmt::Debug + Send + Sync {
+    /// Fetches the value of the specified storage slot.
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors communicating with the remote node.
+    fn get_storage_value(&self, key: &StorageKey) -> anyhow::Result<StorageValue>;
+
+    /// Fetches the bytecode with the specified hash.
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors communicating with the remote node.
+    fn get_bytecode(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+#[derive(Debug, Default)]
+struct ForkCache {
+    values: HashMap<StorageKey, StorageValue>,
+    factory_deps: HashMap<H256, Option<Vec<u8>>>,
+}
+
+/// Policy for retrying failed requests to a [`ForkSource`].
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    max_attempts: usize,
+    initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Calls `fetch` until it succeeds or the number of attempts is exhausted, doubling the backoff
+    /// after each failed attempt. Blocks the current thread while waiting.
+    fn retry<T>(&self, mut fetch: impl FnMut() -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match fetch() {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < self.max_attempts => {
+                    tracing::warn!(
+                        "Failed fetching data from fork (attempt {attempt}/{}): {err:#}; \
+                         retrying in {backoff:?}",
+                        self.max_attempts
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return Err(err.context(format!("failed after {attempt} attempts")));
+                }
+            }
+        }
+    }
+}
+
+/// Remote state together with the cache of fetched values. Since the remote state is pinned at a specific block,
+/// fetched values never become stale, and thus are cached indefinitely. Failed requests to the remote node
+/// are retried with exponential backoff.
+///
+/// This type is cheaply cloneable; all clones share the same cache.
+#[derive(Debug, Clone)]
+pub struct ForkedState {
+    source: Arc<dyn ForkSource>,
+    cache: Arc<RwLock<ForkCache>>,
+    retry_policy: RetryPolicy,
+}
+
+impl ForkedState {
+    /// Creates a forked state backed by the specified source.
+    pub fn new(source: Arc<dyn ForkSource>) -> Self {
+        Self {
+            source,
+            cache: Arc::default(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the retry policy for requests to the source. By default, requests are attempted 5 times
+    /// with the initial backoff of 200ms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_attempts` is zero.
+    #[must_use]
+    pub fn with_retries(mut self, max_attempts: usize, initial_backoff: Duration) -> Self {
+        assert!(max_attempts > 0, "at least one attempt must be allowed");
+        self.retry_policy = RetryPolicy {
+            max_attempts,
+            initial_backoff,
+        };
+        self
+    }
+
+    /// Reads the value of the specified storage slot from the remote state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not cached and cannot be fetched from the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cache lock is poisoned.
+    pub fn read_value(&self, key: &StorageKey) -> anyhow::Result<StorageValue> {
+        if let Some(value) = self.cache.read().unwrap().values.get(key) {
+            return Ok(*value);
+        }
+
+        let value = self
+            .retry_policy
+            .retry(|| self.source.get_storage_value(key))
+            .with_context(|| format!("failed fetching value for {key:?} from fork"))?;
+        self.cache.write().unwrap().values.insert(*key, value);
+        Ok(value)
+    }
+
+    /// Loads bytecode with the specified hash from the remote state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytecode is not cached and cannot be fetched from the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cache lock is poisoned.
+    pub fn load_factory_dep(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(bytecode) = self.cache.read().unwrap().factory_deps.get(&hash) {
+            return Ok(bytecode.clone());
+        }
+
+        let bytecode = self
+            .retry_policy
+            .retry(|| self.source.get_bytecode(hash))
+            .with_context(|| format!("failed fetching bytecode {hash:?} from fork"))?;
+        let mut cache = self.cache.write().unwrap();
+        cache.factory_deps.insert(hash, bytecode.clone());
+        Ok(bytecode)
+    }
+}
+
+/// Slot for an error encountered by a [`ForkStorage`]. Since [`ReadStorage`] methods are infallible,
+/// the storage records the first error fetching data from the fork and returns a default value; the code
+/// driving the VM must check the slot after execution and discard the execution result if it contains an error.
+///
+/// This type is cheaply cloneable; all clones share the same slot.
+#[derive(Debug, Clone, Default)]
+pub struct ForkErrorSlot(Arc<Mutex<Option<anyhow::Error>>>);
+
+impl ForkErrorSlot {
+    fn record(&self, err: anyhow::Error) {
+        tracing::error!("{err:#}");
+        let mut slot = self.0.lock().unwrap();
+        if slot.is_none() {
+            *slot = Some(err);
+        }
+    }
+
+    /// Checks whether an error was recorded, clearing the slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first recorded error, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot lock is poisoned.
+    pub fn check(&self) -> anyhow::Result<()> {
+        match mem::take(&mut *self.0.lock().unwrap()) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// [`ReadStorage`] implementation combining local storage with a [`ForkedState`]. Storage slots that were never
+/// written locally and bytecodes missing from the local storage are read from the remote state.
+///
+/// If no forked state is provided, the storage is a transparent wrapper around the local storage.
+#[derive(Debug)]
+pub struct ForkStorage<S> {
+    local: S,
+    fork: Option<ForkedState>,
+    errors: ForkErrorSlot,
+}
+
+impl<S: ReadStorage> ForkStorage<S> {
+    /// Creates a new storage on top of the specified local storage.
+    pub fn new(local: S, fork: Option<ForkedState>) -> Self {
+        Self {
+            local,
+            fork,
+            errors: ForkErrorSlot::default(),
+        }
+    }
+
+    /// Returns the slot for errors fetching data from the fork. It should be checked after each VM execution.
+    pub fn error_slot(&self) -> ForkErrorSlot {
+        self.errors.clone()
+    }
+
+    fn read_fork_value(&self, fork: &ForkedState, key: &StorageKey) -> StorageValue {
+        fork.read_value(key).unwrap_or_else(|err| {
+            self.errors.record(err);
+            StorageValue::zero()
+        })
+    }
+}
+
+impl<S: ReadStorage> ReadStorage for ForkStorage<S> {
+    fn read_value(&mut self, key: &StorageKey) -> StorageValue {
+        match &self.fork {
+            Some(fork) if self.local.is_write_initial(key) => self.read_fork_value(fork, key),
+            _ => self.local.read_value(key),
+        }
+    }
+
+    fn is_write_initial(&mut self, key: &StorageKey) -> bool {
+        let is_initial_locally = self.local.is_write_initial(key);
+        match &self.fork {
+            // The remote node does not expose whether a slot was ever written, so we treat zero slots
+            // as never written. This is imprecise for slots zeroed out after a write, but only influences
+            // pubdata accounting.
+            Some(fork) if is_initial_locally => self.read_fork_value(fork, key).is_zero(),
+            _ => is_initial_locally,
+        }
+    }
+
+    fn load_factory_dep(&mut self, hash: H256) -> Option<Vec<u8>> {
+        let local_bytecode = self.local.load_factory_dep(hash);
+        match &self.fork {
+            Some(fork) if local_bytecode.is_none() => {
+                fork.load_factory_dep(hash).unwrap_or_else(|err| {
+                    self.errors.record(err);
+                    None
+                })
+            }
+            _ => local_bytecode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use zksync_types::{AccountTreeId, Address};
+
+    use super::*;
+    use crate::InMemoryStorage;
+
+    #[derive(Debug, Default)]
+    struct MockForkSource {
+        values: HashMap<StorageKey, StorageValue>,
+        factory_deps: HashMap<H256, Vec<u8>>,
+        request_count: AtomicUsize,
+        /// Number of initial requests that fail.
+        failing_request_count: usize,
+    }
+
+    impl MockForkSource {
+        fn start_request(&self) -> anyhow::Result<()> {
+            let request_idx = self.request_count.fetch_add(1, Ordering::SeqCst);
+            if request_idx < self.failing_request_count {
+                anyhow::bail!("connection reset");
+            }
+            Ok(())
+        }
+    }
+
+    impl ForkSource for MockForkSource {
+        fn get_storage_value(&self, key: &StorageKey) -> anyhow::Result<StorageValue> {
+            self.start_request()?;
+            Ok(self.values.get(key).copied().unwrap_or_default())
+        }
+
+        fn get_bytecode(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+            self.start_request()?;
+            Ok(self.factory_deps.get(&hash).cloned())
+        }
+    }
+
+    fn storage_key(byte: u8) -> StorageKey {
+        StorageKey::new(
+            AccountTreeId::new(Address::repeat_byte(1)),
+            H256::repeat_byte(byte),
+        )
+    }
+
+    #[test]
+    fn fork_storage_basics() {
+        let source = Arc::new(MockForkSource {
+            values: HashMap::from([
+                (storage_key(1), H256::repeat_byte(0xaa)),
+                (storage_key(2), H256::repeat_byte(0xbb)),
+            ]),
+            factory_deps: HashMap::from([(H256::repeat_byte(1), vec![1; 32])]),
+            ..MockForkSource::default()
+        });
+        let fork = ForkedState::new(source.clone());
+
+        let mut local = InMemoryStorage::default();
+        // Overwritten values (including zeroed ones) must be read from the local storage.
+        local.set_value(storage_key(2), H256::zero());
+        local.set_value(storage_key(3), H256::repeat_byte(0xcc));
+        local.store_factory_dep(H256::repeat_byte(2), vec![2; 32]);
+        let mut storage = ForkStorage::new(local, Some(fork.clone()));
+
+        assert_eq!(storage.read_value(&storage_key(1)), H256::repeat_byte(0xaa));
+        assert_eq!(storage.read_value(&storage_key(2)), H256::zero());
+        assert_eq!(storage.read_value(&storage_key(3)), H256::repeat_byte(0xcc));
+        assert_eq!(storage.read_value(&storage_key(4)), H256::zero());
+        assert!(!storage.is_write_initial(&storage_key(1)));
+        assert!(!storage.is_write_initial(&storage_key(2)));
+        assert!(storage.is_write_initial(&storage_key(4)));
+
+        let bytecode = storage.load_factory_dep(H256::repeat_byte(1));
+        assert_eq!(bytecode.unwrap(), [1; 32]);
+        let bytecode = storage.load_factory_dep(H256::repeat_byte(2));
+        assert_eq!(bytecode.unwrap(), [2; 32]);
+        assert!(storage.load_factory_dep(H256::repeat_byte(3)).is_none());
+
+        // Fetched values should be cached and shared with other storages using the same fork.
+        let request_count = source.request_count.load(Ordering::SeqCst);
+        assert_eq!(request_count, 4);
+        let mut other_storage = ForkStorage::new(InMemoryStorage::default(), Some(fork));
+        assert_eq!(
+            other_storage.read_value(&storage_key(1)),
+            H256::repeat_byte(0xaa)
+        );
+        assert!(other_storage
+            .load_factory_dep(H256::repeat_byte(3))
+            .is_none());
+        assert_eq!(source.request_count.load(Ordering::SeqCst), request_count);
+    }
+
+    #[test]
+    fn transient_fork_errors_are_retried() {
+        let source = Arc::new(MockForkSource {
+            values: HashMap::from([(storage_key(1), H256::repeat_byte(0xaa))]),
+            failing_request_count: 2,
+            ..MockForkSource::default()
+        });
+        let fork = ForkedState::new(source.clone()).with_retries(3, Duration::ZERO);
+        let mut storage = ForkStorage::new(InMemoryStorage::default(), Some(fork));
+        let errors = storage.error_slot();
+
+        assert_eq!(storage.read_value(&storage_key(1)), H256::repeat_byte(0xaa));
+        errors.check().unwrap();
+        assert_eq!(source.request_count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn persistent_fork_errors_are_recorded() {
+        let source = Arc::new(MockForkSource {
+            values: HashMap::from([(storage_key(1), H256::repeat_byte(0xaa))]),
+            factory_deps: HashMap::from([(H256::repeat_byte(1), vec![1; 32])]),
+            failing_request_count: usize::MAX,
+            ..MockForkSource::default()
+        });
+        let fork = ForkedState::new(source.clone()).with_retries(3, Duration::ZERO);
+        let mut storage = ForkStorage::new(InMemoryStorage::default(), Some(fork.clone()));
+        let errors = storage.error_slot();
+
+        assert_eq!(storage.read_value(&storage_key(1)), H256::zero());
+        assert!(storage.load_factory_dep(H256::repeat_byte(1)).is_none());
+        assert_eq!(source.request_count.load(Ordering::SeqCst), 6);
+        let err = errors.check().unwrap_err();
+        let err = format!("{err:#}");
+        assert!(err.contains("failed fetching value"), "{err}");
+        assert!(err.contains("connection reset"), "{err}");
+        // The slot is cleared after the check.
+        errors.check().unwrap();
+
+        // Failed fetches must not be cached.
+        let err = fork.read_value(&storage_key(1)).unwrap_err();
+        assert!(format!("{err:#}").contains("failed after 3 attempts"));
+    }
+}
@@ -18,6 +18,7 @@ use zksync_types::{
 };
 
 mod cache;
+mod fork;
 mod in_memory;
 mod postgres;
 mod rocksdb;
@@ -28,9 +29,10 @@ mod test_utils;
 mod witness;
 
 pub use self::{
+    fork::{ForkErrorSlot, ForkSource, ForkStorage, ForkedState},
     in_memory::{InMemoryStorage, IN_MEMORY_STORAGE_DEFAULT_NETWORK_ID},
     postgres::{PostgresStorage, PostgresStorageCaches},
-    rocksdb::RocksdbStorage,
+    rocksdb::{RocksdbCatchUpMode, RocksdbSecondaryStorage, RocksdbStorage},
     shadow_storage::ShadowStorage,
     storage_view::{StorageView, StorageViewMetrics},
     witness::WitnessStorage,
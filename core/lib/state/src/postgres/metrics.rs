@@ -26,6 +26,10 @@ pub(super) struct ValuesCacheMetrics {
     pub values_update_modified_keys: Histogram<usize>,
     /// Current miniblock for the values cache.
     pub values_valid_for_miniblock: Gauge<u64>,
+    /// Number of storage values read from the RocksDB secondary instance instead of Postgres.
+    pub secondary_rocksdb_hits: Counter,
+    /// Latest miniblock for which the RocksDB secondary instance can serve storage values.
+    pub secondary_rocksdb_valid_for_miniblock: Gauge<u64>,
     /// Number of times the negative initial writes cache was successfully used. This is distinct
     /// from cache hits (we can hit the cache, but the cached value may be outdated).
     pub effective_values: Counter,
@@ -1,7 +1,10 @@
+use anyhow::Context as _;
 use tokio::{runtime::Handle, sync::mpsc};
 
 use std::{
+    collections::HashSet,
     mem,
+    ops::RangeInclusive,
     sync::{Arc, RwLock},
 };
 
@@ -15,7 +18,7 @@ mod tests;
 use self::metrics::{Method, ValuesUpdateStage, CACHE_METRICS, STORAGE_METRICS};
 use crate::{
     cache::{Cache, CacheValue},
-    ReadStorage,
+    ReadStorage, RocksdbSecondaryStorage,
 };
 
 /// Type alias for smart contract source code cache.
@@ -140,6 +143,19 @@ impl ValuesCache {
         }
     }
 
+    /// Resets the cache to an earlier miniblock after reverting the following miniblocks.
+    fn reset(&self, valid_for: MiniblockNumber) {
+        let mut lock = self.0.write().expect("values cache is poisoned");
+        lock.valid_for = valid_for;
+        lock.values.clear();
+        drop(lock);
+
+        CACHE_METRICS.values_emptied.inc();
+        CACHE_METRICS
+            .values_valid_for_miniblock
+            .set(u64::from(valid_for.0));
+    }
+
     #[allow(clippy::cast_precision_loss)] // acceptable for metrics
     fn update(
         &self,
@@ -162,7 +178,10 @@ impl ValuesCache {
                  requested update to {to_miniblock}); resetting the cache"
             );
             let mut lock = self.0.write().expect("values cache is poisoned");
-            assert_eq!(lock.valid_for, from_miniblock);
+            if lock.valid_for != from_miniblock {
+                tracing::info!("Storage values cache was reset during update; skipping update");
+                return;
+            }
             lock.valid_for = to_miniblock;
             lock.values.clear();
 
@@ -192,7 +211,10 @@ impl ValuesCache {
             // The code below holding onto the write `lock` is the only code that can theoretically poison the `RwLock`
             // (other than emptying the cache above). Thus, it's kept as simple and tight as possible.
             // E.g., we load data from Postgres beforehand.
-            assert_eq!(lock.valid_for, from_miniblock);
+            if lock.valid_for != from_miniblock {
+                tracing::info!("Storage values cache was reset during update; skipping update");
+                return;
+            }
             lock.valid_for = to_miniblock;
             for modified_key in &modified_keys {
                 lock.values.remove(modified_key);
@@ -207,6 +229,182 @@ impl ValuesCache {
     }
 }
 
+#[derive(Debug, Default)]
+struct SecondaryRocksdbInner {
+    /// Miniblocks for which RocksDB values are valid, except for `modified_keys`. The range starts
+    /// from the last miniblock of the last L1 batch processed by RocksDB. `None` if the range is unknown
+    /// (e.g., if Postgres doesn't have the L1 batch processed by RocksDB yet).
+    valid_for: Option<RangeInclusive<MiniblockNumber>>,
+    /// Hashed keys modified in `valid_for` miniblocks after the first one.
+    modified_keys: HashSet<H256>,
+    /// Number of times the state was reset. Allows the update task to detect resets made while it was running.
+    resets: u64,
+}
+
+/// Latest VM state served from a RocksDB secondary instance of the state keeper cache. RocksDB only
+/// contains state as of the end of an L1 batch, so the keys modified in the later miniblocks are tracked
+/// and read from Postgres.
+///
+/// Similarly to [`ValuesCache`], the state is wrapped in an `RwLock`; the read lock is held while
+/// reading from RocksDB. Before RocksDB catches up with the primary instance, the state is invalidated,
+/// so that values are read from Postgres while the catch-up is in progress; the catch-up itself
+/// doesn't hold the lock.
+#[derive(Debug, Clone)]
+struct SecondaryRocksdb {
+    storage: RocksdbSecondaryStorage,
+    inner: Arc<RwLock<SecondaryRocksdbInner>>,
+}
+
+impl SecondaryRocksdb {
+    fn new(storage: RocksdbSecondaryStorage) -> Self {
+        Self {
+            storage,
+            inner: Arc::default(),
+        }
+    }
+
+    /// *NB.* The returned value should be considered immediately stale.
+    fn valid_until(&self) -> Option<MiniblockNumber> {
+        let lock = self
+            .inner
+            .read()
+            .expect("secondary RocksDB state is poisoned");
+        lock.valid_for.as_ref().map(|range| *range.end())
+    }
+
+    /// Reads the value for `key` provided that RocksDB contains a valid value for `miniblock_number`.
+    fn get(&self, miniblock_number: MiniblockNumber, key: &StorageKey) -> Option<StorageValue> {
+        let lock = self
+            .inner
+            .read()
+            .expect("secondary RocksDB state is poisoned");
+        let valid_for = lock.valid_for.as_ref()?;
+        if !valid_for.contains(&miniblock_number) || lock.modified_keys.contains(&key.hashed_key())
+        {
+            return None;
+        }
+        let value = self.storage.read_value(key);
+        drop(lock);
+
+        CACHE_METRICS.secondary_rocksdb_hits.inc();
+        Some(value)
+    }
+
+    /// Invalidates the state after reverting miniblocks. Until the next update, all values are read from Postgres.
+    fn reset(&self) {
+        let mut lock = self
+            .inner
+            .write()
+            .expect("secondary RocksDB state is poisoned");
+        let resets = lock.resets + 1;
+        *lock = SecondaryRocksdbInner {
+            resets,
+            ..SecondaryRocksdbInner::default()
+        };
+    }
+
+    fn update(
+        &self,
+        to_miniblock: MiniblockNumber,
+        rt_handle: &Handle,
+        connection: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<()> {
+        let (mut valid_for, resets) = {
+            let lock = self
+                .inner
+                .read()
+                .expect("secondary RocksDB state is poisoned");
+            (lock.valid_for.clone(), lock.resets)
+        };
+
+        let prev_l1_batch_number = self.storage.l1_batch_number();
+        let sealed_l1_batch_number = rt_handle
+            .block_on(connection.blocks_dal().get_sealed_l1_batch_number())
+            .context("failed getting sealed L1 batch number")?;
+        if sealed_l1_batch_number >= prev_l1_batch_number {
+            // The primary instance may have processed a new L1 batch. Catching up changes RocksDB contents,
+            // so the state is invalidated beforehand and is restored below if the L1 batch didn't change.
+            self.inner
+                .write()
+                .expect("secondary RocksDB state is poisoned")
+                .valid_for = None;
+            self.storage.catch_up()?;
+            if self.storage.l1_batch_number() != prev_l1_batch_number {
+                valid_for = None;
+            }
+        }
+        let l1_batch_number = self.storage.l1_batch_number();
+
+        let (start_miniblock, first_unknown_miniblock) = if let Some(valid_for) = &valid_for {
+            (*valid_for.start(), *valid_for.end() + 1)
+        } else {
+            let Some(last_l1_batch_number) = l1_batch_number.0.checked_sub(1) else {
+                return Ok(()); // RocksDB is empty
+            };
+            let miniblock_range = rt_handle
+                .block_on(
+                    connection
+                        .blocks_dal()
+                        .get_miniblock_range_of_l1_batch(L1BatchNumber(last_l1_batch_number)),
+                )
+                .context("failed getting miniblock range for L1 batch")?;
+            let Some((_, last_miniblock)) = miniblock_range else {
+                tracing::debug!(
+                    "L1 batch #{last_l1_batch_number} processed by secondary RocksDB is not in Postgres yet"
+                );
+                return Ok(());
+            };
+            if to_miniblock < last_miniblock {
+                return Ok(());
+            }
+            (last_miniblock, last_miniblock + 1)
+        };
+
+        let modified_keys = if first_unknown_miniblock <= to_miniblock {
+            rt_handle.block_on(
+                connection
+                    .storage_web3_dal()
+                    .modified_keys_in_miniblocks(first_unknown_miniblock..=to_miniblock),
+            )
+        } else {
+            vec![]
+        };
+        tracing::debug!(
+            "Updating secondary RocksDB state to miniblocks {start_miniblock}..={to_miniblock} \
+             with {} newly modified keys",
+            modified_keys.len()
+        );
+
+        let mut lock = self
+            .inner
+            .write()
+            .expect("secondary RocksDB state is poisoned");
+        // This is the only thread updating the state, so it can only change in the meantime if it was reset.
+        if lock.resets != resets {
+            tracing::info!("Secondary RocksDB state was reset during update; skipping update");
+            return Ok(());
+        }
+        if valid_for.is_none() {
+            lock.modified_keys.clear();
+        }
+        lock.modified_keys.extend(modified_keys);
+        let valid_until = valid_for.map_or(to_miniblock, |range| to_miniblock.max(*range.end()));
+        lock.valid_for = Some(start_miniblock..=valid_until);
+        drop(lock);
+
+        CACHE_METRICS
+            .secondary_rocksdb_valid_for_miniblock
+            .set(u64::from(valid_until.0));
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SecondaryRocksdbAndUpdater {
+    rocksdb: SecondaryRocksdb,
+    command_sender: mpsc::UnboundedSender<MiniblockNumber>,
+}
+
 #[derive(Debug, Clone)]
 struct ValuesCacheAndUpdater {
     cache: ValuesCache,
@@ -221,6 +419,9 @@ struct ValuesCacheAndUpdater {
 /// - Cache for L1 batch numbers of initial writes for storage keys (never invalidated, except after
 ///   reverting L1 batch execution)
 /// - Cache of the VM storage snapshot corresponding to the latest sealed miniblock
+///
+/// Additionally, latest storage values can be read from a RocksDB secondary instance of the state keeper cache
+/// (see [`Self::configure_secondary_rocksdb()`]).
 #[derive(Debug, Clone)]
 pub struct PostgresStorageCaches {
     factory_deps: FactoryDepsCache,
@@ -234,6 +435,7 @@ pub struct PostgresStorageCaches {
     // it wasn't written to at the point that interests us.
     negative_initial_writes: InitialWritesCache,
     values: Option<ValuesCacheAndUpdater>,
+    secondary_rocksdb: Option<SecondaryRocksdbAndUpdater>,
 }
 
 impl PostgresStorageCaches {
@@ -257,6 +459,7 @@ impl PostgresStorageCaches {
                 initial_writes_capacity / 2,
             ),
             values: None,
+            secondary_rocksdb: None,
         }
     }
 
@@ -292,8 +495,9 @@ impl PostgresStorageCaches {
         // `Self::schedule_values_update()` will produce some no-op update commands from concurrently
         // executing VM instances. Due to built-in filtering, this seems manageable.
         move || {
-            let mut current_miniblock = values_cache.valid_for();
             while let Some(to_miniblock) = command_receiver.blocking_recv() {
+                // The cache may be reset concurrently (see `Self::reset()`), so `valid_for` is re-read each time.
+                let current_miniblock = values_cache.valid_for();
                 if to_miniblock <= current_miniblock {
                     continue;
                 }
@@ -301,32 +505,112 @@ impl PostgresStorageCaches {
                     .block_on(connection_pool.access_storage_tagged("values_cache_updater"))
                     .unwrap();
                 values_cache.update(current_miniblock, to_miniblock, &rt_handle, &mut connection);
-                current_miniblock = to_miniblock;
             }
             Ok(())
         }
     }
 
-    /// Schedules an update of the VM storage values cache to the specified miniblock.
+    /// Configures reading latest storage values from a RocksDB secondary instance of the state keeper cache.
+    /// The returned closure is the background task that will catch up RocksDB with the primary instance
+    /// according to [`Self::schedule_values_update()`] calls. It should be spawned on a separate thread
+    /// or a blocking Tokio task.
+    pub fn configure_secondary_rocksdb(
+        &mut self,
+        storage: RocksdbSecondaryStorage,
+        connection_pool: ConnectionPool,
+        rt_handle: Handle,
+    ) -> impl FnOnce() -> anyhow::Result<()> + Send {
+        tracing::debug!("Initializing reading latest storage values from secondary RocksDB");
+
+        let (command_sender, mut command_receiver) = mpsc::unbounded_channel();
+        let rocksdb = SecondaryRocksdb::new(storage);
+        self.secondary_rocksdb = Some(SecondaryRocksdbAndUpdater {
+            rocksdb: rocksdb.clone(),
+            command_sender,
+        });
+
+        move || {
+            while let Some(to_miniblock) = command_receiver.blocking_recv() {
+                if rocksdb.valid_until() >= Some(to_miniblock) {
+                    continue;
+                }
+                let update_result = rt_handle
+                    .block_on(connection_pool.access_storage_tagged("secondary_rocksdb_updater"))
+                    .and_then(|mut connection| {
+                        rocksdb.update(to_miniblock, &rt_handle, &mut connection)
+                    });
+                if let Err(err) = update_result {
+                    // Values will be read from Postgres until the next successful update.
+                    tracing::warn!(
+                        "Failed updating secondary RocksDB state to miniblock #{to_miniblock}: {err:#}"
+                    );
+                    rocksdb.reset();
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Schedules an update of the VM storage values cache and the secondary RocksDB state
+    /// to the specified miniblock. Does nothing for the caches that are not configured.
+    ///
+    /// If the secondary RocksDB update task has terminated, the secondary RocksDB state is no longer updated,
+    /// and latest storage values are read from Postgres.
     ///
     /// # Panics
     ///
-    /// - Panics if the cache wasn't previously configured using [`Self::configure_storage_values_cache()`].
-    /// - Panics if the cache update task returned from `configure_storage_values_cache()` has panicked.
+    /// Panics if the update task returned from `configure_storage_values_cache()` has failed.
     pub fn schedule_values_update(&self, to_miniblock: MiniblockNumber) {
-        let values = self
-            .values
-            .as_ref()
-            .expect("`schedule_update()` called without configuring values cache");
-
-        if values.cache.valid_for() < to_miniblock {
-            // Filter out no-op updates right away in order to not store lots of them in RAM.
-            values
-                .command_sender
-                .send(to_miniblock)
-                .expect("values cache update task failed");
+        if let Some(values) = &self.values {
+            if values.cache.valid_for() < to_miniblock {
+                // Filter out no-op updates right away in order to not store lots of them in RAM.
+                values
+                    .command_sender
+                    .send(to_miniblock)
+                    .expect("values cache update task failed");
+            }
+        }
+        if let Some(secondary) = &self.secondary_rocksdb {
+            if secondary.rocksdb.valid_until() < Some(to_miniblock) {
+                if secondary.command_sender.send(to_miniblock).is_err() {
+                    tracing::warn!(
+                        "Secondary RocksDB update task has terminated; cannot update state to miniblock #{to_miniblock}"
+                    );
+                }
+            }
         }
     }
+
+    /// Invalidates cached data after reverting the node state to `last_miniblock`, e.g. in the dev mode.
+    /// Factory deps are content-addressable, so they are retained.
+    pub fn reset(&self, last_miniblock: MiniblockNumber) {
+        tracing::info!("Resetting VM execution caches after revert to miniblock #{last_miniblock}");
+        self.initial_writes.clear();
+        self.negative_initial_writes.clear();
+        if let Some(values) = &self.values {
+            values.cache.reset(last_miniblock);
+        }
+        if let Some(secondary) = &self.secondary_rocksdb {
+            secondary.rocksdb.reset();
+        }
+    }
+
+    /// Reads the value of `key` as of `miniblock_number` from the values cache or the secondary RocksDB
+    /// without querying Postgres. Returns `None` if neither of them can provide the value.
+    pub fn read_value_without_postgres(
+        &self,
+        miniblock_number: MiniblockNumber,
+        key: &StorageKey,
+    ) -> Option<StorageValue> {
+        let values_cache = self.values.as_ref().map(|values| &values.cache);
+        values_cache
+            .and_then(|cache| cache.get(miniblock_number, key))
+            .or_else(|| self.secondary_rocksdb()?.get(miniblock_number, key))
+    }
+
+    fn secondary_rocksdb(&self) -> Option<&SecondaryRocksdb> {
+        Some(&self.secondary_rocksdb.as_ref()?.rocksdb)
+    }
 }
 
 /// [`ReadStorage`] implementation backed by the Postgres database.
@@ -401,6 +685,14 @@ impl ReadStorage for PostgresStorage<'_> {
         let latency = STORAGE_METRICS.storage[&Method::ReadValue].start();
         let values_cache = self.values_cache();
         let cached_value = values_cache.and_then(|cache| cache.get(self.miniblock_number, &key));
+        let cached_value = cached_value.or_else(|| {
+            let secondary_rocksdb = self.caches.as_ref()?.secondary_rocksdb()?;
+            let value = secondary_rocksdb.get(self.miniblock_number, &key)?;
+            if let Some(cache) = self.values_cache() {
+                cache.insert(self.miniblock_number, key, value);
+            }
+            Some(value)
+        });
 
         let value = cached_value.unwrap_or_else(|| {
             let mut dal = self.connection.storage_web3_dal();
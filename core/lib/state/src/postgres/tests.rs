@@ -6,14 +6,22 @@ use rand::{
 };
 
 use rand::rngs::StdRng;
-use std::{collections::HashMap, mem};
+use std::{
+    collections::HashMap,
+    mem,
+    time::{Duration, Instant},
+};
 
 use db_test_macro::db_test;
+use tempfile::TempDir;
 use zksync_dal::ConnectionPool;
 use zksync_types::StorageLog;
 
 use super::*;
-use crate::test_utils::{create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres};
+use crate::{
+    test_utils::{create_l1_batch, create_miniblock, gen_storage_logs, prepare_postgres},
+    RocksdbStorage,
+};
 
 fn test_postgres_storage_basics(
     pool: &ConnectionPool,
@@ -497,6 +505,231 @@ async fn using_values_cache(pool: ConnectionPool) {
         .unwrap();
 }
 
+fn test_resetting_caches_after_revert(pool: &ConnectionPool, rt_handle: Handle) {
+    let mut caches = PostgresStorageCaches::new(1_024, 4 * 1_024 * 1_024);
+    let _ = caches.configure_storage_values_cache(1_024 * 1_024, pool.clone(), rt_handle.clone());
+    let values_cache = caches.values.as_ref().unwrap().cache.clone();
+
+    let mut connection = rt_handle.block_on(pool.access_storage()).unwrap();
+    rt_handle.block_on(prepare_postgres(&mut connection));
+    let existing_key = gen_storage_logs(0..20)[1].key;
+    let new_key = gen_storage_logs(100..120)[0].key;
+    let logs = vec![
+        StorageLog::new_write_log(existing_key, H256::repeat_byte(1)),
+        StorageLog::new_write_log(new_key, H256::repeat_byte(2)),
+    ];
+    rt_handle.block_on(create_miniblock(
+        &mut connection,
+        MiniblockNumber(1),
+        logs.clone(),
+    ));
+    rt_handle.block_on(create_l1_batch(&mut connection, L1BatchNumber(1), &logs));
+    values_cache.update(
+        MiniblockNumber(0),
+        MiniblockNumber(1),
+        &rt_handle,
+        &mut connection,
+    );
+
+    let mut storage = PostgresStorage::new(rt_handle, connection, MiniblockNumber(1), true)
+        .with_caches(caches.clone());
+    assert_eq!(storage.read_value(&existing_key), H256::repeat_byte(1));
+    assert!(!storage.is_write_initial(&new_key));
+    values_cache
+        .assertions(MiniblockNumber(1))
+        .assert_entries(&[(existing_key, Some(H256::repeat_byte(1)))]);
+    assert_eq!(caches.initial_writes.get(&new_key), Some(L1BatchNumber(1)));
+
+    // Revert miniblock #1 and L1 batch #1.
+    let mut connection = storage.connection;
+    storage.rt_handle.block_on(async {
+        connection
+            .storage_logs_dal()
+            .rollback_storage_logs(MiniblockNumber(0))
+            .await;
+        connection
+            .blocks_dal()
+            .delete_miniblocks(MiniblockNumber(0))
+            .await
+            .unwrap();
+        connection
+            .blocks_dal()
+            .delete_l1_batches(L1BatchNumber(0))
+            .await
+            .unwrap();
+    });
+    caches.reset(MiniblockNumber(0));
+
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(0));
+    values_cache
+        .assertions(MiniblockNumber(0))
+        .assert_entries(&[(existing_key, None)]);
+    assert_eq!(caches.initial_writes.get(&new_key), None);
+
+    let mut storage = PostgresStorage::new(storage.rt_handle, connection, MiniblockNumber(0), true)
+        .with_caches(caches);
+    assert_ne!(storage.read_value(&existing_key), H256::repeat_byte(1));
+    assert_eq!(storage.read_value(&new_key), H256::zero());
+    assert!(storage.is_write_initial(&new_key));
+
+    // Updates from before the revert must not be applied to the reset cache.
+    values_cache.update(
+        MiniblockNumber(1),
+        MiniblockNumber(2),
+        &storage.rt_handle,
+        &mut storage.connection,
+    );
+    assert_eq!(values_cache.valid_for(), MiniblockNumber(0));
+}
+
+#[db_test]
+async fn resetting_caches_after_revert(pool: ConnectionPool) {
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || test_resetting_caches_after_revert(&pool, handle))
+        .await
+        .unwrap();
+}
+
+fn test_secondary_rocksdb(pool: &ConnectionPool, rt_handle: Handle) {
+    let mut connection = rt_handle.block_on(pool.access_storage()).unwrap();
+    rt_handle.block_on(prepare_postgres(&mut connection));
+    let primary_dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+    let secondary_dir = TempDir::new().expect("cannot create temporary dir for secondary");
+    let mut primary = RocksdbStorage::new(primary_dir.path());
+    rt_handle.block_on(primary.update_from_postgres(&mut connection));
+    assert_eq!(primary.l1_batch_number(), L1BatchNumber(1));
+
+    let secondary = RocksdbSecondaryStorage::open(primary_dir.path(), secondary_dir.path());
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let _ = caches.configure_secondary_rocksdb(secondary, pool.clone(), rt_handle.clone());
+    // We update the secondary RocksDB state manually, similarly to the values cache tests.
+    let rocksdb = caches.secondary_rocksdb().unwrap().clone();
+    assert_eq!(rocksdb.valid_until(), None);
+
+    let initial_logs = gen_storage_logs(0..20);
+    let modified_key = initial_logs[1].key;
+    let unmodified_key = initial_logs[2].key;
+    let logs = vec![StorageLog::new_write_log(
+        modified_key,
+        H256::repeat_byte(1),
+    )];
+    rt_handle.block_on(create_miniblock(
+        &mut connection,
+        MiniblockNumber(1),
+        logs.clone(),
+    ));
+    rocksdb
+        .update(MiniblockNumber(1), &rt_handle, &mut connection)
+        .unwrap();
+    assert_eq!(rocksdb.valid_until(), Some(MiniblockNumber(1)));
+
+    for miniblock_number in [MiniblockNumber(0), MiniblockNumber(1)] {
+        assert_eq!(
+            caches.read_value_without_postgres(miniblock_number, &unmodified_key),
+            Some(initial_logs[2].value)
+        );
+    }
+    assert_eq!(
+        caches.read_value_without_postgres(MiniblockNumber(0), &modified_key),
+        None
+    );
+    assert_eq!(
+        caches.read_value_without_postgres(MiniblockNumber(2), &unmodified_key),
+        None
+    );
+
+    let mut storage = PostgresStorage::new(rt_handle.clone(), connection, MiniblockNumber(1), true)
+        .with_caches(caches.clone());
+    assert_eq!(storage.read_value(&modified_key), H256::repeat_byte(1));
+    assert_eq!(storage.read_value(&unmodified_key), initial_logs[2].value);
+
+    // Seal the L1 batch and let the primary storage process it.
+    let mut connection = storage.connection;
+    rt_handle.block_on(create_l1_batch(&mut connection, L1BatchNumber(1), &logs));
+    rt_handle.block_on(primary.update_from_postgres(&mut connection));
+    rocksdb
+        .update(MiniblockNumber(1), &rt_handle, &mut connection)
+        .unwrap();
+    assert_eq!(
+        caches.read_value_without_postgres(MiniblockNumber(1), &modified_key),
+        Some(H256::repeat_byte(1))
+    );
+    // The state as of miniblock #0 is no longer in RocksDB.
+    assert_eq!(
+        caches.read_value_without_postgres(MiniblockNumber(0), &unmodified_key),
+        None
+    );
+}
+
+#[db_test]
+async fn using_secondary_rocksdb(pool: ConnectionPool) {
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || test_secondary_rocksdb(&pool, handle))
+        .await
+        .unwrap();
+}
+
+fn test_secondary_rocksdb_update_task(pool: &ConnectionPool, rt_handle: Handle) {
+    let mut connection = rt_handle.block_on(pool.access_storage()).unwrap();
+    rt_handle.block_on(prepare_postgres(&mut connection));
+    let primary_dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+    let secondary_dir = TempDir::new().expect("cannot create temporary dir for secondary");
+    let mut primary = RocksdbStorage::new(primary_dir.path());
+    rt_handle.block_on(primary.update_from_postgres(&mut connection));
+
+    let secondary = RocksdbSecondaryStorage::open(primary_dir.path(), secondary_dir.path());
+    let mut caches = PostgresStorageCaches::new(1_024, 1_024);
+    let update_task =
+        caches.configure_secondary_rocksdb(secondary, pool.clone(), rt_handle.clone());
+    let update_task_handle = std::thread::spawn(update_task);
+    let rocksdb = caches.secondary_rocksdb().unwrap().clone();
+
+    let modified_key = gen_storage_logs(0..20)[1].key;
+    let logs = vec![StorageLog::new_write_log(
+        modified_key,
+        H256::repeat_byte(1),
+    )];
+    rt_handle.block_on(create_miniblock(&mut connection, MiniblockNumber(1), logs));
+    drop(connection);
+
+    caches.schedule_values_update(MiniblockNumber(1));
+    let started_at = Instant::now();
+    while rocksdb.valid_until() != Some(MiniblockNumber(1)) {
+        assert!(
+            started_at.elapsed() < Duration::from_secs(10),
+            "secondary RocksDB was not updated"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(
+        caches.read_value_without_postgres(MiniblockNumber(1), &modified_key),
+        None
+    );
+
+    // Scheduling updates after the update task has terminated must not panic; values are read from Postgres.
+    let secondary_rocksdb = caches.secondary_rocksdb.take().unwrap();
+    drop(secondary_rocksdb.command_sender);
+    update_task_handle.join().unwrap().unwrap();
+    caches.secondary_rocksdb = Some(SecondaryRocksdbAndUpdater {
+        rocksdb: secondary_rocksdb.rocksdb,
+        command_sender: mpsc::unbounded_channel().0,
+    });
+    caches.schedule_values_update(MiniblockNumber(2));
+
+    let connection = rt_handle.block_on(pool.access_storage()).unwrap();
+    let mut storage =
+        PostgresStorage::new(rt_handle, connection, MiniblockNumber(1), true).with_caches(caches);
+    assert_eq!(storage.read_value(&modified_key), H256::repeat_byte(1));
+}
+
+#[db_test]
+async fn secondary_rocksdb_update_task(pool: ConnectionPool) {
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || test_secondary_rocksdb_update_task(&pool, handle))
+        .await
+        .unwrap();
+}
+
 /// (Sort of) fuzzes [`ValuesCache`] by comparing outputs of [`PostgresStorage`] with and without caching
 /// on randomly generated `read_value()` queries.
 fn mini_fuzz_values_cache_inner(rng: &mut impl Rng, pool: &ConnectionPool, mut rt_handle: Handle) {
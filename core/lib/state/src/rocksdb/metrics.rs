@@ -1,9 +1,16 @@
 //! Metrics for `RocksdbStorage`.
 
-use vise::{Buckets, Gauge, Histogram, Metrics};
+use vise::{Buckets, EncodeLabelSet, EncodeLabelValue, Family, Gauge, Histogram, Metrics};
 
 use std::time::Duration;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelSet, EncodeLabelValue)]
+#[metrics(label = "stage", rename_all = "snake_case")]
+pub(super) enum CatchUpStage {
+    BulkLoadState,
+    BulkLoadFactoryDeps,
+}
+
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "server_state_keeper_secondary_storage")]
 pub(super) struct RocksdbStorageMetrics {
@@ -14,6 +21,9 @@ pub(super) struct RocksdbStorageMetrics {
     pub lag: Gauge<u64>,
     /// Estimated number of entries in the secondary storage.
     pub size: Gauge<u64>,
+    /// Latency of catch-up stages for the secondary storage.
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub catch_up: Family<CatchUpStage, Histogram<Duration>>,
 }
 
 #[vise::register]
@@ -15,6 +15,8 @@
 //! | Contracts    | address (20 bytes)     | `Vec<u8>`               | Contract contents                    |
 //! | Factory deps | hash (32 bytes)        | `Vec<u8>`               | Bytecodes for new contracts that a certain contract may deploy. |
 
+use anyhow::Context as _;
+
 use std::{collections::HashMap, mem, path::Path, time::Instant};
 
 use zksync_dal::StorageProcessor;
@@ -23,7 +25,7 @@ use zksync_types::{L1BatchNumber, StorageKey, StorageValue, H256};
 
 mod metrics;
 
-use self::metrics::METRICS;
+use self::metrics::{CatchUpStage, METRICS};
 use crate::{InMemoryStorage, ReadStorage};
 
 fn serialize_block_number(block_number: u32) -> [u8; 4] {
@@ -55,15 +57,33 @@ impl NamedColumnFamily for StateKeeperColumnFamily {
     }
 }
 
+/// Mode used by [`RocksdbStorage`] to catch up with Postgres.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RocksdbCatchUpMode {
+    /// Changes are applied L1 batch by L1 batch.
+    #[default]
+    Incremental,
+    /// If the storage is empty, the VM state as of the last sealed L1 batch is loaded from Postgres
+    /// in chunks and ingested into RocksDB as SST files, which is much faster than replaying all L1 batches.
+    /// If the storage is not empty, this mode is equivalent to [`Self::Incremental`].
+    BulkLoad,
+}
+
 /// [`ReadStorage`] implementation backed by RocksDB.
 #[derive(Debug)]
 pub struct RocksdbStorage {
     db: RocksDB<StateKeeperColumnFamily>,
     pending_patch: InMemoryStorage,
+    catch_up_mode: RocksdbCatchUpMode,
 }
 
 impl RocksdbStorage {
     const BLOCK_NUMBER_KEY: &'static [u8] = b"block_number";
+    /// Number of entries loaded from Postgres and ingested into RocksDB at a time during bulk loading.
+    const BULK_LOAD_CHUNK_SIZE: usize = 500_000;
+    /// Number of factory deps loaded from Postgres at a time during bulk loading. Factory deps
+    /// are quite large, so the chunk is smaller than for storage entries.
+    const BULK_LOAD_FACTORY_DEPS_CHUNK_SIZE: usize = 1_000;
 
     /// Creates a new storage with the provided RocksDB `path`.
     pub fn new(path: &Path) -> Self {
@@ -71,9 +91,17 @@ impl RocksdbStorage {
         Self {
             db,
             pending_patch: InMemoryStorage::default(),
+            catch_up_mode: RocksdbCatchUpMode::default(),
         }
     }
 
+    /// Sets the mode used to catch up with Postgres in [`Self::update_from_postgres()`].
+    #[must_use]
+    pub fn with_catch_up_mode(mut self, mode: RocksdbCatchUpMode) -> Self {
+        self.catch_up_mode = mode;
+        self
+    }
+
     /// Synchronizes this storage with Postgres using the provided connection.
     ///
     /// # Panics
@@ -99,6 +127,11 @@ impl RocksdbStorage {
              the last sealed L1 batch number in Postgres ({latest_l1_batch_number})"
         );
 
+        if self.catch_up_mode == RocksdbCatchUpMode::BulkLoad && current_l1_batch_number == 0 {
+            self.bulk_load(conn, latest_l1_batch_number).await;
+            current_l1_batch_number = latest_l1_batch_number.0 + 1;
+        }
+
         while current_l1_batch_number <= latest_l1_batch_number.0 {
             let current_lag = latest_l1_batch_number.0 - current_l1_batch_number + 1;
             METRICS.lag.set(current_lag.into());
@@ -133,10 +166,96 @@ impl RocksdbStorage {
         );
     }
 
+    /// Loads the entire VM state as of the end of the specified L1 batch from Postgres
+    /// by ingesting it into RocksDB. Must be called on an empty storage.
+    async fn bulk_load(&mut self, conn: &mut StorageProcessor<'_>, l1_batch_number: L1BatchNumber) {
+        tracing::info!("Bulk-loading secondary storage for L1 batch #{l1_batch_number}");
+        let (_, last_miniblock) = conn
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(l1_batch_number)
+            .await
+            .unwrap()
+            .expect("L1 batch should contain at least one miniblock");
+
+        let latency = METRICS.catch_up[&CatchUpStage::BulkLoadState].start();
+        let mut last_hashed_key = None;
+        let mut total_entries = 0;
+        loop {
+            let chunk = conn
+                .storage_logs_dal()
+                .get_state_chunk(
+                    l1_batch_number,
+                    last_miniblock,
+                    last_hashed_key,
+                    Self::BULK_LOAD_CHUNK_SIZE,
+                )
+                .await;
+            let Some(&(hashed_key, _)) = chunk.last() else {
+                break;
+            };
+            last_hashed_key = Some(hashed_key);
+
+            let db = self.db.clone();
+            let ingested_count = tokio::task::spawn_blocking(move || {
+                let entries = chunk
+                    .iter()
+                    .map(|(hashed_key, value)| (hashed_key.as_bytes(), value.as_bytes()));
+                db.ingest_sorted_entries(StateKeeperColumnFamily::State, entries)
+                    .expect("failed ingesting state chunk into RocksDB")
+            })
+            .await
+            .unwrap();
+            total_entries += ingested_count;
+            tracing::debug!("Ingested {total_entries} state entries so far");
+        }
+        let elapsed = latency.observe();
+        tracing::info!("Ingested {total_entries} state entries in {elapsed:?}");
+
+        let latency = METRICS.catch_up[&CatchUpStage::BulkLoadFactoryDeps].start();
+        let mut last_hash = None;
+        let mut total_factory_deps = 0;
+        loop {
+            let chunk = conn
+                .storage_dal()
+                .get_factory_deps_chunk(
+                    last_miniblock,
+                    last_hash,
+                    Self::BULK_LOAD_FACTORY_DEPS_CHUNK_SIZE,
+                )
+                .await;
+            let Some(&(hash, _)) = chunk.last() else {
+                break;
+            };
+            last_hash = Some(hash);
+
+            let db = self.db.clone();
+            let ingested_count = tokio::task::spawn_blocking(move || {
+                let entries = chunk
+                    .iter()
+                    .map(|(hash, bytecode)| (hash.as_bytes(), bytecode.as_slice()));
+                db.ingest_sorted_entries(StateKeeperColumnFamily::FactoryDeps, entries)
+                    .expect("failed ingesting factory deps into RocksDB")
+            })
+            .await
+            .unwrap();
+            total_factory_deps += ingested_count;
+        }
+        let elapsed = latency.observe();
+        tracing::info!("Ingested {total_factory_deps} factory deps in {elapsed:?}");
+
+        self.save(l1_batch_number + 1).await;
+    }
+
     fn read_value_inner(&self, key: &StorageKey) -> Option<StorageValue> {
+        Self::read_value_from_db(&self.db, key)
+    }
+
+    fn read_value_from_db(
+        db: &RocksDB<StateKeeperColumnFamily>,
+        key: &StorageKey,
+    ) -> Option<StorageValue> {
         let cf = StateKeeperColumnFamily::State;
-        self.db
-            .get_cf(cf, &Self::serialize_state_key(key))
+        db.get_cf(cf, &Self::serialize_state_key(key))
             .expect("failed to read rocksdb state value")
             .map(|value| H256::from_slice(&value))
     }
@@ -261,9 +380,12 @@ impl RocksdbStorage {
     /// # Panics
     /// Panics on RocksDB errors.
     pub fn l1_batch_number(&self) -> L1BatchNumber {
+        Self::l1_batch_number_from_db(&self.db)
+    }
+
+    fn l1_batch_number_from_db(db: &RocksDB<StateKeeperColumnFamily>) -> L1BatchNumber {
         let cf = StateKeeperColumnFamily::State;
-        let block_number = self
-            .db
+        let block_number = db
             .get_cf(cf, Self::BLOCK_NUMBER_KEY)
             .expect("failed to fetch block number");
         let block_number = block_number.map_or(0, |bytes| deserialize_block_number(&bytes));
@@ -281,6 +403,49 @@ impl RocksdbStorage {
     }
 }
 
+/// Read-only view of a [`RocksdbStorage`] maintained by another component (e.g., the state keeper),
+/// opened as a RocksDB secondary instance. Can be used to offload latest state reads from Postgres.
+///
+/// The view is cheaply cloneable and can be shared among threads.
+#[derive(Debug, Clone)]
+pub struct RocksdbSecondaryStorage {
+    db: RocksDB<StateKeeperColumnFamily>,
+}
+
+impl RocksdbSecondaryStorage {
+    /// Opens a secondary instance for the storage at `primary_path`. `secondary_path` is used to store
+    /// info logs of the secondary instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the primary storage cannot be opened.
+    pub fn open(primary_path: &Path, secondary_path: &Path) -> Self {
+        let db = RocksDB::open_secondary(primary_path, secondary_path, None);
+        Self { db }
+    }
+
+    /// Catches up with changes made to the primary storage.
+    ///
+    /// # Errors
+    ///
+    /// Propagates RocksDB errors.
+    pub fn catch_up(&self) -> anyhow::Result<()> {
+        self.db
+            .try_catch_up_with_primary()
+            .context("failed catching up with primary RocksDB instance")
+    }
+
+    /// Returns the last L1 batch number processed by the primary storage + 1.
+    pub fn l1_batch_number(&self) -> L1BatchNumber {
+        RocksdbStorage::l1_batch_number_from_db(&self.db)
+    }
+
+    /// Reads the value of the specified storage `key`.
+    pub fn read_value(&self, key: &StorageKey) -> StorageValue {
+        RocksdbStorage::read_value_from_db(&self.db, key).unwrap_or_else(H256::zero)
+    }
+}
+
 impl ReadStorage for RocksdbStorage {
     fn read_value(&mut self, key: &StorageKey) -> StorageValue {
         self.read_value_inner(key).unwrap_or_else(H256::zero)
@@ -340,6 +505,37 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn reading_from_secondary_storage() {
+        let dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+        let secondary_dir = TempDir::new().expect("cannot create temporary dir for secondary");
+        let mut storage = RocksdbStorage::new(dir.path());
+        let storage_logs: HashMap<_, _> = gen_storage_logs(0..20)
+            .into_iter()
+            .map(|log| (log.key, log.value))
+            .collect();
+        storage.process_transaction_logs(&storage_logs);
+        storage.save(L1BatchNumber(0)).await;
+
+        let secondary = RocksdbSecondaryStorage::open(dir.path(), secondary_dir.path());
+        assert_eq!(secondary.l1_batch_number(), L1BatchNumber(0));
+        for (key, value) in &storage_logs {
+            assert_eq!(secondary.read_value(key), *value);
+        }
+
+        let new_logs: HashMap<_, _> = gen_storage_logs(20..30)
+            .into_iter()
+            .map(|log| (log.key, log.value))
+            .collect();
+        storage.process_transaction_logs(&new_logs);
+        storage.save(L1BatchNumber(1)).await;
+        secondary.catch_up().unwrap();
+        assert_eq!(secondary.l1_batch_number(), L1BatchNumber(1));
+        for (key, value) in &new_logs {
+            assert_eq!(secondary.read_value(key), *value);
+        }
+    }
+
     #[db_test]
     async fn rocksdb_storage_syncing_with_postgres(pool: ConnectionPool) {
         let mut conn = pool.access_storage().await.unwrap();
@@ -358,6 +554,58 @@ mod tests {
         }
     }
 
+    #[db_test]
+    async fn rocksdb_storage_bulk_loading(pool: ConnectionPool) {
+        let mut conn = pool.access_storage().await.unwrap();
+        prepare_postgres(&mut conn).await;
+        let storage_logs = gen_storage_logs(20..40);
+        create_miniblock(&mut conn, MiniblockNumber(1), storage_logs.clone()).await;
+        insert_factory_deps(&mut conn, MiniblockNumber(1), 0..3).await;
+        create_l1_batch(&mut conn, L1BatchNumber(1), &storage_logs).await;
+
+        let replaced_storage_logs: Vec<_> = storage_logs
+            .iter()
+            .step_by(2)
+            .map(|&log| StorageLog {
+                value: H256::zero(),
+                ..log
+            })
+            .collect();
+        create_miniblock(&mut conn, MiniblockNumber(2), replaced_storage_logs.clone()).await;
+        create_l1_batch(&mut conn, L1BatchNumber(2), &[]).await;
+        // Data for the pending miniblock must not be loaded.
+        let pending_storage_logs = gen_storage_logs(50..60);
+        create_miniblock(&mut conn, MiniblockNumber(3), pending_storage_logs.clone()).await;
+        insert_factory_deps(&mut conn, MiniblockNumber(3), 3..5).await;
+
+        let dir = TempDir::new().expect("cannot create temporary dir for state keeper");
+        let mut storage =
+            RocksdbStorage::new(dir.path()).with_catch_up_mode(RocksdbCatchUpMode::BulkLoad);
+        storage.update_from_postgres(&mut conn).await;
+
+        assert_eq!(storage.l1_batch_number(), L1BatchNumber(3));
+        for log in storage_logs.iter().skip(1).step_by(2) {
+            assert!(!storage.is_write_initial(&log.key));
+            assert_eq!(storage.read_value(&log.key), log.value);
+        }
+        for log in &replaced_storage_logs {
+            assert!(!storage.is_write_initial(&log.key));
+            assert_eq!(storage.read_value(&log.key), H256::zero());
+        }
+        for log in &pending_storage_logs {
+            assert!(storage.is_write_initial(&log.key));
+        }
+        for i in 0..3 {
+            assert_eq!(
+                storage.load_factory_dep(H256::repeat_byte(i)).unwrap(),
+                [i; 64]
+            );
+        }
+        for i in 3..5 {
+            assert!(storage.load_factory_dep(H256::repeat_byte(i)).is_none());
+        }
+    }
+
     async fn insert_factory_deps(
         conn: &mut StorageProcessor<'_>,
         miniblock_number: MiniblockNumber,
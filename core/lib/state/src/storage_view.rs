@@ -59,6 +59,11 @@ impl<S> StorageView<S> {
             is_write_initial: self.initial_writes_cache.clone(),
         }
     }
+
+    /// Returns storage keys read from the underlying storage together with the read values.
+    pub fn read_storage_keys(&self) -> &HashMap<StorageKey, StorageValue> {
+        &self.read_storage_keys
+    }
 }
 
 impl<S> ReadStorage for Box<S>
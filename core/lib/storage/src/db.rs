@@ -1,16 +1,20 @@
 use rocksdb::{
     properties, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, DBPinnableSlice,
-    IteratorMode, Options, PrefixRange, ReadOptions, WriteOptions, DB,
+    IngestExternalFileOptions, IteratorMode, Options, PrefixRange, ReadOptions, SstFileWriter,
+    WriteOptions, DB,
 };
 
 use std::ffi::CStr;
 use std::{
     collections::HashSet,
-    fmt,
+    fmt, fs,
     marker::PhantomData,
     ops,
     path::Path,
-    sync::{Arc, Condvar, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex,
+    },
 };
 
 use crate::metrics::{RocksdbLabels, RocksdbSizeMetrics, METRICS};
@@ -87,6 +91,8 @@ pub(crate) struct RocksDBInner {
     db: DB,
     db_name: &'static str,
     cf_names: HashSet<&'static str>,
+    /// Counter used to generate unique names for SST files ingested into the DB.
+    ingested_file_counter: AtomicU64,
     _registry_entry: RegistryEntry,
     // Importantly, `Cache`s must be dropped after `DB`, so we place them as the last field
     // (fields in a struct are dropped in the declaration order).
@@ -210,6 +216,7 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
             db,
             db_name: CF::DB_NAME,
             cf_names,
+            ingested_file_counter: AtomicU64::new(0),
             _registry_entry: RegistryEntry::new(),
             _caches: caches,
         });
@@ -222,6 +229,77 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
         }
     }
 
+    /// Opens a read-only secondary instance of the DB located at `primary_path`. The secondary instance
+    /// can be opened while the primary instance is being written to (including by another process),
+    /// and can follow the primary using [`Self::try_catch_up_with_primary()`]. `secondary_path` is used
+    /// to store info logs of the secondary instance. Writes to a secondary instance fail.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the primary DB cannot be opened, or if it lacks any of the column families.
+    pub fn open_secondary(
+        primary_path: &Path,
+        secondary_path: &Path,
+        block_cache_capacity: Option<usize>,
+    ) -> Self {
+        let caches = RocksDBCaches::new(block_cache_capacity);
+        let mut options = Options::default();
+        // Recommended for secondary instances, so that they have access to all SST files of the primary.
+        options.set_max_open_files(-1);
+        let existing_cfs = DB::list_cf(&options, primary_path).unwrap_or_else(|err| {
+            panic!(
+                "Failed getting column families for RocksDB `{}` at `{}`: {err}",
+                CF::DB_NAME,
+                primary_path.display()
+            )
+        });
+
+        let cf_names: HashSet<_> = CF::ALL.iter().map(|cf| cf.name()).collect();
+        let missing_cfs: Vec<_> = cf_names
+            .iter()
+            .filter(|&&cf_name| !existing_cfs.iter().any(|name| name == cf_name))
+            .collect();
+        assert!(
+            missing_cfs.is_empty(),
+            "RocksDB `{}` at `{}` lacks column families {missing_cfs:?}",
+            CF::DB_NAME,
+            primary_path.display()
+        );
+
+        // A secondary instance must open all column families of the primary instance.
+        let cfs = existing_cfs.iter().map(|cf_name| {
+            let mut block_based_options = BlockBasedOptions::default();
+            if let Some(cache) = &caches.shared {
+                block_based_options.set_block_cache(cache);
+            }
+            let mut cf_options = Options::default();
+            cf_options.set_block_based_table_factory(&block_based_options);
+            ColumnFamilyDescriptor::new(cf_name, cf_options)
+        });
+        let db = DB::open_cf_descriptors_as_secondary(&options, primary_path, secondary_path, cfs)
+            .expect("failed to init secondary rocksdb instance");
+        let inner = Arc::new(RocksDBInner {
+            db,
+            db_name: CF::DB_NAME,
+            cf_names,
+            ingested_file_counter: AtomicU64::new(0),
+            _registry_entry: RegistryEntry::new(),
+            _caches: caches,
+        });
+
+        Self {
+            inner,
+            sync_writes: false,
+            _cf: PhantomData,
+        }
+    }
+
+    /// Makes a secondary instance (see [`Self::open_secondary()`]) catch up with the changes
+    /// made to the primary instance.
+    pub fn try_catch_up_with_primary(&self) -> Result<(), rocksdb::Error> {
+        self.inner.db.try_catch_up_with_primary()
+    }
+
     /// Switches on sync writes in [`Self::write()`] and [`Self::put()`]. This has a performance
     /// penalty and is mostly useful for tests.
     #[must_use]
@@ -297,6 +375,72 @@ impl<CF: NamedColumnFamily> RocksDB<CF> {
         Ok(())
     }
 
+    /// Bulk-loads `entries` into the specified column family. Entries are written into an SST file,
+    /// which is then ingested into the DB. This is significantly faster than writing the same entries
+    /// via [`Self::write()`], but requires that `entries` are sorted by key in the ascending order
+    /// and do not contain duplicate keys.
+    ///
+    /// Returns the number of ingested entries.
+    ///
+    /// # Errors
+    ///
+    /// Propagates RocksDB errors, e.g. if `entries` are not sorted.
+    pub fn ingest_sorted_entries<K, V>(
+        &self,
+        cf: CF,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<usize, rocksdb::Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        let file_index = self
+            .inner
+            .ingested_file_counter
+            .fetch_add(1, Ordering::Relaxed);
+        let file_path = self
+            .inner
+            .db
+            .path()
+            .join(format!("ingest-{}-{file_index}.sst", cf.name()));
+
+        let options = Options::default();
+        let mut writer = SstFileWriter::create(&options);
+        writer.open(&file_path)?;
+        let mut entry_count = 0;
+        for (key, value) in entries {
+            writer.put(key, value)?;
+            entry_count += 1;
+        }
+        if entry_count == 0 {
+            // RocksDB cannot finalize an empty SST file.
+            drop(writer);
+            Self::remove_ingested_file(&file_path);
+            return Ok(0);
+        }
+        writer.finish()?;
+
+        let mut ingest_options = IngestExternalFileOptions::default();
+        ingest_options.set_move_files(true);
+        let ingest_result = self.inner.db.ingest_external_file_cf_opts(
+            self.column_family(cf),
+            &ingest_options,
+            vec![&file_path],
+        );
+        Self::remove_ingested_file(&file_path);
+        ingest_result?;
+        Ok(entry_count)
+    }
+
+    fn remove_ingested_file(path: &Path) {
+        // The file may be already removed by RocksDB if it was moved into the DB.
+        if let Err(err) = fs::remove_file(path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed removing SST file `{}`: {err}", path.display());
+            }
+        }
+    }
+
     fn column_family(&self, cf: CF) -> &ColumnFamily {
         self.inner
             .db
@@ -434,6 +578,74 @@ mod tests {
         assert_eq!(value.unwrap(), b"value");
     }
 
+    #[test]
+    fn secondary_instance_following_primary() {
+        let primary_dir = TempDir::new().unwrap();
+        let secondary_dir = TempDir::new().unwrap();
+        let db = RocksDB::<OldColumnFamilies>::new(primary_dir.path(), true).with_sync_writes();
+        let mut batch = db.new_write_batch();
+        batch.put_cf(OldColumnFamilies::Junk, b"test", b"value");
+        db.write(batch).unwrap();
+
+        let secondary = RocksDB::<OldColumnFamilies>::open_secondary(
+            primary_dir.path(),
+            secondary_dir.path(),
+            None,
+        );
+        let value = secondary.get_cf(OldColumnFamilies::Junk, b"test").unwrap();
+        assert_eq!(value.unwrap(), b"value");
+
+        let mut batch = db.new_write_batch();
+        batch.put_cf(OldColumnFamilies::Junk, b"other", b"other_value");
+        db.write(batch).unwrap();
+        assert!(secondary
+            .get_cf(OldColumnFamilies::Junk, b"other")
+            .unwrap()
+            .is_none());
+        secondary.try_catch_up_with_primary().unwrap();
+        let value = secondary.get_cf(OldColumnFamilies::Junk, b"other").unwrap();
+        assert_eq!(value.unwrap(), b"other_value");
+
+        let mut batch = secondary.new_write_batch();
+        batch.put_cf(OldColumnFamilies::Junk, b"test", b"new_value");
+        secondary.write(batch).unwrap_err();
+    }
+
+    #[test]
+    fn ingesting_sorted_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = RocksDB::<OldColumnFamilies>::new(temp_dir.path(), true);
+        let entries: Vec<_> = (0_u32..100)
+            .map(|i| (i.to_be_bytes(), i.to_le_bytes()))
+            .collect();
+        let ingested_count = db
+            .ingest_sorted_entries(OldColumnFamilies::Junk, entries.clone())
+            .unwrap();
+        assert_eq!(ingested_count, 100);
+        let ingested_count = db
+            .ingest_sorted_entries(OldColumnFamilies::Junk, Vec::<(Vec<u8>, Vec<u8>)>::new())
+            .unwrap();
+        assert_eq!(ingested_count, 0);
+
+        for (key, value) in &entries {
+            let loaded_value = db.get_cf(OldColumnFamilies::Junk, key).unwrap();
+            assert_eq!(loaded_value.unwrap(), value);
+        }
+
+        let unsorted_entries = [(b"b", b"1"), (b"a", b"2")];
+        db.ingest_sorted_entries(OldColumnFamilies::Junk, unsorted_entries)
+            .unwrap_err();
+        // Temporary SST files should be cleaned up.
+        let sst_files = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().starts_with("ingest-")
+            })
+            .count();
+        assert_eq!(sst_files, 0);
+    }
+
     #[derive(Debug, Clone, Copy)]
     struct JunkColumnFamily;
 
@@ -7,7 +7,7 @@ use zkevm_test_harness::abstract_zksync_circuit::concrete_circuits::ZkSyncCircui
 use zkevm_test_harness::bellman::bn256::Bn256;
 use zkevm_test_harness::bellman::plonk::better_better_cs::proof::Proof;
 use zkevm_test_harness::witness::oracle::VmWitnessOracle;
-use zksync_basic_types::{ethabi::Token, L1BatchNumber};
+use zksync_basic_types::{ethabi::Token, L1BatchCommitmentMode, L1BatchNumber, PubdataSendingMode};
 
 use crate::{commitment::L1BatchWithMetadata, U256};
 
@@ -25,10 +25,41 @@ fn l1_batch_range_from_batches(
     start..=end
 }
 
+/// Costs of publishing pubdata of a commit operation as calldata and in blobs, estimated at L1 prices
+/// at the time the operation was aggregated.
+///
+/// This is an estimate for analysis only: pubdata is always sent as a part of the commit calldata,
+/// since EIP-4844 blob transactions are not supported by the L1 sender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PubdataCostEstimate {
+    /// Raw pubdata size of the operation, in bytes.
+    pub pubdata_size: usize,
+    /// Estimated cost (in wei) of sending pubdata of the operation as calldata.
+    pub calldata_cost: U256,
+    /// Estimated cost (in wei) of sending pubdata of the operation in blobs. `None` if the blob base fee is unknown.
+    pub blobs_cost: Option<U256>,
+}
+
+impl PubdataCostEstimate {
+    /// Returns the cheaper way of sending pubdata, or `None` if it cannot be determined.
+    pub fn cheaper_mode(&self) -> Option<PubdataSendingMode> {
+        let blobs_cost = self.blobs_cost?;
+        Some(if blobs_cost < self.calldata_cost {
+            PubdataSendingMode::Blobs
+        } else {
+            PubdataSendingMode::Calldata
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct L1BatchCommitOperation {
     pub last_committed_l1_batch: L1BatchWithMetadata,
     pub l1_batches: Vec<L1BatchWithMetadata>,
+    pub commitment_mode: L1BatchCommitmentMode,
+    /// Estimated costs of publishing pubdata of the committed batches. Only computed
+    /// if pubdata cost estimation is enabled.
+    pub pubdata_cost_estimate: Option<PubdataCostEstimate>,
 }
 
 impl L1BatchCommitOperation {
@@ -37,7 +68,7 @@ impl L1BatchCommitOperation {
         let l1_batches_to_commit = self
             .l1_batches
             .iter()
-            .map(L1BatchWithMetadata::l1_commit_data)
+            .map(|l1_batch| l1_batch.l1_commit_data(self.commitment_mode))
             .collect();
 
         vec![stored_batch_info, Token::Array(l1_batches_to_commit)]
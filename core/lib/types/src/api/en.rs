@@ -46,6 +46,10 @@ pub struct SyncBlock {
     pub l1_gas_price: u64,
     /// L2 gas price used as VM parameter for the L1 batch corresponding to this L2 block.
     pub l2_fair_gas_price: u64,
+    /// Pubdata price used as VM parameter for the L1 batch corresponding to this L2 block.
+    /// May be `None` if the main node doesn't track it separately from the L1 gas price.
+    #[serde(default)]
+    pub fair_pubdata_price: Option<u64>,
     /// Hashes of the base system contracts used in for the L1 batch corresponding to this L2 block.
     pub base_system_contracts_hashes: BaseSystemContractsHashes,
     /// Address of the operator account who produced for the L1 batch corresponding to this L2 block.
@@ -198,6 +198,18 @@ pub struct BridgeAddresses {
     pub l2_weth_bridge: Option<Address>,
 }
 
+/// Paymaster policies enforced by the node when accepting transactions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterPolicies {
+    /// Paymasters allowed to sponsor transactions. `None` means that any paymaster is allowed.
+    pub allowed_paymasters: Option<Vec<Address>>,
+    /// Maximum gas limit of a sponsored transaction.
+    pub max_sponsored_gas: Option<U256>,
+    /// Tokens allowed in the approval-based paymaster flow. `None` means that any token is allowed.
+    pub allowed_tokens: Option<Vec<Address>>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionReceipt {
     /// Transaction hash.
@@ -257,6 +269,10 @@ pub struct TransactionReceipt {
     /// Effective gas price
     #[serde(rename = "effectiveGasPrice")]
     pub effective_gas_price: Option<U256>,
+    /// Gas refunded by the operator to the transaction initiator after execution.
+    /// `None` if the transaction is not executed yet.
+    #[serde(rename = "gasRefunded", default)]
+    pub gas_refunded: Option<U256>,
 }
 
 /// The block type returned from RPC calls.
@@ -537,6 +553,10 @@ pub struct TransactionDetails {
     pub is_l1_originated: bool,
     pub status: TransactionStatus,
     pub fee: U256,
+    pub gas_limit: U256,
+    /// Gas refunded to the initiator after execution; `None` if the transaction is not executed yet.
+    pub gas_refunded: Option<U256>,
+    pub effective_gas_price: U256,
     pub gas_per_pubdata: U256,
     pub initiator_address: Address,
     pub received_at: DateTime<Utc>,
@@ -545,6 +565,239 @@ pub struct TransactionDetails {
     pub eth_execute_tx_hash: Option<H256>,
 }
 
+/// Lifecycle stage of an L1->L2 priority operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PriorityOpStage {
+    /// The operation is observed on L1 and waits in the priority queue.
+    Queued,
+    /// The operation is executed in a miniblock, but the L1 batch containing it is not sealed yet.
+    IncludedInMiniblock,
+    /// The operation is executed in a sealed L1 batch.
+    IncludedInL1Batch,
+    /// The operation is still queued, but L1 has passed its expiration block.
+    Expired,
+}
+
+/// Status of an L1->L2 priority operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriorityOpStatus {
+    /// Hash of the L1 transaction that emitted the operation.
+    pub l1_tx_hash: H256,
+    /// Hash of the corresponding L2 transaction.
+    pub l2_tx_hash: H256,
+    pub priority_op_id: u64,
+    /// L1 block in which the operation was emitted.
+    pub l1_block_number: u64,
+    /// Deadline L1 block until which the operation must be processed.
+    pub expiration_block: u64,
+    pub received_at: DateTime<Utc>,
+    pub stage: PriorityOpStage,
+    /// Number of priority operations ahead of this one in the queue. Only set for queued operations.
+    pub queue_position: Option<u64>,
+    pub miniblock_number: Option<MiniblockNumber>,
+    pub l1_batch_number: Option<L1BatchNumber>,
+    /// Execution status; 1 for success and 0 for failure. Only set for executed operations.
+    pub status: Option<U64>,
+    /// Revert reason for failed operations (e.g., failed deposits).
+    pub revert_reason: Option<String>,
+}
+
+/// Milestone in the lifecycle of an L2 transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionMilestone {
+    /// The transaction is received by the API server.
+    Received,
+    /// The transaction has passed validation and is persisted.
+    Validated,
+    /// The transaction is loaded into the state keeper mempool.
+    EnteredMempool,
+    IncludedInMiniblock,
+    L1BatchSealed,
+    /// The L1 batch containing the transaction is committed on L1.
+    Committed,
+    /// The L1 batch containing the transaction is proven on L1.
+    Proven,
+    /// The L1 batch containing the transaction is executed on L1.
+    ExecutedOnL1,
+}
+
+/// Milestone reached by a transaction together with the time it was reached.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMilestoneRecord {
+    pub milestone: TransactionMilestone,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Timestamped milestones reached by a transaction, in the order they were reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionJourney {
+    pub tx_hash: H256,
+    pub miniblock_number: Option<MiniblockNumber>,
+    pub l1_batch_number: Option<L1BatchNumber>,
+    pub milestones: Vec<TransactionMilestoneRecord>,
+}
+
+/// Value differing between two executions of the same transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueDiff<T> {
+    pub left: T,
+    pub right: T,
+}
+
+/// Storage slot with different final values after two executions. `None` means that the slot
+/// was not written to during the corresponding execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageWriteDiff {
+    pub address: Address,
+    pub key: H256,
+    pub left: Option<H256>,
+    pub right: Option<H256>,
+}
+
+/// Event emitted during a transaction execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmittedEvent {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    pub data: Bytes,
+}
+
+/// Events at the same position in two executions that differ. `None` means that the corresponding
+/// execution emitted fewer events.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDiff {
+    pub index: usize,
+    pub left: Option<EmittedEvent>,
+    pub right: Option<EmittedEvent>,
+}
+
+/// Structured difference between two executions of the same transaction. Fields are empty
+/// for equivalent executions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionDiff {
+    /// Execution outcomes, e.g. `success` or `revert: <reason>`.
+    pub outcome: Option<ValueDiff<String>>,
+    pub gas_used: Option<ValueDiff<u32>>,
+    /// Storage slots with different final values, ordered by address and key.
+    pub storage_writes: Vec<StorageWriteDiff>,
+    pub events: Vec<EventDiff>,
+}
+
+impl ExecutionDiff {
+    /// Checks whether the compared executions are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.outcome.is_none()
+            && self.gas_used.is_none()
+            && self.storage_writes.is_empty()
+            && self.events.is_empty()
+    }
+}
+
+/// Comparison of executing a transaction with and without bytecode compression.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytecodeCompressionComparison {
+    /// Error compressing bytecodes. If set, only the execution without compression was performed.
+    pub compression_error: Option<String>,
+    /// Difference between the execution with compression (left) and without compression (right).
+    pub diff: Option<ExecutionDiff>,
+}
+
+/// Direction of a bridge transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BridgeTransferKind {
+    Deposit,
+    Withdrawal,
+}
+
+/// Status of a bridge transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BridgeTransferStatus {
+    /// Withdrawal is initiated on L2, but is not known to be finalized on L1.
+    Initiated,
+    /// Deposit is executed on L2, or withdrawal is finalized on L1.
+    Finalized,
+    /// Deposit has failed on L2; the funds can be claimed back on L1.
+    Failed,
+}
+
+/// Deposit or withdrawal via the ETH or ERC20 bridge, together with its L1 and L2 parts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeTransfer {
+    pub kind: BridgeTransferKind,
+    pub status: BridgeTransferStatus,
+    /// L1 sender for deposits and L2 sender for withdrawals.
+    pub sender: Address,
+    /// L2 receiver for deposits and L1 receiver for withdrawals.
+    pub receiver: Address,
+    /// L1 address of the token; zero for ETH.
+    pub l1_token: Address,
+    /// L2 address of the token; zero for ETH. May be unknown for failed deposits of new tokens.
+    pub l2_token: Option<Address>,
+    pub amount: U256,
+    /// Hash of the L1 transaction: the deposit transaction or the withdrawal finalization transaction.
+    pub l1_tx_hash: Option<H256>,
+    /// L2 transaction: the executed deposit or the withdrawal initiation.
+    pub l2_tx_hash: H256,
+    pub miniblock_number: MiniblockNumber,
+    pub l1_batch_number: L1BatchNumber,
+    /// Index of the L2->L1 message in the L1 batch; only set for withdrawals.
+    pub l2_message_index: Option<u32>,
+}
+
+/// Utilization of a single criterion deciding whether to seal the L1 batch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SealCriterionUtilization {
+    pub criterion: String,
+    /// Amount of the resource used by the L1 batch so far.
+    pub used: u64,
+    /// Amount of the resource at which the L1 batch is sealed.
+    pub seal_threshold: u64,
+    /// Ratio of `used` to `seal_threshold`; the criterion triggers once it exceeds 1.
+    pub utilization: f64,
+}
+
+impl SealCriterionUtilization {
+    pub fn new(criterion: &str, used: u64, seal_threshold: u64) -> Self {
+        let utilization = if seal_threshold == 0 {
+            0.0
+        } else {
+            used as f64 / seal_threshold as f64
+        };
+        Self {
+            criterion: criterion.to_owned(),
+            used,
+            seal_threshold,
+            utilization,
+        }
+    }
+}
+
+/// Sealing status of the L1 batch currently being executed by the state keeper.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchSealingStatus {
+    pub l1_batch_number: L1BatchNumber,
+    /// Number of transactions in the L1 batch, including the last executed one.
+    pub tx_count: usize,
+    pub criteria: Vec<SealCriterionUtilization>,
+    /// Criterion with the highest utilization, i.e., the criterion expected to seal the L1 batch.
+    pub next_seal_criterion: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct GetLogsFilter {
     pub from_block: MiniblockNumber,
@@ -683,4 +936,20 @@ pub struct L1BatchDetails {
     pub number: L1BatchNumber,
     #[serde(flatten)]
     pub base: BlockDetailsBase,
+    /// Information about the L1 batch pubdata published on the external DA layer. Only present
+    /// for validium chains after the pubdata is dispatched to the DA layer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_availability: Option<DataAvailabilityDetails>,
+}
+
+/// Pointer to the L1 batch pubdata on the external DA layer together with its inclusion proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataAvailabilityDetails {
+    /// Pointer to the pubdata on the DA layer (e.g., a blob ID); the format is specific to the DA layer.
+    pub blob_id: String,
+    pub sent_at: DateTime<Utc>,
+    /// Inclusion proof / attestation returned by the DA layer. `None` until inclusion is confirmed.
+    pub inclusion_data: Option<Bytes>,
+    pub inclusion_confirmed_at: Option<DateTime<Utc>>,
 }
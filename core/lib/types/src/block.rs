@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
-use zksync_config::constants::SYSTEM_BLOCK_INFO_BLOCK_NUMBER_MULTIPLIER;
+use zksync_config::constants::{
+    L1_GAS_PER_PUBDATA_BYTE, SYSTEM_BLOCK_INFO_BLOCK_NUMBER_MULTIPLIER,
+};
 
 use std::{fmt, ops};
 
@@ -76,6 +78,9 @@ pub struct MiniblockHeader {
 
     pub l1_gas_price: u64, // L1 gas price assumed in the corresponding batch
     pub l2_fair_gas_price: u64, // L2 gas price assumed in the corresponding batch
+    /// Price of publishing a byte of pubdata (in wei) assumed in the corresponding batch. `None` for miniblocks
+    /// sealed before the price was tracked separately; for them, it's derived from `l1_gas_price`.
+    pub fair_pubdata_price: Option<u64>,
     pub base_system_contracts_hashes: BaseSystemContractsHashes,
     pub protocol_version: Option<ProtocolVersionId>,
     /// The maximal number of virtual blocks to be created in the miniblock.
@@ -92,6 +97,15 @@ pub struct MiniblockReexecuteData {
     pub txs: Vec<Transaction>,
 }
 
+impl MiniblockHeader {
+    /// Returns the pubdata price assumed in this miniblock, falling back to the calldata price
+    /// for miniblocks that don't have it persisted.
+    pub fn fair_pubdata_price(&self) -> u64 {
+        self.fair_pubdata_price
+            .unwrap_or(self.l1_gas_price * u64::from(L1_GAS_PER_PUBDATA_BYTE))
+    }
+}
+
 impl L1BatchHeader {
     pub fn new(
         number: L1BatchNumber,
@@ -6,11 +6,12 @@
 //! required for the rollup to execute L1 batches, it's needed for the proof generation and the Ethereum
 //! transactions, thus the calculations are done separately and asynchronously.
 
+use blake2::{Blake2s256, Digest};
 use serde::{Deserialize, Serialize};
 
 use std::{collections::HashMap, convert::TryFrom};
 
-use zksync_config::constants::ZKPORTER_IS_AVAILABLE;
+use zksync_config::{configs::chain::CommitmentSchemeConfig, constants::ZKPORTER_IS_AVAILABLE};
 use zksync_mini_merkle_tree::MiniMerkleTree;
 
 use crate::{
@@ -20,7 +21,8 @@ use crate::{
     l2_to_l1_log::L2ToL1Log,
     web3::signing::keccak256,
     writes::{InitialStorageWrite, RepeatedStorageWrite},
-    H256, KNOWN_CODES_STORAGE_ADDRESS, U256,
+    CommitmentHashFunction, L1BatchCommitmentMode, ProtocolVersionId, H256,
+    KNOWN_CODES_STORAGE_ADDRESS, U256,
 };
 
 /// Type that can be serialized for commitment.
@@ -127,7 +129,22 @@ impl L1BatchWithMetadata {
         ])
     }
 
-    pub fn l1_commit_data(&self) -> Token {
+    /// Returns storage writes in the form they are published to L1 in the specified `mode`.
+    ///
+    /// In the rollup mode, these are the compressed writes themselves. In the validium mode,
+    /// nothing is published, since storage writes are not a part of the batch commitment.
+    pub fn published_storage_writes(&self, mode: L1BatchCommitmentMode) -> (Vec<u8>, Vec<u8>) {
+        match mode {
+            L1BatchCommitmentMode::Rollup => (
+                self.metadata.initial_writes_compressed.clone(),
+                self.metadata.repeated_writes_compressed.clone(),
+            ),
+            L1BatchCommitmentMode::Validium => (vec![], vec![]),
+        }
+    }
+
+    pub fn l1_commit_data(&self, mode: L1BatchCommitmentMode) -> Token {
+        let (initial_writes, repeated_writes) = self.published_storage_writes(mode);
         Token::Tuple(vec![
             Token::Uint(U256::from(self.header.number.0)),
             Token::Uint(U256::from(self.header.timestamp)),
@@ -141,8 +158,8 @@ impl L1BatchWithMetadata {
                     .as_bytes()
                     .to_vec(),
             ),
-            Token::Bytes(self.metadata.initial_writes_compressed.clone()),
-            Token::Bytes(self.metadata.repeated_writes_compressed.clone()),
+            Token::Bytes(initial_writes),
+            Token::Bytes(repeated_writes),
             Token::Bytes(self.metadata.l2_l1_messages_compressed.clone()),
             Token::Array(
                 self.header
@@ -160,8 +177,22 @@ impl L1BatchWithMetadata {
         ])
     }
 
-    pub fn l1_commit_data_size(&self) -> usize {
-        crate::ethabi::encode(&[Token::Array(vec![self.l1_commit_data()])]).len()
+    pub fn l1_commit_data_size(&self, mode: L1BatchCommitmentMode) -> usize {
+        crate::ethabi::encode(&[Token::Array(vec![self.l1_commit_data(mode)])]).len()
+    }
+
+    /// Returns the size of raw pubdata published for this L1 batch: storage writes (if they are published
+    /// in the specified mode), L2-to-L1 logs and messages, and published bytecodes. Unlike
+    /// [`Self::l1_commit_data_size()`], this doesn't include the fixed-size batch fields or ABI encoding overhead.
+    pub fn pubdata_size(&self, mode: L1BatchCommitmentMode) -> usize {
+        let (initial_writes, repeated_writes) = self.published_storage_writes(mode);
+        let messages_size: usize = self.header.l2_to_l1_messages.iter().map(Vec::len).sum();
+        let bytecodes_size: usize = self.factory_deps.iter().map(Vec::len).sum();
+        initial_writes.len()
+            + repeated_writes.len()
+            + self.metadata.l2_l1_messages_compressed.len()
+            + messages_size
+            + bytecodes_size
     }
 }
 
@@ -225,14 +256,18 @@ impl L1BatchAuxiliaryOutput {
         l2_l1_logs: Vec<L2ToL1Log>,
         initial_writes: Vec<InitialStorageWrite>,
         repeated_writes: Vec<RepeatedStorageWrite>,
+        mode: L1BatchCommitmentMode,
     ) -> Self {
         let l2_l1_logs_compressed = serialize_commitments(&l2_l1_logs);
         let initial_writes_compressed = serialize_commitments(&initial_writes);
         let repeated_writes_compressed = serialize_commitments(&repeated_writes);
 
         let l2_l1_logs_linear_hash = H256::from(keccak256(&l2_l1_logs_compressed));
-        let initial_writes_hash = H256::from(keccak256(&initial_writes_compressed));
-        let repeated_writes_hash = H256::from(keccak256(&repeated_writes_compressed));
+        let (initial_writes_hash, repeated_writes_hash) = Self::storage_writes_hashes(
+            &initial_writes_compressed,
+            &repeated_writes_compressed,
+            mode,
+        );
 
         let merkle_tree_leaves = l2_l1_logs_compressed[4..]
             .chunks(L2ToL1Log::SERIALIZED_SIZE)
@@ -255,6 +290,22 @@ impl L1BatchAuxiliaryOutput {
         }
     }
 
+    fn storage_writes_hashes(
+        initial_writes_compressed: &[u8],
+        repeated_writes_compressed: &[u8],
+        mode: L1BatchCommitmentMode,
+    ) -> (H256, H256) {
+        // In the validium mode, storage writes are committed to only via the tree root hash
+        // in the pass-through data, so their hashes are zeroed in the auxiliary output.
+        match mode {
+            L1BatchCommitmentMode::Rollup => (
+                H256::from(keccak256(initial_writes_compressed)),
+                H256::from(keccak256(repeated_writes_compressed)),
+            ),
+            L1BatchCommitmentMode::Validium => (H256::zero(), H256::zero()),
+        }
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         // 4 H256 values
         const SERIALIZED_SIZE: usize = 128;
@@ -266,8 +317,8 @@ impl L1BatchAuxiliaryOutput {
         result
     }
 
-    pub fn hash(&self) -> H256 {
-        H256::from_slice(&keccak256(&self.to_bytes()))
+    pub fn hash(&self, hash_function: CommitmentHashFunction) -> H256 {
+        hash_function.apply(&self.to_bytes())
     }
 }
 
@@ -322,8 +373,8 @@ impl L1BatchPassThroughData {
         result
     }
 
-    pub fn hash(&self) -> H256 {
-        H256::from_slice(&keccak256(&self.to_bytes()))
+    pub fn hash(&self, hash_function: CommitmentHashFunction) -> H256 {
+        hash_function.apply(&self.to_bytes())
     }
 }
 
@@ -351,6 +402,7 @@ impl L1BatchCommitment {
         repeated_writes: Vec<RepeatedStorageWrite>,
         bootloader_code_hash: H256,
         default_aa_code_hash: H256,
+        mode: L1BatchCommitmentMode,
     ) -> Self {
         let meta_parameters = L1BatchMetaParameters {
             zkporter_is_available: ZKPORTER_IS_AVAILABLE,
@@ -376,11 +428,32 @@ impl L1BatchCommitment {
                 l2_to_l1_logs,
                 initial_writes,
                 repeated_writes,
+                mode,
             ),
             meta_parameters,
         }
     }
 
+    /// Publishes storage writes as state diffs compressed in the V2 format (see [`crate::state_diffs`])
+    /// instead of the V1 encoding. The compressed diffs take the place of initial writes, and repeated writes
+    /// are left empty.
+    pub fn with_compressed_state_diffs(
+        mut self,
+        compressed_state_diffs: Vec<u8>,
+        mode: L1BatchCommitmentMode,
+    ) -> Self {
+        let output = &mut self.auxiliary_output;
+        output.initial_writes_compressed = compressed_state_diffs;
+        output.repeated_writes_compressed = vec![];
+        (output.initial_writes_hash, output.repeated_writes_hash) =
+            L1BatchAuxiliaryOutput::storage_writes_hashes(
+                &output.initial_writes_compressed,
+                &output.repeated_writes_compressed,
+                mode,
+            );
+        self
+    }
+
     pub fn meta_parameters(&self) -> L1BatchMetaParameters {
         self.meta_parameters.clone()
     }
@@ -414,15 +487,23 @@ impl L1BatchCommitment {
     }
 
     pub fn hash(&self) -> L1BatchCommitmentHash {
+        self.hash_with_scheme(&CommitmentScheme::default())
+    }
+
+    /// Hashes this commitment using the specified scheme.
+    pub fn hash_with_scheme(&self, scheme: &CommitmentScheme) -> L1BatchCommitmentHash {
+        let component_hash = scheme.component_hash;
         let mut result = vec![];
-        let pass_through_data_hash = self.pass_through_data.hash();
+        let pass_through_data_hash = self.pass_through_data.hash(component_hash);
         result.extend_from_slice(pass_through_data_hash.as_bytes());
-        let metadata_hash = self.meta_parameters.hash();
+        let metadata_hash = component_hash.apply(&self.meta_parameters.to_bytes());
         result.extend_from_slice(metadata_hash.as_bytes());
-        let auxiliary_output_hash = self.auxiliary_output.hash();
+        let auxiliary_output_hash = self.auxiliary_output.hash(component_hash);
         result.extend_from_slice(auxiliary_output_hash.as_bytes());
-        let hash = keccak256(&result);
-        let commitment = H256::from_slice(&hash);
+        for field in &scheme.custom_fields {
+            result.extend_from_slice(field.as_bytes());
+        }
+        let commitment = scheme.commitment_hash.apply(&result);
         L1BatchCommitmentHash {
             pass_through_data: pass_through_data_hash,
             aux_output: auxiliary_output_hash,
@@ -432,17 +513,90 @@ impl L1BatchCommitment {
     }
 }
 
+trait CommitmentHashFunctionExt {
+    fn apply(self, bytes: &[u8]) -> H256;
+}
+
+impl CommitmentHashFunctionExt for CommitmentHashFunction {
+    fn apply(self, bytes: &[u8]) -> H256 {
+        match self {
+            Self::Keccak256 => H256(keccak256(bytes)),
+            Self::Blake2s256 => H256(Blake2s256::digest(bytes).into()),
+        }
+    }
+}
+
+/// Scheme used to hash L1 batch commitments. The default scheme matches the zkSync Era L1 contracts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitmentScheme {
+    /// Hash function applied to serialized pass-through data, meta parameters and auxiliary output.
+    pub component_hash: CommitmentHashFunction,
+    /// Hash function used to combine component hashes into the commitment.
+    pub commitment_hash: CommitmentHashFunction,
+    /// Custom fields appended to the commitment preimage after the component hashes.
+    pub custom_fields: Vec<H256>,
+}
+
+/// Commitment schemes used by a chain, gated by protocol versions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitmentSchemes {
+    default: CommitmentScheme,
+    custom: Option<(ProtocolVersionId, CommitmentScheme)>,
+}
+
+impl CommitmentSchemes {
+    /// Creates schemes that use the `custom` scheme for L1 batches with protocol version `since`
+    /// and newer, and the default scheme for older L1 batches.
+    pub fn with_custom_scheme(since: ProtocolVersionId, custom: CommitmentScheme) -> Self {
+        Self {
+            default: CommitmentScheme::default(),
+            custom: Some((since, custom)),
+        }
+    }
+
+    /// Returns the scheme used for L1 batches with the specified protocol version.
+    /// L1 batches without a protocol version always use the default scheme.
+    pub fn for_protocol_version(&self, version: Option<ProtocolVersionId>) -> &CommitmentScheme {
+        match (&self.custom, version) {
+            (Some((since, custom)), Some(version)) if version >= *since => custom,
+            _ => &self.default,
+        }
+    }
+}
+
+impl TryFrom<&CommitmentSchemeConfig> for CommitmentSchemes {
+    type Error = num_enum::TryFromPrimitiveError<ProtocolVersionId>;
+
+    fn try_from(config: &CommitmentSchemeConfig) -> Result<Self, Self::Error> {
+        let Some(since) = config.custom_since_protocol_version else {
+            return Ok(Self::default());
+        };
+        let custom = CommitmentScheme {
+            component_hash: config.component_hash_function,
+            commitment_hash: config.commitment_hash_function,
+            custom_fields: config.custom_fields.clone(),
+        };
+        Ok(Self::with_custom_scheme(since.try_into()?, custom))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
     use serde_with::serde_as;
 
+    use crate::block::L1BatchHeader;
     use crate::commitment::{
-        L1BatchAuxiliaryOutput, L1BatchCommitment, L1BatchMetaParameters, L1BatchPassThroughData,
+        CommitmentScheme, CommitmentSchemes, L1BatchAuxiliaryOutput, L1BatchCommitment,
+        L1BatchMetaParameters, L1BatchMetadata, L1BatchPassThroughData, L1BatchWithMetadata,
     };
+    use crate::ethabi::Token;
     use crate::l2_to_l1_log::L2ToL1Log;
     use crate::writes::{InitialStorageWrite, RepeatedStorageWrite};
-    use crate::{H256, U256};
+    use crate::{
+        Address, CommitmentHashFunction, L1BatchCommitmentMode, L1BatchNumber, ProtocolVersionId,
+        H256, U256,
+    };
 
     #[serde_as]
     #[derive(Debug, Serialize, Deserialize)]
@@ -490,19 +644,22 @@ mod tests {
         expected_outputs: ExpectedOutput,
     }
 
-    #[test]
-    fn commitment_test() {
+    fn load_commitment_test() -> CommitmentTest {
         let zksync_home = std::env::var("ZKSYNC_HOME").unwrap_or_else(|_| ".".into());
         let path = std::path::Path::new(&zksync_home)
             .join("etc/commitment_tests/zksync_testharness_test.json");
         let contents = std::fs::read_to_string(path).unwrap();
-        let commitment_test: CommitmentTest = serde_json::from_str(&contents).unwrap();
+        serde_json::from_str(&contents).unwrap()
+    }
 
+    fn commitment_from_test(
+        commitment_test: &CommitmentTest,
+        mode: L1BatchCommitmentMode,
+    ) -> L1BatchCommitment {
         let initial_writes = commitment_test
             .auxiliary_input
             .initial_writes
-            .clone()
-            .into_iter()
+            .iter()
             .enumerate()
             .map(|(index, a)| InitialStorageWrite {
                 index: index as u64 + 1,
@@ -514,13 +671,19 @@ mod tests {
             commitment_test.auxiliary_input.l2_l1_logs.clone(),
             initial_writes,
             commitment_test.auxiliary_input.repeated_writes.clone(),
+            mode,
         );
-
-        let commitment = L1BatchCommitment {
-            pass_through_data: commitment_test.pass_through_data,
+        L1BatchCommitment {
+            pass_through_data: commitment_test.pass_through_data.clone(),
             auxiliary_output,
-            meta_parameters: commitment_test.meta_parameters,
-        };
+            meta_parameters: commitment_test.meta_parameters.clone(),
+        }
+    }
+
+    #[test]
+    fn commitment_test() {
+        let commitment_test = load_commitment_test();
+        let commitment = commitment_from_test(&commitment_test, L1BatchCommitmentMode::Rollup);
 
         assert_eq!(
             commitment.auxiliary_output.l2_l1_logs_compressed.len(),
@@ -570,7 +733,9 @@ mod tests {
             commitment_test.expected_outputs.pass_through_bytes
         );
         assert_eq!(
-            commitment.pass_through_data.hash(),
+            commitment
+                .pass_through_data
+                .hash(CommitmentHashFunction::Keccak256),
             commitment_test.expected_outputs.pass_through_hash
         );
         assert_eq!(
@@ -586,7 +751,9 @@ mod tests {
             commitment_test.expected_outputs.auxiliary_bytes
         );
         assert_eq!(
-            commitment.auxiliary_output.hash(),
+            commitment
+                .auxiliary_output
+                .hash(CommitmentHashFunction::Keccak256),
             commitment_test.expected_outputs.auxiliary_hash
         );
         assert_eq!(
@@ -594,4 +761,194 @@ mod tests {
             commitment_test.expected_outputs.commitment_hash
         );
     }
+
+    fn l1_batch_from_test(expected_outputs: &ExpectedOutput) -> L1BatchWithMetadata {
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            100,
+            Address::zero(),
+            Default::default(),
+            ProtocolVersionId::latest(),
+        );
+        let metadata = L1BatchMetadata {
+            root_hash: H256::repeat_byte(1),
+            rollup_last_leaf_index: 10,
+            merkle_root_hash: H256::repeat_byte(1),
+            initial_writes_compressed: expected_outputs.initial_writes_bytes.clone(),
+            repeated_writes_compressed: expected_outputs.repeated_writes_bytes.clone(),
+            commitment: expected_outputs.commitment_hash,
+            l2_l1_messages_compressed: expected_outputs.l2_l1_bytes.clone(),
+            l2_l1_merkle_root: expected_outputs.l2_l1_root_hash,
+            block_meta_params: L1BatchMetaParameters {
+                zkporter_is_available: false,
+                bootloader_code_hash: H256::zero(),
+                default_aa_code_hash: H256::zero(),
+            },
+            aux_data_hash: expected_outputs.auxiliary_hash,
+            meta_parameters_hash: expected_outputs.meta_params_hash,
+            pass_through_data_hash: expected_outputs.pass_through_hash,
+        };
+        L1BatchWithMetadata {
+            header,
+            metadata,
+            factory_deps: vec![],
+        }
+    }
+
+    fn published_writes(commit_data: Token) -> (Vec<u8>, Vec<u8>) {
+        let Token::Tuple(fields) = commit_data else {
+            panic!("unexpected commit data: {commit_data:?}");
+        };
+        let initial_writes = fields[7].clone().into_bytes().unwrap();
+        let repeated_writes = fields[8].clone().into_bytes().unwrap();
+        (initial_writes, repeated_writes)
+    }
+
+    #[test]
+    fn commit_data_in_rollup_mode() {
+        let commitment_test = load_commitment_test();
+        let expected = &commitment_test.expected_outputs;
+        let l1_batch = l1_batch_from_test(expected);
+
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup);
+        let (initial_writes, repeated_writes) = published_writes(commit_data);
+        assert_eq!(initial_writes, expected.initial_writes_bytes);
+        assert_eq!(repeated_writes, expected.repeated_writes_bytes);
+    }
+
+    #[test]
+    fn commit_data_in_validium_mode() {
+        let commitment_test = load_commitment_test();
+        let expected = &commitment_test.expected_outputs;
+        let l1_batch = l1_batch_from_test(expected);
+
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Validium);
+        let (initial_writes, repeated_writes) = published_writes(commit_data);
+        assert_eq!(initial_writes, b"");
+        assert_eq!(repeated_writes, b"");
+
+        let rollup_size = l1_batch.l1_commit_data_size(L1BatchCommitmentMode::Rollup);
+        let validium_size = l1_batch.l1_commit_data_size(L1BatchCommitmentMode::Validium);
+        assert!(
+            validium_size < rollup_size,
+            "{validium_size} >= {rollup_size}"
+        );
+
+        let published_writes_size =
+            expected.initial_writes_bytes.len() + expected.repeated_writes_bytes.len();
+        let rollup_pubdata_size = l1_batch.pubdata_size(L1BatchCommitmentMode::Rollup);
+        let validium_pubdata_size = l1_batch.pubdata_size(L1BatchCommitmentMode::Validium);
+        assert_eq!(
+            rollup_pubdata_size - validium_pubdata_size,
+            published_writes_size
+        );
+        assert!(rollup_pubdata_size < rollup_size);
+    }
+
+    fn mock_commitment() -> L1BatchCommitment {
+        L1BatchCommitment::new(
+            vec![],
+            10,
+            H256::repeat_byte(1),
+            vec![],
+            vec![],
+            H256::repeat_byte(2),
+            H256::repeat_byte(3),
+            L1BatchCommitmentMode::Rollup,
+        )
+    }
+
+    /// Golden commitment vectors for each commitment mode; the rollup vector is the one
+    /// from the test harness JSON.
+    #[test]
+    fn commitment_vectors_per_mode() {
+        let commitment_test = load_commitment_test();
+        let expected = &commitment_test.expected_outputs;
+
+        let commitment = commitment_from_test(&commitment_test, L1BatchCommitmentMode::Rollup);
+        let hash = commitment.hash();
+        assert_eq!(hash.aux_output, expected.auxiliary_hash);
+        assert_eq!(
+            hash.commitment,
+            "0x3af4672cd1362badfc0cbc47a7e8b3fbcd3c947055af041b4481bb15009c41a8"
+                .parse::<H256>()
+                .unwrap()
+        );
+
+        let commitment = commitment_from_test(&commitment_test, L1BatchCommitmentMode::Validium);
+        assert_eq!(
+            commitment.initial_writes_compressed(),
+            expected.initial_writes_bytes
+        );
+        assert_eq!(commitment.initial_writes_pubdata_hash(), H256::zero());
+        assert_eq!(commitment.repeated_writes_pubdata_hash(), H256::zero());
+        let hash = commitment.hash();
+        assert_eq!(hash.pass_through_data, expected.pass_through_hash);
+        assert_eq!(hash.meta_parameters, expected.meta_params_hash);
+        assert_eq!(
+            hash.aux_output,
+            "0xeeb4d9246cb655ca848a757052647c8a3dbf4135a27e57333b22c0a1a2923141"
+                .parse::<H256>()
+                .unwrap()
+        );
+        assert_eq!(
+            hash.commitment,
+            "0xc454711b9e13ca305d3d5ab67c56918597f3519bd431470f3b452ef2ab326204"
+                .parse::<H256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn custom_commitment_scheme() {
+        let commitment = mock_commitment();
+        let default_hash = commitment.hash();
+
+        let scheme_with_custom_fields = CommitmentScheme {
+            custom_fields: vec![H256::repeat_byte(0xff)],
+            ..CommitmentScheme::default()
+        };
+        let hash = commitment.hash_with_scheme(&scheme_with_custom_fields);
+        assert_eq!(hash.pass_through_data, default_hash.pass_through_data);
+        assert_eq!(hash.meta_parameters, default_hash.meta_parameters);
+        assert_eq!(hash.aux_output, default_hash.aux_output);
+        assert_ne!(hash.commitment, default_hash.commitment);
+
+        let blake2_scheme = CommitmentScheme {
+            component_hash: CommitmentHashFunction::Blake2s256,
+            ..CommitmentScheme::default()
+        };
+        let hash = commitment.hash_with_scheme(&blake2_scheme);
+        assert_ne!(hash.pass_through_data, default_hash.pass_through_data);
+        assert_ne!(hash.meta_parameters, default_hash.meta_parameters);
+        assert_ne!(hash.aux_output, default_hash.aux_output);
+        assert_ne!(hash.commitment, default_hash.commitment);
+    }
+
+    #[test]
+    fn commitment_schemes_are_gated_by_protocol_version() {
+        let custom_scheme = CommitmentScheme {
+            commitment_hash: CommitmentHashFunction::Blake2s256,
+            ..CommitmentScheme::default()
+        };
+        let schemes = CommitmentSchemes::with_custom_scheme(
+            ProtocolVersionId::Version15,
+            custom_scheme.clone(),
+        );
+
+        let default_scheme = CommitmentScheme::default();
+        assert_eq!(schemes.for_protocol_version(None), &default_scheme);
+        assert_eq!(
+            schemes.for_protocol_version(Some(ProtocolVersionId::Version14)),
+            &default_scheme
+        );
+        assert_eq!(
+            schemes.for_protocol_version(Some(ProtocolVersionId::Version15)),
+            &custom_scheme
+        );
+        assert_eq!(
+            schemes.for_protocol_version(Some(ProtocolVersionId::Version16)),
+            &custom_scheme
+        );
+    }
 }
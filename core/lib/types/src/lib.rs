@@ -45,12 +45,15 @@ pub mod l2;
 pub mod l2_to_l1_log;
 pub mod priority_op_onchain_data;
 pub mod protocol_version;
+pub mod pubdata;
+pub mod state_diffs;
 pub mod storage;
 pub mod storage_writes_deduplicator;
 pub mod system_contracts;
 pub mod tokens;
 pub mod tx;
 pub mod vm_trace;
+pub mod withdrawals;
 
 pub mod api;
 pub mod eth_sender;
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use zksync_basic_types::L1BatchNumber;
+use zksync_basic_types::{web3::signing::keccak256, L1BatchNumber, H256};
 
 use crate::aggregated_operations::L1BatchProofForL1;
 use crate::proofs::PrepareBasicCircuitsJob;
@@ -23,6 +23,25 @@ pub enum ProofGenerationDataResponse {
     Error(String),
 }
 
+/// Metadata of the witness input for an L1 batch. The input itself (serialized `PrepareBasicCircuitsJob`)
+/// can be downloaded in chunks of `chunk_size` bytes using HTTP range requests; each chunk can be verified
+/// against the corresponding checksum from `chunk_checksums`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofGenerationDataInfo {
+    pub l1_batch_number: L1BatchNumber,
+    pub data_size: u64,
+    pub chunk_size: u64,
+    pub chunk_checksums: Vec<H256>,
+    pub fri_protocol_version_id: FriProtocolVersionId,
+    pub l1_verifier_config: L1VerifierConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ProofGenerationDataInfoResponse {
+    Success(ProofGenerationDataInfo),
+    Error(String),
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SubmitProofRequest {
     Proof(Box<L1BatchProofForL1>),
@@ -35,3 +54,24 @@ pub enum SubmitProofResponse {
     Success,
     Error(String),
 }
+
+/// Query parameters for uploading a chunk of a serialized `SubmitProofRequest`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitProofChunkParams {
+    pub total_chunks: u32,
+    pub checksum: H256,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SubmitProofChunkResponse {
+    /// The chunk is accepted. If `missing_chunks` is empty, the proof is assembled and saved.
+    Success {
+        missing_chunks: Vec<u32>,
+    },
+    Error(String),
+}
+
+/// Computes the checksum of a chunk of witness input or proof data.
+pub fn chunk_checksum(chunk: &[u8]) -> H256 {
+    H256(keccak256(chunk))
+}
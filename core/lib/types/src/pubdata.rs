@@ -0,0 +1,542 @@
+//! Decoding of pubdata committed to L1.
+//!
+//! All data necessary to reconstruct the L2 state is published in the calldata of `commitBlocks()` transactions
+//! (see [`L1BatchWithMetadata::l1_commit_data()`] for the encoding). This module parses it back into storage
+//! writes, L2-to-L1 logs and messages, and published bytecodes, so that the state can be reconstructed
+//! purely from L1 data.
+//!
+//! [`L1BatchWithMetadata::l1_commit_data()`]: crate::commitment::L1BatchWithMetadata::l1_commit_data()
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    commitment::SerializeCommitment,
+    ethabi::{Contract, Token},
+    l2_to_l1_log::L2ToL1Log,
+    state_diffs::{self, DecompressedStateDiffs, StateDiffCompressionVersion},
+    web3::types::Bytes,
+    writes::{InitialStorageWrite, RepeatedStorageWrite},
+    L1BatchCommitmentMode, L1BatchNumber, H256, U256,
+};
+
+/// Number of fields in the commit data of a single L1 batch.
+const COMMIT_DATA_FIELD_COUNT: usize = 12;
+
+/// Errors that can occur when decoding committed pubdata.
+#[derive(Debug, Error)]
+pub enum PubdataDecodeError {
+    #[error("commit data doesn't match the `commitBlocks()` ABI: {0}")]
+    Abi(String),
+    #[error("malformed {field}: {reason}")]
+    Malformed { field: &'static str, reason: String },
+}
+
+impl PubdataDecodeError {
+    fn malformed(field: &'static str, reason: impl Into<String>) -> Self {
+        Self::Malformed {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Storage writes as published to L1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PublishedStorageWrites {
+    /// Storage writes are published in full (the rollup mode).
+    #[serde(rename_all = "camelCase")]
+    Full {
+        /// Initial writes with the enumeration indices assigned to them. Indices are not published explicitly;
+        /// they are derived from the index of the next leaf in the Merkle tree, since indices are allocated
+        /// sequentially in the order of publication.
+        initial_writes: Vec<InitialStorageWrite>,
+        repeated_writes: Vec<RepeatedStorageWrite>,
+    },
+    /// Storage writes are published as state diffs in the V2 format (the rollup mode). Values are left
+    /// compressed since restoring them requires the values of slots before the L1 batch.
+    Compressed(DecompressedStateDiffs),
+    /// Storage writes are not published (the validium mode). The writes must be obtained
+    /// from the data availability layer of the chain.
+    Offchain,
+}
+
+/// Pubdata of a single L1 batch decoded from its commit data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BatchPubdata {
+    pub l1_batch_number: L1BatchNumber,
+    pub storage_writes: PublishedStorageWrites,
+    pub l2_to_l1_logs: Vec<L2ToL1Log>,
+    pub l2_to_l1_messages: Vec<Bytes>,
+    pub factory_deps: Vec<Bytes>,
+}
+
+impl L1BatchPubdata {
+    /// Decodes pubdata from the commit data of a single L1 batch. `state_diffs_version` is the format of storage writes
+    /// determined by the protocol version of the L1 batch.
+    pub fn decode(
+        commit_data: &Token,
+        mode: L1BatchCommitmentMode,
+        state_diffs_version: StateDiffCompressionVersion,
+    ) -> Result<Self, PubdataDecodeError> {
+        let Token::Tuple(fields) = commit_data else {
+            return Err(PubdataDecodeError::Abi("commit data is not a tuple".into()));
+        };
+        if fields.len() != COMMIT_DATA_FIELD_COUNT {
+            return Err(PubdataDecodeError::Abi(format!(
+                "expected {COMMIT_DATA_FIELD_COUNT} fields in commit data, got {}",
+                fields.len()
+            )));
+        }
+
+        let l1_batch_number = uint_field(&fields[0], "blockNumber")?;
+        let l1_batch_number = u32::try_from(l1_batch_number)
+            .map_err(|_| PubdataDecodeError::malformed("blockNumber", "out of range"))?;
+        let next_leaf_index = uint_field(&fields[2], "indexRepeatedStorageChanges")?;
+        let initial_writes = bytes_field(&fields[7], "initialStorageChanges")?;
+        let repeated_writes = bytes_field(&fields[8], "repeatedStorageChanges")?;
+        let storage_writes = match (mode, state_diffs_version) {
+            (L1BatchCommitmentMode::Rollup, StateDiffCompressionVersion::V1) => {
+                PublishedStorageWrites::Full {
+                    initial_writes: decode_initial_writes(initial_writes, next_leaf_index)?,
+                    repeated_writes: decode_repeated_writes(repeated_writes)?,
+                }
+            }
+            (L1BatchCommitmentMode::Rollup, StateDiffCompressionVersion::V2) => {
+                ensure_empty(repeated_writes, "repeatedStorageChanges")?;
+                let state_diffs = state_diffs::decompress_v2(initial_writes).map_err(|err| {
+                    PubdataDecodeError::malformed("initialStorageChanges", err.to_string())
+                })?;
+                PublishedStorageWrites::Compressed(state_diffs)
+            }
+            (L1BatchCommitmentMode::Validium, _) => {
+                ensure_empty(initial_writes, "initialStorageChanges")?;
+                ensure_empty(repeated_writes, "repeatedStorageChanges")?;
+                PublishedStorageWrites::Offchain
+            }
+        };
+
+        let l2_to_l1_logs = bytes_field(&fields[9], "l2Logs")?;
+        let l2_to_l1_logs = split_serialized::<L2ToL1Log>(l2_to_l1_logs, "l2Logs")?
+            .map(L2ToL1Log::from_slice)
+            .collect();
+
+        Ok(Self {
+            l1_batch_number: L1BatchNumber(l1_batch_number),
+            storage_writes,
+            l2_to_l1_logs,
+            l2_to_l1_messages: bytes_array_field(&fields[10], "l2ArbitraryLengthMessages")?,
+            factory_deps: bytes_array_field(&fields[11], "factoryDeps")?,
+        })
+    }
+
+    /// Decodes pubdata for all L1 batches committed in the calldata of a `commitBlocks()` transaction.
+    /// `contract` is the ABI of the zkSync contract. All batches in a single transaction must have
+    /// the same state diffs format.
+    pub fn decode_commit_calldata(
+        contract: &Contract,
+        calldata: &[u8],
+        mode: L1BatchCommitmentMode,
+        state_diffs_version: StateDiffCompressionVersion,
+    ) -> Result<Vec<Self>, PubdataDecodeError> {
+        let function = contract
+            .function("commitBlocks")
+            .map_err(|err| PubdataDecodeError::Abi(err.to_string()))?;
+        let calldata = calldata
+            .strip_prefix(function.short_signature().as_slice())
+            .ok_or_else(|| PubdataDecodeError::Abi("calldata selector mismatch".into()))?;
+        let mut inputs = function
+            .decode_input(calldata)
+            .map_err(|err| PubdataDecodeError::Abi(err.to_string()))?;
+
+        let Some(Token::Array(commit_data)) = inputs.pop() else {
+            return Err(PubdataDecodeError::Abi(
+                "last `commitBlocks()` argument is not an array".into(),
+            ));
+        };
+        commit_data
+            .iter()
+            .map(|data| Self::decode(data, mode, state_diffs_version))
+            .collect()
+    }
+}
+
+fn uint_field(token: &Token, field: &'static str) -> Result<u64, PubdataDecodeError> {
+    match token {
+        Token::Uint(value) if *value <= U256::from(u64::MAX) => Ok(value.as_u64()),
+        Token::Uint(_) => Err(PubdataDecodeError::malformed(field, "out of range")),
+        _ => Err(PubdataDecodeError::Abi(format!("`{field}` is not a uint"))),
+    }
+}
+
+fn bytes_field<'a>(token: &'a Token, field: &'static str) -> Result<&'a [u8], PubdataDecodeError> {
+    match token {
+        Token::Bytes(bytes) => Ok(bytes),
+        _ => Err(PubdataDecodeError::Abi(format!("`{field}` is not bytes"))),
+    }
+}
+
+fn bytes_array_field(token: &Token, field: &'static str) -> Result<Vec<Bytes>, PubdataDecodeError> {
+    let Token::Array(items) = token else {
+        return Err(PubdataDecodeError::Abi(format!(
+            "`{field}` is not an array"
+        )));
+    };
+    items
+        .iter()
+        .map(|item| bytes_field(item, field).map(|bytes| Bytes(bytes.to_vec())))
+        .collect()
+}
+
+fn ensure_empty(bytes: &[u8], field: &'static str) -> Result<(), PubdataDecodeError> {
+    if bytes.is_empty() {
+        Ok(())
+    } else {
+        let reason = format!("expected no data, got {} bytes", bytes.len());
+        Err(PubdataDecodeError::malformed(field, reason))
+    }
+}
+
+/// Splits data serialized with [`SerializeCommitment`] into chunks corresponding to separate items.
+fn split_serialized<T: SerializeCommitment>(
+    bytes: &[u8],
+    field: &'static str,
+) -> Result<std::slice::Chunks<'_, u8>, PubdataDecodeError> {
+    if bytes.len() < 4 {
+        return Err(PubdataDecodeError::malformed(field, "missing item count"));
+    }
+    let (count, items) = bytes.split_at(4);
+    let count = u32::from_be_bytes(count.try_into().unwrap()) as usize;
+    if items.len() != count * T::SERIALIZED_SIZE {
+        let reason = format!(
+            "expected {count} items of {} bytes, got {} bytes",
+            T::SERIALIZED_SIZE,
+            items.len()
+        );
+        return Err(PubdataDecodeError::malformed(field, reason));
+    }
+    Ok(items.chunks(T::SERIALIZED_SIZE))
+}
+
+fn decode_initial_writes(
+    bytes: &[u8],
+    next_leaf_index: u64,
+) -> Result<Vec<InitialStorageWrite>, PubdataDecodeError> {
+    const FIELD: &str = "initialStorageChanges";
+
+    let chunks = split_serialized::<InitialStorageWrite>(bytes, FIELD)?;
+    let count = chunks.len() as u64;
+    let first_index = next_leaf_index
+        .checked_sub(count)
+        .filter(|&index| index > 0)
+        .ok_or_else(|| {
+            let reason = format!("{count} initial writes don't fit before leaf #{next_leaf_index}");
+            PubdataDecodeError::malformed(FIELD, reason)
+        })?;
+
+    let writes = chunks.zip(first_index..).map(|(chunk, index)| {
+        let (key, value) = chunk.split_at(32);
+        InitialStorageWrite {
+            index,
+            key: U256::from_little_endian(key),
+            value: H256::from_slice(value),
+        }
+    });
+    Ok(writes.collect())
+}
+
+fn decode_repeated_writes(bytes: &[u8]) -> Result<Vec<RepeatedStorageWrite>, PubdataDecodeError> {
+    let chunks = split_serialized::<RepeatedStorageWrite>(bytes, "repeatedStorageChanges")?;
+    let writes = chunks.map(|chunk| {
+        let (index, value) = chunk.split_at(8);
+        RepeatedStorageWrite {
+            index: u64::from_be_bytes(index.try_into().unwrap()),
+            value: H256::from_slice(value),
+        }
+    });
+    Ok(writes.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::L1BatchHeader,
+        commitment::{
+            serialize_commitments, L1BatchMetaParameters, L1BatchMetadata, L1BatchWithMetadata,
+        },
+        state_diffs::{
+            compress_v2, CompressedValue, RepeatedWriteDiff,
+            StateDiffCompressionVersion::{V1, V2},
+            ValueCompression,
+        },
+        Address, ProtocolVersionId,
+    };
+
+    fn mock_l1_batch() -> L1BatchWithMetadata {
+        let initial_writes = vec![
+            InitialStorageWrite {
+                index: 8,
+                key: U256::from(1),
+                value: H256::repeat_byte(1),
+            },
+            InitialStorageWrite {
+                index: 9,
+                key: U256::from(2),
+                value: H256::repeat_byte(2),
+            },
+        ];
+        let repeated_writes = vec![RepeatedStorageWrite {
+            index: 3,
+            value: H256::repeat_byte(3),
+        }];
+        let logs = vec![L2ToL1Log {
+            shard_id: 0,
+            is_service: true,
+            tx_number_in_block: 1,
+            sender: Address::repeat_byte(0x80),
+            key: H256::repeat_byte(4),
+            value: H256::repeat_byte(5),
+        }];
+
+        let mut header = L1BatchHeader::new(
+            L1BatchNumber(5),
+            100,
+            Address::zero(),
+            Default::default(),
+            ProtocolVersionId::latest(),
+        );
+        header.l2_to_l1_messages = vec![vec![1, 2, 3]];
+        let metadata = L1BatchMetadata {
+            root_hash: H256::repeat_byte(6),
+            rollup_last_leaf_index: 10,
+            merkle_root_hash: H256::repeat_byte(6),
+            initial_writes_compressed: serialize_commitments(&initial_writes),
+            repeated_writes_compressed: serialize_commitments(&repeated_writes),
+            commitment: H256::zero(),
+            l2_l1_messages_compressed: serialize_commitments(&logs),
+            l2_l1_merkle_root: H256::zero(),
+            block_meta_params: L1BatchMetaParameters {
+                zkporter_is_available: false,
+                bootloader_code_hash: H256::zero(),
+                default_aa_code_hash: H256::zero(),
+            },
+            aux_data_hash: H256::zero(),
+            meta_parameters_hash: H256::zero(),
+            pass_through_data_hash: H256::zero(),
+        };
+        L1BatchWithMetadata {
+            header,
+            metadata,
+            factory_deps: vec![vec![0xfe; 64]],
+        }
+    }
+
+    /// Commit data with pubdata fields encoded by hand, independently of [`L1BatchWithMetadata::l1_commit_data()`].
+    fn expected_commit_data() -> Token {
+        // 1 initial write: key 1 (little-endian), value 0x11..11
+        let initial_writes = hex::decode(
+            "00000001\
+             0100000000000000000000000000000000000000000000000000000000000000\
+             1111111111111111111111111111111111111111111111111111111111111111",
+        )
+        .unwrap();
+        // 1 repeated write: index 3 (big-endian), value 0x22..22
+        let repeated_writes = hex::decode(
+            "00000001\
+             0000000000000003\
+             2222222222222222222222222222222222222222222222222222222222222222",
+        )
+        .unwrap();
+        // 1 log: shard 0, service, tx #1, sender 0x80..80, key 0x04..04, value 0x05..05
+        let logs = hex::decode(
+            "00000001\
+             0001\
+             0001\
+             8080808080808080808080808080808080808080\
+             0404040404040404040404040404040404040404040404040404040404040404\
+             0505050505050505050505050505050505050505050505050505050505050505",
+        )
+        .unwrap();
+
+        Token::Tuple(vec![
+            Token::Uint(5.into()),
+            Token::Uint(100.into()),
+            Token::Uint(10.into()),
+            Token::FixedBytes(vec![6; 32]),
+            Token::Uint(0.into()),
+            Token::FixedBytes(vec![0; 32]),
+            Token::FixedBytes(vec![0; 32]),
+            Token::Bytes(initial_writes),
+            Token::Bytes(repeated_writes),
+            Token::Bytes(logs),
+            Token::Array(vec![Token::Bytes(vec![1, 2, 3])]),
+            Token::Array(vec![Token::Bytes(vec![0xfe; 64])]),
+        ])
+    }
+
+    #[test]
+    fn decoding_rollup_pubdata() {
+        let pubdata =
+            L1BatchPubdata::decode(&expected_commit_data(), L1BatchCommitmentMode::Rollup, V1)
+                .unwrap();
+
+        assert_eq!(pubdata.l1_batch_number, L1BatchNumber(5));
+        let expected_writes = PublishedStorageWrites::Full {
+            initial_writes: vec![InitialStorageWrite {
+                index: 9,
+                key: U256::from(1),
+                value: H256::repeat_byte(0x11),
+            }],
+            repeated_writes: vec![RepeatedStorageWrite {
+                index: 3,
+                value: H256::repeat_byte(0x22),
+            }],
+        };
+        assert_eq!(pubdata.storage_writes, expected_writes);
+        let expected_log = L2ToL1Log {
+            shard_id: 0,
+            is_service: true,
+            tx_number_in_block: 1,
+            sender: Address::repeat_byte(0x80),
+            key: H256::repeat_byte(4),
+            value: H256::repeat_byte(5),
+        };
+        assert_eq!(pubdata.l2_to_l1_logs, [expected_log]);
+        assert_eq!(pubdata.l2_to_l1_messages, [Bytes(vec![1, 2, 3])]);
+        assert_eq!(pubdata.factory_deps, [Bytes(vec![0xfe; 64])]);
+    }
+
+    #[test]
+    fn commit_data_encoding_matches_expected_bytes() {
+        let mut l1_batch = mock_l1_batch();
+        l1_batch.metadata.initial_writes_compressed =
+            serialize_commitments(&[InitialStorageWrite {
+                index: 9,
+                key: U256::from(1),
+                value: H256::repeat_byte(0x11),
+            }]);
+        l1_batch.metadata.repeated_writes_compressed =
+            serialize_commitments(&[RepeatedStorageWrite {
+                index: 3,
+                value: H256::repeat_byte(0x22),
+            }]);
+
+        let Token::Tuple(actual) = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup) else {
+            panic!("commit data is not a tuple");
+        };
+        let Token::Tuple(expected) = expected_commit_data() else {
+            unreachable!();
+        };
+        // Pubdata fields (storage writes, logs, messages and bytecodes) must be encoded exactly as expected.
+        assert_eq!(&actual[7..], &expected[7..]);
+    }
+
+    #[test]
+    fn decoding_compressed_rollup_pubdata() {
+        let mut l1_batch = mock_l1_batch();
+        let initial_writes = [InitialStorageWrite {
+            index: 9,
+            key: U256::from(1),
+            value: H256::from_low_u64_be(0x11),
+        }];
+        let repeated_writes = [RepeatedWriteDiff {
+            write: RepeatedStorageWrite {
+                index: 3,
+                value: H256::from_low_u64_be(0x20),
+            },
+            previous_value: H256::from_low_u64_be(0x22),
+        }];
+        l1_batch.metadata.initial_writes_compressed =
+            compress_v2(&initial_writes, &repeated_writes);
+        l1_batch.metadata.repeated_writes_compressed = vec![];
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup);
+
+        let pubdata =
+            L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Rollup, V2).unwrap();
+        let PublishedStorageWrites::Compressed(state_diffs) = &pubdata.storage_writes else {
+            panic!("unexpected storage writes: {:?}", pubdata.storage_writes);
+        };
+        let expected_initial_value = CompressedValue {
+            operation: ValueCompression::Add,
+            operand: U256::from(0x11),
+        };
+        assert_eq!(
+            state_diffs.initial_writes,
+            [(U256::from(1), expected_initial_value)]
+        );
+        let expected_repeated_value = CompressedValue {
+            operation: ValueCompression::Sub,
+            operand: U256::from(2),
+        };
+        assert_eq!(state_diffs.repeated_writes, [(3, expected_repeated_value)]);
+        assert_eq!(pubdata.l2_to_l1_logs.len(), 1);
+
+        // Compressed state diffs cannot be decoded as V1 ones.
+        L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Rollup, V1).unwrap_err();
+    }
+
+    #[test]
+    fn decoding_validium_pubdata() {
+        let l1_batch = mock_l1_batch();
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Validium);
+        let pubdata =
+            L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Validium, V1).unwrap();
+
+        assert_eq!(pubdata.storage_writes, PublishedStorageWrites::Offchain);
+        assert_eq!(pubdata.l2_to_l1_logs.len(), 1);
+
+        // Rollup commit data must not be accepted as validium one.
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup);
+        let err =
+            L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Validium, V1).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PubdataDecodeError::Malformed {
+                    field: "initialStorageChanges",
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn malformed_pubdata_is_rejected() {
+        let mut l1_batch = mock_l1_batch();
+        l1_batch.metadata.repeated_writes_compressed.pop();
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup);
+        let err =
+            L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Rollup, V1).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PubdataDecodeError::Malformed {
+                    field: "repeatedStorageChanges",
+                    ..
+                }
+            ),
+            "{err}"
+        );
+
+        // Initial writes cannot be assigned indices if the next leaf index is too small.
+        let mut l1_batch = mock_l1_batch();
+        l1_batch.metadata.rollup_last_leaf_index = 2;
+        let commit_data = l1_batch.l1_commit_data(L1BatchCommitmentMode::Rollup);
+        let err =
+            L1BatchPubdata::decode(&commit_data, L1BatchCommitmentMode::Rollup, V1).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                PubdataDecodeError::Malformed {
+                    field: "initialStorageChanges",
+                    ..
+                }
+            ),
+            "{err}"
+        );
+    }
+}
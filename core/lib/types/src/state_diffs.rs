@@ -0,0 +1,658 @@
+//! Compression of storage writes (state diffs) published as L1 batch pubdata.
+//!
+//! Two formats are supported:
+//!
+//! - **V1** is the format of the `initialStorageChanges` and `repeatedStorageChanges` fields in the commit data.
+//!   Each initial write is published as a 32-byte derived key and a 32-byte value, and each repeated write
+//!   as an 8-byte enumeration index and a 32-byte value.
+//! - **V2** packs enumeration indices of repeated writes into the minimum number of bytes sufficient
+//!   for the L1 batch, and compresses values relative to the value of the slot before the L1 batch.
+//!   Enumeration indices of initial writes are not published since they are derived from the order of writes;
+//!   derived keys of repeated writes are not published since they are derived from enumeration indices.
+//!
+//! V2 layout:
+//!
+//! ```text
+//! [version: 1 byte][enumeration index size: 1 byte][number of initial writes: 4 bytes, BE]
+//! initial writes: [derived key: 32 bytes, LE][metadata: 1 byte][operand]
+//! repeated writes: [enumeration index: `enumeration index size` bytes, BE][metadata: 1 byte][operand]
+//! ```
+//!
+//! The metadata byte is `(operand_len << 3) | operation`; see [`ValueCompression`] for the supported operations.
+//! For [`ValueCompression::Nothing`], `operand_len` is 0 and the operand is the full 32-byte value.
+//!
+//! For L1 batches using the V2 format, the compressed state diffs are published in place of
+//! the `initialStorageChanges` field of the commit data, and `repeatedStorageChanges` is empty.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    commitment::serialize_commitments,
+    writes::{InitialStorageWrite, RepeatedStorageWrite},
+    ProtocolVersionId, H256, U256,
+};
+
+/// Version byte of the V2 format.
+const V2_VERSION_BYTE: u8 = 2;
+/// Number of bits of the metadata byte used to encode the compression operation.
+const OPERATION_BITS: u8 = 3;
+/// Maximum operand length for compressed values; must fit into the remaining bits of the metadata byte.
+const MAX_OPERAND_LEN: usize = 31;
+
+/// Format of the state diffs published as pubdata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StateDiffCompressionVersion {
+    V1,
+    V2,
+}
+
+impl StateDiffCompressionVersion {
+    /// First protocol version using the V2 format.
+    pub const V2_SINCE: ProtocolVersionId = ProtocolVersionId::Version16;
+
+    /// Returns the format used by L1 batches with the specified protocol version.
+    pub fn for_protocol_version(version: ProtocolVersionId) -> Self {
+        if version >= Self::V2_SINCE {
+            Self::V2
+        } else {
+            Self::V1
+        }
+    }
+
+    /// Returns a string label for the version to be used in metrics.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+}
+
+/// Repeated storage write together with the value of the slot before the L1 batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatedWriteDiff {
+    pub write: RepeatedStorageWrite,
+    pub previous_value: H256,
+}
+
+/// Operation used to compress a value relative to the previous value of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum ValueCompression {
+    /// The value is published in full.
+    Nothing = 0,
+    /// `value = previous_value + operand`.
+    Add = 1,
+    /// `value = previous_value - operand`.
+    Sub = 2,
+    /// `value = operand`.
+    Transform = 3,
+}
+
+impl ValueCompression {
+    fn from_metadata(metadata: u8) -> Option<Self> {
+        Some(match metadata & ((1 << OPERATION_BITS) - 1) {
+            0 => Self::Nothing,
+            1 => Self::Add,
+            2 => Self::Sub,
+            3 => Self::Transform,
+            _ => return None,
+        })
+    }
+}
+
+/// Value compressed relative to the previous value of a slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedValue {
+    pub operation: ValueCompression,
+    pub operand: U256,
+}
+
+impl CompressedValue {
+    /// Compresses `value` using the operation producing the shortest operand. If several operations
+    /// produce operands of the same length, the first one in the order `Add`, `Sub`, `Transform` is used.
+    pub fn new(previous_value: U256, value: U256) -> Self {
+        let mut candidates = vec![];
+        if let Some(diff) = value.checked_sub(previous_value) {
+            candidates.push(Self {
+                operation: ValueCompression::Add,
+                operand: diff,
+            });
+        }
+        if let Some(diff) = previous_value.checked_sub(value) {
+            candidates.push(Self {
+                operation: ValueCompression::Sub,
+                operand: diff,
+            });
+        }
+        candidates.push(Self {
+            operation: ValueCompression::Transform,
+            operand: value,
+        });
+
+        let best = candidates
+            .into_iter()
+            .min_by_key(|candidate| operand_len(candidate.operand))
+            .unwrap(); // `candidates` is non-empty
+        if operand_len(best.operand) > MAX_OPERAND_LEN {
+            Self {
+                operation: ValueCompression::Nothing,
+                operand: value,
+            }
+        } else {
+            best
+        }
+    }
+
+    /// Restores the value given the previous value of the slot. Returns `None` on overflow.
+    pub fn apply(&self, previous_value: U256) -> Option<U256> {
+        match self.operation {
+            ValueCompression::Nothing | ValueCompression::Transform => Some(self.operand),
+            ValueCompression::Add => previous_value.checked_add(self.operand),
+            ValueCompression::Sub => previous_value.checked_sub(self.operand),
+        }
+    }
+
+    fn encoded_operand_len(&self) -> usize {
+        match self.operation {
+            ValueCompression::Nothing => 32,
+            _ => operand_len(self.operand),
+        }
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>) {
+        let len = self.encoded_operand_len();
+        let metadata = match self.operation {
+            ValueCompression::Nothing => 0,
+            operation => ((len as u8) << OPERATION_BITS) | operation as u8,
+        };
+        buffer.push(metadata);
+
+        let mut operand = [0_u8; 32];
+        self.operand.to_big_endian(&mut operand);
+        buffer.extend_from_slice(&operand[32 - len..]);
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<Self, StateDiffDecodeError> {
+        let metadata = reader.read(1, "value metadata")?[0];
+        let operation = ValueCompression::from_metadata(metadata)
+            .ok_or(StateDiffDecodeError::UnknownOperation(metadata))?;
+        let len = match operation {
+            ValueCompression::Nothing => {
+                if metadata != 0 {
+                    return Err(StateDiffDecodeError::UnknownOperation(metadata));
+                }
+                32
+            }
+            _ => usize::from(metadata >> OPERATION_BITS),
+        };
+        let operand = U256::from_big_endian(reader.read(len, "value operand")?);
+        Ok(Self { operation, operand })
+    }
+}
+
+/// Minimum number of bytes necessary to encode `value`.
+fn operand_len(value: U256) -> usize {
+    (value.bits() + 7) / 8
+}
+
+/// Minimum number of bytes necessary to encode all enumeration indices of repeated writes.
+fn enumeration_index_size(repeated_writes: &[RepeatedWriteDiff]) -> usize {
+    let max_index = repeated_writes
+        .iter()
+        .map(|diff| diff.write.index)
+        .max()
+        .unwrap_or(0);
+    let bits = 64 - max_index.leading_zeros() as usize;
+    ((bits + 7) / 8).max(1)
+}
+
+/// Errors that can occur when decompressing or verifying state diffs.
+#[derive(Debug, Error, PartialEq)]
+pub enum StateDiffDecodeError {
+    #[error("unsupported state diff compression version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid enumeration index size: {0}")]
+    InvalidEnumerationIndexSize(u8),
+    #[error("unknown value compression in metadata byte {0:#04x}")]
+    UnknownOperation(u8),
+    #[error("unexpected end of data while reading {0}")]
+    UnexpectedEnd(&'static str),
+    #[error("{0} trailing bytes after state diffs")]
+    TrailingBytes(usize),
+    #[error("state diff #{index} doesn't match compressed data: {reason}")]
+    Mismatch { index: usize, reason: String },
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, len: usize, what: &'static str) -> Result<&'a [u8], StateDiffDecodeError> {
+        if self.bytes.len() < len {
+            return Err(StateDiffDecodeError::UnexpectedEnd(what));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+}
+
+/// Compresses state diffs in the V1 format. The output is the concatenation of the `initialStorageChanges`
+/// and `repeatedStorageChanges` fields of the commit data.
+pub fn compress_v1(
+    initial_writes: &[InitialStorageWrite],
+    repeated_writes: &[RepeatedWriteDiff],
+) -> Vec<u8> {
+    let repeated_writes: Vec<_> = repeated_writes
+        .iter()
+        .map(|diff| diff.write.clone())
+        .collect();
+    let mut bytes = serialize_commitments(initial_writes);
+    bytes.extend(serialize_commitments(&repeated_writes));
+    bytes
+}
+
+/// Compresses state diffs in the V2 format. Writes are published in the provided order, which
+/// must be the order in which enumeration indices are assigned to initial writes.
+pub fn compress_v2(
+    initial_writes: &[InitialStorageWrite],
+    repeated_writes: &[RepeatedWriteDiff],
+) -> Vec<u8> {
+    let index_size = enumeration_index_size(repeated_writes);
+    let mut bytes = vec![V2_VERSION_BYTE, index_size as u8];
+    bytes.extend_from_slice(&(initial_writes.len() as u32).to_be_bytes());
+
+    for write in initial_writes {
+        let mut key = [0_u8; 32];
+        write.key.to_little_endian(&mut key);
+        bytes.extend_from_slice(&key);
+        let value = U256::from_big_endian(write.value.as_bytes());
+        CompressedValue::new(U256::zero(), value).write(&mut bytes);
+    }
+    for diff in repeated_writes {
+        bytes.extend_from_slice(&diff.write.index.to_be_bytes()[8 - index_size..]);
+        let previous_value = U256::from_big_endian(diff.previous_value.as_bytes());
+        let value = U256::from_big_endian(diff.write.value.as_bytes());
+        CompressedValue::new(previous_value, value).write(&mut bytes);
+    }
+    bytes
+}
+
+/// State diffs decompressed from the V2 format. Values are left compressed since restoring them
+/// requires the values of slots before the L1 batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecompressedStateDiffs {
+    /// Derived keys of initially written slots with their values compressed relative to zero.
+    pub initial_writes: Vec<(U256, CompressedValue)>,
+    /// Enumeration indices of repeatedly written slots with their compressed values.
+    pub repeated_writes: Vec<(u64, CompressedValue)>,
+}
+
+/// Decompresses state diffs in the V2 format.
+pub fn decompress_v2(bytes: &[u8]) -> Result<DecompressedStateDiffs, StateDiffDecodeError> {
+    let mut reader = Reader { bytes };
+    let version = reader.read(1, "version")?[0];
+    if version != V2_VERSION_BYTE {
+        return Err(StateDiffDecodeError::UnsupportedVersion(version));
+    }
+    let index_size = reader.read(1, "enumeration index size")?[0];
+    if !(1..=8).contains(&index_size) {
+        return Err(StateDiffDecodeError::InvalidEnumerationIndexSize(
+            index_size,
+        ));
+    }
+    let index_size = usize::from(index_size);
+    let initial_writes_count = reader.read(4, "number of initial writes")?;
+    let initial_writes_count = u32::from_be_bytes(initial_writes_count.try_into().unwrap());
+
+    let mut initial_writes = Vec::with_capacity(initial_writes_count as usize);
+    for _ in 0..initial_writes_count {
+        let key = U256::from_little_endian(reader.read(32, "derived key")?);
+        initial_writes.push((key, CompressedValue::read(&mut reader)?));
+    }
+
+    let mut repeated_writes = vec![];
+    while !reader.bytes.is_empty() {
+        let mut index = [0_u8; 8];
+        index[8 - index_size..].copy_from_slice(reader.read(index_size, "enumeration index")?);
+        let index = u64::from_be_bytes(index);
+        repeated_writes.push((index, CompressedValue::read(&mut reader)?));
+    }
+    Ok(DecompressedStateDiffs {
+        initial_writes,
+        repeated_writes,
+    })
+}
+
+/// Verifies that `compressed` is a valid V2 encoding of the provided state diffs. This mirrors the checks
+/// performed by the compressor system contract, which is provided with uncompressed state diffs.
+pub fn verify_v2(
+    initial_writes: &[InitialStorageWrite],
+    repeated_writes: &[RepeatedWriteDiff],
+    compressed: &[u8],
+) -> Result<(), StateDiffDecodeError> {
+    let mut reader = Reader { bytes: compressed };
+    let version = reader.read(1, "version")?[0];
+    if version != V2_VERSION_BYTE {
+        return Err(StateDiffDecodeError::UnsupportedVersion(version));
+    }
+    let index_size = reader.read(1, "enumeration index size")?[0];
+    if !(1..=8).contains(&index_size) {
+        return Err(StateDiffDecodeError::InvalidEnumerationIndexSize(
+            index_size,
+        ));
+    }
+    let index_size = usize::from(index_size);
+    let initial_writes_count = reader.read(4, "number of initial writes")?;
+    let initial_writes_count = u32::from_be_bytes(initial_writes_count.try_into().unwrap());
+    if initial_writes_count as usize != initial_writes.len() {
+        return Err(StateDiffDecodeError::Mismatch {
+            index: 0,
+            reason: format!(
+                "expected {} initial writes, got {initial_writes_count}",
+                initial_writes.len()
+            ),
+        });
+    }
+
+    for (i, write) in initial_writes.iter().enumerate() {
+        let key = U256::from_little_endian(reader.read(32, "derived key")?);
+        if key != write.key {
+            return Err(StateDiffDecodeError::Mismatch {
+                index: i,
+                reason: format!(
+                    "derived key mismatch: expected {:#x}, got {key:#x}",
+                    write.key
+                ),
+            });
+        }
+        let value = CompressedValue::read(&mut reader)?;
+        check_value(i, value, U256::zero(), write.value)?;
+    }
+
+    for (i, diff) in repeated_writes.iter().enumerate() {
+        let i = initial_writes.len() + i;
+        let mut index = [0_u8; 8];
+        index[8 - index_size..].copy_from_slice(reader.read(index_size, "enumeration index")?);
+        let index = u64::from_be_bytes(index);
+        if index != diff.write.index {
+            return Err(StateDiffDecodeError::Mismatch {
+                index: i,
+                reason: format!(
+                    "enumeration index mismatch: expected {}, got {index}",
+                    diff.write.index
+                ),
+            });
+        }
+        let value = CompressedValue::read(&mut reader)?;
+        let previous_value = U256::from_big_endian(diff.previous_value.as_bytes());
+        check_value(i, value, previous_value, diff.write.value)?;
+    }
+
+    if reader.bytes.is_empty() {
+        Ok(())
+    } else {
+        Err(StateDiffDecodeError::TrailingBytes(reader.bytes.len()))
+    }
+}
+
+fn check_value(
+    index: usize,
+    value: CompressedValue,
+    previous_value: U256,
+    expected: H256,
+) -> Result<(), StateDiffDecodeError> {
+    let expected = U256::from_big_endian(expected.as_bytes());
+    match value.apply(previous_value) {
+        Some(restored) if restored == expected => Ok(()),
+        Some(restored) => Err(StateDiffDecodeError::Mismatch {
+            index,
+            reason: format!("value mismatch: expected {expected:#x}, got {restored:#x}"),
+        }),
+        None => Err(StateDiffDecodeError::Mismatch {
+            index,
+            reason: format!("{:?} overflows", value.operation),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{collection, prelude::*};
+
+    use super::*;
+
+    fn u256_to_h256(value: U256) -> H256 {
+        let mut bytes = [0_u8; 32];
+        value.to_big_endian(&mut bytes);
+        H256(bytes)
+    }
+
+    fn h256_to_u256(value: H256) -> U256 {
+        U256::from_big_endian(value.as_bytes())
+    }
+
+    fn gen_u256() -> impl Strategy<Value = U256> {
+        // Bias values towards small ones and ones close to `U256::MAX`, which are typical for real state diffs
+        // and exercise the `Add` / `Sub` operations.
+        prop_oneof![
+            any::<u64>().prop_map(U256::from),
+            any::<u8>().prop_map(U256::from),
+            any::<u16>().prop_map(|diff| U256::MAX - U256::from(diff)),
+            any::<[u8; 32]>().prop_map(|bytes| U256::from_big_endian(&bytes)),
+        ]
+    }
+
+    fn gen_initial_writes() -> impl Strategy<Value = Vec<InitialStorageWrite>> {
+        collection::vec((any::<[u8; 32]>(), gen_u256()), 0..20).prop_map(|writes| {
+            writes
+                .into_iter()
+                .zip(1_000..)
+                .map(|((key, value), index)| InitialStorageWrite {
+                    index,
+                    key: U256::from_big_endian(&key),
+                    value: u256_to_h256(value),
+                })
+                .collect()
+        })
+    }
+
+    fn gen_repeated_writes() -> impl Strategy<Value = Vec<RepeatedWriteDiff>> {
+        let value_and_previous = (gen_u256(), gen_u256(), any::<u16>(), 0..3_u8).prop_map(
+            |(previous_value, random_value, diff, kind)| {
+                let value = match kind {
+                    0 => previous_value.saturating_add(U256::from(diff)),
+                    1 => previous_value.saturating_sub(U256::from(diff)),
+                    _ => random_value,
+                };
+                (previous_value, value)
+            },
+        );
+        (0_u32..64).prop_flat_map(move |index_bits| {
+            let max_index = 1_u64 << index_bits;
+            let diff = (1..=max_index, value_and_previous.clone()).prop_map(
+                |(index, (previous_value, value))| RepeatedWriteDiff {
+                    write: RepeatedStorageWrite {
+                        index,
+                        value: u256_to_h256(value),
+                    },
+                    previous_value: u256_to_h256(previous_value),
+                },
+            );
+            collection::vec(diff, 0..20)
+        })
+    }
+
+    #[test]
+    fn compressing_values() {
+        let value = CompressedValue::new(U256::from(100), U256::from(101));
+        assert_eq!(value.operation, ValueCompression::Add);
+        assert_eq!(value.operand, U256::one());
+
+        let value = CompressedValue::new(U256::MAX, U256::MAX - 2);
+        assert_eq!(value.operation, ValueCompression::Sub);
+        assert_eq!(value.operand, U256::from(2));
+
+        let value = CompressedValue::new(U256::MAX, U256::from(5));
+        assert_eq!(value.operation, ValueCompression::Transform);
+        assert_eq!(value.operand, U256::from(5));
+
+        let value = CompressedValue::new(U256::zero(), U256::MAX);
+        assert_eq!(value.operation, ValueCompression::Nothing);
+        let mut bytes = vec![];
+        value.write(&mut bytes);
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(bytes[0], 0);
+    }
+
+    #[test]
+    fn repeated_writes_are_packed() {
+        let repeated_writes = vec![RepeatedWriteDiff {
+            write: RepeatedStorageWrite {
+                index: 0x0102,
+                value: u256_to_h256(U256::from(3)),
+            },
+            previous_value: u256_to_h256(U256::from(2)),
+        }];
+        let compressed = compress_v2(&[], &repeated_writes);
+        // version, index size, initial writes count, 2-byte index, metadata (Add with 1-byte operand), operand
+        let expected = [2, 2, 0, 0, 0, 0, 1, 2, (1 << 3) | 1, 1];
+        assert_eq!(compressed, expected);
+        assert!(compressed.len() < compress_v1(&[], &repeated_writes).len());
+    }
+
+    #[test]
+    fn initial_writes_are_encoded_with_keys() {
+        let initial_writes = [InitialStorageWrite {
+            index: 5,
+            key: U256::from(0x0201),
+            value: u256_to_h256(U256::from(0xff)),
+        }];
+        let compressed = compress_v2(&initial_writes, &[]);
+        let mut expected = vec![2, 1, 0, 0, 0, 1];
+        // derived key (little-endian)
+        expected.extend_from_slice(&[1, 2]);
+        expected.extend_from_slice(&[0; 30]);
+        // metadata (Add with 1-byte operand), operand
+        expected.extend_from_slice(&[(1 << 3) | 1, 0xff]);
+        assert_eq!(compressed, expected);
+    }
+
+    #[test]
+    fn version_is_gated_by_protocol_version() {
+        assert_eq!(
+            StateDiffCompressionVersion::for_protocol_version(ProtocolVersionId::latest()),
+            StateDiffCompressionVersion::V1
+        );
+        assert_eq!(
+            StateDiffCompressionVersion::for_protocol_version(ProtocolVersionId::Version16),
+            StateDiffCompressionVersion::V2
+        );
+    }
+
+    #[test]
+    fn decompression_errors() {
+        assert_eq!(
+            decompress_v2(&[1, 1, 0, 0, 0, 0]).unwrap_err(),
+            StateDiffDecodeError::UnsupportedVersion(1)
+        );
+        assert_eq!(
+            decompress_v2(&[2, 9, 0, 0, 0, 0]).unwrap_err(),
+            StateDiffDecodeError::InvalidEnumerationIndexSize(9)
+        );
+        assert_eq!(
+            decompress_v2(&[2, 1, 0, 0, 0, 0, 1, 7]).unwrap_err(),
+            StateDiffDecodeError::UnknownOperation(7)
+        );
+        assert_eq!(
+            decompress_v2(&[2, 1, 0, 0, 0, 0, 1, (2 << 3) | 1, 1]).unwrap_err(),
+            StateDiffDecodeError::UnexpectedEnd("value operand")
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn value_compression_roundtrip(previous_value in gen_u256(), value in gen_u256()) {
+            let compressed = CompressedValue::new(previous_value, value);
+            prop_assert_eq!(compressed.apply(previous_value), Some(value));
+
+            // The chosen operand is not longer than any of the alternatives.
+            let mut alternatives = vec![operand_len(value)];
+            alternatives.extend(value.checked_sub(previous_value).map(operand_len));
+            alternatives.extend(previous_value.checked_sub(value).map(operand_len));
+            let min_len = alternatives.into_iter().min().unwrap();
+            if compressed.operation != ValueCompression::Nothing {
+                prop_assert_eq!(operand_len(compressed.operand), min_len);
+            } else {
+                prop_assert!(min_len > MAX_OPERAND_LEN);
+            }
+
+            let mut bytes = vec![];
+            compressed.write(&mut bytes);
+            let mut reader = Reader { bytes: &bytes };
+            prop_assert_eq!(CompressedValue::read(&mut reader).unwrap(), compressed);
+            prop_assert!(reader.bytes.is_empty());
+        }
+
+        #[test]
+        fn state_diffs_roundtrip(
+            initial_writes in gen_initial_writes(),
+            repeated_writes in gen_repeated_writes(),
+        ) {
+            let compressed = compress_v2(&initial_writes, &repeated_writes);
+            verify_v2(&initial_writes, &repeated_writes, &compressed).unwrap();
+
+            let decompressed = decompress_v2(&compressed).unwrap();
+            prop_assert_eq!(decompressed.initial_writes.len(), initial_writes.len());
+            for ((key, value), write) in decompressed.initial_writes.iter().zip(&initial_writes) {
+                prop_assert_eq!(*key, write.key);
+                prop_assert_eq!(value.apply(U256::zero()), Some(h256_to_u256(write.value)));
+            }
+            prop_assert_eq!(decompressed.repeated_writes.len(), repeated_writes.len());
+            for ((index, value), diff) in decompressed.repeated_writes.iter().zip(&repeated_writes) {
+                prop_assert_eq!(*index, diff.write.index);
+                let restored = value.apply(h256_to_u256(diff.previous_value));
+                prop_assert_eq!(restored, Some(h256_to_u256(diff.write.value)));
+            }
+
+            // Each initial write takes at most 32 + 33 bytes (vs 64 in V1), and each repeated write
+            // at most 8 + 33 bytes (vs 40 in V1).
+            let v1_size = compress_v1(&initial_writes, &repeated_writes).len();
+            let writes_count = initial_writes.len() + repeated_writes.len();
+            prop_assert!(compressed.len() <= v1_size + writes_count + 2);
+        }
+
+        #[test]
+        fn corrupted_state_diffs_are_rejected(
+            initial_writes in gen_initial_writes(),
+            repeated_writes in gen_repeated_writes(),
+            corrupted_pos in any::<prop::sample::Index>(),
+            corrupted_bit in 0_u8..8,
+        ) {
+            prop_assume!(!initial_writes.is_empty() || !repeated_writes.is_empty());
+            let mut compressed = compress_v2(&initial_writes, &repeated_writes);
+            // Corrupt a bit after the header.
+            let pos = 6 + corrupted_pos.index(compressed.len() - 6);
+            compressed[pos] ^= 1 << corrupted_bit;
+
+            // Corrupted data may still be a valid (non-canonical) encoding of the same state diffs,
+            // e.g. if `Add` is replaced with `Sub` for a zero operand. Otherwise, it must be rejected.
+            if verify_v2(&initial_writes, &repeated_writes, &compressed).is_ok() {
+                let decompressed = decompress_v2(&compressed).unwrap();
+                for ((_, value), write) in decompressed.initial_writes.iter().zip(&initial_writes) {
+                    prop_assert_eq!(value.apply(U256::zero()), Some(h256_to_u256(write.value)));
+                }
+                for ((index, value), diff) in decompressed.repeated_writes.iter().zip(&repeated_writes) {
+                    prop_assert_eq!(*index, diff.write.index);
+                    let restored = value.apply(h256_to_u256(diff.previous_value));
+                    prop_assert_eq!(restored, Some(h256_to_u256(diff.write.value)));
+                }
+            }
+        }
+    }
+}
@@ -5,7 +5,7 @@ use zksync_basic_types::U256;
 /// In vm there are two types of writes Initial and Repeated. After the first write to the leaf,
 /// we assign an index to it and in the future we should use index instead of full key.
 /// It allows us to compress the data.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, Eq, PartialEq)]
 pub struct InitialStorageWrite {
     pub index: u64,
     pub key: U256,
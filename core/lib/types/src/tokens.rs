@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use num::{rational::Ratio, BigUint};
 use serde::{Deserialize, Serialize};
-use zksync_basic_types::Address;
+use zksync_basic_types::{Address, MiniblockNumber};
 pub use zksync_config::constants::ETHEREUM_ADDRESS;
 use zksync_utils::UnsignedRatioSerializeAsDecimal;
 
@@ -36,6 +36,15 @@ impl TokenMetadata {
     }
 }
 
+/// Provenance of a token bridged to L2.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TokenProvenance {
+    /// L2 bridge that deployed the token.
+    pub bridge_address: Address,
+    /// Miniblock in which the token was deployed.
+    pub added_in_miniblock: MiniblockNumber,
+}
+
 /// Token price known to the zkSync network.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPrice {
@@ -0,0 +1,71 @@
+//! Types related to finalization of L2->L1 withdrawals.
+
+use std::{fmt, str::FromStr};
+
+use crate::{Address, L1BatchNumber, H256, U256};
+
+/// Withdrawal initiated on L2 that can be finalized on L1 once its L1 batch is executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub l1_batch_number: L1BatchNumber,
+    /// Index of the L2->L1 log corresponding to the withdrawal among all logs in the L1 batch.
+    pub l2_message_index: u32,
+    pub l2_tx_number_in_batch: u16,
+    pub l2_tx_hash: H256,
+    pub l1_receiver: Address,
+    /// L1 address of the withdrawn token; ETH is denoted by the zero address.
+    pub l1_token: Address,
+    pub amount: U256,
+    /// L2->L1 message sent on withdrawal; it must be passed to the finalization method on L1.
+    pub message: Vec<u8>,
+    /// Merkle proof of the message inclusion into the L2->L1 log tree of the L1 batch.
+    pub merkle_proof: Vec<H256>,
+}
+
+/// Status of a withdrawal tracked by the withdrawal finalizer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WithdrawalStatus {
+    /// Finalization transaction is not sent yet.
+    Pending,
+    /// Finalization transaction is sent, but not yet included into an L1 block.
+    Sent,
+    /// Finalization transaction succeeded.
+    Finalized,
+    /// Finalization transaction reverted.
+    Failed,
+    /// Withdrawal was finalized by a third party.
+    FinalizedExternally,
+}
+
+impl WithdrawalStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Finalized => "finalized",
+            Self::Failed => "failed",
+            Self::FinalizedExternally => "finalized_externally",
+        }
+    }
+}
+
+impl fmt::Display for WithdrawalStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WithdrawalStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "pending" => Self::Pending,
+            "sent" => Self::Sent,
+            "finalized" => Self::Finalized,
+            "failed" => Self::Failed,
+            "finalized_externally" => Self::FinalizedExternally,
+            _ => return Err(format!("unknown withdrawal status `{s}`")),
+        })
+    }
+}
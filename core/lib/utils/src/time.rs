@@ -4,8 +4,10 @@ pub fn seconds_since_epoch() -> u64 {
     duration_since_epoch().as_secs()
 }
 
+/// Returns the number of milliseconds passed since the specified UNIX timestamp (in seconds),
+/// or 0 if the timestamp is in the future.
 pub fn millis_since(since: u64) -> u64 {
-    (millis_since_epoch() - since as u128 * 1000) as u64
+    millis_since_epoch().saturating_sub(since as u128 * 1000) as u64
 }
 
 pub fn millis_since_epoch() -> u128 {
@@ -63,9 +63,17 @@ impl<H: HistoryMode> InMemoryEventSink<H> {
         Self::events_and_l1_messages_from_history(self.log_queries_after_timestamp(from_timestamp))
     }
 
-    fn events_and_l1_messages_from_history(
-        history: &[Box<LogQuery>],
-    ) -> (Vec<EventMessage>, Vec<EventMessage>) {
+    /// Returns deduplicated event log queries (i.e., without rolled back events) ordered by timestamp.
+    /// This is the events queue that the prover commits to.
+    pub fn get_deduplicated_events_logs(&self) -> Vec<LogQuery> {
+        let history = self.frames_stack.forward().current_frame();
+        Self::deduplicated_log_queries(history)
+            .into_iter()
+            .filter(|log| log.aux_byte == EVENT_AUX_BYTE)
+            .collect()
+    }
+
+    fn deduplicated_log_queries(history: &[Box<LogQuery>]) -> Vec<LogQuery> {
         let mut tmp = HashMap::<u32, LogQuery>::with_capacity(history.len());
 
         // note that we only use "forward" part and discard the rollbacks at the end,
@@ -84,12 +92,18 @@ impl<H: HistoryMode> InMemoryEventSink<H> {
         // naturally sorted by timestamp
         let mut keys: Vec<_> = tmp.keys().cloned().collect();
         keys.sort_unstable();
+        keys.into_iter()
+            .map(|key| tmp.remove(&key).unwrap())
+            .collect()
+    }
 
+    fn events_and_l1_messages_from_history(
+        history: &[Box<LogQuery>],
+    ) -> (Vec<EventMessage>, Vec<EventMessage>) {
         let mut events = vec![];
         let mut l1_messages = vec![];
 
-        for k in keys.into_iter() {
-            let el = tmp.remove(&k).unwrap();
+        for el in Self::deduplicated_log_queries(history) {
             let LogQuery {
                 shard_id,
                 is_service,
@@ -22,7 +22,7 @@ use crate::bootloader_state::BootloaderState;
 use crate::constants::{BOOTLOADER_HEAP_PAGE, OPERATOR_REFUNDS_OFFSET, TX_GAS_LIMIT_OFFSET};
 use crate::old_vm::{
     events::merge_events, history_recorder::HistoryMode, memory::SimpleMemory,
-    oracles::storage::storage_key_of_log, utils::eth_price_per_pubdata_byte,
+    oracles::storage::storage_key_of_log,
 };
 use crate::tracers::utils::gas_spent_on_bytecodes_and_long_messages_this_opcode;
 use crate::tracers::{
@@ -108,8 +108,7 @@ impl RefundsTracer {
         let bootloader_eth_price_per_pubdata_byte =
             U256::from(effective_gas_price) * U256::from(current_ergs_per_pubdata_byte);
 
-        let fair_eth_price_per_pubdata_byte =
-            U256::from(eth_price_per_pubdata_byte(self.l1_batch.l1_gas_price));
+        let fair_eth_price_per_pubdata_byte = U256::from(self.l1_batch.fair_pubdata_price);
 
         // For now, L1 originated transactions are allowed to pay less than fair fee per pubdata,
         // so we should take it into account.
@@ -16,6 +16,9 @@ pub struct L1BatchEnv {
     pub timestamp: u64,
     pub l1_gas_price: u64,
     pub fair_l2_gas_price: u64,
+    /// Fair price of publishing a byte of pubdata, in wei. The bootloader charges for pubdata based on
+    /// `l1_gas_price` (i.e., as if it was sent as calldata); the excess is refunded based on this price.
+    pub fair_pubdata_price: u64,
     pub fee_account: Address,
     pub enforced_base_fee: Option<u64>,
     pub first_l2_block: L2BlockEnv,
@@ -240,7 +240,7 @@ impl TransactionData {
             .expect("Could not recover L2 transaction hash")
     }
 
-    fn canonical_l1_tx_hash(&self) -> Result<H256, TxHashCalculationError> {
+    pub(crate) fn canonical_l1_tx_hash(&self) -> Result<H256, TxHashCalculationError> {
         use zksync_types::web3::signing::keccak256;
 
         if !is_l1_tx_type(self.tx_type) {
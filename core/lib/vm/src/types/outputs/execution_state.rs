@@ -1,11 +1,14 @@
 use zksync_types::l2_to_l1_log::L2ToL1Log;
-use zksync_types::{StorageLogQuery, VmEvent, U256};
+use zksync_types::{LogQuery, StorageLogQuery, VmEvent, U256};
 
 /// State of the VM since the start of the batch execution.
 #[derive(Debug, Clone, PartialEq)]
 pub struct CurrentExecutionState {
     /// Events produced by the VM.
     pub events: Vec<VmEvent>,
+    /// Deduplicated event log queries produced by the VM, ordered by timestamp. Used to compute
+    /// the events queue commitment. Empty for legacy VM versions.
+    pub deduplicated_events_logs: Vec<LogQuery>,
     /// Storage logs produced by the VM.
     pub storage_log_queries: Vec<StorageLogQuery>,
     /// Hashes of the contracts used by the VM.
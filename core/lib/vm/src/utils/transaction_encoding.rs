@@ -1,10 +1,13 @@
 use crate::types::internals::TransactionData;
-use zksync_types::Transaction;
+use zksync_types::{Transaction, H256};
 
 /// Extension  for transactions, specific for VM. Required for bypassing the orphan rule
 pub trait TransactionVmExt {
     /// Get the size of the transaction in tokens.
     fn bootloader_encoding_size(&self) -> usize;
+    /// Calculates the canonical hash of an L1 transaction (i.e., the hash of its bootloader encoding).
+    /// Returns `None` for L2 transactions.
+    fn canonical_l1_tx_hash(&self) -> Option<H256>;
 }
 
 impl TransactionVmExt for Transaction {
@@ -12,4 +15,9 @@ impl TransactionVmExt for Transaction {
         let transaction_data: TransactionData = self.clone().into();
         transaction_data.into_tokens().len()
     }
+
+    fn canonical_l1_tx_hash(&self) -> Option<H256> {
+        let transaction_data: TransactionData = self.clone().into();
+        transaction_data.canonical_l1_tx_hash().ok()
+    }
 }
@@ -99,6 +99,7 @@ impl<S: WriteStorage, H: HistoryMode> Vm<S, H> {
 
         CurrentExecutionState {
             events,
+            deduplicated_events_logs: self.state.event_sink.get_deduplicated_events_logs(),
             storage_log_queries: self.state.storage.get_final_log_queries(),
             used_contract_hashes: self.get_used_contracts(),
             l2_to_l1_logs,
@@ -37,4 +37,6 @@ pub enum Web3Error {
     InvalidFilterBlockHash,
     #[error("Query returned more than {0} results. Try smaller range of blocks")]
     TooManyLogs(usize),
+    #[error("Response exceeds the size limit of {0} bytes. Narrow your query")]
+    ResponseTooLarge(usize),
 }
@@ -1,7 +1,10 @@
 use crate::types::H256;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
-use zksync_types::api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig};
+use zksync_types::api::{
+    BlockId, BlockNumber, BytecodeCompressionComparison, DebugCall, L1BatchSealingStatus,
+    ResultDebugCall, TracerConfig,
+};
 use zksync_types::transaction_request::CallRequest;
 
 #[cfg_attr(
@@ -42,4 +45,12 @@ pub trait DebugNamespace {
         tx_hash: H256,
         options: Option<TracerConfig>,
     ) -> RpcResult<Option<DebugCall>>;
+    #[method(name = "getL1BatchSealingStatus")]
+    async fn get_l1_batch_sealing_status(&self) -> RpcResult<Option<L1BatchSealingStatus>>;
+    #[method(name = "compareBytecodeCompression")]
+    async fn compare_bytecode_compression(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+    ) -> RpcResult<BytecodeCompressionComparison>;
 }
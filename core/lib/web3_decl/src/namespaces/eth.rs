@@ -44,6 +44,9 @@ pub trait EthNamespace {
     #[method(name = "gasPrice")]
     async fn gas_price(&self) -> RpcResult<U256>;
 
+    #[method(name = "maxPriorityFeePerGas")]
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+
     #[method(name = "newFilter")]
     async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
 
@@ -139,6 +142,9 @@ pub trait EthNamespace {
     #[method(name = "sendRawTransaction")]
     async fn send_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256>;
 
+    #[method(name = "sendTransaction")]
+    async fn send_transaction(&self, req: CallRequest) -> RpcResult<H256>;
+
     #[method(name = "syncing")]
     async fn syncing(&self) -> RpcResult<SyncState>;
 
@@ -0,0 +1,31 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::U64;
+
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "evm")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "evm")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "evm")
+)]
+pub trait EvmNamespace {
+    #[method(name = "increaseTime")]
+    async fn increase_time(&self, seconds: U64) -> RpcResult<String>;
+
+    #[method(name = "setNextBlockTimestamp")]
+    async fn set_next_block_timestamp(&self, timestamp: U64) -> RpcResult<String>;
+
+    #[method(name = "mine")]
+    async fn mine(&self, timestamp: Option<U64>) -> RpcResult<String>;
+
+    #[method(name = "snapshot")]
+    async fn snapshot(&self) -> RpcResult<U64>;
+
+    #[method(name = "revert")]
+    async fn revert(&self, snapshot_id: U64) -> RpcResult<bool>;
+}
@@ -0,0 +1,28 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use zksync_types::{Address, Bytes, U256};
+
+#[cfg_attr(
+    all(feature = "client", feature = "server"),
+    rpc(server, client, namespace = "hardhat")
+)]
+#[cfg_attr(
+    all(feature = "client", not(feature = "server")),
+    rpc(client, namespace = "hardhat")
+)]
+#[cfg_attr(
+    all(not(feature = "client"), feature = "server"),
+    rpc(server, namespace = "hardhat")
+)]
+pub trait HardhatNamespace {
+    #[method(name = "impersonateAccount")]
+    async fn impersonate_account(&self, address: Address) -> RpcResult<bool>;
+
+    #[method(name = "stopImpersonatingAccount")]
+    async fn stop_impersonating_account(&self, address: Address) -> RpcResult<bool>;
+
+    #[method(name = "setBalance")]
+    async fn set_balance(&self, address: Address, balance: U256) -> RpcResult<bool>;
+
+    #[method(name = "setCode")]
+    async fn set_code(&self, address: Address, bytecode: Bytes) -> RpcResult<bool>;
+}
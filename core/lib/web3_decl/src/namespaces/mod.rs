@@ -2,6 +2,8 @@ pub mod debug;
 pub mod en;
 pub mod eth;
 pub mod eth_subscribe;
+pub mod evm;
+pub mod hardhat;
 pub mod net;
 pub mod web3;
 pub mod zks;
@@ -10,12 +12,14 @@ pub mod zks;
 #[cfg(feature = "server")]
 pub use self::{
     debug::DebugNamespaceServer, en::EnNamespaceServer, eth::EthNamespaceServer,
-    net::NetNamespaceServer, web3::Web3NamespaceServer, zks::ZksNamespaceServer,
+    evm::EvmNamespaceServer, hardhat::HardhatNamespaceServer, net::NetNamespaceServer,
+    web3::Web3NamespaceServer, zks::ZksNamespaceServer,
 };
 
 // Client trait re-exports.
 #[cfg(feature = "client")]
 pub use self::{
     debug::DebugNamespaceClient, en::EnNamespaceClient, eth::EthNamespaceClient,
-    net::NetNamespaceClient, web3::Web3NamespaceClient, zks::ZksNamespaceClient,
+    evm::EvmNamespaceClient, hardhat::HardhatNamespaceClient, net::NetNamespaceClient,
+    web3::Web3NamespaceClient, zks::ZksNamespaceClient,
 };
@@ -5,10 +5,12 @@ use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BridgeAddresses, BridgeTransfer, L1BatchDetails, L2ToL1LogProof,
+        PaymasterPolicies, PriorityOpStatus, ProtocolVersion, TransactionDetails,
+        TransactionJourney,
     },
     fee::Fee,
+    pubdata::L1BatchPubdata,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
@@ -40,6 +42,9 @@ pub trait ZksNamespace {
     #[method(name = "getTestnetPaymaster")]
     async fn get_testnet_paymaster(&self) -> RpcResult<Option<Address>>;
 
+    #[method(name = "getPaymasterPolicies")]
+    async fn get_paymaster_policies(&self) -> RpcResult<PaymasterPolicies>;
+
     #[method(name = "getBridgeContracts")]
     async fn get_bridge_contracts(&self) -> RpcResult<BridgeAddresses>;
 
@@ -86,6 +91,20 @@ pub trait ZksNamespace {
     #[method(name = "getTransactionDetails")]
     async fn get_transaction_details(&self, hash: H256) -> RpcResult<Option<TransactionDetails>>;
 
+    #[method(name = "getPriorityOpStatus")]
+    async fn get_priority_op_status(&self, l1_tx_hash: H256) -> RpcResult<Vec<PriorityOpStatus>>;
+
+    #[method(name = "getTransactionJourney")]
+    async fn get_transaction_journey(&self, hash: H256) -> RpcResult<Option<TransactionJourney>>;
+
+    #[method(name = "getBridgeTransfers")]
+    async fn get_bridge_transfers(
+        &self,
+        address: Address,
+        from: u32,
+        limit: u8,
+    ) -> RpcResult<Vec<BridgeTransfer>>;
+
     #[method(name = "getRawBlockTransactions")]
     async fn get_raw_block_transactions(
         &self,
@@ -96,6 +115,10 @@ pub trait ZksNamespace {
     async fn get_l1_batch_details(&self, batch: L1BatchNumber)
         -> RpcResult<Option<L1BatchDetails>>;
 
+    #[method(name = "getL1BatchPubdata")]
+    async fn get_l1_batch_pubdata(&self, batch: L1BatchNumber)
+        -> RpcResult<Option<L1BatchPubdata>>;
+
     #[method(name = "getBytecodeByHash")]
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>>;
 
@@ -15,7 +15,9 @@ use zksync_config::constants::{
     SYSTEM_CONTEXT_CURRENT_TX_ROLLING_HASH_POSITION, ZKPORTER_IS_AVAILABLE,
 };
 use zksync_dal::{ConnectionPool, SqlxError, StorageProcessor};
-use zksync_state::{PostgresStorage, ReadStorage, StorageView, WriteStorage};
+use zksync_state::{
+    ForkStorage, PostgresStorage, ReadStorage, StoragePtr, StorageView, WriteStorage,
+};
 use zksync_types::{
     api,
     block::{legacy_miniblock_hash, pack_block_info, unpack_block_info},
@@ -36,8 +38,12 @@ pub(super) fn apply_vm_in_sandbox<T>(
     connection_pool: &ConnectionPool,
     tx: Transaction,
     block_args: BlockArgs,
-    apply: impl FnOnce(&mut VmInstance<'_, PostgresStorage<'_>, HistoryDisabled>, Transaction) -> T,
-) -> T {
+    apply: impl FnOnce(
+        &mut VmInstance<'_, ForkStorage<PostgresStorage<'_>>, HistoryDisabled>,
+        Transaction,
+        &StoragePtr<StorageView<ForkStorage<PostgresStorage<'_>>>>,
+    ) -> T,
+) -> anyhow::Result<T> {
     let stage_started_at = Instant::now();
     let span = tracing::debug_span!("initialization").entered();
 
@@ -119,7 +125,11 @@ pub(super) fn apply_vm_in_sandbox<T>(
 
     let storage = PostgresStorage::new(rt_handle.clone(), connection, state_l2_block_number, false)
         .with_caches(shared_args.caches);
-    let mut storage_view = StorageView::new(storage);
+    let fork_storage = ForkStorage::new(storage, shared_args.fork);
+    // Errors fetching data from the fork are recorded instead of propagated by the storage, so we check them
+    // after the execution; the execution result is meaningless in this case.
+    let fork_errors = fork_storage.error_slot();
+    let mut storage_view = StorageView::new(fork_storage);
 
     let storage_view_setup_started_at = Instant::now();
     if let Some(nonce) = execution_args.enforced_nonce {
@@ -167,6 +177,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
     let TxSharedArgs {
         operator_account,
         l1_gas_price,
+        fair_pubdata_price,
         fair_l2_gas_price,
         base_system_contracts,
         validation_computational_gas_limit,
@@ -190,6 +201,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
         number: vm_l1_batch_number,
         timestamp: l1_batch_timestamp,
         l1_gas_price,
+        fair_pubdata_price,
         fair_l2_gas_price,
         fee_account: *operator_account.address(),
         enforced_base_fee: execution_args.enforced_base_fee,
@@ -218,7 +230,7 @@ pub(super) fn apply_vm_in_sandbox<T>(
         tx.nonce().unwrap_or(Nonce(0))
     );
     let stage_started_at = Instant::now();
-    let result = apply(&mut vm, tx);
+    let result = apply(&mut vm, tx, &storage_view);
     let vm_execution_took = stage_started_at.elapsed();
     metrics::histogram!("api.web3.sandbox", vm_execution_took, "stage" => "execution");
 
@@ -233,7 +245,8 @@ pub(super) fn apply_vm_in_sandbox<T>(
     }
     drop(vm_permit); // Ensure that the permit lives until this point
 
-    result
+    fork_errors.check()?;
+    Ok(result)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -284,8 +297,8 @@ async fn read_l2_block_info(
     }
 }
 
-#[derive(Debug)]
-struct ResolvedBlockInfo {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ResolvedBlockInfo {
     pub state_l2_block_number: MiniblockNumber,
     pub vm_l1_batch_number: L1BatchNumber,
     pub l1_batch_timestamp: u64,
@@ -300,10 +313,28 @@ impl BlockArgs {
         )
     }
 
+    /// Resolves block information once, so that all executions with the returned args are performed
+    /// in the same block context. Without pinning, args for the pending block are resolved against
+    /// the latest sealed miniblock on each execution, which can change between executions.
+    pub(crate) async fn pin(
+        self,
+        connection: &mut StorageProcessor<'_>,
+    ) -> Result<Self, SqlxError> {
+        let pinned_info = self.resolve_block_info(connection).await?;
+        Ok(Self {
+            pinned_info: Some(pinned_info),
+            ..self
+        })
+    }
+
     async fn resolve_block_info(
         &self,
         connection: &mut StorageProcessor<'_>,
     ) -> Result<ResolvedBlockInfo, SqlxError> {
+        if let Some(pinned_info) = self.pinned_info {
+            return Ok(pinned_info);
+        }
+
         let (state_l2_block_number, vm_l1_batch_number, l1_batch_timestamp) =
             if self.is_pending_miniblock() {
                 let sealed_l1_batch_number = connection
@@ -361,3 +392,69 @@ impl BlockArgs {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use db_test_macro::db_test;
+
+    use zksync_contracts::BaseSystemContracts;
+    use zksync_types::{
+        block::{miniblock_hash, MiniblockHeader},
+        commitment::CommitmentSchemes,
+        protocol_version::L1VerifierConfig,
+        system_contracts::get_system_smart_contracts,
+        Address, L1BatchCommitmentMode, L2ChainId,
+    };
+
+    use super::*;
+    use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+    #[db_test]
+    async fn pinned_block_args_are_not_affected_by_new_miniblocks(pool: ConnectionPool) {
+        let mut storage = pool.access_storage().await.unwrap();
+        let params = GenesisParams {
+            first_validator: Address::repeat_byte(0x01),
+            protocol_version: ProtocolVersionId::latest(),
+            base_system_contracts: BaseSystemContracts::load_from_disk(),
+            system_contracts: get_system_smart_contracts(),
+            first_l1_verifier_config: L1VerifierConfig::default(),
+            first_verifier_address: Address::zero(),
+            commitment_schemes: CommitmentSchemes::default(),
+            commitment_mode: L1BatchCommitmentMode::Rollup,
+        };
+        ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+            .await
+            .unwrap();
+
+        let block_args = BlockArgs::pending(&mut storage).await;
+        let pinned_args = block_args.pin(&mut storage).await.unwrap();
+        let pinned_info = pinned_args.resolve_block_info(&mut storage).await.unwrap();
+        assert_eq!(pinned_info.state_l2_block_number, MiniblockNumber(0));
+        assert_eq!(pinned_info.vm_l1_batch_number, L1BatchNumber(1));
+
+        let miniblock_header = MiniblockHeader {
+            number: MiniblockNumber(1),
+            timestamp: 1,
+            hash: miniblock_hash(MiniblockNumber(1), 1, H256::zero(), H256::zero()),
+            l1_tx_count: 0,
+            l2_tx_count: 0,
+            base_fee_per_gas: 100,
+            l1_gas_price: 100,
+            l2_fair_gas_price: 100,
+            fair_pubdata_price: None,
+            base_system_contracts_hashes: params.base_system_contracts.hashes(),
+            protocol_version: Some(ProtocolVersionId::latest()),
+            virtual_blocks: 1,
+        };
+        storage
+            .blocks_dal()
+            .insert_miniblock(&miniblock_header)
+            .await
+            .unwrap();
+
+        let info = pinned_args.resolve_block_info(&mut storage).await.unwrap();
+        assert_eq!(info, pinned_info);
+        let info = block_args.resolve_block_info(&mut storage).await.unwrap();
+        assert_eq!(info.state_l2_block_number, MiniblockNumber(1));
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use vm::{Halt, TxRevertReason};
+use vm::{Halt, TxRevertReason, ValidationError};
 
 #[derive(Debug, Error)]
 pub(crate) enum SandboxExecutionError {
@@ -76,3 +76,14 @@ impl From<TxRevertReason> for SandboxExecutionError {
         }
     }
 }
+
+/// Error validating a transaction in the sandbox.
+#[derive(Debug, Error)]
+pub(crate) enum SandboxValidationError {
+    /// The transaction has failed validation.
+    #[error("{0}")]
+    Validation(ValidationError),
+    /// Validation could not be performed, e.g. because of an error fetching data from the fork.
+    #[error("internal error: {0:#}")]
+    Internal(#[from] anyhow::Error),
+}
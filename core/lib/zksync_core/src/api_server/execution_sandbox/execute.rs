@@ -1,19 +1,23 @@
 //! Implementation of "executing" methods, e.g. `eth_call`.
 
+use anyhow::Context as _;
 use tracing::{span, Level};
 
 use multivm::MultivmTracer;
 use vm::{
-    constants::ETH_CALL_GAS_LIMIT, StorageInvocations, TxExecutionMode, VmExecutionResultAndLogs,
+    constants::ETH_CALL_GAS_LIMIT, BytecodeCompressionError, StorageInvocations, TxExecutionMode,
+    VmExecutionResultAndLogs,
 };
 use zksync_dal::ConnectionPool;
-
+use zksync_state::ReadStorage;
 use zksync_types::{
     fee::TransactionExecutionMetrics, l2::L2Tx, ExecuteTransactionCommon, Nonce,
     PackedEthSignature, Transaction, U256,
 };
+use zksync_utils::bytecode::hash_bytecode;
 
 use super::{apply, vm_metrics, ApiTracer, BlockArgs, TxSharedArgs, VmPermit};
+use crate::state_keeper::execution_hints::TxExecutionHint;
 
 #[derive(Debug)]
 pub(crate) struct TxExecutionArgs {
@@ -81,7 +85,7 @@ pub(crate) async fn execute_tx_eth_call(
     block_args: BlockArgs,
     vm_execution_cache_misses_limit: Option<usize>,
     custom_tracers: Vec<ApiTracer>,
-) -> VmExecutionResultAndLogs {
+) -> anyhow::Result<VmExecutionResultAndLogs> {
     let enforced_base_fee = tx.common_data.fee.max_fee_per_gas.as_u64();
     let execution_args =
         TxExecutionArgs::for_eth_call(enforced_base_fee, vm_execution_cache_misses_limit);
@@ -94,7 +98,7 @@ pub(crate) async fn execute_tx_eth_call(
     // limiting the amount of gas the call can use.
     // We can't use BLOCK_ERGS_LIMIT here since the VM itself has some overhead.
     tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
-    let (vm_result, _) = execute_tx_in_sandbox(
+    let (vm_result, ..) = execute_tx_in_sandbox(
         vm_permit,
         shared_args,
         execution_args,
@@ -103,9 +107,70 @@ pub(crate) async fn execute_tx_eth_call(
         block_args,
         custom_tracers,
     )
-    .await;
+    .await?;
+
+    Ok(vm_result)
+}
+
+/// Executes an `eth_call`-like transaction twice: with and without bytecode compression. Both executions
+/// are performed on the same state; block args are pinned, so that a miniblock sealed between executions
+/// doesn't affect the comparison.
+pub(crate) async fn execute_tx_with_and_without_compression(
+    vm_permit: VmPermit,
+    shared_args: TxSharedArgs,
+    connection_pool: ConnectionPool,
+    mut tx: L2Tx,
+    block_args: BlockArgs,
+    vm_execution_cache_misses_limit: Option<usize>,
+) -> anyhow::Result<(
+    Result<VmExecutionResultAndLogs, BytecodeCompressionError>,
+    VmExecutionResultAndLogs,
+)> {
+    let enforced_base_fee = tx.common_data.fee.max_fee_per_gas.as_u64();
+    let execution_args =
+        TxExecutionArgs::for_eth_call(enforced_base_fee, vm_execution_cache_misses_limit);
+    if tx.common_data.signature.is_empty() {
+        tx.common_data.signature = PackedEthSignature::default().serialize_packed().into();
+    }
+    tx.common_data.fee.gas_limit = ETH_CALL_GAS_LIMIT.into();
+    let tx = Transaction::from(tx);
+
+    let mut connection = connection_pool.access_storage_tagged("api").await?;
+    let block_args = block_args
+        .pin(&mut connection)
+        .await
+        .context("failed pinning block args")?;
+    drop(connection);
 
-    vm_result
+    let thread_pool = vm_permit.thread_pool().clone();
+    thread_pool
+        .spawn(move || -> anyhow::Result<_> {
+            let execute = |with_compression: bool| {
+                apply::apply_vm_in_sandbox(
+                    vm_permit.clone(),
+                    shared_args.clone(),
+                    &execution_args,
+                    &connection_pool,
+                    tx.clone(),
+                    block_args,
+                    |vm, tx, _| {
+                        let storage_invocation_tracer =
+                            StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                        vm.inspect_transaction_with_bytecode_compression(
+                            vec![storage_invocation_tracer.into_boxed()],
+                            tx,
+                            with_compression,
+                        )
+                    },
+                )
+            };
+            let compressed_result = execute(true)?;
+            let uncompressed_result =
+                execute(false)?.expect("Compression can't fail if we don't apply it");
+            Ok((compressed_result, uncompressed_result))
+        })
+        .await
+        .unwrap()
 }
 
 #[tracing::instrument(skip_all)]
@@ -115,7 +180,11 @@ pub(crate) async fn execute_tx_with_pending_state(
     execution_args: TxExecutionArgs,
     connection_pool: ConnectionPool,
     tx: Transaction,
-) -> (VmExecutionResultAndLogs, TransactionExecutionMetrics) {
+) -> anyhow::Result<(
+    VmExecutionResultAndLogs,
+    TransactionExecutionMetrics,
+    TxExecutionHint,
+)> {
     let mut connection = connection_pool.access_storage_tagged("api").await.unwrap();
     let block_args = BlockArgs::pending(&mut connection).await;
     drop(connection);
@@ -123,7 +192,8 @@ pub(crate) async fn execute_tx_with_pending_state(
     // <= to the one in the transaction itself.
     shared_args.adjust_l1_gas_price(tx.gas_per_pubdata_byte_limit());
 
-    execute_tx_in_sandbox(
+    let simulated_in = block_args.resolved_block_number();
+    let (execution_result, tx_metrics, bytecode_compression_failed) = execute_tx_in_sandbox(
         vm_permit,
         shared_args,
         execution_args,
@@ -132,11 +202,19 @@ pub(crate) async fn execute_tx_with_pending_state(
         block_args,
         vec![],
     )
-    .await
+    .await?;
+    let hint = TxExecutionHint {
+        simulated_in,
+        bytecode_compression_failed,
+    };
+    Ok((execution_result, tx_metrics, hint))
 }
 
 /// This method assumes that (block with number `resolved_block_number` is present in DB)
 /// or (`block_id` is `pending` and block with number `resolved_block_number - 1` is present in DB)
+///
+/// Besides the execution result and metrics, returns whether the transaction has failed to publish
+/// its bytecodes in the compressed form (in which case the state keeper would re-execute it without compression).
 #[allow(clippy::too_many_arguments)]
 #[tracing::instrument(skip_all)]
 async fn execute_tx_in_sandbox(
@@ -147,41 +225,55 @@ async fn execute_tx_in_sandbox(
     tx: Transaction,
     block_args: BlockArgs,
     custom_tracers: Vec<ApiTracer>,
-) -> (VmExecutionResultAndLogs, TransactionExecutionMetrics) {
+) -> anyhow::Result<(VmExecutionResultAndLogs, TransactionExecutionMetrics, bool)> {
     let total_factory_deps = tx
         .execute
         .factory_deps
         .as_ref()
         .map_or(0, |deps| deps.len() as u16);
 
-    let execution_result = tokio::task::spawn_blocking(move || {
-        let span = span!(Level::DEBUG, "execute_in_sandbox").entered();
-        let result = apply::apply_vm_in_sandbox(
-            vm_permit,
-            shared_args,
-            &execution_args,
-            &connection_pool,
-            tx,
-            block_args,
-            |vm, tx| {
-                vm.push_transaction(&tx);
-                let storage_invocation_tracer =
-                    StorageInvocations::new(execution_args.missed_storage_invocation_limit);
-                let custom_tracers: Vec<_> = custom_tracers
-                    .into_iter()
-                    .map(|tracer| tracer.into_boxed())
-                    .chain(vec![storage_invocation_tracer.into_boxed()])
-                    .collect();
-                vm.inspect_next_transaction(custom_tracers)
-            },
-        );
-        span.exit();
-        result
-    })
-    .await
-    .unwrap();
+    let thread_pool = vm_permit.thread_pool().clone();
+    let (execution_result, bytecode_compression_failed) = thread_pool
+        .spawn(move || {
+            let span = span!(Level::DEBUG, "execute_in_sandbox").entered();
+            let result = apply::apply_vm_in_sandbox(
+                vm_permit,
+                shared_args,
+                &execution_args,
+                &connection_pool,
+                tx,
+                block_args,
+                |vm, tx, storage| {
+                    vm.push_transaction(&tx);
+                    let storage_invocation_tracer =
+                        StorageInvocations::new(execution_args.missed_storage_invocation_limit);
+                    let custom_tracers: Vec<_> = custom_tracers
+                        .into_iter()
+                        .map(|tracer| tracer.into_boxed())
+                        .chain(vec![storage_invocation_tracer.into_boxed()])
+                        .collect();
+                    let result = vm.inspect_next_transaction(custom_tracers);
+                    // Mirrors the check performed by the VM in `inspect_transaction_with_bytecode_compression()`.
+                    let bytecode_compression_failed =
+                        vm.get_last_tx_compressed_bytecodes().iter().any(|info| {
+                            !storage
+                                .borrow_mut()
+                                .is_bytecode_known(&hash_bytecode(&info.original))
+                        });
+                    (result, bytecode_compression_failed)
+                },
+            );
+            span.exit();
+            result
+        })
+        .await
+        .unwrap()?;
 
     let tx_execution_metrics =
         vm_metrics::collect_tx_execution_metrics(total_factory_deps, &execution_result);
-    (execution_result, tx_execution_metrics)
+    Ok((
+        execution_result,
+        tx_execution_metrics,
+        bytecode_compression_failed,
+    ))
 }
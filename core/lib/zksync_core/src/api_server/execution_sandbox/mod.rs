@@ -5,11 +5,14 @@ use tokio::runtime::Handle;
 use vm::utils::fee::derive_base_fee_and_gas_per_pubdata;
 use zksync_config::constants::PUBLISH_BYTECODE_OVERHEAD;
 use zksync_dal::{ConnectionPool, SqlxError, StorageProcessor};
-use zksync_state::{PostgresStorage, PostgresStorageCaches, ReadStorage, StorageView};
+use zksync_state::{
+    ForkStorage, ForkedState, PostgresStorage, PostgresStorageCaches, ReadStorage, StorageView,
+};
 use zksync_types::{api, AccountTreeId, L2ChainId, MiniblockNumber, U256};
 use zksync_utils::bytecode::{compress_bytecode, hash_bytecode};
 
 use super::tx_sender::MultiVMBaseSystemContracts;
+use crate::vm_thread_pool::VmThreadPool;
 
 // Note: keep the modules private, and instead re-export functions that make public interface.
 mod apply;
@@ -17,12 +20,17 @@ mod error;
 mod execute;
 mod tracers;
 mod validate;
+mod validation_cache;
 mod vm_metrics;
 
-pub(super) use self::{
-    error::SandboxExecutionError,
-    execute::{execute_tx_eth_call, execute_tx_with_pending_state, TxExecutionArgs},
+pub(crate) use self::{
+    error::{SandboxExecutionError, SandboxValidationError},
+    execute::{
+        execute_tx_eth_call, execute_tx_with_and_without_compression,
+        execute_tx_with_pending_state, TxExecutionArgs,
+    },
     tracers::ApiTracer,
+    validation_cache::ValidationCache,
 };
 
 /// Permit to invoke VM code.
@@ -33,6 +41,8 @@ pub(super) use self::{
 pub struct VmPermit {
     /// A handle to the runtime that is used to query the VM storage.
     rt_handle: Handle,
+    /// Thread pool on which VM code should be executed.
+    thread_pool: VmThreadPool,
     _permit: Arc<tokio::sync::OwnedSemaphorePermit>,
 }
 
@@ -40,6 +50,10 @@ impl VmPermit {
     fn rt_handle(&self) -> &Handle {
         &self.rt_handle
     }
+
+    fn thread_pool(&self) -> &VmThreadPool {
+        &self.thread_pool
+    }
 }
 
 /// Barrier-like synchronization primitive allowing to close a [`VmConcurrencyLimiter`] it's attached to
@@ -88,13 +102,15 @@ impl VmConcurrencyBarrier {
 /// level (i.e. before any async calls are made or VM is instantiated),
 ///
 /// Note that the actual limit on the number of VMs is a minimum of the limit in this structure,
-/// *and* the size of the blocking tokio threadpool. So, even if the limit is set to 1024, but
-/// tokio is configured to have no more than 512 blocking threads, the actual limit will be 512.
+/// *and* the size of the thread pool VMs are executed on (by default, the blocking tokio threadpool).
+/// So, even if the limit is set to 1024, but tokio is configured to have no more than 512 blocking threads,
+/// the actual limit will be 512.
 #[derive(Debug)]
 pub struct VmConcurrencyLimiter {
     /// Semaphore that limits the number of concurrent VM executions.
     limiter: Arc<tokio::sync::Semaphore>,
     rt_handle: Handle,
+    thread_pool: VmThreadPool,
 }
 
 impl VmConcurrencyLimiter {
@@ -108,6 +124,7 @@ impl VmConcurrencyLimiter {
         let this = Self {
             limiter: Arc::clone(&limiter),
             rt_handle: Handle::current(),
+            thread_pool: VmThreadPool::default(),
         };
         let barrier = VmConcurrencyBarrier {
             limiter,
@@ -116,6 +133,14 @@ impl VmConcurrencyLimiter {
         (this, barrier)
     }
 
+    /// Sets the thread pool to execute VM code on. If not called, VMs are executed
+    /// on the blocking tokio threadpool.
+    #[must_use]
+    pub fn with_thread_pool(mut self, thread_pool: VmThreadPool) -> Self {
+        self.thread_pool = thread_pool;
+        self
+    }
+
     /// Waits until there is a free slot in the concurrency limiter.
     /// Returns a permit that should be dropped when the VM execution is finished.
     pub async fn acquire(&self) -> Option<VmPermit> {
@@ -137,6 +162,7 @@ impl VmConcurrencyLimiter {
         metrics::histogram!("api.web3.sandbox", elapsed, "stage" => "vm_concurrency_limiter_acquire");
         Some(VmPermit {
             rt_handle: self.rt_handle.clone(),
+            thread_pool: self.thread_pool.clone(),
             _permit: Arc::new(permit),
         })
     }
@@ -179,13 +205,14 @@ async fn get_pending_state(
 
 /// Returns the number of the pubdata that the transaction will spend on factory deps.
 pub(super) async fn get_pubdata_for_factory_deps(
-    _vm_permit: &VmPermit,
+    vm_permit: &VmPermit,
     connection_pool: &ConnectionPool,
     factory_deps: &[Vec<u8>],
     storage_caches: PostgresStorageCaches,
-) -> u32 {
+    fork: Option<ForkedState>,
+) -> anyhow::Result<u32> {
     if factory_deps.is_empty() {
-        return 0; // Shortcut for the common case allowing to not acquire DB connections etc.
+        return Ok(0); // Shortcut for the common case allowing to not acquire DB connections etc.
     }
 
     let mut connection = connection_pool.access_storage_tagged("api").await.unwrap();
@@ -195,30 +222,36 @@ pub(super) async fn get_pubdata_for_factory_deps(
     let rt_handle = Handle::current();
     let connection_pool = connection_pool.clone();
     let factory_deps = factory_deps.to_vec();
-    tokio::task::spawn_blocking(move || {
-        let connection = rt_handle
-            .block_on(connection_pool.access_storage_tagged("api"))
-            .unwrap();
-        let storage = PostgresStorage::new(rt_handle, connection, block_number, false)
-            .with_caches(storage_caches);
-        let mut storage_view = StorageView::new(storage);
-
-        let effective_lengths = factory_deps.iter().map(|bytecode| {
-            if storage_view.is_bytecode_known(&hash_bytecode(bytecode)) {
-                return 0;
-            }
-
-            let length = if let Ok(compressed) = compress_bytecode(bytecode) {
-                compressed.len()
-            } else {
-                bytecode.len()
-            };
-            length as u32 + PUBLISH_BYTECODE_OVERHEAD
-        });
-        effective_lengths.sum()
-    })
-    .await
-    .unwrap()
+    vm_permit
+        .thread_pool()
+        .spawn(move || -> anyhow::Result<_> {
+            let connection = rt_handle
+                .block_on(connection_pool.access_storage_tagged("api"))
+                .unwrap();
+            let storage = PostgresStorage::new(rt_handle, connection, block_number, false)
+                .with_caches(storage_caches);
+            let fork_storage = ForkStorage::new(storage, fork);
+            let fork_errors = fork_storage.error_slot();
+            let mut storage_view = StorageView::new(fork_storage);
+
+            let effective_lengths = factory_deps.iter().map(|bytecode| {
+                if storage_view.is_bytecode_known(&hash_bytecode(bytecode)) {
+                    return 0;
+                }
+
+                let length = if let Ok(compressed) = compress_bytecode(bytecode) {
+                    compressed.len()
+                } else {
+                    bytecode.len()
+                };
+                length as u32 + PUBLISH_BYTECODE_OVERHEAD
+            });
+            let pubdata: u32 = effective_lengths.sum();
+            fork_errors.check()?;
+            Ok(pubdata)
+        })
+        .await
+        .unwrap()
 }
 
 /// Arguments for VM execution not specific to a particular transaction.
@@ -226,9 +259,13 @@ pub(super) async fn get_pubdata_for_factory_deps(
 pub(crate) struct TxSharedArgs {
     pub operator_account: AccountTreeId,
     pub l1_gas_price: u64,
+    /// Price of publishing a byte of pubdata, used to refund the pubdata overcharged by the bootloader.
+    pub fair_pubdata_price: u64,
     pub fair_l2_gas_price: u64,
     pub base_system_contracts: MultiVMBaseSystemContracts,
     pub caches: PostgresStorageCaches,
+    /// Remote state forked in the dev mode.
+    pub fork: Option<ForkedState>,
     pub validation_computational_gas_limit: u32,
     pub chain_id: L2ChainId,
 }
@@ -239,6 +276,8 @@ pub(crate) struct BlockArgs {
     block_id: api::BlockId,
     resolved_block_number: MiniblockNumber,
     l1_batch_timestamp_s: Option<u64>,
+    /// Block information resolved once for all executions using these args; see [`Self::pin()`].
+    pinned_info: Option<apply::ResolvedBlockInfo>,
 }
 
 impl BlockArgs {
@@ -248,6 +287,7 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s: None,
+            pinned_info: None,
         }
     }
 
@@ -285,6 +325,7 @@ impl BlockArgs {
             block_id,
             resolved_block_number,
             l1_batch_timestamp_s,
+            pinned_info: None,
         }))
     }
 
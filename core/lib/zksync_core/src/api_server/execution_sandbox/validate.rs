@@ -7,10 +7,15 @@ use vm::{
 };
 use zksync_dal::{ConnectionPool, StorageProcessor};
 
-use zksync_types::{l2::L2Tx, Transaction, TRUSTED_ADDRESS_SLOTS, TRUSTED_TOKEN_SLOTS, U256};
+use zksync_types::{
+    l2::L2Tx, web3::signing::keccak256, Address, MiniblockNumber, Transaction, H256,
+    TRUSTED_ADDRESS_SLOTS, TRUSTED_TOKEN_SLOTS, U256,
+};
 
 use super::{
-    adjust_l1_gas_price_for_tx, apply, BlockArgs, TxExecutionArgs, TxSharedArgs, VmPermit,
+    adjust_l1_gas_price_for_tx, apply,
+    validation_cache::{tracked_read_keys, ValidationCache, ValidationCacheKey},
+    BlockArgs, SandboxValidationError, TxExecutionArgs, TxSharedArgs, VmPermit,
 };
 
 impl TxSharedArgs {
@@ -20,7 +25,8 @@ impl TxSharedArgs {
         connection_pool: ConnectionPool,
         tx: L2Tx,
         computational_gas_limit: u32,
-    ) -> Result<(), ValidationError> {
+        validation_cache: Option<&ValidationCache>,
+    ) -> Result<(), SandboxValidationError> {
         let mut connection = connection_pool.access_storage_tagged("api").await.unwrap();
         let block_args = BlockArgs::pending(&mut connection).await;
         drop(connection);
@@ -31,10 +37,20 @@ impl TxSharedArgs {
             tx,
             block_args,
             computational_gas_limit,
+            validation_cache,
         )
         .await
     }
 
+    /// Computes the hash of the transaction and VM parameters that influence its validation.
+    fn validation_params_hash(&self, tx: &L2Tx, computational_gas_limit: u32) -> H256 {
+        let mut bytes = tx.hash().as_bytes().to_vec();
+        bytes.extend_from_slice(&self.l1_gas_price.to_be_bytes());
+        bytes.extend_from_slice(&self.fair_l2_gas_price.to_be_bytes());
+        bytes.extend_from_slice(&computational_gas_limit.to_be_bytes());
+        H256(keccak256(&bytes))
+    }
+
     // In order for validation to pass smoothlessly, we need to ensure that block's required gasPerPubdata will be
     // <= to the one in the transaction itself.
     pub fn adjust_l1_gas_price(&mut self, gas_per_pubdata_limit: U256) {
@@ -52,17 +68,41 @@ impl TxSharedArgs {
         tx: L2Tx,
         block_args: BlockArgs,
         computational_gas_limit: u32,
-    ) -> Result<(), ValidationError> {
+        validation_cache: Option<&ValidationCache>,
+    ) -> Result<(), SandboxValidationError> {
         let stage_started_at = Instant::now();
+        let cache_key = ValidationCacheKey {
+            account: tx.initiator_account(),
+            nonce: tx.nonce(),
+            params_hash: self.validation_params_hash(&tx, computational_gas_limit),
+        };
+        let validation_cache = if let Some(cache) = validation_cache {
+            // Validation is performed on top of the last sealed miniblock, which precedes the pending one.
+            let sealed_miniblock = MiniblockNumber(block_args.resolved_block_number().0 - 1);
+            if let Some(result) = cache.get(&cache_key, sealed_miniblock) {
+                return result.map_err(SandboxValidationError::Validation);
+            }
+            Some((cache.clone(), sealed_miniblock))
+        } else {
+            None
+        };
+
         let mut connection = connection_pool.access_storage_tagged("api").await.unwrap();
         let validation_params =
             get_validation_params(&mut connection, &tx, computational_gas_limit).await;
         drop(connection);
 
+        let track_reads = validation_cache.is_some();
+        let payer = match tx.common_data.paymaster_params.paymaster {
+            paymaster if paymaster == Address::zero() => tx.initiator_account(),
+            paymaster => paymaster,
+        };
+
         let execution_args = TxExecutionArgs::for_validation(&tx);
         let tx: Transaction = tx.into();
 
-        let validation_result = tokio::task::spawn_blocking(move || {
+        let thread_pool = vm_permit.thread_pool().clone();
+        let validation_result = thread_pool.spawn(move || {
             let span = tracing::debug_span!("validate_in_sandbox").entered();
             let result = apply::apply_vm_in_sandbox(
                 vm_permit,
@@ -71,7 +111,7 @@ impl TxSharedArgs {
                 &connection_pool,
                 tx,
                 block_args,
-                |vm, tx| {
+                |vm, tx, storage_view| {
                     let stage_started_at = Instant::now();
                     let span = tracing::debug_span!("validation").entered();
                     vm.push_transaction(&tx);
@@ -91,18 +131,33 @@ impl TxSharedArgs {
                         (_, None) => Ok(()),
                     };
 
+                    let read_keys = if track_reads {
+                        let storage_view = storage_view.borrow();
+                        tracked_read_keys(storage_view.read_storage_keys().keys(), payer)
+                    } else {
+                        vec![]
+                    };
 
                     metrics::histogram!("api.web3.sandbox", stage_started_at.elapsed(), "stage" => "validation");
                     span.exit();
-                    result
+                    (result, read_keys)
                 },
             );
             span.exit();
             result
-        }).await.unwrap();
+        }).await.unwrap()?;
 
         metrics::histogram!("server.api.validation_sandbox", stage_started_at.elapsed(), "stage" => "validate_in_sandbox");
-        validation_result
+        let (validation_result, read_keys) = validation_result;
+        if let Some((cache, sealed_miniblock)) = validation_cache {
+            cache.insert(
+                cache_key,
+                sealed_miniblock,
+                read_keys,
+                validation_result.clone(),
+            );
+        }
+        validation_result.map_err(SandboxValidationError::Validation)
     }
 }
 
@@ -0,0 +1,411 @@
+//! Cache of transaction validation outcomes.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use vm::ValidationError;
+use zksync_config::constants::{BOOTLOADER_ADDRESS, SYSTEM_CONTEXT_ADDRESS};
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::{
+    utils::storage_key_for_eth_balance, Address, MiniblockNumber, Nonce, StorageKey, H256,
+};
+
+/// Maximum number of miniblocks the cache can lag behind the latest sealed miniblock. If the lag is larger,
+/// the cache is cleared instead of loading storage keys modified in all lagging miniblocks.
+const MAX_MINIBLOCKS_LAG: u32 = 10;
+
+/// Selects hashed storage keys read during validation that a cached outcome depends on. Slots modified
+/// by (almost) every transaction are excluded, since they would invalidate the outcome on each miniblock:
+///
+/// - The system context, which the bootloader reads when starting a miniblock.
+/// - ETH balances of the bootloader and of the fee `payer` (the paymaster, or the initiator if there is none),
+///   which are read when charging the fee. The fee payment is re-checked by the dry run preceding validation
+///   in the transaction sender.
+pub(super) fn tracked_read_keys<'a>(
+    read_keys: impl Iterator<Item = &'a StorageKey>,
+    payer: Address,
+) -> Vec<H256> {
+    let excluded_keys = [
+        storage_key_for_eth_balance(&BOOTLOADER_ADDRESS),
+        storage_key_for_eth_balance(&payer),
+    ];
+    read_keys
+        .filter(|key| *key.address() != SYSTEM_CONTEXT_ADDRESS && !excluded_keys.contains(key))
+        .map(StorageKey::hashed_key)
+        .collect()
+}
+
+/// Key of a cached validation outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ValidationCacheKey {
+    pub account: Address,
+    pub nonce: Nonce,
+    /// Hash of the transaction and the VM parameters influencing its validation (e.g., the L1 gas price).
+    pub params_hash: H256,
+}
+
+#[derive(Debug)]
+struct CachedValidation {
+    /// Hashed storage keys read during validation.
+    read_keys: Vec<H256>,
+    result: Result<(), ValidationError>,
+}
+
+#[derive(Debug)]
+struct ValidationCacheInner {
+    capacity: usize,
+    /// Latest sealed miniblock. All cached outcomes are valid for the state after this miniblock.
+    valid_for: MiniblockNumber,
+    entries: HashMap<ValidationCacheKey, CachedValidation>,
+    insertion_order: VecDeque<ValidationCacheKey>,
+    /// Cache keys indexed by hashed storage keys read during validation.
+    readers: HashMap<H256, HashSet<ValidationCacheKey>>,
+}
+
+impl ValidationCacheInner {
+    fn remove(&mut self, key: &ValidationCacheKey) {
+        let Some(entry) = self.entries.remove(key) else {
+            return;
+        };
+        for read_key in &entry.read_keys {
+            if let Some(readers) = self.readers.get_mut(read_key) {
+                readers.remove(key);
+                if readers.is_empty() {
+                    self.readers.remove(read_key);
+                }
+            }
+        }
+    }
+
+    fn invalidate(&mut self, to_miniblock: MiniblockNumber, modified_keys: &[H256]) {
+        for modified_key in modified_keys {
+            let Some(readers) = self.readers.remove(modified_key) else {
+                continue;
+            };
+            for key in &readers {
+                self.remove(key);
+            }
+        }
+        self.valid_for = to_miniblock;
+    }
+
+    fn clear(&mut self, to_miniblock: MiniblockNumber) {
+        self.entries.clear();
+        self.insertion_order.clear();
+        self.readers.clear();
+        self.valid_for = to_miniblock;
+    }
+}
+
+/// Bounded cache of transaction validation outcomes used by the API server when admitting transactions.
+///
+/// Validation of a transaction (most notably, account abstraction logic) only depends on the transaction itself,
+/// a few VM parameters and the storage slots read during validation. Thus, a cached outcome is invalidated
+/// once any of these slots is modified in a sealed miniblock. Modified slots are loaded by the task returned
+/// from [`Self::update_task()`], so that cache lookups don't require DB queries.
+#[derive(Debug, Clone)]
+pub(crate) struct ValidationCache(Arc<Mutex<ValidationCacheInner>>);
+
+impl ValidationCache {
+    /// Creates a cache that holds at most `capacity` outcomes; older outcomes are evicted first.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(ValidationCacheInner {
+            capacity,
+            valid_for: MiniblockNumber(0),
+            entries: HashMap::new(),
+            insertion_order: VecDeque::with_capacity(capacity),
+            readers: HashMap::new(),
+        })))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ValidationCacheInner> {
+        self.0.lock().expect("validation cache is poisoned")
+    }
+
+    /// Returns a task invalidating outcomes as new miniblocks are sealed. The task checks the latest sealed
+    /// miniblock with the specified interval, and terminates once all other cache handles are dropped.
+    pub fn update_task(
+        &self,
+        connection_pool: ConnectionPool,
+        update_interval: Duration,
+    ) -> impl Future<Output = ()> + Send {
+        let cache = self.clone();
+        async move {
+            loop {
+                if Arc::strong_count(&cache.0) == 1 {
+                    tracing::debug!("Stopping validation cache updates");
+                    break;
+                }
+
+                let mut connection = connection_pool.access_storage_tagged("api").await.unwrap();
+                let sealed_miniblock = connection
+                    .blocks_web3_dal()
+                    .get_sealed_miniblock_number()
+                    .await;
+                match sealed_miniblock {
+                    Ok(number) => cache.update(&mut connection, number).await,
+                    Err(err) => tracing::warn!(
+                        "Failed fetching latest sealed miniblock to update validation cache: {err}"
+                    ),
+                }
+                drop(connection);
+                tokio::time::sleep(update_interval).await;
+            }
+        }
+    }
+
+    /// Invalidates outcomes affected by storage writes in miniblocks up to and including `sealed_miniblock`.
+    async fn update(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        sealed_miniblock: MiniblockNumber,
+    ) {
+        let valid_for = self.lock().valid_for;
+        if valid_for > sealed_miniblock {
+            // Miniblocks were reverted (e.g., using `evm_revert` in the dev mode).
+            self.lock().clear(sealed_miniblock);
+            return;
+        }
+        if valid_for == sealed_miniblock {
+            return;
+        }
+        if sealed_miniblock.0 - valid_for.0 > MAX_MINIBLOCKS_LAG {
+            self.lock().clear(sealed_miniblock);
+            return;
+        }
+
+        let modified_keys = connection
+            .storage_web3_dal()
+            .modified_keys_in_miniblocks(valid_for + 1..=sealed_miniblock)
+            .await;
+        let mut inner = self.lock();
+        // Another update may have happened concurrently; in this case, it has already invalidated
+        // all affected outcomes.
+        if inner.valid_for == valid_for {
+            inner.invalidate(sealed_miniblock, &modified_keys);
+        }
+    }
+
+    /// Returns the cached validation outcome, if any, for the validation on top of `sealed_miniblock`.
+    /// Nothing is returned if the cache has not processed storage writes up to `sealed_miniblock` yet.
+    pub fn get(
+        &self,
+        key: &ValidationCacheKey,
+        sealed_miniblock: MiniblockNumber,
+    ) -> Option<Result<(), ValidationError>> {
+        let inner = self.lock();
+        let result = if inner.valid_for >= sealed_miniblock {
+            inner.entries.get(key).map(|entry| entry.result.clone())
+        } else {
+            None
+        };
+        drop(inner);
+        let outcome = if result.is_some() { "hit" } else { "miss" };
+        metrics::increment_counter!("api.execution.validation_cache", "outcome" => outcome);
+        result
+    }
+
+    /// Caches the validation outcome computed for the state after `computed_for` miniblock.
+    /// The outcome is discarded if the cache was updated in the meantime, since the outcome
+    /// may be affected by storage writes the cache has already processed.
+    pub fn insert(
+        &self,
+        key: ValidationCacheKey,
+        computed_for: MiniblockNumber,
+        read_keys: Vec<H256>,
+        result: Result<(), ValidationError>,
+    ) {
+        let mut inner = self.lock();
+        if inner.valid_for != computed_for || inner.capacity == 0 {
+            return;
+        }
+
+        inner.remove(&key);
+        for read_key in &read_keys {
+            inner.readers.entry(*read_key).or_default().insert(key);
+        }
+        inner
+            .entries
+            .insert(key, CachedValidation { read_keys, result });
+        if !inner.insertion_order.contains(&key) {
+            inner.insertion_order.push_back(key);
+        }
+        while inner.insertion_order.len() > inner.capacity {
+            let evicted_key = inner.insertion_order.pop_front().unwrap();
+            inner.remove(&evicted_key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db_test_macro::db_test;
+
+    use vm::ViolatedValidationRule;
+    use zksync_types::{
+        block::{miniblock_hash, MiniblockHeader},
+        get_nonce_key, AccountTreeId, StorageLog,
+    };
+    use zksync_utils::u32_to_h256;
+
+    use super::*;
+
+    fn key(nonce: u32) -> ValidationCacheKey {
+        ValidationCacheKey {
+            account: Address::repeat_byte(1),
+            nonce: Nonce(nonce),
+            params_hash: H256::zero(),
+        }
+    }
+
+    #[test]
+    fn validation_cache_basics() {
+        let cache = ValidationCache::new(2);
+        let err = ValidationError::ViolatedRule(ViolatedValidationRule::TouchedUnallowedContext);
+        cache.insert(
+            key(0),
+            MiniblockNumber(0),
+            vec![H256::repeat_byte(1)],
+            Ok(()),
+        );
+        cache.insert(
+            key(1),
+            MiniblockNumber(0),
+            vec![H256::repeat_byte(1), H256::repeat_byte(2)],
+            Err(err),
+        );
+        assert!(cache.get(&key(0), MiniblockNumber(0)).unwrap().is_ok());
+        assert!(cache.get(&key(1), MiniblockNumber(0)).unwrap().is_err());
+
+        // Only the outcome depending on the modified key should be invalidated.
+        cache.lock().invalidate(
+            MiniblockNumber(1),
+            &[H256::repeat_byte(2), H256::repeat_byte(3)],
+        );
+        assert!(cache.get(&key(0), MiniblockNumber(1)).is_some());
+        assert!(cache.get(&key(1), MiniblockNumber(1)).is_none());
+        // Outcomes should not be returned for a state the cache hasn't caught up with.
+        assert!(cache.get(&key(0), MiniblockNumber(2)).is_none());
+        assert!(!cache.lock().readers.contains_key(&H256::repeat_byte(2)));
+
+        // Outcomes computed for an outdated state should not be cached.
+        cache.insert(key(2), MiniblockNumber(0), vec![], Ok(()));
+        assert!(cache.get(&key(2), MiniblockNumber(1)).is_none());
+
+        // Older outcomes are evicted first.
+        let err = ValidationError::ViolatedRule(ViolatedValidationRule::CalledContractWithNoCode(
+            Address::zero(),
+        ));
+        cache.insert(key(2), MiniblockNumber(1), vec![], Err(err));
+        cache.insert(
+            key(3),
+            MiniblockNumber(1),
+            vec![H256::repeat_byte(1)],
+            Ok(()),
+        );
+        assert!(cache.get(&key(0), MiniblockNumber(1)).is_none());
+        assert!(cache.get(&key(2), MiniblockNumber(1)).unwrap().is_err());
+        assert!(cache.get(&key(3), MiniblockNumber(1)).unwrap().is_ok());
+        assert_eq!(cache.lock().readers[&H256::repeat_byte(1)].len(), 1);
+    }
+
+    async fn seal_miniblock(
+        storage: &mut StorageProcessor<'_>,
+        number: MiniblockNumber,
+        modified_keys: &[StorageKey],
+    ) {
+        let miniblock_header = MiniblockHeader {
+            number,
+            timestamp: number.0.into(),
+            hash: miniblock_hash(number, number.0.into(), H256::zero(), H256::zero()),
+            l1_tx_count: 0,
+            l2_tx_count: 0,
+            base_fee_per_gas: 100,
+            l1_gas_price: 100,
+            l2_fair_gas_price: 100,
+            fair_pubdata_price: None,
+            base_system_contracts_hashes: Default::default(),
+            protocol_version: None,
+            virtual_blocks: 1,
+        };
+        storage
+            .blocks_dal()
+            .insert_miniblock(&miniblock_header)
+            .await
+            .unwrap();
+        let logs: Vec<_> = modified_keys
+            .iter()
+            .map(|&key| StorageLog::new_write_log(key, u32_to_h256(number.0)))
+            .collect();
+        storage
+            .storage_logs_dal()
+            .insert_storage_logs(number, &[(H256::zero(), logs)])
+            .await;
+    }
+
+    #[db_test]
+    async fn validation_cache_hit_across_sealed_miniblock(pool: ConnectionPool) {
+        let account = Address::repeat_byte(1);
+        let account_slot = StorageKey::new(AccountTreeId::new(account), H256::repeat_byte(1));
+        let bootloader_balance_key = storage_key_for_eth_balance(&BOOTLOADER_ADDRESS);
+        let payer_balance_key = storage_key_for_eth_balance(&account);
+        let system_context_key =
+            StorageKey::new(AccountTreeId::new(SYSTEM_CONTEXT_ADDRESS), H256::zero());
+        let read_keys = [
+            account_slot,
+            get_nonce_key(&account),
+            bootloader_balance_key,
+            payer_balance_key,
+            system_context_key,
+        ];
+        let read_keys = tracked_read_keys(read_keys.iter(), account);
+        assert_eq!(
+            read_keys,
+            [
+                account_slot.hashed_key(),
+                get_nonce_key(&account).hashed_key()
+            ]
+        );
+
+        let mut storage = pool.access_storage().await.unwrap();
+        let unrelated_key =
+            StorageKey::new(AccountTreeId::new(Address::repeat_byte(2)), H256::zero());
+        seal_miniblock(&mut storage, MiniblockNumber(0), &[unrelated_key]).await;
+
+        let cache = ValidationCache::new(10);
+        cache.insert(key(0), MiniblockNumber(0), read_keys, Ok(()));
+        let update_task = tokio::spawn(cache.update_task(pool.clone(), Duration::from_millis(10)));
+
+        // A miniblock with a transaction unrelated to the account: it modifies the system context
+        // and the balances of the bootloader and the account (e.g., by a transfer to it).
+        seal_miniblock(
+            &mut storage,
+            MiniblockNumber(1),
+            &[
+                unrelated_key,
+                bootloader_balance_key,
+                payer_balance_key,
+                system_context_key,
+            ],
+        )
+        .await;
+        while cache.lock().valid_for < MiniblockNumber(1) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.get(&key(0), MiniblockNumber(1)).unwrap().is_ok());
+
+        // A miniblock modifying a slot read during validation.
+        seal_miniblock(&mut storage, MiniblockNumber(2), &[account_slot]).await;
+        while cache.lock().valid_for < MiniblockNumber(2) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(cache.get(&key(0), MiniblockNumber(2)).is_none());
+
+        drop(cache);
+        update_task.await.unwrap();
+    }
+}
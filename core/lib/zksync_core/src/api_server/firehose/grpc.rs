@@ -0,0 +1,125 @@
+//! gRPC services of the `sf.firehose.v2` protocol. Like the messages in [`super::protocol`],
+//! the services are written by hand instead of being generated from `firehose.proto`, so that
+//! the crate doesn't need `protoc` at build time.
+
+use tonic::{
+    body::BoxBody,
+    codec::ProstCodec,
+    codegen::{http, Body, BoxFuture, Service, StdError},
+    server::{Grpc, NamedService, ServerStreamingService, UnaryService},
+    Status,
+};
+
+use std::{
+    convert::Infallible,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use super::{protocol, to_status, BlockStream, FirehoseState};
+
+fn unimplemented_method(path: &str) -> http::Response<BoxBody> {
+    Status::unimplemented(format!("method `{path}` is not implemented")).to_http()
+}
+
+/// `sf.firehose.v2.Stream` service.
+#[derive(Debug, Clone)]
+pub(super) struct StreamService(pub Arc<FirehoseState>);
+
+impl NamedService for StreamService {
+    const NAME: &'static str = "sf.firehose.v2.Stream";
+}
+
+impl ServerStreamingService<protocol::Request> for StreamService {
+    type Response = protocol::Response;
+    type ResponseStream = BlockStream;
+    type Future = BoxFuture<tonic::Response<BlockStream>, Status>;
+
+    fn call(&mut self, request: tonic::Request<protocol::Request>) -> Self::Future {
+        let state = self.0.clone();
+        Box::pin(async move {
+            let stream = state.stream_blocks(request.into_inner()).await?;
+            Ok(tonic::Response::new(stream))
+        })
+    }
+}
+
+impl<B> Service<http::Request<B>> for StreamService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let service = self.clone();
+        match request.uri().path() {
+            "/sf.firehose.v2.Stream/Blocks" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.server_streaming(service, request).await)
+            }),
+            path => {
+                let response = unimplemented_method(path);
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+/// `sf.firehose.v2.Fetch` service.
+#[derive(Debug, Clone)]
+pub(super) struct FetchService(pub Arc<FirehoseState>);
+
+impl NamedService for FetchService {
+    const NAME: &'static str = "sf.firehose.v2.Fetch";
+}
+
+impl UnaryService<protocol::SingleBlockRequest> for FetchService {
+    type Response = protocol::SingleBlockResponse;
+    type Future = BoxFuture<tonic::Response<protocol::SingleBlockResponse>, Status>;
+
+    fn call(&mut self, request: tonic::Request<protocol::SingleBlockRequest>) -> Self::Future {
+        let state = self.0.clone();
+        Box::pin(async move {
+            let response = state
+                .fetch_block(request.into_inner())
+                .await
+                .map_err(to_status)?;
+            Ok(tonic::Response::new(response))
+        })
+    }
+}
+
+impl<B> Service<http::Request<B>> for FetchService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let service = self.clone();
+        match request.uri().path() {
+            "/sf.firehose.v2.Fetch/Block" => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(service, request).await)
+            }),
+            path => {
+                let response = unimplemented_method(path);
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
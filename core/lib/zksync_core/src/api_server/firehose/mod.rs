@@ -0,0 +1,849 @@
+//! Firehose-compatible block stream for indexers.
+//!
+//! The server implements the `Stream` and `Fetch` gRPC services of the `sf.firehose.v2` protocol
+//! (see [`protocol`]), so it can be consumed by standard Firehose clients. Streamed blocks are flat
+//! protobuf blocks (see [`proto`]) which contain everything an indexer usually needs: transactions,
+//! receipts, logs, call traces and ETH balance changes.
+//!
+//! Miniblocks become final once their L1 batch is executed on L1; before that, they can be reverted.
+//! If a stream detects that some of the blocks it has sent were reverted, it sends `STEP_UNDO`
+//! responses for them (newest first) and continues with the replacement blocks. Each response
+//! carries a cursor that can be used to resume the stream; if the cursor block was reverted while
+//! the client was disconnected, all non-final blocks up to the cursor are undone on resumption.
+
+use anyhow::Context as _;
+use futures::{stream, Stream};
+use prost::Message as _;
+use tokio::sync::{mpsc, watch, Semaphore};
+use tonic::Status;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    fmt, mem,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use zksync_config::configs::api::FirehoseApiConfig;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::{
+    api,
+    utils::storage_key_for_eth_balance,
+    vm_trace::{Call, CallType},
+    Address, FarCallOpcode, L1BatchNumber, L2ChainId, MiniblockNumber, BOOTLOADER_ADDRESS, H256,
+    L2_ETH_TOKEN_ADDRESS, U256,
+};
+use zksync_utils::h256_to_u256;
+
+mod grpc;
+pub mod proto;
+pub mod protocol;
+#[cfg(test)]
+mod tests;
+
+/// Number of responses buffered for each stream before the stream waits for the client.
+const STREAM_BUFFER_CAPACITY: usize = 16;
+
+type BlockStream = Pin<Box<dyn Stream<Item = Result<protocol::Response, Status>> + Send>>;
+
+/// Runs the block stream server until a stop signal is received.
+pub async fn run_server(
+    config: FirehoseApiConfig,
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let max_concurrent_streams = config.max_concurrent_streams();
+    anyhow::ensure!(
+        max_concurrent_streams > 0,
+        "`max_concurrent_streams` must be positive"
+    );
+    let bind_address = config.bind_addr();
+    tracing::info!("Starting Firehose block stream server on {bind_address}");
+    let state = Arc::new(FirehoseState::new(
+        pool,
+        chain_id,
+        config.poll_interval(),
+        max_concurrent_streams,
+        stop_receiver.clone(),
+    ));
+
+    let mut stop_receiver = stop_receiver;
+    tonic::transport::Server::builder()
+        .add_service(grpc::StreamService(state.clone()))
+        .add_service(grpc::FetchService(state))
+        .serve_with_shutdown(bind_address, async move {
+            if stop_receiver.changed().await.is_err() {
+                tracing::warn!("Stop signal sender for Firehose block stream server was dropped without sending a signal");
+            }
+            tracing::info!("Stop signal received, Firehose block stream server is shutting down");
+        })
+        .await
+        .with_context(|| format!("Firehose block stream server on {bind_address} failed"))?;
+    tracing::info!("Firehose block stream server shut down");
+    Ok(())
+}
+
+/// Converts an error to a gRPC status. Client errors are passed as [`Status`] wrapped into
+/// `anyhow::Error`; all other errors are considered internal.
+fn to_status(err: anyhow::Error) -> Status {
+    match err.downcast::<Status>() {
+        Ok(status) => status,
+        Err(err) => {
+            tracing::warn!("Firehose block stream request failed: {err:#}");
+            Status::internal(format!("{err:#}"))
+        }
+    }
+}
+
+fn parse_hash(hash: &str) -> anyhow::Result<H256> {
+    let hash = hash.strip_prefix("0x").unwrap_or(hash);
+    H256::from_str(hash).context("invalid block hash")
+}
+
+/// Stream position: the number and (if known) the hash of the last block applied by the client.
+/// Encoded as `{number}:{hash}` or, if the hash is unknown, as `{number}`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cursor {
+    number: MiniblockNumber,
+    hash: Option<H256>,
+}
+
+impl Cursor {
+    fn new(number: MiniblockNumber, hash: H256) -> Self {
+        Self {
+            number,
+            hash: Some(hash),
+        }
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hash {
+            Some(hash) => write!(formatter, "{}:{hash:?}", self.number.0),
+            None => write!(formatter, "{}", self.number.0),
+        }
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (number, hash) = match s.split_once(':') {
+            Some((number, hash)) => (number, Some(hash)),
+            None => (s, None),
+        };
+        let number = number.parse().context("invalid block number")?;
+        Ok(Self {
+            number: MiniblockNumber(number),
+            hash: hash.map(parse_hash).transpose()?,
+        })
+    }
+}
+
+/// Reference to a block sent to the client.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BlockRef {
+    number: MiniblockNumber,
+    hash: H256,
+    parent_hash: H256,
+}
+
+impl BlockRef {
+    fn new(block: &proto::Block) -> Self {
+        let parent_hash = block
+            .header
+            .as_ref()
+            .map_or_else(H256::zero, |header| H256::from_slice(&header.parent_hash));
+        Self {
+            number: MiniblockNumber(block.number as u32),
+            hash: H256::from_slice(&block.hash),
+            parent_hash,
+        }
+    }
+}
+
+fn encode_block(block: &proto::Block) -> prost_types::Any {
+    prost_types::Any {
+        type_url: protocol::BLOCK_TYPE_URL.to_owned(),
+        value: block.encode_to_vec(),
+    }
+}
+
+/// Creates an undo response for a block. Only the number and (if known) the hash are set
+/// in the undone block. `parent` is the cursor after the block is undone.
+fn undo_response(
+    number: MiniblockNumber,
+    hash: Option<H256>,
+    parent: Cursor,
+) -> protocol::Response {
+    let block = proto::Block {
+        number: number.0.into(),
+        hash: hash.map_or_else(Vec::new, |hash| hash.as_bytes().to_vec()),
+        ..proto::Block::default()
+    };
+    protocol::Response {
+        block: Some(encode_block(&block)),
+        step: protocol::ForkStep::Undo as i32,
+        cursor: parent.to_string(),
+    }
+}
+
+/// Returns the last final miniblock, i.e. the last miniblock of the last L1 batch executed on L1.
+async fn last_final_block(storage: &mut StorageProcessor<'_>) -> anyhow::Result<Cursor> {
+    let executed_l1_batch = storage
+        .blocks_dal()
+        .get_number_of_last_l1_batch_executed_on_eth()
+        .await
+        .context("get_number_of_last_l1_batch_executed_on_eth()")?
+        .unwrap_or(L1BatchNumber(0));
+    let (_, last_miniblock) = storage
+        .blocks_dal()
+        .get_miniblock_range_of_l1_batch(executed_l1_batch)
+        .await
+        .context("get_miniblock_range_of_l1_batch()")?
+        .with_context(|| format!("L1 batch #{executed_l1_batch} has no miniblocks"))?;
+    let hash = storage
+        .blocks_web3_dal()
+        .get_miniblock_hash(last_miniblock)
+        .await
+        .context("get_miniblock_hash()")?
+        .with_context(|| format!("miniblock #{last_miniblock} is not in the database"))?;
+    Ok(Cursor::new(last_miniblock, hash))
+}
+
+#[derive(Debug)]
+struct FirehoseState {
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    poll_interval: Duration,
+    /// Limits the number of concurrently served block streams.
+    streams: Arc<Semaphore>,
+    stop_receiver: watch::Receiver<bool>,
+}
+
+impl FirehoseState {
+    fn new(
+        pool: ConnectionPool,
+        chain_id: L2ChainId,
+        poll_interval: Duration,
+        max_concurrent_streams: usize,
+        stop_receiver: watch::Receiver<bool>,
+    ) -> Self {
+        Self {
+            pool,
+            chain_id,
+            poll_interval,
+            streams: Arc::new(Semaphore::new(max_concurrent_streams)),
+            stop_receiver,
+        }
+    }
+
+    /// Handles the `sf.firehose.v2.Stream/Blocks` method.
+    async fn stream_blocks(
+        self: Arc<Self>,
+        request: protocol::Request,
+    ) -> Result<BlockStream, Status> {
+        if !request.transforms.is_empty() {
+            return Err(Status::invalid_argument("transforms are not supported"));
+        }
+        let permit = self
+            .streams
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| Status::resource_exhausted("too many concurrent block streams"))?;
+        let streamer = BlockStreamer::new(self, &request)
+            .await
+            .map_err(to_status)?;
+        metrics::increment_counter!("api.firehose.streams");
+
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER_CAPACITY);
+        tokio::spawn(async move {
+            let _permit = permit;
+            if let Err(err) = streamer.run(&sender).await {
+                sender.send(Err(to_status(err))).await.ok();
+            }
+        });
+        let responses = stream::unfold(receiver, |mut receiver| async move {
+            let response = receiver.recv().await?;
+            Some((response, receiver))
+        });
+        Ok(Box::pin(responses))
+    }
+
+    /// Handles the `sf.firehose.v2.Fetch/Block` method.
+    async fn fetch_block(
+        &self,
+        request: protocol::SingleBlockRequest,
+    ) -> anyhow::Result<protocol::SingleBlockResponse> {
+        use protocol::single_block_request::Reference;
+
+        if !request.transforms.is_empty() {
+            return Err(Status::invalid_argument("transforms are not supported").into());
+        }
+        let (number, hash) = match request.reference {
+            None => return Err(Status::invalid_argument("block reference is not specified").into()),
+            Some(Reference::BlockNumber(reference)) => (reference.num, None),
+            Some(Reference::BlockHashAndNumber(reference)) => {
+                let hash = parse_hash(&reference.hash)
+                    .map_err(|err| Status::invalid_argument(format!("{err:#}")))?;
+                (reference.num, Some(hash))
+            }
+            Some(Reference::Cursor(reference)) => {
+                let cursor: Cursor = reference
+                    .cursor
+                    .parse()
+                    .map_err(|err| Status::invalid_argument(format!("invalid cursor: {err:#}")))?;
+                (cursor.number.0.into(), cursor.hash)
+            }
+        };
+        let not_found = || Status::not_found(format!("block #{number} is not found"));
+        let number = u32::try_from(number).map_err(|_| not_found())?;
+        let number = MiniblockNumber(number);
+
+        let mut storage = self.pool.access_storage_tagged("firehose_api").await?;
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        if number > sealed_miniblock {
+            return Err(not_found().into());
+        }
+        let block = load_block(&mut storage, number, self.chain_id).await?;
+        if matches!(hash, Some(hash) if block.hash != hash.as_bytes()) {
+            return Err(not_found().into());
+        }
+        Ok(protocol::SingleBlockResponse {
+            block: Some(encode_block(&block)),
+        })
+    }
+}
+
+/// Streams blocks to a single client.
+#[derive(Debug)]
+struct BlockStreamer {
+    state: Arc<FirehoseState>,
+    next_block: MiniblockNumber,
+    stop_block: Option<MiniblockNumber>,
+    final_blocks_only: bool,
+    /// Non-final blocks sent to the client, in the ascending order.
+    sent_blocks: VecDeque<BlockRef>,
+    /// Undo responses to send before streaming blocks (used when resuming from a reverted cursor).
+    pending_undos: Vec<protocol::Response>,
+}
+
+impl BlockStreamer {
+    async fn new(state: Arc<FirehoseState>, request: &protocol::Request) -> anyhow::Result<Self> {
+        let stop_block = (request.stop_block_num > 0)
+            .then(|| MiniblockNumber(u32::try_from(request.stop_block_num).unwrap_or(u32::MAX)));
+        let mut storage = state.pool.access_storage_tagged("firehose_api").await?;
+        let final_block = last_final_block(&mut storage).await?;
+        let mut this = Self {
+            state: state.clone(),
+            next_block: MiniblockNumber(0),
+            stop_block,
+            final_blocks_only: request.final_blocks_only,
+            sent_blocks: VecDeque::new(),
+            pending_undos: vec![],
+        };
+
+        if request.cursor.is_empty() {
+            this.next_block = if let Ok(start_block) = u32::try_from(request.start_block_num) {
+                MiniblockNumber(start_block)
+            } else if request.start_block_num < 0 {
+                let head = if request.final_blocks_only {
+                    final_block.number
+                } else {
+                    storage
+                        .blocks_web3_dal()
+                        .get_sealed_miniblock_number()
+                        .await
+                        .context("get_sealed_miniblock_number()")?
+                };
+                let offset = request.start_block_num.unsigned_abs() - 1;
+                let offset = u32::try_from(offset).unwrap_or(u32::MAX);
+                MiniblockNumber(head.0.saturating_sub(offset))
+            } else {
+                return Err(Status::invalid_argument("start block is out of range").into());
+            };
+            return Ok(this);
+        }
+
+        let cursor: Cursor = request
+            .cursor
+            .parse()
+            .map_err(|err| Status::invalid_argument(format!("invalid cursor: {err:#}")))?;
+        this.next_block = cursor.number + 1;
+        if cursor.number <= final_block.number {
+            // Final blocks cannot be reverted, so the cursor must match the stored block.
+            let stored_hash = storage
+                .blocks_web3_dal()
+                .get_miniblock_hash(cursor.number)
+                .await
+                .context("get_miniblock_hash()")?;
+            if matches!(cursor.hash, Some(hash) if stored_hash != Some(hash)) {
+                let message = "cursor refers to a block that is not in the chain";
+                return Err(Status::invalid_argument(message).into());
+            }
+            return Ok(this);
+        }
+        if request.final_blocks_only {
+            let message = "cursor refers to a non-final block, but only final blocks are requested";
+            return Err(Status::invalid_argument(message).into());
+        }
+
+        let non_final_count = (cursor.number.0 - final_block.number.0) as usize;
+        let (hashes, _) = storage
+            .blocks_web3_dal()
+            .get_block_hashes_after(final_block.number, non_final_count)
+            .await
+            .context("get_block_hashes_after()")?;
+        let is_cursor_valid = cursor.hash.is_some()
+            && hashes.len() == non_final_count
+            && hashes.last() == cursor.hash.as_ref();
+        if is_cursor_valid {
+            let mut parent_hash = final_block.hash.unwrap_or_default();
+            for (number, hash) in (final_block.number.0 + 1..).zip(hashes) {
+                this.sent_blocks.push_back(BlockRef {
+                    number: MiniblockNumber(number),
+                    hash,
+                    parent_hash,
+                });
+                parent_hash = hash;
+            }
+        } else {
+            // The cursor block was reverted while the client was disconnected. The client may have
+            // applied any non-final blocks up to the cursor, so all of them are undone.
+            for number in (final_block.number.0 + 1..=cursor.number.0).rev() {
+                let number = MiniblockNumber(number);
+                let hash = if number == cursor.number {
+                    cursor.hash
+                } else {
+                    None
+                };
+                let parent = if number - 1 == final_block.number {
+                    final_block
+                } else {
+                    Cursor {
+                        number: number - 1,
+                        hash: None,
+                    }
+                };
+                this.pending_undos.push(undo_response(number, hash, parent));
+            }
+            this.next_block = final_block.number + 1;
+        }
+        Ok(this)
+    }
+
+    async fn run(
+        mut self,
+        sender: &mpsc::Sender<Result<protocol::Response, Status>>,
+    ) -> anyhow::Result<()> {
+        let state = self.state.clone();
+        let mut stop_receiver = state.stop_receiver.clone();
+        for response in mem::take(&mut self.pending_undos) {
+            metrics::increment_counter!("api.firehose.undone_blocks");
+            if sender.send(Ok(response)).await.is_err() {
+                return Ok(()); // The client has disconnected
+            }
+        }
+
+        loop {
+            if *stop_receiver.borrow() {
+                return Ok(());
+            }
+            if matches!(self.stop_block, Some(stop_block) if self.next_block > stop_block) {
+                return Ok(());
+            }
+
+            let mut storage = state.pool.access_storage_tagged("firehose_api").await?;
+            let final_block = last_final_block(&mut storage).await?;
+            self.sent_blocks
+                .retain(|block| block.number > final_block.number);
+            let undos = self.undo_reverted_blocks(&mut storage).await?;
+            if !undos.is_empty() {
+                drop(storage);
+                for response in undos {
+                    metrics::increment_counter!("api.firehose.undone_blocks");
+                    if sender.send(Ok(response)).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                continue;
+            }
+
+            let head = if self.final_blocks_only {
+                final_block.number
+            } else {
+                storage
+                    .blocks_web3_dal()
+                    .get_sealed_miniblock_number()
+                    .await
+                    .context("get_sealed_miniblock_number()")?
+            };
+            if self.next_block > head {
+                drop(storage);
+                tokio::select! {
+                    () = tokio::time::sleep(state.poll_interval) => {}
+                    () = sender.closed() => return Ok(()),
+                    changed = stop_receiver.changed() => {
+                        if changed.is_err() {
+                            // The stop signal sender was dropped; the server is shutting down.
+                            return Ok(());
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let block = load_block(&mut storage, self.next_block, state.chain_id).await?;
+            drop(storage);
+            let block_ref = BlockRef::new(&block);
+            if let Some(last_sent) = self.sent_blocks.back() {
+                if last_sent.number + 1 == block_ref.number
+                    && last_sent.hash != block_ref.parent_hash
+                {
+                    // The last sent block was reverted after the check above; it will be undone
+                    // on the next iteration.
+                    continue;
+                }
+            }
+
+            let step = if self.final_blocks_only {
+                protocol::ForkStep::Final
+            } else {
+                protocol::ForkStep::New
+            };
+            let response = protocol::Response {
+                block: Some(encode_block(&block)),
+                step: step as i32,
+                cursor: Cursor::new(block_ref.number, block_ref.hash).to_string(),
+            };
+            if sender.send(Ok(response)).await.is_err() {
+                return Ok(());
+            }
+            metrics::increment_counter!("api.firehose.streamed_blocks");
+            if block_ref.number > final_block.number {
+                self.sent_blocks.push_back(block_ref);
+            }
+            self.next_block += 1;
+        }
+    }
+
+    /// Checks whether sent non-final blocks are still in the chain. Returns undo responses
+    /// for the reverted blocks (newest first) and rewinds the stream to the first reverted block.
+    async fn undo_reverted_blocks(
+        &mut self,
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<Vec<protocol::Response>> {
+        let Some(first_sent) = self.sent_blocks.front() else {
+            return Ok(vec![]);
+        };
+        let (stored_hashes, _) = storage
+            .blocks_web3_dal()
+            .get_block_hashes_after(first_sent.number - 1, self.sent_blocks.len())
+            .await
+            .context("get_block_hashes_after()")?;
+        let reverted_idx = self
+            .sent_blocks
+            .iter()
+            .enumerate()
+            .position(|(i, block)| stored_hashes.get(i) != Some(&block.hash));
+        let Some(reverted_idx) = reverted_idx else {
+            return Ok(vec![]);
+        };
+
+        let reverted_blocks: Vec<_> = self.sent_blocks.drain(reverted_idx..).collect();
+        tracing::info!(
+            "Miniblocks #{}..=#{} sent to a Firehose client were reverted; undoing them",
+            reverted_blocks[0].number,
+            reverted_blocks[reverted_blocks.len() - 1].number
+        );
+        self.next_block = reverted_blocks[0].number;
+        Ok(reverted_blocks
+            .iter()
+            .rev()
+            .map(|block| {
+                let parent = Cursor::new(block.number - 1, block.parent_hash);
+                undo_response(block.number, Some(block.hash), parent)
+            })
+            .collect())
+    }
+}
+
+/// Loads a sealed miniblock and converts it to a flat block.
+async fn load_block(
+    storage: &mut StorageProcessor<'_>,
+    number: MiniblockNumber,
+    chain_id: L2ChainId,
+) -> anyhow::Result<proto::Block> {
+    let block_id = api::BlockId::Number(api::BlockNumber::Number(number.0.into()));
+    let block = storage
+        .blocks_web3_dal()
+        .get_block_by_web3_block_id(block_id, true, chain_id)
+        .await
+        .context("get_block_by_web3_block_id()")?
+        .with_context(|| format!("miniblock #{number} is not in the database"))?;
+    let mut receipts: HashMap<_, _> = storage
+        .transactions_web3_dal()
+        .get_miniblock_receipts(number)
+        .await
+        .context("get_miniblock_receipts()")?
+        .into_iter()
+        .map(|receipt| (receipt.transaction_hash, receipt))
+        .collect();
+    let mut call_traces: HashMap<_, _> = storage
+        .blocks_web3_dal()
+        .get_call_traces_for_miniblock(number)
+        .await
+        .context("get_call_traces_for_miniblock()")?
+        .into_iter()
+        .collect();
+
+    let mut transaction_traces = Vec::with_capacity(block.transactions.len());
+    let mut block_log_index = 0;
+    for tx in &block.transactions {
+        let api::TransactionVariant::Full(tx) = tx else {
+            anyhow::bail!("miniblock #{number} was loaded without full transactions");
+        };
+        let receipt = receipts
+            .remove(&tx.hash)
+            .with_context(|| format!("receipt for transaction {:?} is missing", tx.hash))?;
+        let call_trace = call_traces.remove(&tx.hash);
+        let mut trace = transaction_trace(tx, &receipt, call_trace.as_ref());
+        for log in &mut trace.logs {
+            log.block_index = block_log_index;
+            block_log_index += 1;
+        }
+        transaction_traces.push(trace);
+    }
+
+    let balance_changes = load_balance_changes(storage, &block, &transaction_traces).await?;
+    let mut system_balance_changes = vec![];
+    for (tx_hash, change) in balance_changes {
+        let trace = transaction_traces
+            .iter_mut()
+            .find(|trace| trace.hash == tx_hash.as_bytes());
+        match trace {
+            Some(trace) => trace.balance_changes.push(change),
+            None => system_balance_changes.push(change),
+        }
+    }
+
+    Ok(proto::Block {
+        number: block.number.as_u64(),
+        hash: block.hash.as_bytes().to_vec(),
+        header: Some(proto::BlockHeader {
+            parent_hash: block.parent_hash.as_bytes().to_vec(),
+            timestamp: block.timestamp.as_u64(),
+            l1_batch_number: block.l1_batch_number.map(|number| number.as_u64()),
+            base_fee_per_gas: u256_bytes(block.base_fee_per_gas),
+            gas_limit: block.gas_limit.low_u64(),
+            gas_used: block.gas_used.low_u64(),
+        }),
+        transaction_traces,
+        system_balance_changes,
+    })
+}
+
+fn transaction_trace(
+    tx: &api::Transaction,
+    receipt: &api::TransactionReceipt,
+    call_trace: Option<&Call>,
+) -> proto::TransactionTrace {
+    let status = match receipt.status.map(|status| status.as_u64()) {
+        Some(1) => proto::TransactionStatus::Succeeded,
+        Some(0) => proto::TransactionStatus::Failed,
+        _ => proto::TransactionStatus::Unknown,
+    };
+    let logs = receipt
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(index, log)| proto::Log {
+            address: log.address.as_bytes().to_vec(),
+            topics: log
+                .topics
+                .iter()
+                .map(|topic| topic.as_bytes().to_vec())
+                .collect(),
+            data: log.data.0.clone(),
+            index: index as u32,
+            block_index: 0, // set by the caller
+        })
+        .collect();
+    let mut calls = vec![];
+    if let Some(call_trace) = call_trace {
+        flatten_calls(call_trace, 0, 0, &mut calls);
+    }
+
+    proto::TransactionTrace {
+        hash: tx.hash.as_bytes().to_vec(),
+        index: receipt.transaction_index.as_u32(),
+        from: receipt.from.as_bytes().to_vec(),
+        to: tx.to.map_or_else(Vec::new, |to| to.as_bytes().to_vec()),
+        nonce: tx.nonce.low_u64(),
+        gas_limit: tx.gas.low_u64(),
+        gas_price: u256_bytes(tx.gas_price.unwrap_or_default()),
+        value: u256_bytes(tx.value),
+        input: tx.input.0.clone(),
+        transaction_type: receipt
+            .transaction_type
+            .map_or(0, |tx_type| tx_type.as_u32()),
+        status: status as i32,
+        gas_used: receipt.gas_used.unwrap_or_default().low_u64(),
+        logs,
+        calls,
+        balance_changes: vec![],
+    }
+}
+
+/// Flattens the call tree in the depth-first order. Near calls are not included in the output;
+/// their subcalls are attached to the closest far call.
+fn flatten_calls(call: &Call, parent_index: u32, depth: u32, output: &mut Vec<proto::Call>) {
+    let call_type = match call.r#type {
+        CallType::NearCall => {
+            for subcall in &call.calls {
+                flatten_calls(subcall, parent_index, depth, output);
+            }
+            return;
+        }
+        CallType::Call(FarCallOpcode::Normal) => proto::CallType::Call,
+        CallType::Call(FarCallOpcode::Delegate) => proto::CallType::Delegate,
+        CallType::Call(FarCallOpcode::Mimic) => proto::CallType::Mimic,
+        CallType::Create => proto::CallType::Create,
+    };
+
+    let index = output.len() as u32 + 1;
+    output.push(proto::Call {
+        index,
+        parent_index,
+        depth,
+        call_type: call_type as i32,
+        caller: call.from.as_bytes().to_vec(),
+        address: call.to.as_bytes().to_vec(),
+        value: u256_bytes(call.value),
+        gas_limit: call.gas.into(),
+        gas_consumed: call.gas_used.into(),
+        input: call.input.clone(),
+        return_data: call.output.clone(),
+        status_failed: call.error.is_some() || call.revert_reason.is_some(),
+        status_reverted: call.revert_reason.is_some(),
+        failure_reason: call
+            .revert_reason
+            .clone()
+            .or_else(|| call.error.clone())
+            .unwrap_or_default(),
+    });
+    for subcall in &call.calls {
+        flatten_calls(subcall, index, depth + 1, output);
+    }
+}
+
+/// Loads ETH balance changes in the miniblock together with the hashes of transactions
+/// that have produced them.
+///
+/// Storage logs only contain hashed storage keys, so balance changes can only be recovered
+/// for known addresses: transaction senders and recipients, call participants, participants
+/// of ETH token events (transfers, mints and withdrawals), the bootloader and the block operator.
+/// Writes to the ETH token storage that cannot be attributed to these addresses (e.g., to the total
+/// supply) are counted in the `api.firehose.unattributed_eth_storage_writes` metric.
+async fn load_balance_changes(
+    storage: &mut StorageProcessor<'_>,
+    block: &api::Block<api::TransactionVariant>,
+    transaction_traces: &[proto::TransactionTrace],
+) -> anyhow::Result<Vec<(H256, proto::BalanceChange)>> {
+    let number = MiniblockNumber(block.number.as_u32());
+    let mut candidates = vec![BOOTLOADER_ADDRESS, block.author];
+    for trace in transaction_traces {
+        candidates.push(Address::from_slice(&trace.from));
+        if !trace.to.is_empty() {
+            candidates.push(Address::from_slice(&trace.to));
+        }
+        for call in &trace.calls {
+            candidates.push(Address::from_slice(&call.caller));
+            candidates.push(Address::from_slice(&call.address));
+        }
+        let eth_token_logs = trace
+            .logs
+            .iter()
+            .filter(|log| log.address == L2_ETH_TOKEN_ADDRESS.as_bytes());
+        for log in eth_token_logs {
+            // All ETH token events have their participants as indexed topics.
+            let participants = log.topics.iter().skip(1).filter(|topic| topic.len() == 32);
+            candidates.extend(participants.map(|topic| Address::from_slice(&topic[12..])));
+        }
+    }
+    let balance_keys: HashMap<_, _> = candidates
+        .into_iter()
+        .map(|address| {
+            let key = storage_key_for_eth_balance(&address);
+            (*key.key(), (address, key.hashed_key()))
+        })
+        .collect();
+
+    let logs = storage
+        .storage_logs_dal()
+        .get_miniblock_logs_for_address(number, L2_ETH_TOKEN_ADDRESS)
+        .await
+        .context("get_miniblock_logs_for_address()")?;
+    let hashed_keys: Vec<_> = logs
+        .iter()
+        .filter_map(|(_, key, _)| balance_keys.get(key))
+        .map(|&(_, hashed_key)| hashed_key)
+        .collect();
+    let mut latest_values = HashMap::new();
+    if number.0 > 0 && !hashed_keys.is_empty() {
+        let previous_values = storage
+            .storage_logs_dal()
+            .try_get_storage_values(&hashed_keys, number - 1)
+            .await
+            .context("try_get_storage_values()")?;
+        latest_values.extend(
+            previous_values
+                .into_iter()
+                .map(|(key, value)| (key, value.unwrap_or_default())),
+        );
+    }
+
+    let mut changes = vec![];
+    let mut unattributed_writes = 0_u64;
+    for (tx_hash, key, value) in logs {
+        let Some(&(address, hashed_key)) = balance_keys.get(&key) else {
+            unattributed_writes += 1;
+            continue;
+        };
+        let old_value = latest_values.insert(hashed_key, value).unwrap_or_default();
+        if old_value == value {
+            continue;
+        }
+        changes.push((
+            tx_hash,
+            proto::BalanceChange {
+                address: address.as_bytes().to_vec(),
+                old_value: u256_bytes(h256_to_u256(old_value)),
+                new_value: u256_bytes(h256_to_u256(value)),
+            },
+        ));
+    }
+    metrics::counter!(
+        "api.firehose.unattributed_eth_storage_writes",
+        unattributed_writes
+    );
+    Ok(changes)
+}
+
+/// Encodes a big integer as big-endian bytes without leading zeros.
+fn u256_bytes(value: U256) -> Vec<u8> {
+    let mut bytes = [0_u8; 32];
+    value.to_big_endian(&mut bytes);
+    let first_nonzero = bytes.iter().position(|&byte| byte != 0).unwrap_or(32);
+    bytes[first_nonzero..].to_vec()
+}
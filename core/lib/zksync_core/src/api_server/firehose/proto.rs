@@ -0,0 +1,157 @@
+//! Protobuf messages of the block stream. The messages follow the Firehose "flat block" model:
+//! each block contains all its transaction traces together with their logs, call trees
+//! (flattened in the depth-first order) and ETH balance changes. The schema is also available
+//! in the `block.proto` file next to this module; the two must be kept in sync.
+//!
+//! Big integers (e.g., values and gas prices) are encoded as big-endian bytes without
+//! leading zeros; addresses and hashes are encoded as raw bytes.
+
+/// Sealed miniblock with all its transaction traces.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Block {
+    #[prost(uint64, tag = "1")]
+    pub number: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub hash: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub header: Option<BlockHeader>,
+    #[prost(message, repeated, tag = "4")]
+    pub transaction_traces: Vec<TransactionTrace>,
+    /// Balance changes not attributed to any transaction in the block (e.g., made by the bootloader).
+    #[prost(message, repeated, tag = "5")]
+    pub system_balance_changes: Vec<BalanceChange>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BlockHeader {
+    #[prost(bytes = "vec", tag = "1")]
+    pub parent_hash: Vec<u8>,
+    /// Block timestamp in seconds.
+    #[prost(uint64, tag = "2")]
+    pub timestamp: u64,
+    /// Number of the L1 batch the block belongs to; not set if the batch is not sealed yet.
+    #[prost(uint64, optional, tag = "3")]
+    pub l1_batch_number: Option<u64>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub base_fee_per_gas: Vec<u8>,
+    #[prost(uint64, tag = "5")]
+    pub gas_limit: u64,
+    #[prost(uint64, tag = "6")]
+    pub gas_used: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum TransactionStatus {
+    Unknown = 0,
+    Succeeded = 1,
+    Failed = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactionTrace {
+    #[prost(bytes = "vec", tag = "1")]
+    pub hash: Vec<u8>,
+    /// Index of the transaction in the block.
+    #[prost(uint32, tag = "2")]
+    pub index: u32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub from: Vec<u8>,
+    /// Empty for contract deployments.
+    #[prost(bytes = "vec", tag = "4")]
+    pub to: Vec<u8>,
+    #[prost(uint64, tag = "5")]
+    pub nonce: u64,
+    #[prost(uint64, tag = "6")]
+    pub gas_limit: u64,
+    #[prost(bytes = "vec", tag = "7")]
+    pub gas_price: Vec<u8>,
+    #[prost(bytes = "vec", tag = "8")]
+    pub value: Vec<u8>,
+    #[prost(bytes = "vec", tag = "9")]
+    pub input: Vec<u8>,
+    #[prost(uint32, tag = "10")]
+    pub transaction_type: u32,
+    #[prost(enumeration = "TransactionStatus", tag = "11")]
+    pub status: i32,
+    #[prost(uint64, tag = "12")]
+    pub gas_used: u64,
+    #[prost(message, repeated, tag = "13")]
+    pub logs: Vec<Log>,
+    /// Calls made during the transaction execution, in the depth-first order.
+    #[prost(message, repeated, tag = "14")]
+    pub calls: Vec<Call>,
+    #[prost(message, repeated, tag = "15")]
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Log {
+    #[prost(bytes = "vec", tag = "1")]
+    pub address: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub topics: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+    /// Index of the log in the transaction.
+    #[prost(uint32, tag = "4")]
+    pub index: u32,
+    /// Index of the log in the block.
+    #[prost(uint32, tag = "5")]
+    pub block_index: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CallType {
+    Unspecified = 0,
+    Call = 1,
+    Delegate = 2,
+    /// zkSync-specific call on behalf of another address made by system contracts.
+    Mimic = 3,
+    Create = 4,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Call {
+    /// 1-based index of the call in the transaction.
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// Index of the parent call; 0 for the root call.
+    #[prost(uint32, tag = "2")]
+    pub parent_index: u32,
+    #[prost(uint32, tag = "3")]
+    pub depth: u32,
+    #[prost(enumeration = "CallType", tag = "4")]
+    pub call_type: i32,
+    #[prost(bytes = "vec", tag = "5")]
+    pub caller: Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub address: Vec<u8>,
+    #[prost(bytes = "vec", tag = "7")]
+    pub value: Vec<u8>,
+    #[prost(uint64, tag = "8")]
+    pub gas_limit: u64,
+    #[prost(uint64, tag = "9")]
+    pub gas_consumed: u64,
+    #[prost(bytes = "vec", tag = "10")]
+    pub input: Vec<u8>,
+    #[prost(bytes = "vec", tag = "11")]
+    pub return_data: Vec<u8>,
+    #[prost(bool, tag = "12")]
+    pub status_failed: bool,
+    #[prost(bool, tag = "13")]
+    pub status_reverted: bool,
+    #[prost(string, tag = "14")]
+    pub failure_reason: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BalanceChange {
+    #[prost(bytes = "vec", tag = "1")]
+    pub address: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub old_value: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub new_value: Vec<u8>,
+}
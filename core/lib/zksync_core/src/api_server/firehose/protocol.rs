@@ -0,0 +1,95 @@
+//! Messages of the `sf.firehose.v2` protocol served by the block stream. The schema is also
+//! available in the `firehose.proto` file next to this module; the two must be kept in sync.
+//!
+//! Blocks are wrapped into [`prost_types::Any`] messages with the [`BLOCK_TYPE_URL`] type URL;
+//! their schema is defined in [`super::proto`].
+
+/// Type URL of [`super::proto::Block`] messages wrapped into `Any`.
+pub const BLOCK_TYPE_URL: &str = "type.googleapis.com/zksync.firehose.v1.Block";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Request {
+    /// Negative values are relative to the last sealed block; -1 is the last sealed block itself.
+    /// Ignored if `cursor` is set.
+    #[prost(int64, tag = "1")]
+    pub start_block_num: i64,
+    #[prost(string, tag = "2")]
+    pub cursor: String,
+    /// Inclusive; 0 means that the stream follows the chain head and never ends.
+    #[prost(uint64, tag = "3")]
+    pub stop_block_num: u64,
+    #[prost(bool, tag = "4")]
+    pub final_blocks_only: bool,
+    /// Not supported; must be empty.
+    #[prost(message, repeated, tag = "10")]
+    pub transforms: Vec<prost_types::Any>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Response {
+    #[prost(message, optional, tag = "1")]
+    pub block: Option<prost_types::Any>,
+    #[prost(enumeration = "ForkStep", tag = "6")]
+    pub step: i32,
+    #[prost(string, tag = "10")]
+    pub cursor: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum ForkStep {
+    Unset = 0,
+    /// Block was added to the chain.
+    New = 1,
+    /// Block was reverted; consumers must roll back its changes.
+    Undo = 2,
+    /// Block is final, i.e., its L1 batch is executed on L1.
+    Final = 3,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SingleBlockRequest {
+    #[prost(oneof = "single_block_request::Reference", tags = "3, 4, 5")]
+    pub reference: Option<single_block_request::Reference>,
+    /// Not supported; must be empty.
+    #[prost(message, repeated, tag = "6")]
+    pub transforms: Vec<prost_types::Any>,
+}
+
+pub mod single_block_request {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BlockNumber {
+        #[prost(uint64, tag = "1")]
+        pub num: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BlockHashAndNumber {
+        #[prost(uint64, tag = "1")]
+        pub num: u64,
+        #[prost(string, tag = "2")]
+        pub hash: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Cursor {
+        #[prost(string, tag = "1")]
+        pub cursor: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Reference {
+        #[prost(message, tag = "3")]
+        BlockNumber(BlockNumber),
+        #[prost(message, tag = "4")]
+        BlockHashAndNumber(BlockHashAndNumber),
+        #[prost(message, tag = "5")]
+        Cursor(Cursor),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SingleBlockResponse {
+    #[prost(message, optional, tag = "1")]
+    pub block: Option<prost_types::Any>,
+}
@@ -0,0 +1,404 @@
+//! Tests for the Firehose block stream.
+
+use db_test_macro::db_test;
+use futures::StreamExt;
+use prost::Message as _;
+
+use zksync_contracts::BaseSystemContracts;
+use zksync_types::{
+    block::{miniblock_hash, MiniblockHeader},
+    commitment::CommitmentSchemes,
+    protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts,
+    L1BatchCommitmentMode, ProtocolVersionId,
+};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+fn call(r#type: CallType, to: Address, calls: Vec<Call>) -> Call {
+    Call {
+        r#type,
+        to,
+        ..Call::new_high_level(1_000, 100, U256::zero(), vec![], vec![], None, calls)
+    }
+}
+
+#[test]
+fn encoding_big_integers() {
+    assert_eq!(u256_bytes(U256::zero()), Vec::<u8>::new());
+    assert_eq!(u256_bytes(U256::from(1)), [1]);
+    assert_eq!(u256_bytes(U256::from(0x1234)), [0x12, 0x34]);
+    assert_eq!(u256_bytes(U256::MAX), [0xff; 32]);
+}
+
+#[test]
+fn flattening_call_tree() {
+    let root = call(
+        CallType::Call(FarCallOpcode::Normal),
+        Address::repeat_byte(1),
+        vec![
+            call(
+                CallType::NearCall,
+                Address::repeat_byte(1),
+                vec![call(
+                    CallType::Call(FarCallOpcode::Mimic),
+                    Address::repeat_byte(2),
+                    vec![],
+                )],
+            ),
+            call(
+                CallType::Create,
+                Address::repeat_byte(3),
+                vec![call(
+                    CallType::Call(FarCallOpcode::Delegate),
+                    Address::repeat_byte(4),
+                    vec![],
+                )],
+            ),
+        ],
+    );
+
+    let mut calls = vec![];
+    flatten_calls(&root, 0, 0, &mut calls);
+    let summary: Vec<_> = calls
+        .iter()
+        .map(|call| {
+            (
+                call.index,
+                call.parent_index,
+                call.depth,
+                call.call_type,
+                call.address[0],
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (1, 0, 0, proto::CallType::Call as i32, 1),
+            (2, 1, 1, proto::CallType::Mimic as i32, 2),
+            (3, 1, 1, proto::CallType::Create as i32, 3),
+            (4, 3, 2, proto::CallType::Delegate as i32, 4),
+        ]
+    );
+}
+
+#[test]
+fn reverted_call_status() {
+    let mut reverted = call(CallType::Create, Address::repeat_byte(1), vec![]);
+    reverted.revert_reason = Some("oops".to_owned());
+    let mut calls = vec![];
+    flatten_calls(&reverted, 0, 0, &mut calls);
+    assert!(calls[0].status_failed);
+    assert!(calls[0].status_reverted);
+    assert_eq!(calls[0].failure_reason, "oops");
+}
+
+#[test]
+fn parsing_cursors() {
+    let hash = H256::repeat_byte(0x42);
+    let cursor = Cursor::new(MiniblockNumber(10), hash);
+    assert_eq!(cursor.to_string().parse::<Cursor>().unwrap(), cursor);
+
+    let hashless_cursor = Cursor {
+        number: MiniblockNumber(10),
+        hash: None,
+    };
+    assert_eq!(hashless_cursor.to_string(), "10");
+    assert_eq!("10".parse::<Cursor>().unwrap(), hashless_cursor);
+
+    assert!("".parse::<Cursor>().is_err());
+    assert!("10:0x42".parse::<Cursor>().is_err());
+    assert!("-1".parse::<Cursor>().is_err());
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+        .await
+        .unwrap();
+}
+
+fn test_miniblock_hash(number: u32, timestamp: u64) -> H256 {
+    miniblock_hash(
+        MiniblockNumber(number),
+        timestamp,
+        H256::zero(),
+        H256::zero(),
+    )
+}
+
+async fn seal_miniblock(pool: &ConnectionPool, number: u32, timestamp: u64) {
+    let miniblock_header = MiniblockHeader {
+        number: MiniblockNumber(number),
+        timestamp,
+        hash: test_miniblock_hash(number, timestamp),
+        l1_tx_count: 0,
+        l2_tx_count: 0,
+        base_fee_per_gas: 100,
+        l1_gas_price: 100,
+        l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
+        base_system_contracts_hashes: BaseSystemContracts::load_from_disk().hashes(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+        virtual_blocks: 1,
+    };
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+}
+
+async fn revert_miniblocks(pool: &ConnectionPool, last_miniblock_to_keep: u32) {
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(last_miniblock_to_keep))
+        .await
+        .unwrap();
+}
+
+async fn genesis_hash(pool: &ConnectionPool) -> H256 {
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_web3_dal()
+        .get_miniblock_hash(MiniblockNumber(0))
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+fn create_state(pool: &ConnectionPool, max_concurrent_streams: usize) -> Arc<FirehoseState> {
+    let (_, stop_receiver) = watch::channel(false);
+    Arc::new(FirehoseState::new(
+        pool.clone(),
+        L2ChainId(270),
+        Duration::from_millis(10),
+        max_concurrent_streams,
+        stop_receiver,
+    ))
+}
+
+/// Summary of a stream response: step, block number, block hash and cursor.
+type ResponseSummary = (protocol::ForkStep, u64, H256, String);
+
+async fn next_response(stream: &mut BlockStream) -> ResponseSummary {
+    let response = tokio::time::timeout(Duration::from_secs(10), stream.next())
+        .await
+        .expect("timed out waiting for a block")
+        .expect("stream ended")
+        .expect("stream failed");
+    let block = response.block.unwrap();
+    assert_eq!(block.type_url, protocol::BLOCK_TYPE_URL);
+    let block = proto::Block::decode(block.value.as_slice()).unwrap();
+    let hash = if block.hash.is_empty() {
+        H256::zero()
+    } else {
+        H256::from_slice(&block.hash)
+    };
+    let step = protocol::ForkStep::try_from(response.step).unwrap();
+    (step, block.number, hash, response.cursor)
+}
+
+fn new_step(number: u32, timestamp: u64) -> ResponseSummary {
+    let hash = test_miniblock_hash(number, timestamp);
+    let cursor = Cursor::new(MiniblockNumber(number), hash).to_string();
+    (protocol::ForkStep::New, number.into(), hash, cursor)
+}
+
+fn stream_request(start_block_num: i64, stop_block_num: u64) -> protocol::Request {
+    protocol::Request {
+        start_block_num,
+        stop_block_num,
+        ..protocol::Request::default()
+    }
+}
+
+#[db_test]
+async fn streaming_blocks_with_undo_after_revert(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    seal_miniblock(&pool, 1, 1).await;
+    seal_miniblock(&pool, 2, 2).await;
+
+    let state = create_state(&pool, 1);
+    let mut stream = state.stream_blocks(stream_request(1, 0)).await.unwrap();
+    assert_eq!(next_response(&mut stream).await, new_step(1, 1));
+    assert_eq!(next_response(&mut stream).await, new_step(2, 2));
+
+    revert_miniblocks(&pool, 1).await;
+    seal_miniblock(&pool, 2, 3).await;
+    let undo = next_response(&mut stream).await;
+    let parent_cursor = Cursor::new(MiniblockNumber(1), test_miniblock_hash(1, 1));
+    assert_eq!(
+        undo,
+        (
+            protocol::ForkStep::Undo,
+            2,
+            test_miniblock_hash(2, 2),
+            parent_cursor.to_string()
+        )
+    );
+    assert_eq!(next_response(&mut stream).await, new_step(2, 3));
+
+    seal_miniblock(&pool, 3, 4).await;
+    assert_eq!(next_response(&mut stream).await, new_step(3, 4));
+}
+
+#[db_test]
+async fn streaming_bounded_block_range(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    for number in 1..=3 {
+        seal_miniblock(&pool, number, number.into()).await;
+    }
+
+    let state = create_state(&pool, 1);
+    let mut stream = state.stream_blocks(stream_request(-2, 3)).await.unwrap();
+    assert_eq!(next_response(&mut stream).await, new_step(2, 2));
+    assert_eq!(next_response(&mut stream).await, new_step(3, 3));
+    let end = tokio::time::timeout(Duration::from_secs(10), stream.next())
+        .await
+        .unwrap();
+    assert!(end.is_none());
+
+    // Only the genesis miniblock is final.
+    let request = protocol::Request {
+        final_blocks_only: true,
+        ..stream_request(0, 0)
+    };
+    let mut stream = state.stream_blocks(request).await.unwrap();
+    let (step, number, ..) = next_response(&mut stream).await;
+    assert_eq!((step, number), (protocol::ForkStep::Final, 0));
+}
+
+#[db_test]
+async fn resuming_stream_from_reverted_cursor(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    seal_miniblock(&pool, 1, 1).await;
+    seal_miniblock(&pool, 2, 3).await;
+    let genesis_hash = genesis_hash(&pool).await;
+
+    // The client has applied miniblock #2 with another hash, which was reverted since then.
+    let stale_cursor = Cursor::new(MiniblockNumber(2), test_miniblock_hash(2, 2));
+    let request = protocol::Request {
+        cursor: stale_cursor.to_string(),
+        ..protocol::Request::default()
+    };
+    let state = create_state(&pool, 1);
+    let mut stream = state.stream_blocks(request).await.unwrap();
+    assert_eq!(
+        next_response(&mut stream).await,
+        (
+            protocol::ForkStep::Undo,
+            2,
+            test_miniblock_hash(2, 2),
+            "1".to_owned()
+        )
+    );
+    let genesis_cursor = Cursor::new(MiniblockNumber(0), genesis_hash).to_string();
+    assert_eq!(
+        next_response(&mut stream).await,
+        (protocol::ForkStep::Undo, 1, H256::zero(), genesis_cursor)
+    );
+    assert_eq!(next_response(&mut stream).await, new_step(1, 1));
+    assert_eq!(next_response(&mut stream).await, new_step(2, 3));
+    drop(stream);
+
+    // Resume from a valid cursor; reverting the resumed blocks must be detected.
+    let (.., cursor) = new_step(1, 1);
+    let request = protocol::Request {
+        cursor,
+        ..protocol::Request::default()
+    };
+    let state = create_state(&pool, 1);
+    let mut stream = state.stream_blocks(request).await.unwrap();
+    assert_eq!(next_response(&mut stream).await, new_step(2, 3));
+    revert_miniblocks(&pool, 0).await;
+    seal_miniblock(&pool, 1, 5).await;
+    let (step, number, ..) = next_response(&mut stream).await;
+    assert_eq!((step, number), (protocol::ForkStep::Undo, 2));
+    let (step, number, hash, cursor) = next_response(&mut stream).await;
+    assert_eq!((step, number), (protocol::ForkStep::Undo, 1));
+    assert_eq!(hash, test_miniblock_hash(1, 1));
+    assert_eq!(
+        cursor,
+        Cursor::new(MiniblockNumber(0), genesis_hash).to_string()
+    );
+    assert_eq!(next_response(&mut stream).await, new_step(1, 5));
+}
+
+#[db_test]
+async fn limiting_concurrent_streams(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let state = create_state(&pool, 1);
+    let stream = state
+        .clone()
+        .stream_blocks(stream_request(1, 0))
+        .await
+        .unwrap();
+    let err = state
+        .clone()
+        .stream_blocks(stream_request(1, 0))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+
+    // The permit is released once the streaming task notices that the client has disconnected.
+    drop(stream);
+    let started_at = tokio::time::Instant::now();
+    loop {
+        match state.clone().stream_blocks(stream_request(1, 0)).await {
+            Ok(_) => break,
+            Err(err) => assert_eq!(err.code(), tonic::Code::ResourceExhausted),
+        }
+        assert!(started_at.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[db_test]
+async fn fetching_single_block(pool: ConnectionPool) {
+    use protocol::single_block_request::{BlockHashAndNumber, BlockNumber, Reference};
+
+    prepare_storage(&pool).await;
+    seal_miniblock(&pool, 1, 1).await;
+    let state = create_state(&pool, 1);
+
+    let request = protocol::SingleBlockRequest {
+        reference: Some(Reference::BlockNumber(BlockNumber { num: 1 })),
+        transforms: vec![],
+    };
+    let response = state.fetch_block(request).await.unwrap();
+    let block = proto::Block::decode(response.block.unwrap().value.as_slice()).unwrap();
+    assert_eq!(block.hash, test_miniblock_hash(1, 1).as_bytes());
+
+    let request = protocol::SingleBlockRequest {
+        reference: Some(Reference::BlockHashAndNumber(BlockHashAndNumber {
+            num: 1,
+            hash: format!("{:?}", test_miniblock_hash(1, 2)),
+        })),
+        transforms: vec![],
+    };
+    let err = to_status(state.fetch_block(request).await.unwrap_err());
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let request = protocol::SingleBlockRequest {
+        reference: Some(Reference::BlockNumber(BlockNumber { num: 2 })),
+        transforms: vec![],
+    };
+    let err = to_status(state.fetch_block(request).await.unwrap_err());
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
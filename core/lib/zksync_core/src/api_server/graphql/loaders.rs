@@ -0,0 +1,90 @@
+//! Data loaders batching the loads made by GraphQL resolvers for each item in a list
+//! (e.g., transactions for each block in a page) into a constant number of DB queries.
+
+use anyhow::Context as _;
+use async_graphql::dataloader::Loader;
+use async_trait::async_trait;
+
+use std::{collections::HashMap, sync::Arc};
+
+use zksync_dal::ConnectionPool;
+use zksync_types::{api, L2ChainId, MiniblockNumber, H256};
+
+/// Loads transactions in miniblocks. Miniblocks without transactions are missing from the output.
+#[derive(Debug)]
+pub(super) struct BlockTransactionsLoader {
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+}
+
+impl BlockTransactionsLoader {
+    pub fn new(pool: ConnectionPool, chain_id: L2ChainId) -> Self {
+        Self { pool, chain_id }
+    }
+}
+
+#[async_trait]
+impl Loader<MiniblockNumber> for BlockTransactionsLoader {
+    type Value = Arc<Vec<api::Transaction>>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        keys: &[MiniblockNumber],
+    ) -> Result<HashMap<MiniblockNumber, Self::Value>, Self::Error> {
+        let mut storage = self.pool.access_storage_tagged("api").await?;
+        let transactions = storage
+            .transactions_web3_dal()
+            .get_miniblocks_transactions(keys, self.chain_id)
+            .await
+            .context("get_miniblocks_transactions()")?;
+
+        let mut transactions_by_block = HashMap::<_, Vec<_>>::new();
+        for tx in transactions {
+            let Some(block_number) = tx.block_number else {
+                continue;
+            };
+            let block_number = MiniblockNumber(block_number.as_u32());
+            transactions_by_block
+                .entry(block_number)
+                .or_default()
+                .push(tx);
+        }
+        Ok(transactions_by_block
+            .into_iter()
+            .map(|(number, transactions)| (number, Arc::new(transactions)))
+            .collect())
+    }
+}
+
+/// Loads receipts of transactions by their hashes. Receipts of non-executed transactions
+/// are missing from the output.
+#[derive(Debug)]
+pub(super) struct ReceiptLoader {
+    pool: ConnectionPool,
+}
+
+impl ReceiptLoader {
+    pub fn new(pool: ConnectionPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Loader<H256> for ReceiptLoader {
+    type Value = api::TransactionReceipt;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[H256]) -> Result<HashMap<H256, Self::Value>, Self::Error> {
+        let mut storage = self.pool.access_storage_tagged("api").await?;
+        let receipts = storage
+            .transactions_web3_dal()
+            .get_transaction_receipts(keys)
+            .await
+            .context("get_transaction_receipts()")?;
+        Ok(receipts
+            .into_iter()
+            .map(|receipt| (receipt.transaction_hash, receipt))
+            .collect())
+    }
+}
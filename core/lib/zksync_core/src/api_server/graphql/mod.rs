@@ -0,0 +1,195 @@
+//! GraphQL API over the chain data (blocks, transactions, logs and token transfers).
+//!
+//! The API is served at `POST /graphql` and is intended for explorers and analytics tools
+//! that need flexible queries: only the requested fields are loaded from Postgres, and list
+//! queries are paginated with opaque cursors. See [`schema`] for the supported queries.
+//! Nested lists (e.g., transactions of each block in a page) are loaded in batches via
+//! the data loaders from [`loaders`]. If configured, requests are rate-limited per client IP address.
+
+use anyhow::Context as _;
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{EmptyMutation, EmptySubscription, Schema};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    routing::post,
+    Json, Router,
+};
+use governor::{
+    clock::DefaultClock, middleware::NoOpMiddleware, state::keyed::DefaultKeyedStateStore, Quota,
+    RateLimiter,
+};
+use tokio::sync::watch;
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU32,
+    sync::Arc,
+    time::Duration,
+};
+
+use zksync_config::configs::api::GraphqlApiConfig;
+use zksync_dal::ConnectionPool;
+use zksync_types::{L2ChainId, MiniblockNumber};
+
+use self::{
+    loaders::{BlockTransactionsLoader, ReceiptLoader},
+    schema::QueryRoot,
+};
+use super::web3::state::SealedMiniblockNumber;
+
+mod loaders;
+pub mod schema;
+#[cfg(test)]
+mod tests;
+
+pub type ChainSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Rate limiter keyed by the client IP address.
+type GraphqlRateLimiter =
+    RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock, NoOpMiddleware>;
+
+/// Interval between removals of rate limiter state for clients without recent requests.
+const RATE_LIMITER_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Data shared by all GraphQL resolvers.
+#[derive(Debug)]
+pub(crate) struct GraphqlState {
+    pub pool: ConnectionPool,
+    pub chain_id: L2ChainId,
+    pub max_page_size: usize,
+    last_sealed_miniblock: SealedMiniblockNumber,
+}
+
+impl GraphqlState {
+    /// Returns the number of the last sealed miniblock. Uses the same cache as the Web3 API,
+    /// so that queries for the latest block don't require an additional DB roundtrip.
+    pub async fn last_sealed_miniblock(&self) -> anyhow::Result<MiniblockNumber> {
+        let cached = self.last_sealed_miniblock.get();
+        if cached.0 > 0 {
+            return Ok(cached);
+        }
+        let mut storage = self.pool.access_storage_tagged("api").await?;
+        let number = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        self.last_sealed_miniblock.diff(number);
+        Ok(number)
+    }
+}
+
+/// Builds the GraphQL schema. The schema can be used to run queries without the HTTP server.
+pub(crate) fn build_schema(config: &GraphqlApiConfig, state: GraphqlState) -> ChainSchema {
+    let transactions_loader = BlockTransactionsLoader::new(state.pool.clone(), state.chain_id);
+    let receipt_loader = ReceiptLoader::new(state.pool.clone());
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(DataLoader::new(transactions_loader, tokio::spawn))
+        .data(DataLoader::new(receipt_loader, tokio::spawn))
+        .data(state)
+        .limit_depth(config.max_query_depth())
+        .limit_complexity(config.max_query_complexity())
+        .finish()
+}
+
+#[derive(Clone)]
+struct ServerState {
+    schema: ChainSchema,
+    rate_limiter: Option<Arc<GraphqlRateLimiter>>,
+}
+
+/// Creates a rate limiter according to the config, or returns `None` if requests are not rate-limited.
+fn create_rate_limiter(
+    config: &GraphqlApiConfig,
+) -> anyhow::Result<Option<Arc<GraphqlRateLimiter>>> {
+    let Some(limit) = config.requests_per_minute_limit else {
+        return Ok(None);
+    };
+    let limit = NonZeroU32::new(limit).context("`requests_per_minute_limit` must be positive")?;
+    Ok(Some(Arc::new(RateLimiter::keyed(Quota::per_minute(limit)))))
+}
+
+async fn execute_query(
+    State(state): State<ServerState>,
+    ConnectInfo(client_address): ConnectInfo<SocketAddr>,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, (StatusCode, &'static str)> {
+    if let Some(rate_limiter) = &state.rate_limiter {
+        if rate_limiter.check_key(&client_address.ip()).is_err() {
+            metrics::increment_counter!("api.graphql.rate_limited");
+            return Err((StatusCode::TOO_MANY_REQUESTS, "rate limit exceeded"));
+        }
+    }
+    let response = state.schema.execute(request).await;
+    let outcome = if response.is_ok() { "ok" } else { "error" };
+    metrics::increment_counter!("api.graphql.requests", "outcome" => outcome);
+    Ok(Json(response))
+}
+
+/// Runs the GraphQL server until a stop signal is received.
+pub async fn run_server(
+    config: GraphqlApiConfig,
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    // Same interval as used by the Web3 API server.
+    const SEALED_MINIBLOCK_UPDATE_INTERVAL: Duration = Duration::from_millis(25);
+
+    anyhow::ensure!(
+        config.max_page_size() > 0,
+        "`max_page_size` must be positive"
+    );
+    let rate_limiter = create_rate_limiter(&config)?;
+    let bind_address = config.bind_addr();
+    tracing::info!("Starting GraphQL server on {bind_address}");
+    let (last_sealed_miniblock, update_task) =
+        SealedMiniblockNumber::new(pool.clone(), SEALED_MINIBLOCK_UPDATE_INTERVAL);
+    // The update tasks takes care of its termination, so we don't need to retain its handle.
+    tokio::spawn(update_task);
+
+    let state = GraphqlState {
+        pool,
+        chain_id,
+        max_page_size: config.max_page_size(),
+        last_sealed_miniblock,
+    };
+    if let Some(rate_limiter) = rate_limiter.clone() {
+        let mut stop_receiver = stop_receiver.clone();
+        tokio::spawn(async move {
+            // Forget clients without recent requests, so that the limiter state doesn't grow indefinitely.
+            loop {
+                tokio::select! {
+                    () = tokio::time::sleep(RATE_LIMITER_CLEANUP_INTERVAL) => {}
+                    _ = stop_receiver.changed() => break,
+                }
+                rate_limiter.retain_recent();
+                rate_limiter.shrink_to_fit();
+            }
+        });
+    }
+    let server_state = ServerState {
+        schema: build_schema(&config, state),
+        rate_limiter,
+    };
+    let app = Router::new()
+        .route("/graphql", post(execute_query))
+        .with_state(server_state);
+
+    axum::Server::try_bind(&bind_address)
+        .with_context(|| format!("failed binding GraphQL server to {bind_address}"))?
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            if stop_receiver.changed().await.is_err() {
+                tracing::warn!(
+                    "Stop signal sender for GraphQL server was dropped without sending a signal"
+                );
+            }
+            tracing::info!("Stop signal received, GraphQL server is shutting down");
+        })
+        .await
+        .context("GraphQL server failed")?;
+    tracing::info!("GraphQL server shut down");
+    Ok(())
+}
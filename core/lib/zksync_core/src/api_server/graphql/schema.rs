@@ -0,0 +1,638 @@
+//! GraphQL schema of the chain data API.
+//!
+//! Hashes, addresses and byte strings are represented as `0x`-prefixed hex strings; big integers
+//! (values, gas amounts and prices) are represented as decimal strings. List queries return
+//! a page of items together with a cursor that should be passed as `after` to get the next page;
+//! the cursor is `null` if there are currently no more items.
+
+use async_graphql::{
+    dataloader::DataLoader, Context, Enum, Error, InputObject, Object, OutputType, SimpleObject,
+};
+
+use std::{fmt, str::FromStr};
+
+use zksync_dal::StorageProcessor;
+use zksync_types::{api, Address, MiniblockNumber, H256, U64};
+use zksync_utils::address_to_h256;
+
+use super::{
+    loaders::{BlockTransactionsLoader, ReceiptLoader},
+    GraphqlState,
+};
+use crate::api_server::token_transfers::{Erc20Transfer, TRANSFER_EVENT_TOPIC};
+
+fn internal_error(method: &'static str, err: impl fmt::Display) -> Error {
+    tracing::error!("Internal error in GraphQL resolver `{method}`: {err:#}");
+    metrics::increment_counter!("api.graphql.internal_errors", "method" => method);
+    Error::new("internal error")
+}
+
+fn parse_hex<T: FromStr>(name: &str, value: &str) -> async_graphql::Result<T> {
+    value
+        .parse()
+        .map_err(|_| Error::new(format!("`{name}` is not a valid hex string: {value}")))
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+async fn access_storage<'a>(
+    state: &'a GraphqlState,
+    method: &'static str,
+) -> async_graphql::Result<StorageProcessor<'a>> {
+    state
+        .pool
+        .access_storage_tagged("api")
+        .await
+        .map_err(|err| internal_error(method, err))
+}
+
+fn page_size(state: &GraphqlState, first: Option<u32>) -> async_graphql::Result<usize> {
+    let first = first.map_or(state.max_page_size, |first| first as usize);
+    if first == 0 || first > state.max_page_size {
+        return Err(Error::new(format!(
+            "`first` must be in 1..={}",
+            state.max_page_size
+        )));
+    }
+    Ok(first)
+}
+
+/// Page of items returned by list queries.
+#[derive(Debug, SimpleObject)]
+#[graphql(concrete(name = "BlockPage", params(Block)))]
+#[graphql(concrete(name = "LogPage", params(Log)))]
+#[graphql(concrete(name = "TokenTransferPage", params(TokenTransfer)))]
+pub struct Page<T: OutputType> {
+    pub items: Vec<T>,
+    /// Cursor to pass as `after` to get the next page; `null` if there are no more items.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Miniblock; transactions are only loaded if requested.
+#[derive(Debug)]
+pub struct Block(api::Block<api::TransactionVariant>);
+
+#[Object]
+impl Block {
+    async fn number(&self) -> u32 {
+        self.0.number.as_u32()
+    }
+
+    async fn hash(&self) -> String {
+        format!("{:?}", self.0.hash)
+    }
+
+    async fn parent_hash(&self) -> String {
+        format!("{:?}", self.0.parent_hash)
+    }
+
+    /// Block timestamp in seconds.
+    async fn timestamp(&self) -> u64 {
+        self.0.timestamp.as_u64()
+    }
+
+    /// Number of the L1 batch the block belongs to; `null` if the batch is not sealed yet.
+    async fn l1_batch_number(&self) -> Option<u32> {
+        self.0.l1_batch_number.map(|number| number.as_u32())
+    }
+
+    async fn gas_used(&self) -> String {
+        self.0.gas_used.to_string()
+    }
+
+    async fn gas_limit(&self) -> String {
+        self.0.gas_limit.to_string()
+    }
+
+    async fn base_fee_per_gas(&self) -> String {
+        self.0.base_fee_per_gas.to_string()
+    }
+
+    async fn transaction_count(&self) -> u32 {
+        self.0.transactions.len() as u32
+    }
+
+    /// Transactions in the block in the execution order.
+    #[graphql(complexity = "10 * child_complexity")]
+    async fn transactions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Transaction>> {
+        const METHOD: &str = "Block.transactions";
+
+        let loader = ctx.data_unchecked::<DataLoader<BlockTransactionsLoader>>();
+        let number = MiniblockNumber(self.0.number.as_u32());
+        let transactions = loader
+            .load_one(number)
+            .await
+            .map_err(|err| internal_error(METHOD, err))?
+            .unwrap_or_default();
+        let transactions = transactions
+            .iter()
+            // The block may have been reverted and replaced after it was loaded.
+            .filter(|tx| tx.block_hash == Some(self.0.hash))
+            .cloned()
+            .map(Transaction);
+        Ok(transactions.collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct Transaction(api::Transaction);
+
+#[Object]
+impl Transaction {
+    async fn hash(&self) -> String {
+        format!("{:?}", self.0.hash)
+    }
+
+    async fn nonce(&self) -> String {
+        self.0.nonce.to_string()
+    }
+
+    async fn block_number(&self) -> Option<u32> {
+        self.0.block_number.map(|number| number.as_u32())
+    }
+
+    async fn block_hash(&self) -> Option<String> {
+        self.0.block_hash.map(|hash| format!("{hash:?}"))
+    }
+
+    /// Index of the transaction in the block.
+    async fn index(&self) -> Option<u32> {
+        self.0.transaction_index.map(|index| index.as_u32())
+    }
+
+    async fn from(&self) -> Option<String> {
+        self.0.from.map(|address| format!("{address:?}"))
+    }
+
+    /// Recipient of the transaction; `null` for contract deployments.
+    async fn to(&self) -> Option<String> {
+        self.0.to.map(|address| format!("{address:?}"))
+    }
+
+    async fn value(&self) -> String {
+        self.0.value.to_string()
+    }
+
+    async fn gas_price(&self) -> Option<String> {
+        self.0.gas_price.map(|price| price.to_string())
+    }
+
+    async fn gas_limit(&self) -> String {
+        self.0.gas.to_string()
+    }
+
+    async fn input(&self) -> String {
+        format_bytes(&self.0.input.0)
+    }
+
+    async fn transaction_type(&self) -> Option<u32> {
+        self.0.transaction_type.map(|tx_type| tx_type.as_u32())
+    }
+
+    /// Receipt of the transaction; `null` if the transaction is not executed yet.
+    #[graphql(complexity = "5 + child_complexity")]
+    async fn receipt(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Receipt>> {
+        const METHOD: &str = "Transaction.receipt";
+
+        let loader = ctx.data_unchecked::<DataLoader<ReceiptLoader>>();
+        let receipt = loader
+            .load_one(self.0.hash)
+            .await
+            .map_err(|err| internal_error(METHOD, err))?;
+        Ok(receipt.map(Receipt))
+    }
+}
+
+#[derive(Debug)]
+pub struct Receipt(api::TransactionReceipt);
+
+#[Object]
+impl Receipt {
+    /// Execution status: 1 for success, 0 for failure.
+    async fn status(&self) -> Option<u32> {
+        self.0.status.map(|status| status.as_u32())
+    }
+
+    async fn gas_used(&self) -> Option<String> {
+        self.0.gas_used.map(|gas| gas.to_string())
+    }
+
+    async fn cumulative_gas_used(&self) -> String {
+        self.0.cumulative_gas_used.to_string()
+    }
+
+    async fn effective_gas_price(&self) -> Option<String> {
+        self.0.effective_gas_price.map(|price| price.to_string())
+    }
+
+    /// Address of the deployed contract, if the transaction is a contract deployment.
+    async fn contract_address(&self) -> Option<String> {
+        self.0
+            .contract_address
+            .map(|address| format!("{address:?}"))
+    }
+
+    async fn logs(&self) -> Vec<Log> {
+        self.0.logs.iter().cloned().map(Log).collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Log(api::Log);
+
+#[Object]
+impl Log {
+    async fn address(&self) -> String {
+        format!("{:?}", self.0.address)
+    }
+
+    async fn topics(&self) -> Vec<String> {
+        self.0
+            .topics
+            .iter()
+            .map(|topic| format!("{topic:?}"))
+            .collect()
+    }
+
+    async fn data(&self) -> String {
+        format_bytes(&self.0.data.0)
+    }
+
+    async fn block_number(&self) -> Option<u32> {
+        self.0.block_number.map(|number| number.as_u32())
+    }
+
+    async fn block_hash(&self) -> Option<String> {
+        self.0.block_hash.map(|hash| format!("{hash:?}"))
+    }
+
+    async fn transaction_hash(&self) -> Option<String> {
+        self.0.transaction_hash.map(|hash| format!("{hash:?}"))
+    }
+
+    async fn transaction_index(&self) -> Option<u32> {
+        self.0.transaction_index.map(|index| index.as_u32())
+    }
+
+    /// Index of the log in the block.
+    async fn log_index(&self) -> Option<u32> {
+        self.0.log_index.map(|index| index.as_u32())
+    }
+}
+
+/// Transfer of an ERC-20 token (including the base token) decoded from a `Transfer` event.
+#[derive(Debug, SimpleObject)]
+pub struct TokenTransfer {
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub block_number: u32,
+    pub transaction_hash: String,
+    /// Index of the `Transfer` event in the block.
+    pub log_index: u32,
+}
+
+impl TokenTransfer {
+    /// Decodes a transfer together with its position in the chain.
+    pub(super) fn from_log(log: &api::Log) -> Option<Self> {
+        let transfer = Erc20Transfer::from_log(log)?;
+        Some(Self {
+            token: format!("{:?}", transfer.token),
+            from: format!("{:?}", transfer.from),
+            to: format!("{:?}", transfer.to),
+            amount: transfer.amount.to_string(),
+            block_number: log.block_number?.as_u32(),
+            transaction_hash: format!("{:?}", log.transaction_hash?),
+            log_index: log.log_index?.as_u32(),
+        })
+    }
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct LogFilter {
+    pub from_block: Option<u32>,
+    pub to_block: Option<u32>,
+    /// Emitter addresses; if not specified, logs from all addresses are returned.
+    pub addresses: Option<Vec<String>>,
+    /// Topics by position; `null` matches any topic at the corresponding position.
+    pub topics: Option<Vec<Option<Vec<String>>>>,
+}
+
+#[derive(Debug, Default, InputObject)]
+pub struct TokenTransferFilter {
+    pub from_block: Option<u32>,
+    pub to_block: Option<u32>,
+    /// Token address; if not specified, transfers of all tokens are returned.
+    pub token: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Position of a log in the chain used as a pagination cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct LogCursor {
+    block: u32,
+    index: u32,
+}
+
+impl LogCursor {
+    fn for_log(log: &api::Log) -> Option<Self> {
+        Some(Self {
+            block: log.block_number?.as_u32(),
+            index: log.log_index?.as_u32(),
+        })
+    }
+}
+
+impl fmt::Display for LogCursor {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}:{}", self.block, self.index)
+    }
+}
+
+impl FromStr for LogCursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || {
+            let (block, index) = s.split_once(':')?;
+            Some(Self {
+                block: block.parse().ok()?,
+                index: index.parse().ok()?,
+            })
+        };
+        parse().ok_or_else(|| Error::new(format!("invalid cursor: {s}")))
+    }
+}
+
+fn block_range_filter(from_block: Option<u32>, to_block: Option<u32>) -> api::GetLogsFilter {
+    api::GetLogsFilter {
+        from_block: MiniblockNumber(from_block.unwrap_or(0)),
+        to_block: to_block.map(|number| api::BlockNumber::Number(U64::from(number))),
+        addresses: vec![],
+        topics: vec![],
+    }
+}
+
+/// Loads a page of logs matching the filter, starting after the cursor. Returns the logs
+/// and the cursor for the next page.
+async fn load_logs_page(
+    state: &GraphqlState,
+    method: &'static str,
+    mut filter: api::GetLogsFilter,
+    after: Option<String>,
+    first: usize,
+) -> async_graphql::Result<(Vec<api::Log>, Option<String>)> {
+    let after: Option<LogCursor> = after.as_deref().map(str::parse).transpose()?;
+    // Logs in the cursor block up to and including the cursor position are fetched and discarded;
+    // there's at most `cursor.index + 1` of them.
+    let mut skipped_count = 0;
+    if let Some(after) = after {
+        if after.block >= filter.from_block.0 {
+            filter.from_block = MiniblockNumber(after.block);
+            skipped_count = after.index as usize + 1;
+        }
+    }
+
+    let mut storage = access_storage(state, method).await?;
+    let logs = storage
+        .events_web3_dal()
+        .get_logs(filter, skipped_count + first + 1)
+        .await
+        .map_err(|err| internal_error(method, err))?;
+    let mut logs: Vec<_> = logs
+        .into_iter()
+        .filter(|log| after.map_or(true, |after| LogCursor::for_log(log) > Some(after)))
+        .collect();
+
+    let has_more = logs.len() > first;
+    logs.truncate(first);
+    let next_cursor = if has_more {
+        logs.last()
+            .and_then(LogCursor::for_log)
+            .map(|cursor| cursor.to_string())
+    } else {
+        None
+    };
+    Ok((logs, next_cursor))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Number of the last sealed block.
+    async fn latest_block_number(&self, ctx: &Context<'_>) -> async_graphql::Result<u32> {
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let number = state
+            .last_sealed_miniblock()
+            .await
+            .map_err(|err| internal_error("latestBlockNumber", err))?;
+        Ok(number.0)
+    }
+
+    /// Returns a block by its number or hash. If neither is specified, returns the latest block.
+    async fn block(
+        &self,
+        ctx: &Context<'_>,
+        number: Option<u32>,
+        hash: Option<String>,
+    ) -> async_graphql::Result<Option<Block>> {
+        const METHOD: &str = "block";
+
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let block_id = match (number, hash) {
+            (Some(_), Some(_)) => {
+                return Err(Error::new(
+                    "only one of `number` and `hash` may be specified",
+                ));
+            }
+            (Some(number), None) => api::BlockId::Number(api::BlockNumber::Number(number.into())),
+            (None, Some(hash)) => api::BlockId::Hash(parse_hex("hash", &hash)?),
+            (None, None) => {
+                let number = state
+                    .last_sealed_miniblock()
+                    .await
+                    .map_err(|err| internal_error(METHOD, err))?;
+                api::BlockId::Number(api::BlockNumber::Number(number.0.into()))
+            }
+        };
+
+        let mut storage = access_storage(state, METHOD).await?;
+        let block = storage
+            .blocks_web3_dal()
+            .get_block_by_web3_block_id(block_id, false, state.chain_id)
+            .await
+            .map_err(|err| internal_error(METHOD, err))?;
+        Ok(block.map(Block))
+    }
+
+    /// Returns a page of blocks. By default, blocks are returned starting from the latest one.
+    #[graphql(complexity = "first.unwrap_or(100) as usize * child_complexity")]
+    async fn blocks(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<u32>,
+        #[graphql(default_with = "SortOrder::Desc")] order: SortOrder,
+    ) -> async_graphql::Result<Page<Block>> {
+        const METHOD: &str = "blocks";
+
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let first = page_size(state, first)?;
+        let after = after
+            .map(|cursor| {
+                cursor
+                    .parse::<u32>()
+                    .map_err(|_| Error::new(format!("invalid cursor: {cursor}")))
+            })
+            .transpose()?;
+        let latest = state
+            .last_sealed_miniblock()
+            .await
+            .map_err(|err| internal_error(METHOD, err))?
+            .0;
+
+        let (numbers, next_cursor): (Vec<u32>, _) = match order {
+            SortOrder::Asc => {
+                let start = after.map_or(0, |after| after.saturating_add(1));
+                if start > latest {
+                    (vec![], None)
+                } else {
+                    let end = latest.min(start.saturating_add(first as u32 - 1));
+                    let next_cursor = (end < latest).then(|| end.to_string());
+                    ((start..=end).collect(), next_cursor)
+                }
+            }
+            SortOrder::Desc => {
+                let start = match after {
+                    Some(0) => None,
+                    Some(after) => Some(after.min(latest + 1) - 1),
+                    None => Some(latest),
+                };
+                if let Some(start) = start {
+                    let end = start.saturating_sub(first as u32 - 1);
+                    let next_cursor = (end > 0).then(|| end.to_string());
+                    ((end..=start).rev().collect(), next_cursor)
+                } else {
+                    (vec![], None)
+                }
+            }
+        };
+
+        let mut storage = access_storage(state, METHOD).await?;
+        let mut items = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let block_id = api::BlockId::Number(api::BlockNumber::Number(number.into()));
+            let block = storage
+                .blocks_web3_dal()
+                .get_block_by_web3_block_id(block_id, false, state.chain_id)
+                .await
+                .map_err(|err| internal_error(METHOD, err))?;
+            // The block may be missing if it was reverted after the latest block number was read.
+            items.extend(block.map(Block));
+        }
+        Ok(Page { items, next_cursor })
+    }
+
+    /// Returns a transaction by its hash.
+    async fn transaction(
+        &self,
+        ctx: &Context<'_>,
+        hash: String,
+    ) -> async_graphql::Result<Option<Transaction>> {
+        const METHOD: &str = "transaction";
+
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let hash: H256 = parse_hex("hash", &hash)?;
+        let mut storage = access_storage(state, METHOD).await?;
+        let tx = storage
+            .transactions_web3_dal()
+            .get_transaction(api::TransactionId::Hash(hash), state.chain_id)
+            .await
+            .map_err(|err| internal_error(METHOD, err))?;
+        Ok(tx.map(Transaction))
+    }
+
+    /// Returns a page of logs matching the filter in the chain order.
+    #[graphql(complexity = "first.unwrap_or(100) as usize * child_complexity")]
+    async fn logs(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: LogFilter,
+        after: Option<String>,
+        first: Option<u32>,
+    ) -> async_graphql::Result<Page<Log>> {
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let first = page_size(state, first)?;
+        let mut logs_filter = block_range_filter(filter.from_block, filter.to_block);
+        for address in filter.addresses.unwrap_or_default() {
+            logs_filter
+                .addresses
+                .push(parse_hex::<Address>("addresses", &address)?);
+        }
+        let topics = filter.topics.unwrap_or_default();
+        if topics.len() > 4 {
+            return Err(Error::new("at most 4 topics may be specified"));
+        }
+        for (idx, topics) in topics.into_iter().enumerate() {
+            if let Some(topics) = topics {
+                let topics = topics
+                    .iter()
+                    .map(|topic| parse_hex::<H256>("topics", topic))
+                    .collect::<Result<_, _>>()?;
+                logs_filter.topics.push((idx as u32 + 1, topics));
+            }
+        }
+
+        let (logs, next_cursor) = load_logs_page(state, "logs", logs_filter, after, first).await?;
+        Ok(Page {
+            items: logs.into_iter().map(Log).collect(),
+            next_cursor,
+        })
+    }
+
+    /// Returns a page of ERC-20 token transfers matching the filter in the chain order.
+    /// Pages are formed from `Transfer` events, so a page may contain fewer than `first` items
+    /// if some events are not ERC-20 transfers (e.g., ERC-721 ones).
+    #[graphql(complexity = "first.unwrap_or(100) as usize * child_complexity")]
+    async fn token_transfers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: TokenTransferFilter,
+        after: Option<String>,
+        first: Option<u32>,
+    ) -> async_graphql::Result<Page<TokenTransfer>> {
+        let state = ctx.data_unchecked::<GraphqlState>();
+        let first = page_size(state, first)?;
+        let mut logs_filter = block_range_filter(filter.from_block, filter.to_block);
+        if let Some(token) = &filter.token {
+            logs_filter.addresses.push(parse_hex("token", token)?);
+        }
+        logs_filter.topics.push((1, vec![*TRANSFER_EVENT_TOPIC]));
+        if let Some(from) = &filter.from {
+            let from: Address = parse_hex("from", from)?;
+            logs_filter.topics.push((2, vec![address_to_h256(&from)]));
+        }
+        if let Some(to) = &filter.to {
+            let to: Address = parse_hex("to", to)?;
+            logs_filter.topics.push((3, vec![address_to_h256(&to)]));
+        }
+
+        let (logs, next_cursor) =
+            load_logs_page(state, "tokenTransfers", logs_filter, after, first).await?;
+        Ok(Page {
+            items: logs.iter().filter_map(TokenTransfer::from_log).collect(),
+            next_cursor,
+        })
+    }
+}
@@ -0,0 +1,264 @@
+//! Tests for the GraphQL API.
+
+use db_test_macro::db_test;
+
+use zksync_contracts::BaseSystemContracts;
+use zksync_types::{
+    api,
+    block::{miniblock_hash, MiniblockHeader},
+    commitment::CommitmentSchemes,
+    fee::{Fee, TransactionExecutionMetrics},
+    l2::L2Tx,
+    protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts,
+    transaction_request::PaymasterParams,
+    tx::{tx_execution_info::TxExecutionStatus, ExecutionMetrics, TransactionExecutionResult},
+    Address, Bytes, L1BatchCommitmentMode, Nonce, ProtocolVersionId, H256, U256,
+};
+use zksync_utils::address_to_h256;
+
+use super::{schema::*, *};
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+fn mock_config() -> GraphqlApiConfig {
+    GraphqlApiConfig {
+        port: 3073,
+        requests_per_minute_limit: None,
+        max_page_size: Some(2),
+        max_query_depth: None,
+        max_query_complexity: None,
+    }
+}
+
+async fn create_schema(pool: &ConnectionPool) -> ChainSchema {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+        .await
+        .unwrap();
+
+    let config = mock_config();
+    // The update task is not spawned, so the latest block number is always loaded from Postgres.
+    let (last_sealed_miniblock, _) =
+        SealedMiniblockNumber::new(pool.clone(), Duration::from_secs(60));
+    let state = GraphqlState {
+        pool: pool.clone(),
+        chain_id: L2ChainId(270),
+        max_page_size: config.max_page_size(),
+        last_sealed_miniblock,
+    };
+    build_schema(&config, state)
+}
+
+#[test]
+fn schema_is_valid() {
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription).finish();
+    let sdl = schema.sdl();
+    assert!(sdl.contains("type BlockPage"), "{sdl}");
+    assert!(sdl.contains("tokenTransfers("), "{sdl}");
+}
+
+#[test]
+fn decoding_token_transfer() {
+    let token = Address::repeat_byte(0x10);
+    let from = Address::repeat_byte(0x01);
+    let to = Address::repeat_byte(0x02);
+    let mut amount = [0_u8; 32];
+    U256::from(1_000).to_big_endian(&mut amount);
+    let mut log = api::Log {
+        address: token,
+        topics: vec![
+            H256(zksync_types::web3::signing::keccak256(
+                b"Transfer(address,address,uint256)",
+            )),
+            address_to_h256(&from),
+            address_to_h256(&to),
+        ],
+        data: Bytes(amount.to_vec()),
+        block_hash: Some(H256::zero()),
+        block_number: Some(5.into()),
+        l1_batch_number: None,
+        transaction_hash: Some(H256::repeat_byte(0xaa)),
+        transaction_index: Some(0.into()),
+        log_index: Some(3.into()),
+        transaction_log_index: Some(3.into()),
+        log_type: None,
+        removed: Some(false),
+    };
+
+    let transfer = TokenTransfer::from_log(&log).unwrap();
+    assert_eq!(transfer.token, format!("{token:?}"));
+    assert_eq!(transfer.from, format!("{from:?}"));
+    assert_eq!(transfer.to, format!("{to:?}"));
+    assert_eq!(transfer.amount, "1000");
+    assert_eq!(transfer.block_number, 5);
+    assert_eq!(transfer.log_index, 3);
+
+    // ERC-721 transfers have the same signature, but the token ID is indexed.
+    log.topics.push(H256::from_low_u64_be(1));
+    log.data = Bytes(vec![]);
+    assert!(TokenTransfer::from_log(&log).is_none());
+}
+
+#[db_test]
+async fn querying_genesis_block(pool: ConnectionPool) {
+    let schema = create_schema(&pool).await;
+    let response = schema
+        .execute("{ latestBlockNumber block(number: 0) { number transactionCount } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["latestBlockNumber"], 0);
+    assert_eq!(data["block"]["number"], 0);
+    assert_eq!(data["block"]["transactionCount"], 0);
+
+    let response = schema.execute("{ block(number: 1) { hash } }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert!(response.data.into_json().unwrap()["block"].is_null());
+}
+
+#[db_test]
+async fn paginating_blocks(pool: ConnectionPool) {
+    let schema = create_schema(&pool).await;
+    let response = schema
+        .execute("{ blocks(order: ASC) { items { number } nextCursor } }")
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    assert_eq!(data["blocks"]["items"][0]["number"], 0);
+    assert!(data["blocks"]["nextCursor"].is_null());
+
+    // Page size is limited by the config.
+    let response = schema
+        .execute("{ blocks(first: 3) { items { number } } }")
+        .await;
+    assert_eq!(response.errors.len(), 1);
+}
+
+#[test]
+fn limiting_requests_per_client() {
+    let config = GraphqlApiConfig {
+        requests_per_minute_limit: Some(1),
+        ..mock_config()
+    };
+    let rate_limiter = create_rate_limiter(&config).unwrap().unwrap();
+    let first_client = IpAddr::from([10, 0, 0, 1]);
+    let second_client = IpAddr::from([10, 0, 0, 2]);
+    rate_limiter.check_key(&first_client).unwrap();
+    rate_limiter.check_key(&first_client).unwrap_err();
+    rate_limiter.check_key(&second_client).unwrap();
+
+    let config = GraphqlApiConfig {
+        requests_per_minute_limit: Some(0),
+        ..mock_config()
+    };
+    create_rate_limiter(&config).unwrap_err();
+}
+
+fn create_l2_transaction(nonce: u32) -> L2Tx {
+    let fee = Fee {
+        gas_limit: 1_000_u64.into(),
+        max_fee_per_gas: 100_u64.into(),
+        max_priority_fee_per_gas: 0_u64.into(),
+        gas_per_pubdata_limit: 800_u64.into(),
+    };
+    L2Tx::new_signed(
+        Address::repeat_byte(0x20),
+        vec![],
+        Nonce(nonce),
+        fee,
+        U256::zero(),
+        L2ChainId(270),
+        &H256::repeat_byte(0x11),
+        None,
+        PaymasterParams::default(),
+    )
+    .unwrap()
+}
+
+/// Seals miniblock #1 with the specified transactions.
+async fn seal_miniblock_with_transactions(pool: &ConnectionPool, transactions: Vec<L2Tx>) {
+    let mut storage = pool.access_storage().await.unwrap();
+    for tx in &transactions {
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+    }
+    let miniblock_header = MiniblockHeader {
+        number: MiniblockNumber(1),
+        timestamp: 1,
+        hash: miniblock_hash(MiniblockNumber(1), 1, H256::zero(), H256::zero()),
+        l1_tx_count: 0,
+        l2_tx_count: transactions.len() as u16,
+        base_fee_per_gas: 100,
+        l1_gas_price: 100,
+        l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
+        base_system_contracts_hashes: BaseSystemContracts::load_from_disk().hashes(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+        virtual_blocks: 1,
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+
+    let tx_results: Vec<_> = transactions
+        .into_iter()
+        .map(|tx| TransactionExecutionResult {
+            hash: tx.hash(),
+            transaction: tx.into(),
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            refunded_gas: 0,
+            operator_suggested_refund: 0,
+            compressed_bytecodes: vec![],
+            call_traces: vec![],
+            revert_reason: None,
+        })
+        .collect();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, U256::from(100))
+        .await;
+}
+
+#[db_test]
+async fn querying_block_transactions_with_receipts(pool: ConnectionPool) {
+    let schema = create_schema(&pool).await;
+    let transactions = vec![create_l2_transaction(0), create_l2_transaction(1)];
+    let tx_hashes: Vec<_> = transactions
+        .iter()
+        .map(|tx| format!("{:?}", tx.hash()))
+        .collect();
+    seal_miniblock_with_transactions(&pool, transactions).await;
+
+    let response = schema
+        .execute(
+            "{ blocks(order: ASC) { items { number transactions { hash index receipt { status } } } } }",
+        )
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    let data = response.data.into_json().unwrap();
+    let blocks = data["blocks"]["items"].as_array().unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0]["transactions"].as_array().unwrap().len(), 0);
+    let transactions = blocks[1]["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), 2);
+    for (i, (tx, expected_hash)) in transactions.iter().zip(&tx_hashes).enumerate() {
+        assert_eq!(tx["hash"], *expected_hash);
+        assert_eq!(tx["index"], i);
+        assert_eq!(tx["receipt"]["status"], 1);
+    }
+}
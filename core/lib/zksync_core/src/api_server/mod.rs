@@ -1,6 +1,10 @@
 // Everywhere in this module the word "block" actually means "miniblock".
 pub mod contract_verification;
 pub mod execution_sandbox;
+pub mod firehose;
+pub mod graphql;
 pub mod healthcheck;
+pub mod rosetta;
+mod token_transfers;
 pub mod tx_sender;
 pub mod web3;
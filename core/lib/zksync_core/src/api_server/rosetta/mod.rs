@@ -0,0 +1,666 @@
+//! [Rosetta](https://www.rosetta-api.org/) Data API for exchange integrations.
+//!
+//! The server implements the `/network/*`, `/block`, `/block/transaction` and `/account/balance`
+//! endpoints. Blocks correspond to miniblocks. Balance-changing operations are derived from
+//! token events emitted by transactions:
+//!
+//! - ERC-20 `Transfer` events (including base token transfers and fee payments) produce
+//!   a pair of `TRANSFER` operations debiting the sender and crediting the recipient.
+//! - ERC-20 `Transfer` events from the zero address and base token `Mint` events (e.g., for deposits)
+//!   produce `MINT` operations crediting the recipient.
+//! - ERC-20 `Transfer` events to the zero address and base token `Withdrawal` events
+//!   produce `BURN` operations debiting the sender.
+//!
+//! Reverted execution frames don't emit events, so all returned operations are successful.
+
+use anyhow::Context as _;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use zksync_config::configs::api::RosettaApiConfig;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::{
+    api,
+    tokens::{TokenMetadata, ETHEREUM_ADDRESS},
+    utils::storage_key_for_standard_token_balance,
+    web3::signing::keccak256,
+    AccountTreeId, Address, L2ChainId, MiniblockNumber, H256, L2_ETH_TOKEN_ADDRESS, U256,
+};
+use zksync_utils::{h256_to_account_address, h256_to_u256};
+
+use self::types::*;
+use super::token_transfers::Erc20Transfer;
+
+#[cfg(test)]
+mod tests;
+pub mod types;
+
+const BLOCKCHAIN: &str = "zksync-era";
+const ROSETTA_VERSION: &str = "1.4.13";
+const STATUS_SUCCESS: &str = "SUCCESS";
+const OPERATION_TRANSFER: &str = "TRANSFER";
+const OPERATION_MINT: &str = "MINT";
+const OPERATION_BURN: &str = "BURN";
+
+/// Topic of the `Mint(address,uint256)` event emitted by the `L2EthToken` system contract.
+static MINT_EVENT_TOPIC: Lazy<H256> = Lazy::new(|| H256(keccak256(b"Mint(address,uint256)")));
+/// Topic of the `Withdrawal(address,address,uint256)` event emitted by the `L2EthToken` system contract.
+static WITHDRAWAL_EVENT_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256(keccak256(b"Withdrawal(address,address,uint256)")));
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("network is not supported")]
+    NetworkNotSupported,
+    #[error("block not found")]
+    BlockNotFound,
+    #[error("transaction not found")]
+    TransactionNotFound,
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("internal error")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl ApiError {
+    const ALL_CODES: [(u32, &'static str, bool); 5] = [
+        (1, "network is not supported", false),
+        (2, "block not found", true),
+        (3, "transaction not found", true),
+        (4, "invalid request", false),
+        (5, "internal error", true),
+    ];
+
+    fn code(&self) -> u32 {
+        match self {
+            Self::NetworkNotSupported => 1,
+            Self::BlockNotFound => 2,
+            Self::TransactionNotFound => 3,
+            Self::InvalidRequest(_) => 4,
+            Self::Internal(_) => 5,
+        }
+    }
+
+    fn all() -> Vec<Error> {
+        Self::ALL_CODES
+            .iter()
+            .map(|&(code, message, retriable)| Error {
+                code,
+                message: message.to_owned(),
+                retriable,
+            })
+            .collect()
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Self::Internal(err) = &self {
+            tracing::error!("Internal error in Rosetta API: {err:#}");
+        }
+        let code = self.code();
+        metrics::increment_counter!("api.rosetta.errors", "code" => code.to_string());
+        let retriable = Self::ALL_CODES
+            .iter()
+            .any(|&(known_code, _, retriable)| known_code == code && retriable);
+        let error = Error {
+            code,
+            message: self.to_string(),
+            retriable,
+        };
+        // Rosetta requires all errors to be returned with the 500 status code.
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response()
+    }
+}
+
+fn parse_hex<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError::InvalidRequest(format!("`{name}` is not a valid hex string")))
+}
+
+/// Change of a token balance caused by an event.
+#[derive(Debug, Clone, PartialEq)]
+enum BalanceEvent {
+    Transfer(Erc20Transfer),
+    Mint {
+        token: Address,
+        account: Address,
+        amount: U256,
+    },
+    Burn {
+        token: Address,
+        account: Address,
+        amount: U256,
+    },
+}
+
+impl BalanceEvent {
+    fn from_log(log: &api::Log) -> Option<Self> {
+        if let Some(transfer) = Erc20Transfer::from_log(log) {
+            // ERC-20 tokens signal mints and burns with transfers from / to the zero address;
+            // the zero address never holds the balance, so it must not be debited or credited.
+            return match (transfer.from.is_zero(), transfer.to.is_zero()) {
+                (false, false) => Some(Self::Transfer(transfer)),
+                (true, false) => Some(Self::Mint {
+                    token: transfer.token,
+                    account: transfer.to,
+                    amount: transfer.amount,
+                }),
+                (false, true) => Some(Self::Burn {
+                    token: transfer.token,
+                    account: transfer.from,
+                    amount: transfer.amount,
+                }),
+                (true, true) => None,
+            };
+        }
+        if log.address != L2_ETH_TOKEN_ADDRESS || log.data.0.len() != 32 {
+            return None;
+        }
+        let amount = U256::from_big_endian(&log.data.0);
+        match log.topics.as_slice() {
+            [topic, account] if *topic == *MINT_EVENT_TOPIC => Some(Self::Mint {
+                token: L2_ETH_TOKEN_ADDRESS,
+                account: h256_to_account_address(account),
+                amount,
+            }),
+            [topic, sender, _] if *topic == *WITHDRAWAL_EVENT_TOPIC => Some(Self::Burn {
+                token: L2_ETH_TOKEN_ADDRESS,
+                account: h256_to_account_address(sender),
+                amount,
+            }),
+            _ => None,
+        }
+    }
+
+    fn token(&self) -> Address {
+        match self {
+            Self::Transfer(transfer) => transfer.token,
+            Self::Mint { token, .. } | Self::Burn { token, .. } => *token,
+        }
+    }
+}
+
+fn amount(value: U256, negative: bool, currency: &Currency) -> Amount {
+    let sign = if negative && !value.is_zero() {
+        "-"
+    } else {
+        ""
+    };
+    Amount {
+        value: format!("{sign}{value}"),
+        currency: currency.clone(),
+    }
+}
+
+/// Converts balance events to Rosetta operations. Events for tokens without a known currency
+/// are skipped.
+fn build_operations(
+    events: &[BalanceEvent],
+    currencies: &HashMap<Address, Currency>,
+) -> Vec<Operation> {
+    let mut operations = vec![];
+    for event in events {
+        let Some(currency) = currencies.get(&event.token()) else {
+            continue;
+        };
+        let index = operations.len() as u64;
+        match event {
+            BalanceEvent::Transfer(transfer) => {
+                operations.push(Operation {
+                    operation_identifier: OperationIdentifier { index },
+                    related_operations: vec![],
+                    operation_type: OPERATION_TRANSFER,
+                    status: STATUS_SUCCESS,
+                    account: AccountIdentifier {
+                        address: format!("{:?}", transfer.from),
+                    },
+                    amount: amount(transfer.amount, true, currency),
+                });
+                operations.push(Operation {
+                    operation_identifier: OperationIdentifier { index: index + 1 },
+                    related_operations: vec![OperationIdentifier { index }],
+                    operation_type: OPERATION_TRANSFER,
+                    status: STATUS_SUCCESS,
+                    account: AccountIdentifier {
+                        address: format!("{:?}", transfer.to),
+                    },
+                    amount: amount(transfer.amount, false, currency),
+                });
+            }
+            BalanceEvent::Mint {
+                account,
+                amount: value,
+                ..
+            }
+            | BalanceEvent::Burn {
+                account,
+                amount: value,
+                ..
+            } => {
+                let is_burn = matches!(event, BalanceEvent::Burn { .. });
+                operations.push(Operation {
+                    operation_identifier: OperationIdentifier { index },
+                    related_operations: vec![],
+                    operation_type: if is_burn {
+                        OPERATION_BURN
+                    } else {
+                        OPERATION_MINT
+                    },
+                    status: STATUS_SUCCESS,
+                    account: AccountIdentifier {
+                        address: format!("{account:?}"),
+                    },
+                    amount: amount(*value, is_burn, currency),
+                });
+            }
+        }
+    }
+    operations
+}
+
+#[derive(Debug)]
+struct RosettaState {
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    network: NetworkIdentifier,
+}
+
+impl RosettaState {
+    fn check_network(&self, network: &NetworkIdentifier) -> Result<(), ApiError> {
+        if *network == self.network {
+            Ok(())
+        } else {
+            Err(ApiError::NetworkNotSupported)
+        }
+    }
+
+    async fn access_storage(&self) -> Result<StorageProcessor<'_>, ApiError> {
+        Ok(self.pool.access_storage_tagged("api").await?)
+    }
+
+    async fn block_identifier(
+        storage: &mut StorageProcessor<'_>,
+        number: MiniblockNumber,
+    ) -> Result<BlockIdentifier, ApiError> {
+        let hash = storage
+            .blocks_web3_dal()
+            .get_miniblock_hash(number)
+            .await
+            .context("get_miniblock_hash()")?
+            .ok_or(ApiError::BlockNotFound)?;
+        Ok(BlockIdentifier {
+            index: number.0.into(),
+            hash: format!("{hash:?}"),
+        })
+    }
+
+    /// Resolves a partial block identifier to a miniblock number. If neither the index
+    /// nor the hash is specified, resolves to the latest sealed miniblock.
+    async fn resolve_block(
+        storage: &mut StorageProcessor<'_>,
+        block: &PartialBlockIdentifier,
+    ) -> Result<MiniblockNumber, ApiError> {
+        let block_id = match (block.index, &block.hash) {
+            (Some(index), _) => {
+                let index = u32::try_from(index).map_err(|_| ApiError::BlockNotFound)?;
+                api::BlockId::Number(api::BlockNumber::Number(index.into()))
+            }
+            (None, Some(hash)) => api::BlockId::Hash(parse_hex("block_identifier.hash", hash)?),
+            (None, None) => api::BlockId::Number(api::BlockNumber::Latest),
+        };
+        let number = storage
+            .blocks_web3_dal()
+            .resolve_block_id(block_id)
+            .await
+            .context("resolve_block_id()")?
+            .ok_or(ApiError::BlockNotFound)?;
+        if let (Some(_), Some(hash)) = (block.index, &block.hash) {
+            let expected_hash = Self::block_identifier(storage, number).await?.hash;
+            if !expected_hash.eq_ignore_ascii_case(hash) {
+                return Err(ApiError::BlockNotFound);
+            }
+        }
+        Ok(number)
+    }
+
+    /// Loads currencies for the specified token addresses. Tokens missing from the tokens table
+    /// get a default currency derived from their address.
+    async fn load_currencies(
+        storage: &mut StorageProcessor<'_>,
+        tokens: HashSet<Address>,
+    ) -> Result<HashMap<Address, Currency>, ApiError> {
+        // Base token metadata is stored under the L1 ETH address.
+        let metadata_address = |token: Address| {
+            if token == L2_ETH_TOKEN_ADDRESS {
+                ETHEREUM_ADDRESS
+            } else {
+                token
+            }
+        };
+        let metadata_addresses: Vec<_> = tokens.iter().copied().map(metadata_address).collect();
+        let mut tokens_metadata = storage
+            .tokens_web3_dal()
+            .get_tokens_metadata(&metadata_addresses)
+            .await
+            .context("get_tokens_metadata()")?;
+
+        let mut currencies = HashMap::with_capacity(tokens.len());
+        for token in tokens {
+            let metadata = (token != L2_ETH_TOKEN_ADDRESS).then(|| CurrencyMetadata {
+                token_address: format!("{token:?}"),
+            });
+            let token_metadata = tokens_metadata
+                .remove(&metadata_address(token))
+                .unwrap_or_else(|| TokenMetadata::default(token));
+            currencies.insert(
+                token,
+                Currency {
+                    symbol: token_metadata.symbol,
+                    decimals: token_metadata.decimals,
+                    metadata,
+                },
+            );
+        }
+        Ok(currencies)
+    }
+
+    /// Converts receipts to Rosetta transactions, loading currencies for all of them at once.
+    async fn build_transactions(
+        storage: &mut StorageProcessor<'_>,
+        receipts: &[api::TransactionReceipt],
+    ) -> Result<Vec<Transaction>, ApiError> {
+        let events: Vec<Vec<_>> = receipts
+            .iter()
+            .map(|receipt| {
+                receipt
+                    .logs
+                    .iter()
+                    .filter_map(BalanceEvent::from_log)
+                    .collect()
+            })
+            .collect();
+        let tokens = events.iter().flatten().map(BalanceEvent::token).collect();
+        let currencies = Self::load_currencies(storage, tokens).await?;
+
+        let transactions = receipts
+            .iter()
+            .zip(&events)
+            .map(|(receipt, events)| Transaction {
+                transaction_identifier: TransactionIdentifier {
+                    hash: format!("{:?}", receipt.transaction_hash),
+                },
+                operations: build_operations(events, &currencies),
+            });
+        Ok(transactions.collect())
+    }
+
+    async fn load_transaction(
+        storage: &mut StorageProcessor<'_>,
+        tx_hash: H256,
+    ) -> Result<Option<(MiniblockNumber, Transaction)>, ApiError> {
+        let Some(receipt) = storage
+            .transactions_web3_dal()
+            .get_transaction_receipt(tx_hash)
+            .await
+            .context("get_transaction_receipt()")?
+        else {
+            return Ok(None);
+        };
+        let Some(block_number) = receipt.block_number else {
+            return Ok(None); // The transaction is not executed yet
+        };
+
+        let mut transactions =
+            Self::build_transactions(storage, std::slice::from_ref(&receipt)).await?;
+        let transaction = transactions.pop().context("no transaction built")?;
+        Ok(Some((MiniblockNumber(block_number.as_u32()), transaction)))
+    }
+}
+
+async fn network_list(State(state): State<Arc<RosettaState>>) -> Json<NetworkListResponse> {
+    Json(NetworkListResponse {
+        network_identifiers: vec![state.network.clone()],
+    })
+}
+
+async fn network_options(
+    State(state): State<Arc<RosettaState>>,
+    Json(request): Json<NetworkRequest>,
+) -> Result<Json<NetworkOptionsResponse>, ApiError> {
+    state.check_network(&request.network_identifier)?;
+    Ok(Json(NetworkOptionsResponse {
+        version: Version {
+            rosetta_version: ROSETTA_VERSION,
+            node_version: env!("CARGO_PKG_VERSION"),
+        },
+        allow: Allow {
+            operation_statuses: vec![OperationStatus {
+                status: STATUS_SUCCESS,
+                successful: true,
+            }],
+            operation_types: vec![OPERATION_TRANSFER, OPERATION_MINT, OPERATION_BURN],
+            errors: ApiError::all(),
+            historical_balance_lookup: true,
+        },
+    }))
+}
+
+async fn network_status(
+    State(state): State<Arc<RosettaState>>,
+    Json(request): Json<NetworkRequest>,
+) -> Result<Json<NetworkStatusResponse>, ApiError> {
+    state.check_network(&request.network_identifier)?;
+    let mut storage = state.access_storage().await?;
+    let latest = storage
+        .blocks_web3_dal()
+        .get_sealed_miniblock_number()
+        .await
+        .context("get_sealed_miniblock_number()")?;
+    let latest_block = storage
+        .blocks_web3_dal()
+        .get_block_by_web3_block_id(
+            api::BlockId::Number(api::BlockNumber::Number(latest.0.into())),
+            false,
+            state.chain_id,
+        )
+        .await
+        .context("get_block_by_web3_block_id()")?
+        .ok_or(ApiError::BlockNotFound)?;
+    let genesis_block_identifier =
+        RosettaState::block_identifier(&mut storage, MiniblockNumber(0)).await?;
+
+    Ok(Json(NetworkStatusResponse {
+        current_block_identifier: BlockIdentifier {
+            index: latest.0.into(),
+            hash: format!("{:?}", latest_block.hash),
+        },
+        current_block_timestamp: latest_block.timestamp.as_u64() * 1_000,
+        genesis_block_identifier,
+        peers: vec![],
+    }))
+}
+
+async fn block(
+    State(state): State<Arc<RosettaState>>,
+    Json(request): Json<BlockRequest>,
+) -> Result<Json<BlockResponse>, ApiError> {
+    state.check_network(&request.network_identifier)?;
+    let mut storage = state.access_storage().await?;
+    let number = RosettaState::resolve_block(&mut storage, &request.block_identifier).await?;
+    let block = storage
+        .blocks_web3_dal()
+        .get_block_by_web3_block_id(
+            api::BlockId::Number(api::BlockNumber::Number(number.0.into())),
+            false,
+            state.chain_id,
+        )
+        .await
+        .context("get_block_by_web3_block_id()")?
+        .ok_or(ApiError::BlockNotFound)?;
+
+    let receipts = storage
+        .transactions_web3_dal()
+        .get_miniblock_receipts(number)
+        .await
+        .context("get_miniblock_receipts()")?;
+    if receipts.len() != block.transactions.len() {
+        let err = anyhow::anyhow!(
+            "miniblock #{number} has {} transactions, but {} receipts",
+            block.transactions.len(),
+            receipts.len()
+        );
+        return Err(err.into());
+    }
+    let transactions = RosettaState::build_transactions(&mut storage, &receipts).await?;
+
+    let block_identifier = BlockIdentifier {
+        index: number.0.into(),
+        hash: format!("{:?}", block.hash),
+    };
+    let parent_block_identifier = if number.0 == 0 {
+        block_identifier.clone() // Rosetta convention for the genesis block
+    } else {
+        BlockIdentifier {
+            index: (number.0 - 1).into(),
+            hash: format!("{:?}", block.parent_hash),
+        }
+    };
+    Ok(Json(BlockResponse {
+        block: Block {
+            block_identifier,
+            parent_block_identifier,
+            timestamp: block.timestamp.as_u64() * 1_000,
+            transactions,
+        },
+    }))
+}
+
+async fn block_transaction(
+    State(state): State<Arc<RosettaState>>,
+    Json(request): Json<BlockTransactionRequest>,
+) -> Result<Json<BlockTransactionResponse>, ApiError> {
+    state.check_network(&request.network_identifier)?;
+    let tx_hash = parse_hex(
+        "transaction_identifier.hash",
+        &request.transaction_identifier.hash,
+    )?;
+    let mut storage = state.access_storage().await?;
+    let (block_number, transaction) = RosettaState::load_transaction(&mut storage, tx_hash)
+        .await?
+        .ok_or(ApiError::TransactionNotFound)?;
+    if u64::from(block_number.0) != request.block_identifier.index {
+        return Err(ApiError::TransactionNotFound);
+    }
+    Ok(Json(BlockTransactionResponse { transaction }))
+}
+
+async fn account_balance(
+    State(state): State<Arc<RosettaState>>,
+    Json(request): Json<AccountBalanceRequest>,
+) -> Result<Json<AccountBalanceResponse>, ApiError> {
+    state.check_network(&request.network_identifier)?;
+    let account: Address = parse_hex(
+        "account_identifier.address",
+        &request.account_identifier.address,
+    )?;
+    let mut storage = state.access_storage().await?;
+    let block = request.block_identifier.unwrap_or_default();
+    let number = RosettaState::resolve_block(&mut storage, &block).await?;
+    let block_identifier = RosettaState::block_identifier(&mut storage, number).await?;
+
+    let tokens: Vec<Address> = match &request.currencies {
+        None => vec![L2_ETH_TOKEN_ADDRESS],
+        Some(currencies) => currencies
+            .iter()
+            .map(|currency| match &currency.metadata {
+                Some(metadata) => {
+                    parse_hex("currency.metadata.token_address", &metadata.token_address)
+                }
+                None => Ok(L2_ETH_TOKEN_ADDRESS),
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    let currencies =
+        RosettaState::load_currencies(&mut storage, tokens.iter().copied().collect()).await?;
+
+    let balance_keys: Vec<_> = tokens
+        .iter()
+        .map(|&token| {
+            storage_key_for_standard_token_balance(AccountTreeId::new(token), &account).hashed_key()
+        })
+        .collect();
+    let values = storage
+        .storage_logs_dal()
+        .try_get_storage_values(&balance_keys, number)
+        .await
+        .context("try_get_storage_values()")?;
+    let balances = tokens
+        .iter()
+        .zip(&balance_keys)
+        .map(|(token, key)| {
+            let balance = values.get(key).copied().flatten().unwrap_or_default();
+            amount(h256_to_u256(balance), false, &currencies[token])
+        })
+        .collect();
+    Ok(Json(AccountBalanceResponse {
+        block_identifier,
+        balances,
+    }))
+}
+
+/// Runs the Rosetta Data API server until a stop signal is received.
+pub async fn run_server(
+    config: RosettaApiConfig,
+    pool: ConnectionPool,
+    chain_id: L2ChainId,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let bind_address = config.bind_addr();
+    tracing::info!("Starting Rosetta API server on {bind_address}");
+    let state = Arc::new(RosettaState {
+        pool,
+        chain_id,
+        network: NetworkIdentifier {
+            blockchain: BLOCKCHAIN.to_owned(),
+            network: chain_id.0.to_string(),
+        },
+    });
+    let app = Router::new()
+        .route("/network/list", post(network_list))
+        .route("/network/options", post(network_options))
+        .route("/network/status", post(network_status))
+        .route("/block", post(block))
+        .route("/block/transaction", post(block_transaction))
+        .route("/account/balance", post(account_balance))
+        .with_state(state);
+
+    axum::Server::try_bind(&bind_address)
+        .with_context(|| format!("failed binding Rosetta API server to {bind_address}"))?
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(async move {
+            if stop_receiver.changed().await.is_err() {
+                tracing::warn!(
+                    "Stop signal sender for Rosetta API server was dropped without sending a signal"
+                );
+            }
+            tracing::info!("Stop signal received, Rosetta API server is shutting down");
+        })
+        .await
+        .context("Rosetta API server failed")?;
+    tracing::info!("Rosetta API server shut down");
+    Ok(())
+}
@@ -0,0 +1,488 @@
+//! Tests for the Rosetta Data API.
+
+use db_test_macro::db_test;
+
+use zksync_contracts::BaseSystemContracts;
+use zksync_types::{
+    block::{miniblock_hash, MiniblockHeader},
+    commitment::CommitmentSchemes,
+    fee::{Fee, TransactionExecutionMetrics},
+    l2::L2Tx,
+    protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts,
+    transaction_request::PaymasterParams,
+    tx::{
+        tx_execution_info::TxExecutionStatus, ExecutionMetrics, IncludedTxLocation,
+        TransactionExecutionResult,
+    },
+    Bytes, L1BatchCommitmentMode, L1BatchNumber, Nonce, ProtocolVersionId, VmEvent,
+};
+use zksync_utils::address_to_h256;
+
+use super::*;
+use crate::{
+    api_server::token_transfers::TRANSFER_EVENT_TOPIC,
+    genesis::{ensure_genesis_state, GenesisParams},
+};
+
+fn mock_log(address: Address, topics: Vec<H256>, amount: u64) -> api::Log {
+    let mut data = [0_u8; 32];
+    U256::from(amount).to_big_endian(&mut data);
+    api::Log {
+        address,
+        topics,
+        data: Bytes(data.to_vec()),
+        block_hash: None,
+        block_number: None,
+        l1_batch_number: None,
+        transaction_hash: None,
+        transaction_index: None,
+        log_index: None,
+        transaction_log_index: None,
+        log_type: None,
+        removed: None,
+    }
+}
+
+fn eth_currency() -> Currency {
+    Currency {
+        symbol: "ETH".to_owned(),
+        decimals: 18,
+        metadata: None,
+    }
+}
+
+#[test]
+fn decoding_balance_events() {
+    let alice = Address::repeat_byte(1);
+    let bob = Address::repeat_byte(2);
+    let transfer_log = mock_log(
+        L2_ETH_TOKEN_ADDRESS,
+        vec![
+            *TRANSFER_EVENT_TOPIC,
+            address_to_h256(&alice),
+            address_to_h256(&bob),
+        ],
+        100,
+    );
+    let mint_log = mock_log(
+        L2_ETH_TOKEN_ADDRESS,
+        vec![*MINT_EVENT_TOPIC, address_to_h256(&alice)],
+        1_000,
+    );
+    let withdrawal_log = mock_log(
+        L2_ETH_TOKEN_ADDRESS,
+        vec![
+            *WITHDRAWAL_EVENT_TOPIC,
+            address_to_h256(&bob),
+            address_to_h256(&Address::repeat_byte(3)),
+        ],
+        50,
+    );
+    // `Mint` events from other contracts are ignored.
+    let foreign_mint_log = mock_log(
+        Address::repeat_byte(0x10),
+        vec![*MINT_EVENT_TOPIC, address_to_h256(&alice)],
+        1_000,
+    );
+
+    let events: Vec<_> = [transfer_log, mint_log, withdrawal_log, foreign_mint_log]
+        .iter()
+        .filter_map(BalanceEvent::from_log)
+        .collect();
+    assert_eq!(events.len(), 3);
+
+    let currencies = HashMap::from([(L2_ETH_TOKEN_ADDRESS, eth_currency())]);
+    let operations = build_operations(&events, &currencies);
+    let summary: Vec<_> = operations
+        .iter()
+        .map(|op| {
+            (
+                op.operation_identifier.index,
+                op.operation_type,
+                op.account.address.clone(),
+                op.amount.value.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (0, OPERATION_TRANSFER, format!("{alice:?}"), "-100"),
+            (1, OPERATION_TRANSFER, format!("{bob:?}"), "100"),
+            (2, OPERATION_MINT, format!("{alice:?}"), "1000"),
+            (3, OPERATION_BURN, format!("{bob:?}"), "-50"),
+        ]
+    );
+    assert_eq!(
+        operations[1].related_operations,
+        [OperationIdentifier { index: 0 }]
+    );
+
+    // Operations for tokens without a currency are skipped.
+    assert!(build_operations(&events, &HashMap::new()).is_empty());
+}
+
+#[test]
+fn decoding_erc20_mints_and_burns() {
+    let token = Address::repeat_byte(0x10);
+    let alice = Address::repeat_byte(1);
+    let transfer_log = |from: Address, to: Address, amount| {
+        mock_log(
+            token,
+            vec![
+                *TRANSFER_EVENT_TOPIC,
+                address_to_h256(&from),
+                address_to_h256(&to),
+            ],
+            amount,
+        )
+    };
+    let logs = [
+        transfer_log(Address::zero(), alice, 1_000),
+        transfer_log(alice, Address::zero(), 300),
+        transfer_log(Address::zero(), Address::zero(), 1),
+    ];
+    let events: Vec<_> = logs.iter().filter_map(BalanceEvent::from_log).collect();
+    assert_eq!(
+        events,
+        [
+            BalanceEvent::Mint {
+                token,
+                account: alice,
+                amount: 1_000.into(),
+            },
+            BalanceEvent::Burn {
+                token,
+                account: alice,
+                amount: 300.into(),
+            },
+        ]
+    );
+
+    let currency = Currency {
+        symbol: "TEST".to_owned(),
+        decimals: 6,
+        metadata: Some(CurrencyMetadata {
+            token_address: format!("{token:?}"),
+        }),
+    };
+    let currencies = HashMap::from([(token, currency)]);
+    let operations = build_operations(&events, &currencies);
+    let summary: Vec<_> = operations
+        .iter()
+        .map(|op| {
+            (
+                op.operation_type,
+                op.account.address.clone(),
+                op.amount.value.as_str(),
+            )
+        })
+        .collect();
+    // The zero address must not be debited or credited.
+    assert_eq!(
+        summary,
+        [
+            (OPERATION_MINT, format!("{alice:?}"), "1000"),
+            (OPERATION_BURN, format!("{alice:?}"), "-300"),
+        ]
+    );
+}
+
+async fn create_state(pool: &ConnectionPool) -> Arc<RosettaState> {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+        .await
+        .unwrap();
+
+    Arc::new(RosettaState {
+        pool: pool.clone(),
+        chain_id: L2ChainId(270),
+        network: NetworkIdentifier {
+            blockchain: BLOCKCHAIN.to_owned(),
+            network: "270".to_owned(),
+        },
+    })
+}
+
+#[db_test]
+async fn network_status_and_genesis_block(pool: ConnectionPool) {
+    let state = create_state(&pool).await;
+    let network = state.network.clone();
+
+    let Json(status) = network_status(
+        State(state.clone()),
+        Json(NetworkRequest {
+            network_identifier: network.clone(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(status.current_block_identifier.index, 0);
+    assert_eq!(
+        status.current_block_identifier,
+        status.genesis_block_identifier
+    );
+
+    let Json(response) = block(
+        State(state.clone()),
+        Json(BlockRequest {
+            network_identifier: network.clone(),
+            block_identifier: PartialBlockIdentifier {
+                index: Some(0),
+                hash: None,
+            },
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response.block.block_identifier,
+        status.genesis_block_identifier
+    );
+    assert_eq!(
+        response.block.parent_block_identifier,
+        response.block.block_identifier
+    );
+
+    let err = block(
+        State(state.clone()),
+        Json(BlockRequest {
+            network_identifier: network,
+            block_identifier: PartialBlockIdentifier {
+                index: Some(1),
+                hash: None,
+            },
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::BlockNotFound), "{err:?}");
+
+    let other_network = NetworkIdentifier {
+        blockchain: BLOCKCHAIN.to_owned(),
+        network: "1".to_owned(),
+    };
+    let err = network_status(
+        State(state),
+        Json(NetworkRequest {
+            network_identifier: other_network,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ApiError::NetworkNotSupported), "{err:?}");
+}
+
+fn create_l2_transaction(nonce: u32) -> L2Tx {
+    let fee = Fee {
+        gas_limit: 1_000_u64.into(),
+        max_fee_per_gas: 100_u64.into(),
+        max_priority_fee_per_gas: 0_u64.into(),
+        gas_per_pubdata_limit: 800_u64.into(),
+    };
+    L2Tx::new_signed(
+        Address::repeat_byte(0x20),
+        vec![],
+        Nonce(nonce),
+        fee,
+        U256::zero(),
+        L2ChainId(270),
+        &H256::repeat_byte(0x11),
+        None,
+        PaymasterParams::default(),
+    )
+    .unwrap()
+}
+
+fn transfer_event(token: Address, from: Address, to: Address, amount: u64) -> VmEvent {
+    let mut value = [0_u8; 32];
+    U256::from(amount).to_big_endian(&mut value);
+    VmEvent {
+        location: (L1BatchNumber(1), 0),
+        address: token,
+        indexed_topics: vec![
+            *TRANSFER_EVENT_TOPIC,
+            address_to_h256(&from),
+            address_to_h256(&to),
+        ],
+        value: value.to_vec(),
+    }
+}
+
+/// Seals miniblock #1 with the specified transactions, each emitting the specified events.
+async fn seal_miniblock_with_events(
+    pool: &ConnectionPool,
+    transactions: Vec<(L2Tx, Vec<VmEvent>)>,
+) {
+    let mut storage = pool.access_storage().await.unwrap();
+    for (tx, _) in &transactions {
+        storage
+            .transactions_dal()
+            .insert_transaction_l2(tx.clone(), TransactionExecutionMetrics::default())
+            .await;
+    }
+    let miniblock_header = MiniblockHeader {
+        number: MiniblockNumber(1),
+        timestamp: 1,
+        hash: miniblock_hash(MiniblockNumber(1), 1, H256::zero(), H256::zero()),
+        l1_tx_count: 0,
+        l2_tx_count: transactions.len() as u16,
+        base_fee_per_gas: 100,
+        l1_gas_price: 100,
+        l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
+        base_system_contracts_hashes: BaseSystemContracts::load_from_disk().hashes(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+        virtual_blocks: 1,
+    };
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+
+    let events: Vec<_> = transactions
+        .iter()
+        .enumerate()
+        .map(|(i, (tx, events))| {
+            let location = IncludedTxLocation {
+                tx_hash: tx.hash(),
+                tx_index_in_miniblock: i as u32,
+                tx_initiator_address: tx.initiator_account(),
+            };
+            (location, events.iter().collect::<Vec<_>>())
+        })
+        .collect();
+    storage
+        .events_dal()
+        .save_events(MiniblockNumber(1), &events)
+        .await;
+
+    let tx_results: Vec<_> = transactions
+        .iter()
+        .map(|(tx, _)| TransactionExecutionResult {
+            hash: tx.hash(),
+            transaction: tx.clone().into(),
+            execution_info: ExecutionMetrics::default(),
+            execution_status: TxExecutionStatus::Success,
+            refunded_gas: 0,
+            operator_suggested_refund: 0,
+            compressed_bytecodes: vec![],
+            call_traces: vec![],
+            revert_reason: None,
+        })
+        .collect();
+    storage
+        .transactions_dal()
+        .mark_txs_as_executed_in_miniblock(MiniblockNumber(1), &tx_results, U256::from(100))
+        .await;
+}
+
+#[db_test]
+async fn block_with_mint_and_transfer(pool: ConnectionPool) {
+    let state = create_state(&pool).await;
+    let token = Address::repeat_byte(0x10);
+    let alice = Address::repeat_byte(1);
+    let bob = Address::repeat_byte(2);
+    let mint_tx = create_l2_transaction(0);
+    let mint_tx_hash = mint_tx.hash();
+    let mint = transfer_event(token, Address::zero(), alice, 1_000);
+    let transfer_tx = create_l2_transaction(1);
+    let transfer = transfer_event(L2_ETH_TOKEN_ADDRESS, alice, bob, 100);
+    seal_miniblock_with_events(
+        &pool,
+        vec![(mint_tx, vec![mint]), (transfer_tx, vec![transfer])],
+    )
+    .await;
+
+    let Json(response) = block(
+        State(state.clone()),
+        Json(BlockRequest {
+            network_identifier: state.network.clone(),
+            block_identifier: PartialBlockIdentifier {
+                index: Some(1),
+                hash: None,
+            },
+        }),
+    )
+    .await
+    .unwrap();
+    let transactions = &response.block.transactions;
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(
+        transactions[0].transaction_identifier.hash,
+        format!("{mint_tx_hash:?}")
+    );
+
+    let mint_operations = &transactions[0].operations;
+    assert_eq!(mint_operations.len(), 1);
+    assert_eq!(mint_operations[0].operation_type, OPERATION_MINT);
+    assert_eq!(mint_operations[0].account.address, format!("{alice:?}"));
+    assert_eq!(mint_operations[0].amount.value, "1000");
+    // The token is missing from the tokens table, so it gets a default currency.
+    let expected_metadata = CurrencyMetadata {
+        token_address: format!("{token:?}"),
+    };
+    assert_eq!(
+        mint_operations[0].amount.currency.metadata,
+        Some(expected_metadata)
+    );
+
+    let transfer_operations = &transactions[1].operations;
+    assert_eq!(transfer_operations.len(), 2);
+    assert_eq!(transfer_operations[0].account.address, format!("{alice:?}"));
+    assert_eq!(transfer_operations[0].amount.value, "-100");
+    assert_eq!(transfer_operations[0].amount.currency, eth_currency());
+    assert_eq!(transfer_operations[1].account.address, format!("{bob:?}"));
+    assert_eq!(transfer_operations[1].amount.value, "100");
+
+    let Json(response) = block_transaction(
+        State(state.clone()),
+        Json(BlockTransactionRequest {
+            network_identifier: state.network.clone(),
+            block_identifier: BlockIdentifier {
+                index: 1,
+                hash: response.block.block_identifier.hash.clone(),
+            },
+            transaction_identifier: TransactionIdentifier {
+                hash: format!("{mint_tx_hash:?}"),
+            },
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.transaction.operations, *mint_operations);
+}
+
+#[db_test]
+async fn base_token_balance(pool: ConnectionPool) {
+    let state = create_state(&pool).await;
+    let Json(response) = account_balance(
+        State(state.clone()),
+        Json(AccountBalanceRequest {
+            network_identifier: state.network.clone(),
+            account_identifier: AccountIdentifier {
+                address: format!("{:?}", Address::repeat_byte(0x23)),
+            },
+            block_identifier: None,
+            currencies: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.block_identifier.index, 0);
+    assert_eq!(response.balances.len(), 1);
+    assert_eq!(response.balances[0].value, "0");
+    assert_eq!(response.balances[0].currency, eth_currency());
+}
@@ -0,0 +1,182 @@
+//! Request and response models of the Rosetta Data API. Only the fields used by the server
+//! are defined; see the [Rosetta specification](https://www.rosetta-api.org/docs/Reference.html)
+//! for the full models.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkIdentifier {
+    pub blockchain: String,
+    pub network: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockIdentifier {
+    pub index: u64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PartialBlockIdentifier {
+    pub index: Option<u64>,
+    pub hash: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionIdentifier {
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountIdentifier {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrencyMetadata {
+    /// L2 address of the token contract. Not set for the base token.
+    pub token_address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Currency {
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CurrencyMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Amount {
+    /// Signed decimal amount in the smallest units of the currency.
+    pub value: String,
+    pub currency: Currency,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OperationIdentifier {
+    pub index: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Operation {
+    pub operation_identifier: OperationIdentifier,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_operations: Vec<OperationIdentifier>,
+    #[serde(rename = "type")]
+    pub operation_type: &'static str,
+    pub status: &'static str,
+    pub account: AccountIdentifier,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Transaction {
+    pub transaction_identifier: TransactionIdentifier,
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Block {
+    pub block_identifier: BlockIdentifier,
+    pub parent_block_identifier: BlockIdentifier,
+    /// Block timestamp in milliseconds.
+    pub timestamp: u64,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Error {
+    pub code: u32,
+    pub message: String,
+    pub retriable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NetworkRequest {
+    pub network_identifier: NetworkIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub block_identifier: PartialBlockIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BlockTransactionRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub block_identifier: BlockIdentifier,
+    pub transaction_identifier: TransactionIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountBalanceRequest {
+    pub network_identifier: NetworkIdentifier,
+    pub account_identifier: AccountIdentifier,
+    #[serde(default)]
+    pub block_identifier: Option<PartialBlockIdentifier>,
+    #[serde(default)]
+    pub currencies: Option<Vec<Currency>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkListResponse {
+    pub network_identifiers: Vec<NetworkIdentifier>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Version {
+    pub rosetta_version: &'static str,
+    pub node_version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationStatus {
+    pub status: &'static str,
+    pub successful: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Allow {
+    pub operation_statuses: Vec<OperationStatus>,
+    pub operation_types: Vec<&'static str>,
+    pub errors: Vec<Error>,
+    pub historical_balance_lookup: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkOptionsResponse {
+    pub version: Version,
+    pub allow: Allow,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Peer {
+    pub peer_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkStatusResponse {
+    pub current_block_identifier: BlockIdentifier,
+    /// Timestamp of the current block in milliseconds.
+    pub current_block_timestamp: u64,
+    pub genesis_block_identifier: BlockIdentifier,
+    pub peers: Vec<Peer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockResponse {
+    pub block: Block,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockTransactionResponse {
+    pub transaction: Transaction,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountBalanceResponse {
+    pub block_identifier: BlockIdentifier,
+    pub balances: Vec<Amount>,
+}
@@ -0,0 +1,37 @@
+//! Decoding of token transfers from event logs, shared by the data APIs.
+
+use once_cell::sync::Lazy;
+
+use zksync_types::{api, web3::signing::keccak256, Address, H256, U256};
+use zksync_utils::h256_to_account_address;
+
+/// Topic of the ERC-20 `Transfer(address,address,uint256)` event.
+pub(crate) static TRANSFER_EVENT_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256(keccak256(b"Transfer(address,address,uint256)")));
+
+/// ERC-20 token transfer (including transfers of the base token, which are emitted
+/// by the `L2EthToken` system contract).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Erc20Transfer {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+}
+
+impl Erc20Transfer {
+    /// Decodes a transfer from a log. Returns `None` if the log is not an ERC-20 `Transfer` event
+    /// (e.g., if it's an ERC-721 event with the same signature).
+    pub fn from_log(log: &api::Log) -> Option<Self> {
+        if log.topics.len() != 3 || log.topics[0] != *TRANSFER_EVENT_TOPIC || log.data.0.len() != 32
+        {
+            return None;
+        }
+        Some(Self {
+            token: log.address,
+            from: h256_to_account_address(&log.topics[1]),
+            to: h256_to_account_address(&log.topics[2]),
+            amount: U256::from_big_endian(&log.data.0),
+        })
+    }
+}
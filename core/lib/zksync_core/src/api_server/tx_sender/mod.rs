@@ -9,7 +9,12 @@ use governor::{
 };
 
 // Built-in uses
-use std::{cmp, num::NonZeroU32, sync::Arc, time::Instant};
+use std::{
+    cmp,
+    num::NonZeroU32,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 // Workspace uses
 
@@ -19,22 +24,22 @@ use vm::{
         fee::derive_base_fee_and_gas_per_pubdata,
         overhead::{derive_overhead, OverheadCoeficients},
     },
-    VmExecutionResultAndLogs,
+    ExecutionResult, Halt, ValidationError, VmExecutionResultAndLogs,
 };
 
 use zksync_config::configs::{api::Web3JsonRpcConfig, chain::StateKeeperConfig};
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::{transactions_dal::L2TxSubmissionResult, ConnectionPool};
-use zksync_state::PostgresStorageCaches;
+use zksync_state::{ForkedState, PostgresStorageCaches};
 use zksync_types::{
     fee::{Fee, TransactionExecutionMetrics},
-    get_code_key, get_intrinsic_constants,
+    get_code_key, get_intrinsic_constants, get_nonce_key,
     l2::error::TxCheckError::TxDuplication,
     l2::L2Tx,
-    utils::storage_key_for_eth_balance,
+    utils::{decompose_full_nonce, storage_key_for_eth_balance},
     AccountTreeId, Address, ExecuteTransactionCommon, L2ChainId, Nonce, PackedEthSignature,
-    ProtocolVersionId, Transaction, H160, H256, MAX_GAS_PER_PUBDATA_BYTE, MAX_L2_TX_GAS_LIMIT,
-    MAX_NEW_FACTORY_DEPS, U256,
+    ProtocolVersionId, StorageKey, Transaction, H160, H256, MAX_GAS_PER_PUBDATA_BYTE,
+    MAX_L2_TX_GAS_LIMIT, MAX_NEW_FACTORY_DEPS, U256,
 };
 
 use zksync_utils::h256_to_u256;
@@ -43,18 +48,41 @@ use zksync_utils::h256_to_u256;
 use crate::api_server::{
     execution_sandbox::{
         adjust_l1_gas_price_for_tx, execute_tx_eth_call, execute_tx_with_pending_state,
-        get_pubdata_for_factory_deps, BlockArgs, TxExecutionArgs, TxSharedArgs,
+        get_pubdata_for_factory_deps, BlockArgs, TxExecutionArgs, TxSharedArgs, ValidationCache,
         VmConcurrencyLimiter, VmPermit,
     },
     tx_sender::result::ApiCallResult,
 };
 
+use crate::gas_tracker::PubdataDaMode;
 use crate::l1_gas_price::L1GasPriceProvider;
+use crate::state_keeper::execution_hints::TxExecutionHints;
 use crate::state_keeper::seal_criteria::{ConditionalSealer, SealData};
 
+mod paymaster_policy;
 mod proxy;
 mod result;
+#[cfg(test)]
+mod tests;
+
+/// Interval between checks for new sealed miniblocks by the validation cache. Chosen to be significantly smaller
+/// than the interval between miniblocks, so that cached outcomes are usable soon after a miniblock is sealed.
+const VALIDATION_CACHE_UPDATE_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Checks whether a transaction halt happens during the validation step of the bootloader.
+fn is_validation_halt(halt: &Halt) -> bool {
+    matches!(
+        halt,
+        Halt::ValidationFailed(_)
+            | Halt::PaymasterValidationFailed(_)
+            | Halt::PrePaymasterPreparationFailed(_)
+            | Halt::PayForTxFailed(_)
+            | Halt::FailedToChargeFee(_)
+            | Halt::FromIsNotAnAccount
+    )
+}
 
+pub use self::paymaster_policy::{PaymasterPolicy, PaymasterPolicyViolation};
 pub(super) use self::{proxy::TxProxy, result::SubmitTxError};
 
 /// Type alias for the rate limiter implementation.
@@ -147,6 +175,12 @@ pub struct TxSenderBuilder {
     /// Actual state keeper configuration, required for tx verification.
     /// If not set, transactions would not be checked against seal criteria.
     state_keeper_config: Option<StateKeeperConfig>,
+    /// Cache of execution hints shared with the state keeper.
+    execution_hints: Option<TxExecutionHints>,
+    /// Remote state forked in the dev mode.
+    fork: Option<ForkedState>,
+    /// Cache of transaction validation outcomes.
+    validation_cache: Option<ValidationCache>,
 }
 
 impl TxSenderBuilder {
@@ -158,6 +192,9 @@ impl TxSenderBuilder {
             rate_limiter: None,
             proxy: None,
             state_keeper_config: None,
+            execution_hints: None,
+            fork: None,
+            validation_cache: None,
         }
     }
 
@@ -187,6 +224,23 @@ impl TxSenderBuilder {
         self
     }
 
+    pub fn with_execution_hints(mut self, execution_hints: TxExecutionHints) -> Self {
+        self.execution_hints = Some(execution_hints);
+        self
+    }
+
+    /// Sets the remote state forked in the dev mode. The state is used for VM execution and transaction checks.
+    pub fn with_fork(mut self, fork: ForkedState) -> Self {
+        self.fork = Some(fork);
+        self
+    }
+
+    /// Enables caching of validation outcomes for at most `capacity` transactions.
+    pub fn with_validation_cache(mut self, capacity: usize) -> Self {
+        self.validation_cache = Some(ValidationCache::new(capacity));
+        self
+    }
+
     pub async fn build<G: L1GasPriceProvider>(
         self,
         l1_gas_price_source: Arc<G>,
@@ -198,6 +252,14 @@ impl TxSenderBuilder {
             self.master_connection_pool.is_some() || self.proxy.is_some(),
             "Either master connection pool or proxy must be set"
         );
+        if let Some(cache) = &self.validation_cache {
+            let update_task = cache.update_task(
+                self.replica_connection_pool.clone(),
+                VALIDATION_CACHE_UPDATE_INTERVAL,
+            );
+            // The update task takes care of its termination, so we don't need to retain its handle.
+            tokio::spawn(update_task);
+        }
 
         TxSender(Arc::new(TxSenderInner {
             sender_config: self.config,
@@ -208,6 +270,9 @@ impl TxSenderBuilder {
             rate_limiter: self.rate_limiter,
             proxy: self.proxy,
             state_keeper_config: self.state_keeper_config,
+            execution_hints: self.execution_hints,
+            fork: self.fork,
+            validation_cache: self.validation_cache,
             vm_concurrency_limiter,
             storage_caches,
         }))
@@ -230,6 +295,9 @@ pub struct TxSenderConfig {
     pub default_aa: H256,
     pub bootloader: H256,
     pub chain_id: L2ChainId,
+    pub paymaster_policy: PaymasterPolicy,
+    /// Whether to reject transactions reverting in the simulation performed on submission.
+    pub reject_reverted_txs: bool,
 }
 
 impl TxSenderConfig {
@@ -250,6 +318,8 @@ impl TxSenderConfig {
             default_aa: state_keeper_config.default_aa_hash,
             bootloader: state_keeper_config.bootloader_hash,
             chain_id,
+            paymaster_policy: PaymasterPolicy::new(web3_json_config),
+            reject_reverted_txs: web3_json_config.reject_reverted_txs,
         }
     }
 }
@@ -269,6 +339,14 @@ pub struct TxSenderInner<G> {
     /// This field may be omitted on the external node, since the configuration may change unexpectedly.
     /// If this field is set to `None`, `TxSender` will assume that any transaction is executable.
     state_keeper_config: Option<StateKeeperConfig>,
+    /// Hints about simulated transaction execution passed to the state keeper. Only set on the main node
+    /// if the state keeper is run in the same process.
+    execution_hints: Option<TxExecutionHints>,
+    /// Remote state forked in the dev mode.
+    fork: Option<ForkedState>,
+    /// Cache of transaction validation outcomes. Outcomes are invalidated once storage slots read
+    /// during validation are modified.
+    validation_cache: Option<ValidationCache>,
     /// Used to limit the amount of VMs that can be executed simultaneously.
     pub(super) vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
     // Caches used in VM execution.
@@ -301,6 +379,14 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         self.0.storage_caches.clone()
     }
 
+    pub(crate) fn fork(&self) -> Option<ForkedState> {
+        self.0.fork.clone()
+    }
+
+    pub(crate) fn paymaster_policy(&self) -> &PaymasterPolicy {
+        &self.0.sender_config.paymaster_policy
+    }
+
     #[tracing::instrument(skip(self, tx))]
     pub async fn submit_tx(&self, tx: L2Tx) -> Result<L2TxSubmissionResult, SubmitTxError> {
         if let Some(rate_limiter) = &self.0.rate_limiter {
@@ -318,14 +404,14 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         let vm_permit = self.0.vm_concurrency_limiter.acquire().await;
         let vm_permit = vm_permit.ok_or(SubmitTxError::ServerShuttingDown)?;
 
-        let (_, tx_metrics) = execute_tx_with_pending_state(
+        let (exec_result, tx_metrics, execution_hint) = execute_tx_with_pending_state(
             vm_permit.clone(),
             shared_args.clone(),
             TxExecutionArgs::for_validation(&tx),
             self.0.replica_connection_pool.clone(),
             tx.clone().into(),
         )
-        .await;
+        .await?;
 
         tracing::info!(
             "Submit tx {:?} with execution metrics {:?}",
@@ -342,6 +428,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
                 self.0.replica_connection_pool.clone(),
                 tx.clone(),
                 computational_gas_limit,
+                self.0.validation_cache.as_ref(),
             )
             .await;
 
@@ -351,9 +438,27 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         if let Err(err) = validation_result {
             return Err(err.into());
         }
+        // Cached validation outcomes don't depend on the fee payer's balance (see `ValidationCache`), so a failure
+        // to pay the fee is detected using the dry run. Without the cache, validation would fail in the same way.
+        if let ExecutionResult::Halt { reason } = &exec_result.result {
+            if is_validation_halt(reason) {
+                return Err(ValidationError::FailedTx(reason.clone()).into());
+            }
+        }
 
         self.ensure_tx_executable(tx.clone().into(), &tx_metrics, true)?;
 
+        if self.0.sender_config.reject_reverted_txs {
+            if let Some(err) = SubmitTxError::from_failed_execution(&exec_result.result) {
+                tracing::info!(
+                    "Rejecting tx {:?} since it fails when simulated: {err}",
+                    tx.hash()
+                );
+                metrics::counter!("server.processed_txs", 1, "stage" => "rejected_reverted");
+                return Err(err);
+            }
+        }
+
         if let Some(proxy) = &self.0.proxy {
             // We're running an external node: we have to proxy the transaction to the main node.
             // But before we do that, save the tx to cache in case someone will request it
@@ -376,18 +481,35 @@ impl<G: L1GasPriceProvider> TxSender<G> {
 
         let nonce = tx.common_data.nonce.0;
         let hash = tx.hash();
-        let expected_nonce = self.get_expected_nonce(&tx).await;
-        let submission_res_handle = self
+        if let Some(execution_hints) = &self.0.execution_hints {
+            // Only a failed bytecode compression changes how the state keeper executes the transaction;
+            // see the batch executor for details.
+            if execution_hint.bytecode_compression_failed {
+                execution_hints.insert(hash, execution_hint);
+            }
+        }
+        let expected_nonce = self.get_expected_nonce(&tx).await?;
+        let mut storage = self
             .0
             .master_connection_pool
             .as_ref()
             .unwrap() // Checked above
             .access_storage_tagged("api")
             .await
-            .unwrap()
+            .unwrap();
+        let submission_res_handle = storage
             .transactions_dal()
             .insert_transaction_l2(tx, tx_metrics)
             .await;
+        if matches!(
+            submission_res_handle,
+            L2TxSubmissionResult::Added | L2TxSubmissionResult::Replaced
+        ) {
+            // Lifecycle events are informational, so failing to record them shouldn't fail the submission.
+            if let Err(err) = storage.tx_lifecycle_dal().record_validated(hash).await {
+                tracing::warn!("Failed recording validation of transaction {hash:?}: {err}");
+            }
+        }
 
         let status: String;
         let submission_result = match submission_res_handle {
@@ -426,9 +548,14 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         TxSharedArgs {
             operator_account: AccountTreeId::new(self.0.sender_config.fee_account_addr),
             l1_gas_price: self.0.l1_gas_price_source.estimate_effective_gas_price(),
+            fair_pubdata_price: self
+                .0
+                .l1_gas_price_source
+                .estimate_effective_pubdata_price(),
             fair_l2_gas_price: self.0.sender_config.fair_l2_gas_price,
             base_system_contracts: self.0.api_contracts.eth_call.clone(),
             caches: self.storage_caches(),
+            fork: self.fork(),
             validation_computational_gas_limit: self
                 .0
                 .sender_config
@@ -477,6 +604,13 @@ impl<G: L1GasPriceProvider> TxSender<G> {
                 MAX_NEW_FACTORY_DEPS,
             ));
         }
+        if let Err(err) = self.0.sender_config.paymaster_policy.check(tx) {
+            tracing::info!(
+                "Submitted Tx {:?} violates paymaster policy: {err}",
+                tx.hash()
+            );
+            return Err(err.into());
+        }
 
         let l1_gas_price = self.0.l1_gas_price_source.estimate_effective_gas_price();
         let (_, gas_per_pubdata_byte) = derive_base_fee_and_gas_per_pubdata(
@@ -505,7 +639,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
     }
 
     async fn validate_account_nonce(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
-        let expected_nonce = self.get_expected_nonce(tx).await;
+        let expected_nonce = self.get_expected_nonce(tx).await?;
 
         if tx.common_data.nonce.0 < expected_nonce.0 {
             Err(SubmitTxError::NonceIsTooLow(
@@ -527,7 +661,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         }
     }
 
-    async fn get_expected_nonce(&self, tx: &L2Tx) -> Nonce {
+    async fn get_expected_nonce(&self, tx: &L2Tx) -> anyhow::Result<Nonce> {
         let mut connection = self
             .0
             .replica_connection_pool
@@ -545,7 +679,17 @@ impl<G: L1GasPriceProvider> TxSender<G> {
             .get_address_historical_nonce(tx.initiator_account(), latest_block_number)
             .await
             .unwrap();
-        Nonce(nonce.as_u32())
+        drop(connection);
+
+        // Nonces never decrease, so a zero nonce means that the account wasn't used locally.
+        if nonce.is_zero() {
+            let nonce_key = get_nonce_key(&tx.initiator_account());
+            if let Some(full_nonce) = self.read_forked_value(nonce_key).await? {
+                let (nonce, _) = decompose_full_nonce(h256_to_u256(full_nonce));
+                return Ok(Nonce(nonce.as_u32()));
+            }
+        }
+        Ok(Nonce(nonce.as_u32()))
     }
 
     async fn validate_enough_balance(&self, tx: &L2Tx) -> Result<(), SubmitTxError> {
@@ -557,7 +701,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
             return Ok(());
         }
 
-        let balance = self.get_balance(&tx.common_data.initiator_address).await;
+        let balance = self.get_balance(&tx.common_data.initiator_address).await?;
 
         // Estimate the minimum fee price user will agree to.
         let gas_price = cmp::min(
@@ -579,7 +723,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         }
     }
 
-    async fn get_balance(&self, initiator_address: &H160) -> U256 {
+    async fn get_balance(&self, initiator_address: &H160) -> anyhow::Result<U256> {
         let eth_balance_key = storage_key_for_eth_balance(initiator_address);
 
         let balance = self
@@ -590,10 +734,26 @@ impl<G: L1GasPriceProvider> TxSender<G> {
             .unwrap()
             .storage_dal()
             .get_by_key(&eth_balance_key)
-            .await
-            .unwrap_or_default();
+            .await;
+        let balance = match balance {
+            Some(balance) => Some(balance),
+            None => self.read_forked_value(eth_balance_key).await?,
+        };
+
+        Ok(h256_to_u256(balance.unwrap_or_default()))
+    }
 
-        h256_to_u256(balance)
+    /// Reads a storage slot from the remote state if it's forked. Must only be used for slots
+    /// that weren't written locally.
+    async fn read_forked_value(&self, key: StorageKey) -> anyhow::Result<Option<H256>> {
+        let Some(fork) = self.fork() else {
+            return Ok(None);
+        };
+        // Forked state may block on network requests, so it cannot be accessed from async code directly.
+        let value = tokio::task::spawn_blocking(move || fork.read_value(&key))
+            .await
+            .unwrap()?;
+        Ok(Some(value))
     }
 
     /// Given the gas_limit to be used for the body of the transaction,
@@ -607,7 +767,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         tx_gas_limit: u32,
         l1_gas_price: u64,
         base_fee: u64,
-    ) -> (VmExecutionResultAndLogs, TransactionExecutionMetrics) {
+    ) -> anyhow::Result<(VmExecutionResultAndLogs, TransactionExecutionMetrics)> {
         let gas_limit_with_overhead = tx_gas_limit
             + derive_overhead(
                 tx_gas_limit,
@@ -640,28 +800,35 @@ impl<G: L1GasPriceProvider> TxSender<G> {
         let vm_execution_cache_misses_limit = self.0.sender_config.vm_execution_cache_misses_limit;
         let execution_args =
             TxExecutionArgs::for_gas_estimate(vm_execution_cache_misses_limit, &tx, base_fee);
-        let (exec_result, tx_metrics) = execute_tx_with_pending_state(
+        let (exec_result, tx_metrics, _) = execute_tx_with_pending_state(
             vm_permit,
             shared_args,
             execution_args,
             self.0.replica_connection_pool.clone(),
             tx.clone(),
         )
-        .await;
+        .await?;
 
-        (exec_result, tx_metrics)
+        Ok((exec_result, tx_metrics))
     }
 
     fn shared_args_for_gas_estimate(&self, l1_gas_price: u64) -> TxSharedArgs {
         let config = &self.0.sender_config;
+        let pubdata_price = self
+            .0
+            .l1_gas_price_source
+            .estimate_effective_pubdata_price();
         TxSharedArgs {
             operator_account: AccountTreeId::new(config.fee_account_addr),
             l1_gas_price,
+            // Overestimate the pubdata price in the same way as the L1 gas price, so that fewer pubdata is refunded.
+            fair_pubdata_price: (pubdata_price as f64 * config.gas_price_scale_factor) as u64,
             fair_l2_gas_price: config.fair_l2_gas_price,
             // We want to bypass the computation gas limit check for gas estimation
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
             base_system_contracts: self.0.api_contracts.estimate_gas.clone(),
             caches: self.storage_caches(),
+            fork: self.fork(),
             chain_id: config.chain_id,
         }
     }
@@ -721,7 +888,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
 
         if !tx.is_l1()
             && account_code_hash == H256::zero()
-            && tx.execute.value > self.get_balance(&tx.initiator_account()).await
+            && tx.execute.value > self.get_balance(&tx.initiator_account()).await?
         {
             tracing::info!(
                 "fee estimation failed on validation step.
@@ -756,8 +923,9 @@ impl<G: L1GasPriceProvider> TxSender<G> {
                 &self.0.replica_connection_pool,
                 tx.execute.factory_deps.as_deref().unwrap_or_default(),
                 self.storage_caches(),
+                self.fork(),
             )
-            .await;
+            .await?;
 
             if pubdata_for_factory_deps > MAX_PUBDATA_PER_BLOCK {
                 return Err(SubmitTxError::Unexecutable(
@@ -799,7 +967,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
                     l1_gas_price,
                     base_fee,
                 )
-                .await;
+                .await?;
 
             if result.result.is_failed() {
                 lower_bound = mid + 1;
@@ -837,7 +1005,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
                 l1_gas_price,
                 base_fee,
             )
-            .await;
+            .await?;
 
         result.into_api_call_result()?;
         self.ensure_tx_executable(tx.clone(), &tx_metrics, false)?;
@@ -886,7 +1054,7 @@ impl<G: L1GasPriceProvider> TxSender<G> {
             vm_execution_cache_misses_limit,
             vec![],
         )
-        .await
+        .await?
         .into_api_call_result()
     }
 
@@ -921,7 +1089,11 @@ impl<G: L1GasPriceProvider> TxSender<G> {
             H256::zero()
         };
 
-        let seal_data = SealData::for_transaction(transaction, tx_metrics);
+        let da_mode = PubdataDaMode::new(
+            sk_config.l1_batch_commitment_mode,
+            sk_config.pubdata_sending_mode,
+        );
+        let seal_data = SealData::for_transaction(transaction, tx_metrics, da_mode);
         if let Some(reason) = ConditionalSealer::find_unexecutable_reason(sk_config, &seal_data) {
             let message = format!(
                 "Tx is Unexecutable because of {reason}; inputs for decision: {seal_data:?}"
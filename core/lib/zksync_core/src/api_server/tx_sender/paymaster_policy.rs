@@ -0,0 +1,212 @@
+//! Operator-configurable policies for transactions sponsored by paymasters.
+
+use once_cell::sync::Lazy;
+use thiserror::Error;
+
+use std::collections::HashSet;
+
+use zksync_config::configs::api::Web3JsonRpcConfig;
+use zksync_types::{
+    api::PaymasterPolicies,
+    ethabi::{self, ParamType},
+    l2::L2Tx,
+    Address, U256,
+};
+
+/// Selector of the `approvalBased(address,uint256,bytes)` paymaster flow.
+static APPROVAL_BASED_SELECTOR: Lazy<[u8; 4]> = Lazy::new(|| {
+    ethabi::short_signature(
+        "approvalBased",
+        &[ParamType::Address, ParamType::Uint(256), ParamType::Bytes],
+    )
+});
+
+#[derive(Debug, Error, PartialEq)]
+pub enum PaymasterPolicyViolation {
+    #[error("paymaster {0:?} is not allowed")]
+    PaymasterNotAllowed(Address),
+    #[error("gas limit of a sponsored transaction {0} exceeds the maximum allowed {1}")]
+    SponsoredGasTooBig(U256, U256),
+    #[error("malformed input for the approval-based paymaster flow")]
+    MalformedApprovalInput,
+    #[error("token {0:?} is not allowed in the approval-based paymaster flow")]
+    TokenNotAllowed(Address),
+}
+
+/// Policies for paymaster flows enforced by the API server when accepting transactions. By default,
+/// no restrictions are imposed.
+#[derive(Debug, Clone, Default)]
+pub struct PaymasterPolicy {
+    allowed_paymasters: Option<HashSet<Address>>,
+    max_sponsored_gas: Option<U256>,
+    allowed_tokens: Option<HashSet<Address>>,
+}
+
+impl PaymasterPolicy {
+    pub fn new(web3_json_config: &Web3JsonRpcConfig) -> Self {
+        Self {
+            allowed_paymasters: web3_json_config
+                .allowed_paymasters
+                .as_ref()
+                .map(|addresses| addresses.iter().copied().collect()),
+            max_sponsored_gas: web3_json_config.max_sponsored_gas.map(U256::from),
+            allowed_tokens: web3_json_config
+                .allowed_paymaster_tokens
+                .as_ref()
+                .map(|addresses| addresses.iter().copied().collect()),
+        }
+    }
+
+    /// Checks whether the transaction satisfies the policy. Transactions not using a paymaster
+    /// always satisfy it.
+    pub fn check(&self, tx: &L2Tx) -> Result<(), PaymasterPolicyViolation> {
+        let paymaster_params = &tx.common_data.paymaster_params;
+        let paymaster = paymaster_params.paymaster;
+        if paymaster == Address::zero() {
+            return Ok(());
+        }
+
+        if let Some(allowed_paymasters) = &self.allowed_paymasters {
+            if !allowed_paymasters.contains(&paymaster) {
+                return Err(PaymasterPolicyViolation::PaymasterNotAllowed(paymaster));
+            }
+        }
+        if let Some(max_sponsored_gas) = self.max_sponsored_gas {
+            let gas_limit = tx.common_data.fee.gas_limit;
+            if gas_limit > max_sponsored_gas {
+                return Err(PaymasterPolicyViolation::SponsoredGasTooBig(
+                    gas_limit,
+                    max_sponsored_gas,
+                ));
+            }
+        }
+        if let Some(allowed_tokens) = &self.allowed_tokens {
+            if let Some(token) = Self::approval_token(&paymaster_params.paymaster_input)? {
+                if !allowed_tokens.contains(&token) {
+                    return Err(PaymasterPolicyViolation::TokenNotAllowed(token));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Extracts the token from the paymaster input if it corresponds to the approval-based flow.
+    fn approval_token(paymaster_input: &[u8]) -> Result<Option<Address>, PaymasterPolicyViolation> {
+        let Some(encoded_args) = paymaster_input.strip_prefix(APPROVAL_BASED_SELECTOR.as_slice())
+        else {
+            return Ok(None);
+        };
+        let arg_types = [ParamType::Address, ParamType::Uint(256), ParamType::Bytes];
+        let mut args = ethabi::decode(&arg_types, encoded_args)
+            .map_err(|_| PaymasterPolicyViolation::MalformedApprovalInput)?;
+        let token = args.swap_remove(0).into_address();
+        token
+            .map(Some)
+            .ok_or(PaymasterPolicyViolation::MalformedApprovalInput)
+    }
+
+    pub fn to_api(&self) -> PaymasterPolicies {
+        let sorted = |addresses: &HashSet<Address>| {
+            let mut addresses: Vec<_> = addresses.iter().copied().collect();
+            addresses.sort_unstable();
+            addresses
+        };
+        PaymasterPolicies {
+            allowed_paymasters: self.allowed_paymasters.as_ref().map(sorted),
+            max_sponsored_gas: self.max_sponsored_gas,
+            allowed_tokens: self.allowed_tokens.as_ref().map(sorted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_types::{ethabi::Token, fee::Fee, transaction_request::PaymasterParams};
+
+    use super::*;
+
+    fn sponsored_tx(paymaster: Address, paymaster_input: Vec<u8>, gas_limit: u64) -> L2Tx {
+        let fee = Fee {
+            gas_limit: gas_limit.into(),
+            ..Fee::default()
+        };
+        let paymaster_params = PaymasterParams {
+            paymaster,
+            paymaster_input,
+        };
+        L2Tx::new(
+            Address::repeat_byte(0x10),
+            vec![],
+            0.into(),
+            fee,
+            Address::repeat_byte(0x20),
+            U256::zero(),
+            None,
+            paymaster_params,
+        )
+    }
+
+    fn approval_input(token: Address) -> Vec<u8> {
+        let args = ethabi::encode(&[
+            Token::Address(token),
+            Token::Uint(1_000.into()),
+            Token::Bytes(vec![]),
+        ]);
+        APPROVAL_BASED_SELECTOR
+            .iter()
+            .copied()
+            .chain(args)
+            .collect()
+    }
+
+    #[test]
+    fn checking_paymaster_policy() {
+        let paymaster = Address::repeat_byte(1);
+        let token = Address::repeat_byte(2);
+        let policy = PaymasterPolicy {
+            allowed_paymasters: Some(HashSet::from([paymaster])),
+            max_sponsored_gas: Some(1_000_000.into()),
+            allowed_tokens: Some(HashSet::from([token])),
+        };
+
+        let tx = sponsored_tx(Address::zero(), vec![], 10_000_000);
+        policy.check(&tx).unwrap();
+        let tx = sponsored_tx(paymaster, approval_input(token), 1_000_000);
+        policy.check(&tx).unwrap();
+        // The general flow does not specify a token.
+        let general_input = ethabi::short_signature("general", &[ParamType::Bytes]).to_vec();
+        let tx = sponsored_tx(paymaster, general_input, 1_000_000);
+        policy.check(&tx).unwrap();
+
+        let other_paymaster = Address::repeat_byte(3);
+        let tx = sponsored_tx(other_paymaster, vec![], 1_000_000);
+        assert_eq!(
+            policy.check(&tx).unwrap_err(),
+            PaymasterPolicyViolation::PaymasterNotAllowed(other_paymaster)
+        );
+        let tx = sponsored_tx(paymaster, vec![], 1_000_001);
+        assert_eq!(
+            policy.check(&tx).unwrap_err(),
+            PaymasterPolicyViolation::SponsoredGasTooBig(1_000_001.into(), 1_000_000.into())
+        );
+        let other_token = Address::repeat_byte(4);
+        let tx = sponsored_tx(paymaster, approval_input(other_token), 1_000_000);
+        assert_eq!(
+            policy.check(&tx).unwrap_err(),
+            PaymasterPolicyViolation::TokenNotAllowed(other_token)
+        );
+        let tx = sponsored_tx(paymaster, APPROVAL_BASED_SELECTOR.to_vec(), 1_000_000);
+        assert_eq!(
+            policy.check(&tx).unwrap_err(),
+            PaymasterPolicyViolation::MalformedApprovalInput
+        );
+
+        assert_eq!(
+            PaymasterPolicy::default().to_api(),
+            PaymasterPolicies::default()
+        );
+        let api_policies = policy.to_api();
+        assert_eq!(api_policies.allowed_paymasters, Some(vec![paymaster]));
+        assert_eq!(api_policies.max_sponsored_gas, Some(1_000_000.into()));
+    }
+}
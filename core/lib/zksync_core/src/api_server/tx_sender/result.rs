@@ -1,4 +1,5 @@
-use crate::api_server::execution_sandbox::SandboxExecutionError;
+use super::PaymasterPolicyViolation;
+use crate::api_server::execution_sandbox::{SandboxExecutionError, SandboxValidationError};
 use thiserror::Error;
 
 use vm::{ExecutionResult, ValidationError, VmExecutionResultAndLogs};
@@ -64,9 +65,15 @@ pub enum SubmitTxError {
     /// than required to start the invocation.
     #[error("intrinsic gas too low")]
     IntrinsicGas,
+    /// The transaction is sponsored by a paymaster in a way not allowed by the operator.
+    #[error("transaction rejected by paymaster policy: {0}")]
+    PaymasterPolicyViolation(#[from] PaymasterPolicyViolation),
     /// Error returned from main node
     #[error("{0}")]
     ProxyError(#[from] zksync_web3_decl::jsonrpsee::core::Error),
+    /// Error unrelated to the transaction itself, e.g. an error fetching data from the fork.
+    #[error("internal error: {0:#}")]
+    Internal(#[from] anyhow::Error),
 }
 
 impl SubmitTxError {
@@ -96,7 +103,9 @@ impl SubmitTxError {
             Self::FeePerPubdataByteTooHigh => "pubdata-price-limit-too-high",
             Self::InsufficientFundsForTransfer => "insufficient-funds-for-transfer",
             Self::IntrinsicGas => "intrinsic-gas",
+            Self::PaymasterPolicyViolation(_) => "paymaster-policy-violation",
             Self::ProxyError(_) => "proxy-error",
+            Self::Internal(_) => "internal",
         }
     }
 
@@ -107,6 +116,22 @@ impl SubmitTxError {
             Vec::new()
         }
     }
+
+    /// Converts the outcome of a failed transaction execution into an error with the decoded
+    /// revert / halt reason. Returns `None` if the execution succeeded.
+    pub(crate) fn from_failed_execution(result: &ExecutionResult) -> Option<Self> {
+        match result {
+            ExecutionResult::Success { .. } => None,
+            ExecutionResult::Revert { output } => Some(Self::ExecutionReverted(
+                output.to_user_friendly_string(),
+                output.encoded_data(),
+            )),
+            ExecutionResult::Halt { reason } => {
+                let err: SandboxExecutionError = reason.clone().into();
+                Some(err.into())
+            }
+        }
+    }
 }
 
 impl From<SandboxExecutionError> for SubmitTxError {
@@ -144,6 +169,15 @@ impl From<ValidationError> for SubmitTxError {
     }
 }
 
+impl From<SandboxValidationError> for SubmitTxError {
+    fn from(err: SandboxValidationError) -> Self {
+        match err {
+            SandboxValidationError::Validation(err) => err.into(),
+            SandboxValidationError::Internal(err) => Self::Internal(err),
+        }
+    }
+}
+
 pub(crate) trait ApiCallResult {
     fn into_api_call_result(self) -> Result<Vec<u8>, SubmitTxError>;
 }
@@ -163,3 +197,33 @@ impl ApiCallResult for VmExecutionResultAndLogs {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use vm::{Halt, VmRevertReason};
+
+    use super::*;
+
+    #[test]
+    fn converting_failed_execution_to_error() {
+        let success = ExecutionResult::Success { output: vec![] };
+        assert!(SubmitTxError::from_failed_execution(&success).is_none());
+
+        let revert = ExecutionResult::Revert {
+            output: VmRevertReason::General {
+                msg: "oops".to_owned(),
+                data: vec![],
+            },
+        };
+        let err = SubmitTxError::from_failed_execution(&revert).unwrap();
+        assert!(
+            matches!(&err, SubmitTxError::ExecutionReverted(msg, _) if msg.contains("oops")),
+            "{err:?}"
+        );
+
+        let halt = ExecutionResult::Halt {
+            reason: Halt::NotEnoughGasProvided,
+        };
+        assert!(SubmitTxError::from_failed_execution(&halt).is_some());
+    }
+}
@@ -0,0 +1,182 @@
+//! Tests for the transaction sender.
+
+use assert_matches::assert_matches;
+use db_test_macro::db_test;
+
+use std::{collections::HashMap, convert::TryInto};
+
+use zksync_contracts::{load_contract, read_bytecode};
+use zksync_test_account::Account;
+use zksync_types::{
+    commitment::CommitmentSchemes, protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts, utils::storage_key_for_eth_balance, Execute,
+    L1BatchCommitmentMode, MiniblockNumber, StorageLog,
+};
+use zksync_utils::{bytecode::hash_bytecode, u256_to_h256};
+
+use super::*;
+use crate::{
+    api_server::execution_sandbox::VmConcurrencyBarrier,
+    genesis::{ensure_genesis_state, GenesisParams},
+};
+
+const CHAIN_ID: L2ChainId = L2ChainId(270);
+const FAIR_L2_GAS_PRICE: u64 = 250_000_000;
+const ERROR_CONTRACT_PATH: &str =
+    "etc/contracts-test-data/artifacts-zk/contracts/error/error.sol/SimpleRequire.json";
+
+#[derive(Debug)]
+struct MockPriceProvider;
+
+impl L1GasPriceProvider for MockPriceProvider {
+    fn estimate_effective_gas_price(&self) -> u64 {
+        1_000_000_000
+    }
+}
+
+fn mock_config(reject_reverted_txs: bool) -> TxSenderConfig {
+    TxSenderConfig {
+        fee_account_addr: Address::repeat_byte(0x01),
+        gas_price_scale_factor: 1.0,
+        max_nonce_ahead: 10,
+        max_allowed_l2_tx_gas_limit: 4_000_000_000,
+        fair_l2_gas_price: FAIR_L2_GAS_PRICE,
+        vm_execution_cache_misses_limit: None,
+        validation_computational_gas_limit: 300_000,
+        default_aa: H256::zero(),
+        bootloader: H256::zero(),
+        chain_id: CHAIN_ID,
+        paymaster_policy: PaymasterPolicy::default(),
+        reject_reverted_txs,
+    }
+}
+
+/// Performs the genesis, deploys the `SimpleRequire` contract at `contract_address` and funds `account`.
+async fn prepare_storage(pool: &ConnectionPool, contract_address: Address, account: Address) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, CHAIN_ID, &params)
+        .await
+        .unwrap();
+
+    let bytecode = read_bytecode(ERROR_CONTRACT_PATH);
+    let bytecode_hash = hash_bytecode(&bytecode);
+    storage
+        .storage_dal()
+        .insert_factory_deps(
+            MiniblockNumber(0),
+            &HashMap::from([(bytecode_hash, bytecode)]),
+        )
+        .await;
+
+    let balance = U256::from(10_u32).pow(U256::from(32)); // 10^32 wei
+    let storage_logs = vec![
+        StorageLog::new_write_log(get_code_key(&contract_address), bytecode_hash),
+        StorageLog::new_write_log(storage_key_for_eth_balance(&account), u256_to_h256(balance)),
+    ];
+    storage
+        .storage_logs_dal()
+        .append_storage_logs(MiniblockNumber(0), &[(H256::zero(), storage_logs.clone())])
+        .await;
+    storage
+        .storage_dal()
+        .apply_storage_logs(&[(H256::zero(), storage_logs)])
+        .await;
+}
+
+async fn create_tx_sender(
+    pool: &ConnectionPool,
+    reject_reverted_txs: bool,
+) -> (TxSender<MockPriceProvider>, VmConcurrencyBarrier) {
+    let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(1);
+    let tx_sender = TxSenderBuilder::new(mock_config(reject_reverted_txs), pool.clone())
+        .with_main_connection_pool(pool.clone())
+        .build(
+            Arc::new(MockPriceProvider),
+            Arc::new(vm_concurrency_limiter),
+            ApiContracts::load_from_disk(),
+            PostgresStorageCaches::new(1 << 20, 1 << 20),
+        )
+        .await;
+    (tx_sender, vm_barrier)
+}
+
+/// Creates a transaction calling a `SimpleRequire` method that always reverts.
+fn reverting_tx(account: &mut Account, contract_address: Address) -> L2Tx {
+    let calldata = load_contract(ERROR_CONTRACT_PATH)
+        .function("require_short")
+        .unwrap()
+        .encode_input(&[])
+        .unwrap();
+    let execute = Execute {
+        contract_address,
+        calldata,
+        value: U256::zero(),
+        factory_deps: None,
+    };
+    let fee = Fee {
+        gas_limit: U256::from(10_000_000_u32),
+        max_fee_per_gas: U256::from(FAIR_L2_GAS_PRICE),
+        max_priority_fee_per_gas: U256::zero(),
+        gas_per_pubdata_limit: U256::from(MAX_GAS_PER_PUBDATA_BYTE),
+    };
+    account
+        .get_l2_tx_for_execute(execute, Some(fee))
+        .try_into()
+        .unwrap()
+}
+
+#[db_test]
+async fn reverted_tx_is_rejected_if_configured(pool: ConnectionPool) {
+    let mut account = Account::random();
+    let contract_address = Address::repeat_byte(0x20);
+    prepare_storage(&pool, contract_address, account.address).await;
+    let (tx_sender, _vm_barrier) = create_tx_sender(&pool, true).await;
+
+    let tx = reverting_tx(&mut account, contract_address);
+    let tx_hash = tx.hash();
+    let err = tx_sender.submit_tx(tx).await.unwrap_err();
+    assert_matches!(
+        &err,
+        SubmitTxError::ExecutionReverted(msg, _) if msg.contains("short"),
+        "{err:?}"
+    );
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let tx_details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx_hash)
+        .await
+        .unwrap();
+    assert!(tx_details.is_none(), "{tx_details:?}");
+}
+
+#[db_test]
+async fn reverted_tx_is_accepted_by_default(pool: ConnectionPool) {
+    let mut account = Account::random();
+    let contract_address = Address::repeat_byte(0x20);
+    prepare_storage(&pool, contract_address, account.address).await;
+    let (tx_sender, _vm_barrier) = create_tx_sender(&pool, false).await;
+
+    let tx = reverting_tx(&mut account, contract_address);
+    let tx_hash = tx.hash();
+    let submission_result = tx_sender.submit_tx(tx).await.unwrap();
+    assert_eq!(submission_result, L2TxSubmissionResult::Added);
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let tx_details = storage
+        .transactions_web3_dal()
+        .get_transaction_details(tx_hash)
+        .await
+        .unwrap();
+    assert!(tx_details.is_some());
+}
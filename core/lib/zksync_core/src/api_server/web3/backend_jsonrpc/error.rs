@@ -1,6 +1,8 @@
 use jsonrpc_core::{Error, ErrorCode};
 use zksync_web3_decl::error::Web3Error;
 
+use crate::api_server::web3::geth_compat;
+
 pub fn into_jsrpc_error(err: Web3Error) -> Error {
     Error {
         code: match err {
@@ -14,6 +16,7 @@ pub fn into_jsrpc_error(err: Web3Error) -> Error {
             | Web3Error::InvalidFeeParams(_)
             | Web3Error::LogsLimitExceeded(_, _, _)
             | Web3Error::TooManyLogs(_)
+            | Web3Error::ResponseTooLarge(_)
             | Web3Error::InvalidFilterBlockHash => ErrorCode::InvalidParams,
             Web3Error::SubmitTransactionError(_, _) | Web3Error::SerializationError(_) => 3.into(),
             Web3Error::PubSubTimeout => 4.into(),
@@ -32,6 +35,22 @@ pub fn into_jsrpc_error(err: Web3Error) -> Error {
     }
 }
 
+/// Returns a converter for errors returned by the specified `eth_` method. If `geth_compatible` is set,
+/// errors follow geth conventions; see [`geth_compat`] for details.
+pub fn into_eth_jsrpc_error(
+    method: &'static str,
+    geth_compatible: bool,
+) -> impl FnOnce(Web3Error) -> Error {
+    move |err| match geth_compatible.then(|| geth_compat::geth_error(method, &err)) {
+        Some(Some(geth_err)) => Error {
+            code: geth_err.code.into(),
+            message: geth_err.message,
+            data: None,
+        },
+        _ => into_jsrpc_error(err),
+    }
+}
+
 pub fn internal_error(method_name: &str, error: impl ToString) -> Web3Error {
     tracing::error!(
         "Internal error in method {}: {}",
@@ -0,0 +1,47 @@
+use futures::{future::Either, FutureExt};
+use jsonrpc_core::{middleware::Middleware, BoxFuture, Call, Metadata, Output};
+
+use crate::api_server::web3::geth_compat;
+
+/// Middleware adapting results of `eth_` methods to geth conventions if the strict geth compatibility mode
+/// is enabled. Errors are adapted by the `eth_` namespace itself. The middleware is used for both HTTP and WS transports.
+/// See [`geth_compat`] for details.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GethCompatMiddleware {
+    enabled: bool,
+}
+
+impl GethCompatMiddleware {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl<M: Metadata> Middleware<M> for GethCompatMiddleware {
+    type Future = jsonrpc_core::middleware::NoopFuture;
+
+    type CallFuture = BoxFuture<Option<Output>>;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: futures::Future<Output = Option<Output>> + Send + 'static,
+    {
+        let method = match &call {
+            Call::MethodCall(method_call)
+                if self.enabled && geth_compat::adapts_result(&method_call.method) =>
+            {
+                method_call.method.clone()
+            }
+            _ => return Either::Right(next(call, meta)),
+        };
+
+        let output = next(call, meta).map(move |mut output| {
+            if let Some(Output::Success(success)) = &mut output {
+                geth_compat::adapt_result(&method, &mut success.result);
+            }
+            output
+        });
+        Either::Left(Box::pin(output))
+    }
+}
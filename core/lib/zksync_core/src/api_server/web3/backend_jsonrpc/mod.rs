@@ -1,4 +1,5 @@
 pub(crate) mod batch_limiter_middleware;
 pub mod error;
+pub(crate) mod geth_compat_middleware;
 pub mod namespaces;
 pub mod pub_sub;
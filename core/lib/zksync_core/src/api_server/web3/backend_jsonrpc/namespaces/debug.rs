@@ -5,7 +5,10 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, BytecodeCompressionComparison, DebugCall, L1BatchSealingStatus,
+        ResultDebugCall, TracerConfig,
+    },
     transaction_request::CallRequest,
     H256,
 };
@@ -40,6 +43,16 @@ pub trait DebugNamespaceT {
         tx_hash: H256,
         options: Option<TracerConfig>,
     ) -> BoxFuture<Result<Option<DebugCall>>>;
+
+    #[rpc(name = "debug_getL1BatchSealingStatus")]
+    fn get_l1_batch_sealing_status(&self) -> BoxFuture<Result<Option<L1BatchSealingStatus>>>;
+
+    #[rpc(name = "debug_compareBytecodeCompression")]
+    fn compare_bytecode_compression(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+    ) -> BoxFuture<Result<BytecodeCompressionComparison>>;
 }
 
 impl DebugNamespaceT for DebugNamespace {
@@ -94,4 +107,25 @@ impl DebugNamespaceT for DebugNamespace {
         let self_ = self.clone();
         Box::pin(async move { Ok(self_.debug_trace_transaction_impl(tx_hash, options).await) })
     }
+
+    fn get_l1_batch_sealing_status(&self) -> BoxFuture<Result<Option<L1BatchSealingStatus>>> {
+        let result = self
+            .debug_get_l1_batch_sealing_status_impl()
+            .map_err(into_jsrpc_error);
+        Box::pin(async move { result })
+    }
+
+    fn compare_bytecode_compression(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+    ) -> BoxFuture<Result<BytecodeCompressionComparison>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .debug_compare_bytecode_compression_impl(request, block)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
 }
@@ -18,7 +18,7 @@ use zksync_web3_decl::types::{Block, Filter, FilterChanges, Log};
 
 // Local uses
 use crate::web3::namespaces::EthNamespace;
-use crate::{l1_gas_price::L1GasPriceProvider, web3::backend_jsonrpc::error::into_jsrpc_error};
+use crate::{l1_gas_price::L1GasPriceProvider, web3::backend_jsonrpc::error::into_eth_jsrpc_error};
 
 #[rpc]
 pub trait EthNamespaceT {
@@ -41,6 +41,9 @@ pub trait EthNamespaceT {
     #[rpc(name = "eth_gasPrice")]
     fn gas_price(&self) -> BoxFuture<Result<U256>>;
 
+    #[rpc(name = "eth_maxPriorityFeePerGas")]
+    fn max_priority_fee_per_gas(&self) -> BoxFuture<Result<U256>>;
+
     #[rpc(name = "eth_newFilter")]
     fn new_filter(&self, filter: Filter) -> BoxFuture<Result<U256>>;
 
@@ -140,6 +143,9 @@ pub trait EthNamespaceT {
     #[rpc(name = "eth_sendRawTransaction")]
     fn send_raw_transaction(&self, tx_bytes: Bytes) -> BoxFuture<Result<H256>>;
 
+    #[rpc(name = "eth_sendTransaction")]
+    fn send_transaction(&self, req: CallRequest) -> BoxFuture<Result<H256>>;
+
     #[rpc(name = "eth_syncing")]
     fn syncing(&self) -> BoxFuture<Result<SyncState>>;
 
@@ -183,7 +189,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_block_number_impl()
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_blockNumber",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -198,7 +207,7 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .call_impl(req, block.map(Into::into))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error("eth_call", self_.is_geth_compatible()))
         })
     }
 
@@ -212,13 +221,34 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .estimate_gas_impl(req, block)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_estimateGas",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
     fn gas_price(&self) -> BoxFuture<Result<U256>> {
         let self_ = self.clone();
-        Box::pin(async move { self_.gas_price_impl().map_err(into_jsrpc_error) })
+        Box::pin(async move {
+            self_.gas_price_impl().await.map_err(into_eth_jsrpc_error(
+                "eth_gasPrice",
+                self_.is_geth_compatible(),
+            ))
+        })
+    }
+
+    fn max_priority_fee_per_gas(&self) -> BoxFuture<Result<U256>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .max_priority_fee_per_gas_impl()
+                .await
+                .map_err(into_eth_jsrpc_error(
+                    "eth_maxPriorityFeePerGas",
+                    self_.is_geth_compatible(),
+                ))
+        })
     }
 
     fn new_filter(&self, filter: Filter) -> BoxFuture<Result<U256>> {
@@ -227,7 +257,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .new_filter_impl(filter)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_newFilter",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -237,7 +270,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .new_block_filter_impl()
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_newBlockFilter",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -253,7 +289,15 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
 
     fn get_logs(&self, filter: Filter) -> BoxFuture<Result<Vec<Log>>> {
         let self_ = self.clone();
-        Box::pin(async move { self_.get_logs_impl(filter).await.map_err(into_jsrpc_error) })
+        Box::pin(async move {
+            self_
+                .get_logs_impl(filter)
+                .await
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getLogs",
+                    self_.is_geth_compatible(),
+                ))
+        })
     }
 
     fn get_filter_logs(&self, filter_index: U256) -> BoxFuture<Result<FilterChanges>> {
@@ -262,7 +306,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_filter_logs_impl(filter_index)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getFilterLogs",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -272,7 +319,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_filter_changes_impl(filter_index)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getFilterChanges",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -286,7 +336,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_balance_impl(address, block.map(Into::into))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getBalance",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -300,7 +353,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_block_impl(BlockId::Number(block_number), full_transactions)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getBlockByNumber",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -314,7 +370,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_block_impl(BlockId::Hash(hash), full_transactions)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getBlockByHash",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -327,7 +386,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_block_transaction_count_impl(BlockId::Number(block_number))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getBlockTransactionCountByNumber",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -340,7 +402,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_block_transaction_count_impl(BlockId::Hash(block_hash))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getBlockTransactionCountByHash",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -354,7 +419,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_code_impl(address, block.map(Into::into))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getCode",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -369,7 +437,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_storage_at_impl(address, idx, block.map(Into::into))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getStorageAt",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -383,7 +454,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_transaction_count_impl(address, block.map(Into::into))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getTransactionCount",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -393,7 +467,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_transaction_impl(TransactionId::Hash(hash))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getTransactionByHash",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -407,7 +484,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_transaction_impl(TransactionId::Block(BlockId::Hash(block_hash), index))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getTransactionByBlockHashAndIndex",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -421,7 +501,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_transaction_impl(TransactionId::Block(BlockId::Number(block_number), index))
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getTransactionByBlockNumberAndIndex",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -431,7 +514,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .get_transaction_receipt_impl(hash)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_getTransactionReceipt",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -446,7 +532,23 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .send_raw_transaction_impl(tx_bytes)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_sendRawTransaction",
+                    self_.is_geth_compatible(),
+                ))
+        })
+    }
+
+    fn send_transaction(&self, req: CallRequest) -> BoxFuture<Result<H256>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .send_transaction_impl(req)
+                .await
+                .map_err(into_eth_jsrpc_error(
+                    "eth_sendTransaction",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 
@@ -504,7 +606,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceT for EthNamespa
             self_
                 .fee_history_impl(block_count, newest_block, reward_percentiles)
                 .await
-                .map_err(into_jsrpc_error)
+                .map_err(into_eth_jsrpc_error(
+                    "eth_feeHistory",
+                    self_.is_geth_compatible(),
+                ))
         })
     }
 }
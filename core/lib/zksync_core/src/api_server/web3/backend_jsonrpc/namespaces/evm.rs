@@ -0,0 +1,66 @@
+// Built-in uses
+
+// External uses
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+// Workspace uses
+use zksync_types::U64;
+
+// Local uses
+use crate::api_server::web3::{backend_jsonrpc::error::into_jsrpc_error, namespaces::EvmNamespace};
+
+#[rpc]
+pub trait EvmNamespaceT {
+    #[rpc(name = "evm_increaseTime")]
+    fn increase_time(&self, seconds: U64) -> BoxFuture<Result<String>>;
+
+    #[rpc(name = "evm_setNextBlockTimestamp")]
+    fn set_next_block_timestamp(&self, timestamp: U64) -> BoxFuture<Result<String>>;
+
+    #[rpc(name = "evm_mine")]
+    fn mine(&self, timestamp: Option<U64>) -> BoxFuture<Result<String>>;
+
+    #[rpc(name = "evm_snapshot")]
+    fn snapshot(&self) -> BoxFuture<Result<U64>>;
+
+    #[rpc(name = "evm_revert")]
+    fn revert(&self, snapshot_id: U64) -> BoxFuture<Result<bool>>;
+}
+
+impl EvmNamespaceT for EvmNamespace {
+    fn increase_time(&self, seconds: U64) -> BoxFuture<Result<String>> {
+        let self_ = self.clone();
+        Box::pin(async move { Ok(self_.increase_time_impl(seconds)) })
+    }
+
+    fn set_next_block_timestamp(&self, timestamp: U64) -> BoxFuture<Result<String>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .set_next_block_timestamp_impl(timestamp)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
+    fn mine(&self, timestamp: Option<U64>) -> BoxFuture<Result<String>> {
+        let self_ = self.clone();
+        Box::pin(async move { self_.mine_impl(timestamp).await.map_err(into_jsrpc_error) })
+    }
+
+    fn snapshot(&self) -> BoxFuture<Result<U64>> {
+        let self_ = self.clone();
+        Box::pin(async move { self_.snapshot_impl().await.map_err(into_jsrpc_error) })
+    }
+
+    fn revert(&self, snapshot_id: U64) -> BoxFuture<Result<bool>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .revert_impl(snapshot_id)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+}
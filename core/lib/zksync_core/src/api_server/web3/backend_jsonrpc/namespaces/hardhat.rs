@@ -0,0 +1,60 @@
+// Built-in uses
+
+// External uses
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+
+// Workspace uses
+use zksync_types::{Address, Bytes, U256};
+
+// Local uses
+use crate::api_server::web3::{
+    backend_jsonrpc::error::into_jsrpc_error, namespaces::HardhatNamespace,
+};
+
+#[rpc]
+pub trait HardhatNamespaceT {
+    #[rpc(name = "hardhat_impersonateAccount")]
+    fn impersonate_account(&self, address: Address) -> BoxFuture<Result<bool>>;
+
+    #[rpc(name = "hardhat_stopImpersonatingAccount")]
+    fn stop_impersonating_account(&self, address: Address) -> BoxFuture<Result<bool>>;
+
+    #[rpc(name = "hardhat_setBalance")]
+    fn set_balance(&self, address: Address, balance: U256) -> BoxFuture<Result<bool>>;
+
+    #[rpc(name = "hardhat_setCode")]
+    fn set_code(&self, address: Address, bytecode: Bytes) -> BoxFuture<Result<bool>>;
+}
+
+impl HardhatNamespaceT for HardhatNamespace {
+    fn impersonate_account(&self, address: Address) -> BoxFuture<Result<bool>> {
+        let self_ = self.clone();
+        Box::pin(async move { Ok(self_.impersonate_account_impl(address)) })
+    }
+
+    fn stop_impersonating_account(&self, address: Address) -> BoxFuture<Result<bool>> {
+        let self_ = self.clone();
+        Box::pin(async move { Ok(self_.stop_impersonating_account_impl(address)) })
+    }
+
+    fn set_balance(&self, address: Address, balance: U256) -> BoxFuture<Result<bool>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .set_balance_impl(address, balance)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
+    fn set_code(&self, address: Address, bytecode: Bytes) -> BoxFuture<Result<bool>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .set_code_impl(address, bytecode)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+}
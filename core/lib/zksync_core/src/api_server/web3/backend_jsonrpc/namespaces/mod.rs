@@ -1,6 +1,8 @@
 pub mod debug;
 pub mod en;
 pub mod eth;
+pub mod evm;
+pub mod hardhat;
 pub mod net;
 pub mod web3;
 pub mod zks;
@@ -9,10 +9,12 @@ use jsonrpc_derive::rpc;
 // Workspace uses
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BridgeAddresses, BridgeTransfer, L1BatchDetails, L2ToL1LogProof,
+        PaymasterPolicies, PriorityOpStatus, ProtocolVersion, TransactionDetails,
+        TransactionJourney,
     },
     fee::Fee,
+    pubdata::L1BatchPubdata,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
@@ -36,6 +38,9 @@ pub trait ZksNamespaceT {
     #[rpc(name = "zks_getTestnetPaymaster")]
     fn get_testnet_paymaster(&self) -> BoxFuture<Result<Option<Address>>>;
 
+    #[rpc(name = "zks_getPaymasterPolicies")]
+    fn get_paymaster_policies(&self) -> BoxFuture<Result<PaymasterPolicies>>;
+
     #[rpc(name = "zks_getBridgeContracts")]
     fn get_bridge_contracts(&self) -> BoxFuture<Result<BridgeAddresses>>;
 
@@ -85,6 +90,20 @@ pub trait ZksNamespaceT {
     #[rpc(name = "zks_getTransactionDetails")]
     fn get_transaction_details(&self, hash: H256) -> BoxFuture<Result<Option<TransactionDetails>>>;
 
+    #[rpc(name = "zks_getPriorityOpStatus")]
+    fn get_priority_op_status(&self, l1_tx_hash: H256) -> BoxFuture<Result<Vec<PriorityOpStatus>>>;
+
+    #[rpc(name = "zks_getTransactionJourney")]
+    fn get_transaction_journey(&self, hash: H256) -> BoxFuture<Result<Option<TransactionJourney>>>;
+
+    #[rpc(name = "zks_getBridgeTransfers")]
+    fn get_bridge_transfers(
+        &self,
+        address: Address,
+        from: u32,
+        limit: u8,
+    ) -> BoxFuture<Result<Vec<BridgeTransfer>>>;
+
     #[rpc(name = "zks_getRawBlockTransactions")]
     fn get_raw_block_transactions(
         &self,
@@ -97,6 +116,12 @@ pub trait ZksNamespaceT {
         batch: L1BatchNumber,
     ) -> BoxFuture<Result<Option<L1BatchDetails>>>;
 
+    #[rpc(name = "zks_getL1BatchPubdata")]
+    fn get_l1_batch_pubdata(
+        &self,
+        batch: L1BatchNumber,
+    ) -> BoxFuture<Result<Option<L1BatchPubdata>>>;
+
     #[rpc(name = "zks_getBytecodeByHash")]
     fn get_bytecode_by_hash(&self, hash: H256) -> BoxFuture<Result<Option<Vec<u8>>>>;
 
@@ -149,6 +174,11 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceT for ZksNamespa
         Box::pin(async move { Ok(self_.get_testnet_paymaster_impl()) })
     }
 
+    fn get_paymaster_policies(&self) -> BoxFuture<Result<PaymasterPolicies>> {
+        let self_ = self.clone();
+        Box::pin(async move { Ok(self_.get_paymaster_policies_impl()) })
+    }
+
     fn get_bridge_contracts(&self) -> BoxFuture<Result<BridgeAddresses>> {
         let self_ = self.clone();
         Box::pin(async move { Ok(self_.get_bridge_contracts_impl()) })
@@ -255,6 +285,41 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceT for ZksNamespa
         })
     }
 
+    fn get_priority_op_status(&self, l1_tx_hash: H256) -> BoxFuture<Result<Vec<PriorityOpStatus>>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .get_priority_op_status_impl(l1_tx_hash)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
+    fn get_transaction_journey(&self, hash: H256) -> BoxFuture<Result<Option<TransactionJourney>>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .get_transaction_journey_impl(hash)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
+    fn get_bridge_transfers(
+        &self,
+        address: Address,
+        from: u32,
+        limit: u8,
+    ) -> BoxFuture<Result<Vec<BridgeTransfer>>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .get_bridge_transfers_impl(address, from, limit)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
     fn get_raw_block_transactions(
         &self,
         block_number: MiniblockNumber,
@@ -281,6 +346,19 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceT for ZksNamespa
         })
     }
 
+    fn get_l1_batch_pubdata(
+        &self,
+        batch: L1BatchNumber,
+    ) -> BoxFuture<Result<Option<L1BatchPubdata>>> {
+        let self_ = self.clone();
+        Box::pin(async move {
+            self_
+                .get_l1_batch_pubdata_impl(batch)
+                .await
+                .map_err(into_jsrpc_error)
+        })
+    }
+
     fn get_bytecode_by_hash(&self, hash: H256) -> BoxFuture<Result<Option<Vec<u8>>>> {
         let self_ = self.clone();
         Box::pin(async move { Ok(self_.get_bytecode_by_hash_impl(hash).await) })
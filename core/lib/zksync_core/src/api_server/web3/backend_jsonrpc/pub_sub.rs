@@ -5,8 +5,6 @@ use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::typed;
 use jsonrpc_pubsub::{Session, SubscriptionId};
 
-use zksync_web3_decl::types::PubSubResult;
-
 use super::super::namespaces::EthSubscribe;
 use super::batch_limiter_middleware::RateLimitMetadata;
 
@@ -18,7 +16,7 @@ pub trait Web3PubSub {
     fn subscribe(
         &self,
         meta: Self::Metadata,
-        subscriber: typed::Subscriber<PubSubResult>,
+        subscriber: typed::Subscriber<serde_json::Value>,
         sub_type: String,
         params: Option<serde_json::Value>,
     );
@@ -41,7 +39,7 @@ impl Web3PubSub for EthSubscribe {
     fn subscribe(
         &self,
         _meta: Self::Metadata,
-        subscriber: typed::Subscriber<PubSubResult>,
+        subscriber: typed::Subscriber<serde_json::Value>,
         sub_type: String,
         params: Option<serde_json::Value>,
     ) {
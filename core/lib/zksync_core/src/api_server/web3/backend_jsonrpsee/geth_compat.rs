@@ -0,0 +1,141 @@
+//! Strict geth compatibility mode for the `jsonrpsee` backend. `jsonrpsee` doesn't provide hooks
+//! to post-process method responses, so `eth_` methods with results deviating from geth are re-registered
+//! in the RPC module with handlers adapting the serialized result. Since this happens on the RPC module level,
+//! it works for both HTTP and WS transports without buffering raw request / response bodies.
+
+use serde::Serialize;
+
+use std::future::Future;
+
+use zksync_web3_decl::{
+    jsonrpsee::{
+        core::RpcResult,
+        types::{ErrorObjectOwned, Params},
+        RpcModule,
+    },
+    namespaces::eth::EthNamespaceServer,
+};
+
+use super::from_std_error;
+use crate::{
+    api_server::web3::{geth_compat, namespaces::EthNamespace},
+    l1_gas_price::L1GasPriceProvider,
+};
+
+/// Replaces the handler for `method` with the one calling `handler` and adapting its result.
+fn override_method<G, T, F, Fut>(
+    rpc: &mut RpcModule<()>,
+    method: &'static str,
+    namespace: &EthNamespace<G>,
+    handler: F,
+) where
+    G: L1GasPriceProvider + Send + Sync + 'static,
+    T: Serialize,
+    F: Fn(EthNamespace<G>, Params<'static>) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = RpcResult<T>> + Send,
+{
+    assert!(
+        geth_compat::adapts_result(method),
+        "`{method}` result is not adapted"
+    );
+    rpc.remove_method(method)
+        .unwrap_or_else(|| panic!("`{method}` is not registered"));
+
+    let namespace = namespace.clone();
+    rpc.register_async_method(method, move |params, _| {
+        let namespace = namespace.clone();
+        let handler = handler.clone();
+        async move {
+            let result = handler(namespace, params).await?;
+            let mut result = serde_json::to_value(result).map_err(from_std_error)?;
+            geth_compat::adapt_result(method, &mut result);
+            Ok::<_, ErrorObjectOwned>(result)
+        }
+    })
+    .unwrap_or_else(|err| panic!("failed registering `{method}`: {err}"));
+}
+
+/// Overrides `eth_` methods in the module so that their results follow geth conventions.
+/// The module must already contain the `eth_` namespace.
+pub(crate) fn override_eth_methods<G>(rpc: &mut RpcModule<()>, namespace: &EthNamespace<G>)
+where
+    G: L1GasPriceProvider + Send + Sync + 'static,
+{
+    override_method(
+        rpc,
+        "eth_getBlockByNumber",
+        namespace,
+        |eth, params| async move {
+            let mut seq = params.sequence();
+            let (block_number, full_transactions) = (seq.next()?, seq.next()?);
+            eth.get_block_by_number(block_number, full_transactions)
+                .await
+        },
+    );
+    override_method(
+        rpc,
+        "eth_getBlockByHash",
+        namespace,
+        |eth, params| async move {
+            let mut seq = params.sequence();
+            let (hash, full_transactions) = (seq.next()?, seq.next()?);
+            eth.get_block_by_hash(hash, full_transactions).await
+        },
+    );
+    override_method(
+        rpc,
+        "eth_getTransactionByHash",
+        namespace,
+        |eth, params| async move { eth.get_transaction_by_hash(params.one()?).await },
+    );
+    override_method(
+        rpc,
+        "eth_getTransactionByBlockHashAndIndex",
+        namespace,
+        |eth, params| async move {
+            let mut seq = params.sequence();
+            let (block_hash, index) = (seq.next()?, seq.next()?);
+            eth.get_transaction_by_block_hash_and_index(block_hash, index)
+                .await
+        },
+    );
+    override_method(
+        rpc,
+        "eth_getTransactionByBlockNumberAndIndex",
+        namespace,
+        |eth, params| async move {
+            let mut seq = params.sequence();
+            let (block_number, index) = (seq.next()?, seq.next()?);
+            eth.get_transaction_by_block_number_and_index(block_number, index)
+                .await
+        },
+    );
+    override_method(
+        rpc,
+        "eth_getTransactionReceipt",
+        namespace,
+        |eth, params| async move { eth.get_transaction_receipt(params.one()?).await },
+    );
+    override_method(rpc, "eth_getLogs", namespace, |eth, params| async move {
+        eth.get_logs(params.one()?).await
+    });
+    override_method(
+        rpc,
+        "eth_getFilterLogs",
+        namespace,
+        |eth, params| async move { eth.get_filter_logs(params.one()?).await },
+    );
+    override_method(
+        rpc,
+        "eth_getFilterChanges",
+        namespace,
+        |eth, params| async move { eth.get_filter_changes(params.one()?).await },
+    );
+    override_method(rpc, "eth_feeHistory", namespace, |eth, params| async move {
+        let mut seq = params.sequence();
+        let (block_count, newest_block, reward_percentiles) =
+            (seq.next()?, seq.next()?, seq.next()?);
+        eth.fee_history(block_count, newest_block, reward_percentiles)
+            .await
+    });
+}
@@ -6,6 +6,9 @@ use std::error::Error;
 use zksync_web3_decl::error::Web3Error;
 use zksync_web3_decl::jsonrpsee::types::{error::ErrorCode, ErrorObjectOwned};
 
+use super::geth_compat as geth;
+
+pub(crate) mod geth_compat;
 pub mod namespaces;
 
 pub fn from_std_error(e: impl Error) -> ErrorObjectOwned {
@@ -25,7 +28,8 @@ pub fn into_jsrpc_error(err: Web3Error) -> ErrorObjectOwned {
             | Web3Error::InvalidFeeParams(_)
             | Web3Error::InvalidFilterBlockHash
             | Web3Error::LogsLimitExceeded(_, _, _)
-            | Web3Error::TooManyLogs(_) => ErrorCode::InvalidParams.code(),
+            | Web3Error::TooManyLogs(_)
+            | Web3Error::ResponseTooLarge(_) => ErrorCode::InvalidParams.code(),
             Web3Error::SubmitTransactionError(_, _) | Web3Error::SerializationError(_) => 3,
             Web3Error::PubSubTimeout => 4,
             Web3Error::RequestTimeout => 5,
@@ -40,3 +44,17 @@ pub fn into_jsrpc_error(err: Web3Error) -> ErrorObjectOwned {
         },
     )
 }
+
+/// Returns a converter for errors returned by the specified `eth_` method. If `geth_compatible` is set,
+/// errors follow geth conventions; see [`geth`] for details.
+pub fn into_eth_jsrpc_error(
+    method: &'static str,
+    geth_compatible: bool,
+) -> impl FnOnce(Web3Error) -> ErrorObjectOwned {
+    move |err| match geth_compatible.then(|| geth::geth_error(method, &err)) {
+        Some(Some(geth_err)) => {
+            ErrorObjectOwned::owned(geth_err.code as i32, geth_err.message, None::<()>)
+        }
+        _ => into_jsrpc_error(err),
+    }
+}
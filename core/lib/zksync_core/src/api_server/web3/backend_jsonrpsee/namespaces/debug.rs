@@ -1,5 +1,8 @@
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, BytecodeCompressionComparison, DebugCall, L1BatchSealingStatus,
+        ResultDebugCall, TracerConfig,
+    },
     transaction_request::CallRequest,
     H256,
 };
@@ -47,4 +50,17 @@ impl DebugNamespaceServer for DebugNamespace {
     ) -> RpcResult<Option<DebugCall>> {
         Ok(self.debug_trace_transaction_impl(tx_hash, options).await)
     }
+    async fn get_l1_batch_sealing_status(&self) -> RpcResult<Option<L1BatchSealingStatus>> {
+        self.debug_get_l1_batch_sealing_status_impl()
+            .map_err(into_jsrpc_error)
+    }
+    async fn compare_bytecode_compression(
+        &self,
+        request: CallRequest,
+        block: Option<BlockId>,
+    ) -> RpcResult<BytecodeCompressionComparison> {
+        self.debug_compare_bytecode_compression_impl(request, block)
+            .await
+            .map_err(into_jsrpc_error)
+    }
 }
@@ -14,14 +14,19 @@ use zksync_web3_decl::{
 };
 
 use crate::{
-    api_server::web3::{backend_jsonrpsee::into_jsrpc_error, EthNamespace},
+    api_server::web3::{backend_jsonrpsee::into_eth_jsrpc_error, EthNamespace},
     l1_gas_price::L1GasPriceProvider,
 };
 
 #[async_trait]
 impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNamespace<G> {
     async fn get_block_number(&self) -> RpcResult<U64> {
-        self.get_block_number_impl().await.map_err(into_jsrpc_error)
+        self.get_block_number_impl()
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_blockNumber",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn chain_id(&self) -> RpcResult<U64> {
@@ -31,25 +36,50 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     async fn call(&self, req: CallRequest, block: Option<BlockIdVariant>) -> RpcResult<Bytes> {
         self.call_impl(req, block.map(Into::into))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error("eth_call", self.is_geth_compatible()))
     }
 
     async fn estimate_gas(&self, req: CallRequest, block: Option<BlockNumber>) -> RpcResult<U256> {
         self.estimate_gas_impl(req, block)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_estimateGas",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn gas_price(&self) -> RpcResult<U256> {
-        self.gas_price_impl().map_err(into_jsrpc_error)
+        self.gas_price_impl().await.map_err(into_eth_jsrpc_error(
+            "eth_gasPrice",
+            self.is_geth_compatible(),
+        ))
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+        self.max_priority_fee_per_gas_impl()
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_maxPriorityFeePerGas",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
-        self.new_filter_impl(filter).await.map_err(into_jsrpc_error)
+        self.new_filter_impl(filter)
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_newFilter",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn new_block_filter(&self) -> RpcResult<U256> {
-        self.new_block_filter_impl().await.map_err(into_jsrpc_error)
+        self.new_block_filter_impl()
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_newBlockFilter",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn uninstall_filter(&self, idx: U256) -> RpcResult<bool> {
@@ -61,19 +91,30 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     }
 
     async fn get_logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
-        self.get_logs_impl(filter).await.map_err(into_jsrpc_error)
+        self.get_logs_impl(filter)
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_getLogs",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_filter_logs(&self, filter_index: U256) -> RpcResult<FilterChanges> {
         self.get_filter_logs_impl(filter_index)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getFilterLogs",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_filter_changes(&self, filter_index: U256) -> RpcResult<FilterChanges> {
         self.get_filter_changes_impl(filter_index)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getFilterChanges",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_balance(
@@ -83,7 +124,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<U256> {
         self.get_balance_impl(address, block.map(Into::into))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getBalance",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_block_by_number(
@@ -93,7 +137,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<Block<TransactionVariant>>> {
         self.get_block_impl(BlockId::Number(block_number), full_transactions)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getBlockByNumber",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_block_by_hash(
@@ -103,7 +150,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<Block<TransactionVariant>>> {
         self.get_block_impl(BlockId::Hash(hash), full_transactions)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getBlockByHash",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_block_transaction_count_by_number(
@@ -112,7 +162,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<U256>> {
         self.get_block_transaction_count_impl(BlockId::Number(block_number))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getBlockTransactionCountByNumber",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_block_transaction_count_by_hash(
@@ -121,13 +174,19 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<U256>> {
         self.get_block_transaction_count_impl(BlockId::Hash(block_hash))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getBlockTransactionCountByHash",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_code(&self, address: Address, block: Option<BlockIdVariant>) -> RpcResult<Bytes> {
         self.get_code_impl(address, block.map(Into::into))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getCode",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_storage_at(
@@ -138,7 +197,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<H256> {
         self.get_storage_at_impl(address, idx, block.map(Into::into))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getStorageAt",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_transaction_count(
@@ -148,13 +210,19 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<U256> {
         self.get_transaction_count_impl(address, block.map(Into::into))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getTransactionCount",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_transaction_by_hash(&self, hash: H256) -> RpcResult<Option<Transaction>> {
         self.get_transaction_impl(TransactionId::Hash(hash))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getTransactionByHash",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_transaction_by_block_hash_and_index(
@@ -164,7 +232,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<Transaction>> {
         self.get_transaction_impl(TransactionId::Block(BlockId::Hash(block_hash), index))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getTransactionByBlockHashAndIndex",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_transaction_by_block_number_and_index(
@@ -174,13 +245,19 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<Option<Transaction>> {
         self.get_transaction_impl(TransactionId::Block(BlockId::Number(block_number), index))
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getTransactionByBlockNumberAndIndex",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn get_transaction_receipt(&self, hash: H256) -> RpcResult<Option<TransactionReceipt>> {
         self.get_transaction_receipt_impl(hash)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_getTransactionReceipt",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn protocol_version(&self) -> RpcResult<String> {
@@ -190,7 +267,19 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     async fn send_raw_transaction(&self, tx_bytes: Bytes) -> RpcResult<H256> {
         self.send_raw_transaction_impl(tx_bytes)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_sendRawTransaction",
+                self.is_geth_compatible(),
+            ))
+    }
+
+    async fn send_transaction(&self, req: CallRequest) -> RpcResult<H256> {
+        self.send_transaction_impl(req)
+            .await
+            .map_err(into_eth_jsrpc_error(
+                "eth_sendTransaction",
+                self.is_geth_compatible(),
+            ))
     }
 
     async fn syncing(&self) -> RpcResult<SyncState> {
@@ -236,6 +325,9 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> EthNamespaceServer for EthNa
     ) -> RpcResult<FeeHistory> {
         self.fee_history_impl(block_count, newest_block, reward_percentiles)
             .await
-            .map_err(into_jsrpc_error)
+            .map_err(into_eth_jsrpc_error(
+                "eth_feeHistory",
+                self.is_geth_compatible(),
+            ))
     }
 }
@@ -0,0 +1,34 @@
+use zksync_types::U64;
+use zksync_web3_decl::{
+    jsonrpsee::core::{async_trait, RpcResult},
+    namespaces::evm::EvmNamespaceServer,
+};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::EvmNamespace};
+
+#[async_trait]
+impl EvmNamespaceServer for EvmNamespace {
+    async fn increase_time(&self, seconds: U64) -> RpcResult<String> {
+        Ok(self.increase_time_impl(seconds))
+    }
+
+    async fn set_next_block_timestamp(&self, timestamp: U64) -> RpcResult<String> {
+        self.set_next_block_timestamp_impl(timestamp)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn mine(&self, timestamp: Option<U64>) -> RpcResult<String> {
+        self.mine_impl(timestamp).await.map_err(into_jsrpc_error)
+    }
+
+    async fn snapshot(&self) -> RpcResult<U64> {
+        self.snapshot_impl().await.map_err(into_jsrpc_error)
+    }
+
+    async fn revert(&self, snapshot_id: U64) -> RpcResult<bool> {
+        self.revert_impl(snapshot_id)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
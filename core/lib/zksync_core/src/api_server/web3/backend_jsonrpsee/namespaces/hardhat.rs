@@ -0,0 +1,30 @@
+use zksync_types::{Address, Bytes, U256};
+use zksync_web3_decl::{
+    jsonrpsee::core::{async_trait, RpcResult},
+    namespaces::hardhat::HardhatNamespaceServer,
+};
+
+use crate::api_server::web3::{backend_jsonrpsee::into_jsrpc_error, namespaces::HardhatNamespace};
+
+#[async_trait]
+impl HardhatNamespaceServer for HardhatNamespace {
+    async fn impersonate_account(&self, address: Address) -> RpcResult<bool> {
+        Ok(self.impersonate_account_impl(address))
+    }
+
+    async fn stop_impersonating_account(&self, address: Address) -> RpcResult<bool> {
+        Ok(self.stop_impersonating_account_impl(address))
+    }
+
+    async fn set_balance(&self, address: Address, balance: U256) -> RpcResult<bool> {
+        self.set_balance_impl(address, balance)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn set_code(&self, address: Address, bytecode: Bytes) -> RpcResult<bool> {
+        self.set_code_impl(address, bytecode)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+}
@@ -2,6 +2,8 @@ pub mod debug;
 pub mod en;
 pub mod eth;
 pub mod eth_subscribe;
+pub mod evm;
+pub mod hardhat;
 pub mod net;
 pub mod web3;
 pub mod zks;
@@ -4,10 +4,12 @@ use std::collections::HashMap;
 
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, L1BatchDetails, L2ToL1LogProof, ProtocolVersion,
-        TransactionDetails,
+        BlockDetails, BridgeAddresses, BridgeTransfer, L1BatchDetails, L2ToL1LogProof,
+        PaymasterPolicies, PriorityOpStatus, ProtocolVersion, TransactionDetails,
+        TransactionJourney,
     },
     fee::Fee,
+    pubdata::L1BatchPubdata,
     transaction_request::CallRequest,
     Address, L1BatchNumber, MiniblockNumber, H256, U256, U64,
 };
@@ -42,6 +44,10 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceServer for ZksNa
         Ok(self.get_testnet_paymaster_impl())
     }
 
+    async fn get_paymaster_policies(&self) -> RpcResult<PaymasterPolicies> {
+        Ok(self.get_paymaster_policies_impl())
+    }
+
     async fn get_bridge_contracts(&self) -> RpcResult<BridgeAddresses> {
         Ok(self.get_bridge_contracts_impl())
     }
@@ -120,6 +126,29 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceServer for ZksNa
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_priority_op_status(&self, l1_tx_hash: H256) -> RpcResult<Vec<PriorityOpStatus>> {
+        self.get_priority_op_status_impl(l1_tx_hash)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_transaction_journey(&self, hash: H256) -> RpcResult<Option<TransactionJourney>> {
+        self.get_transaction_journey_impl(hash)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
+    async fn get_bridge_transfers(
+        &self,
+        address: Address,
+        from: u32,
+        limit: u8,
+    ) -> RpcResult<Vec<BridgeTransfer>> {
+        self.get_bridge_transfers_impl(address, from, limit)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn get_raw_block_transactions(
         &self,
         block_number: MiniblockNumber,
@@ -138,6 +167,15 @@ impl<G: L1GasPriceProvider + Send + Sync + 'static> ZksNamespaceServer for ZksNa
             .map_err(into_jsrpc_error)
     }
 
+    async fn get_l1_batch_pubdata(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> RpcResult<Option<L1BatchPubdata>> {
+        self.get_l1_batch_pubdata_impl(batch_number)
+            .await
+            .map_err(into_jsrpc_error)
+    }
+
     async fn get_bytecode_by_hash(&self, hash: H256) -> RpcResult<Option<Vec<u8>>> {
         Ok(self.get_bytecode_by_hash_impl(hash).await)
     }
@@ -0,0 +1,208 @@
+//! Strict geth compatibility mode for the `eth_` namespace.
+//!
+//! zkSync extends several Ethereum response objects with L1 batch information and uses its own
+//! error codes. Generic Ethereum tooling (indexers, block explorers, client libraries with strict
+//! schema validation) may choke on these deviations. When the mode is enabled, responses are
+//! post-processed so that they follow geth conventions:
+//!
+//! - zkSync-specific fields (e.g., `l1BatchNumber`, `l2ToL1Logs`, `logType`) are removed
+//!   from blocks, transactions, receipts and logs;
+//! - fields that geth never returns for a particular object are omitted (e.g., EIP-1559 fee fields
+//!   for legacy transactions, `reward` in `eth_feeHistory` if no percentiles were requested);
+//! - application errors are returned with the generic `-32000` code and geth error messages.
+//!
+//! Errors are mapped from [`Web3Error`] variants by the `eth_` namespace glue of each backend
+//! (see [`geth_error()`]). Results are adapted on the JSON level for the methods listed in
+//! [`adapts_result()`]: the `jsonrpc` backend does this in a call middleware, and the `jsonrpsee` backend
+//! overrides these methods in the RPC module. Both approaches work for HTTP and WS transports; log
+//! notifications for `eth_subscribe` are adapted by the pubsub notifier.
+
+use serde_json::{Map, Value};
+
+use zksync_web3_decl::error::Web3Error;
+
+/// Error code used by geth for all errors that don't have a dedicated code.
+const GENERIC_ERROR_CODE: i64 = -32000;
+/// Error code used by geth for request timeouts.
+const TIMEOUT_ERROR_CODE: i64 = -32002;
+
+const BLOCK_EXTRA_FIELDS: &[&str] = &["l1BatchNumber", "l1BatchTimestamp", "sealFields"];
+const TRANSACTION_EXTRA_FIELDS: &[&str] = &["l1BatchNumber", "l1BatchTxIndex", "raw"];
+const RECEIPT_EXTRA_FIELDS: &[&str] = &[
+    "l1BatchNumber",
+    "l1BatchTxIndex",
+    "l2ToL1Logs",
+    "gasRefunded",
+];
+const LOG_EXTRA_FIELDS: &[&str] = &["l1BatchNumber", "transactionLogIndex", "logType"];
+
+/// Checks whether results of the specified method are adapted by the compatibility mode.
+pub(crate) fn adapts_result(method: &str) -> bool {
+    matches!(
+        method,
+        "eth_getBlockByNumber"
+            | "eth_getBlockByHash"
+            | "eth_getTransactionByHash"
+            | "eth_getTransactionByBlockHashAndIndex"
+            | "eth_getTransactionByBlockNumberAndIndex"
+            | "eth_getTransactionReceipt"
+            | "eth_getLogs"
+            | "eth_getFilterLogs"
+            | "eth_getFilterChanges"
+            | "eth_feeHistory"
+    )
+}
+
+/// Adapts a successful result returned by the specified method.
+pub(crate) fn adapt_result(method: &str, result: &mut Value) {
+    match method {
+        "eth_getBlockByNumber" | "eth_getBlockByHash" => adapt_block(result),
+        "eth_getTransactionByHash"
+        | "eth_getTransactionByBlockHashAndIndex"
+        | "eth_getTransactionByBlockNumberAndIndex" => adapt_transaction(result),
+        "eth_getTransactionReceipt" => adapt_receipt(result),
+        "eth_getLogs" | "eth_getFilterLogs" | "eth_getFilterChanges" => {
+            // Filter changes may also consist of block / transaction hashes; these are left as is.
+            if let Value::Array(logs) = result {
+                logs.iter_mut().for_each(adapt_log);
+            }
+        }
+        "eth_feeHistory" => adapt_fee_history(result),
+        _ => { /* Response doesn't deviate from geth */ }
+    }
+}
+
+/// Application error as returned by geth.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GethError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl GethError {
+    fn generic(message: impl Into<String>) -> Self {
+        Self {
+            code: GENERIC_ERROR_CODE,
+            message: message.into(),
+        }
+    }
+}
+
+/// Maps an error returned by the specified `eth_` method to the error geth would return in the same situation.
+/// Returns `None` if the error should be returned as is (e.g., for reverts, which use the same code and data
+/// in geth, or for internal errors).
+pub(crate) fn geth_error(method: &str, err: &Web3Error) -> Option<GethError> {
+    Some(match err {
+        Web3Error::NoBlock => {
+            let message = if matches!(method, "eth_getLogs" | "eth_newFilter") {
+                "unknown block"
+            } else {
+                "header not found"
+            };
+            GethError::generic(message)
+        }
+        Web3Error::FilterNotFound => GethError::generic("filter not found"),
+        Web3Error::TooManyTopics => GethError::generic("exceed max topics"),
+        Web3Error::RequestTimeout => GethError {
+            code: TIMEOUT_ERROR_CODE,
+            message: "request timed out".to_owned(),
+        },
+        // geth reserves code 3 for reverts; other transaction submission errors
+        // (e.g., "nonce too low") use the generic code and have no data.
+        Web3Error::SubmitTransactionError(message, _) => {
+            if message.starts_with("execution reverted") {
+                return None;
+            }
+            GethError::generic(message.clone())
+        }
+        // zkSync reports these application errors as invalid params or with the revert code,
+        // while geth uses the generic code.
+        Web3Error::SerializationError(_)
+        | Web3Error::RLPError(_)
+        | Web3Error::InvalidTransactionData(_)
+        | Web3Error::NoSuchFunction
+        | Web3Error::InvalidFeeParams(_)
+        | Web3Error::LogsLimitExceeded(..)
+        | Web3Error::TooManyLogs(_)
+        | Web3Error::ResponseTooLarge(_)
+        | Web3Error::InvalidFilterBlockHash => GethError::generic(err.to_string()),
+        Web3Error::InternalError | Web3Error::NotImplemented | Web3Error::PubSubTimeout => {
+            return None;
+        }
+    })
+}
+
+fn remove_fields(object: &mut Map<String, Value>, fields: &[&str]) {
+    for &field in fields {
+        object.remove(field);
+    }
+}
+
+fn adapt_block(block: &mut Value) {
+    let Value::Object(block) = block else {
+        return;
+    };
+    remove_fields(block, BLOCK_EXTRA_FIELDS);
+    if let Some(Value::Array(transactions)) = block.get_mut("transactions") {
+        // For blocks without full transactions, this is a no-op since transactions are hashes.
+        transactions.iter_mut().for_each(adapt_transaction);
+    }
+}
+
+fn adapt_transaction(transaction: &mut Value) {
+    let Value::Object(transaction) = transaction else {
+        return;
+    };
+    remove_fields(transaction, TRANSACTION_EXTRA_FIELDS);
+    let is_legacy = matches!(transaction.get("type"), Some(Value::String(ty)) if ty == "0x0");
+    if is_legacy {
+        transaction.remove("maxFeePerGas");
+        transaction.remove("maxPriorityFeePerGas");
+    }
+}
+
+fn adapt_receipt(receipt: &mut Value) {
+    let Value::Object(receipt) = receipt else {
+        return;
+    };
+    remove_fields(receipt, RECEIPT_EXTRA_FIELDS);
+    // geth only returns `root` for pre-Byzantium receipts, which always have it set.
+    if matches!(receipt.get("root"), Some(Value::Null)) {
+        receipt.remove("root");
+    }
+    if let Some(Value::Array(logs)) = receipt.get_mut("logs") {
+        logs.iter_mut().for_each(adapt_log);
+    }
+}
+
+/// Adapts a single log. Besides `eth_getLogs` and similar methods, this is used for log notifications.
+pub(crate) fn adapt_log(log: &mut Value) {
+    let Value::Object(log) = log else {
+        return;
+    };
+    remove_fields(log, LOG_EXTRA_FIELDS);
+    let removed = log.entry("removed").or_insert(Value::Bool(false));
+    if removed.is_null() {
+        *removed = Value::Bool(false);
+    }
+}
+
+fn adapt_fee_history(fee_history: &mut Value) {
+    let Value::Object(fee_history) = fee_history else {
+        return;
+    };
+    // geth omits rewards if no reward percentiles were requested.
+    let has_no_rewards = match fee_history.get("reward") {
+        Some(Value::Array(rewards)) => rewards
+            .iter()
+            .all(|reward| matches!(reward, Value::Array(reward) if reward.is_empty())),
+        Some(Value::Null) => true,
+        _ => false,
+    };
+    if has_no_rewards {
+        fee_history.remove("reward");
+    }
+}
+
+#[cfg(test)]
+mod tests;
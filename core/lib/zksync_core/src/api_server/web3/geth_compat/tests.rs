@@ -0,0 +1,442 @@
+//! Tests for the strict geth compatibility mode. Besides unit tests for individual transformations,
+//! adapted responses are compared with geth responses stored in the `fixtures` directory.
+
+use serde_json::json;
+
+use std::collections::BTreeSet;
+
+use zksync_types::{
+    api::{self, TransactionVariant},
+    web3::types::{BlockNumber, FeeHistory},
+    Address, Bytes, H256, U256,
+};
+
+use super::*;
+
+fn adapted(method: &str, mut result: Value) -> Value {
+    adapt_result(method, &mut result);
+    result
+}
+
+fn zksync_log() -> Value {
+    json!({
+        "address": "0x000000000000000000000000000000000000800a",
+        "topics": [],
+        "data": "0x",
+        "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "blockNumber": "0x1",
+        "l1BatchNumber": "0x1",
+        "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "transactionLogIndex": "0x0",
+        "logType": null,
+        "removed": null,
+    })
+}
+
+fn geth_log() -> Value {
+    json!({
+        "address": "0x000000000000000000000000000000000000800a",
+        "topics": [],
+        "data": "0x",
+        "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "blockNumber": "0x1",
+        "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false,
+    })
+}
+
+fn zksync_transaction(ty: &str) -> Value {
+    json!({
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "nonce": "0x0",
+        "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "blockNumber": "0x1",
+        "transactionIndex": "0x0",
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": "0x0000000000000000000000000000000000000002",
+        "value": "0x0",
+        "gasPrice": "0x1",
+        "gas": "0x5208",
+        "input": "0x",
+        "type": ty,
+        "maxFeePerGas": "0x1",
+        "maxPriorityFeePerGas": "0x0",
+        "chainId": "0x10e",
+        "l1BatchNumber": "0x1",
+        "l1BatchTxIndex": "0x0",
+    })
+}
+
+#[test]
+fn block_fields_are_removed() {
+    let block = json!({
+        "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "number": "0x1",
+        "l1BatchNumber": "0x1",
+        "l1BatchTimestamp": "0x64",
+        "sealFields": [],
+        "uncles": [],
+        "transactions": [zksync_transaction("0x2")],
+    });
+    let response = adapted("eth_getBlockByNumber", block);
+
+    let block = response.as_object().unwrap();
+    for field in BLOCK_EXTRA_FIELDS {
+        assert!(!block.contains_key(*field), "{field}");
+    }
+    assert_eq!(block["number"], "0x1");
+    let transaction = block["transactions"][0].as_object().unwrap();
+    assert!(!transaction.contains_key("l1BatchNumber"));
+    assert_eq!(transaction["maxFeePerGas"], "0x1");
+}
+
+#[test]
+fn block_with_transaction_hashes_is_adapted() {
+    let hash = "0x0000000000000000000000000000000000000000000000000000000000000002";
+    let block = json!({ "number": "0x1", "l1BatchNumber": null, "transactions": [hash] });
+    let response = adapted("eth_getBlockByHash", block);
+    assert_eq!(response, json!({ "number": "0x1", "transactions": [hash] }));
+}
+
+#[test]
+fn legacy_transaction_has_no_eip1559_fields() {
+    let response = adapted("eth_getTransactionByHash", zksync_transaction("0x0"));
+
+    let transaction = response.as_object().unwrap();
+    for field in [
+        "l1BatchNumber",
+        "l1BatchTxIndex",
+        "maxFeePerGas",
+        "maxPriorityFeePerGas",
+    ] {
+        assert!(!transaction.contains_key(field), "{field}");
+    }
+    assert_eq!(transaction["gasPrice"], "0x1");
+    assert_eq!(transaction["chainId"], "0x10e");
+}
+
+#[test]
+fn receipt_fields_are_removed() {
+    let receipt = json!({
+        "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+        "l1BatchNumber": "0x1",
+        "l1BatchTxIndex": "0x0",
+        "l2ToL1Logs": [],
+        "gasRefunded": "0x5208",
+        "root": null,
+        "status": "0x1",
+        "logs": [zksync_log()],
+    });
+    let response = adapted("eth_getTransactionReceipt", receipt);
+
+    assert_eq!(
+        response,
+        json!({
+            "transactionHash": "0x0000000000000000000000000000000000000000000000000000000000000002",
+            "status": "0x1",
+            "logs": [geth_log()],
+        })
+    );
+}
+
+#[test]
+fn logs_are_adapted() {
+    for method in ["eth_getLogs", "eth_getFilterLogs", "eth_getFilterChanges"] {
+        let response = adapted(method, json!([zksync_log(), zksync_log()]));
+        assert_eq!(response, json!([geth_log(), geth_log()]), "{method}");
+    }
+
+    let hash = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let response = adapted("eth_getFilterChanges", json!([hash]));
+    assert_eq!(response, json!([hash]));
+}
+
+#[test]
+fn null_results_are_preserved() {
+    for method in [
+        "eth_getBlockByNumber",
+        "eth_getTransactionByHash",
+        "eth_getTransactionReceipt",
+    ] {
+        assert!(adapts_result(method), "{method}");
+        assert_eq!(adapted(method, Value::Null), Value::Null, "{method}");
+    }
+}
+
+#[test]
+fn fee_history_without_percentiles_has_no_rewards() {
+    let fee_history = json!({
+        "oldestBlock": "0x1",
+        "baseFeePerGas": ["0x1", "0x1", "0x1"],
+        "gasUsedRatio": [0.0, 0.0],
+        "reward": [[], []],
+    });
+    let response = adapted("eth_feeHistory", fee_history);
+    assert!(!response.as_object().unwrap().contains_key("reward"));
+
+    let fee_history = json!({
+        "oldestBlock": "0x1",
+        "baseFeePerGas": ["0x1", "0x1"],
+        "gasUsedRatio": [0.0],
+        "reward": [["0x0"]],
+    });
+    let response = adapted("eth_feeHistory", fee_history.clone());
+    assert_eq!(response, fee_history);
+}
+
+#[test]
+fn errors_use_geth_codes() {
+    assert_eq!(
+        geth_error("eth_getBalance", &Web3Error::NoBlock),
+        Some(GethError::generic("header not found"))
+    );
+    assert_eq!(
+        geth_error("eth_getLogs", &Web3Error::NoBlock),
+        Some(GethError::generic("unknown block"))
+    );
+    assert_eq!(
+        geth_error("eth_getFilterChanges", &Web3Error::FilterNotFound),
+        Some(GethError::generic("filter not found"))
+    );
+    let too_many_logs = Web3Error::TooManyLogs(10_000);
+    assert_eq!(
+        geth_error("eth_getLogs", &too_many_logs),
+        Some(GethError::generic(too_many_logs.to_string()))
+    );
+    assert_eq!(
+        geth_error("eth_call", &Web3Error::RequestTimeout),
+        Some(GethError {
+            code: TIMEOUT_ERROR_CODE,
+            message: "request timed out".to_owned(),
+        })
+    );
+    assert_eq!(geth_error("eth_call", &Web3Error::InternalError), None);
+}
+
+#[test]
+fn revert_errors_are_preserved() {
+    let revert = Web3Error::SubmitTransactionError(
+        "execution reverted: Not enough balance".to_owned(),
+        vec![0x08, 0xc3, 0x79, 0xa0],
+    );
+    assert_eq!(geth_error("eth_call", &revert), None);
+
+    let nonce_error = Web3Error::SubmitTransactionError("nonce too low".to_owned(), vec![]);
+    assert_eq!(
+        geth_error("eth_sendRawTransaction", &nonce_error),
+        Some(GethError::generic("nonce too low"))
+    );
+}
+
+// Fixture-based tests. Fixtures are responses returned by geth; adapted zkSync responses must have
+// the same shape, i.e., the same set of fields with the same JSON types.
+
+fn load_fixture(name: &str) -> Value {
+    let raw = match name {
+        "block" => include_str!("fixtures/block.json"),
+        "receipt" => include_str!("fixtures/receipt.json"),
+        "fee_history" => include_str!("fixtures/fee_history.json"),
+        "errors" => include_str!("fixtures/errors.json"),
+        _ => panic!("unknown fixture `{name}`"),
+    };
+    serde_json::from_str(raw).unwrap()
+}
+
+/// Checks that `actual` has the same shape as `expected`. `null` values match any type since
+/// they depend on the particular object (e.g., `contractAddress` in receipts).
+fn assert_same_shape(actual: &Value, expected: &Value, path: &str) {
+    match (actual, expected) {
+        (Value::Null, _) | (_, Value::Null) => { /* OK */ }
+        (Value::Object(actual), Value::Object(expected)) => {
+            let actual_fields: BTreeSet<_> = actual.keys().collect();
+            let expected_fields: BTreeSet<_> = expected.keys().collect();
+            assert_eq!(actual_fields, expected_fields, "fields mismatch at {path}");
+            for (field, value) in actual {
+                assert_same_shape(value, &expected[field], &format!("{path}.{field}"));
+            }
+        }
+        (Value::Array(actual), Value::Array(expected)) => {
+            for (i, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                assert_same_shape(actual, expected, &format!("{path}[{i}]"));
+            }
+        }
+        (Value::String(_), Value::String(_))
+        | (Value::Number(_), Value::Number(_))
+        | (Value::Bool(_), Value::Bool(_)) => { /* OK */ }
+        _ => panic!("type mismatch at {path}: {actual} vs {expected}"),
+    }
+}
+
+fn adapted_response(method: &str, response: impl serde::Serialize) -> Value {
+    adapted(method, serde_json::to_value(response).unwrap())
+}
+
+fn mock_log() -> api::Log {
+    api::Log {
+        address: Address::repeat_byte(1),
+        topics: vec![H256::repeat_byte(2)],
+        data: Bytes(vec![0; 32]),
+        block_hash: Some(H256::repeat_byte(3)),
+        block_number: Some(1.into()),
+        l1_batch_number: Some(1.into()),
+        transaction_hash: Some(H256::repeat_byte(4)),
+        transaction_index: Some(1.into()),
+        log_index: Some(0.into()),
+        transaction_log_index: Some(0.into()),
+        log_type: None,
+        removed: None,
+    }
+}
+
+fn mock_transaction(transaction_type: u64) -> api::Transaction {
+    api::Transaction {
+        hash: H256::repeat_byte(4),
+        nonce: 0.into(),
+        block_hash: Some(H256::repeat_byte(3)),
+        block_number: Some(1.into()),
+        transaction_index: Some(0.into()),
+        from: Some(Address::repeat_byte(5)),
+        to: Some(Address::repeat_byte(1)),
+        value: 0.into(),
+        gas_price: Some(250_000_000.into()),
+        gas: 1_000_000.into(),
+        input: Bytes::default(),
+        v: Some(1.into()),
+        r: Some(U256::one()),
+        s: Some(U256::one()),
+        raw: Some(Bytes(vec![1, 2, 3])),
+        transaction_type: Some(transaction_type.into()),
+        access_list: (transaction_type != 0).then(Vec::new),
+        max_fee_per_gas: Some(250_000_000.into()),
+        max_priority_fee_per_gas: Some(0.into()),
+        chain_id: 270.into(),
+        l1_batch_number: Some(1.into()),
+        l1_batch_tx_index: Some(0.into()),
+    }
+}
+
+#[test]
+fn adapted_block_matches_geth_fixture() {
+    let block = api::Block {
+        number: 1.into(),
+        l1_batch_number: Some(1.into()),
+        l1_batch_timestamp: Some(1.into()),
+        seal_fields: vec![Bytes(vec![0])],
+        transactions: vec![
+            TransactionVariant::Full(mock_transaction(0)),
+            TransactionVariant::Full(mock_transaction(2)),
+        ],
+        ..api::Block::default()
+    };
+    let block = adapted_response("eth_getBlockByNumber", block);
+    assert_same_shape(&block, &load_fixture("block"), "block");
+}
+
+#[test]
+fn adapted_transactions_match_geth_fixture() {
+    let fixture = load_fixture("block");
+    for (i, transaction_type) in [0, 2].into_iter().enumerate() {
+        let transaction = adapted_response(
+            "eth_getTransactionByHash",
+            mock_transaction(transaction_type),
+        );
+        let path = format!("transaction (type {transaction_type})");
+        assert_same_shape(&transaction, &fixture["transactions"][i], &path);
+    }
+}
+
+#[test]
+fn adapted_receipt_matches_geth_fixture() {
+    let receipt = api::TransactionReceipt {
+        transaction_hash: H256::repeat_byte(4),
+        transaction_index: 1.into(),
+        block_hash: Some(H256::repeat_byte(3)),
+        block_number: Some(1.into()),
+        l1_batch_tx_index: Some(1.into()),
+        l1_batch_number: Some(1.into()),
+        from: Address::repeat_byte(5),
+        to: Some(Address::repeat_byte(1)),
+        cumulative_gas_used: 21_000.into(),
+        gas_used: Some(21_000.into()),
+        logs: vec![mock_log()],
+        status: Some(1.into()),
+        transaction_type: Some(2.into()),
+        effective_gas_price: Some(250_000_000.into()),
+        gas_refunded: Some(1_000.into()),
+        ..api::TransactionReceipt::default()
+    };
+    let receipt = adapted_response("eth_getTransactionReceipt", receipt);
+    assert_same_shape(&receipt, &load_fixture("receipt"), "receipt");
+}
+
+#[test]
+fn adapted_logs_match_geth_fixture() {
+    let fixture = load_fixture("receipt");
+    let logs = adapted_response("eth_getLogs", vec![mock_log()]);
+    assert_same_shape(&logs, &fixture["logs"], "logs");
+
+    // Log notifications for `eth_subscribe` are adapted in the same way.
+    let mut log = serde_json::to_value(mock_log()).unwrap();
+    adapt_log(&mut log);
+    assert_same_shape(&log, &fixture["logs"][0], "log notification");
+}
+
+#[test]
+fn adapted_fee_history_matches_geth_fixture() {
+    for reward in [None, Some(vec![vec![], vec![]])] {
+        let fee_history = FeeHistory {
+            oldest_block: BlockNumber::Number(1.into()),
+            base_fee_per_gas: vec![250_000_000.into(); 3],
+            gas_used_ratio: vec![0.5, 0.0],
+            reward,
+        };
+        let fee_history = adapted_response("eth_feeHistory", fee_history);
+        assert_same_shape(&fee_history, &load_fixture("fee_history"), "fee_history");
+    }
+}
+
+#[test]
+fn errors_match_geth_fixture() {
+    let fixture = load_fixture("errors");
+    let cases = [
+        (
+            "eth_getBalance",
+            Web3Error::NoBlock,
+            "eth_getBalance_unknown_block",
+        ),
+        (
+            "eth_getLogs",
+            Web3Error::NoBlock,
+            "eth_getLogs_unknown_block",
+        ),
+        (
+            "eth_getFilterChanges",
+            Web3Error::FilterNotFound,
+            "eth_getFilterChanges_unknown_filter",
+        ),
+        (
+            "eth_getLogs",
+            Web3Error::TooManyTopics,
+            "eth_getLogs_too_many_topics",
+        ),
+        (
+            "eth_sendRawTransaction",
+            Web3Error::SubmitTransactionError("nonce too low".to_owned(), vec![]),
+            "eth_sendRawTransaction_nonce_too_low",
+        ),
+        ("eth_call", Web3Error::RequestTimeout, "eth_call_timeout"),
+    ];
+    for (method, err, fixture_name) in cases {
+        let err = geth_error(method, &err).unwrap();
+        let expected = &fixture[fixture_name];
+        assert_eq!(
+            json!({ "code": err.code, "message": err.message }),
+            *expected,
+            "{fixture_name}"
+        );
+    }
+}
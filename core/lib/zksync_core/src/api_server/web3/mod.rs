@@ -23,8 +23,8 @@ use zksync_web3_decl::{
         RpcModule,
     },
     namespaces::{
-        DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer, NetNamespaceServer,
-        Web3NamespaceServer, ZksNamespaceServer,
+        DebugNamespaceServer, EnNamespaceServer, EthNamespaceServer, EvmNamespaceServer,
+        HardhatNamespaceServer, NetNamespaceServer, Web3NamespaceServer, ZksNamespaceServer,
     },
 };
 
@@ -34,12 +34,15 @@ use crate::{
         execution_sandbox::VmConcurrencyBarrier, tx_sender::TxSender,
         web3::backend_jsonrpc::batch_limiter_middleware::RateLimitMetadata,
     },
+    dev_mode::DevModeHandle,
     l1_gas_price::L1GasPriceProvider,
+    state_keeper::SealingStatusHandle,
     sync_layer::SyncState,
 };
 
 pub mod backend_jsonrpc;
 pub mod backend_jsonrpsee;
+mod geth_compat;
 pub mod namespaces;
 mod pubsub_notifier;
 pub mod state;
@@ -48,15 +51,16 @@ pub mod state;
 use self::backend_jsonrpc::{
     batch_limiter_middleware::{LimitMiddleware, Transport},
     error::internal_error,
+    geth_compat_middleware::GethCompatMiddleware,
     namespaces::{
-        debug::DebugNamespaceT, en::EnNamespaceT, eth::EthNamespaceT, net::NetNamespaceT,
-        web3::Web3NamespaceT, zks::ZksNamespaceT,
+        debug::DebugNamespaceT, en::EnNamespaceT, eth::EthNamespaceT, evm::EvmNamespaceT,
+        hardhat::HardhatNamespaceT, net::NetNamespaceT, web3::Web3NamespaceT, zks::ZksNamespaceT,
     },
     pub_sub::Web3PubSub,
 };
 use self::namespaces::{
-    DebugNamespace, EnNamespace, EthNamespace, EthSubscribe, NetNamespace, Web3Namespace,
-    ZksNamespace,
+    DebugNamespace, EnNamespace, EthNamespace, EthSubscribe, EvmNamespace, HardhatNamespace,
+    NetNamespace, Web3Namespace, ZksNamespace,
 };
 use self::pubsub_notifier::{notify_blocks, notify_logs, notify_txs};
 use self::state::{Filters, InternalApiConfig, RpcState, SealedMiniblockNumber};
@@ -86,6 +90,11 @@ pub enum Namespace {
     Zks,
     En,
     Pubsub,
+    /// `hardhat_*` methods for manipulating the node state. Only available in the dev mode.
+    Hardhat,
+    /// `evm_*` methods for controlling block timestamps, mining blocks and reverting state. Only available
+    /// in the dev mode.
+    Evm,
 }
 
 impl Namespace {
@@ -129,6 +138,9 @@ pub struct ApiBuilder<G> {
     polling_interval: Option<Duration>,
     namespaces: Option<Vec<Namespace>>,
     logs_translator_enabled: bool,
+    strict_geth_compatibility: bool,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode: Option<DevModeHandle>,
 }
 
 impl<G> ApiBuilder<G> {
@@ -152,6 +164,9 @@ impl<G> ApiBuilder<G> {
             namespaces: None,
             config,
             logs_translator_enabled: false,
+            strict_geth_compatibility: false,
+            sealing_status: None,
+            dev_mode: None,
         }
     }
 
@@ -248,6 +263,27 @@ impl<G> ApiBuilder<G> {
         self.logs_translator_enabled = true;
         self
     }
+
+    /// Makes `eth_` namespace responses follow geth conventions. See [`geth_compat`] for details.
+    pub fn enable_strict_geth_compatibility(mut self) -> Self {
+        tracing::info!("Strict geth compatibility mode enabled");
+        self.strict_geth_compatibility = true;
+        self
+    }
+
+    /// Allows `debug_getL1BatchSealingStatus` to report utilization of seal criteria for the pending L1 batch.
+    /// Only available if the state keeper runs in the same process.
+    pub fn with_sealing_status(mut self, sealing_status: SealingStatusHandle) -> Self {
+        self.sealing_status = Some(sealing_status);
+        self
+    }
+
+    /// Enables `eth_sendTransaction` for impersonated accounts. `hardhat_*` and `evm_*` methods additionally
+    /// require enabling [`Namespace::Hardhat`] and [`Namespace::Evm`] respectively. See [`crate::dev_mode`] for details.
+    pub fn with_dev_mode(mut self, dev_mode: DevModeHandle) -> Self {
+        self.dev_mode = Some(dev_mode);
+        self
+    }
 }
 
 impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
@@ -273,6 +309,9 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
             api_config: self.config,
             last_sealed_miniblock,
             logs_translator_enabled: self.logs_translator_enabled,
+            strict_geth_compatibility: self.strict_geth_compatibility,
+            sealing_status: self.sealing_status,
+            dev_mode: self.dev_mode,
         }
     }
 
@@ -284,8 +323,12 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
         // Collect all the methods into a single RPC module.
         let mut rpc = RpcModule::new(());
         if namespaces.contains(&Namespace::Eth) {
-            rpc.merge(EthNamespace::new(rpc_state.clone()).into_rpc())
+            let eth = EthNamespace::new(rpc_state.clone());
+            rpc.merge(eth.clone().into_rpc())
                 .expect("Can't merge eth namespace");
+            if rpc_state.strict_geth_compatibility {
+                backend_jsonrpsee::geth_compat::override_eth_methods(&mut rpc, &eth);
+            }
         }
         if namespaces.contains(&Namespace::Net) {
             rpc.merge(NetNamespace::new(zksync_network_id).into_rpc())
@@ -303,6 +346,16 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
             rpc.merge(EnNamespace::new(rpc_state.clone()).into_rpc())
                 .expect("Can't merge en namespace");
         }
+        if namespaces.contains(&Namespace::Hardhat) {
+            let dev_mode = rpc_state.dev_mode.clone().expect("dev mode is not enabled");
+            rpc.merge(HardhatNamespace::new(dev_mode).into_rpc())
+                .expect("Can't merge hardhat namespace");
+        }
+        if namespaces.contains(&Namespace::Evm) {
+            let dev_mode = rpc_state.dev_mode.clone().expect("dev mode is not enabled");
+            rpc.merge(EvmNamespace::new(dev_mode).into_rpc())
+                .expect("Can't merge evm namespace");
+        }
         if namespaces.contains(&Namespace::Debug) {
             rpc.merge(DebugNamespace::new(rpc_state).await.into_rpc())
                 .expect("Can't merge debug namespace");
@@ -410,7 +463,9 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
             .worker_threads(self.threads.unwrap())
             .build()
             .unwrap();
-        let mut io_handler: MetaIoHandler<()> = MetaIoHandler::default();
+        let mut io_handler: MetaIoHandler<(), _> = MetaIoHandler::with_middleware(
+            GethCompatMiddleware::new(self.strict_geth_compatibility),
+        );
         self.extend_jsonrpc_methods(&mut io_handler).await;
 
         tokio::task::spawn_blocking(move || {
@@ -476,6 +531,14 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
         if namespaces.contains(&Namespace::Net) {
             io.extend_with(NetNamespace::new(zksync_network_id).to_delegate());
         }
+        if namespaces.contains(&Namespace::Hardhat) {
+            let dev_mode = rpc_state.dev_mode.clone().expect("dev mode is not enabled");
+            io.extend_with(HardhatNamespace::new(dev_mode).to_delegate());
+        }
+        if namespaces.contains(&Namespace::Evm) {
+            let dev_mode = rpc_state.dev_mode.clone().expect("dev mode is not enabled");
+            io.extend_with(EvmNamespace::new(dev_mode).to_delegate());
+        }
         if namespaces.contains(&Namespace::Debug) {
             let debug_ns = DebugNamespace::new(rpc_state).await;
             io.extend_with(debug_ns.to_delegate());
@@ -495,6 +558,7 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
 
         let batch_limiter_middleware =
             LimitMiddleware::new(Transport::Ws, self.batch_request_size_limit);
+        let geth_compat_middleware = GethCompatMiddleware::new(self.strict_geth_compatibility);
 
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -506,7 +570,7 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
         let vm_barrier = self.vm_barrier.take().unwrap();
 
         let io_handler: MetaIoHandler<RateLimitMetadata<Arc<jsonrpc_pubsub::Session>>, _> =
-            MetaIoHandler::with_middleware(batch_limiter_middleware);
+            MetaIoHandler::with_middleware((batch_limiter_middleware, geth_compat_middleware));
         let mut io_handler = PubSubHandler::new(io_handler);
         let mut notify_handles = Vec::new();
 
@@ -535,6 +599,7 @@ impl<G: 'static + Send + Sync + L1GasPriceProvider> ApiBuilder<G> {
                     pub_sub.active_log_subs.clone(),
                     self.pool.clone(),
                     polling_interval,
+                    self.strict_geth_compatibility,
                     stop_receiver.clone(),
                 )),
             ]);
@@ -5,20 +5,24 @@ use vm::constants::BLOCK_GAS_LIMIT;
 use vm::ExecutionResult;
 
 use zksync_dal::ConnectionPool;
-use zksync_state::PostgresStorageCaches;
+use zksync_state::{ForkedState, PostgresStorageCaches};
 use zksync_types::{
-    api::{BlockId, BlockNumber, DebugCall, ResultDebugCall, TracerConfig},
+    api::{
+        BlockId, BlockNumber, BytecodeCompressionComparison, DebugCall, L1BatchSealingStatus,
+        ResultDebugCall, TracerConfig,
+    },
     l2::L2Tx,
     transaction_request::CallRequest,
     vm_trace::Call,
-    AccountTreeId, L2ChainId, H256, USED_BOOTLOADER_MEMORY_BYTES,
+    AccountTreeId, L2ChainId, H256, L1_GAS_PER_PUBDATA_BYTE, USED_BOOTLOADER_MEMORY_BYTES,
 };
 use zksync_web3_decl::error::Web3Error;
 
-use super::report_latency_with_block_id_and_diff;
+use super::{report_latency_with_block_id_and_diff, ResponseSizeLimiter};
 use crate::api_server::{
     execution_sandbox::{
-        execute_tx_eth_call, ApiTracer, BlockArgs, TxSharedArgs, VmConcurrencyLimiter,
+        execute_tx_eth_call, execute_tx_with_and_without_compression, ApiTracer, BlockArgs,
+        TxSharedArgs, VmConcurrencyLimiter,
     },
     tx_sender::ApiContracts,
     web3::{
@@ -27,7 +31,9 @@ use crate::api_server::{
         state::{RpcState, SealedMiniblockNumber},
     },
 };
+use crate::execution_diff::diff_executions;
 use crate::l1_gas_price::L1GasPriceProvider;
+use crate::state_keeper::SealingStatusHandle;
 
 #[derive(Debug, Clone)]
 pub struct DebugNamespace {
@@ -37,8 +43,12 @@ pub struct DebugNamespace {
     vm_execution_cache_misses_limit: Option<usize>,
     vm_concurrency_limiter: Arc<VmConcurrencyLimiter>,
     storage_caches: PostgresStorageCaches,
+    fork: Option<ForkedState>,
     last_sealed_miniblock: SealedMiniblockNumber,
     chain_id: L2ChainId,
+    large_response_size_limit: usize,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode_enabled: bool,
 }
 
 impl DebugNamespace {
@@ -53,8 +63,12 @@ impl DebugNamespace {
             vm_execution_cache_misses_limit: sender_config.vm_execution_cache_misses_limit,
             vm_concurrency_limiter: state.tx_sender.vm_concurrency_limiter(),
             storage_caches: state.tx_sender.storage_caches(),
+            fork: state.tx_sender.fork(),
             last_sealed_miniblock: state.last_sealed_miniblock,
             chain_id: sender_config.chain_id,
+            large_response_size_limit: state.api_config.large_response_size_limit,
+            sealing_status: state.sealing_status,
+            dev_mode_enabled: state.dev_mode.is_some(),
         }
     }
 
@@ -80,6 +94,8 @@ impl DebugNamespace {
             .blocks_web3_dal()
             .get_trace_for_miniblock(block_number)
             .await;
+        let mut size_limiter =
+            ResponseSizeLimiter::new(METHOD_NAME, self.large_response_size_limit);
         let call_trace = call_trace
             .into_iter()
             .map(|call_trace| {
@@ -87,9 +103,11 @@ impl DebugNamespace {
                 if only_top_call {
                     result.calls = vec![];
                 }
-                ResultDebugCall { result }
+                let result = ResultDebugCall { result };
+                size_limiter.push(&result)?;
+                Ok(result)
             })
-            .collect();
+            .collect::<Result<_, Web3Error>>()?;
 
         let block_diff = self.last_sealed_miniblock.diff(block_number);
         report_latency_with_block_id_and_diff(METHOD_NAME, start, block_id, block_diff);
@@ -170,7 +188,8 @@ impl DebugNamespace {
             self.vm_execution_cache_misses_limit,
             custom_tracers,
         )
-        .await;
+        .await
+        .map_err(|err| internal_error(METHOD_NAME, err))?;
 
         let (output, revert_reason) = match result.result {
             ExecutionResult::Success { output, .. } => (output, None),
@@ -203,15 +222,188 @@ impl DebugNamespace {
         Ok(call.into())
     }
 
+    /// Returns utilization of seal criteria for the L1 batch pending in the state keeper.
+    /// Returns `None` if no transactions were executed in the pending L1 batch yet.
+    #[tracing::instrument(skip(self))]
+    pub fn debug_get_l1_batch_sealing_status_impl(
+        &self,
+    ) -> Result<Option<L1BatchSealingStatus>, Web3Error> {
+        let sealing_status = self
+            .sealing_status
+            .as_ref()
+            .ok_or(Web3Error::NotImplemented)?;
+        Ok(sealing_status.get())
+    }
+
+    /// Executes a call with and without bytecode compression and compares the executions. Only available
+    /// in the dev mode.
+    #[tracing::instrument(skip(self, request, block_id))]
+    pub async fn debug_compare_bytecode_compression_impl(
+        &self,
+        request: CallRequest,
+        block_id: Option<BlockId>,
+    ) -> Result<BytecodeCompressionComparison, Web3Error> {
+        const METHOD_NAME: &str = "debug_compare_bytecode_compression";
+
+        if !self.dev_mode_enabled {
+            return Err(Web3Error::NotImplemented);
+        }
+        let start = Instant::now();
+        let block_id = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
+        let mut connection = self
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let block_args = BlockArgs::new(&mut connection, block_id)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .ok_or(Web3Error::NoBlock)?;
+        drop(connection);
+
+        let tx = L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES)?;
+        let vm_permit = self.vm_concurrency_limiter.acquire().await;
+        let vm_permit = vm_permit.ok_or(Web3Error::InternalError)?;
+        let (compressed_result, uncompressed_result) = execute_tx_with_and_without_compression(
+            vm_permit,
+            self.shared_args(),
+            self.connection_pool.clone(),
+            tx,
+            block_args,
+            self.vm_execution_cache_misses_limit,
+        )
+        .await
+        .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        let comparison = match compressed_result {
+            Ok(compressed_result) => BytecodeCompressionComparison {
+                compression_error: None,
+                diff: Some(diff_executions(&compressed_result, &uncompressed_result)),
+            },
+            Err(err) => BytecodeCompressionComparison {
+                compression_error: Some(err.to_string()),
+                diff: None,
+            },
+        };
+        let block_diff = self.last_sealed_miniblock.diff_with_block_args(&block_args);
+        report_latency_with_block_id_and_diff(METHOD_NAME, start, block_id, block_diff);
+        Ok(comparison)
+    }
+
     fn shared_args(&self) -> TxSharedArgs {
         TxSharedArgs {
             operator_account: AccountTreeId::default(),
             l1_gas_price: 100_000,
+            fair_pubdata_price: 100_000 * u64::from(L1_GAS_PER_PUBDATA_BYTE),
             fair_l2_gas_price: self.fair_l2_gas_price,
             base_system_contracts: self.api_contracts.eth_call.clone(),
             caches: self.storage_caches.clone(),
+            fork: self.fork.clone(),
             validation_computational_gas_limit: BLOCK_GAS_LIMIT,
             chain_id: self.chain_id,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use db_test_macro::db_test;
+
+    use std::time::Duration;
+
+    use zksync_contracts::BaseSystemContracts;
+    use zksync_types::{
+        commitment::CommitmentSchemes, protocol_version::L1VerifierConfig,
+        system_contracts::get_system_smart_contracts, Address, L1BatchCommitmentMode,
+        ProtocolVersionId,
+    };
+
+    use super::*;
+    use crate::{
+        api_server::execution_sandbox::VmConcurrencyBarrier,
+        genesis::{ensure_genesis_state, GenesisParams},
+    };
+
+    async fn create_namespace(
+        pool: &ConnectionPool,
+        dev_mode_enabled: bool,
+    ) -> (DebugNamespace, VmConcurrencyBarrier) {
+        let mut storage = pool.access_storage().await.unwrap();
+        let params = GenesisParams {
+            first_validator: Address::repeat_byte(0x01),
+            protocol_version: ProtocolVersionId::latest(),
+            base_system_contracts: BaseSystemContracts::load_from_disk(),
+            system_contracts: get_system_smart_contracts(),
+            first_l1_verifier_config: L1VerifierConfig::default(),
+            first_verifier_address: Address::zero(),
+            commitment_schemes: CommitmentSchemes::default(),
+            commitment_mode: L1BatchCommitmentMode::Rollup,
+        };
+        ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+            .await
+            .unwrap();
+
+        let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(1);
+        // The update task is not spawned, so the latest block number is always loaded from Postgres.
+        let (last_sealed_miniblock, _) =
+            SealedMiniblockNumber::new(pool.clone(), Duration::from_secs(60));
+        let namespace = DebugNamespace {
+            connection_pool: pool.clone(),
+            fair_l2_gas_price: 250_000_000,
+            api_contracts: ApiContracts::load_from_disk(),
+            vm_execution_cache_misses_limit: None,
+            vm_concurrency_limiter: Arc::new(vm_concurrency_limiter),
+            storage_caches: PostgresStorageCaches::new(1 << 20, 1 << 20),
+            fork: None,
+            last_sealed_miniblock,
+            chain_id: L2ChainId(270),
+            large_response_size_limit: usize::MAX,
+            sealing_status: None,
+            dev_mode_enabled,
+        };
+        (namespace, vm_barrier)
+    }
+
+    fn transfer_request() -> CallRequest {
+        CallRequest {
+            from: Some(Address::repeat_byte(0x10)),
+            to: Some(Address::repeat_byte(0x20)),
+            ..CallRequest::default()
+        }
+    }
+
+    #[db_test]
+    async fn comparing_bytecode_compression_requires_dev_mode(pool: ConnectionPool) {
+        let (namespace, _vm_barrier) = create_namespace(&pool, false).await;
+        let err = namespace
+            .debug_compare_bytecode_compression_impl(transfer_request(), None)
+            .await
+            .unwrap_err();
+        assert_matches!(err, Web3Error::NotImplemented);
+    }
+
+    #[db_test]
+    async fn comparing_bytecode_compression(pool: ConnectionPool) {
+        let (namespace, _vm_barrier) = create_namespace(&pool, true).await;
+        for block_id in [None, Some(BlockId::Number(BlockNumber::Latest))] {
+            let comparison = namespace
+                .debug_compare_bytecode_compression_impl(transfer_request(), block_id)
+                .await
+                .unwrap();
+            // The transaction has no factory deps, so compression cannot fail or influence the execution.
+            assert_eq!(comparison.compression_error, None);
+            let diff = comparison.diff.expect("no diff for successful compression");
+            assert!(diff.is_empty(), "{diff:?}");
+        }
+
+        let err = namespace
+            .debug_compare_bytecode_compression_impl(
+                transfer_request(),
+                Some(BlockId::Number(BlockNumber::Number(100.into()))),
+            )
+            .await
+            .unwrap_err();
+        assert_matches!(err, Web3Error::NoBlock);
+    }
+}
@@ -1,5 +1,6 @@
 use std::time::Instant;
 
+use zksync_dal::{SqlxError, StorageProcessor};
 use zksync_types::{
     api::{
         BlockId, BlockNumber, GetLogsFilter, Transaction, TransactionId, TransactionReceipt,
@@ -7,19 +8,18 @@ use zksync_types::{
     },
     l2::{L2Tx, TransactionType},
     transaction_request::CallRequest,
-    utils::decompose_full_nonce,
+    utils::{decompose_full_nonce, storage_key_for_eth_balance},
     web3,
     web3::types::{FeeHistory, SyncInfo, SyncState},
-    AccountTreeId, Bytes, MiniblockNumber, StorageKey, H256, L2_ETH_TOKEN_ADDRESS,
-    MAX_GAS_PER_PUBDATA_BYTE, U256,
+    AccountTreeId, Bytes, MiniblockNumber, StorageKey, H256, MAX_GAS_PER_PUBDATA_BYTE, U256,
 };
-use zksync_utils::u256_to_h256;
+use zksync_utils::{h256_to_u256, u256_to_h256};
 use zksync_web3_decl::{
     error::Web3Error,
     types::{Address, Block, Filter, FilterChanges, Log, TypedFilter, U64},
 };
 
-use super::report_latency_with_block_id_and_diff;
+use super::{hardhat::dev_mode_error, report_latency_with_block_id_and_diff, ResponseSizeLimiter};
 use crate::{
     api_server::{
         execution_sandbox::BlockArgs,
@@ -30,6 +30,9 @@ use crate::{
 
 pub const EVENT_TOPIC_NUMBER_LIMIT: usize = 4;
 pub const PROTOCOL_VERSION: &str = "zks/1";
+/// Percentile of priority fees paid by recently included transactions that is suggested as the priority fee
+/// (same as the default percentile used by geth).
+const PRIORITY_FEE_PERCENTILE: f64 = 0.6;
 
 #[derive(Debug)]
 pub struct EthNamespace<G> {
@@ -49,6 +52,11 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
         Self { state }
     }
 
+    /// Checks whether errors returned by the namespace should follow geth conventions.
+    pub(crate) fn is_geth_compatible(&self) -> bool {
+        self.state.strict_geth_compatibility
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_block_number_impl(&self) -> Result<U64, Web3Error> {
         const METHOD_NAME: &str = "get_block_number";
@@ -164,15 +172,86 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
     }
 
     #[tracing::instrument(skip(self))]
-    pub fn gas_price_impl(&self) -> Result<U256, Web3Error> {
+    pub async fn gas_price_impl(&self) -> Result<U256, Web3Error> {
         const METHOD_NAME: &str = "gas_price";
 
         let start = Instant::now();
-        let price = self.state.tx_sender.gas_price();
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let newest_miniblock = resolve_block(
+            &mut connection,
+            BlockId::Number(BlockNumber::Latest),
+            METHOD_NAME,
+        )
+        .await?;
+        // Transactions are only charged the base fee of the miniblock they are included in, so suggesting
+        // the maximum recent base fee protects legacy transactions from base fee fluctuations at no extra cost.
+        let max_recent_base_fee = connection
+            .blocks_web3_dal()
+            .get_max_base_fee_per_gas(
+                newest_miniblock,
+                self.state.api_config.fee_estimation_blocks,
+            )
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?
+            .unwrap_or_default();
+        let base_fee = U256::from(self.state.tx_sender.gas_price()).max(max_recent_base_fee);
+        let priority_fee = self
+            .suggest_priority_fee(&mut connection, newest_miniblock, METHOD_NAME)
+            .await?;
 
         metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(base_fee + priority_fee)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn max_priority_fee_per_gas_impl(&self) -> Result<U256, Web3Error> {
+        const METHOD_NAME: &str = "max_priority_fee_per_gas";
+
+        let start = Instant::now();
+        let mut connection = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let newest_miniblock = resolve_block(
+            &mut connection,
+            BlockId::Number(BlockNumber::Latest),
+            METHOD_NAME,
+        )
+        .await?;
+        let priority_fee = self
+            .suggest_priority_fee(&mut connection, newest_miniblock, METHOD_NAME)
+            .await?;
 
-        Ok(price.into())
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(priority_fee)
+    }
+
+    /// Suggests a priority fee based on priority fees effectively paid by L2 transactions in recent miniblocks.
+    /// Since the operator only charges the miniblock base fee, the suggested priority fee is 0 unless
+    /// the fee model changes.
+    async fn suggest_priority_fee(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        newest_miniblock: MiniblockNumber,
+        method_name: &'static str,
+    ) -> Result<U256, Web3Error> {
+        let priority_fee = connection
+            .blocks_web3_dal()
+            .get_effective_priority_fee_percentile(
+                newest_miniblock,
+                self.state.api_config.fee_estimation_blocks,
+                PRIORITY_FEE_PERCENTILE,
+            )
+            .await
+            .map_err(|err| internal_error(method_name, err))?;
+        Ok(priority_fee.unwrap_or_default())
     }
 
     #[tracing::instrument(skip(self))]
@@ -192,18 +271,41 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
             .unwrap();
         let block = block_id.unwrap_or(BlockId::Number(BlockNumber::Pending));
         let block_number = resolve_block(&mut connection, block, METHOD_NAME).await?;
-        let balance = connection
-            .storage_web3_dal()
-            .standard_token_historical_balance(
-                AccountTreeId::new(L2_ETH_TOKEN_ADDRESS),
-                AccountTreeId::new(address),
-                block_number,
-            )
+        let balance_key = storage_key_for_eth_balance(&address);
+        let balance = self
+            .read_storage_value(&mut connection, block, block_number, &balance_key)
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?;
         self.report_latency_with_block_id(METHOD_NAME, start, block, block_number);
 
-        Ok(balance)
+        Ok(h256_to_u256(balance))
+    }
+
+    /// Reads a storage value as of `block_number`. The value is taken from the storage caches shared
+    /// with the VM sandbox (including the state keeper cache, if configured) where possible, and from Postgres
+    /// otherwise.
+    async fn read_storage_value(
+        &self,
+        connection: &mut StorageProcessor<'_>,
+        block: BlockId,
+        block_number: MiniblockNumber,
+        key: &StorageKey,
+    ) -> Result<H256, SqlxError> {
+        let caches = self.state.tx_sender.storage_caches();
+        let is_latest = matches!(
+            block,
+            BlockId::Number(BlockNumber::Pending | BlockNumber::Latest | BlockNumber::Committed)
+        );
+        if is_latest {
+            caches.schedule_values_update(block_number);
+        }
+        if let Some(value) = caches.read_value_without_postgres(block_number, key) {
+            return Ok(value);
+        }
+        connection
+            .storage_web3_dal()
+            .get_historical_value_unchecked(key, block_number)
+            .await
     }
 
     fn report_latency_with_block_id(
@@ -219,26 +321,30 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
 
     #[tracing::instrument(skip(self, filter))]
     pub async fn get_logs_impl(&self, mut filter: Filter) -> Result<Vec<Log>, Web3Error> {
-        if self.state.logs_translator_enabled {
-            return self.state.translate_get_logs(filter).await;
-        }
+        const METHOD_NAME: &str = "get_logs";
 
-        let start = Instant::now();
+        let logs = if self.state.logs_translator_enabled {
+            self.state.translate_get_logs(filter).await?
+        } else {
+            let start = Instant::now();
 
-        self.state.resolve_filter_block_hash(&mut filter).await?;
-        let (from_block, to_block) = self.state.resolve_filter_block_range(&filter).await?;
-
-        filter.to_block = Some(BlockNumber::Number(to_block.0.into()));
-        let changes = self
-            .filter_changes(TypedFilter::Events(filter, from_block))
-            .await?
-            .0;
-
-        metrics::histogram!("api.web3.call", start.elapsed(), "method" => "get_logs");
-        Ok(match changes {
-            FilterChanges::Logs(list) => list,
-            _ => unreachable!("Unexpected `FilterChanges` type, expected `Logs`"),
-        })
+            self.state.resolve_filter_block_hash(&mut filter).await?;
+            let (from_block, to_block) = self.state.resolve_filter_block_range(&filter).await?;
+
+            filter.to_block = Some(BlockNumber::Number(to_block.0.into()));
+            let changes = self
+                .filter_changes(TypedFilter::Events(filter, from_block))
+                .await?
+                .0;
+
+            metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+            match changes {
+                FilterChanges::Logs(list) => list,
+                _ => unreachable!("Unexpected `FilterChanges` type, expected `Logs`"),
+            }
+        };
+
+        Ok(logs)
     }
 
     // #[tracing::instrument(skip(self))]
@@ -391,9 +497,8 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
             .await
             .unwrap();
         let block_number = resolve_block(&mut connection, block, METHOD_NAME).await?;
-        let value = connection
-            .storage_web3_dal()
-            .get_historical_value_unchecked(&storage_key, block_number)
+        let value = self
+            .read_storage_value(&mut connection, block, block_number, &storage_key)
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?;
 
@@ -685,6 +790,26 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
         submit_result
     }
 
+    /// Sends a transaction on behalf of an impersonated account. Only supported in the dev mode.
+    #[tracing::instrument(skip(self, request))]
+    pub async fn send_transaction_impl(&self, request: CallRequest) -> Result<H256, Web3Error> {
+        const METHOD_NAME: &str = "send_transaction";
+
+        let start = Instant::now();
+        let dev_mode = self
+            .state
+            .dev_mode
+            .as_ref()
+            .ok_or(Web3Error::NotImplemented)?;
+        let tx_hash = dev_mode
+            .send_impersonated_transaction(request)
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(tx_hash)
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn accounts_impl(&self) -> Vec<Address> {
         Vec::new()
@@ -870,11 +995,18 @@ impl<G: L1GasPriceProvider> EthNamespace<G> {
                     }
                 }
 
+                let mut size_limiter = ResponseSizeLimiter::new(
+                    METHOD_NAME,
+                    self.state.api_config.large_response_size_limit,
+                );
                 let logs = storage
                     .events_web3_dal()
-                    .get_logs(get_logs_filter, i32::MAX as usize)
+                    .get_logs_while(get_logs_filter, i32::MAX as usize, |log| {
+                        size_limiter.accept(log)
+                    })
                     .await
                     .map_err(|err| internal_error(METHOD_NAME, err))?;
+                size_limiter.finish()?;
                 let new_from_block = logs
                     .last()
                     .map(|log| MiniblockNumber(log.block_number.unwrap().as_u32()))
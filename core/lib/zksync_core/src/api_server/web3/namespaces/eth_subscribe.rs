@@ -7,7 +7,7 @@ use jsonrpc_pubsub::SubscriptionId;
 use tokio::sync::RwLock;
 
 use zksync_types::web3::types::H128;
-use zksync_web3_decl::types::{PubSubFilter, PubSubResult};
+use zksync_web3_decl::types::PubSubFilter;
 
 use super::eth::EVENT_TOPIC_NUMBER_LIMIT;
 
@@ -30,13 +30,15 @@ impl SubscriptionType {
     }
 }
 
+/// Notifications are sent as serialized [`PubSubResult`](zksync_web3_decl::types::PubSubResult)s,
+/// which allows adapting them to geth conventions in the strict geth compatibility mode.
 #[derive(Debug, Clone)]
 pub struct EthSubscribe {
     // `jsonrpc` backend executes task subscription on a separate thread that has no tokio context.
     pub runtime_handle: tokio::runtime::Handle,
-    pub active_block_subs: SubscriptionMap<typed::Sink<PubSubResult>>,
-    pub active_tx_subs: SubscriptionMap<typed::Sink<PubSubResult>>,
-    pub active_log_subs: SubscriptionMap<(typed::Sink<PubSubResult>, PubSubFilter)>,
+    pub active_block_subs: SubscriptionMap<typed::Sink<serde_json::Value>>,
+    pub active_tx_subs: SubscriptionMap<typed::Sink<serde_json::Value>>,
+    pub active_log_subs: SubscriptionMap<(typed::Sink<serde_json::Value>, PubSubFilter)>,
 }
 
 impl EthSubscribe {
@@ -51,15 +53,15 @@ impl EthSubscribe {
 
     /// Assigns ID for the subscriber if the connection is open, returns error otherwise.
     fn assign_id(
-        subscriber: typed::Subscriber<PubSubResult>,
-    ) -> Result<(typed::Sink<PubSubResult>, SubscriptionId), ()> {
+        subscriber: typed::Subscriber<serde_json::Value>,
+    ) -> Result<(typed::Sink<serde_json::Value>, SubscriptionId), ()> {
         let id = H128::random();
         let sub_id = SubscriptionId::String(format!("0x{}", hex::encode(id.0)));
         let sink = subscriber.assign_id(sub_id.clone())?;
         Ok((sink, sub_id))
     }
 
-    fn reject(subscriber: typed::Subscriber<PubSubResult>) {
+    fn reject(subscriber: typed::Subscriber<serde_json::Value>) {
         subscriber
             .reject(Error {
                 code: ErrorCode::InvalidParams,
@@ -72,7 +74,7 @@ impl EthSubscribe {
     #[tracing::instrument(skip(self, subscriber, params))]
     pub async fn sub(
         &self,
-        subscriber: typed::Subscriber<PubSubResult>,
+        subscriber: typed::Subscriber<serde_json::Value>,
         sub_type: String,
         params: Option<serde_json::Value>,
     ) {
@@ -126,7 +128,7 @@ impl EthSubscribe {
                 let Ok((sink, _id)) = Self::assign_id(subscriber) else {
                     return;
                 };
-                let _ = sink.notify(Ok(PubSubResult::Syncing(false)));
+                let _ = sink.notify(Ok(serde_json::Value::Bool(false)));
                 None
             }
             _ => {
@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+use zksync_types::U64;
+use zksync_web3_decl::error::Web3Error;
+
+use super::hardhat::dev_mode_error;
+use crate::dev_mode::DevModeHandle;
+
+/// Namespace with `evm_*` methods for controlling block timestamps, mining blocks and taking / reverting
+/// state snapshots during local development.
+/// Only available in the dev mode.
+#[derive(Debug, Clone)]
+pub struct EvmNamespace {
+    dev_mode: DevModeHandle,
+}
+
+impl EvmNamespace {
+    pub fn new(dev_mode: DevModeHandle) -> Self {
+        Self { dev_mode }
+    }
+
+    /// Returns the total time offset in seconds, similar to Hardhat Network.
+    #[tracing::instrument(skip(self))]
+    pub fn increase_time_impl(&self, seconds: U64) -> String {
+        self.dev_mode.increase_time(seconds.as_u64()).to_string()
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn set_next_block_timestamp_impl(&self, timestamp: U64) -> Result<String, Web3Error> {
+        const METHOD_NAME: &str = "set_next_block_timestamp";
+
+        self.dev_mode
+            .set_next_block_timestamp(timestamp.as_u64())
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        Ok(timestamp.as_u64().to_string())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn mine_impl(&self, timestamp: Option<U64>) -> Result<String, Web3Error> {
+        const METHOD_NAME: &str = "mine";
+
+        let start = Instant::now();
+        self.dev_mode
+            .mine(timestamp.map(|timestamp| timestamp.as_u64()))
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok("0".to_owned())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn snapshot_impl(&self) -> Result<U64, Web3Error> {
+        const METHOD_NAME: &str = "snapshot";
+
+        let snapshot_id = self
+            .dev_mode
+            .snapshot()
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        Ok(snapshot_id.into())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn revert_impl(&self, snapshot_id: U64) -> Result<bool, Web3Error> {
+        const METHOD_NAME: &str = "revert";
+
+        let start = Instant::now();
+        let reverted = self
+            .dev_mode
+            .revert(snapshot_id.as_u64())
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(reverted)
+    }
+}
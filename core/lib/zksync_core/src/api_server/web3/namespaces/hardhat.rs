@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+use zksync_types::{Address, Bytes, U256};
+use zksync_web3_decl::error::Web3Error;
+
+use crate::{
+    api_server::web3::backend_jsonrpc::error::internal_error,
+    dev_mode::{DevModeError, DevModeHandle},
+};
+
+/// Converts a dev mode error into the Web3 API error. Errors caused by the request are returned
+/// to the caller as is; internal errors are logged and masked.
+pub(super) fn dev_mode_error(method_name: &str, err: DevModeError) -> Web3Error {
+    match err {
+        DevModeError::Internal(err) => internal_error(method_name, err),
+        err => Web3Error::SubmitTransactionError(err.to_string(), vec![]),
+    }
+}
+
+/// Hardhat-compatible namespace for manipulating the node state during local development.
+/// Only available in the dev mode.
+#[derive(Debug, Clone)]
+pub struct HardhatNamespace {
+    dev_mode: DevModeHandle,
+}
+
+impl HardhatNamespace {
+    pub fn new(dev_mode: DevModeHandle) -> Self {
+        Self { dev_mode }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn impersonate_account_impl(&self, address: Address) -> bool {
+        self.dev_mode.impersonate_account(address);
+        true
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub fn stop_impersonating_account_impl(&self, address: Address) -> bool {
+        self.dev_mode.stop_impersonating_account(address);
+        true
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn set_balance_impl(
+        &self,
+        address: Address,
+        balance: U256,
+    ) -> Result<bool, Web3Error> {
+        const METHOD_NAME: &str = "set_balance";
+
+        let start = Instant::now();
+        self.dev_mode
+            .set_balance(address, balance)
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(true)
+    }
+
+    #[tracing::instrument(skip(self, bytecode))]
+    pub async fn set_code_impl(
+        &self,
+        address: Address,
+        bytecode: Bytes,
+    ) -> Result<bool, Web3Error> {
+        const METHOD_NAME: &str = "set_code";
+
+        let start = Instant::now();
+        self.dev_mode
+            .set_code(address, bytecode.0)
+            .await
+            .map_err(|err| dev_mode_error(METHOD_NAME, err))?;
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(true)
+    }
+}
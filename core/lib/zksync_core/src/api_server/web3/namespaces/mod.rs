@@ -1,14 +1,19 @@
 //! Actual implementation of Web3 API namespaces logic, not tied to the backend
 //! used to create a JSON RPC server.
 
-use std::time::Instant;
+use serde::Serialize;
+
+use std::{io, time::Instant};
 
 use zksync_types::api;
+use zksync_web3_decl::error::Web3Error;
 
 mod debug;
 mod en;
 pub(crate) mod eth;
 mod eth_subscribe;
+mod evm;
+mod hardhat;
 mod net;
 mod web3;
 mod zks;
@@ -18,6 +23,8 @@ pub use self::{
     en::EnNamespace,
     eth::EthNamespace,
     eth_subscribe::{EthSubscribe, SubscriptionMap},
+    evm::EvmNamespace,
+    hardhat::HardhatNamespace,
     net::NetNamespace,
     web3::Web3Namespace,
     zks::ZksNamespace,
@@ -50,3 +57,133 @@ fn report_latency_with_block_id_and_diff(
 
     metrics::histogram!("api.web3.call.block_diff", block_diff as f64, "method" => method_name);
 }
+
+/// Enforces a hard cap on the serialized size of a response that is assembled item by item.
+///
+/// Each item is serialized into a counting sink that aborts as soon as the cap is exceeded, so that
+/// an oversized response is rejected with [`Web3Error::ResponseTooLarge`] without materializing
+/// its serialized form. Items should be pushed as they are loaded (e.g., using
+/// [`EventsWeb3Dal::get_logs_while()`](zksync_dal::events_web3_dal::EventsWeb3Dal::get_logs_while())),
+/// so that loading stops as soon as the cap is exceeded.
+#[derive(Debug)]
+pub(super) struct ResponseSizeLimiter {
+    method_name: &'static str,
+    limit: usize,
+    size: usize,
+    exceeded: bool,
+}
+
+impl ResponseSizeLimiter {
+    pub(super) fn new(method_name: &'static str, limit: usize) -> Self {
+        Self {
+            method_name,
+            limit,
+            size: 2, // account for the enclosing `[]` brackets
+            exceeded: false,
+        }
+    }
+
+    /// Same as [`Self::push()`], but records the error instead of returning it. Returns `false` if the item
+    /// doesn't fit; in this case, [`Self::finish()`] will return an error.
+    pub(super) fn accept(&mut self, item: &impl Serialize) -> bool {
+        let fits = self.push(item).is_ok();
+        self.exceeded |= !fits;
+        fits
+    }
+
+    /// Returns an error if any of [accepted](Self::accept()) items didn't fit into the cap.
+    pub(super) fn finish(self) -> Result<(), Web3Error> {
+        if self.exceeded {
+            Err(Web3Error::ResponseTooLarge(self.limit))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Accounts for the next item in the response array.
+    fn push(&mut self, item: &impl Serialize) -> Result<(), Web3Error> {
+        let mut sink = CountingSink {
+            remaining: self.limit.saturating_sub(self.size),
+            written: 0,
+        };
+        if serde_json::to_writer(&mut sink, item).is_err() {
+            tracing::debug!(
+                "Response for `{}` exceeds size limit of {} bytes",
+                self.method_name,
+                self.limit
+            );
+            metrics::counter!("api.web3.response_too_large", 1, "method" => self.method_name);
+            return Err(Web3Error::ResponseTooLarge(self.limit));
+        }
+        self.size += sink.written + 1; // account for the `,` separator
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct CountingSink {
+    remaining: usize,
+    written: usize,
+}
+
+impl io::Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "response size limit exceeded",
+            ));
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn response_size_limiter_accepts_items_within_limit() {
+        let items = ["a", "b", "c"];
+        let expected_size = serde_json::to_vec(&items).unwrap().len();
+        let mut limiter = ResponseSizeLimiter::new("test", expected_size + 1);
+        for item in &items {
+            assert!(limiter.accept(item));
+        }
+        limiter.finish().unwrap();
+    }
+
+    #[test]
+    fn response_size_limiter_rejects_oversized_item() {
+        let mut limiter = ResponseSizeLimiter::new("test", 16);
+        limiter.push(&"short").unwrap();
+        let err = limiter.push(&"a".repeat(32)).unwrap_err();
+        assert_matches!(err, Web3Error::ResponseTooLarge(16));
+    }
+
+    #[test]
+    fn response_size_limiter_records_error_for_accept() {
+        let mut limiter = ResponseSizeLimiter::new("test", 16);
+        assert!(limiter.accept(&"short"));
+        assert!(!limiter.accept(&"a".repeat(32)));
+        assert_matches!(limiter.finish(), Err(Web3Error::ResponseTooLarge(16)));
+    }
+
+    #[test]
+    fn response_size_limiter_accounts_for_cumulative_size() {
+        let mut limiter = ResponseSizeLimiter::new("test", 32);
+        for _ in 0..3 {
+            assert!(limiter.accept(&"0123456"));
+        }
+        // `["0123456","0123456","0123456",` already takes 32 bytes
+        assert!(!limiter.accept(&"0"));
+        assert_matches!(limiter.finish(), Err(Web3Error::ResponseTooLarge(32)));
+    }
+}
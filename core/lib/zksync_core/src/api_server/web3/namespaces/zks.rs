@@ -2,21 +2,26 @@ use std::{collections::HashMap, convert::TryInto, time::Instant};
 
 use bigdecimal::{BigDecimal, Zero};
 
-use zksync_mini_merkle_tree::MiniMerkleTree;
+use zksync_dal::StorageProcessor;
+use zksync_mini_merkle_tree::{MiniMerkleTree, MiniMerkleTreeLayers};
 use zksync_types::{
     api::{
-        BlockDetails, BridgeAddresses, GetLogsFilter, L1BatchDetails, L2ToL1LogProof,
-        ProtocolVersion, TransactionDetails,
+        BlockDetails, BridgeAddresses, BridgeTransfer, GetLogsFilter, L1BatchDetails,
+        L2ToL1LogProof, PaymasterPolicies, PriorityOpStage, PriorityOpStatus, ProtocolVersion,
+        TransactionDetails, TransactionJourney,
     },
     commitment::SerializeCommitment,
     fee::Fee,
     l1::L1Tx,
     l2::L2Tx,
     l2_to_l1_log::L2ToL1Log,
+    pubdata::L1BatchPubdata,
+    state_diffs::StateDiffCompressionVersion,
     tokens::ETHEREUM_ADDRESS,
     transaction_request::CallRequest,
-    L1BatchNumber, MiniblockNumber, Transaction, L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
-    MAX_GAS_PER_PUBDATA_BYTE, REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, U256, U64,
+    L1BatchCommitmentMode, L1BatchNumber, MiniblockNumber, Transaction, L1_MESSENGER_ADDRESS,
+    L2_ETH_TOKEN_ADDRESS, MAX_GAS_PER_PUBDATA_BYTE, REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, U256,
+    U64,
 };
 use zksync_utils::address_to_h256;
 use zksync_web3_decl::{
@@ -125,6 +130,11 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         self.state.api_config.l2_testnet_paymaster_addr
     }
 
+    #[tracing::instrument(skip(self))]
+    pub fn get_paymaster_policies_impl(&self) -> PaymasterPolicies {
+        self.state.tx_sender.paymaster_policy().to_api()
+    }
+
     #[tracing::instrument(skip(self))]
     pub fn get_bridge_contracts_impl(&self) -> BridgeAddresses {
         self.state.api_config.bridge_addresses.clone()
@@ -144,6 +154,7 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         const METHOD_NAME: &str = "get_confirmed_tokens";
 
         let start = Instant::now();
+        let token_policy = &self.state.api_config.token_policy;
         let tokens = self
             .state
             .connection_pool
@@ -155,6 +166,7 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?
             .into_iter()
+            .filter(|token_info| token_policy.is_allowed(&token_info.l2_address))
             .skip(from as usize)
             .take(limit.into())
             .map(|token_info| Token {
@@ -185,6 +197,7 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             let mut tokens_web3_dal = storage.tokens_web3_dal();
             FeeTicker::get_l2_token_price(
                 &mut tokens_web3_dal,
+                &self.state.api_config.token_policy,
                 TokenPriceRequestType::USDForOneToken,
                 &l2_token,
             )
@@ -193,7 +206,9 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
 
         let result = match token_price_result {
             Ok(price) => Ok(price),
-            Err(TickerError::PriceNotTracked(_)) => Ok(BigDecimal::zero()),
+            Err(TickerError::PriceNotTracked(_) | TickerError::TokenNotAllowed(_)) => {
+                Ok(BigDecimal::zero())
+            }
             Err(err) => Err(internal_error(METHOD_NAME, err)),
         };
 
@@ -209,6 +224,7 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         const METHOD_NAME: &str = "get_all_balances";
 
         let start = Instant::now();
+        let token_policy = &self.state.api_config.token_policy;
         let balances = self
             .state
             .connection_pool
@@ -220,6 +236,7 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?
             .into_iter()
+            .filter(|(address, _)| token_policy.is_allowed(address))
             .map(|(address, balance)| {
                 if address == L2_ETH_TOKEN_ADDRESS {
                     (ETHEREUM_ADDRESS, balance)
@@ -233,6 +250,62 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         Ok(balances)
     }
 
+    /// Loads the L2->L1 log Merkle tree persisted when sealing the L1 batch. L1 batches sealed before
+    /// trees were persisted don't have a stored tree; for them, the tree is computed from all L2->L1 logs
+    /// in the batch.
+    async fn l2_to_l1_log_tree(
+        storage: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<MiniMerkleTreeLayers<'static, { L2ToL1Log::SERIALIZED_SIZE }>> {
+        let stored_layers = storage
+            .blocks_web3_dal()
+            .get_l2_to_l1_log_tree(l1_batch_number)
+            .await?;
+        let outcome = if stored_layers.is_some() {
+            "hit"
+        } else {
+            "miss"
+        };
+        metrics::increment_counter!("api.web3.l2_to_l1_log_tree", "outcome" => outcome);
+
+        Ok(match stored_layers {
+            Some(layers) => MiniMerkleTreeLayers::new(layers).map_err(|err| {
+                anyhow::anyhow!(
+                    "invalid L2->L1 log tree stored for L1 batch #{l1_batch_number}: {err}"
+                )
+            })?,
+            None => {
+                let logs = storage
+                    .blocks_web3_dal()
+                    .get_l2_to_l1_logs(l1_batch_number)
+                    .await?;
+                let leaves = logs.iter().map(L2ToL1Log::to_bytes);
+                MiniMerkleTree::new(leaves, L2ToL1Log::LIMIT_PER_L1_BATCH).into_layers()
+            }
+        })
+    }
+
+    /// Builds a Merkle proof for the L2->L1 log with the specified 0-based index in the L1 batch.
+    async fn l2_to_l1_log_proof(
+        storage: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+        l1_log_index: usize,
+    ) -> anyhow::Result<L2ToL1LogProof> {
+        let tree = Self::l2_to_l1_log_tree(storage, l1_batch_number).await?;
+        anyhow::ensure!(
+            l1_log_index < tree.leaf_count(),
+            "L2->L1 log #{l1_log_index} is missing from the tree for L1 batch #{l1_batch_number} \
+             with {} leaves",
+            tree.leaf_count()
+        );
+        let (root, proof) = tree.merkle_root_and_path(l1_log_index);
+        Ok(L2ToL1LogProof {
+            proof,
+            root,
+            id: l1_log_index as u32,
+        })
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_l2_to_l1_msg_proof_impl(
         &self,
@@ -266,12 +339,6 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             .map_err(|err| internal_error(METHOD_NAME, err))?
             .expect("L1 batch should contain at least one miniblock");
 
-        let all_l1_logs_in_batch = storage
-            .blocks_web3_dal()
-            .get_l2_to_l1_logs(l1_batch_number)
-            .await
-            .map_err(|err| internal_error(METHOD_NAME, err))?;
-
         // Position of l1 log in L1 batch relative to logs with identical data
         let l1_log_relative_position = if let Some(l2_log_position) = l2_log_position {
             let pos = storage
@@ -302,30 +369,24 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             0
         };
 
-        let l1_log_index = match all_l1_logs_in_batch
-            .iter()
-            .enumerate()
-            .filter(|(_, log)| {
-                log.sender == L1_MESSENGER_ADDRESS
-                    && log.key == address_to_h256(&sender)
-                    && log.value == msg
-            })
-            .nth(l1_log_relative_position)
-        {
-            Some(nth_elem) => nth_elem.0,
-            None => {
-                return Ok(None);
-            }
+        let l1_log_index = storage
+            .blocks_web3_dal()
+            .get_l2_to_l1_log_index_by_content(
+                l1_batch_number,
+                L1_MESSENGER_ADDRESS,
+                address_to_h256(&sender),
+                msg,
+                l1_log_relative_position,
+            )
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+        let Some(l1_log_index) = l1_log_index else {
+            return Ok(None);
         };
 
-        let merkle_tree_leaves = all_l1_logs_in_batch.iter().map(L2ToL1Log::to_bytes);
-        let (root, proof) = MiniMerkleTree::new(merkle_tree_leaves, L2ToL1Log::LIMIT_PER_L1_BATCH)
-            .merkle_root_and_path(l1_log_index);
-        let msg_proof = L2ToL1LogProof {
-            proof,
-            root,
-            id: l1_log_index as u32,
-        };
+        let msg_proof = Self::l2_to_l1_log_proof(&mut storage, l1_batch_number, l1_log_index)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
         metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
         Ok(Some(msg_proof))
     }
@@ -355,32 +416,18 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
             None => return Ok(None),
         };
 
-        let all_l1_logs_in_batch = storage
+        let l1_log_index = storage
             .blocks_web3_dal()
-            .get_l2_to_l1_logs(l1_batch_number)
+            .get_l2_to_l1_log_index_for_tx(l1_batch_number, l1_batch_tx_index, index.unwrap_or(0))
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?;
-
-        let l1_log_index = match all_l1_logs_in_batch
-            .iter()
-            .enumerate()
-            .filter(|(_, log)| log.tx_number_in_block == l1_batch_tx_index)
-            .nth(index.unwrap_or(0))
-        {
-            Some(nth_elem) => nth_elem.0,
-            None => {
-                return Ok(None);
-            }
+        let Some(l1_log_index) = l1_log_index else {
+            return Ok(None);
         };
 
-        let merkle_tree_leaves = all_l1_logs_in_batch.iter().map(L2ToL1Log::to_bytes);
-        let (root, proof) = MiniMerkleTree::new(merkle_tree_leaves, L2ToL1Log::LIMIT_PER_L1_BATCH)
-            .merkle_root_and_path(l1_log_index);
-        let msg_proof = L2ToL1LogProof {
-            proof,
-            root,
-            id: l1_log_index as u32,
-        };
+        let msg_proof = Self::l2_to_l1_log_proof(&mut storage, l1_batch_number, l1_log_index)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
 
         metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
         Ok(Some(msg_proof))
@@ -515,6 +562,92 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         tx_details
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn get_priority_op_status_impl(
+        &self,
+        l1_tx_hash: H256,
+    ) -> Result<Vec<PriorityOpStatus>, Web3Error> {
+        const METHOD_NAME: &str = "get_priority_op_status";
+
+        let start = Instant::now();
+        let statuses = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .transactions_web3_dal()
+            .get_priority_op_statuses(l1_tx_hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        let latest_l1_block = self
+            .state
+            .tx_sender
+            .0
+            .l1_gas_price_source
+            .latest_l1_block_number();
+        let statuses = statuses.map(|mut statuses| {
+            if let Some(latest_l1_block) = latest_l1_block {
+                for status in &mut statuses {
+                    mark_if_expired(status, latest_l1_block);
+                }
+            }
+            statuses
+        });
+
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        statuses
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_transaction_journey_impl(
+        &self,
+        hash: H256,
+    ) -> Result<Option<TransactionJourney>, Web3Error> {
+        const METHOD_NAME: &str = "get_transaction_journey";
+
+        let start = Instant::now();
+        let journey = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .tx_lifecycle_dal()
+            .get_transaction_journey(hash)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        journey
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn get_bridge_transfers_impl(
+        &self,
+        address: Address,
+        from: u32,
+        limit: u8,
+    ) -> Result<Vec<BridgeTransfer>, Web3Error> {
+        const METHOD_NAME: &str = "get_bridge_transfers";
+
+        let start = Instant::now();
+        let transfers = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap()
+            .bridge_transfers_dal()
+            .get_transfers_for_address(address, from as usize, limit.into())
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err));
+
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        transfers
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_l1_batch_details_impl(
         &self,
@@ -538,6 +671,31 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         l1_batch
     }
 
+    /// Returns pubdata of the specified L1 batch, decoded from the data committed (or to be committed) to L1.
+    /// Returns `None` if the L1 batch doesn't exist or its metadata is not computed yet.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_l1_batch_pubdata_impl(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> Result<Option<L1BatchPubdata>, Web3Error> {
+        const METHOD_NAME: &str = "get_l1_batch_pubdata";
+
+        let start = Instant::now();
+        let mut storage = self
+            .state
+            .connection_pool
+            .access_storage_tagged("api")
+            .await
+            .unwrap();
+        let commitment_mode = self.state.api_config.l1_batch_commitment_mode;
+        let pubdata = load_l1_batch_pubdata(&mut storage, batch_number, commitment_mode)
+            .await
+            .map_err(|err| internal_error(METHOD_NAME, err))?;
+
+        metrics::histogram!("api.web3.call", start.elapsed(), "method" => METHOD_NAME);
+        Ok(pubdata)
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn get_bytecode_by_hash_impl(&self, hash: H256) -> Option<Vec<u8>> {
         const METHOD_NAME: &str = "get_bytecode_by_hash";
@@ -616,3 +774,147 @@ impl<G: L1GasPriceProvider> ZksNamespace<G> {
         self.state.translate_get_logs(filter).await
     }
 }
+
+async fn load_l1_batch_pubdata(
+    storage: &mut StorageProcessor<'_>,
+    batch_number: L1BatchNumber,
+    commitment_mode: L1BatchCommitmentMode,
+) -> anyhow::Result<Option<L1BatchPubdata>> {
+    let Some(storage_l1_batch) = storage
+        .blocks_dal()
+        .get_storage_l1_batch(batch_number)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let Some(l1_batch) = storage
+        .blocks_dal()
+        .get_l1_batch_with_metadata(storage_l1_batch)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let commit_data = l1_batch.l1_commit_data(commitment_mode);
+    let protocol_version = l1_batch.header.protocol_version.unwrap_or_default();
+    let state_diffs_version = StateDiffCompressionVersion::for_protocol_version(protocol_version);
+    let pubdata = L1BatchPubdata::decode(&commit_data, commitment_mode, state_diffs_version)?;
+    Ok(Some(pubdata))
+}
+
+/// Marks a queued priority operation as expired if L1 has passed its expiration block. A zero expiration
+/// block means that the deadline is unknown.
+fn mark_if_expired(status: &mut PriorityOpStatus, latest_l1_block: u64) {
+    let is_expired = status.stage == PriorityOpStage::Queued
+        && status.expiration_block != 0
+        && latest_l1_block > status.expiration_block;
+    if is_expired {
+        status.stage = PriorityOpStage::Expired;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use db_test_macro::db_test;
+    use tempfile::TempDir;
+
+    use zksync_dal::ConnectionPool;
+    use zksync_types::pubdata::PublishedStorageWrites;
+
+    use super::*;
+    use crate::metadata_calculator::tests::{
+        gen_storage_logs, reset_db_state, run_calculator, setup_calculator,
+    };
+
+    #[db_test]
+    async fn loading_pubdata_for_sealed_l1_batch(
+        pool: ConnectionPool,
+        prover_pool: ConnectionPool,
+    ) {
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+        reset_db_state(&pool, 2).await;
+        run_calculator(calculator, pool.clone(), prover_pool).await;
+
+        let mut storage = pool.access_storage().await.unwrap();
+        let pubdata = load_l1_batch_pubdata(
+            &mut storage,
+            L1BatchNumber(1),
+            L1BatchCommitmentMode::Rollup,
+        )
+        .await
+        .unwrap()
+        .expect("no pubdata for sealed L1 batch");
+        assert_eq!(pubdata.l1_batch_number, L1BatchNumber(1));
+
+        // All writes in the L1 batch are initial; their keys, values and enumeration indices must match
+        // the storage logs and initial writes persisted when the batch was sealed.
+        let PublishedStorageWrites::Full {
+            initial_writes,
+            repeated_writes,
+        } = pubdata.storage_writes
+        else {
+            panic!("unexpected storage writes: {:?}", pubdata.storage_writes);
+        };
+        assert!(repeated_writes.is_empty());
+        let expected_indices: HashMap<_, _> = storage
+            .storage_logs_dedup_dal()
+            .initial_writes_for_batch(L1BatchNumber(1))
+            .await
+            .into_iter()
+            .collect();
+        let expected_values: HashMap<_, _> = gen_storage_logs(0..100, 2)[0]
+            .iter()
+            .map(|log| (log.key.hashed_key(), log.value))
+            .collect();
+        assert_eq!(initial_writes.len(), expected_indices.len());
+        for write in &initial_writes {
+            let mut hashed_key = [0_u8; 32];
+            write.key.to_little_endian(&mut hashed_key);
+            let hashed_key = H256(hashed_key);
+            assert_eq!(write.index, expected_indices[&hashed_key]);
+            assert_eq!(write.value, expected_values[&hashed_key]);
+        }
+        assert!(pubdata.l2_to_l1_logs.is_empty());
+        assert!(pubdata.l2_to_l1_messages.is_empty());
+
+        let missing_pubdata = load_l1_batch_pubdata(
+            &mut storage,
+            L1BatchNumber(3),
+            L1BatchCommitmentMode::Rollup,
+        )
+        .await
+        .unwrap();
+        assert!(missing_pubdata.is_none());
+    }
+
+    #[test]
+    fn marking_expired_priority_ops() {
+        let mut status = PriorityOpStatus {
+            l1_tx_hash: H256::repeat_byte(1),
+            l2_tx_hash: H256::repeat_byte(2),
+            priority_op_id: 1,
+            l1_block_number: 1,
+            expiration_block: 100,
+            received_at: chrono::Utc::now(),
+            stage: PriorityOpStage::Queued,
+            queue_position: Some(0),
+            miniblock_number: None,
+            l1_batch_number: None,
+            status: None,
+            revert_reason: None,
+        };
+        mark_if_expired(&mut status, 100);
+        assert_eq!(status.stage, PriorityOpStage::Queued);
+        mark_if_expired(&mut status, 101);
+        assert_eq!(status.stage, PriorityOpStage::Expired);
+
+        // Executed operations and operations with an unknown deadline never expire.
+        status.stage = PriorityOpStage::IncludedInMiniblock;
+        mark_if_expired(&mut status, 101);
+        assert_eq!(status.stage, PriorityOpStage::IncludedInMiniblock);
+        status.stage = PriorityOpStage::Queued;
+        status.expiration_block = 0;
+        mark_if_expired(&mut status, 101);
+        assert_eq!(status.stage, PriorityOpStage::Queued);
+    }
+}
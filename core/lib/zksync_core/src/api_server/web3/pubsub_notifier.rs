@@ -7,10 +7,14 @@ use zksync_dal::ConnectionPool;
 use zksync_types::MiniblockNumber;
 use zksync_web3_decl::types::{PubSubFilter, PubSubResult};
 
-use super::namespaces::SubscriptionMap;
+use super::{geth_compat, namespaces::SubscriptionMap};
+
+fn notification(result: PubSubResult) -> serde_json::Value {
+    serde_json::to_value(result).expect("failed serializing pubsub notification")
+}
 
 pub async fn notify_blocks(
-    subscribers: SubscriptionMap<typed::Sink<PubSubResult>>,
+    subscribers: SubscriptionMap<typed::Sink<serde_json::Value>>,
     connection_pool: ConnectionPool,
     polling_interval: Duration,
     stop_receiver: watch::Receiver<bool>,
@@ -53,9 +57,13 @@ pub async fn notify_blocks(
                 .values()
                 .cloned()
                 .collect::<Vec<_>>();
+            let new_blocks: Vec<_> = new_blocks
+                .into_iter()
+                .map(|block| notification(PubSubResult::Header(block)))
+                .collect();
             for sink in subscribers {
                 for block in new_blocks.iter().cloned() {
-                    if sink.notify(Ok(PubSubResult::Header(block))).is_err() {
+                    if sink.notify(Ok(block)).is_err() {
                         // Subscriber disconnected.
                         break;
                     }
@@ -69,7 +77,7 @@ pub async fn notify_blocks(
 }
 
 pub async fn notify_txs(
-    subscribers: SubscriptionMap<typed::Sink<PubSubResult>>,
+    subscribers: SubscriptionMap<typed::Sink<serde_json::Value>>,
     connection_pool: ConnectionPool,
     polling_interval: Duration,
     stop_receiver: watch::Receiver<bool>,
@@ -106,7 +114,10 @@ pub async fn notify_txs(
                 .collect::<Vec<_>>();
             for sink in subscribers {
                 for tx_hash in new_txs.iter().cloned() {
-                    if sink.notify(Ok(PubSubResult::TxHash(tx_hash))).is_err() {
+                    if sink
+                        .notify(Ok(notification(PubSubResult::TxHash(tx_hash))))
+                        .is_err()
+                    {
                         // Subscriber disconnected.
                         break;
                     }
@@ -120,9 +131,10 @@ pub async fn notify_txs(
 }
 
 pub async fn notify_logs(
-    subscribers: SubscriptionMap<(typed::Sink<PubSubResult>, PubSubFilter)>,
+    subscribers: SubscriptionMap<(typed::Sink<serde_json::Value>, PubSubFilter)>,
     connection_pool: ConnectionPool,
     polling_interval: Duration,
+    geth_compatible: bool,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let mut last_block_number = connection_pool
@@ -164,10 +176,21 @@ pub async fn notify_logs(
                 .cloned()
                 .collect::<Vec<_>>();
 
+            // Logs are serialized once and shared among all subscribers.
+            let new_logs: Vec<_> = new_logs
+                .into_iter()
+                .map(|log| {
+                    let mut notification = notification(PubSubResult::Log(log.clone()));
+                    if geth_compatible {
+                        geth_compat::adapt_log(&mut notification);
+                    }
+                    (log, notification)
+                })
+                .collect();
             for (sink, filter) in subscribers {
-                for log in new_logs.iter().cloned() {
-                    if filter.matches(&log) {
-                        if sink.notify(Ok(PubSubResult::Log(log))).is_err() {
+                for (log, notification) in &new_logs {
+                    if filter.matches(log) {
+                        if sink.notify(Ok(notification.clone())).is_err() {
                             // Subscriber disconnected.
                             break;
                         }
@@ -12,15 +12,20 @@ use std::{
     time::{Duration, Instant},
 };
 
-use zksync_config::configs::{api::Web3JsonRpcConfig, chain::NetworkConfig, ContractsConfig};
+use zksync_config::configs::{
+    api::Web3JsonRpcConfig,
+    chain::{NetworkConfig, StateKeeperConfig},
+    ContractsConfig,
+};
 use zksync_dal::ConnectionPool;
 use zksync_types::{
     api::{self, BlockId, BlockNumber, GetLogsFilter},
     block::unpack_block_upgrade_info,
     l2::L2Tx,
     transaction_request::CallRequest,
-    AccountTreeId, Address, L1BatchNumber, L1ChainId, L2ChainId, MiniblockNumber, StorageKey, H256,
-    SYSTEM_CONTEXT_ADDRESS, U256, U64, VIRTUIAL_BLOCK_UPGRADE_INFO_POSITION,
+    AccountTreeId, Address, L1BatchCommitmentMode, L1BatchNumber, L1ChainId, L2ChainId,
+    MiniblockNumber, StorageKey, H256, SYSTEM_CONTEXT_ADDRESS, U256, U64,
+    VIRTUIAL_BLOCK_UPGRADE_INFO_POSITION,
 };
 use zksync_web3_decl::{
     error::Web3Error,
@@ -32,11 +37,15 @@ use crate::{
         execution_sandbox::BlockArgs,
         tx_sender::TxSender,
         web3::{
-            backend_jsonrpc::error::internal_error, namespaces::eth::EVENT_TOPIC_NUMBER_LIMIT,
+            backend_jsonrpc::error::internal_error,
+            namespaces::{eth::EVENT_TOPIC_NUMBER_LIMIT, ResponseSizeLimiter},
             resolve_block,
         },
     },
+    dev_mode::DevModeHandle,
+    state_keeper::SealingStatusHandle,
     sync_layer::SyncState,
+    token_registry::TokenPolicy,
 };
 
 /// Configuration values for the API.
@@ -55,6 +64,12 @@ pub struct InternalApiConfig {
     pub l2_testnet_paymaster_addr: Option<Address>,
     pub req_entities_limit: usize,
     pub fee_history_limit: u64,
+    pub fee_estimation_blocks: u64,
+    /// Hard cap on the serialized size of large responses (e.g., `eth_getLogs` or `debug_traceBlock*`) in bytes.
+    pub large_response_size_limit: usize,
+    pub l1_batch_commitment_mode: L1BatchCommitmentMode,
+    /// Tokens exposed via the API.
+    pub token_policy: TokenPolicy,
 }
 
 impl InternalApiConfig {
@@ -62,6 +77,8 @@ impl InternalApiConfig {
         eth_config: &NetworkConfig,
         web3_config: &Web3JsonRpcConfig,
         contracts_config: &ContractsConfig,
+        state_keeper_config: &StateKeeperConfig,
+        token_policy: TokenPolicy,
     ) -> Self {
         Self {
             l1_chain_id: eth_config.network.chain_id(),
@@ -80,6 +97,10 @@ impl InternalApiConfig {
             l2_testnet_paymaster_addr: contracts_config.l2_testnet_paymaster_addr,
             req_entities_limit: web3_config.req_entities_limit(),
             fee_history_limit: web3_config.fee_history_limit(),
+            fee_estimation_blocks: web3_config.fee_estimation_blocks(),
+            large_response_size_limit: web3_config.large_response_size_limit(),
+            l1_batch_commitment_mode: state_keeper_config.l1_batch_commitment_mode,
+            token_policy,
         }
     }
 }
@@ -142,6 +163,12 @@ impl SealedMiniblockNumber {
         MiniblockNumber(prev_value).max(maybe_newer_miniblock_number)
     }
 
+    /// Returns the last known sealed miniblock number. The value may be slightly outdated,
+    /// and is zero until the first update.
+    pub fn get(&self) -> MiniblockNumber {
+        MiniblockNumber(self.0.load(Ordering::Relaxed))
+    }
+
     pub fn diff(&self, miniblock_number: MiniblockNumber) -> u32 {
         let sealed_miniblock_number = self.update(miniblock_number);
         sealed_miniblock_number.0.saturating_sub(miniblock_number.0)
@@ -173,6 +200,10 @@ pub struct RpcState<E> {
     // The flag that enables redirect of eth get logs implementation to
     // implementation with virtual block translation to miniblocks
     pub logs_translator_enabled: bool,
+    /// Whether `eth_` namespace errors follow geth conventions (see [`super::geth_compat`]).
+    pub strict_geth_compatibility: bool,
+    pub sealing_status: Option<SealingStatusHandle>,
+    pub dev_mode: Option<DevModeHandle>,
 }
 
 // Custom implementation is required due to generic param:
@@ -188,6 +219,9 @@ impl<E> Clone for RpcState<E> {
             api_config: self.api_config.clone(),
             last_sealed_miniblock: self.last_sealed_miniblock.clone(),
             logs_translator_enabled: self.logs_translator_enabled,
+            strict_geth_compatibility: self.strict_geth_compatibility,
+            sealing_status: self.sealing_status.clone(),
+            dev_mode: self.dev_mode.clone(),
         }
     }
 }
@@ -527,12 +561,16 @@ impl<E> RpcState<E> {
             return Err(Web3Error::TooManyLogs(self.api_config.req_entities_limit));
         }
 
+        let mut size_limiter =
+            ResponseSizeLimiter::new(METHOD_NAME, self.api_config.large_response_size_limit);
         let logs = storage
             .events_web3_dal()
-            .get_logs(get_logs_filter, i32::MAX as usize)
+            .get_logs_while(get_logs_filter, i32::MAX as usize, |log| {
+                size_limiter.accept(log)
+            })
             .await
             .map_err(|err| internal_error(METHOD_NAME, err))?;
-
+        size_limiter.finish()?;
         Ok(logs)
     }
 }
@@ -0,0 +1,481 @@
+//! Bridge indexer that correlates L1 and L2 parts of bridge transfers into a single `bridge_transfers` table,
+//! so that explorers and wallets don't need to reassemble them.
+//!
+//! The indexer processes sealed L1 batches in order. Deposits are extracted from priority transactions
+//! (the L1 deposit transaction and its execution result on L2); withdrawals are extracted from L2->L1 messages
+//! sent by the L2 ETH token and the L2 ERC20 bridge.
+//!
+//! Finalization of withdrawals is detected from `EthWithdrawalFinalized` / `WithdrawalFinalized` events emitted
+//! on L1 by the zkSync contract and the L1 ERC20 bridge, regardless of who has sent the finalization transaction.
+//! Since these events don't identify the finalized withdrawal, the indexer matches them with initiated withdrawals
+//! by receiver, token and amount, and confirms the match by querying the finalization status on L1.
+
+use anyhow::Context as _;
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+use std::time::Duration;
+
+use zksync_config::{constants::PRIORITY_EXPIRATION, ContractsConfig};
+use zksync_contracts::{l1_bridge_contract, zksync_contract};
+use zksync_dal::{bridge_transfers_dal::BridgeTransferTx, ConnectionPool, StorageProcessor};
+use zksync_eth_client::EthInterface;
+use zksync_types::{
+    api::{BridgeTransfer, BridgeTransferKind, BridgeTransferStatus},
+    ethabi::{self, Contract, ParamType},
+    web3::{
+        contract::Options,
+        signing::keccak256,
+        types::{BlockNumber, FilterBuilder, Log},
+    },
+    Address, L1BatchNumber, H256, U256,
+};
+use zksync_utils::h256_to_account_address;
+
+use crate::withdrawal_finalizer::{withdrawal_messages, WithdrawalLogMessage};
+
+#[cfg(test)]
+mod tests;
+
+const COMPONENT: &str = "bridge_indexer";
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_L1_BATCHES_PER_ITERATION: u32 = 50;
+const MAX_L1_BLOCKS_PER_ITERATION: u64 = 10_000;
+
+/// Topic of `EthWithdrawalFinalized(address indexed to, uint256 amount)` emitted by the zkSync contract.
+static ETH_WITHDRAWAL_FINALIZED_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256(keccak256(b"EthWithdrawalFinalized(address,uint256)")));
+/// Topic of `WithdrawalFinalized(address indexed to, address indexed l1Token, uint256 amount)` emitted
+/// by the L1 ERC20 bridge.
+static WITHDRAWAL_FINALIZED_TOPIC: Lazy<H256> =
+    Lazy::new(|| H256(keccak256(b"WithdrawalFinalized(address,address,uint256)")));
+
+/// Parameter types of `finalizeDeposit(l1Sender, l2Receiver, l1Token, amount, data)` on the L2 bridge.
+fn finalize_deposit_params() -> [ParamType; 5] {
+    [
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]
+}
+
+static FINALIZE_DEPOSIT_SELECTOR: Lazy<[u8; 4]> =
+    Lazy::new(|| ethabi::short_signature("finalizeDeposit", &finalize_deposit_params()));
+
+/// Deposit parameters decoded from a `finalizeDeposit` call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DepositCall {
+    l1_sender: Address,
+    l2_receiver: Address,
+    l1_token: Address,
+    amount: U256,
+}
+
+impl DepositCall {
+    fn parse(calldata: &[u8]) -> Option<Self> {
+        let encoded_args = calldata.strip_prefix(FINALIZE_DEPOSIT_SELECTOR.as_slice())?;
+        let mut args = ethabi::decode(&finalize_deposit_params(), encoded_args)
+            .ok()?
+            .into_iter();
+        Some(Self {
+            l1_sender: args.next()?.into_address()?,
+            l2_receiver: args.next()?.into_address()?,
+            l1_token: args.next()?.into_address()?,
+            amount: args.next()?.into_uint()?,
+        })
+    }
+}
+
+/// Withdrawal finalization event emitted on L1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FinalizationEvent {
+    l1_receiver: Address,
+    l1_token: Address,
+    amount: U256,
+    l1_tx_hash: H256,
+}
+
+impl FinalizationEvent {
+    fn parse(
+        log: &Log,
+        diamond_proxy_addr: Address,
+        l1_erc20_bridge_addr: Address,
+    ) -> Option<Self> {
+        if log.removed == Some(true) || log.data.0.len() != 32 {
+            return None;
+        }
+        let l1_tx_hash = log.transaction_hash?;
+        let amount = U256::from_big_endian(&log.data.0);
+        match log.topics.as_slice() {
+            [topic, receiver]
+                if log.address == diamond_proxy_addr
+                    && *topic == *ETH_WITHDRAWAL_FINALIZED_TOPIC =>
+            {
+                Some(Self {
+                    l1_receiver: h256_to_account_address(receiver),
+                    l1_token: Address::zero(),
+                    amount,
+                    l1_tx_hash,
+                })
+            }
+            [topic, receiver, token]
+                if log.address == l1_erc20_bridge_addr && *topic == *WITHDRAWAL_FINALIZED_TOPIC =>
+            {
+                Some(Self {
+                    l1_receiver: h256_to_account_address(receiver),
+                    l1_token: h256_to_account_address(token),
+                    amount,
+                    l1_tx_hash,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Assembles transfers in an L1 batch from its transactions and withdrawals. L2 token addresses are not resolved.
+fn collect_transfers(
+    l1_batch_number: L1BatchNumber,
+    txs: &[BridgeTransferTx],
+    withdrawals: &[WithdrawalLogMessage<'_>],
+    l2_erc20_bridge_addr: Address,
+) -> anyhow::Result<Vec<BridgeTransfer>> {
+    let mut transfers = vec![];
+    for tx in txs.iter().filter(|tx| tx.is_priority) {
+        let execute = &tx.execute;
+        let deposit_call = if execute.contract_address == l2_erc20_bridge_addr {
+            DepositCall::parse(&execute.calldata)
+        } else {
+            None
+        };
+        let deposit_call = match deposit_call {
+            Some(call) => call,
+            None if !execute.value.is_zero() => DepositCall {
+                l1_sender: tx.initiator_address,
+                l2_receiver: execute.contract_address,
+                l1_token: Address::zero(),
+                amount: execute.value,
+            },
+            // Not a transfer (e.g., an L1->L2 contract call).
+            None => continue,
+        };
+
+        transfers.push(BridgeTransfer {
+            kind: BridgeTransferKind::Deposit,
+            status: if tx.failed {
+                BridgeTransferStatus::Failed
+            } else {
+                BridgeTransferStatus::Finalized
+            },
+            sender: deposit_call.l1_sender,
+            receiver: deposit_call.l2_receiver,
+            l1_token: deposit_call.l1_token,
+            l2_token: None,
+            amount: deposit_call.amount,
+            l1_tx_hash: tx.l1_tx_hash,
+            l2_tx_hash: tx.hash,
+            miniblock_number: tx.miniblock_number,
+            l1_batch_number,
+            l2_message_index: None,
+        });
+    }
+
+    for withdrawal in withdrawals {
+        let tx_index_in_l1_batch = withdrawal.log.tx_number_in_block;
+        let tx = txs
+            .iter()
+            .find(|tx| tx.index_in_l1_batch == tx_index_in_l1_batch)
+            .with_context(|| {
+                format!(
+                    "transaction #{tx_index_in_l1_batch} in L1 batch #{l1_batch_number} is missing"
+                )
+            })?;
+        transfers.push(BridgeTransfer {
+            kind: BridgeTransferKind::Withdrawal,
+            status: BridgeTransferStatus::Initiated,
+            // The withdrawal message does not contain the L2 sender, so we use the transaction initiator.
+            // This is imprecise only if the withdrawal is initiated by a contract on behalf of the initiator.
+            sender: tx.initiator_address,
+            receiver: withdrawal.message.l1_receiver,
+            l1_token: withdrawal.message.l1_token,
+            l2_token: None,
+            amount: withdrawal.message.amount,
+            l1_tx_hash: None,
+            l2_tx_hash: tx.hash,
+            miniblock_number: tx.miniblock_number,
+            l1_batch_number,
+            l2_message_index: Some(withdrawal.l2_message_index),
+        });
+    }
+
+    // Order transfers by their position in the L1 batch.
+    let tx_index = |transfer: &BridgeTransfer| {
+        txs.iter()
+            .find(|tx| tx.hash == transfer.l2_tx_hash)
+            .map(|tx| tx.index_in_l1_batch)
+    };
+    transfers.sort_by_key(|transfer| (tx_index(transfer), transfer.l2_message_index));
+    Ok(transfers)
+}
+
+/// Component indexing bridge transfers.
+#[derive(Debug)]
+pub struct BridgeIndexer<E> {
+    pool: ConnectionPool,
+    eth_client: E,
+    l2_erc20_bridge_addr: Address,
+    diamond_proxy_addr: Address,
+    l1_erc20_bridge_addr: Address,
+    zksync_contract: Contract,
+    l1_bridge_contract: Contract,
+}
+
+impl<E: EthInterface> BridgeIndexer<E> {
+    pub fn new(contracts_config: &ContractsConfig, pool: ConnectionPool, eth_client: E) -> Self {
+        Self {
+            pool,
+            eth_client,
+            l2_erc20_bridge_addr: contracts_config.l2_erc20_bridge_addr,
+            diamond_proxy_addr: contracts_config.diamond_proxy_addr,
+            l1_erc20_bridge_addr: contracts_config.l1_erc20_bridge_proxy_addr,
+            zksync_contract: zksync_contract(),
+            l1_bridge_contract: l1_bridge_contract(),
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            let has_more_l1_batches = match self.iteration().await {
+                Ok(has_more_l1_batches) => has_more_l1_batches,
+                Err(err) => {
+                    tracing::warn!("Error indexing bridge transfers: {err:#}");
+                    metrics::increment_counter!("server.bridge_indexer.errors");
+                    false
+                }
+            };
+            if has_more_l1_batches {
+                continue;
+            }
+
+            let stop_signal = tokio::time::timeout(POLL_INTERVAL, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for bridge indexer was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, bridge indexer is shutting down");
+        Ok(())
+    }
+
+    /// Indexes the next sealed L1 batches, and then processes withdrawal finalization events on L1.
+    /// Returns `true` if there are more L1 batches or L1 blocks to process.
+    async fn iteration(&self) -> anyhow::Result<bool> {
+        if self.index_l1_batches().await? {
+            // Finalization events are only processed once all sealed L1 batches are indexed,
+            // so that finalized withdrawals can be matched with indexed ones.
+            return Ok(true);
+        }
+        self.process_l1_events().await
+    }
+
+    /// Indexes the next sealed L1 batches. Returns `true` if there are more L1 batches to index.
+    async fn index_l1_batches(&self) -> anyhow::Result<bool> {
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let last_sealed = storage
+            .blocks_dal()
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        let next_l1_batch = storage
+            .bridge_transfers_dal()
+            .get_last_indexed_l1_batch()
+            .await
+            .context("get_last_indexed_l1_batch()")?
+            .map_or(0, |number| number.0 + 1);
+        if next_l1_batch > last_sealed.0 {
+            return Ok(false);
+        }
+
+        let to_l1_batch = last_sealed
+            .0
+            .min(next_l1_batch + MAX_L1_BATCHES_PER_ITERATION - 1);
+        for number in next_l1_batch..=to_l1_batch {
+            let transfer_count = self
+                .index_l1_batch(&mut storage, L1BatchNumber(number))
+                .await?;
+            metrics::counter!("server.bridge_indexer.transfers", transfer_count as u64);
+        }
+        metrics::gauge!("server.bridge_indexer.l1_batch", to_l1_batch as f64);
+        Ok(to_l1_batch < last_sealed.0)
+    }
+
+    async fn index_l1_batch(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        number: L1BatchNumber,
+    ) -> anyhow::Result<usize> {
+        let header = storage
+            .blocks_dal()
+            .get_l1_batch_header(number)
+            .await
+            .context("get_l1_batch_header()")?
+            .with_context(|| format!("sealed L1 batch #{number} is missing"))?;
+        let withdrawals = withdrawal_messages(
+            number,
+            &header.l2_to_l1_logs,
+            &header.l2_to_l1_messages,
+            self.l2_erc20_bridge_addr,
+        )?;
+        let withdrawal_tx_indices: Vec<_> = withdrawals
+            .iter()
+            .map(|withdrawal| withdrawal.log.tx_number_in_block)
+            .collect();
+        let txs = storage
+            .bridge_transfers_dal()
+            .get_l1_batch_transfer_txs(number, &withdrawal_tx_indices)
+            .await
+            .context("get_l1_batch_transfer_txs()")?;
+
+        let mut transfers =
+            collect_transfers(number, &txs, &withdrawals, self.l2_erc20_bridge_addr)?;
+        let l1_tokens: Vec<_> = transfers.iter().map(|transfer| transfer.l1_token).collect();
+        let l2_tokens = storage
+            .bridge_transfers_dal()
+            .get_l2_token_addresses(&l1_tokens)
+            .await
+            .context("get_l2_token_addresses()")?;
+        for transfer in &mut transfers {
+            transfer.l2_token = l2_tokens.get(&transfer.l1_token).copied();
+        }
+
+        storage
+            .bridge_transfers_dal()
+            .insert_l1_batch_transfers(number, &transfers)
+            .await
+            .context("insert_l1_batch_transfers()")?;
+        Ok(transfers.len())
+    }
+
+    /// Processes withdrawal finalization events in the next finalized L1 blocks. Returns `true`
+    /// if there are more L1 blocks to process.
+    async fn process_l1_events(&self) -> anyhow::Result<bool> {
+        let finalized_block = self
+            .eth_client
+            .block("finalized".to_owned(), COMPONENT)
+            .await?
+            .context("finalized L1 block is missing")?
+            .number
+            .context("finalized L1 block has no number")?
+            .as_u64();
+
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let from_block = storage
+            .bridge_transfers_dal()
+            .get_last_processed_l1_block()
+            .await
+            .context("get_last_processed_l1_block()")?
+            // If no blocks were processed yet, scan the same history as the priority ops watcher.
+            .map_or(
+                finalized_block.saturating_sub(PRIORITY_EXPIRATION),
+                |block| block + 1,
+            );
+        if from_block > finalized_block {
+            return Ok(false);
+        }
+        let to_block = finalized_block.min(from_block + MAX_L1_BLOCKS_PER_ITERATION - 1);
+
+        let topics = vec![*ETH_WITHDRAWAL_FINALIZED_TOPIC, *WITHDRAWAL_FINALIZED_TOPIC];
+        let filter = FilterBuilder::default()
+            .address(vec![self.diamond_proxy_addr, self.l1_erc20_bridge_addr])
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into()))
+            .topics(Some(topics), None, None, None)
+            .build();
+        let logs = self.eth_client.logs(filter, COMPONENT).await?;
+        let events = logs.iter().filter_map(|log| {
+            FinalizationEvent::parse(log, self.diamond_proxy_addr, self.l1_erc20_bridge_addr)
+        });
+        for event in events {
+            self.process_finalization(&mut storage, &event).await?;
+        }
+
+        storage
+            .bridge_transfers_dal()
+            .set_last_processed_l1_block(to_block)
+            .await
+            .context("set_last_processed_l1_block()")?;
+        metrics::gauge!("server.bridge_indexer.l1_block", to_block as f64);
+        Ok(to_block < finalized_block)
+    }
+
+    /// Marks the withdrawal finalized by the event as finalized. If there are several initiated withdrawals
+    /// matching the event, the oldest one finalized on L1 is marked.
+    async fn process_finalization(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        event: &FinalizationEvent,
+    ) -> anyhow::Result<()> {
+        let candidates = storage
+            .bridge_transfers_dal()
+            .get_initiated_withdrawals(event.l1_receiver, event.l1_token, event.amount)
+            .await
+            .context("get_initiated_withdrawals()")?;
+        for (l1_batch_number, l2_message_index) in candidates {
+            if !self
+                .is_finalized_on_l1(event.l1_token, l1_batch_number, l2_message_index)
+                .await?
+            {
+                continue;
+            }
+            storage
+                .bridge_transfers_dal()
+                .mark_withdrawal_as_finalized(l1_batch_number, l2_message_index, event.l1_tx_hash)
+                .await
+                .context("mark_withdrawal_as_finalized()")?;
+            metrics::increment_counter!("server.bridge_indexer.finalized_withdrawals");
+            return Ok(());
+        }
+
+        // May happen for withdrawals initiated before the indexer was launched.
+        tracing::info!("No initiated withdrawal matches finalization event {event:?}");
+        Ok(())
+    }
+
+    async fn is_finalized_on_l1(
+        &self,
+        l1_token: Address,
+        l1_batch_number: L1BatchNumber,
+        l2_message_index: u32,
+    ) -> anyhow::Result<bool> {
+        let (function, contract_addr, contract) = if l1_token == Address::zero() {
+            (
+                "isEthWithdrawalFinalized",
+                self.diamond_proxy_addr,
+                &self.zksync_contract,
+            )
+        } else {
+            (
+                "isWithdrawalFinalized",
+                self.l1_erc20_bridge_addr,
+                &self.l1_bridge_contract,
+            )
+        };
+        let is_finalized = self
+            .eth_client
+            .call_contract_function(
+                function,
+                (U256::from(l1_batch_number.0), U256::from(l2_message_index)),
+                None,
+                Options::default(),
+                None,
+                contract_addr,
+                contract.clone(),
+            )
+            .await?;
+        Ok(is_finalized)
+    }
+}
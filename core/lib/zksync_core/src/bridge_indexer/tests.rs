@@ -0,0 +1,324 @@
+use db_test_macro::db_test;
+use std::sync::Arc;
+
+use zksync_dal::ConnectionPool;
+use zksync_eth_client::clients::mock::MockEthereum;
+use zksync_types::{
+    block::L1BatchHeader, ethabi::Token, l2_to_l1_log::L2ToL1Log, Execute, MiniblockNumber,
+    L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS,
+};
+use zksync_utils::address_to_h256;
+
+use super::*;
+
+const L2_ERC20_BRIDGE_ADDR: Address = Address::repeat_byte(0xbb);
+const DIAMOND_PROXY_ADDR: Address = Address::repeat_byte(0xd1);
+const L1_ERC20_BRIDGE_ADDR: Address = Address::repeat_byte(0xb1);
+
+fn eth_withdrawal_message(receiver: Address, amount: u64) -> Vec<u8> {
+    let finalize_params = [
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(16),
+        ParamType::Bytes,
+        ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+    ];
+    let mut message = ethabi::short_signature("finalizeEthWithdrawal", &finalize_params).to_vec();
+    message.extend_from_slice(receiver.as_bytes());
+    message.extend_from_slice(&<[u8; 32]>::from(U256::from(amount)));
+    message
+}
+
+fn messenger_log(tx_number_in_block: u16, sender: Address, message: &[u8]) -> L2ToL1Log {
+    L2ToL1Log {
+        shard_id: 0,
+        is_service: true,
+        tx_number_in_block,
+        sender: L1_MESSENGER_ADDRESS,
+        key: address_to_h256(&sender),
+        value: H256(keccak256(message)),
+    }
+}
+
+fn eth_finalization_log(receiver: Address, amount: u64, l1_tx_hash: H256) -> Log {
+    Log {
+        address: DIAMOND_PROXY_ADDR,
+        topics: vec![*ETH_WITHDRAWAL_FINALIZED_TOPIC, address_to_h256(&receiver)],
+        data: <[u8; 32]>::from(U256::from(amount)).to_vec().into(),
+        block_hash: Some(H256::repeat_byte(0x11)),
+        block_number: Some(1_u64.into()),
+        transaction_hash: Some(l1_tx_hash),
+        transaction_index: Some(0_u64.into()),
+        log_index: Some(0_u64.into()),
+        transaction_log_index: Some(0_u64.into()),
+        log_type: None,
+        removed: None,
+    }
+}
+
+fn finalize_deposit_calldata(call: &DepositCall) -> Vec<u8> {
+    let args = ethabi::encode(&[
+        Token::Address(call.l1_sender),
+        Token::Address(call.l2_receiver),
+        Token::Address(call.l1_token),
+        Token::Uint(call.amount),
+        Token::Bytes(vec![]),
+    ]);
+    FINALIZE_DEPOSIT_SELECTOR
+        .iter()
+        .copied()
+        .chain(args)
+        .collect()
+}
+
+fn transfer_tx(index_in_l1_batch: u16, is_priority: bool, execute: Execute) -> BridgeTransferTx {
+    BridgeTransferTx {
+        hash: H256::repeat_byte(index_in_l1_batch as u8),
+        index_in_l1_batch,
+        miniblock_number: MiniblockNumber(10),
+        is_priority,
+        initiator_address: Address::repeat_byte(0x10 + index_in_l1_batch as u8),
+        execute,
+        l1_tx_hash: is_priority.then(|| H256::repeat_byte(0xff)),
+        failed: false,
+    }
+}
+
+#[test]
+fn parsing_deposit_calls() {
+    let call = DepositCall {
+        l1_sender: Address::repeat_byte(1),
+        l2_receiver: Address::repeat_byte(2),
+        l1_token: Address::repeat_byte(3),
+        amount: 1_000.into(),
+    };
+    let calldata = finalize_deposit_calldata(&call);
+    assert_eq!(DepositCall::parse(&calldata), Some(call));
+    assert_eq!(DepositCall::parse(&calldata[..calldata.len() - 32]), None);
+    assert_eq!(DepositCall::parse(&calldata[4..]), None);
+}
+
+#[test]
+fn collecting_transfers_from_l1_batch() {
+    let l1_batch_number = L1BatchNumber(1);
+    let receiver = Address::repeat_byte(1);
+    let messages = vec![eth_withdrawal_message(receiver, 500), b"other".to_vec()];
+    let logs = vec![
+        messenger_log(2, L2_ETH_TOKEN_ADDRESS, &messages[0]),
+        messenger_log(2, Address::repeat_byte(0xcc), &messages[1]),
+    ];
+    let withdrawals =
+        withdrawal_messages(l1_batch_number, &logs, &messages, L2_ERC20_BRIDGE_ADDR).unwrap();
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0].log.tx_number_in_block, 2);
+    assert_eq!(withdrawals[0].l2_message_index, 0);
+
+    let deposit_call = DepositCall {
+        l1_sender: Address::repeat_byte(2),
+        l2_receiver: Address::repeat_byte(3),
+        l1_token: Address::repeat_byte(4),
+        amount: 1_000.into(),
+    };
+    let erc20_deposit = Execute {
+        contract_address: L2_ERC20_BRIDGE_ADDR,
+        calldata: finalize_deposit_calldata(&deposit_call),
+        value: U256::zero(),
+        factory_deps: None,
+    };
+    let eth_deposit = Execute {
+        contract_address: receiver,
+        calldata: vec![],
+        value: 100.into(),
+        factory_deps: None,
+    };
+    let l1_call = Execute {
+        contract_address: Address::repeat_byte(5),
+        calldata: vec![1, 2, 3],
+        value: U256::zero(),
+        factory_deps: None,
+    };
+    let txs = [
+        transfer_tx(0, true, erc20_deposit),
+        transfer_tx(1, true, l1_call),
+        transfer_tx(2, false, eth_deposit.clone()),
+        transfer_tx(3, true, eth_deposit),
+    ];
+
+    let transfers =
+        collect_transfers(l1_batch_number, &txs, &withdrawals, L2_ERC20_BRIDGE_ADDR).unwrap();
+    assert_eq!(transfers.len(), 3);
+    let kinds: Vec<_> = transfers.iter().map(|transfer| transfer.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            BridgeTransferKind::Deposit,
+            BridgeTransferKind::Withdrawal,
+            BridgeTransferKind::Deposit
+        ]
+    );
+
+    assert_eq!(transfers[0].sender, deposit_call.l1_sender);
+    assert_eq!(transfers[0].receiver, deposit_call.l2_receiver);
+    assert_eq!(transfers[0].l1_token, deposit_call.l1_token);
+    assert_eq!(transfers[0].status, BridgeTransferStatus::Finalized);
+    assert_eq!(transfers[0].l1_tx_hash, Some(H256::repeat_byte(0xff)));
+
+    assert_eq!(transfers[1].sender, txs[2].initiator_address);
+    assert_eq!(transfers[1].receiver, receiver);
+    assert_eq!(transfers[1].amount, 500.into());
+    assert_eq!(transfers[1].status, BridgeTransferStatus::Initiated);
+    assert_eq!(transfers[1].l2_message_index, Some(0));
+
+    assert_eq!(transfers[2].sender, txs[3].initiator_address);
+    assert_eq!(transfers[2].receiver, receiver);
+    assert_eq!(transfers[2].l1_token, Address::zero());
+    assert_eq!(transfers[2].amount, 100.into());
+}
+
+#[test]
+fn parsing_finalization_events() {
+    let receiver = Address::repeat_byte(1);
+    let l1_tx_hash = H256::repeat_byte(0xaa);
+    let log = eth_finalization_log(receiver, 500, l1_tx_hash);
+    let event = FinalizationEvent::parse(&log, DIAMOND_PROXY_ADDR, L1_ERC20_BRIDGE_ADDR).unwrap();
+    assert_eq!(
+        event,
+        FinalizationEvent {
+            l1_receiver: receiver,
+            l1_token: Address::zero(),
+            amount: 500.into(),
+            l1_tx_hash,
+        }
+    );
+
+    let l1_token = Address::repeat_byte(2);
+    let erc20_log = Log {
+        address: L1_ERC20_BRIDGE_ADDR,
+        topics: vec![
+            *WITHDRAWAL_FINALIZED_TOPIC,
+            address_to_h256(&receiver),
+            address_to_h256(&l1_token),
+        ],
+        ..log.clone()
+    };
+    let event =
+        FinalizationEvent::parse(&erc20_log, DIAMOND_PROXY_ADDR, L1_ERC20_BRIDGE_ADDR).unwrap();
+    assert_eq!(event.l1_token, l1_token);
+    assert_eq!(event.amount, 500.into());
+
+    // Events from unexpected contracts and removed events must be ignored.
+    let foreign_log = Log {
+        address: Address::repeat_byte(0xff),
+        ..log.clone()
+    };
+    assert_eq!(
+        FinalizationEvent::parse(&foreign_log, DIAMOND_PROXY_ADDR, L1_ERC20_BRIDGE_ADDR),
+        None
+    );
+    let mismatched_log = Log {
+        address: L1_ERC20_BRIDGE_ADDR,
+        ..log.clone()
+    };
+    assert_eq!(
+        FinalizationEvent::parse(&mismatched_log, DIAMOND_PROXY_ADDR, L1_ERC20_BRIDGE_ADDR),
+        None
+    );
+    let removed_log = Log {
+        removed: Some(true),
+        ..log
+    };
+    assert_eq!(
+        FinalizationEvent::parse(&removed_log, DIAMOND_PROXY_ADDR, L1_ERC20_BRIDGE_ADDR),
+        None
+    );
+}
+
+fn create_indexer(
+    pool: ConnectionPool,
+    eth_client: Arc<MockEthereum>,
+) -> BridgeIndexer<Arc<MockEthereum>> {
+    BridgeIndexer {
+        pool,
+        eth_client,
+        l2_erc20_bridge_addr: L2_ERC20_BRIDGE_ADDR,
+        diamond_proxy_addr: DIAMOND_PROXY_ADDR,
+        l1_erc20_bridge_addr: L1_ERC20_BRIDGE_ADDR,
+        zksync_contract: zksync_contract(),
+        l1_bridge_contract: l1_bridge_contract(),
+    }
+}
+
+#[db_test]
+async fn finalizing_withdrawals_from_l1_events(pool: ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let header = L1BatchHeader::new(
+        L1BatchNumber(1),
+        0,
+        Address::default(),
+        Default::default(),
+        Default::default(),
+    );
+    storage
+        .blocks_dal()
+        .insert_l1_batch(&header, &[], Default::default())
+        .await
+        .unwrap();
+    let receiver = Address::repeat_byte(1);
+    let withdrawal = BridgeTransfer {
+        kind: BridgeTransferKind::Withdrawal,
+        status: BridgeTransferStatus::Initiated,
+        sender: Address::repeat_byte(2),
+        receiver,
+        l1_token: Address::zero(),
+        l2_token: Some(Address::zero()),
+        amount: 500.into(),
+        l1_tx_hash: None,
+        l2_tx_hash: H256::repeat_byte(1),
+        miniblock_number: MiniblockNumber(1),
+        l1_batch_number: L1BatchNumber(1),
+        l2_message_index: Some(0),
+    };
+    storage
+        .bridge_transfers_dal()
+        .insert_l1_batch_transfers(L1BatchNumber(1), &[withdrawal])
+        .await
+        .unwrap();
+
+    let eth_client = Arc::new(MockEthereum::default());
+    eth_client
+        .block_number
+        .store(100, std::sync::atomic::Ordering::SeqCst);
+    let l1_tx_hash = H256::repeat_byte(0xaa);
+    eth_client.logs.write().unwrap().extend([
+        eth_finalization_log(receiver, 500, l1_tx_hash),
+        // Doesn't match any initiated withdrawal.
+        eth_finalization_log(receiver, 1_000, H256::repeat_byte(0xbb)),
+    ]);
+    eth_client
+        .contract_call_results
+        .write()
+        .unwrap()
+        .insert("isEthWithdrawalFinalized".to_owned(), Token::Bool(true));
+
+    let indexer = create_indexer(pool.clone(), eth_client);
+    let has_more = indexer.iteration().await.unwrap();
+    assert!(!has_more);
+
+    let transfers = storage
+        .bridge_transfers_dal()
+        .get_transfers_for_address(receiver, 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].status, BridgeTransferStatus::Finalized);
+    assert_eq!(transfers[0].l1_tx_hash, Some(l1_tx_hash));
+    let last_processed_block = storage
+        .bridge_transfers_dal()
+        .get_last_processed_l1_block()
+        .await
+        .unwrap();
+    assert_eq!(last_processed_block, Some(100));
+
+    // Events from the already processed blocks must not be processed again.
+    assert!(!indexer.iteration().await.unwrap());
+}
@@ -0,0 +1,656 @@
+//! Auditing of L1 batch commitments.
+//!
+//! The auditor recomputes every component of an L1 batch commitment (pass-through data, meta parameters
+//! and auxiliary output, including the L2-to-L1 log digests) from the raw data in Postgres, and diffs the results
+//! both against the metadata stored in Postgres and against the data committed on L1.
+
+use anyhow::Context as _;
+use serde::Serialize;
+
+use std::collections::HashMap;
+
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_merkle_tree::domain::TreeMetadata;
+use zksync_types::{
+    block::L1BatchHeader,
+    commitment::{CommitmentSchemes, L1BatchMetadata, L1BatchWithMetadata},
+    l2_to_l1_log::L2ToL1Log,
+    state_diffs::{self, RepeatedWriteDiff, StateDiffCompressionVersion},
+    web3::{
+        ethabi,
+        signing::keccak256,
+        transports::Http,
+        types::{TransactionId, U64},
+        Web3,
+    },
+    writes::{InitialStorageWrite, RepeatedStorageWrite},
+    L1BatchCommitmentMode, L1BatchNumber, StorageLogKind, H256,
+};
+
+use super::extract_commit_data;
+use crate::metadata_calculator::{L1BatchWithLogs, MetadataCalculator};
+
+/// Names of the fields in the commit data of an L1 batch, in the order they are encoded
+/// (see [`L1BatchWithMetadata::l1_commit_data()`]).
+const COMMIT_DATA_FIELDS: [&str; 12] = [
+    "blockNumber",
+    "timestamp",
+    "indexRepeatedStorageChanges",
+    "newStateRoot",
+    "numberOfLayer1Txs",
+    "l2LogsTreeRoot",
+    "priorityOperationsHash",
+    "initialStorageChanges",
+    "repeatedStorageChanges",
+    "l2Logs",
+    "l2ArbitraryLengthMessages",
+    "factoryDeps",
+];
+
+/// Mismatch between a recomputed component of an L1 batch commitment and its actual value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ComponentMismatch {
+    pub component: &'static str,
+    /// Value recomputed from the raw data in Postgres.
+    pub recomputed: String,
+    /// Value stored in Postgres or committed on L1.
+    pub actual: String,
+}
+
+impl ComponentMismatch {
+    fn new(component: &'static str, recomputed: String, actual: String) -> Self {
+        Self {
+            component,
+            recomputed,
+            actual,
+        }
+    }
+}
+
+/// Results of auditing a single L1 batch.
+#[derive(Debug, Clone, Serialize)]
+pub struct L1BatchAuditReport {
+    pub l1_batch_number: L1BatchNumber,
+    /// Hash of the L1 transaction committing the batch. `None` if the batch isn't committed yet,
+    /// in which case only the metadata stored in Postgres is audited.
+    pub commit_tx_hash: Option<H256>,
+    /// Mismatches between the recomputed commitment components and the metadata stored in Postgres.
+    pub stored_mismatches: Vec<ComponentMismatch>,
+    /// Mismatches between the recomputed commit data and the data committed on L1.
+    pub committed_mismatches: Vec<ComponentMismatch>,
+}
+
+impl L1BatchAuditReport {
+    pub fn is_consistent(&self) -> bool {
+        self.stored_mismatches.is_empty() && self.committed_mismatches.is_empty()
+    }
+}
+
+/// Recomputes L1 batch commitments from the data in Postgres and compares them with the stored metadata
+/// and the data committed on L1.
+///
+/// The Merkle tree root hash cannot be recomputed without the tree itself, so it's taken from Postgres as is;
+/// use the Merkle tree consistency checker to verify it.
+#[derive(Debug)]
+pub struct CommitmentAuditor {
+    contract: ethabi::Contract,
+    web3: Web3<Http>,
+    pool: ConnectionPool,
+    commitment_mode: L1BatchCommitmentMode,
+    commitment_schemes: CommitmentSchemes,
+}
+
+impl CommitmentAuditor {
+    pub fn new(
+        web3_url: &str,
+        pool: ConnectionPool,
+        commitment_mode: L1BatchCommitmentMode,
+        commitment_schemes: CommitmentSchemes,
+    ) -> anyhow::Result<Self> {
+        let transport = Http::new(web3_url).context("failed creating HTTP transport for L1")?;
+        Ok(Self {
+            contract: zksync_contracts::zksync_contract(),
+            web3: Web3::new(transport),
+            pool,
+            commitment_mode,
+            commitment_schemes,
+        })
+    }
+
+    pub async fn audit_l1_batch(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<L1BatchAuditReport> {
+        let mut storage = self
+            .pool
+            .access_storage_tagged("commitment_auditor")
+            .await?;
+        let storage_l1_batch = storage
+            .blocks_dal()
+            .get_storage_l1_batch(l1_batch_number)
+            .await?
+            .with_context(|| format!("L1 batch #{l1_batch_number} not found in the database"))?;
+        let commit_tx_id = storage_l1_batch.eth_commit_tx_id;
+        let stored = storage
+            .blocks_dal()
+            .get_l1_batch_with_metadata(storage_l1_batch)
+            .await?
+            .with_context(|| {
+                format!("Metadata for L1 batch #{l1_batch_number} not found in the database")
+            })?;
+
+        let (recomputed, mut stored_mismatches) =
+            self.recompute_metadata(&mut storage, &stored).await?;
+        stored_mismatches.extend(diff_metadata(&recomputed.metadata, &stored.metadata));
+
+        let commit_tx_hash = match commit_tx_id {
+            Some(id) => {
+                storage
+                    .eth_sender_dal()
+                    .get_confirmed_tx_hash_by_eth_tx_id(id as u32)
+                    .await
+            }
+            None => None,
+        };
+        drop(storage);
+
+        let Some(commit_tx_hash) = commit_tx_hash else {
+            tracing::info!(
+                "L1 batch #{l1_batch_number} is not committed on L1; skipping L1 checks"
+            );
+            return Ok(L1BatchAuditReport {
+                l1_batch_number,
+                commit_tx_hash: None,
+                stored_mismatches,
+                committed_mismatches: vec![],
+            });
+        };
+        let committed_mismatches = self
+            .audit_commit_tx(&recomputed, commit_tx_hash)
+            .await
+            .with_context(|| format!("failed auditing commit tx {commit_tx_hash:?}"))?;
+        Ok(L1BatchAuditReport {
+            l1_batch_number,
+            commit_tx_hash: Some(commit_tx_hash),
+            stored_mismatches,
+            committed_mismatches,
+        })
+    }
+
+    /// Recomputes metadata for an L1 batch from its storage logs, initial writes and L2-to-L1 logs.
+    /// Returns the batch with the recomputed header and metadata together with mismatches between
+    /// the raw data and the stored header / metadata detected during the recomputation.
+    async fn recompute_metadata(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        stored: &L1BatchWithMetadata,
+    ) -> anyhow::Result<(L1BatchWithMetadata, Vec<ComponentMismatch>)> {
+        let l1_batch_number = stored.header.number;
+        let l1_batch = L1BatchWithLogs::new(storage, l1_batch_number)
+            .await
+            .with_context(|| format!("L1 batch #{l1_batch_number} not found in the database"))?;
+        let initial_write_indices: HashMap<_, _> = storage
+            .storage_logs_dedup_dal()
+            .initial_writes_for_batch(l1_batch_number)
+            .await
+            .into_iter()
+            .collect();
+
+        // Writes are ordered in the same way as when they are applied to the Merkle tree.
+        let writes: Vec<_> = l1_batch
+            .storage_logs
+            .iter()
+            .filter(|log| log.kind == StorageLogKind::Write)
+            .collect();
+        let updated_keys: Vec<_> = writes
+            .iter()
+            .map(|log| log.key.hashed_key())
+            .filter(|hashed_key| !initial_write_indices.contains_key(hashed_key))
+            .collect();
+        let enumeration_indices = storage
+            .storage_logs_dedup_dal()
+            .get_enumeration_indices(&updated_keys)
+            .await;
+        let previous_values = storage
+            .storage_logs_dal()
+            .get_previous_storage_values(&updated_keys, l1_batch_number)
+            .await;
+
+        let mut initial_writes = vec![];
+        let mut repeated_writes = vec![];
+        let mut repeated_write_diffs = vec![];
+        for log in writes {
+            let hashed_key = log.key.hashed_key();
+            if let Some(&index) = initial_write_indices.get(&hashed_key) {
+                initial_writes.push(InitialStorageWrite {
+                    index,
+                    key: log.key.hashed_key_u256(),
+                    value: log.value,
+                });
+            } else {
+                let index = *enumeration_indices.get(&hashed_key).with_context(|| {
+                    format!("enumeration index for updated key {hashed_key:?} is missing")
+                })?;
+                // No-op updates are not published, same as in the Merkle tree.
+                let previous_value = previous_values.get(&hashed_key).copied().flatten();
+                if previous_value != Some(log.value) {
+                    let write = RepeatedStorageWrite {
+                        index,
+                        value: log.value,
+                    };
+                    repeated_write_diffs.push(RepeatedWriteDiff {
+                        write: write.clone(),
+                        previous_value: previous_value.unwrap_or_default(),
+                    });
+                    repeated_writes.push(write);
+                }
+            }
+        }
+
+        let mut mismatches = vec![];
+        // Leaf indices are allocated sequentially, so the next index must follow the last index allocated
+        // in this batch or before it.
+        let rollup_last_leaf_index = storage
+            .storage_logs_dedup_dal()
+            .max_enumeration_index_for_l1_batch(l1_batch_number)
+            .await
+            .map_or(1, |index| index + 1);
+        if rollup_last_leaf_index != stored.metadata.rollup_last_leaf_index {
+            mismatches.push(ComponentMismatch::new(
+                "rollup_last_leaf_index",
+                rollup_last_leaf_index.to_string(),
+                stored.metadata.rollup_last_leaf_index.to_string(),
+            ));
+        }
+
+        // L2-to-L1 logs are taken from the per-miniblock table rather than from the batch header.
+        let l2_to_l1_logs = storage
+            .events_dal()
+            .get_l2_to_l1_logs_for_l1_batch(l1_batch_number)
+            .await?;
+        if l2_to_l1_logs != stored.header.l2_to_l1_logs {
+            let describe_logs = |logs: &[L2ToL1Log]| {
+                let bytes: Vec<_> = logs.iter().flat_map(L2ToL1Log::to_bytes).collect();
+                format!("{} logs, {}", logs.len(), describe_bytes(&bytes))
+            };
+            mismatches.push(ComponentMismatch::new(
+                "l2_to_l1_logs",
+                describe_logs(&l2_to_l1_logs),
+                describe_logs(&stored.header.l2_to_l1_logs),
+            ));
+        }
+        let header = L1BatchHeader {
+            l2_to_l1_logs,
+            ..stored.header.clone()
+        };
+
+        let protocol_version = header.protocol_version.unwrap_or_default();
+        let compressed_state_diffs =
+            match StateDiffCompressionVersion::for_protocol_version(protocol_version) {
+                StateDiffCompressionVersion::V1 => None,
+                StateDiffCompressionVersion::V2 => Some(state_diffs::compress_v2(
+                    &initial_writes,
+                    &repeated_write_diffs,
+                )),
+            };
+
+        // The Merkle tree root hash cannot be recomputed here; it's checked against `newStateRoot` committed on L1.
+        let tree_metadata = TreeMetadata {
+            root_hash: stored.metadata.root_hash,
+            rollup_last_leaf_index,
+            initial_writes,
+            repeated_writes,
+            witness: None,
+        };
+        let metadata = MetadataCalculator::build_l1_batch_metadata(
+            tree_metadata,
+            &header,
+            &self.commitment_schemes,
+            self.commitment_mode,
+            compressed_state_diffs,
+        );
+        let recomputed = L1BatchWithMetadata {
+            header,
+            metadata,
+            factory_deps: stored.factory_deps.clone(),
+        };
+        Ok((recomputed, mismatches))
+    }
+
+    async fn audit_commit_tx(
+        &self,
+        recomputed: &L1BatchWithMetadata,
+        commit_tx_hash: H256,
+    ) -> anyhow::Result<Vec<ComponentMismatch>> {
+        // Calldata is taken from L1 rather than from Postgres, since the latter is what's being audited.
+        let commit_tx = self
+            .web3
+            .eth()
+            .transaction(TransactionId::Hash(commit_tx_hash))
+            .await?
+            .context("commit tx not found on L1")?;
+        let commit_tx_status = self
+            .web3
+            .eth()
+            .transaction_receipt(commit_tx_hash)
+            .await?
+            .context("commit tx receipt not found on L1")?
+            .status;
+
+        let mut mismatches = vec![];
+        if commit_tx_status != Some(U64::one()) {
+            mismatches.push(ComponentMismatch::new(
+                "commit_tx_status",
+                "1".to_owned(),
+                format!("{commit_tx_status:?}"),
+            ));
+        }
+        let committed =
+            extract_commit_data(&self.contract, &commit_tx.input.0, recomputed.header.number)?;
+        let recomputed = recomputed.l1_commit_data(self.commitment_mode);
+        mismatches.extend(diff_commit_data(&recomputed, &committed));
+        Ok(mismatches)
+    }
+}
+
+fn describe_bytes(bytes: &[u8]) -> String {
+    format!(
+        "{} bytes (keccak256: {:?})",
+        bytes.len(),
+        H256(keccak256(bytes))
+    )
+}
+
+fn describe_token(token: &ethabi::Token) -> String {
+    match token {
+        ethabi::Token::Bytes(bytes) => describe_bytes(bytes),
+        ethabi::Token::Array(items) => {
+            let encoded = ethabi::encode(std::slice::from_ref(token));
+            format!(
+                "{} items, ABI-encoded {}",
+                items.len(),
+                describe_bytes(&encoded)
+            )
+        }
+        _ => token.to_string(),
+    }
+}
+
+fn diff_metadata(recomputed: &L1BatchMetadata, stored: &L1BatchMetadata) -> Vec<ComponentMismatch> {
+    let mut mismatches = vec![];
+    let mut check_hash = |component, recomputed: H256, stored: H256| {
+        if recomputed != stored {
+            mismatches.push(ComponentMismatch::new(
+                component,
+                format!("{recomputed:?}"),
+                format!("{stored:?}"),
+            ));
+        }
+    };
+    check_hash("commitment", recomputed.commitment, stored.commitment);
+    check_hash(
+        "pass_through_data_hash",
+        recomputed.pass_through_data_hash,
+        stored.pass_through_data_hash,
+    );
+    check_hash(
+        "meta_parameters_hash",
+        recomputed.meta_parameters_hash,
+        stored.meta_parameters_hash,
+    );
+    check_hash(
+        "aux_data_hash",
+        recomputed.aux_data_hash,
+        stored.aux_data_hash,
+    );
+    check_hash(
+        "l2_l1_merkle_root",
+        recomputed.l2_l1_merkle_root,
+        stored.l2_l1_merkle_root,
+    );
+
+    if recomputed.block_meta_params != stored.block_meta_params {
+        mismatches.push(ComponentMismatch::new(
+            "block_meta_params",
+            format!("{:?}", recomputed.block_meta_params),
+            format!("{:?}", stored.block_meta_params),
+        ));
+    }
+
+    let mut check_bytes = |component, recomputed: &[u8], stored: &[u8]| {
+        if recomputed != stored {
+            mismatches.push(ComponentMismatch::new(
+                component,
+                describe_bytes(recomputed),
+                describe_bytes(stored),
+            ));
+        }
+    };
+    check_bytes(
+        "l2_l1_messages_compressed",
+        &recomputed.l2_l1_messages_compressed,
+        &stored.l2_l1_messages_compressed,
+    );
+    check_bytes(
+        "initial_writes_compressed",
+        &recomputed.initial_writes_compressed,
+        &stored.initial_writes_compressed,
+    );
+    check_bytes(
+        "repeated_writes_compressed",
+        &recomputed.repeated_writes_compressed,
+        &stored.repeated_writes_compressed,
+    );
+    mismatches
+}
+
+pub(super) fn diff_commit_data(
+    recomputed: &ethabi::Token,
+    committed: &ethabi::Token,
+) -> Vec<ComponentMismatch> {
+    let (ethabi::Token::Tuple(recomputed_fields), ethabi::Token::Tuple(committed_fields)) =
+        (recomputed, committed)
+    else {
+        return vec![ComponentMismatch::new(
+            "commit_data",
+            describe_token(recomputed),
+            describe_token(committed),
+        )];
+    };
+    if committed_fields.len() != COMMIT_DATA_FIELDS.len() {
+        return vec![ComponentMismatch::new(
+            "commit_data",
+            format!("{} fields", recomputed_fields.len()),
+            format!("{} fields", committed_fields.len()),
+        )];
+    }
+
+    COMMIT_DATA_FIELDS
+        .iter()
+        .zip(recomputed_fields.iter().zip(committed_fields))
+        .filter(|(_, (recomputed, committed))| recomputed != committed)
+        .map(|(&name, (recomputed, committed))| {
+            ComponentMismatch::new(name, describe_token(recomputed), describe_token(committed))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use db_test_macro::db_test;
+    use tempfile::TempDir;
+
+    use zksync_types::{
+        tx::IncludedTxLocation, AccountTreeId, Address, MiniblockNumber, StorageKey, StorageLog,
+        U256,
+    };
+
+    use super::*;
+    use crate::metadata_calculator::tests::{reset_db_state, run_calculator, setup_calculator};
+
+    fn mock_commit_data() -> ethabi::Token {
+        ethabi::Token::Tuple(vec![
+            ethabi::Token::Uint(1.into()),
+            ethabi::Token::Uint(1_000.into()),
+            ethabi::Token::Uint(10.into()),
+            ethabi::Token::FixedBytes(vec![1; 32]),
+            ethabi::Token::Uint(0.into()),
+            ethabi::Token::FixedBytes(vec![2; 32]),
+            ethabi::Token::FixedBytes(vec![3; 32]),
+            ethabi::Token::Bytes(vec![0; 4]),
+            ethabi::Token::Bytes(vec![0; 4]),
+            ethabi::Token::Bytes(vec![0; 4]),
+            ethabi::Token::Array(vec![]),
+            ethabi::Token::Array(vec![ethabi::Token::Bytes(vec![4; 64])]),
+        ])
+    }
+
+    #[test]
+    fn identical_commit_data_has_no_mismatches() {
+        let data = mock_commit_data();
+        assert_eq!(diff_commit_data(&data, &data), []);
+    }
+
+    #[test]
+    fn commit_data_mismatches_are_reported_per_field() {
+        let recomputed = mock_commit_data();
+        let mut committed = recomputed.clone();
+        let ethabi::Token::Tuple(fields) = &mut committed else {
+            unreachable!();
+        };
+        fields[2] = ethabi::Token::Uint(U256::from(11));
+        fields[8] = ethabi::Token::Bytes(vec![0; 8]);
+
+        let mismatches = diff_commit_data(&recomputed, &committed);
+        let components: Vec<_> = mismatches.iter().map(|m| m.component).collect();
+        assert_eq!(
+            components,
+            ["indexRepeatedStorageChanges", "repeatedStorageChanges"]
+        );
+        assert!(mismatches[1].recomputed.starts_with("4 bytes"));
+        assert!(mismatches[1].actual.starts_with("8 bytes"));
+    }
+
+    #[test]
+    fn malformed_commit_data_is_reported() {
+        let recomputed = mock_commit_data();
+        let committed = ethabi::Token::Tuple(vec![ethabi::Token::Uint(1.into())]);
+        let mismatches = diff_commit_data(&recomputed, &committed);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].component, "commit_data");
+    }
+
+    async fn prepare_auditor(
+        pool: &ConnectionPool,
+        prover_pool: ConnectionPool,
+    ) -> CommitmentAuditor {
+        let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+        let (calculator, _) = setup_calculator(temp_dir.path(), pool).await;
+        reset_db_state(pool, 2).await;
+        run_calculator(calculator, pool.clone(), prover_pool).await;
+
+        // The auditor doesn't access L1 for uncommitted batches, so the URL is never used.
+        CommitmentAuditor::new(
+            "http://127.0.0.1:1",
+            pool.clone(),
+            L1BatchCommitmentMode::Rollup,
+            CommitmentSchemes::default(),
+        )
+        .unwrap()
+    }
+
+    #[db_test]
+    async fn auditing_consistent_l1_batches(pool: ConnectionPool, prover_pool: ConnectionPool) {
+        let auditor = prepare_auditor(&pool, prover_pool).await;
+        for l1_batch_number in [L1BatchNumber(1), L1BatchNumber(2)] {
+            let report = auditor.audit_l1_batch(l1_batch_number).await.unwrap();
+            assert_eq!(report.commit_tx_hash, None);
+            assert_eq!(report.stored_mismatches, [], "{report:?}");
+            assert!(report.is_consistent());
+        }
+    }
+
+    #[db_test]
+    async fn auditing_l1_batch_with_corrupted_l2_to_l1_logs(
+        pool: ConnectionPool,
+        prover_pool: ConnectionPool,
+    ) {
+        let auditor = prepare_auditor(&pool, prover_pool).await;
+        let log = L2ToL1Log {
+            shard_id: 0,
+            is_service: true,
+            tx_number_in_block: 0,
+            sender: Address::repeat_byte(1),
+            key: H256::repeat_byte(2),
+            value: H256::repeat_byte(3),
+        };
+        let tx_location = IncludedTxLocation {
+            tx_hash: H256::zero(),
+            tx_index_in_miniblock: 0,
+            tx_initiator_address: Address::zero(),
+        };
+        let mut storage = pool.access_storage().await.unwrap();
+        storage
+            .events_dal()
+            .save_l2_to_l1_logs(MiniblockNumber(2), &[(tx_location, vec![&log])])
+            .await;
+        drop(storage);
+
+        let report = auditor.audit_l1_batch(L1BatchNumber(1)).await.unwrap();
+        assert!(report.is_consistent(), "{report:?}");
+
+        let report = auditor.audit_l1_batch(L1BatchNumber(2)).await.unwrap();
+        let components: Vec<_> = report
+            .stored_mismatches
+            .iter()
+            .map(|mismatch| mismatch.component)
+            .collect();
+        assert_eq!(components[0], "l2_to_l1_logs");
+        assert!(components.contains(&"commitment"), "{components:?}");
+        assert!(components.contains(&"l2_l1_merkle_root"), "{components:?}");
+        assert!(
+            components.contains(&"l2_l1_messages_compressed"),
+            "{components:?}"
+        );
+        assert!(report.stored_mismatches[0].recomputed.starts_with("1 logs"));
+        assert!(report.stored_mismatches[0].actual.starts_with("0 logs"));
+    }
+
+    #[db_test]
+    async fn auditing_l1_batch_with_corrupted_initial_writes(
+        pool: ConnectionPool,
+        prover_pool: ConnectionPool,
+    ) {
+        let auditor = prepare_auditor(&pool, prover_pool).await;
+        // Add a write to L1 batch #1 after its metadata was computed. The write gets an enumeration index
+        // greater than the indices allocated in L1 batch #2, as if the indices were corrupted.
+        let key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(0xaa)), H256::zero());
+        let log = StorageLog::new_write_log(key, H256::repeat_byte(1));
+        let mut storage = pool.access_storage().await.unwrap();
+        storage
+            .storage_logs_dal()
+            .append_storage_logs(MiniblockNumber(1), &[(H256::zero(), vec![log])])
+            .await;
+        storage
+            .storage_logs_dedup_dal()
+            .insert_initial_writes(L1BatchNumber(1), &[key])
+            .await;
+        drop(storage);
+
+        let report = auditor.audit_l1_batch(L1BatchNumber(1)).await.unwrap();
+        let components: Vec<_> = report
+            .stored_mismatches
+            .iter()
+            .map(|mismatch| mismatch.component)
+            .collect();
+        assert!(
+            components.contains(&"rollup_last_leaf_index"),
+            "{components:?}"
+        );
+        assert!(
+            components.contains(&"initial_writes_compressed"),
+            "{components:?}"
+        );
+        assert!(components.contains(&"commitment"), "{components:?}");
+    }
+}
@@ -1,7 +1,73 @@
+use anyhow::Context as _;
+
 use std::time::Duration;
-use zksync_dal::ConnectionPool;
-use zksync_types::web3::{error, ethabi, transports::Http, types::TransactionId, Web3};
-use zksync_types::L1BatchNumber;
+
+use zksync_dal::{ConnectionPool, SqlxError};
+use zksync_types::web3::{
+    error, ethabi,
+    transports::Http,
+    types::{TransactionId, U64},
+    Web3,
+};
+use zksync_types::{L1BatchCommitmentMode, L1BatchNumber, U256};
+
+pub use self::audit::{CommitmentAuditor, ComponentMismatch, L1BatchAuditReport};
+
+mod audit;
+
+/// Extracts commit data for the specified L1 batch from the calldata of a `commitBlocks` call.
+pub(crate) fn extract_commit_data(
+    contract: &ethabi::Contract,
+    calldata: &[u8],
+    batch_number: L1BatchNumber,
+) -> anyhow::Result<ethabi::Token> {
+    let calldata = calldata.get(4..).context("calldata is too short")?;
+    let mut commitments = contract
+        .function("commitBlocks")?
+        .decode_input(calldata)?
+        .pop()
+        .and_then(ethabi::Token::into_array)
+        .context("ABI does not match the commitBlocks() function on the zkSync contract")?;
+
+    // Commit transactions usually publish multiple commitments at once, so we need to find
+    // the one that corresponds to the batch we're checking.
+    let first_batch_number = match commitments.first() {
+        Some(ethabi::Token::Tuple(tuple)) => tuple
+            .first()
+            .cloned()
+            .and_then(ethabi::Token::into_uint)
+            .context("ABI does not match the commitBlocks() function on the zkSync contract")?,
+        _ => anyhow::bail!("ABI does not match the commitBlocks() function on the zkSync contract"),
+    };
+    let index = U256::from(batch_number.0)
+        .checked_sub(first_batch_number)
+        .filter(|index| *index < U256::from(commitments.len()))
+        .map(|index| index.as_usize())
+        .with_context(|| {
+            format!(
+                "commit tx doesn't contain L1 batch #{batch_number}; first committed batch: {first_batch_number}, \
+                 committed batches: {}",
+                commitments.len()
+            )
+        })?;
+    Ok(commitments.swap_remove(index))
+}
+
+/// Errors that can occur when checking an L1 batch for consistency.
+#[derive(Debug, thiserror::Error)]
+enum CheckError {
+    /// Transient error communicating with L1; the check should be retried.
+    #[error("Web3 error communicating with L1: {0}")]
+    Web3(#[from] error::Error),
+    #[error("internal error: {0:#}")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl From<SqlxError> for CheckError {
+    fn from(err: SqlxError) -> Self {
+        Self::Internal(err.into())
+    }
+}
 
 #[derive(Debug)]
 pub struct ConsistencyChecker {
@@ -11,12 +77,19 @@ pub struct ConsistencyChecker {
     max_batches_to_recheck: u32,
     web3: Web3<Http>,
     db: ConnectionPool,
+    // Mode in which the main node commits L1 batches
+    commitment_mode: L1BatchCommitmentMode,
 }
 
 const SLEEP_DELAY: Duration = Duration::from_secs(5);
 
 impl ConsistencyChecker {
-    pub fn new(web3_url: &str, max_batches_to_recheck: u32, db: ConnectionPool) -> Self {
+    pub fn new(
+        web3_url: &str,
+        max_batches_to_recheck: u32,
+        db: ConnectionPool,
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> Self {
         let web3 = Web3::new(Http::new(web3_url).unwrap());
         let contract = zksync_contracts::zksync_contract();
         Self {
@@ -24,46 +97,45 @@ impl ConsistencyChecker {
             contract,
             max_batches_to_recheck,
             db,
+            commitment_mode,
         }
     }
 
-    async fn check_commitments(&self, batch_number: L1BatchNumber) -> Result<bool, error::Error> {
-        let mut storage = self.db.access_storage().await.unwrap();
+    /// Compares commit data of the L1 batch with the data committed on L1. Returns mismatched components
+    /// of the commit data; an empty list means that the batch is consistent.
+    async fn check_commitments(
+        &self,
+        batch_number: L1BatchNumber,
+    ) -> Result<Vec<ComponentMismatch>, CheckError> {
+        let mut storage = self.db.access_storage().await?;
 
         let storage_l1_batch = storage
             .blocks_dal()
             .get_storage_l1_batch(batch_number)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| panic!("L1 batch #{} not found in the database", batch_number));
+            .await?
+            .with_context(|| format!("L1 batch #{batch_number} not found in the database"))?;
 
         let commit_tx_id = storage_l1_batch
             .eth_commit_tx_id
-            .unwrap_or_else(|| panic!("Commit tx not found for L1 batch #{}", batch_number))
+            .with_context(|| format!("Commit tx not found for L1 batch #{batch_number}"))?
             as u32;
 
         let block_metadata = storage
             .blocks_dal()
             .get_l1_batch_with_metadata(storage_l1_batch)
-            .await
-            .unwrap()
-            .unwrap_or_else(|| {
-                panic!(
-                    "Metadata for L1 batch #{} not found in the database",
-                    batch_number
-                )
-            });
+            .await?
+            .with_context(|| {
+                format!("Metadata for L1 batch #{batch_number} not found in the database")
+            })?;
 
         let commit_tx_hash = storage
             .eth_sender_dal()
             .get_confirmed_tx_hash_by_eth_tx_id(commit_tx_id)
             .await
-            .unwrap_or_else(|| {
-                panic!(
-                    "Commit tx hash not found in the database. Commit tx id: {}",
-                    commit_tx_id
-                )
-            });
+            .with_context(|| {
+                format!("Commit tx hash not found in the database. Commit tx id: {commit_tx_id}")
+            })?;
+        drop(storage);
 
         tracing::info!(
             "Checking commit tx {} for batch {}",
@@ -77,54 +149,44 @@ impl ConsistencyChecker {
             .eth()
             .transaction(TransactionId::Hash(commit_tx_hash))
             .await?
-            .expect("Commit tx not found on L1");
+            .with_context(|| format!("Commit tx {commit_tx_hash:?} not found on L1"))?;
 
         let commit_tx_status = self
             .web3
             .eth()
             .transaction_receipt(commit_tx_hash)
             .await?
-            .expect("Commit tx receipt not found on L1")
+            .with_context(|| format!("Commit tx {commit_tx_hash:?} receipt not found on L1"))?
             .status;
 
-        assert_eq!(
-            commit_tx_status,
-            Some(1.into()),
-            "Main node gave us a failed commit tx"
-        );
+        let mut mismatches = vec![];
+        if commit_tx_status != Some(U64::one()) {
+            // The main node gave us a failed commit tx.
+            mismatches.push(ComponentMismatch {
+                component: "commit_tx_status",
+                recomputed: "1".to_owned(),
+                actual: format!("{commit_tx_status:?}"),
+            });
+        }
 
-        let commitments = self
-            .contract
-            .function("commitBlocks")
-            .unwrap()
-            .decode_input(&commit_tx.input.0[4..])
-            .unwrap()
-            .pop()
-            .unwrap()
-            .into_array()
-            .unwrap();
-
-        // Commit transactions usually publish multiple commitments at once, so we need to find
-        // the one that corresponds to the batch we're checking.
-        let first_batch_number = match &commitments[0] {
-            ethabi::Token::Tuple(tuple) => tuple[0].clone().into_uint().unwrap().as_usize(),
-            _ => panic!("ABI does not match the commitBlocks() function on the zkSync contract"),
-        };
-        let commitment = &commitments[batch_number.0 as usize - first_batch_number];
-
-        Ok(commitment == &block_metadata.l1_commit_data())
+        let commitment = extract_commit_data(&self.contract, &commit_tx.input.0, batch_number)
+            .with_context(|| {
+                format!("Failed extracting commit data from commit tx {commit_tx_hash:?}")
+            })?;
+        let local_commitment = block_metadata.l1_commit_data(self.commitment_mode);
+        mismatches.extend(audit::diff_commit_data(&local_commitment, &commitment));
+        Ok(mismatches)
     }
 
-    async fn last_committed_batch(&self) -> L1BatchNumber {
-        self.db
+    async fn last_committed_batch(&self) -> anyhow::Result<L1BatchNumber> {
+        Ok(self
+            .db
             .access_storage()
-            .await
-            .unwrap()
+            .await?
             .blocks_dal()
             .get_number_of_last_l1_batch_committed_on_eth()
-            .await
-            .unwrap()
-            .unwrap_or(L1BatchNumber(0))
+            .await?
+            .unwrap_or(L1BatchNumber(0)))
     }
 
     pub async fn run(
@@ -133,7 +195,7 @@ impl ConsistencyChecker {
     ) -> anyhow::Result<()> {
         let mut batch_number: L1BatchNumber = self
             .last_committed_batch()
-            .await
+            .await?
             .0
             .saturating_sub(self.max_batches_to_recheck)
             .max(1)
@@ -150,24 +212,22 @@ impl ConsistencyChecker {
             let batch_has_metadata = self
                 .db
                 .access_storage()
-                .await
-                .unwrap()
+                .await?
                 .blocks_dal()
                 .get_l1_batch_metadata(batch_number)
-                .await
-                .unwrap()
+                .await?
                 .is_some();
 
             // The batch might be already committed but not yet processed by the external node's tree
             // OR the batch might be processed by the external node's tree but not yet committed.
             // We need both.
-            if !batch_has_metadata || self.last_committed_batch().await < batch_number {
+            if !batch_has_metadata || self.last_committed_batch().await? < batch_number {
                 tokio::time::sleep(SLEEP_DELAY).await;
                 continue;
             }
 
             match self.check_commitments(batch_number).await {
-                Ok(true) => {
+                Ok(mismatches) if mismatches.is_empty() => {
                     tracing::info!("Batch {} is consistent with L1", batch_number.0);
                     metrics::gauge!(
                         "external_node.last_correct_batch",
@@ -176,13 +236,35 @@ impl ConsistencyChecker {
                     );
                     batch_number.0 += 1;
                 }
-                Ok(false) => {
-                    anyhow::bail!("Batch {} is inconsistent with L1", batch_number.0);
+                Ok(mismatches) => {
+                    for mismatch in &mismatches {
+                        tracing::error!(
+                            "Batch {} has inconsistent `{}`: expected {}, committed on L1 {}",
+                            batch_number.0,
+                            mismatch.component,
+                            mismatch.recomputed,
+                            mismatch.actual
+                        );
+                    }
+                    metrics::increment_counter!(
+                        "external_node.consistency_checker.inconsistent_batches"
+                    );
+                    anyhow::bail!(
+                        "Batch {} is inconsistent with L1 in {} component(s)",
+                        batch_number.0,
+                        mismatches.len()
+                    );
                 }
-                Err(e) => {
-                    tracing::warn!("Consistency checker error: {}", e);
+                Err(CheckError::Web3(err)) => {
+                    tracing::warn!("Consistency checker error communicating with L1: {err}");
                     tokio::time::sleep(SLEEP_DELAY).await;
                 }
+                Err(CheckError::Internal(err)) => {
+                    return Err(err.context(format!(
+                        "failed checking batch {} for consistency",
+                        batch_number.0
+                    )));
+                }
             }
         }
         Ok(())
@@ -0,0 +1,135 @@
+//! Controllable clock used for miniblock and L1 batch timestamps in the dev mode.
+
+use std::{
+    cmp,
+    sync::{Arc, Mutex},
+};
+
+use zksync_utils::time::seconds_since_epoch;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ClockState {
+    /// Offset of the dev mode time relative to the wall clock, in seconds.
+    offset: i64,
+    /// Exact timestamp to use for the next miniblock or L1 batch.
+    next_timestamp: Option<u64>,
+}
+
+/// Clock providing timestamps for new miniblocks and L1 batches in the dev mode. Similar to Hardhat Network,
+/// allows to shift time forward or to set the exact timestamp for the next block.
+#[derive(Debug, Clone, Default)]
+pub struct DevModeClock(Arc<Mutex<ClockState>>);
+
+/// Saved state of a [`DevModeClock`].
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ClockSnapshot {
+    state: ClockState,
+    taken_at: u64,
+}
+
+impl DevModeClock {
+    /// Shifts time forward by the specified number of seconds. Returns the total offset relative to the wall clock.
+    pub fn increase_time(&self, seconds: u64) -> i64 {
+        let mut state = self.0.lock().unwrap();
+        let seconds = i64::try_from(seconds).unwrap_or(i64::MAX);
+        state.offset = state.offset.saturating_add(seconds);
+        state.offset
+    }
+
+    /// Sets the exact timestamp for the next miniblock or L1 batch. Time continues to flow from this timestamp
+    /// for the following blocks.
+    pub fn set_next_timestamp(&self, timestamp: u64) {
+        self.0.lock().unwrap().next_timestamp = Some(timestamp);
+    }
+
+    /// Returns the timestamp for a new miniblock or L1 batch. It is guaranteed to be larger than
+    /// `prev_miniblock_timestamp`.
+    pub fn next_timestamp(&self, prev_miniblock_timestamp: u64) -> u64 {
+        self.next_timestamp_at(seconds_since_epoch(), prev_miniblock_timestamp)
+    }
+
+    /// Saves the current clock state.
+    pub(super) fn snapshot(&self) -> ClockSnapshot {
+        self.snapshot_at(seconds_since_epoch())
+    }
+
+    fn snapshot_at(&self, now: u64) -> ClockSnapshot {
+        ClockSnapshot {
+            state: *self.0.lock().unwrap(),
+            taken_at: now,
+        }
+    }
+
+    /// Restores the clock state from a snapshot. Similar to Hardhat Network, time is rewound to the moment
+    /// the snapshot was taken.
+    pub(super) fn restore(&self, snapshot: ClockSnapshot) {
+        self.restore_at(seconds_since_epoch(), snapshot);
+    }
+
+    fn restore_at(&self, now: u64, snapshot: ClockSnapshot) {
+        let mut state = self.0.lock().unwrap();
+        *state = snapshot.state;
+        state.offset = state
+            .offset
+            .saturating_add(snapshot.taken_at as i64 - now as i64);
+    }
+
+    fn next_timestamp_at(&self, now: u64, prev_miniblock_timestamp: u64) -> u64 {
+        let mut state = self.0.lock().unwrap();
+        let timestamp = if let Some(timestamp) = state.next_timestamp.take() {
+            state.offset = timestamp as i64 - now as i64;
+            timestamp
+        } else {
+            cmp::max(now as i64 + state.offset, 0) as u64
+        };
+        cmp::max(timestamp, prev_miniblock_timestamp + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_without_adjustments_follows_wall_clock() {
+        let clock = DevModeClock::default();
+        assert_eq!(clock.next_timestamp_at(1_000, 900), 1_000);
+        // Timestamps must be strictly increasing even if miniblocks are sealed more often than once per second.
+        assert_eq!(clock.next_timestamp_at(1_000, 1_000), 1_001);
+        assert_eq!(clock.next_timestamp_at(1_000, 1_005), 1_006);
+    }
+
+    #[test]
+    fn increasing_time() {
+        let clock = DevModeClock::default();
+        assert_eq!(clock.increase_time(60), 60);
+        assert_eq!(clock.increase_time(40), 100);
+        assert_eq!(clock.next_timestamp_at(1_000, 900), 1_100);
+        assert_eq!(clock.next_timestamp_at(1_010, 1_100), 1_110);
+    }
+
+    #[test]
+    fn setting_next_timestamp() {
+        let clock = DevModeClock::default();
+        clock.increase_time(100);
+        clock.set_next_timestamp(5_000);
+        assert_eq!(clock.next_timestamp_at(1_000, 900), 5_000);
+        // Time continues to flow from the set timestamp.
+        assert_eq!(clock.next_timestamp_at(1_010, 5_000), 5_010);
+        assert_eq!(clock.increase_time(0), 4_000);
+    }
+
+    #[test]
+    fn restoring_from_snapshot() {
+        let clock = DevModeClock::default();
+        clock.increase_time(100);
+        let snapshot = clock.snapshot_at(1_000);
+        clock.increase_time(1_000);
+        clock.set_next_timestamp(10_000);
+
+        // Time is rewound to the snapshot moment, and the next timestamp override is discarded.
+        clock.restore_at(1_050, snapshot);
+        assert_eq!(clock.next_timestamp_at(1_050, 900), 1_100);
+        assert_eq!(clock.next_timestamp_at(1_060, 1_100), 1_110);
+    }
+}
@@ -0,0 +1,71 @@
+//! Fork mode lazily fetching the state from a remote zkSync Era node (similar to `anvil --fork-url`).
+
+use anyhow::Context as _;
+use tokio::runtime::Handle;
+
+use std::sync::Arc;
+
+use zksync_state::{ForkSource, ForkedState};
+use zksync_types::{
+    api::{BlockIdVariant, BlockNumber},
+    MiniblockNumber, StorageKey, StorageValue, H256, U64,
+};
+use zksync_utils::h256_to_u256;
+use zksync_web3_decl::{
+    jsonrpsee::http_client::{HttpClient, HttpClientBuilder},
+    namespaces::{EthNamespaceClient, ZksNamespaceClient},
+};
+
+/// [`ForkSource`] fetching the state of a remote node via its Web3 API.
+///
+/// Methods of this source block on the provided Tokio runtime, so they must not be called from async code directly.
+#[derive(Debug)]
+struct RemoteForkSource {
+    client: HttpClient,
+    miniblock: MiniblockNumber,
+    rt_handle: Handle,
+}
+
+impl ForkSource for RemoteForkSource {
+    fn get_storage_value(&self, key: &StorageKey) -> anyhow::Result<StorageValue> {
+        let block = BlockIdVariant::BlockNumber(BlockNumber::Number(U64::from(self.miniblock.0)));
+        let request =
+            self.client
+                .get_storage_at(*key.address(), h256_to_u256(*key.key()), Some(block));
+        self.rt_handle
+            .block_on(request)
+            .with_context(|| format!("eth_getStorageAt({key:?}, {})", self.miniblock))
+    }
+
+    fn get_bytecode(&self, hash: H256) -> anyhow::Result<Option<Vec<u8>>> {
+        // Bytecodes are addressed by their hashes, so they don't depend on the block.
+        self.rt_handle
+            .block_on(self.client.get_bytecode_by_hash(hash))
+            .with_context(|| format!("zks_getBytecodeByHash({hash:?})"))
+    }
+}
+
+/// Creates the forked state for a remote node at the specified URL. If `miniblock` is not specified,
+/// the state is pinned at the latest miniblock of the remote node.
+pub async fn fork_remote_node(
+    url: &str,
+    miniblock: Option<MiniblockNumber>,
+) -> anyhow::Result<ForkedState> {
+    let client = HttpClientBuilder::default()
+        .build(url)
+        .with_context(|| format!("failed creating client for fork URL {url}"))?;
+    let miniblock = match miniblock {
+        Some(number) => number,
+        None => {
+            let number = client.get_block_number().await.context("eth_blockNumber")?;
+            MiniblockNumber(number.as_u32())
+        }
+    };
+    tracing::info!("Forking state of node at {url} at miniblock #{miniblock}");
+
+    Ok(ForkedState::new(Arc::new(RemoteForkSource {
+        client,
+        miniblock,
+        rt_handle: Handle::current(),
+    })))
+}
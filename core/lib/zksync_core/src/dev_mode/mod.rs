@@ -0,0 +1,523 @@
+//! Developer mode intended for local dapp development against the real state keeper and VM
+//! (similar to Hardhat Network or Anvil).
+//!
+//! In this mode, the state keeper seals each transaction in its own L1 batch (so that block timestamps
+//! can be chosen when the transaction arrives), and L1 batches are marked as committed, proven and executed
+//! right after sealing without sending anything to L1. The Web3 API additionally exposes `hardhat_*` methods
+//! allowing to impersonate accounts and to override balances and contract bytecodes, and `evm_*` methods
+//! allowing to control block timestamps and to take and revert state snapshots. All state overrides
+//! are implemented as synthetic priority operations, so they are executed by the VM and end up in miniblocks
+//! like any other transaction.
+//!
+//! Optionally, the dev mode can fork the state of a remote node: storage slots and bytecodes not present
+//! in the local state are lazily fetched from the remote node at a pinned miniblock when executing transactions.
+
+use anyhow::Context as _;
+use chrono::Utc;
+use tokio::sync::{oneshot, watch, Mutex};
+use zksync_contracts::deployer_contract;
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    sync::{Arc, Mutex as StdMutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use vm::TransactionVmExt;
+use zksync_dal::ConnectionPool;
+use zksync_state::{ForkedState, PostgresStorageCaches};
+use zksync_types::{
+    aggregated_operations::AggregatedActionType,
+    api::TransactionStatus,
+    ethabi::Token,
+    l1::{L1Tx, OpProcessingType, PriorityQueueType},
+    storage_key_for_eth_balance,
+    transaction_request::CallRequest,
+    web3::signing::keccak256,
+    Address, Execute, L1BatchNumber, L1BlockNumber, L1TxCommonData, PriorityOpId, Transaction,
+    CONTRACT_DEPLOYER_ADDRESS, CONTRACT_FORCE_DEPLOYER_ADDRESS, H160, H256,
+    REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE, U256,
+};
+use zksync_utils::{
+    bytecode::{hash_bytecode, validate_bytecode, InvalidBytecodeError},
+    h256_to_u256,
+    time::millis_since_epoch,
+};
+
+use crate::block_reverter::BlockReverter;
+
+mod clock;
+mod fork;
+mod snapshots;
+
+pub(crate) use self::snapshots::StateReverter;
+use self::snapshots::{RevertQueue, RevertRequest, Snapshot, Snapshots};
+pub use self::{clock::DevModeClock, fork::fork_remote_node};
+
+/// Gas limit used for synthetic priority operations if it's not specified explicitly.
+pub const DEFAULT_PRIORITY_OP_GAS_LIMIT: u64 = 20_000_000;
+/// Interval between checks whether a priority operation was executed by the state keeper.
+const EXECUTION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Maximum time to wait for a priority operation to be executed by the state keeper.
+const EXECUTION_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval between checks for newly sealed L1 batches in the batch finalizer.
+const FINALIZER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Recipient of ETH removed from accounts when decreasing their balance.
+const BALANCE_SINK_ADDRESS: Address = H160([0; 20]);
+
+/// Errors that can occur when processing dev mode requests.
+#[derive(Debug, thiserror::Error)]
+pub enum DevModeError {
+    #[error("account {0:?} is not impersonated")]
+    NotImpersonated(Address),
+    #[error("invalid request: {0}")]
+    InvalidRequest(&'static str),
+    #[error("invalid bytecode: {0}")]
+    InvalidBytecode(#[from] InvalidBytecodeError),
+    #[error(
+        "timestamp {timestamp} is not greater than the previous block timestamp {prev_timestamp}"
+    )]
+    TimestampTooLow { timestamp: u64, prev_timestamp: u64 },
+    #[error("priority operation {0:?} failed")]
+    PriorityOpFailed(H256),
+    #[error("priority operation {0:?} was not executed in {EXECUTION_TIMEOUT:?}")]
+    Timeout(H256),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+#[derive(Debug)]
+struct DevModeState {
+    pool: ConnectionPool,
+    clock: DevModeClock,
+    fork: Option<ForkedState>,
+    impersonated_accounts: RwLock<HashSet<Address>>,
+    snapshots: Mutex<Snapshots>,
+    revert_queue: RevertQueue,
+    /// VM execution caches used by the API server; reset after state reverts.
+    storage_caches: StdMutex<Vec<PostgresStorageCaches>>,
+    /// Serializes allocation of priority operation IDs. Also held during state reverts, so that no priority
+    /// operations are inserted concurrently.
+    priority_op_lock: Mutex<()>,
+    /// Serializes balance overrides, since they depend on the current balance.
+    balance_lock: Mutex<()>,
+}
+
+/// Handle shared by the dev mode components: the Web3 API methods and the L1 batch finalizer.
+#[derive(Debug, Clone)]
+pub struct DevModeHandle(Arc<DevModeState>);
+
+impl DevModeHandle {
+    pub fn new(pool: ConnectionPool, fork: Option<ForkedState>) -> Self {
+        Self(Arc::new(DevModeState {
+            pool,
+            clock: DevModeClock::default(),
+            fork,
+            impersonated_accounts: RwLock::default(),
+            snapshots: Mutex::default(),
+            revert_queue: RevertQueue::default(),
+            storage_caches: StdMutex::default(),
+            priority_op_lock: Mutex::default(),
+            balance_lock: Mutex::default(),
+        }))
+    }
+
+    /// Returns the clock that should be used by the state keeper to assign block timestamps.
+    pub fn clock(&self) -> DevModeClock {
+        self.0.clock.clone()
+    }
+
+    /// Returns the forked remote state, if any. It should be used by all VM instances (in the state keeper
+    /// and in the API server).
+    pub fn fork(&self) -> Option<ForkedState> {
+        self.0.fork.clone()
+    }
+
+    /// Creates the state keeper counterpart processing state revert requests.
+    pub(crate) fn state_reverter(&self, block_reverter: BlockReverter) -> StateReverter {
+        StateReverter::new(self.0.revert_queue.clone(), block_reverter)
+    }
+
+    /// Registers VM execution caches that should be reset when the node state is reverted.
+    pub fn register_storage_caches(&self, caches: PostgresStorageCaches) {
+        self.0.storage_caches.lock().unwrap().push(caches);
+    }
+
+    /// Shifts time for the following blocks forward. Returns the total time offset in seconds.
+    pub fn increase_time(&self, seconds: u64) -> i64 {
+        self.0.clock.increase_time(seconds)
+    }
+
+    /// Sets the exact timestamp for the next block.
+    pub async fn set_next_block_timestamp(&self, timestamp: u64) -> Result<(), DevModeError> {
+        let mut storage = self.0.pool.access_storage_tagged("api").await?;
+        let prev_timestamp = storage
+            .blocks_dal()
+            .get_last_sealed_miniblock_header()
+            .await
+            .context("get_last_sealed_miniblock_header()")?
+            .map_or(0, |header| header.timestamp);
+        drop(storage);
+
+        if timestamp <= prev_timestamp {
+            return Err(DevModeError::TimestampTooLow {
+                timestamp,
+                prev_timestamp,
+            });
+        }
+        self.0.clock.set_next_timestamp(timestamp);
+        Ok(())
+    }
+
+    /// Produces a new block, optionally with the specified timestamp. Since the bootloader doesn't allow
+    /// empty miniblocks in the middle of an L1 batch, the block contains a single no-op priority operation.
+    pub async fn mine(&self, timestamp: Option<u64>) -> Result<(), DevModeError> {
+        if let Some(timestamp) = timestamp {
+            self.set_next_block_timestamp(timestamp).await?;
+        }
+
+        let execute = Execute {
+            contract_address: Address::zero(),
+            calldata: vec![],
+            value: U256::zero(),
+            factory_deps: None,
+        };
+        let gas_limit = DEFAULT_PRIORITY_OP_GAS_LIMIT.into();
+        let tx_hash = self
+            .insert_priority_op(Address::zero(), execute, U256::zero(), gas_limit)
+            .await?;
+        self.wait_for_successful_execution(tx_hash).await
+    }
+
+    /// Saves the current node state and returns the snapshot ID that can be passed to [`Self::revert()`].
+    pub async fn snapshot(&self) -> Result<u64, DevModeError> {
+        let mut storage = self.0.pool.access_storage_tagged("api").await?;
+        let last_miniblock = storage
+            .blocks_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        drop(storage);
+
+        let snapshot = Snapshot {
+            last_miniblock,
+            clock: self.0.clock.snapshot(),
+        };
+        let id = self.0.snapshots.lock().await.push(snapshot);
+        tracing::info!("Taken snapshot #{id} at miniblock #{last_miniblock}");
+        Ok(id)
+    }
+
+    /// Reverts the node state to the specified snapshot. The snapshot and all snapshots taken after it
+    /// are discarded. Returns `false` if there is no snapshot with the specified ID.
+    ///
+    /// Transactions executed after the snapshot are removed; they are not returned to the mempool.
+    pub async fn revert(&self, snapshot_id: u64) -> Result<bool, DevModeError> {
+        // The lock is held during the revert, so that no snapshots are taken concurrently.
+        let mut snapshots = self.0.snapshots.lock().await;
+        let last_miniblock = match snapshots.get(snapshot_id) {
+            Some(snapshot) => snapshot.last_miniblock,
+            None => return Ok(false),
+        };
+        let _guard = self.0.priority_op_lock.lock().await;
+
+        let (response_sender, response) = oneshot::channel();
+        self.0.revert_queue.push(RevertRequest {
+            last_miniblock_to_keep: last_miniblock,
+            response: response_sender,
+        });
+        tokio::time::timeout(EXECUTION_TIMEOUT, response)
+            .await
+            .context("timed out waiting for the state keeper to revert state")?
+            .context("state keeper dropped the revert request")??;
+
+        // The snapshot is only discarded after a successful revert, so that a failed revert can be retried.
+        let snapshot = snapshots
+            .take(snapshot_id)
+            .expect("snapshot was removed while snapshots were locked");
+        drop(snapshots);
+        for caches in self.0.storage_caches.lock().unwrap().iter() {
+            caches.reset(last_miniblock);
+        }
+        self.0.clock.restore(snapshot.clock);
+        tracing::info!("Reverted to snapshot #{snapshot_id} at miniblock #{last_miniblock}");
+        Ok(true)
+    }
+
+    pub fn impersonate_account(&self, address: Address) {
+        let mut accounts = self.0.impersonated_accounts.write().unwrap();
+        accounts.insert(address);
+    }
+
+    pub fn stop_impersonating_account(&self, address: Address) {
+        let mut accounts = self.0.impersonated_accounts.write().unwrap();
+        accounts.remove(&address);
+    }
+
+    pub fn is_impersonated(&self, address: Address) -> bool {
+        let accounts = self.0.impersonated_accounts.read().unwrap();
+        accounts.contains(&address)
+    }
+
+    /// Sends a transaction on behalf of an impersonated account. Returns the transaction hash
+    /// once the transaction is executed (successfully or not).
+    pub async fn send_impersonated_transaction(
+        &self,
+        request: CallRequest,
+    ) -> Result<H256, DevModeError> {
+        let from = request
+            .from
+            .ok_or(DevModeError::InvalidRequest("`from` must be specified"))?;
+        if !self.is_impersonated(from) {
+            return Err(DevModeError::NotImpersonated(from));
+        }
+        let contract_address = request.to.ok_or(DevModeError::InvalidRequest(
+            "contract deployments must be sent to `ContractDeployer` explicitly",
+        ))?;
+
+        // Since nothing is minted, the transferred value is taken from the sender's balance
+        // like for ordinary transactions.
+        let execute = Execute {
+            contract_address,
+            calldata: request.data.map(|data| data.0).unwrap_or_default(),
+            value: request.value.unwrap_or_default(),
+            factory_deps: request.eip712_meta.and_then(|meta| meta.factory_deps),
+        };
+        let gas_limit = request
+            .gas
+            .unwrap_or_else(|| DEFAULT_PRIORITY_OP_GAS_LIMIT.into());
+        let tx_hash = self
+            .insert_priority_op(from, execute, U256::zero(), gas_limit)
+            .await?;
+        self.wait_for_execution(tx_hash).await?;
+        Ok(tx_hash)
+    }
+
+    /// Sets the ETH balance of the specified account. The balance is increased by minting ETH,
+    /// and decreased by transferring the excess to [`BALANCE_SINK_ADDRESS`].
+    pub async fn set_balance(&self, address: Address, balance: U256) -> Result<(), DevModeError> {
+        let _guard = self.0.balance_lock.lock().await;
+
+        let mut storage = self.0.pool.access_storage_tagged("api").await?;
+        let balance_key = storage_key_for_eth_balance(&address);
+        let current_balance = storage
+            .storage_dal()
+            .get_by_key(&balance_key)
+            .await
+            .map_or_else(U256::zero, h256_to_u256);
+        drop(storage);
+
+        // The minted ETH is credited to the sender; the call itself doesn't touch `address`, so that
+        // it doesn't fail if `address` is a contract without a fallback function.
+        let (to_mint, value) = match balance.cmp(&current_balance) {
+            Ordering::Greater => (balance - current_balance, U256::zero()),
+            Ordering::Less => (U256::zero(), current_balance - balance),
+            Ordering::Equal => return Ok(()),
+        };
+        let execute = Execute {
+            contract_address: BALANCE_SINK_ADDRESS,
+            calldata: vec![],
+            value,
+            factory_deps: None,
+        };
+        let gas_limit = DEFAULT_PRIORITY_OP_GAS_LIMIT.into();
+        let tx_hash = self
+            .insert_priority_op(address, execute, to_mint, gas_limit)
+            .await?;
+        self.wait_for_successful_execution(tx_hash).await
+    }
+
+    /// Sets the bytecode of the specified account using a forced deployment.
+    pub async fn set_code(&self, address: Address, bytecode: Vec<u8>) -> Result<(), DevModeError> {
+        validate_bytecode(&bytecode)?;
+        let bytecode_hash = hash_bytecode(&bytecode);
+
+        let deployment = Token::Tuple(vec![
+            Token::FixedBytes(bytecode_hash.as_bytes().to_vec()),
+            Token::Address(address),
+            Token::Bool(false), // `callConstructor`
+            Token::Uint(U256::zero()),
+            Token::Bytes(vec![]),
+        ]);
+        let calldata = deployer_contract()
+            .function("forceDeployOnAddresses")
+            .context("`forceDeployOnAddresses` is missing from the deployer ABI")?
+            .encode_input(&[Token::Array(vec![deployment])])
+            .context("failed encoding `forceDeployOnAddresses` calldata")?;
+        let execute = Execute {
+            contract_address: CONTRACT_DEPLOYER_ADDRESS,
+            calldata,
+            value: U256::zero(),
+            factory_deps: Some(vec![bytecode]),
+        };
+
+        let gas_limit = DEFAULT_PRIORITY_OP_GAS_LIMIT.into();
+        let tx_hash = self
+            .insert_priority_op(
+                CONTRACT_FORCE_DEPLOYER_ADDRESS,
+                execute,
+                U256::zero(),
+                gas_limit,
+            )
+            .await?;
+        self.wait_for_successful_execution(tx_hash).await
+    }
+
+    /// Inserts a free priority operation into the mempool and returns its hash.
+    async fn insert_priority_op(
+        &self,
+        sender: Address,
+        execute: Execute,
+        to_mint: U256,
+        gas_limit: U256,
+    ) -> anyhow::Result<H256> {
+        let _guard = self.0.priority_op_lock.lock().await;
+
+        let mut storage = self.0.pool.access_storage_tagged("api").await?;
+        let serial_id = match storage.transactions_dal().last_priority_id().await {
+            Some(PriorityOpId(id)) => PriorityOpId(id + 1),
+            None => PriorityOpId(0),
+        };
+        let mut tx = L1Tx {
+            execute,
+            common_data: L1TxCommonData {
+                sender,
+                serial_id,
+                gas_limit,
+                gas_per_pubdata_limit: REQUIRED_L1_TO_L2_GAS_PER_PUBDATA_BYTE.into(),
+                op_processing_type: OpProcessingType::Common,
+                priority_queue_type: PriorityQueueType::Deque,
+                to_mint,
+                refund_recipient: sender,
+                ..L1TxCommonData::default()
+            },
+            received_timestamp_ms: millis_since_epoch() as u64,
+        };
+        tx.common_data.canonical_tx_hash = Transaction::from(tx.clone())
+            .canonical_l1_tx_hash()
+            .context("cannot compute hash for priority operation")?;
+
+        let tx_hash = tx.hash();
+        tracing::info!("Inserting dev mode priority operation #{serial_id} with hash {tx_hash:?}");
+        storage
+            .transactions_dal()
+            .insert_transaction_l1(tx, L1BlockNumber(0))
+            .await;
+        Ok(tx_hash)
+    }
+
+    async fn wait_for_execution(&self, tx_hash: H256) -> Result<TransactionStatus, DevModeError> {
+        let started_at = Instant::now();
+        loop {
+            let mut storage = self.0.pool.access_storage_tagged("api").await?;
+            let details = storage
+                .transactions_web3_dal()
+                .get_transaction_details(tx_hash)
+                .await
+                .context("get_transaction_details()")?;
+            drop(storage);
+
+            match details {
+                Some(details) if !matches!(details.status, TransactionStatus::Pending) => {
+                    return Ok(details.status);
+                }
+                _ if started_at.elapsed() > EXECUTION_TIMEOUT => {
+                    return Err(DevModeError::Timeout(tx_hash));
+                }
+                _ => tokio::time::sleep(EXECUTION_POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    async fn wait_for_successful_execution(&self, tx_hash: H256) -> Result<(), DevModeError> {
+        match self.wait_for_execution(tx_hash).await? {
+            TransactionStatus::Failed => Err(DevModeError::PriorityOpFailed(tx_hash)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Marks sealed L1 batches as committed, proven and executed without sending anything to L1.
+    pub async fn run_batch_finalizer(
+        self,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            self.finalize_sealed_batches().await?;
+
+            let stop_signal =
+                tokio::time::timeout(FINALIZER_POLL_INTERVAL, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for dev mode batch finalizer was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, dev mode batch finalizer is shutting down");
+        Ok(())
+    }
+
+    async fn finalize_sealed_batches(&self) -> anyhow::Result<()> {
+        let mut storage = self.0.pool.access_storage_tagged("dev_mode").await?;
+        let last_sealed_l1_batch = storage.blocks_dal().get_sealed_l1_batch_number().await?;
+        let last_executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?
+            .unwrap_or(L1BatchNumber(0));
+
+        for number in (last_executed_l1_batch.0 + 1)..=last_sealed_l1_batch.0 {
+            let l1_batch_number = L1BatchNumber(number);
+            // All actions are inserted atomically, so that a batch is never left partially finalized.
+            let mut transaction = storage.start_transaction().await?;
+            for action in [
+                AggregatedActionType::Commit,
+                AggregatedActionType::PublishProofOnchain,
+                AggregatedActionType::Execute,
+            ] {
+                transaction
+                    .eth_sender_dal()
+                    .insert_bogus_confirmed_eth_tx(
+                        l1_batch_number,
+                        action,
+                        bogus_eth_tx_hash(l1_batch_number, action),
+                        Utc::now(),
+                    )
+                    .await;
+            }
+            transaction.commit().await?;
+            tracing::info!("Marked L1 batch #{l1_batch_number} as executed");
+        }
+        Ok(())
+    }
+}
+
+/// Returns a unique synthetic L1 transaction hash for the specified L1 batch and action.
+fn bogus_eth_tx_hash(l1_batch_number: L1BatchNumber, action: AggregatedActionType) -> H256 {
+    let preimage = format!("dev_mode:{action}:{l1_batch_number}");
+    H256(keccak256(preimage.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bogus_eth_tx_hashes_are_unique() {
+        let actions = [
+            AggregatedActionType::Commit,
+            AggregatedActionType::PublishProofOnchain,
+            AggregatedActionType::Execute,
+        ];
+        let hashes: HashSet<_> = (1..=10)
+            .flat_map(|number| {
+                actions
+                    .iter()
+                    .map(move |&action| bogus_eth_tx_hash(L1BatchNumber(number), action))
+            })
+            .collect();
+        assert_eq!(hashes.len(), 30);
+    }
+}
@@ -0,0 +1,142 @@
+//! Node state snapshots for `evm_snapshot` / `evm_revert` methods in the dev mode.
+
+use tokio::sync::oneshot;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use zksync_types::{L1BatchNumber, MiniblockNumber};
+
+use super::clock::ClockSnapshot;
+use crate::block_reverter::{BlockReverter, BlockReverterFlags};
+
+#[derive(Debug)]
+pub(super) struct Snapshot {
+    /// Last miniblock included into the snapshot. Since each transaction is sealed in its own L1 batch
+    /// in the dev mode, the state is reverted to the L1 batch containing this miniblock.
+    pub last_miniblock: MiniblockNumber,
+    pub clock: ClockSnapshot,
+}
+
+/// Collection of snapshots taken by the API. Similar to Hardhat Network, snapshot IDs start from 1,
+/// and reverting to a snapshot discards it together with all snapshots taken after it.
+#[derive(Debug)]
+pub(super) struct Snapshots {
+    next_id: u64,
+    snapshots: BTreeMap<u64, Snapshot>,
+}
+
+impl Default for Snapshots {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            snapshots: BTreeMap::new(),
+        }
+    }
+}
+
+impl Snapshots {
+    pub fn push(&mut self, snapshot: Snapshot) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.snapshots.insert(id, snapshot);
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Snapshot> {
+        self.snapshots.get(&id)
+    }
+
+    /// Removes the snapshot with the specified ID and all snapshots taken after it.
+    pub fn take(&mut self, id: u64) -> Option<Snapshot> {
+        let snapshot = self.snapshots.remove(&id)?;
+        self.snapshots.split_off(&id);
+        Some(snapshot)
+    }
+}
+
+/// Request to revert the node state sent from the API server to the state keeper.
+#[derive(Debug)]
+pub(crate) struct RevertRequest {
+    pub last_miniblock_to_keep: MiniblockNumber,
+    pub response: oneshot::Sender<anyhow::Result<()>>,
+}
+
+/// Queue of state revert requests shared by the API server and the state keeper.
+#[derive(Debug, Clone, Default)]
+pub(super) struct RevertQueue(Arc<Mutex<VecDeque<RevertRequest>>>);
+
+impl RevertQueue {
+    pub(super) fn push(&self, request: RevertRequest) {
+        self.0.lock().unwrap().push_back(request);
+    }
+
+    fn pop(&self) -> Option<RevertRequest> {
+        self.0.lock().unwrap().pop_front()
+    }
+}
+
+/// State keeper counterpart of the [`RevertQueue`]. The state keeper polls revert requests between L1 batches,
+/// i.e., when no VM instances are alive, and rolls back the persisted state (Postgres and the state keeper cache)
+/// using the [`BlockReverter`]. The Merkle tree is truncated by the metadata calculator on its own.
+#[derive(Debug)]
+pub(crate) struct StateReverter {
+    queue: RevertQueue,
+    block_reverter: BlockReverter,
+}
+
+impl StateReverter {
+    pub(super) fn new(queue: RevertQueue, block_reverter: BlockReverter) -> Self {
+        Self {
+            queue,
+            block_reverter,
+        }
+    }
+
+    pub fn next_request(&self) -> Option<RevertRequest> {
+        self.queue.pop()
+    }
+
+    pub async fn rollback_db(&self, last_l1_batch_to_keep: L1BatchNumber) {
+        let flags = BlockReverterFlags::POSTGRES | BlockReverterFlags::SK_CACHE;
+        self.block_reverter
+            .rollback_db(last_l1_batch_to_keep, flags)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dev_mode::DevModeClock;
+
+    fn snapshot(last_miniblock: u32) -> Snapshot {
+        Snapshot {
+            last_miniblock: MiniblockNumber(last_miniblock),
+            clock: DevModeClock::default().snapshot(),
+        }
+    }
+
+    #[test]
+    fn taking_snapshots() {
+        let mut snapshots = Snapshots::default();
+        assert_eq!(snapshots.push(snapshot(1)), 1);
+        assert_eq!(snapshots.push(snapshot(3)), 2);
+        assert_eq!(snapshots.push(snapshot(5)), 3);
+
+        assert_eq!(snapshots.get(2).unwrap().last_miniblock, MiniblockNumber(3));
+        let taken = snapshots.take(2).unwrap();
+        assert_eq!(taken.last_miniblock, MiniblockNumber(3));
+        // Later snapshots are discarded, and IDs are not reused.
+        assert!(snapshots.take(3).is_none());
+        assert!(snapshots.take(2).is_none());
+        assert_eq!(snapshots.push(snapshot(7)), 4);
+        assert_eq!(
+            snapshots.take(1).unwrap().last_miniblock,
+            MiniblockNumber(1)
+        );
+        assert!(snapshots.take(4).is_none());
+    }
+}
@@ -11,12 +11,16 @@ use zksync_types::{
     commitment::L1BatchWithMetadata,
     helpers::unix_timestamp_ms,
     protocol_version::L1VerifierConfig,
-    L1BatchNumber, ProtocolVersionId,
+    L1BatchCommitmentMode, L1BatchNumber, ProtocolVersionId,
 };
 
-use super::publish_criterion::{
-    DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
-    TimestampDeadlineCriterion,
+use super::{
+    congestion::{AggregationLimits, L1CongestionTracker},
+    pubdata_costs::PubdataCostEstimator,
+    publish_criterion::{
+        DataSizeCriterion, GasCriterion, L1BatchPublishCriterion, NumberCriterion,
+        TimestampDeadlineCriterion,
+    },
 };
 
 #[derive(Debug)]
@@ -26,30 +30,24 @@ pub struct Aggregator {
     execute_criteria: Vec<Box<dyn L1BatchPublishCriterion>>,
     config: SenderConfig,
     blob_store: Box<dyn ObjectStore>,
+    commitment_mode: L1BatchCommitmentMode,
+    pubdata_cost_estimator: Option<PubdataCostEstimator>,
+    /// Limits on commit / execute operations derived from the config.
+    base_limits: AggregationLimits,
+    /// Limits currently used by the commit / execute criteria.
+    limits: AggregationLimits,
+    congestion_tracker: Option<L1CongestionTracker>,
 }
 
 impl Aggregator {
-    pub fn new(config: SenderConfig, blob_store: Box<dyn ObjectStore>) -> Self {
+    pub fn new(
+        config: SenderConfig,
+        blob_store: Box<dyn ObjectStore>,
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> Self {
+        let limits = AggregationLimits::new(&config);
         Self {
-            commit_criteria: vec![
-                Box::from(NumberCriterion {
-                    op: AggregatedActionType::Commit,
-                    limit: config.max_aggregated_blocks_to_commit,
-                }),
-                Box::from(GasCriterion::new(
-                    AggregatedActionType::Commit,
-                    config.max_aggregated_tx_gas,
-                )),
-                Box::from(DataSizeCriterion {
-                    op: AggregatedActionType::Commit,
-                    data_limit: config.max_eth_tx_data_size,
-                }),
-                Box::from(TimestampDeadlineCriterion {
-                    op: AggregatedActionType::Commit,
-                    deadline_seconds: config.aggregated_block_commit_deadline,
-                    max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
-                }),
-            ],
+            commit_criteria: Self::commit_criteria(&config, commitment_mode, &limits),
             proof_criteria: vec![
                 Box::from(NumberCriterion {
                     op: AggregatedActionType::PublishProofOnchain,
@@ -68,23 +66,89 @@ impl Aggregator {
                     max_allowed_lag: None,
                 }),
             ],
-            execute_criteria: vec![
-                Box::from(NumberCriterion {
-                    op: AggregatedActionType::Execute,
-                    limit: config.max_aggregated_blocks_to_execute,
-                }),
-                Box::from(GasCriterion::new(
-                    AggregatedActionType::Execute,
-                    config.max_aggregated_tx_gas,
-                )),
-                Box::from(TimestampDeadlineCriterion {
-                    op: AggregatedActionType::Execute,
-                    deadline_seconds: config.aggregated_block_execute_deadline,
-                    max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
-                }),
-            ],
+            execute_criteria: Self::execute_criteria(&config, &limits),
             config,
             blob_store,
+            commitment_mode,
+            pubdata_cost_estimator: None,
+            base_limits: limits,
+            limits,
+            congestion_tracker: None,
+        }
+    }
+
+    /// Makes the aggregator estimate costs of publishing pubdata for each commit operation.
+    pub fn with_pubdata_cost_estimator(mut self, estimator: PubdataCostEstimator) -> Self {
+        self.pubdata_cost_estimator = Some(estimator);
+        self
+    }
+
+    /// Makes the aggregator adapt the number of L1 batches in commit / execute operations and aggregation deadlines
+    /// to L1 congestion reported by `tracker`.
+    pub fn with_congestion_tracker(mut self, tracker: L1CongestionTracker) -> Self {
+        self.congestion_tracker = Some(tracker);
+        self
+    }
+
+    fn commit_criteria(
+        config: &SenderConfig,
+        commitment_mode: L1BatchCommitmentMode,
+        limits: &AggregationLimits,
+    ) -> Vec<Box<dyn L1BatchPublishCriterion>> {
+        vec![
+            Box::from(NumberCriterion {
+                op: AggregatedActionType::Commit,
+                limit: limits.max_l1_batches_to_commit,
+            }),
+            Box::from(GasCriterion::new(
+                AggregatedActionType::Commit,
+                config.max_aggregated_tx_gas,
+            )),
+            Box::from(DataSizeCriterion {
+                op: AggregatedActionType::Commit,
+                data_limit: config.max_eth_tx_data_size,
+                commitment_mode,
+            }),
+            Box::from(TimestampDeadlineCriterion {
+                op: AggregatedActionType::Commit,
+                deadline_seconds: limits.commit_deadline_seconds,
+                max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
+            }),
+        ]
+    }
+
+    fn execute_criteria(
+        config: &SenderConfig,
+        limits: &AggregationLimits,
+    ) -> Vec<Box<dyn L1BatchPublishCriterion>> {
+        vec![
+            Box::from(NumberCriterion {
+                op: AggregatedActionType::Execute,
+                limit: limits.max_l1_batches_to_execute,
+            }),
+            Box::from(GasCriterion::new(
+                AggregatedActionType::Execute,
+                config.max_aggregated_tx_gas,
+            )),
+            Box::from(TimestampDeadlineCriterion {
+                op: AggregatedActionType::Execute,
+                deadline_seconds: limits.execute_deadline_seconds,
+                max_allowed_lag: Some(config.timestamp_criteria_max_allowed_lag),
+            }),
+        ]
+    }
+
+    async fn update_limits(&mut self, storage: &mut StorageProcessor<'_>) {
+        let Some(tracker) = &self.congestion_tracker else {
+            return;
+        };
+        let limits = tracker.adapt_limits(storage, &self.base_limits).await;
+        if limits != self.limits {
+            tracing::info!("Updating aggregation limits: {limits:?}");
+            self.commit_criteria =
+                Self::commit_criteria(&self.config, self.commitment_mode, &limits);
+            self.execute_criteria = Self::execute_criteria(&self.config, &limits);
+            self.limits = limits;
         }
     }
 
@@ -96,6 +160,7 @@ impl Aggregator {
         protocol_version_id: ProtocolVersionId,
         l1_verifier_config: L1VerifierConfig,
     ) -> Option<AggregatedOperation> {
+        self.update_limits(storage).await;
         let last_sealed_l1_batch_number = storage
             .blocks_dal()
             .get_sealed_l1_batch_number()
@@ -104,7 +169,7 @@ impl Aggregator {
         if let Some(op) = self
             .get_execute_operations(
                 storage,
-                self.config.max_aggregated_blocks_to_execute as usize,
+                self.limits.max_l1_batches_to_execute as usize,
                 last_sealed_l1_batch_number,
             )
             .await
@@ -124,7 +189,7 @@ impl Aggregator {
         } else {
             self.get_commit_operation(
                 storage,
-                self.config.max_aggregated_blocks_to_commit as usize,
+                self.limits.max_l1_batches_to_commit as usize,
                 last_sealed_l1_batch_number,
                 base_system_contracts_hashes,
                 protocol_version_id,
@@ -144,11 +209,17 @@ impl Aggregator {
             .config
             .l1_batch_min_age_before_execute_seconds
             .map(|age| unix_timestamp_ms() - age * 1_000);
-        let ready_for_execute_batches = storage
+        let mut ready_for_execute_batches = storage
             .blocks_dal()
             .get_ready_for_execute_l1_batches(limit, max_l1_batch_timestamp_millis)
             .await
             .unwrap();
+        if self.commitment_mode == L1BatchCommitmentMode::Validium
+            && self.config.wait_for_da_inclusion
+        {
+            Self::retain_l1_batches_with_da_inclusion(storage, &mut ready_for_execute_batches)
+                .await;
+        }
         let l1_batches = extract_ready_subrange(
             storage,
             &mut self.execute_criteria,
@@ -160,6 +231,47 @@ impl Aggregator {
         l1_batches.map(|l1_batches| L1BatchExecuteOperation { l1_batches })
     }
 
+    /// Truncates `l1_batches` (which must be ordered by number) to the first L1 batch without
+    /// confirmed pubdata inclusion on the external DA layer. Executing an L1 batch before its inclusion
+    /// is confirmed would risk finalizing state that cannot be reconstructed from the DA layer.
+    async fn retain_l1_batches_with_da_inclusion(
+        storage: &mut StorageProcessor<'_>,
+        l1_batches: &mut Vec<L1BatchWithMetadata>,
+    ) {
+        let (Some(first), Some(last)) = (l1_batches.first(), l1_batches.last()) else {
+            return;
+        };
+        let included_l1_batches = storage
+            .data_availability_dal()
+            .get_l1_batches_with_confirmed_inclusion(first.header.number..=last.header.number)
+            .await
+            .unwrap();
+        let included_count = l1_batches
+            .iter()
+            .take_while(|l1_batch| included_l1_batches.contains(&l1_batch.header.number))
+            .count();
+        if included_count < l1_batches.len() {
+            let pending_l1_batch = l1_batches[included_count].header.number;
+            let da_details = storage
+                .data_availability_dal()
+                .get_l1_batch_da(pending_l1_batch)
+                .await
+                .unwrap();
+            if da_details.is_none() {
+                // Unlike waiting for the inclusion proof, this won't resolve by itself unless pubdata is dispatched.
+                tracing::warn!(
+                    "L1 batch #{pending_l1_batch} is not dispatched to the DA layer; its execution is blocked \
+                     until the DA dispatcher records a blob ID and inclusion data for it"
+                );
+            } else {
+                tracing::debug!(
+                    "Waiting for DA inclusion of L1 batch #{pending_l1_batch} before executing it"
+                );
+            }
+            l1_batches.truncate(included_count);
+        }
+    }
+
     async fn get_commit_operation(
         &mut self,
         storage: &mut StorageProcessor<'_>,
@@ -202,9 +314,17 @@ impl Aggregator {
         )
         .await;
 
-        batches.map(|batches| L1BatchCommitOperation {
-            last_committed_l1_batch,
-            l1_batches: batches,
+        batches.map(|batches| {
+            let pubdata_cost_estimate = self
+                .pubdata_cost_estimator
+                .as_ref()
+                .and_then(|estimator| estimator.estimate(&batches, self.commitment_mode));
+            L1BatchCommitOperation {
+                last_committed_l1_batch,
+                l1_batches: batches,
+                commitment_mode: self.commitment_mode,
+                pubdata_cost_estimate,
+            }
         })
     }
 
@@ -0,0 +1,196 @@
+//! Adaptation of L1 batch aggregation to L1 congestion.
+
+use std::{fmt, sync::Arc};
+
+use zksync_config::configs::eth_sender::SenderConfig;
+use zksync_dal::StorageProcessor;
+
+use crate::l1_gas_price::L1GasPriceProvider;
+
+/// Limits on commit and execute operations that are scaled depending on L1 congestion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct AggregationLimits {
+    pub max_l1_batches_to_commit: u32,
+    pub max_l1_batches_to_execute: u32,
+    pub commit_deadline_seconds: u64,
+    pub execute_deadline_seconds: u64,
+}
+
+impl AggregationLimits {
+    pub fn new(config: &SenderConfig) -> Self {
+        Self {
+            max_l1_batches_to_commit: config.max_aggregated_blocks_to_commit,
+            max_l1_batches_to_execute: config.max_aggregated_blocks_to_execute,
+            commit_deadline_seconds: config.aggregated_block_commit_deadline,
+            execute_deadline_seconds: config.aggregated_block_execute_deadline,
+        }
+    }
+
+    fn scale(&self, factor: f64) -> Self {
+        let scale_count = |count: u32| (f64::from(count) * factor).round() as u32;
+        let scale_deadline = |seconds: u64| (seconds as f64 * factor).round() as u64;
+        Self {
+            max_l1_batches_to_commit: scale_count(self.max_l1_batches_to_commit),
+            max_l1_batches_to_execute: scale_count(self.max_l1_batches_to_execute),
+            commit_deadline_seconds: scale_deadline(self.commit_deadline_seconds),
+            execute_deadline_seconds: scale_deadline(self.execute_deadline_seconds),
+        }
+    }
+}
+
+/// Tracks L1 congestion based on the number of pending operator transactions and the L1 base fee trend.
+/// When L1 is congested, more L1 batches are packed into a single commit / execute operation, and operations
+/// are published less frequently. Gas and data size limits on operations still apply.
+pub struct L1CongestionTracker {
+    max_txs_in_flight: u64,
+    base_fee_ratio: f64,
+    max_aggregation_factor: f64,
+    price_provider: Arc<dyn L1GasPriceProvider + Send + Sync>,
+}
+
+impl fmt::Debug for L1CongestionTracker {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("L1CongestionTracker")
+            .field("max_txs_in_flight", &self.max_txs_in_flight)
+            .field("base_fee_ratio", &self.base_fee_ratio)
+            .field("max_aggregation_factor", &self.max_aggregation_factor)
+            .finish()
+    }
+}
+
+impl L1CongestionTracker {
+    pub fn new(
+        config: &SenderConfig,
+        price_provider: Arc<dyn L1GasPriceProvider + Send + Sync>,
+    ) -> anyhow::Result<Self> {
+        let base_fee_ratio = config.congestion_base_fee_ratio();
+        // Otherwise, any rise of the base fee would be considered full congestion.
+        anyhow::ensure!(
+            base_fee_ratio > 1.0,
+            "congestion base fee ratio must be greater than 1, got {base_fee_ratio}"
+        );
+        Ok(Self {
+            max_txs_in_flight: config.max_txs_in_flight,
+            base_fee_ratio,
+            max_aggregation_factor: config.congestion_max_aggregation_factor().max(1.0),
+            price_provider,
+        })
+    }
+
+    /// Returns the congestion level in the `0.0..=1.0` range. The level is the maximum of the fraction
+    /// of occupied in-flight transaction slots and the relative rise of the L1 base fee.
+    fn congestion_level(&self, inflight_txs: usize, base_fee_trend: Option<f64>) -> f64 {
+        let inflight_level = if self.max_txs_in_flight == 0 {
+            0.0
+        } else {
+            inflight_txs as f64 / self.max_txs_in_flight as f64
+        };
+        let base_fee_level =
+            base_fee_trend.map_or(0.0, |trend| (trend - 1.0) / (self.base_fee_ratio - 1.0));
+        inflight_level.max(base_fee_level).clamp(0.0, 1.0)
+    }
+
+    fn aggregation_factor(&self, congestion_level: f64) -> f64 {
+        1.0 + (self.max_aggregation_factor - 1.0) * congestion_level
+    }
+
+    /// Scales `base_limits` according to the current L1 congestion.
+    pub(super) async fn adapt_limits(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        base_limits: &AggregationLimits,
+    ) -> AggregationLimits {
+        let inflight_txs = storage.eth_sender_dal().get_inflight_txs().await.len();
+        let base_fee_trend = self.price_provider.estimate_base_fee_trend();
+        let congestion_level = self.congestion_level(inflight_txs, base_fee_trend);
+        let factor = self.aggregation_factor(congestion_level);
+        tracing::debug!(
+            "L1 congestion level is {congestion_level:.2} (in-flight txs: {inflight_txs}, \
+             base fee trend: {base_fee_trend:?}); scaling aggregation limits by {factor:.2}"
+        );
+        metrics::gauge!("server.eth_sender.l1_congestion_level", congestion_level);
+        metrics::gauge!("server.eth_sender.aggregation_factor", factor);
+        base_limits.scale(factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::ETHSenderConfig;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockPriceProvider;
+
+    impl L1GasPriceProvider for MockPriceProvider {
+        fn estimate_effective_gas_price(&self) -> u64 {
+            1
+        }
+    }
+
+    fn tracker() -> L1CongestionTracker {
+        L1CongestionTracker {
+            max_txs_in_flight: 10,
+            base_fee_ratio: 2.0,
+            max_aggregation_factor: 4.0,
+            price_provider: Arc::new(MockPriceProvider),
+        }
+    }
+
+    #[test]
+    fn base_fee_ratio_is_validated() {
+        let config = SenderConfig {
+            congestion_base_fee_ratio: Some(1.0),
+            ..ETHSenderConfig::from_env().unwrap().sender
+        };
+        let err = L1CongestionTracker::new(&config, Arc::new(MockPriceProvider)).unwrap_err();
+        assert!(err.to_string().contains("base fee ratio"), "{err}");
+
+        let config = SenderConfig {
+            congestion_base_fee_ratio: Some(1.5),
+            ..config
+        };
+        L1CongestionTracker::new(&config, Arc::new(MockPriceProvider)).unwrap();
+    }
+
+    #[test]
+    fn computing_congestion_level() {
+        let tracker = tracker();
+        assert_eq!(tracker.congestion_level(0, None), 0.0);
+        assert_eq!(tracker.congestion_level(0, Some(0.8)), 0.0);
+        assert_eq!(tracker.congestion_level(5, Some(1.0)), 0.5);
+        assert_eq!(tracker.congestion_level(2, Some(1.75)), 0.75);
+        assert_eq!(tracker.congestion_level(20, None), 1.0);
+        assert_eq!(tracker.congestion_level(0, Some(5.0)), 1.0);
+    }
+
+    #[test]
+    fn scaling_aggregation_limits() {
+        let tracker = tracker();
+        let limits = AggregationLimits {
+            max_l1_batches_to_commit: 10,
+            max_l1_batches_to_execute: 5,
+            commit_deadline_seconds: 30,
+            execute_deadline_seconds: 60,
+        };
+
+        let factor = tracker.aggregation_factor(0.0);
+        assert_eq!(limits.scale(factor), limits);
+
+        let factor = tracker.aggregation_factor(0.5);
+        assert_eq!(
+            limits.scale(factor),
+            AggregationLimits {
+                max_l1_batches_to_commit: 25,
+                max_l1_batches_to_execute: 13,
+                commit_deadline_seconds: 75,
+                execute_deadline_seconds: 150,
+            }
+        );
+
+        let factor = tracker.aggregation_factor(1.0);
+        assert_eq!(limits.scale(factor).max_l1_batches_to_commit, 40);
+    }
+}
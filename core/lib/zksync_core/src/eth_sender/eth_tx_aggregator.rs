@@ -7,7 +7,7 @@ use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_eth_client::BoundEthInterface;
 use zksync_types::{
-    aggregated_operations::AggregatedOperation,
+    aggregated_operations::{AggregatedOperation, L1BatchCommitOperation, PubdataCostEstimate},
     contracts::{Multicall3Call, Multicall3Result},
     eth_sender::EthTx,
     ethabi::Token,
@@ -370,6 +370,15 @@ impl EthTxAggregator {
         );
 
         if let AggregatedOperation::Commit(commit_op) = &aggregated_op {
+            let cheaper_mode = commit_op
+                .pubdata_cost_estimate
+                .and_then(|estimate| estimate.cheaper_mode());
+            if let Some(mode) = cheaper_mode {
+                metrics::increment_counter!(
+                    "server.eth_sender.cheaper_pubdata_sending_mode",
+                    "mode" => mode.to_string()
+                );
+            }
             for batch in &commit_op.l1_batches {
                 metrics::histogram!(
                     "server.eth_sender.pubdata_size",
@@ -450,10 +459,50 @@ impl EthTxAggregator {
             .set_eth_tx_id(l1_batch_number_range, eth_tx.id, op_type)
             .await
             .unwrap();
+        if let AggregatedOperation::Commit(commit_op) = aggregated_op {
+            if let Some(estimate) = &commit_op.pubdata_cost_estimate {
+                Self::save_pubdata_cost_estimates(&mut transaction, commit_op, estimate).await;
+            }
+        }
         transaction.commit().await.unwrap();
         Ok(eth_tx)
     }
 
+    /// Records estimated costs of publishing pubdata for each L1 batch in the commit operation. Costs
+    /// are distributed among L1 batches proportionally to their pubdata size; the cheaper mode is determined
+    /// for the operation as a whole.
+    async fn save_pubdata_cost_estimates(
+        storage: &mut StorageProcessor<'_>,
+        commit_op: &L1BatchCommitOperation,
+        estimate: &PubdataCostEstimate,
+    ) {
+        let cheaper_mode = estimate.cheaper_mode();
+        let cost_share = |cost: U256, pubdata_size: usize| {
+            if estimate.pubdata_size == 0 {
+                U256::zero()
+            } else {
+                cost * pubdata_size / estimate.pubdata_size
+            }
+        };
+
+        for l1_batch in &commit_op.l1_batches {
+            let pubdata_size = l1_batch.pubdata_size(commit_op.commitment_mode);
+            storage
+                .blocks_dal()
+                .save_pubdata_cost_estimate(
+                    l1_batch.header.number,
+                    cheaper_mode,
+                    pubdata_size,
+                    cost_share(estimate.calldata_cost, pubdata_size),
+                    estimate
+                        .blobs_cost
+                        .map(|cost| cost_share(cost, pubdata_size)),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
     async fn get_next_nonce(
         &self,
         storage: &mut StorageProcessor<'_>,
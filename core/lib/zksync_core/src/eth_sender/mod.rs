@@ -1,4 +1,6 @@
 mod aggregator;
+mod congestion;
+mod pubdata_costs;
 mod publish_criterion;
 
 mod error;
@@ -11,6 +13,8 @@ mod zksync_functions;
 mod tests;
 
 pub use aggregator::Aggregator;
+pub use congestion::L1CongestionTracker;
 pub use error::ETHSenderError;
 pub use eth_tx_aggregator::EthTxAggregator;
 pub use eth_tx_manager::EthTxManager;
+pub use pubdata_costs::PubdataCostEstimator;
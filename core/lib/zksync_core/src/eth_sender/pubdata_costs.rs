@@ -0,0 +1,186 @@
+//! Estimation of costs of publishing pubdata of commit operations on L1.
+//!
+//! Pubdata is always sent as a part of the commit calldata; EIP-4844 blob transactions are not supported
+//! by the L1 sender. The estimates below allow to analyze whether sending pubdata in blobs would be cheaper.
+
+use std::{fmt, sync::Arc};
+
+use zksync_types::{
+    aggregated_operations::PubdataCostEstimate, commitment::L1BatchWithMetadata,
+    L1BatchCommitmentMode, U256,
+};
+
+use crate::l1_gas_price::L1GasPriceProvider;
+
+/// L1 gas charged for a non-zero calldata byte.
+const CALLDATA_GAS_PER_BYTE: u64 = 16;
+/// Number of bytes that can be stored in a single blob (4,096 field elements with 31 usable bytes each).
+const BLOB_CAPACITY: usize = 4_096 * 31;
+/// Blob gas consumed by a single blob.
+const BLOB_GAS_PER_BLOB: u64 = 1 << 17;
+/// L1 gas spent on verifying a single blob in the commit transaction (mostly the point evaluation precompile).
+const L1_GAS_PER_BLOB_VERIFICATION: u64 = 60_000;
+
+/// Estimates costs of publishing pubdata of commit operations as calldata and in blobs.
+pub struct PubdataCostEstimator {
+    price_provider: Arc<dyn L1GasPriceProvider + Send + Sync>,
+}
+
+impl fmt::Debug for PubdataCostEstimator {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PubdataCostEstimator")
+            .finish_non_exhaustive()
+    }
+}
+
+impl PubdataCostEstimator {
+    /// Creates an estimator based on L1 prices from `price_provider`. Both prices are taken with
+    /// the internal L1 pricing multiplier applied, so that the estimates are consistent with pubdata pricing in the VM.
+    pub fn new(price_provider: Arc<dyn L1GasPriceProvider + Send + Sync>) -> Self {
+        Self { price_provider }
+    }
+
+    /// Returns the estimated costs for the specified L1 batches, or `None` if storage writes are not published
+    /// in the specified mode (in which case, there's nothing to estimate).
+    pub(super) fn estimate(
+        &self,
+        l1_batches: &[L1BatchWithMetadata],
+        commitment_mode: L1BatchCommitmentMode,
+    ) -> Option<PubdataCostEstimate> {
+        if !commitment_mode.publishes_storage_writes() {
+            return None;
+        }
+
+        let pubdata_size: usize = l1_batches
+            .iter()
+            .map(|l1_batch| l1_batch.pubdata_size(commitment_mode))
+            .sum();
+        let gas_price = self.price_provider.estimate_effective_gas_price();
+        let blobs_cost = self
+            .price_provider
+            .estimate_effective_blob_base_fee()
+            .map(|blob_base_fee| blobs_cost(pubdata_size, gas_price, blob_base_fee));
+        Some(PubdataCostEstimate {
+            pubdata_size,
+            calldata_cost: calldata_cost(pubdata_size, gas_price),
+            blobs_cost,
+        })
+    }
+}
+
+fn calldata_cost(pubdata_size: usize, gas_price: u64) -> U256 {
+    U256::from(pubdata_size) * U256::from(CALLDATA_GAS_PER_BYTE) * U256::from(gas_price)
+}
+
+fn blobs_cost(pubdata_size: usize, gas_price: u64, blob_base_fee: u64) -> U256 {
+    // Blobs are paid for in full regardless of how much data they actually contain.
+    let blob_count = (pubdata_size + BLOB_CAPACITY - 1) / BLOB_CAPACITY;
+    let blob_count = U256::from(blob_count);
+    blob_count * U256::from(BLOB_GAS_PER_BLOB) * U256::from(blob_base_fee)
+        + blob_count * U256::from(L1_GAS_PER_BLOB_VERIFICATION) * U256::from(gas_price)
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_contracts::BaseSystemContractsHashes;
+    use zksync_types::{
+        block::L1BatchHeader, commitment::L1BatchMetadata, Address, L1BatchNumber,
+        ProtocolVersionId, PubdataSendingMode,
+    };
+
+    use super::*;
+    use crate::eth_sender::tests::default_l1_batch_metadata;
+
+    #[derive(Debug)]
+    struct MockPriceProvider {
+        gas_price: u64,
+        blob_base_fee: Option<u64>,
+    }
+
+    impl L1GasPriceProvider for MockPriceProvider {
+        fn estimate_effective_gas_price(&self) -> u64 {
+            self.gas_price
+        }
+
+        fn estimate_effective_blob_base_fee(&self) -> Option<u64> {
+            self.blob_base_fee
+        }
+    }
+
+    fn estimate_for_size(
+        pubdata_size: usize,
+        provider: MockPriceProvider,
+    ) -> Option<PubdataCostEstimate> {
+        let l1_batch = l1_batch_with_pubdata(pubdata_size);
+        PubdataCostEstimator::new(Arc::new(provider))
+            .estimate(&[l1_batch], L1BatchCommitmentMode::Rollup)
+    }
+
+    fn l1_batch_with_pubdata(pubdata_size: usize) -> L1BatchWithMetadata {
+        let header = L1BatchHeader::new(
+            L1BatchNumber(1),
+            1,
+            Address::default(),
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+        );
+        L1BatchWithMetadata {
+            header,
+            metadata: L1BatchMetadata {
+                initial_writes_compressed: vec![1; pubdata_size],
+                ..default_l1_batch_metadata()
+            },
+            factory_deps: vec![],
+        }
+    }
+
+    #[test]
+    fn blobs_are_paid_for_in_full() {
+        let single_blob_cost = blobs_cost(1, 10, 1);
+        assert_eq!(blobs_cost(BLOB_CAPACITY, 10, 1), single_blob_cost);
+        assert_eq!(blobs_cost(BLOB_CAPACITY + 1, 10, 1), single_blob_cost * 2);
+        assert_eq!(blobs_cost(0, 10, 1), U256::zero());
+    }
+
+    #[test]
+    fn cheaper_mode_depends_on_pubdata_size() {
+        let gas_price = 10_000_000_000; // 10 gwei
+        let provider = || MockPriceProvider {
+            gas_price,
+            blob_base_fee: Some(1),
+        };
+
+        // A small amount of pubdata doesn't justify paying for a whole blob.
+        let estimate = estimate_for_size(1_000, provider()).unwrap();
+        assert_eq!(estimate.pubdata_size, 1_000);
+        assert_eq!(estimate.calldata_cost, calldata_cost(1_000, gas_price));
+        assert_eq!(estimate.cheaper_mode(), Some(PubdataSendingMode::Calldata));
+        // ...while a fully loaded blob is much cheaper than calldata.
+        let estimate = estimate_for_size(BLOB_CAPACITY, provider()).unwrap();
+        assert_eq!(estimate.cheaper_mode(), Some(PubdataSendingMode::Blobs));
+    }
+
+    #[test]
+    fn cheaper_mode_is_unknown_without_blob_base_fee() {
+        let provider = MockPriceProvider {
+            gas_price: 10,
+            blob_base_fee: None,
+        };
+        let estimate = estimate_for_size(BLOB_CAPACITY, provider).unwrap();
+        assert_eq!(estimate.blobs_cost, None);
+        assert_eq!(estimate.cheaper_mode(), None);
+    }
+
+    #[test]
+    fn nothing_is_estimated_in_validium_mode() {
+        let provider = MockPriceProvider {
+            gas_price: 10,
+            blob_base_fee: Some(1),
+        };
+        let l1_batch = l1_batch_with_pubdata(100);
+        let estimate = PubdataCostEstimator::new(Arc::new(provider))
+            .estimate(&[l1_batch], L1BatchCommitmentMode::Validium);
+        assert_eq!(estimate, None);
+    }
+}
@@ -5,7 +5,9 @@ use std::fmt;
 
 use zksync_dal::StorageProcessor;
 use zksync_types::commitment::L1BatchWithMetadata;
-use zksync_types::{aggregated_operations::AggregatedActionType, L1BatchNumber};
+use zksync_types::{
+    aggregated_operations::AggregatedActionType, L1BatchCommitmentMode, L1BatchNumber,
+};
 
 use crate::gas_tracker::agg_l1_batch_base_cost;
 
@@ -211,6 +213,7 @@ impl L1BatchPublishCriterion for GasCriterion {
 pub struct DataSizeCriterion {
     pub op: AggregatedActionType,
     pub data_limit: usize,
+    pub commitment_mode: L1BatchCommitmentMode,
 }
 
 #[async_trait]
@@ -229,13 +232,12 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
         let mut data_size_left = self.data_limit - STORED_BLOCK_INFO_SIZE;
 
         for (index, l1_batch) in consecutive_l1_batches.iter().enumerate() {
-            if data_size_left < l1_batch.l1_commit_data_size() {
+            let commit_data_size = l1_batch.l1_commit_data_size(self.commitment_mode);
+            if data_size_left < commit_data_size {
                 if index == 0 {
                     panic!(
                         "L1 batch #{} requires {} data, which is more than the range limit of {}",
-                        l1_batch.header.number,
-                        l1_batch.l1_commit_data_size(),
-                        self.data_limit
+                        l1_batch.header.number, commit_data_size, self.data_limit
                     );
                 }
 
@@ -255,7 +257,7 @@ impl L1BatchPublishCriterion for DataSizeCriterion {
                 );
                 return Some(output);
             }
-            data_size_left -= l1_batch.l1_commit_data_size();
+            data_size_left -= commit_data_size;
         }
 
         None
@@ -19,13 +19,15 @@ use zksync_types::{
     ethabi::Token,
     helpers::unix_timestamp_ms,
     web3::contract::Error,
-    Address, L1BatchNumber, L1BlockNumber, ProtocolVersionId, H256,
+    Address, L1BatchCommitmentMode, L1BatchNumber, L1BlockNumber, ProtocolVersionId, H256,
 };
 
 use crate::eth_sender::{
     eth_tx_manager::L1BlockNumbers, Aggregator, ETHSenderError, EthTxAggregator, EthTxManager,
+    L1CongestionTracker,
 };
-use crate::l1_gas_price::GasAdjuster;
+use crate::gas_tracker::PubdataDaMode;
+use crate::l1_gas_price::{GasAdjuster, L1GasPriceProvider};
 
 // Alias to conveniently call static methods of ETHSender.
 type MockEthTxManager = EthTxManager<Arc<MockEthereum>, GasAdjuster<Arc<MockEthereum>>>;
@@ -82,6 +84,7 @@ impl EthSenderTester {
                     pricing_formula_parameter_b: 2.0,
                     ..eth_sender_config.gas_adjuster
                 },
+                PubdataDaMode::Calldata,
             )
             .await
             .unwrap(),
@@ -97,6 +100,7 @@ impl EthSenderTester {
             Aggregator::new(
                 aggregator_config.clone(),
                 store_factory.create_store().await,
+                L1BatchCommitmentMode::Rollup,
             ),
             // zkSync contract address
             Address::random(),
@@ -447,7 +451,7 @@ async fn failed_eth_tx(connection_pool: ConnectionPool) {
         .unwrap();
 }
 
-fn default_l1_batch_metadata() -> L1BatchMetadata {
+pub(super) fn default_l1_batch_metadata() -> L1BatchMetadata {
     L1BatchMetadata {
         root_hash: Default::default(),
         rollup_last_leaf_index: 0,
@@ -800,6 +804,76 @@ async fn get_multicall_data(connection_pool: ConnectionPool) {
     assert!(multicall_data.is_ok());
 }
 
+#[derive(Debug)]
+struct MockCongestionProvider {
+    base_fee_trend: Option<f64>,
+}
+
+impl L1GasPriceProvider for MockCongestionProvider {
+    fn estimate_effective_gas_price(&self) -> u64 {
+        1
+    }
+
+    fn estimate_base_fee_trend(&self) -> Option<f64> {
+        self.base_fee_trend
+    }
+}
+
+async fn get_committed_l1_batch_count(
+    aggregator: &mut Aggregator,
+    storage: &mut StorageProcessor<'_>,
+    prover_storage: &mut StorageProcessor<'_>,
+) -> usize {
+    let operation = aggregator
+        .get_next_ready_operation(
+            storage,
+            prover_storage,
+            BaseSystemContractsHashes::default(),
+            ProtocolVersionId::latest(),
+            Default::default(),
+        )
+        .await;
+    match operation {
+        Some(AggregatedOperation::Commit(op)) => op.l1_batches.len(),
+        _ => panic!("unexpected operation: {operation:?}"),
+    }
+}
+
+#[db_test]
+async fn aggregation_adapts_to_l1_congestion(pool: ConnectionPool, prover_pool: ConnectionPool) {
+    let mut tester = EthSenderTester::new(pool, vec![100; 100], false).await;
+    insert_genesis_protocol_version(&tester).await;
+    for number in 0..20 {
+        insert_l1_batch(&mut tester, L1BatchNumber(number)).await;
+    }
+
+    let eth_sender_config = ETHSenderConfig::from_env().unwrap();
+    let config = SenderConfig {
+        max_aggregated_blocks_to_commit: 2,
+        congestion_base_fee_ratio: Some(2.0),
+        congestion_max_aggregation_factor: Some(4.0),
+        ..eth_sender_config.sender
+    };
+    let store_factory = ObjectStoreFactory::from_env().unwrap();
+    let mut storage = tester.storage().await;
+    let mut prover_storage = prover_pool.access_test_storage().await;
+
+    for (base_fee_trend, expected_count) in [(None, 2), (Some(1.5), 5), (Some(3.0), 8)] {
+        let provider = MockCongestionProvider { base_fee_trend };
+        let tracker = L1CongestionTracker::new(&config, Arc::new(provider)).unwrap();
+        let mut aggregator = Aggregator::new(
+            config.clone(),
+            store_factory.create_store().await,
+            L1BatchCommitmentMode::Rollup,
+        )
+        .with_congestion_tracker(tracker);
+
+        let count =
+            get_committed_l1_batch_count(&mut aggregator, &mut storage, &mut prover_storage).await;
+        assert_eq!(count, expected_count, "base fee trend: {base_fee_trend:?}");
+    }
+}
+
 async fn insert_genesis_protocol_version(tester: &EthSenderTester) {
     tester
         .storage()
@@ -876,6 +950,8 @@ async fn commit_l1_batch(
     let operation = AggregatedOperation::Commit(L1BatchCommitOperation {
         last_committed_l1_batch: l1_batch_with_metadata(last_committed_l1_batch),
         l1_batches: vec![l1_batch_with_metadata(l1_batch)],
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+        pubdata_cost_estimate: None,
     });
     send_operation(tester, operation, confirm).await
 }
@@ -8,7 +8,6 @@
 use std::time::{Duration, Instant};
 
 // External uses
-use anyhow::Context as _;
 use tokio::{sync::watch, task::JoinHandle};
 
 // Workspace deps
@@ -179,9 +178,9 @@ pub async fn start_eth_watch<E: EthInterface + Send + Sync + 'static>(
     pool: ConnectionPool,
     eth_gateway: E,
     diamond_proxy_addr: Address,
+    eth_watch: &ETHWatchConfig,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<JoinHandle<anyhow::Result<()>>> {
-    let eth_watch = ETHWatchConfig::from_env().context("ETHWatchConfig::from_env()")?;
     let eth_client = EthHttpQueryClient::new(
         eth_gateway,
         diamond_proxy_addr,
@@ -0,0 +1,212 @@
+//! Comparison of two executions of the same transaction, e.g. with and without bytecode compression.
+//! Used to debug divergences between executions that are expected to be equivalent.
+
+use std::collections::{BTreeMap, HashMap};
+
+use vm::{ExecutionResult, VmExecutionResultAndLogs};
+use zksync_types::{
+    api::{EmittedEvent, EventDiff, ExecutionDiff, StorageWriteDiff, ValueDiff},
+    storage_writes_deduplicator::StorageWritesDeduplicator,
+    Address, StorageKey, VmEvent, H256,
+};
+use zksync_utils::u256_to_h256;
+
+fn outcome(result: &ExecutionResult) -> String {
+    match result {
+        ExecutionResult::Success { output } => format!("success: 0x{}", hex::encode(output)),
+        ExecutionResult::Revert { output } => format!("revert: {output}"),
+        ExecutionResult::Halt { reason } => format!("halt: {reason}"),
+    }
+}
+
+/// Returns final values of storage slots written during the execution.
+fn final_writes(result: &VmExecutionResultAndLogs) -> HashMap<StorageKey, H256> {
+    let mut deduplicator = StorageWritesDeduplicator::new();
+    deduplicator.apply(
+        result
+            .logs
+            .storage_logs
+            .iter()
+            .filter(|log| log.log_query.rw_flag),
+    );
+    deduplicator
+        .into_modified_key_values()
+        .into_iter()
+        .map(|(key, slot)| (key, u256_to_h256(slot.value)))
+        .collect()
+}
+
+fn to_emitted_event(event: &VmEvent) -> EmittedEvent {
+    EmittedEvent {
+        address: event.address,
+        topics: event.indexed_topics.clone(),
+        data: event.value.clone().into(),
+    }
+}
+
+/// Computes the difference between two executions of the same transaction. Gas is compared as reported
+/// by the VM; storage writes are compared by their final values.
+pub fn diff_executions(
+    left: &VmExecutionResultAndLogs,
+    right: &VmExecutionResultAndLogs,
+) -> ExecutionDiff {
+    let (left_outcome, right_outcome) = (outcome(&left.result), outcome(&right.result));
+    let outcome = (left_outcome != right_outcome).then_some(ValueDiff {
+        left: left_outcome,
+        right: right_outcome,
+    });
+    let (left_gas, right_gas) = (left.statistics.gas_used, right.statistics.gas_used);
+    let gas_used = (left_gas != right_gas).then_some(ValueDiff {
+        left: left_gas,
+        right: right_gas,
+    });
+
+    let mut slots = BTreeMap::<(Address, H256), (Option<H256>, Option<H256>)>::new();
+    for (key, value) in final_writes(left) {
+        slots.entry((*key.address(), *key.key())).or_default().0 = Some(value);
+    }
+    for (key, value) in final_writes(right) {
+        slots.entry((*key.address(), *key.key())).or_default().1 = Some(value);
+    }
+    let storage_writes = slots
+        .into_iter()
+        .filter(|(_, (left, right))| left != right)
+        .map(|((address, key), (left, right))| StorageWriteDiff {
+            address,
+            key,
+            left,
+            right,
+        })
+        .collect();
+
+    let (left_events, right_events) = (&left.logs.events, &right.logs.events);
+    let event_count = left_events.len().max(right_events.len());
+    let events = (0..event_count)
+        .filter_map(|index| {
+            let left = left_events.get(index).map(to_emitted_event);
+            let right = right_events.get(index).map(to_emitted_event);
+            (left != right).then_some(EventDiff { index, left, right })
+        })
+        .collect();
+
+    ExecutionDiff {
+        outcome,
+        gas_used,
+        storage_writes,
+        events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vm::{Refunds, VmExecutionStatistics, VmRevertReason};
+    use zksync_types::{
+        tx::tx_execution_info::VmExecutionLogs, AccountTreeId, L1BatchNumber, StorageLog,
+        StorageLogQuery, StorageLogQueryType,
+    };
+
+    use super::*;
+
+    fn write_log(address: Address, key: H256, value: H256) -> StorageLogQuery {
+        let key = StorageKey::new(AccountTreeId::new(address), key);
+        StorageLogQuery {
+            log_query: StorageLog::new_write_log(key, value).to_test_log_query(),
+            log_type: StorageLogQueryType::RepeatedWrite,
+        }
+    }
+
+    fn event(address: Address, topic: H256) -> VmEvent {
+        VmEvent {
+            location: (L1BatchNumber(1), 0),
+            address,
+            indexed_topics: vec![topic],
+            value: vec![1, 2, 3],
+        }
+    }
+
+    fn execution_result(
+        storage_logs: Vec<StorageLogQuery>,
+        events: Vec<VmEvent>,
+        gas_used: u32,
+    ) -> VmExecutionResultAndLogs {
+        VmExecutionResultAndLogs {
+            result: ExecutionResult::Success { output: vec![] },
+            logs: VmExecutionLogs {
+                storage_logs,
+                events,
+                ..VmExecutionLogs::default()
+            },
+            statistics: VmExecutionStatistics {
+                gas_used,
+                ..VmExecutionStatistics::default()
+            },
+            refunds: Refunds::default(),
+        }
+    }
+
+    #[test]
+    fn diffing_executions() {
+        let address = Address::repeat_byte(1);
+        let storage_logs = vec![
+            write_log(address, H256::zero(), H256::repeat_byte(1)),
+            write_log(address, H256::repeat_byte(1), H256::repeat_byte(2)),
+            // Overwrites the first write.
+            write_log(address, H256::zero(), H256::repeat_byte(3)),
+        ];
+        let events = vec![event(address, H256::zero())];
+        let left = execution_result(storage_logs.clone(), events.clone(), 100);
+        assert!(diff_executions(&left, &left.clone()).is_empty());
+
+        let right_storage_logs = vec![
+            write_log(address, H256::zero(), H256::repeat_byte(3)),
+            write_log(address, H256::repeat_byte(2), H256::repeat_byte(2)),
+        ];
+        let right_events = vec![
+            event(address, H256::repeat_byte(1)),
+            event(address, H256::zero()),
+        ];
+        let mut right = execution_result(right_storage_logs, right_events, 120);
+        right.result = ExecutionResult::Revert {
+            output: VmRevertReason::General {
+                msg: "oops".to_owned(),
+                data: vec![],
+            },
+        };
+
+        let diff = diff_executions(&left, &right);
+        assert_eq!(
+            diff.outcome.unwrap(),
+            ValueDiff {
+                left: "success: 0x".to_owned(),
+                right: "revert: oops".to_owned(),
+            }
+        );
+        assert_eq!(
+            diff.gas_used,
+            Some(ValueDiff {
+                left: 100,
+                right: 120,
+            })
+        );
+        assert_eq!(
+            diff.storage_writes,
+            [
+                StorageWriteDiff {
+                    address,
+                    key: H256::repeat_byte(1),
+                    left: Some(H256::repeat_byte(2)),
+                    right: None,
+                },
+                StorageWriteDiff {
+                    address,
+                    key: H256::repeat_byte(2),
+                    left: None,
+                    right: Some(H256::repeat_byte(2)),
+                },
+            ]
+        );
+        let event_indices: Vec<_> = diff.events.iter().map(|diff| diff.index).collect();
+        assert_eq!(event_indices, [0, 1]);
+        assert_eq!(diff.events[1].left, None);
+    }
+}
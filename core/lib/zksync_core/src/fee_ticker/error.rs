@@ -5,6 +5,8 @@ use zksync_types::Address;
 pub enum TickerError {
     #[error("Token {0:x} is not being tracked for its price")]
     PriceNotTracked(Address),
+    #[error("Token {0:x} is not supported")]
+    TokenNotAllowed(Address),
     #[error("Third-party API data is temporarily unavailable")]
     ApiDataUnavailable,
     #[error("Fee ticker internal error")]
@@ -3,12 +3,13 @@
 use core::fmt::Debug;
 
 use bigdecimal::BigDecimal;
-use num::{rational::Ratio, BigUint};
+use num::{rational::Ratio, BigUint, Zero};
 use vm::utils::fee::base_fee_to_gas_per_pubdata;
-use zksync_types::Address;
+use zksync_types::{tokens::ETHEREUM_ADDRESS, Address};
 use zksync_utils::ratio_to_big_decimal_normalized;
 
 use self::error::TickerError;
+use crate::token_registry::TokenPolicy;
 use zksync_dal::tokens_web3_dal::TokensWeb3Dal;
 
 pub mod error;
@@ -33,12 +34,16 @@ pub enum TokenPriceRequestType {
 pub struct FeeTicker;
 
 impl FeeTicker {
-    /// Returns the token price in USD.
+    /// Returns the token price in USD. Tokens not supported by `token_policy` are rejected.
     pub async fn get_l2_token_price(
         tokens_web3_dal: &mut TokensWeb3Dal<'_, '_>,
+        token_policy: &TokenPolicy,
         request_type: TokenPriceRequestType,
         l2_token_addr: &Address,
     ) -> Result<BigDecimal, TickerError> {
+        if !token_policy.is_allowed(l2_token_addr) {
+            return Err(TickerError::TokenNotAllowed(*l2_token_addr));
+        }
         Self::get_l2_token_price_inner(tokens_web3_dal, request_type, l2_token_addr)
             .await
             .map(|final_price| {
@@ -46,6 +51,30 @@ impl FeeTicker {
             })
     }
 
+    /// Returns the number of the smallest `base_token` units having the same USD value as 1 wei.
+    /// Used by the fee model on chains with a custom base token.
+    pub async fn base_token_units_per_wei(
+        tokens_web3_dal: &mut TokensWeb3Dal<'_, '_>,
+        base_token: &Address,
+    ) -> Result<Ratio<BigUint>, TickerError> {
+        let wei_price = Self::get_l2_token_price_inner(
+            tokens_web3_dal,
+            TokenPriceRequestType::USDForOneWei,
+            &ETHEREUM_ADDRESS,
+        )
+        .await?;
+        let base_token_unit_price = Self::get_l2_token_price_inner(
+            tokens_web3_dal,
+            TokenPriceRequestType::USDForOneWei,
+            base_token,
+        )
+        .await?;
+        if base_token_unit_price.is_zero() {
+            return Err(TickerError::PriceNotTracked(*base_token));
+        }
+        Ok(wei_price / base_token_unit_price)
+    }
+
     /// Returns the acceptable `gas_per_pubdata_byte` based on the current gas price.
     pub fn gas_per_pubdata_byte(gas_price_wei: u64, base_fee: u64) -> u32 {
         base_fee_to_gas_per_pubdata(gas_price_wei, base_fee) as u32
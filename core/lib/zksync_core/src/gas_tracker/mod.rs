@@ -7,13 +7,62 @@ use zksync_types::{
     block::{BlockGasCount, L1BatchHeader},
     commitment::{L1BatchMetadata, L1BatchWithMetadata},
     tx::tx_execution_info::{DeduplicatedWritesMetrics, ExecutionMetrics},
-    ExecuteTransactionCommon, Transaction, H256,
+    ExecuteTransactionCommon, L1BatchCommitmentMode, PubdataSendingMode, Transaction, H256,
 };
 
 mod constants;
 
 use self::constants::*;
 
+/// Data availability mode of the chain, i.e. where pubdata of committed L1 batches ends up.
+/// Determines how much L1 gas publishing pubdata costs, and how pubdata is priced for the users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PubdataDaMode {
+    /// All pubdata is sent to L1 as calldata of the commit transaction.
+    #[default]
+    Calldata,
+    /// Pubdata is sent to L1 in blobs. Blob gas is paid separately from the execution gas,
+    /// so publishing pubdata doesn't consume L1 gas of the commit transaction.
+    Blobs,
+    /// Storage writes are sent to an external DA layer (validium); L2-to-L1 messages
+    /// and published bytecodes are still sent to L1 as calldata.
+    External,
+}
+
+impl PubdataDaMode {
+    pub fn new(commitment_mode: L1BatchCommitmentMode, sending_mode: PubdataSendingMode) -> Self {
+        match (commitment_mode, sending_mode) {
+            (L1BatchCommitmentMode::Validium, _) => Self::External,
+            (L1BatchCommitmentMode::Rollup, PubdataSendingMode::Calldata) => Self::Calldata,
+            (L1BatchCommitmentMode::Rollup, PubdataSendingMode::Blobs) => Self::Blobs,
+        }
+    }
+
+    /// Returns the mode in which L1 batches are committed.
+    pub fn commitment_mode(self) -> L1BatchCommitmentMode {
+        match self {
+            Self::Calldata | Self::Blobs => L1BatchCommitmentMode::Rollup,
+            Self::External => L1BatchCommitmentMode::Validium,
+        }
+    }
+
+    /// L1 gas spent on publishing a byte of L2-to-L1 logs / messages or published bytecodes.
+    fn l1_gas_per_pubdata_byte(self) -> u32 {
+        match self {
+            Self::Calldata | Self::External => GAS_PER_BYTE,
+            Self::Blobs => 0,
+        }
+    }
+
+    /// L1 gas spent on publishing a byte of storage writes.
+    fn l1_gas_per_storage_write_byte(self) -> u32 {
+        match self {
+            Self::Calldata => GAS_PER_BYTE,
+            Self::Blobs | Self::External => 0,
+        }
+    }
+}
+
 pub fn agg_l1_batch_base_cost(op: AggregatedActionType) -> u32 {
     match op {
         AggregatedActionType::Commit => AGGR_L1_BATCH_COMMIT_BASE_COST,
@@ -42,12 +91,18 @@ fn base_tx_cost(tx: &Transaction, op: AggregatedActionType) -> u32 {
     }
 }
 
-fn additional_pubdata_commit_cost(execution_metrics: &ExecutionMetrics) -> u32 {
-    (execution_metrics.size() as u32) * GAS_PER_BYTE
+fn additional_pubdata_commit_cost(
+    execution_metrics: &ExecutionMetrics,
+    da_mode: PubdataDaMode,
+) -> u32 {
+    (execution_metrics.size() as u32) * da_mode.l1_gas_per_pubdata_byte()
 }
 
-fn additional_writes_commit_cost(writes_metrics: &DeduplicatedWritesMetrics) -> u32 {
-    (writes_metrics.size() as u32) * GAS_PER_BYTE
+fn additional_writes_commit_cost(
+    writes_metrics: &DeduplicatedWritesMetrics,
+    da_mode: PubdataDaMode,
+) -> u32 {
+    (writes_metrics.size() as u32) * da_mode.l1_gas_per_storage_write_byte()
 }
 
 pub fn new_block_gas_count() -> BlockGasCount {
@@ -61,9 +116,10 @@ pub fn new_block_gas_count() -> BlockGasCount {
 pub fn gas_count_from_tx_and_metrics(
     tx: &Transaction,
     execution_metrics: &ExecutionMetrics,
+    da_mode: PubdataDaMode,
 ) -> BlockGasCount {
     let commit = base_tx_cost(tx, AggregatedActionType::Commit)
-        + additional_pubdata_commit_cost(execution_metrics);
+        + additional_pubdata_commit_cost(execution_metrics, da_mode);
     BlockGasCount {
         commit,
         prove: base_tx_cost(tx, AggregatedActionType::PublishProofOnchain),
@@ -71,17 +127,23 @@ pub fn gas_count_from_tx_and_metrics(
     }
 }
 
-pub fn gas_count_from_metrics(execution_metrics: &ExecutionMetrics) -> BlockGasCount {
+pub fn gas_count_from_metrics(
+    execution_metrics: &ExecutionMetrics,
+    da_mode: PubdataDaMode,
+) -> BlockGasCount {
     BlockGasCount {
-        commit: additional_pubdata_commit_cost(execution_metrics),
+        commit: additional_pubdata_commit_cost(execution_metrics, da_mode),
         prove: 0,
         execute: 0,
     }
 }
 
-pub fn gas_count_from_writes(writes_metrics: &DeduplicatedWritesMetrics) -> BlockGasCount {
+pub fn gas_count_from_writes(
+    writes_metrics: &DeduplicatedWritesMetrics,
+    da_mode: PubdataDaMode,
+) -> BlockGasCount {
     BlockGasCount {
-        commit: additional_writes_commit_cost(writes_metrics),
+        commit: additional_writes_commit_cost(writes_metrics, da_mode),
         prove: 0,
         execute: 0,
     }
@@ -91,6 +153,7 @@ pub(crate) fn commit_gas_count_for_l1_batch(
     header: &L1BatchHeader,
     unsorted_factory_deps: &HashMap<H256, Vec<u8>>,
     metadata: &L1BatchMetadata,
+    da_mode: PubdataDaMode,
 ) -> u32 {
     let base_cost = l1_batch_base_cost(AggregatedActionType::Commit);
     let total_messages_len: u32 = header
@@ -103,11 +166,69 @@ pub(crate) fn commit_gas_count_for_l1_batch(
     let total_factory_deps_len: u32 = sorted_factory_deps
         .map(|factory_dep| factory_dep.len() as u32)
         .sum();
-    let additional_calldata_bytes = metadata.initial_writes_compressed.len() as u32
-        + metadata.repeated_writes_compressed.len() as u32
-        + metadata.l2_l1_messages_compressed.len() as u32
+    let writes_bytes = metadata.initial_writes_compressed.len() as u32
+        + metadata.repeated_writes_compressed.len() as u32;
+    let other_pubdata_bytes = metadata.l2_l1_messages_compressed.len() as u32
         + total_messages_len
         + total_factory_deps_len;
-    let additional_cost = additional_calldata_bytes * GAS_PER_BYTE;
+    let additional_cost = writes_bytes * da_mode.l1_gas_per_storage_write_byte()
+        + other_pubdata_bytes * da_mode.l1_gas_per_pubdata_byte();
     base_cost + additional_cost
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pubdata_da_mode_from_config() {
+        use L1BatchCommitmentMode::*;
+
+        assert_eq!(
+            PubdataDaMode::new(Rollup, PubdataSendingMode::Calldata),
+            PubdataDaMode::Calldata
+        );
+        assert_eq!(
+            PubdataDaMode::new(Rollup, PubdataSendingMode::Blobs),
+            PubdataDaMode::Blobs
+        );
+        for sending_mode in [PubdataSendingMode::Calldata, PubdataSendingMode::Blobs] {
+            assert_eq!(
+                PubdataDaMode::new(Validium, sending_mode),
+                PubdataDaMode::External
+            );
+        }
+    }
+
+    #[test]
+    fn commit_gas_depends_on_da_mode() {
+        let writes_metrics = DeduplicatedWritesMetrics {
+            initial_storage_writes: 3,
+            repeated_storage_writes: 5,
+        };
+        let execution_metrics = ExecutionMetrics {
+            l2_l1_long_messages: 100,
+            published_bytecode_bytes: 1_000,
+            ..ExecutionMetrics::default()
+        };
+
+        let calldata_writes_gas = gas_count_from_writes(&writes_metrics, PubdataDaMode::Calldata);
+        assert_eq!(
+            calldata_writes_gas.commit,
+            writes_metrics.size() as u32 * GAS_PER_BYTE
+        );
+        let external_writes_gas = gas_count_from_writes(&writes_metrics, PubdataDaMode::External);
+        assert_eq!(external_writes_gas.commit, 0);
+        let blobs_writes_gas = gas_count_from_writes(&writes_metrics, PubdataDaMode::Blobs);
+        assert_eq!(blobs_writes_gas.commit, 0);
+
+        // Messages and bytecodes are posted as calldata in the validium mode.
+        let expected_gas = execution_metrics.size() as u32 * GAS_PER_BYTE;
+        for da_mode in [PubdataDaMode::Calldata, PubdataDaMode::External] {
+            let gas = gas_count_from_metrics(&execution_metrics, da_mode);
+            assert_eq!(gas.commit, expected_gas);
+        }
+        let blobs_gas = gas_count_from_metrics(&execution_metrics, PubdataDaMode::Blobs);
+        assert_eq!(blobs_gas.commit, 0);
+    }
+}
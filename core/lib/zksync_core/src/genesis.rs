@@ -11,13 +11,13 @@ use zksync_merkle_tree::domain::ZkSyncTree;
 use zksync_types::{
     block::DeployedContract,
     block::{legacy_miniblock_hash, BlockGasCount, L1BatchHeader, MiniblockHeader},
-    commitment::{L1BatchCommitment, L1BatchMetadata},
+    commitment::{CommitmentScheme, CommitmentSchemes, L1BatchCommitment, L1BatchMetadata},
     get_code_key, get_system_context_init_logs,
     protocol_version::{L1VerifierConfig, ProtocolVersion},
     tokens::{TokenInfo, TokenMetadata, ETHEREUM_ADDRESS},
     zkevm_test_harness::witness::sort_storage_access::sort_storage_access_queries,
-    AccountTreeId, Address, L1BatchNumber, L2ChainId, LogQuery, MiniblockNumber, ProtocolVersionId,
-    StorageKey, StorageLog, StorageLogKind, Timestamp, H256,
+    AccountTreeId, Address, L1BatchCommitmentMode, L1BatchNumber, L2ChainId, LogQuery,
+    MiniblockNumber, ProtocolVersionId, StorageKey, StorageLog, StorageLogKind, Timestamp, H256,
 };
 use zksync_utils::{be_words_to_bytes, h256_to_u256};
 use zksync_utils::{bytecode::hash_bytecode, u256_to_h256};
@@ -32,6 +32,8 @@ pub struct GenesisParams {
     pub system_contracts: Vec<DeployedContract>,
     pub first_verifier_address: Address,
     pub first_l1_verifier_config: L1VerifierConfig,
+    pub commitment_schemes: CommitmentSchemes,
+    pub commitment_mode: L1BatchCommitmentMode,
 }
 
 pub async fn ensure_genesis_state(
@@ -60,6 +62,8 @@ pub async fn ensure_genesis_state(
         system_contracts,
         first_verifier_address,
         first_l1_verifier_config,
+        commitment_schemes,
+        commitment_mode,
     } = genesis_params;
 
     let base_system_contracts_hashes = base_system_contracts.hashes();
@@ -91,11 +95,14 @@ pub async fn ensure_genesis_state(
         vec![],
         base_system_contracts_hashes.bootloader,
         base_system_contracts_hashes.default_aa,
+        *commitment_mode,
     );
 
+    let commitment_scheme = commitment_schemes.for_protocol_version(Some(*protocol_version));
     save_genesis_l1_batch_metadata(
         &mut transaction,
         &block_commitment,
+        commitment_scheme,
         genesis_root_hash,
         rollup_last_leaf_index,
     )
@@ -108,7 +115,9 @@ pub async fn ensure_genesis_state(
     println!("CONTRACTS_GENESIS_ROOT={:?}", genesis_root_hash);
     println!(
         "CONTRACTS_GENESIS_BLOCK_COMMITMENT={:?}",
-        block_commitment.hash().commitment
+        block_commitment
+            .hash_with_scheme(commitment_scheme)
+            .commitment
     );
     println!(
         "CONTRACTS_GENESIS_ROLLUP_LEAF_INDEX={}",
@@ -279,6 +288,7 @@ pub(crate) async fn create_genesis_l1_batch(
         base_fee_per_gas: 0,
         l1_gas_price: 0,
         l2_fair_gas_price: 0,
+        fair_pubdata_price: None,
         base_system_contracts_hashes: base_system_contracts.hashes(),
         protocol_version: Some(ProtocolVersionId::latest()),
         virtual_blocks: 0,
@@ -329,7 +339,7 @@ pub(crate) async fn add_eth_token(storage: &mut StorageProcessor<'_>) {
 
     transaction
         .tokens_dal()
-        .add_tokens(vec![eth_token.clone()])
+        .add_tokens(vec![eth_token.clone()], None)
         .await;
     transaction
         .tokens_dal()
@@ -342,10 +352,11 @@ pub(crate) async fn add_eth_token(storage: &mut StorageProcessor<'_>) {
 pub(crate) async fn save_genesis_l1_batch_metadata(
     storage: &mut StorageProcessor<'_>,
     commitment: &L1BatchCommitment,
+    commitment_scheme: &CommitmentScheme,
     genesis_root_hash: H256,
     rollup_last_leaf_index: u64,
 ) {
-    let commitment_hash = commitment.hash();
+    let commitment_hash = commitment.hash_with_scheme(commitment_scheme);
 
     let metadata = L1BatchMetadata {
         root_hash: genesis_root_hash,
@@ -388,6 +399,8 @@ mod tests {
             system_contracts: get_system_smart_contracts(),
             first_l1_verifier_config: L1VerifierConfig::default(),
             first_verifier_address: Address::random(),
+            commitment_schemes: CommitmentSchemes::default(),
+            commitment_mode: L1BatchCommitmentMode::Rollup,
         };
         ensure_genesis_state(&mut conn, L2ChainId(270), &params)
             .await
@@ -1,19 +1,25 @@
 use crate::l1_gas_price::L1GasPriceProvider;
+use crate::token_registry::BaseTokenConversion;
 use std::fmt::Debug;
 use std::sync::Arc;
+use zksync_config::constants::L1_GAS_PER_PUBDATA_BYTE;
 
 /// Gas adjuster that bounds the gas price to the specified value.
 /// We need this to prevent the gas price from growing too much, because our bootloader is sensitive for the gas price and can fail if it's too high.
 /// And for mainnet it's not the case, but for testnet we can have a situation when the gas price is too high.
+///
+/// On chains with a custom base token, bounded prices are additionally converted from wei into the base token.
 pub struct BoundedGasAdjuster<G> {
     max_gas_price: u64,
     default_gas_adjuster: Arc<G>,
+    base_token_conversion: Option<BaseTokenConversion>,
 }
 
 impl<G> Debug for BoundedGasAdjuster<G> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BoundedGasAdjuster")
             .field("max_gas_price", &self.max_gas_price)
+            .field("base_token_conversion", &self.base_token_conversion)
             .finish()
     }
 }
@@ -23,12 +29,28 @@ impl<G> BoundedGasAdjuster<G> {
         Self {
             max_gas_price,
             default_gas_adjuster,
+            base_token_conversion: None,
         }
     }
-}
 
-impl<G: L1GasPriceProvider> L1GasPriceProvider for BoundedGasAdjuster<G> {
-    fn estimate_effective_gas_price(&self) -> u64 {
+    /// Sets the conversion used to express prices in the base token of the chain.
+    #[must_use]
+    pub fn with_base_token_conversion(mut self, conversion: Option<BaseTokenConversion>) -> Self {
+        self.base_token_conversion = conversion;
+        self
+    }
+
+    fn convert(&self, wei_value: u64) -> u64 {
+        match &self.base_token_conversion {
+            Some(conversion) => conversion.convert(wei_value),
+            None => wei_value,
+        }
+    }
+
+    fn bounded_gas_price(&self) -> u64
+    where
+        G: L1GasPriceProvider,
+    {
         let default_gas_price = self.default_gas_adjuster.estimate_effective_gas_price();
         if default_gas_price > self.max_gas_price {
             tracing::warn!(
@@ -42,3 +64,32 @@ impl<G: L1GasPriceProvider> L1GasPriceProvider for BoundedGasAdjuster<G> {
         default_gas_price
     }
 }
+
+impl<G: L1GasPriceProvider> L1GasPriceProvider for BoundedGasAdjuster<G> {
+    fn estimate_effective_gas_price(&self) -> u64 {
+        self.convert(self.bounded_gas_price())
+    }
+
+    fn estimate_effective_pubdata_price(&self) -> u64 {
+        let max_pubdata_price = self
+            .max_gas_price
+            .saturating_mul(u64::from(L1_GAS_PER_PUBDATA_BYTE));
+        let default_pubdata_price = self.default_gas_adjuster.estimate_effective_pubdata_price();
+        self.convert(default_pubdata_price.min(max_pubdata_price))
+    }
+
+    fn estimate_effective_blob_base_fee(&self) -> Option<u64> {
+        let blob_base_fee = self
+            .default_gas_adjuster
+            .estimate_effective_blob_base_fee()?;
+        Some(self.convert(blob_base_fee))
+    }
+
+    fn estimate_base_fee_trend(&self) -> Option<f64> {
+        self.default_gas_adjuster.estimate_base_fee_trend()
+    }
+
+    fn latest_l1_block_number(&self) -> Option<u64> {
+        self.default_gas_adjuster.latest_l1_block_number()
+    }
+}
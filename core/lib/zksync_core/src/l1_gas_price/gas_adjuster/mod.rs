@@ -2,13 +2,20 @@
 
 // Built-in deps
 use std::collections::VecDeque;
-use std::sync::{Arc, RwLock};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, RwLock,
+};
 use tokio::sync::watch::Receiver;
 
-use zksync_config::GasAdjusterConfig;
+use zksync_config::{constants::L1_GAS_PER_PUBDATA_BYTE, GasAdjusterConfig};
 use zksync_eth_client::{types::Error, EthInterface};
 
 use super::{L1GasPriceProvider, L1TxParamsProvider};
+use crate::gas_tracker::PubdataDaMode;
+
+/// Blob gas spent per byte of pubdata sent in a blob (a blob of 128 KiB consumes 2^17 blob gas).
+const BLOB_GAS_PER_BYTE: u64 = 1;
 
 pub mod bounded_gas_adjuster;
 #[cfg(test)]
@@ -20,11 +27,19 @@ mod tests;
 pub struct GasAdjuster<E> {
     pub(super) statistics: GasStatistics,
     pub(super) config: GasAdjusterConfig,
+    pubdata_da_mode: PubdataDaMode,
+    /// Latest blob base fee observed on L1, or 0 if it's unknown (the minimum blob base fee is 1 wei).
+    /// Not tracked for validium chains, since they never send blobs.
+    blob_base_fee: AtomicU64,
     eth_client: E,
 }
 
 impl<E: EthInterface> GasAdjuster<E> {
-    pub async fn new(eth_client: E, config: GasAdjusterConfig) -> Result<Self, Error> {
+    pub async fn new(
+        eth_client: E,
+        config: GasAdjusterConfig,
+        pubdata_da_mode: PubdataDaMode,
+    ) -> Result<Self, Error> {
         // Subtracting 1 from the "latest" block number to prevent errors in case
         // the info about the latest block is not yet present on the node.
         // This sometimes happens on Infura.
@@ -36,11 +51,41 @@ impl<E: EthInterface> GasAdjuster<E> {
         let history = eth_client
             .base_fee_history(current_block, config.max_base_fee_samples, "gas_adjuster")
             .await?;
-        Ok(Self {
+        let this = Self {
             statistics: GasStatistics::new(config.max_base_fee_samples, current_block, &history),
+            blob_base_fee: AtomicU64::new(0),
             eth_client,
             config,
-        })
+            pubdata_da_mode,
+        };
+        this.update_blob_base_fee().await?;
+        Ok(this)
+    }
+
+    /// Fetches the current blob base fee from L1. If pubdata is sent as calldata, blob fees are only used
+    /// to dynamically choose the pubdata sending mode, so errors (e.g., if L1 doesn't support EIP-4844)
+    /// are logged rather than returned.
+    async fn update_blob_base_fee(&self) -> Result<(), Error> {
+        match self.pubdata_da_mode {
+            PubdataDaMode::External => return Ok(()),
+            PubdataDaMode::Calldata | PubdataDaMode::Blobs => { /* continue */ }
+        }
+
+        let blob_base_fee = match self.eth_client.get_blob_base_fee("gas_adjuster").await {
+            // Blob base fee can theoretically exceed `u64`; saturate it rather than panic.
+            Ok(fee) => fee.try_into().unwrap_or(u64::MAX),
+            Err(err) if self.pubdata_da_mode == PubdataDaMode::Calldata => {
+                tracing::debug!("Cannot fetch blob base fee: {err}");
+                0
+            }
+            Err(err) => return Err(err),
+        };
+        metrics::gauge!(
+            "server.gas_adjuster.current_blob_base_fee_per_gas",
+            blob_base_fee as f64
+        );
+        self.blob_base_fee.store(blob_base_fee, Ordering::Relaxed);
+        Ok(())
     }
 
     /// Performs an actualization routine for `GasAdjuster`.
@@ -76,7 +121,8 @@ impl<E: EthInterface> GasAdjuster<E> {
 
             self.statistics.add_samples(&history);
         }
-        Ok(())
+
+        self.update_blob_base_fee().await
     }
 
     pub async fn run(self: Arc<Self>, stop_receiver: Receiver<bool>) -> anyhow::Result<()> {
@@ -108,6 +154,38 @@ impl<E: EthInterface> L1GasPriceProvider for GasAdjuster<E> {
 
         (self.config.internal_l1_pricing_multiplier * effective_gas_price as f64) as u64
     }
+
+    /// Returns the price of publishing a byte of pubdata in the DA mode of the chain.
+    fn estimate_effective_pubdata_price(&self) -> u64 {
+        match self.pubdata_da_mode {
+            PubdataDaMode::Calldata => {
+                self.estimate_effective_gas_price() * u64::from(L1_GAS_PER_PUBDATA_BYTE)
+            }
+            PubdataDaMode::Blobs => {
+                self.estimate_effective_blob_base_fee().unwrap_or(0) * BLOB_GAS_PER_BYTE
+            }
+            PubdataDaMode::External => self.config.external_da_price_per_pubdata_byte,
+        }
+    }
+
+    fn estimate_effective_blob_base_fee(&self) -> Option<u64> {
+        let blob_base_fee = self.blob_base_fee.load(Ordering::Relaxed);
+        (blob_base_fee > 0)
+            .then(|| (self.config.internal_l1_pricing_multiplier * blob_base_fee as f64) as u64)
+    }
+
+    fn estimate_base_fee_trend(&self) -> Option<f64> {
+        let median = self.statistics.median();
+        if median == 0 {
+            return None;
+        }
+        Some(self.statistics.last_added_value() as f64 / median as f64)
+    }
+
+    fn latest_l1_block_number(&self) -> Option<u64> {
+        let block_number = self.statistics.last_processed_block();
+        (block_number > 0).then_some(block_number as u64)
+    }
 }
 
 impl<E: EthInterface> L1TxParamsProvider for GasAdjuster<E> {
@@ -1,6 +1,8 @@
 use super::{GasAdjuster, GasStatisticsInner};
+use crate::gas_tracker::PubdataDaMode;
+use crate::l1_gas_price::L1GasPriceProvider;
 use std::collections::VecDeque;
-use std::sync::Arc;
+use std::sync::{atomic::Ordering, Arc};
 use zksync_config::GasAdjusterConfig;
 use zksync_eth_client::clients::mock::MockEthereum;
 
@@ -43,7 +45,9 @@ async fn kept_updated() {
             internal_enforced_l1_gas_price: None,
             poll_period: 5,
             max_l1_gas_price: None,
+            external_da_price_per_pubdata_byte: 0,
         },
+        PubdataDaMode::Calldata,
     )
     .await
     .unwrap();
@@ -57,3 +61,98 @@ async fn kept_updated() {
     assert_eq!(adjuster.statistics.0.read().unwrap().samples.len(), 5);
     assert_eq!(adjuster.statistics.0.read().unwrap().median(), 7);
 }
+
+/// Check that pubdata is priced according to the DA mode
+#[tokio::test]
+async fn pubdata_price_depends_on_da_mode() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 10, 10, 10, 10])
+            .with_blob_base_fee(3),
+    );
+    eth_client.advance_block_number(5);
+
+    let config = GasAdjusterConfig {
+        default_priority_fee_per_gas: 5,
+        max_base_fee_samples: 5,
+        pricing_formula_parameter_a: 1.0,
+        pricing_formula_parameter_b: 1.0,
+        internal_l1_pricing_multiplier: 1.0,
+        internal_enforced_l1_gas_price: None,
+        poll_period: 5,
+        max_l1_gas_price: None,
+        external_da_price_per_pubdata_byte: 170,
+    };
+    let expected_prices = [
+        (PubdataDaMode::Calldata, 15 * 17),
+        (PubdataDaMode::Blobs, 3),
+        (PubdataDaMode::External, 170),
+    ];
+    for (da_mode, expected_pubdata_price) in expected_prices {
+        let adjuster = GasAdjuster::new(Arc::clone(&eth_client), config.clone(), da_mode)
+            .await
+            .unwrap();
+        // The L1 gas price (used by the VM to charge batch overhead) doesn't depend on the DA mode.
+        assert_eq!(adjuster.estimate_effective_gas_price(), 15);
+        assert_eq!(
+            adjuster.estimate_effective_pubdata_price(),
+            expected_pubdata_price
+        );
+    }
+}
+
+/// Check that the blob base fee is fetched from L1, is kept updated, and has the pricing multiplier applied
+#[tokio::test]
+async fn blob_base_fee_is_kept_updated() {
+    let eth_client = Arc::new(
+        MockEthereum::default()
+            .with_fee_history(vec![0, 10, 10, 10, 10])
+            .with_blob_base_fee(3),
+    );
+    eth_client.advance_block_number(5);
+
+    let config = GasAdjusterConfig {
+        default_priority_fee_per_gas: 5,
+        max_base_fee_samples: 5,
+        pricing_formula_parameter_a: 1.0,
+        pricing_formula_parameter_b: 1.0,
+        internal_l1_pricing_multiplier: 0.5,
+        internal_enforced_l1_gas_price: None,
+        poll_period: 5,
+        max_l1_gas_price: None,
+        external_da_price_per_pubdata_byte: 0,
+    };
+    let adjuster = GasAdjuster::new(Arc::clone(&eth_client), config, PubdataDaMode::Blobs)
+        .await
+        .unwrap();
+    assert_eq!(adjuster.estimate_effective_blob_base_fee(), Some(1));
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 1);
+
+    eth_client.blob_base_fee.store(100, Ordering::SeqCst);
+    adjuster.keep_updated().await.unwrap();
+    assert_eq!(adjuster.estimate_effective_blob_base_fee(), Some(50));
+    assert_eq!(adjuster.estimate_effective_pubdata_price(), 50);
+}
+
+/// Check that the base fee trend compares the latest base fee with the median
+#[tokio::test]
+async fn base_fee_trend() {
+    let eth_client = Arc::new(MockEthereum::default().with_fee_history(vec![0, 10, 10, 10, 30]));
+    eth_client.advance_block_number(5);
+
+    let config = GasAdjusterConfig {
+        default_priority_fee_per_gas: 5,
+        max_base_fee_samples: 5,
+        pricing_formula_parameter_a: 1.0,
+        pricing_formula_parameter_b: 1.0,
+        internal_l1_pricing_multiplier: 1.0,
+        internal_enforced_l1_gas_price: None,
+        poll_period: 5,
+        max_l1_gas_price: None,
+        external_da_price_per_pubdata_byte: 0,
+    };
+    let adjuster = GasAdjuster::new(eth_client, config, PubdataDaMode::Calldata)
+        .await
+        .unwrap();
+    assert_eq!(adjuster.estimate_base_fee_trend(), Some(3.0));
+}
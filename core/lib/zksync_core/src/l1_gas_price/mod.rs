@@ -5,6 +5,8 @@ pub use gas_adjuster::GasAdjuster;
 pub use main_node_fetcher::MainNodeGasPriceFetcher;
 pub use singleton::GasAdjusterSingleton;
 
+use zksync_config::constants::L1_GAS_PER_PUBDATA_BYTE;
+
 mod gas_adjuster;
 mod main_node_fetcher;
 pub mod singleton;
@@ -15,6 +17,32 @@ pub trait L1GasPriceProvider {
     /// Returns a best guess of a realistic value for the L1 gas price.
     /// Return value is in wei.
     fn estimate_effective_gas_price(&self) -> u64;
+
+    /// Returns a best guess of a realistic price for publishing a byte of pubdata, in wei.
+    /// This price is passed to the VM separately from the L1 gas price (which is used to charge
+    /// the L1 batch overhead). The default implementation assumes that pubdata is sent to L1 as calldata.
+    fn estimate_effective_pubdata_price(&self) -> u64 {
+        self.estimate_effective_gas_price() * u64::from(L1_GAS_PER_PUBDATA_BYTE)
+    }
+
+    /// Returns a best guess of a realistic L1 blob base fee (in wei per blob gas), or `None` if the provider
+    /// doesn't have information about blob fees. Similarly to [`Self::estimate_effective_gas_price()`],
+    /// the returned value may be adjusted compared to the fee observed on L1.
+    fn estimate_effective_blob_base_fee(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the ratio of the latest observed L1 base fee to the median base fee, or `None` if the provider
+    /// doesn't track base fee history. Values above 1 mean that the base fee is rising, e.g. because L1 is congested.
+    fn estimate_base_fee_trend(&self) -> Option<f64> {
+        None
+    }
+
+    /// Returns the number of the latest L1 block observed by the provider, or `None` if the provider
+    /// doesn't track L1 blocks.
+    fn latest_l1_block_number(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// Extended version of `L1GasPriceProvider` that can provide parameters
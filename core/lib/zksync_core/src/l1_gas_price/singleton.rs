@@ -1,15 +1,23 @@
+use crate::gas_tracker::PubdataDaMode;
 use crate::l1_gas_price::{BoundedGasAdjuster, GasAdjuster};
+use crate::token_registry::BaseTokenConversion;
 use anyhow::Context as _;
 use std::sync::Arc;
 use tokio::sync::{watch, OnceCell};
 use tokio::task::JoinHandle;
-use zksync_config::{ETHClientConfig, GasAdjusterConfig};
+use zksync_config::{configs::chain::StateKeeperConfig, GasAdjusterConfig};
 use zksync_eth_client::clients::http::QueryClient;
 
 /// Special struct for creating a singleton of `GasAdjuster`.
-/// This is needed only for running the server. This struct uses all configs from env.
-#[derive(Debug, Default)]
-pub struct GasAdjusterSingleton(OnceCell<Result<Arc<GasAdjuster<QueryClient>>, Error>>);
+/// This is needed only for running the server.
+#[derive(Debug)]
+pub struct GasAdjusterSingleton {
+    query_client: QueryClient,
+    gas_adjuster_config: GasAdjusterConfig,
+    state_keeper_config: StateKeeperConfig,
+    adjuster: OnceCell<Result<Arc<GasAdjuster<QueryClient>>, Error>>,
+    base_token_conversion: Option<BaseTokenConversion>,
+}
 
 #[derive(thiserror::Error, Debug, Clone)]
 #[error(transparent)]
@@ -22,23 +30,40 @@ impl From<anyhow::Error> for Error {
 }
 
 impl GasAdjusterSingleton {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(
+        query_client: QueryClient,
+        gas_adjuster_config: GasAdjusterConfig,
+        state_keeper_config: StateKeeperConfig,
+    ) -> Self {
+        Self {
+            query_client,
+            gas_adjuster_config,
+            state_keeper_config,
+            adjuster: OnceCell::new(),
+            base_token_conversion: None,
+        }
+    }
+
+    /// Sets the conversion applied by bounded gas adjusters on chains with a custom base token.
+    pub fn set_base_token_conversion(&mut self, conversion: BaseTokenConversion) {
+        self.base_token_conversion = Some(conversion);
     }
 
     pub async fn get_or_init(&mut self) -> Result<Arc<GasAdjuster<QueryClient>>, Error> {
+        let query_client = &self.query_client;
+        let gas_adjuster_config = &self.gas_adjuster_config;
+        let state_keeper_config = &self.state_keeper_config;
         let adjuster = self
-            .0
+            .adjuster
             .get_or_init(|| async {
-                let eth_client_config =
-                    ETHClientConfig::from_env().context("ETHClientConfig::from_env()")?;
-                let query_client =
-                    QueryClient::new(&eth_client_config.web3_url).context("QueryClient::new()")?;
-                let gas_adjuster_config =
-                    GasAdjusterConfig::from_env().context("GasAdjusterConfig::from_env()")?;
-                let adjuster = GasAdjuster::new(query_client.clone(), gas_adjuster_config)
-                    .await
-                    .context("GasAdjuster::new()")?;
+                let pubdata_da_mode = PubdataDaMode::new(
+                    state_keeper_config.l1_batch_commitment_mode,
+                    state_keeper_config.pubdata_sending_mode,
+                );
+                let adjuster =
+                    GasAdjuster::new(query_client.clone(), *gas_adjuster_config, pubdata_da_mode)
+                        .await
+                        .context("GasAdjuster::new()")?;
                 Ok(Arc::new(adjuster))
             })
             .await;
@@ -48,19 +73,18 @@ impl GasAdjusterSingleton {
     pub async fn get_or_init_bounded(
         &mut self,
     ) -> anyhow::Result<Arc<BoundedGasAdjuster<GasAdjuster<QueryClient>>>> {
-        let config = GasAdjusterConfig::from_env().context("GasAdjusterConfig::from_env()")?;
+        let max_l1_gas_price = self.gas_adjuster_config.max_l1_gas_price();
         let adjuster = self.get_or_init().await.context("get_or_init()")?;
-        Ok(Arc::new(BoundedGasAdjuster::new(
-            config.max_l1_gas_price(),
-            adjuster,
-        )))
+        let bounded_adjuster = BoundedGasAdjuster::new(max_l1_gas_price, adjuster)
+            .with_base_token_conversion(self.base_token_conversion.clone());
+        Ok(Arc::new(bounded_adjuster))
     }
 
     pub fn run_if_initialized(
         self,
         stop_signal: watch::Receiver<bool>,
     ) -> Option<JoinHandle<anyhow::Result<()>>> {
-        let gas_adjuster = self.0.get()?.clone();
+        let gas_adjuster = self.adjuster.get()?.clone();
         Some(tokio::spawn(
             async move { gas_adjuster?.run(stop_signal).await },
         ))
@@ -1,9 +1,10 @@
 #![allow(clippy::upper_case_acronyms, clippy::derive_partial_eq_without_eq)]
 
-use std::{str::FromStr, sync::Arc, time::Instant};
+use std::{path::Path, str::FromStr, sync::Arc, time::Instant};
 
 use anyhow::Context as _;
 use futures::channel::oneshot;
+use metrics::Label;
 use prometheus_exporter::PrometheusExporterConfig;
 use tokio::{sync::watch, task::JoinHandle};
 
@@ -15,21 +16,25 @@ use zksync_circuit_breaker::{
 use zksync_config::configs::{
     api::{HealthCheckConfig, Web3JsonRpcConfig},
     chain::{
-        self, CircuitBreakerConfig, MempoolConfig, NetworkConfig, OperationsManagerConfig,
-        StateKeeperConfig,
+        self, CircuitBreakerConfig, CommitmentSchemeConfig, MempoolConfig, NetworkConfig,
+        OperationsManagerConfig, StateKeeperConfig,
     },
     database::MerkleTreeMode,
     house_keeper::HouseKeeperConfig,
     FriProofCompressorConfig, FriProverConfig, FriWitnessGeneratorConfig, PrometheusConfig,
-    ProofDataHandlerConfig, ProverGroupConfig, WitnessGeneratorConfig,
+    ProofDataHandlerConfig, ProverGroupConfig, StreamPublisherConfig, TokenRegistryConfig,
+    WebhookNotifierConfig, WithdrawalFinalizerConfig, WitnessGeneratorConfig,
 };
 use zksync_config::{
-    ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, FetcherConfig,
-    ProverConfigs,
+    ApiConfig, ContractsConfig, DBConfig, ETHClientConfig, ETHSenderConfig, ETHWatchConfig,
+    EnvVars, FetcherConfig, GasAdjusterConfig, ObjectStoreConfig, ProverConfigs,
 };
 use zksync_contracts::BaseSystemContracts;
 use zksync_dal::{
-    connection::DbVariant, healthcheck::ConnectionPoolHealthCheck, ConnectionPool, StorageProcessor,
+    connection::DbVariant,
+    healthcheck::ConnectionPoolHealthCheck,
+    online_migrations_dal::{run_online_migrations, ONLINE_MIGRATIONS},
+    ConnectionPool,
 };
 use zksync_eth_client::clients::http::QueryClient;
 use zksync_eth_client::{clients::http::PKSigningClient, BoundEthInterface};
@@ -37,12 +42,13 @@ use zksync_health_check::{CheckHealth, HealthStatus, ReactiveHealthCheck};
 use zksync_object_store::ObjectStoreFactory;
 use zksync_prover_utils::periodic_job::PeriodicJob;
 use zksync_queued_job_processor::JobProcessor;
-use zksync_state::PostgresStorageCaches;
+use zksync_state::{ForkedState, PostgresStorageCaches, RocksdbSecondaryStorage};
 use zksync_types::{
+    commitment::CommitmentSchemes,
     proofs::AggregationRound,
     protocol_version::{L1VerifierConfig, VerifierParams},
     system_contracts::get_system_smart_contracts,
-    Address, L2ChainId, PackedEthSignature, ProtocolVersionId,
+    Address, L2ChainId, MiniblockNumber, PackedEthSignature, ProtocolVersionId,
 };
 use zksync_verification_key_server::get_cached_commitments;
 
@@ -50,7 +56,10 @@ use crate::api_server::healthcheck::HealthCheckHandle;
 use crate::api_server::tx_sender::TxSenderConfig;
 use crate::api_server::tx_sender::{TxSender, TxSenderBuilder};
 use crate::api_server::web3::{state::InternalApiConfig, Namespace};
-use crate::eth_sender::{Aggregator, EthTxManager};
+use crate::bridge_indexer::BridgeIndexer;
+use crate::dev_mode::{fork_remote_node, DevModeHandle};
+use crate::eth_sender::{Aggregator, EthTxManager, L1CongestionTracker, PubdataCostEstimator};
+use crate::gas_tracker::PubdataDaMode;
 use crate::house_keeper::fri_proof_compressor_job_retry_manager::FriProofCompressorJobRetryManager;
 use crate::house_keeper::fri_proof_compressor_queue_monitor::FriProofCompressorStatsReporter;
 use crate::house_keeper::fri_prover_job_retry_manager::FriProverJobRetryManager;
@@ -70,7 +79,16 @@ use crate::l1_gas_price::{GasAdjusterSingleton, L1GasPriceProvider};
 use crate::metadata_calculator::{
     MetadataCalculator, MetadataCalculatorConfig, MetadataCalculatorModeConfig,
 };
-use crate::state_keeper::{create_state_keeper, MempoolFetcher, MempoolGuard, MiniblockSealer};
+use crate::state_keeper::{
+    backpressure::{BackpressureMonitor, PipelineLagLimits},
+    create_state_keeper, MempoolFetcher, MempoolGuard, MiniblockSealer, ProtectiveReadsWriter,
+    SealingStatusHandle, TxExecutionHints,
+};
+use crate::stream_publisher::StreamPublisher;
+use crate::token_registry::{BaseTokenConversion, TokenPolicy, TokenRegistry};
+use crate::vm_thread_pool::VmThreadPool;
+use crate::webhook_notifier::WebhookNotifier;
+use crate::withdrawal_finalizer::WithdrawalFinalizer;
 use crate::witness_generator::{
     basic_circuits::BasicWitnessGenerator, leaf_aggregation::LeafAggregationWitnessGenerator,
     node_aggregation::NodeAggregationWitnessGenerator, scheduler::SchedulerWitnessGenerator,
@@ -89,31 +107,48 @@ use crate::{
 
 pub mod api_server;
 pub mod block_reverter;
+pub mod bridge_indexer;
 pub mod consistency_checker;
 pub mod data_fetchers;
+pub mod dev_mode;
 pub mod eth_sender;
 pub mod eth_watch;
+pub mod execution_diff;
 pub mod fee_ticker;
 pub mod gas_tracker;
 pub mod genesis;
 pub mod house_keeper;
 pub mod l1_gas_price;
 pub mod metadata_calculator;
+pub mod multi_chain;
+#[cfg(feature = "profiling")]
+pub mod profiling;
 pub mod proof_data_handler;
 pub mod reorg_detector;
 pub mod state_keeper;
+pub mod stream_publisher;
 pub mod sync_layer;
+pub mod token_registry;
+pub mod vm_thread_pool;
+pub mod webhook_notifier;
+pub mod withdrawal_finalizer;
 pub mod witness_generator;
 
 /// Inserts the initial information about zkSync tokens into the database.
 pub async fn genesis_init(
+    db_config: &DBConfig,
     eth_sender: &ETHSenderConfig,
     network_config: &NetworkConfig,
     contracts_config: &ContractsConfig,
+    state_keeper_config: &StateKeeperConfig,
+    commitment_scheme_config: &CommitmentSchemeConfig,
 ) -> anyhow::Result<()> {
-    let mut storage = StorageProcessor::establish_connection(true)
+    let pool = ConnectionPool::singleton(DbVariant::Master)
+        .set_db_config(db_config)
+        .build()
         .await
-        .context("establish_connection")?;
+        .context("failed to build connection pool")?;
+    let mut storage = pool.access_storage().await?;
     let operator_address = PackedEthSignature::address_from_private_key(
         &eth_sender
             .sender
@@ -142,15 +177,57 @@ pub async fn genesis_init(
                 recursion_scheduler_level_vk_hash: contracts_config
                     .recursion_scheduler_level_vk_hash,
             },
+            commitment_schemes: load_commitment_schemes(commitment_scheme_config)?,
+            commitment_mode: state_keeper_config.l1_batch_commitment_mode,
         },
     )
     .await?;
     Ok(())
 }
 
-pub async fn is_genesis_needed() -> bool {
-    let mut storage = StorageProcessor::establish_connection(true).await.unwrap();
-    storage.blocks_dal().is_genesis_needed().await.unwrap()
+fn load_commitment_schemes(config: &CommitmentSchemeConfig) -> anyhow::Result<CommitmentSchemes> {
+    CommitmentSchemes::try_from(config)
+        .context("invalid protocol version in commitment scheme config")
+}
+
+pub async fn is_genesis_needed(db_config: &DBConfig) -> anyhow::Result<bool> {
+    let pool = ConnectionPool::singleton(DbVariant::Master)
+        .set_db_config(db_config)
+        .build()
+        .await
+        .context("failed to build connection pool")?;
+    let mut storage = pool.access_storage().await?;
+    Ok(storage.blocks_dal().is_genesis_needed().await?)
+}
+
+/// Drops (`enforce == false`) or restores foreign keys referencing miniblocks from the tables with miniblock data.
+/// The keys must be dropped before enabling parallel miniblock sealing, and should be restored once the state keeper
+/// is restarted with parallel sealing disabled. The state keeper doesn't manage the keys itself and refuses
+/// to start parallel sealing while the keys exist.
+pub async fn set_miniblock_data_foreign_keys(
+    db_config: &DBConfig,
+    enforce: bool,
+) -> anyhow::Result<()> {
+    let pool = ConnectionPool::singleton(DbVariant::Master)
+        .set_db_config(db_config)
+        .build()
+        .await
+        .context("failed to build connection pool")?;
+    let mut storage = pool.access_storage().await?;
+    if enforce {
+        storage
+            .blocks_dal()
+            .restore_miniblock_data_foreign_keys()
+            .await
+            .context("restore_miniblock_data_foreign_keys()")?;
+    } else {
+        storage
+            .blocks_dal()
+            .drop_miniblock_data_foreign_keys()
+            .await
+            .context("drop_miniblock_data_foreign_keys()")?;
+    }
+    Ok(())
 }
 
 /// Sets up an interrupt handler and returns a future that resolves once an interrupt signal
@@ -202,6 +279,24 @@ pub enum Component {
     Housekeeper,
     // Component for exposing API's to prover for providing proof generation data and accepting proofs.
     ProofDataHandler,
+    // Component delivering webhook notifications on L1 batch lifecycle transitions.
+    WebhookNotifier,
+    // Component streaming sealed miniblocks to a message broker (NATS or Kafka).
+    StreamPublisher,
+    // Firehose-compatible block stream API for indexers.
+    FirehoseApi,
+    // GraphQL API over the chain data.
+    GraphqlApi,
+    // Rosetta Data API for exchange integrations.
+    RosettaApi,
+    // Component finalizing withdrawals on L1 for configured receivers and tokens.
+    WithdrawalFinalizer,
+    // Component refreshing metadata of tokens bridged to L2.
+    TokenRegistry,
+    // Component indexing bridge deposits and withdrawals.
+    BridgeIndexer,
+    // Component backfilling online migrations of huge tables.
+    OnlineMigrations,
 }
 
 #[derive(Debug)]
@@ -270,11 +365,46 @@ impl FromStr for Components {
             "eth_tx_aggregator" => Ok(Components(vec![Component::EthTxAggregator])),
             "eth_tx_manager" => Ok(Components(vec![Component::EthTxManager])),
             "proof_data_handler" => Ok(Components(vec![Component::ProofDataHandler])),
+            "webhook_notifier" => Ok(Components(vec![Component::WebhookNotifier])),
+            "stream_publisher" => Ok(Components(vec![Component::StreamPublisher])),
+            "firehose_api" => Ok(Components(vec![Component::FirehoseApi])),
+            "graphql_api" => Ok(Components(vec![Component::GraphqlApi])),
+            "rosetta_api" => Ok(Components(vec![Component::RosettaApi])),
+            "withdrawal_finalizer" => Ok(Components(vec![Component::WithdrawalFinalizer])),
+            "token_registry" => Ok(Components(vec![Component::TokenRegistry])),
+            "bridge_indexer" => Ok(Components(vec![Component::BridgeIndexer])),
+            "online_migrations" => Ok(Components(vec![Component::OnlineMigrations])),
             other => Err(format!("{} is not a valid component name", other)),
         }
     }
 }
 
+/// Options for initializing components of a chain, allowing several chains to run in a single process.
+#[derive(Debug, Clone)]
+pub struct ChainOptions {
+    /// L1 client shared with other chains. If not set, a client is created based on the configuration.
+    pub l1_client: Option<QueryClient>,
+    /// Whether to run the Prometheus exporter. The exporter serves metrics for the entire process,
+    /// so it should run for a single chain only.
+    pub run_prometheus_exporter: bool,
+    /// Environment variables the chain configuration is loaded from.
+    pub env_vars: EnvVars,
+    /// Name of the chain used to label metrics reported during initialization. Should be set
+    /// if the process runs several chains.
+    pub chain: Option<String>,
+}
+
+impl Default for ChainOptions {
+    fn default() -> Self {
+        Self {
+            l1_client: None,
+            run_prometheus_exporter: true,
+            env_vars: EnvVars::from_env(),
+            chain: None,
+        }
+    }
+}
+
 pub async fn initialize_components(
     components: Vec<Component>,
     use_prometheus_push_gateway: bool,
@@ -284,32 +414,66 @@ pub async fn initialize_components(
     oneshot::Receiver<CircuitBreakerError>,
     HealthCheckHandle,
 )> {
-    tracing::info!("Starting the components: {components:?}");
+    initialize_chain_components(
+        components,
+        use_prometheus_push_gateway,
+        ChainOptions::default(),
+    )
+    .await
+}
 
-    let db_config = DBConfig::from_env().context("DbConfig::from_env()")?;
+/// Initializes components of a chain configured via [`ChainOptions::env_vars`]. Unlike [`initialize_components()`],
+/// allows to customize resources shared with other chains run by the process.
+pub async fn initialize_chain_components(
+    components: Vec<Component>,
+    use_prometheus_push_gateway: bool,
+    options: ChainOptions,
+) -> anyhow::Result<(
+    Vec<JoinHandle<anyhow::Result<()>>>,
+    watch::Sender<bool>,
+    oneshot::Receiver<CircuitBreakerError>,
+    HealthCheckHandle,
+)> {
+    tracing::info!("Starting the components: {components:?}");
+    let ChainOptions {
+        l1_client,
+        run_prometheus_exporter,
+        env_vars,
+        chain,
+    } = options;
+    let env_vars = &env_vars;
+    let chain = chain.as_deref();
+
+    let db_config = DBConfig::from_env_vars(env_vars).context("DBConfig::from_env_vars()")?;
     let connection_pool = ConnectionPool::builder(DbVariant::Master)
+        .set_db_config(&db_config)
         .build()
         .await
         .context("failed to build connection_pool")?;
     let prover_connection_pool = ConnectionPool::builder(DbVariant::Prover)
+        .set_db_config(&db_config)
         .build()
         .await
         .context("failed to build prover_connection_pool")?;
     let replica_connection_pool = ConnectionPool::builder(DbVariant::Replica)
+        .set_db_config(&db_config)
         .set_statement_timeout(db_config.statement_timeout())
         .build()
         .await
         .context("failed to build replica_connection_pool")?;
 
     let mut healthchecks: Vec<Box<dyn CheckHealth>> = Vec::new();
-    let contracts_config = ContractsConfig::from_env().context("ContractsConfig::from_env()")?;
-    let eth_client_config = ETHClientConfig::from_env().context("ETHClientConfig::from_env()")?;
-    let circuit_breaker_config =
-        CircuitBreakerConfig::from_env().context("CircuitBreakerConfig::from_env()")?;
+    let contracts_config =
+        ContractsConfig::from_env_vars(env_vars).context("ContractsConfig::from_env_vars()")?;
+    let eth_client_config =
+        ETHClientConfig::from_env_vars(env_vars).context("ETHClientConfig::from_env_vars()")?;
+    let circuit_breaker_config = CircuitBreakerConfig::from_env_vars(env_vars)
+        .context("CircuitBreakerConfig::from_env_vars()")?;
 
     let main_zksync_contract_address = contracts_config.diamond_proxy_addr;
     let circuit_breaker_checker = CircuitBreakerChecker::new(
         circuit_breakers_for_components(
+            &db_config,
             &components,
             &eth_client_config.web3_url,
             &circuit_breaker_config,
@@ -323,45 +487,125 @@ pub async fn initialize_components(
         panic!("Circuit breaker triggered: {}", err);
     });
 
-    let query_client = QueryClient::new(&eth_client_config.web3_url).unwrap();
-    let mut gas_adjuster = GasAdjusterSingleton::new();
+    let query_client = match l1_client {
+        Some(client) => client,
+        None => QueryClient::new(&eth_client_config.web3_url).context("QueryClient::new()")?,
+    };
+    let mut gas_adjuster = GasAdjusterSingleton::new(
+        query_client.clone(),
+        GasAdjusterConfig::from_env_vars(env_vars).context("GasAdjusterConfig::from_env_vars()")?,
+        StateKeeperConfig::from_env_vars(env_vars).context("StateKeeperConfig::from_env_vars()")?,
+    );
 
     let (stop_sender, stop_receiver) = watch::channel(false);
     let (cb_sender, cb_receiver) = oneshot::channel();
 
     // Prometheus exporter and circuit breaker checker should run for every component configuration.
-    let prom_config = PrometheusConfig::from_env().context("PrometheusConfig::from_env()")?;
+    let prom_config =
+        PrometheusConfig::from_env_vars(env_vars).context("PrometheusConfig::from_env_vars()")?;
     let prom_config = if use_prometheus_push_gateway {
         PrometheusExporterConfig::push(prom_config.gateway_endpoint(), prom_config.push_interval())
     } else {
         PrometheusExporterConfig::pull(prom_config.listener_port)
     };
 
-    let (prometheus_health_check, prometheus_health_updater) =
-        ReactiveHealthCheck::new("prometheus_exporter");
-    healthchecks.push(Box::new(prometheus_health_check));
-    let prometheus_task = prom_config.run(stop_receiver.clone());
-    let prometheus_task = tokio::spawn(async move {
-        prometheus_health_updater.update(HealthStatus::Ready.into());
-        let res = prometheus_task.await;
-        drop(prometheus_health_updater);
-        res
-    });
+    let mut task_futures: Vec<JoinHandle<anyhow::Result<()>>> = vec![tokio::spawn(
+        circuit_breaker_checker.run(cb_sender, stop_receiver.clone()),
+    )];
+
+    let token_registry_config = TokenRegistryConfig::from_env_vars(env_vars)
+        .context("TokenRegistryConfig::from_env_vars()")?;
+    if let Some(base_token) = token_registry_config.base_token {
+        let conversion = BaseTokenConversion::new(&connection_pool, base_token)
+            .await
+            .context("BaseTokenConversion::new()")?;
+        task_futures.push(tokio::spawn(conversion.clone().run(
+            connection_pool.clone(),
+            token_registry_config.base_token_rate_refresh_interval(),
+            stop_receiver.clone(),
+        )));
+        gas_adjuster.set_base_token_conversion(conversion);
+    }
 
-    let mut task_futures: Vec<JoinHandle<anyhow::Result<()>>> = vec![
-        prometheus_task,
-        tokio::spawn(circuit_breaker_checker.run(cb_sender, stop_receiver.clone())),
-    ];
+    if run_prometheus_exporter {
+        let (prometheus_health_check, prometheus_health_updater) =
+            ReactiveHealthCheck::new("prometheus_exporter");
+        healthchecks.push(Box::new(prometheus_health_check));
+        let prometheus_task = prom_config.run(stop_receiver.clone());
+        task_futures.push(tokio::spawn(async move {
+            prometheus_health_updater.update(HealthStatus::Ready.into());
+            let res = prometheus_task.await;
+            drop(prometheus_health_updater);
+            res
+        }));
+    }
+
+    // Execution hints are only useful if the API server and the state keeper run in the same process.
+    let tx_execution_hints = if components.contains(&Component::StateKeeper) {
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        state_keeper_config
+            .tx_execution_hints_capacity
+            .map(TxExecutionHints::new)
+    } else {
+        None
+    };
+
+    // Sealing status of the pending L1 batch can only be exposed by the API server running
+    // in the same process as the state keeper.
+    let sealing_status = if components.contains(&Component::StateKeeper) {
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        Some(SealingStatusHandle::new(&state_keeper_config))
+    } else {
+        None
+    };
+
+    // Dev mode requires the API server and the state keeper to run in the same process.
+    let dev_mode = if components.contains(&Component::StateKeeper) {
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        if state_keeper_config.dev_mode {
+            anyhow::ensure!(
+                !components.iter().any(|component| matches!(
+                    component,
+                    Component::EthWatcher | Component::EthTxAggregator | Component::EthTxManager
+                )),
+                "Dev mode cannot be enabled together with components interacting with L1"
+            );
+            tracing::warn!("Dev mode is enabled; it must never be used in production");
+            let fork = if let Some(fork_url) = &state_keeper_config.fork_url {
+                let fork_miniblock = state_keeper_config.fork_miniblock.map(MiniblockNumber);
+                let fork = fork_remote_node(fork_url, fork_miniblock)
+                    .await
+                    .context("failed forking remote node")?;
+                Some(fork)
+            } else {
+                None
+            };
+            Some(DevModeHandle::new(connection_pool.clone(), fork))
+        } else {
+            anyhow::ensure!(
+                state_keeper_config.fork_url.is_none(),
+                "Forking a remote node requires the dev mode"
+            );
+            None
+        }
+    } else {
+        None
+    };
 
     if components.contains(&Component::WsApi)
         || components.contains(&Component::HttpApi)
         || components.contains(&Component::ContractVerificationApi)
         || components.contains(&Component::ApiTranslator)
     {
-        let api_config = ApiConfig::from_env().context("ApiConfig::from_env()")?;
-        let state_keeper_config =
-            StateKeeperConfig::from_env().context("StateKeeperConfig::from_env()")?;
-        let network_config = NetworkConfig::from_env().context("NetworkConfig::from_env()")?;
+        let api_config =
+            ApiConfig::from_env_vars(env_vars).context("ApiConfig::from_env_vars()")?;
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
         let tx_sender_config = TxSenderConfig::new(
             &state_keeper_config,
             &api_config.web3_json_rpc,
@@ -371,6 +615,8 @@ pub async fn initialize_components(
             &network_config,
             &api_config.web3_json_rpc,
             &contracts_config,
+            &state_keeper_config,
+            TokenPolicy::new(&token_registry_config),
         );
 
         // Lazily initialize storage caches only when they are needed (e.g., skip their initialization
@@ -381,8 +627,13 @@ pub async fn initialize_components(
 
         if components.contains(&Component::HttpApi) {
             storage_caches = Some(
-                build_storage_caches(&replica_connection_pool, &mut task_futures)
-                    .context("build_storage_caches()")?,
+                build_storage_caches(
+                    env_vars,
+                    &replica_connection_pool,
+                    dev_mode.as_ref(),
+                    &mut task_futures,
+                )
+                .context("build_storage_caches()")?,
             );
 
             let started_at = Instant::now();
@@ -392,6 +643,7 @@ pub async fn initialize_components(
                 .await
                 .context("gas_adjuster.get_or_init_bounded()")?;
             let (futures, health_check) = run_http_api(
+                &db_config,
                 &tx_sender_config,
                 &state_keeper_config,
                 &internal_api_config,
@@ -403,20 +655,28 @@ pub async fn initialize_components(
                 state_keeper_config.save_call_traces,
                 components.contains(&Component::ApiTranslator),
                 storage_caches.clone().unwrap(),
+                tx_execution_hints.clone(),
+                sealing_status.clone(),
+                dev_mode.clone(),
             )
             .await
             .context("run_http_api")?;
             task_futures.extend(futures);
             healthchecks.push(Box::new(health_check));
             tracing::info!("initialized HTTP API in {:?}", started_at.elapsed());
-            metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "http_api");
+            report_init_latency(chain, started_at, vec![Label::new("stage", "http_api")]);
         }
 
         if components.contains(&Component::WsApi) {
             let storage_caches = match storage_caches {
                 Some(storage_caches) => storage_caches,
-                None => build_storage_caches(&replica_connection_pool, &mut task_futures)
-                    .context("build_Storage_caches()")?,
+                None => build_storage_caches(
+                    env_vars,
+                    &replica_connection_pool,
+                    dev_mode.as_ref(),
+                    &mut task_futures,
+                )
+                .context("build_Storage_caches()")?,
             };
 
             let started_at = Instant::now();
@@ -426,6 +686,7 @@ pub async fn initialize_components(
                 .await
                 .context("gas_adjuster.get_or_init_bounded()")?;
             let (futures, health_check) = run_ws_api(
+                &db_config,
                 &tx_sender_config,
                 &state_keeper_config,
                 &internal_api_config,
@@ -436,13 +697,16 @@ pub async fn initialize_components(
                 stop_receiver.clone(),
                 storage_caches,
                 components.contains(&Component::ApiTranslator),
+                tx_execution_hints.clone(),
+                sealing_status.clone(),
+                dev_mode.clone(),
             )
             .await
             .context("run_ws_api")?;
             task_futures.extend(futures);
             healthchecks.push(Box::new(health_check));
             tracing::info!("initialized WS API in {:?}", started_at.elapsed());
-            metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "ws_api");
+            report_init_latency(chain, started_at, vec![Label::new("stage", "ws_api")]);
         }
 
         if components.contains(&Component::ContractVerificationApi) {
@@ -458,10 +722,21 @@ pub async fn initialize_components(
                 "initialized contract verification REST API in {:?}",
                 started_at.elapsed()
             );
-            metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "contract_verification_api");
+            report_init_latency(
+                chain,
+                started_at,
+                vec![Label::new("stage", "contract_verification_api")],
+            );
         }
     }
 
+    let object_store_config =
+        ObjectStoreConfig::from_env_vars(env_vars).context("ObjectStoreConfig::from_env_vars()")?;
+    let store_factory = ObjectStoreFactory::new(object_store_config.clone());
+
+    // Allows the stream publisher to pick up miniblocks as soon as they are sealed by the state keeper.
+    let (sealed_miniblock_sender, sealed_miniblock_receiver) = watch::channel(MiniblockNumber(0));
+
     if components.contains(&Component::StateKeeper) {
         let started_at = Instant::now();
         tracing::info!("initializing State Keeper");
@@ -472,23 +747,35 @@ pub async fn initialize_components(
         add_state_keeper_to_task_futures(
             &mut task_futures,
             &contracts_config,
-            StateKeeperConfig::from_env().context("StateKeeperConfig::from_env()")?,
-            &NetworkConfig::from_env().context("NetworkConfig::from_env()")?,
+            StateKeeperConfig::from_env_vars(env_vars)
+                .context("StateKeeperConfig::from_env_vars()")?,
+            &NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?,
             &db_config,
-            &MempoolConfig::from_env().context("MempoolConfig::from_env()")?,
+            &MempoolConfig::from_env_vars(env_vars).context("MempoolConfig::from_env_vars()")?,
+            &object_store_config,
             bounded_gas_adjuster,
+            tx_execution_hints,
+            sealing_status,
+            dev_mode.as_ref(),
+            sealed_miniblock_sender,
             stop_receiver.clone(),
         )
         .await
         .context("add_state_keeper_to_task_futures()")?;
+        if let Some(dev_mode) = dev_mode {
+            task_futures.push(tokio::spawn(
+                dev_mode.run_batch_finalizer(stop_receiver.clone()),
+            ));
+        }
         tracing::info!("initialized State Keeper in {:?}", started_at.elapsed());
-        metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "state_keeper");
+        report_init_latency(chain, started_at, vec![Label::new("stage", "state_keeper")]);
     }
 
     if components.contains(&Component::EthWatcher) {
         let started_at = Instant::now();
         tracing::info!("initializing ETH-Watcher");
         let eth_watch_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
             .build()
             .await
             .context("failed to build eth_watch_pool")?;
@@ -497,39 +784,76 @@ pub async fn initialize_components(
                 eth_watch_pool,
                 query_client.clone(),
                 main_zksync_contract_address,
+                &ETHWatchConfig::from_env_vars(env_vars)
+                    .context("ETHWatchConfig::from_env_vars()")?,
                 stop_receiver.clone(),
             )
             .await
             .context("start_eth_watch()")?,
         );
         tracing::info!("initialized ETH-Watcher in {:?}", started_at.elapsed());
-        metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "eth_watcher");
+        report_init_latency(chain, started_at, vec![Label::new("stage", "eth_watcher")]);
     }
 
-    let store_factory = ObjectStoreFactory::from_env().context("ObjectStoreFactor::from_env()")?;
+    #[cfg(feature = "profiling")]
+    if components.contains(&Component::StateKeeper) {
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        if let Some(port) = state_keeper_config.profiling_admin_port {
+            profiling::init(store_factory.create_store().await).context("profiling::init()")?;
+            task_futures.push(tokio::spawn(profiling::run_admin_server(
+                port,
+                stop_receiver.clone(),
+            )));
+        }
+    }
 
     if components.contains(&Component::EthTxAggregator) {
         let started_at = Instant::now();
         tracing::info!("initializing ETH-TxAggregator");
         let eth_sender_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
             .build()
             .await
             .context("failed to build eth_sender_pool")?;
         let eth_sender_prover_pool = ConnectionPool::singleton(DbVariant::Prover)
+            .set_db_config(&db_config)
             .build()
             .await
             .context("failed to build eth_sender_prover_pool")?;
 
-        let eth_sender = ETHSenderConfig::from_env().context("ETHSenderConfig::from_env()")?;
+        let eth_sender =
+            ETHSenderConfig::from_env_vars(env_vars).context("ETHSenderConfig::from_env_vars()")?;
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
         let eth_client =
             PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
         let nonce = eth_client.pending_nonce("eth_sender").await.unwrap();
+        let mut aggregator = Aggregator::new(
+            eth_sender.sender.clone(),
+            store_factory.create_store().await,
+            state_keeper_config.l1_batch_commitment_mode,
+        );
+        if eth_sender.sender.estimate_pubdata_costs {
+            let gas_adjuster = gas_adjuster
+                .get_or_init()
+                .await
+                .context("gas_adjuster.get_or_init()")?;
+            aggregator =
+                aggregator.with_pubdata_cost_estimator(PubdataCostEstimator::new(gas_adjuster));
+        }
+        if eth_sender.sender.congestion_adaptive_aggregation {
+            let gas_adjuster = gas_adjuster
+                .get_or_init()
+                .await
+                .context("gas_adjuster.get_or_init()")?;
+            let congestion_tracker = L1CongestionTracker::new(&eth_sender.sender, gas_adjuster)
+                .context("L1CongestionTracker::new()")?;
+            aggregator = aggregator.with_congestion_tracker(congestion_tracker);
+        }
         let eth_tx_aggregator_actor = EthTxAggregator::new(
             eth_sender.sender.clone(),
-            Aggregator::new(
-                eth_sender.sender.clone(),
-                store_factory.create_store().await,
-            ),
+            aggregator,
             contracts_config.validator_timelock_addr,
             contracts_config.l1_multicall3_addr,
             main_zksync_contract_address,
@@ -542,17 +866,23 @@ pub async fn initialize_components(
             stop_receiver.clone(),
         )));
         tracing::info!("initialized ETH-TxAggregator in {:?}", started_at.elapsed());
-        metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "eth_tx_aggregator");
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "eth_tx_aggregator")],
+        );
     }
 
     if components.contains(&Component::EthTxManager) {
         let started_at = Instant::now();
         tracing::info!("initializing ETH-TxManager");
         let eth_manager_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
             .build()
             .await
             .context("failed to build eth_manager_pool")?;
-        let eth_sender = ETHSenderConfig::from_env().context("ETHSenderConfig::from_env()")?;
+        let eth_sender =
+            ETHSenderConfig::from_env_vars(env_vars).context("ETHSenderConfig::from_env_vars()")?;
         let eth_client =
             PKSigningClient::from_config(&eth_sender, &contracts_config, &eth_client_config);
         let eth_tx_manager_actor = EthTxManager::new(
@@ -567,13 +897,19 @@ pub async fn initialize_components(
             eth_tx_manager_actor.run(eth_manager_pool, stop_receiver.clone()),
         )]);
         tracing::info!("initialized ETH-TxManager in {:?}", started_at.elapsed());
-        metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "eth_tx_aggregator");
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "eth_tx_aggregator")],
+        );
     }
 
     if components.contains(&Component::DataFetcher) {
         let started_at = Instant::now();
-        let fetcher_config = FetcherConfig::from_env().context("FetcherConfig::from_env()")?;
-        let eth_network = chain::NetworkConfig::from_env().context("NetworkConfig::from_env()")?;
+        let fetcher_config =
+            FetcherConfig::from_env_vars(env_vars).context("FetcherConfig::from_env_vars()")?;
+        let eth_network = chain::NetworkConfig::from_env_vars(env_vars)
+            .context("NetworkConfig::from_env_vars()")?;
         tracing::info!("initializing data fetchers");
         task_futures.extend(run_data_fetchers(
             &fetcher_config,
@@ -582,10 +918,16 @@ pub async fn initialize_components(
             stop_receiver.clone(),
         ));
         tracing::info!("initialized data fetchers in {:?}", started_at.elapsed());
-        metrics::gauge!("server.init.latency", started_at.elapsed(), "stage" => "data_fetchers");
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "data_fetchers")],
+        );
     }
 
     add_trees_to_task_futures(
+        env_vars,
+        chain,
         &mut task_futures,
         &mut healthchecks,
         &components,
@@ -606,27 +948,242 @@ pub async fn initialize_components(
     .context("add_witness_generator_to_task_futures()")?;
 
     if components.contains(&Component::Housekeeper) {
-        add_house_keeper_to_task_futures(&mut task_futures, &store_factory)
+        add_house_keeper_to_task_futures(&db_config, &mut task_futures, &store_factory)
             .await
             .context("add_house_keeper_to_task_futures()")?;
     }
 
     if components.contains(&Component::ProofDataHandler) {
         task_futures.push(tokio::spawn(proof_data_handler::run_server(
-            ProofDataHandlerConfig::from_env().context("ProofDataHandlerConfig::from_env()")?,
+            ProofDataHandlerConfig::from_env_vars(env_vars)
+                .context("ProofDataHandlerConfig::from_env_vars()")?,
+            contracts_config.clone(),
             store_factory.create_store().await,
             connection_pool.clone(),
             stop_receiver.clone(),
         )));
     }
 
+    if components.contains(&Component::WebhookNotifier) {
+        let started_at = Instant::now();
+        tracing::info!("initializing webhook notifier");
+        let webhook_notifier_config = WebhookNotifierConfig::from_env_vars(env_vars)
+            .context("WebhookNotifierConfig::from_env_vars()")?;
+        let webhook_notifier =
+            WebhookNotifier::new(&webhook_notifier_config, connection_pool.clone())
+                .context("failed initializing webhook notifier")?;
+        task_futures.push(tokio::spawn(webhook_notifier.run(stop_receiver.clone())));
+        tracing::info!("initialized webhook notifier in {:?}", started_at.elapsed());
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "webhook_notifier")],
+        );
+    }
+
+    if components.contains(&Component::StreamPublisher) {
+        let started_at = Instant::now();
+        tracing::info!("initializing stream publisher");
+        let stream_publisher_config = StreamPublisherConfig::from_env_vars(env_vars)
+            .context("StreamPublisherConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
+        let sink = stream_publisher::connect_sink(&stream_publisher_config)
+            .await
+            .context("failed connecting to stream publisher backend")?;
+        let stream_publisher_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .context("failed to build stream_publisher_pool")?;
+        let stream_publisher = StreamPublisher::new(
+            &stream_publisher_config,
+            sink,
+            stream_publisher_pool,
+            L2ChainId(network_config.zksync_network_id),
+        )
+        .with_sealed_miniblocks(sealed_miniblock_receiver);
+        task_futures.push(tokio::spawn(stream_publisher.run(stop_receiver.clone())));
+        tracing::info!("initialized stream publisher in {:?}", started_at.elapsed());
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "stream_publisher")],
+        );
+    }
+
+    if components.contains(&Component::WithdrawalFinalizer) {
+        let started_at = Instant::now();
+        tracing::info!("initializing withdrawal finalizer");
+        let finalizer_config = WithdrawalFinalizerConfig::from_env_vars(env_vars)
+            .context("WithdrawalFinalizerConfig::from_env_vars()")?;
+        let private_key = finalizer_config
+            .private_key()?
+            .context("Private key is required for withdrawal finalizer")?;
+        let eth_client = PKSigningClient::from_private_key(
+            private_key,
+            finalizer_config.priority_fee_per_gas.into(),
+            &contracts_config,
+            &eth_client_config,
+        );
+        let finalizer_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .context("failed to build withdrawal_finalizer_pool")?;
+        let withdrawal_finalizer = WithdrawalFinalizer::new(
+            &finalizer_config,
+            &contracts_config,
+            finalizer_pool,
+            eth_client,
+        );
+        task_futures.push(tokio::spawn(
+            withdrawal_finalizer.run(stop_receiver.clone()),
+        ));
+        tracing::info!(
+            "initialized withdrawal finalizer in {:?}",
+            started_at.elapsed()
+        );
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "withdrawal_finalizer")],
+        );
+    }
+
+    if components.contains(&Component::TokenRegistry) {
+        let started_at = Instant::now();
+        tracing::info!("initializing token registry");
+        let state_keeper_config = StateKeeperConfig::from_env_vars(env_vars)
+            .context("StateKeeperConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
+        let token_registry_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .context("failed to build token_registry_pool")?;
+        let token_registry = TokenRegistry::new(
+            &token_registry_config,
+            token_registry_pool,
+            state_keeper_config.fair_l2_gas_price,
+            L2ChainId(network_config.zksync_network_id),
+        );
+        task_futures.push(tokio::spawn(token_registry.run(stop_receiver.clone())));
+        tracing::info!("initialized token registry in {:?}", started_at.elapsed());
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "token_registry")],
+        );
+    }
+
+    if components.contains(&Component::BridgeIndexer) {
+        let started_at = Instant::now();
+        tracing::info!("initializing bridge indexer");
+        let bridge_indexer_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .context("failed to build bridge_indexer_pool")?;
+        let bridge_indexer =
+            BridgeIndexer::new(&contracts_config, bridge_indexer_pool, query_client.clone());
+        task_futures.push(tokio::spawn(bridge_indexer.run(stop_receiver.clone())));
+        tracing::info!("initialized bridge indexer in {:?}", started_at.elapsed());
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "bridge_indexer")],
+        );
+    }
+
+    if components.contains(&Component::OnlineMigrations) {
+        let started_at = Instant::now();
+        tracing::info!("initializing online migrations");
+        let online_migrations_pool = ConnectionPool::singleton(DbVariant::Master)
+            .set_db_config(&db_config)
+            .build()
+            .await
+            .context("failed to build online_migrations_pool")?;
+        task_futures.push(tokio::spawn(run_online_migrations(
+            online_migrations_pool,
+            ONLINE_MIGRATIONS,
+            db_config.online_migrations_chunk_size.into(),
+            db_config.online_migrations_pause(),
+            stop_receiver.clone(),
+        )));
+        tracing::info!(
+            "initialized online migrations in {:?}",
+            started_at.elapsed()
+        );
+        report_init_latency(
+            chain,
+            started_at,
+            vec![Label::new("stage", "online_migrations")],
+        );
+    }
+
+    if components.contains(&Component::FirehoseApi) {
+        let started_at = Instant::now();
+        tracing::info!("initializing Firehose block stream API");
+        let api_config =
+            ApiConfig::from_env_vars(env_vars).context("ApiConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
+        task_futures.push(tokio::spawn(api_server::firehose::run_server(
+            api_config.firehose,
+            replica_connection_pool.clone(),
+            L2ChainId(network_config.zksync_network_id),
+            stop_receiver.clone(),
+        )));
+        tracing::info!(
+            "initialized Firehose block stream API in {:?}",
+            started_at.elapsed()
+        );
+        report_init_latency(chain, started_at, vec![Label::new("stage", "firehose_api")]);
+    }
+
+    if components.contains(&Component::GraphqlApi) {
+        let started_at = Instant::now();
+        tracing::info!("initializing GraphQL API");
+        let api_config =
+            ApiConfig::from_env_vars(env_vars).context("ApiConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
+        task_futures.push(tokio::spawn(api_server::graphql::run_server(
+            api_config.graphql,
+            replica_connection_pool.clone(),
+            L2ChainId(network_config.zksync_network_id),
+            stop_receiver.clone(),
+        )));
+        tracing::info!("initialized GraphQL API in {:?}", started_at.elapsed());
+        report_init_latency(chain, started_at, vec![Label::new("stage", "graphql_api")]);
+    }
+
+    if components.contains(&Component::RosettaApi) {
+        let started_at = Instant::now();
+        tracing::info!("initializing Rosetta API");
+        let api_config =
+            ApiConfig::from_env_vars(env_vars).context("ApiConfig::from_env_vars()")?;
+        let network_config =
+            NetworkConfig::from_env_vars(env_vars).context("NetworkConfig::from_env_vars()")?;
+        task_futures.push(tokio::spawn(api_server::rosetta::run_server(
+            api_config.rosetta,
+            replica_connection_pool.clone(),
+            L2ChainId(network_config.zksync_network_id),
+            stop_receiver.clone(),
+        )));
+        tracing::info!("initialized Rosetta API in {:?}", started_at.elapsed());
+        report_init_latency(chain, started_at, vec![Label::new("stage", "rosetta_api")]);
+    }
+
     // Run healthcheck server for all components.
     healthchecks.push(Box::new(ConnectionPoolHealthCheck::new(
         replica_connection_pool,
     )));
 
     let healtcheck_api_config =
-        HealthCheckConfig::from_env().context("HealthCheckConfig::from_env()")?;
+        HealthCheckConfig::from_env_vars(env_vars).context("HealthCheckConfig::from_env_vars()")?;
     let health_check_handle =
         HealthCheckHandle::spawn_server(healtcheck_api_config.bind_addr(), healthchecks);
 
@@ -644,11 +1201,17 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     network_config: &NetworkConfig,
     db_config: &DBConfig,
     mempool_config: &MempoolConfig,
+    object_store_config: &ObjectStoreConfig,
     gas_adjuster: Arc<E>,
+    tx_execution_hints: Option<TxExecutionHints>,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode: Option<&DevModeHandle>,
+    sealed_miniblock_sender: watch::Sender<MiniblockNumber>,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
     let fair_l2_gas_price = state_keeper_config.fair_l2_gas_price;
-    let pool_builder = ConnectionPool::singleton(DbVariant::Master);
+    let mut pool_builder = ConnectionPool::singleton(DbVariant::Master);
+    pool_builder.set_db_config(db_config);
     let state_keeper_pool = pool_builder
         .build()
         .await
@@ -663,15 +1226,58 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
     let mempool = MempoolGuard::new(next_priority_id, mempool_config.capacity);
     tokio::task::spawn(mempool.run_metrics_reporting());
 
-    let miniblock_sealer_pool = pool_builder
+    let (miniblock_sealer, miniblock_sealer_handle) =
+        if state_keeper_config.parallel_miniblock_sealing {
+            let miniblock_sealer_pool = ConnectionPool::builder(DbVariant::Master)
+                .set_db_config(db_config)
+                .set_max_size(Some(MiniblockSealer::PARALLEL_POOL_SIZE))
+                .build()
+                .await
+                .context("failed to build miniblock_sealer_pool")?;
+            MiniblockSealer::parallel(
+                miniblock_sealer_pool,
+                state_keeper_config.miniblock_seal_queue_capacity,
+            )
+        } else {
+            let miniblock_sealer_pool = pool_builder
+                .build()
+                .await
+                .context("failed to build miniblock_sealer_pool")?;
+            MiniblockSealer::new(
+                miniblock_sealer_pool,
+                state_keeper_config.miniblock_seal_queue_capacity,
+            )
+        };
+    let miniblock_sealer = miniblock_sealer.with_sealed_miniblock_sender(sealed_miniblock_sender);
+    task_futures.push(tokio::spawn(miniblock_sealer.run()));
+
+    let protective_reads_writer_pool = pool_builder
         .build()
         .await
-        .context("failed to build miniblock_sealer_pool")?;
-    let (miniblock_sealer, miniblock_sealer_handle) = MiniblockSealer::new(
-        miniblock_sealer_pool,
-        state_keeper_config.miniblock_seal_queue_capacity,
-    );
-    task_futures.push(tokio::spawn(miniblock_sealer.run()));
+        .context("failed to build protective_reads_writer_pool")?;
+    let (protective_reads_writer, protective_reads_writer_handle) =
+        ProtectiveReadsWriter::new(protective_reads_writer_pool);
+    task_futures.push(tokio::spawn(protective_reads_writer.run()));
+
+    let lag_limits = PipelineLagLimits::new(&state_keeper_config);
+    let backpressure = if lag_limits.is_enabled() {
+        let backpressure_pool = pool_builder
+            .build()
+            .await
+            .context("failed to build backpressure_pool")?;
+        let (monitor, handle) = BackpressureMonitor::new(backpressure_pool, lag_limits);
+        task_futures.push(tokio::spawn(monitor.run(stop_receiver.clone())));
+        Some(handle)
+    } else {
+        None
+    };
+
+    let batch_executor_thread_pool = build_vm_thread_pool(
+        "state_keeper",
+        state_keeper_config.vm_thread_pool_size,
+        state_keeper_config.vm_thread_pool_pinned_cores.as_deref(),
+    )
+    .context("failed to build batch_executor_thread_pool")?;
 
     let state_keeper = create_state_keeper(
         contracts_config,
@@ -679,13 +1285,21 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
         db_config,
         network_config,
         mempool_config,
+        object_store_config,
         state_keeper_pool,
         mempool.clone(),
         gas_adjuster.clone(),
         miniblock_sealer_handle,
+        protective_reads_writer_handle,
+        batch_executor_thread_pool,
+        tx_execution_hints,
+        sealing_status,
+        dev_mode,
+        backpressure,
         stop_receiver.clone(),
     )
-    .await;
+    .await
+    .context("failed initializing state keeper")?;
     task_futures.push(tokio::spawn(state_keeper.run()));
 
     let mempool_fetcher_pool = pool_builder
@@ -705,6 +1319,8 @@ async fn add_state_keeper_to_task_futures<E: L1GasPriceProvider + Send + Sync +
 }
 
 async fn add_trees_to_task_futures(
+    env_vars: &EnvVars,
+    chain: Option<&str>,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
     healthchecks: &mut Vec<Box<dyn CheckHealth>>,
     components: &[Component],
@@ -715,9 +1331,9 @@ async fn add_trees_to_task_futures(
         anyhow::bail!("Tree backup mode is disabled");
     }
 
-    let db_config = DBConfig::from_env().context("DBConfig::from_env()")?;
-    let operation_config =
-        OperationsManagerConfig::from_env().context("OperationManagerConfig::from_env()")?;
+    let db_config = DBConfig::from_env_vars(env_vars).context("DBConfig::from_env_vars()")?;
+    let operation_config = OperationsManagerConfig::from_env_vars(env_vars)
+        .context("OperationManagerConfig::from_env_vars()")?;
     let has_tree_component = components.contains(&Component::Tree);
     let has_lightweight_component = components.contains(&Component::TreeLightweight);
     let mode = match (has_tree_component, has_lightweight_component) {
@@ -733,18 +1349,38 @@ async fn add_trees_to_task_futures(
         },
         (false, false) => return Ok(()),
     };
-    let (future, tree_health_check) = run_tree(&db_config, &operation_config, mode, stop_receiver)
-        .await
-        .context("run_tree()")?;
+    let state_keeper_config =
+        StateKeeperConfig::from_env_vars(env_vars).context("StateKeeperConfig::from_env_vars()")?;
+    let pubdata_da_mode = PubdataDaMode::new(
+        state_keeper_config.l1_batch_commitment_mode,
+        state_keeper_config.pubdata_sending_mode,
+    );
+    let commitment_scheme_config = CommitmentSchemeConfig::from_env_vars(env_vars)
+        .context("CommitmentSchemeConfig::from_env_vars()")?;
+    let commitment_schemes = load_commitment_schemes(&commitment_scheme_config)?;
+    let (future, tree_health_check) = run_tree(
+        chain,
+        &db_config,
+        &operation_config,
+        mode,
+        pubdata_da_mode,
+        commitment_schemes,
+        stop_receiver,
+    )
+    .await
+    .context("run_tree()")?;
     task_futures.push(future);
     healthchecks.push(Box::new(tree_health_check));
     Ok(())
 }
 
 async fn run_tree(
-    config: &DBConfig,
+    chain: Option<&str>,
+    db_config: &DBConfig,
     operation_manager: &OperationsManagerConfig,
     mode: MetadataCalculatorModeConfig<'_>,
+    pubdata_da_mode: PubdataDaMode,
+    commitment_schemes: CommitmentSchemes,
     stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<(JoinHandle<anyhow::Result<()>>, ReactiveHealthCheck)> {
     let started_at = Instant::now();
@@ -755,25 +1391,32 @@ async fn run_tree(
     };
     tracing::info!("Initializing Merkle tree in {mode_str} mode");
 
-    let config = MetadataCalculatorConfig::for_main_node(config, operation_manager, mode);
+    let config = MetadataCalculatorConfig::for_main_node(
+        db_config,
+        operation_manager,
+        mode,
+        pubdata_da_mode,
+        commitment_schemes,
+    );
     let metadata_calculator = MetadataCalculator::new(&config).await;
     let tree_health_check = metadata_calculator.tree_health_check();
     let pool = ConnectionPool::singleton(DbVariant::Master)
+        .set_db_config(db_config)
         .build()
         .await
         .context("failed to build connection pool")?;
     let prover_pool = ConnectionPool::singleton(DbVariant::Prover)
+        .set_db_config(db_config)
         .build()
         .await
         .context("failed to build prover_pool")?;
     let future = tokio::spawn(metadata_calculator.run(pool, prover_pool, stop_receiver));
 
     tracing::info!("Initialized {mode_str} tree in {:?}", started_at.elapsed());
-    metrics::gauge!(
-        "server.init.latency",
-        started_at.elapsed(),
-        "stage" => "tree",
-        "tree" => mode_str
+    report_init_latency(
+        chain,
+        started_at,
+        vec![Label::new("stage", "tree"), Label::new("tree", mode_str)],
     );
     Ok((future, tree_health_check))
 }
@@ -877,12 +1520,14 @@ async fn add_witness_generator_to_task_futures(
 }
 
 async fn add_house_keeper_to_task_futures(
+    db_config: &DBConfig,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
     store_factory: &ObjectStoreFactory,
 ) -> anyhow::Result<()> {
     let house_keeper_config =
         HouseKeeperConfig::from_env().context("HouseKeeperConfig::from_env()")?;
     let connection_pool = ConnectionPool::singleton(DbVariant::Replica)
+        .set_db_config(db_config)
         .build()
         .await
         .context("failed to build a connection pool")?;
@@ -892,6 +1537,7 @@ async fn add_house_keeper_to_task_futures(
     );
 
     let prover_connection_pool = ConnectionPool::builder(DbVariant::Prover)
+        .set_db_config(db_config)
         .set_max_size(Some(house_keeper_config.prover_db_pool_size))
         .build()
         .await
@@ -1002,10 +1648,13 @@ async fn add_house_keeper_to_task_futures(
 }
 
 fn build_storage_caches(
+    env_vars: &EnvVars,
     replica_connection_pool: &ConnectionPool,
+    dev_mode: Option<&DevModeHandle>,
     task_futures: &mut Vec<JoinHandle<anyhow::Result<()>>>,
 ) -> anyhow::Result<PostgresStorageCaches> {
-    let rpc_config = Web3JsonRpcConfig::from_env().context("Web3JsonRpcConfig::from_env()")?;
+    let rpc_config =
+        Web3JsonRpcConfig::from_env_vars(env_vars).context("Web3JsonRpcConfig::from_env_vars()")?;
     let factory_deps_capacity = rpc_config.factory_deps_cache_size() as u64;
     let initial_writes_capacity = rpc_config.initial_writes_cache_size() as u64;
     let values_capacity = rpc_config.latest_values_cache_size() as u64;
@@ -1020,9 +1669,59 @@ fn build_storage_caches(
         );
         task_futures.push(tokio::task::spawn_blocking(values_cache_task));
     }
+
+    if let Some(secondary_path) = &rpc_config.state_keeper_secondary_db_path {
+        let db_config = DBConfig::from_env_vars(env_vars).context("DBConfig::from_env_vars()")?;
+        let primary_path = Path::new(&db_config.state_keeper_db_path);
+        if primary_path.exists() {
+            tracing::info!(
+                "Reading latest storage values from secondary RocksDB instance for state keeper cache at `{}`",
+                primary_path.display()
+            );
+            let secondary = RocksdbSecondaryStorage::open(primary_path, Path::new(secondary_path));
+            let secondary_task = storage_caches.configure_secondary_rocksdb(
+                secondary,
+                replica_connection_pool.clone(),
+                tokio::runtime::Handle::current(),
+            );
+            task_futures.push(tokio::task::spawn_blocking(secondary_task));
+        } else {
+            tracing::warn!(
+                "State keeper cache at `{}` does not exist; latest storage values will be read from Postgres",
+                primary_path.display()
+            );
+        }
+    }
+
+    if let Some(dev_mode) = dev_mode {
+        // Cached data must be invalidated after `evm_revert`.
+        dev_mode.register_storage_caches(storage_caches.clone());
+    }
     Ok(storage_caches)
 }
 
+/// Reports the initialization latency of a component. If the process runs several chains,
+/// the latency is labeled with the chain name.
+fn report_init_latency(chain: Option<&str>, started_at: Instant, mut labels: Vec<Label>) {
+    if let Some(chain) = chain {
+        labels.push(Label::new("chain", chain.to_owned()));
+    }
+    metrics::gauge!("server.init.latency", started_at.elapsed(), labels);
+}
+
+/// Builds a dedicated VM thread pool if its size is configured, or falls back to the blocking Tokio threadpool otherwise.
+fn build_vm_thread_pool(
+    name: &'static str,
+    size: Option<usize>,
+    pinned_cores: Option<&[usize]>,
+) -> anyhow::Result<VmThreadPool> {
+    Ok(match size {
+        Some(size) => VmThreadPool::new(name, size, pinned_cores.unwrap_or_default())?,
+        None => VmThreadPool::default(),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn build_tx_sender<G: L1GasPriceProvider>(
     tx_sender_config: &TxSenderConfig,
     web3_json_config: &Web3JsonRpcConfig,
@@ -1031,10 +1730,22 @@ async fn build_tx_sender<G: L1GasPriceProvider>(
     master_pool: ConnectionPool,
     l1_gas_price_provider: Arc<G>,
     storage_caches: PostgresStorageCaches,
+    vm_thread_pool: VmThreadPool,
+    tx_execution_hints: Option<TxExecutionHints>,
+    fork: Option<ForkedState>,
 ) -> (TxSender<G>, VmConcurrencyBarrier) {
     let mut tx_sender_builder = TxSenderBuilder::new(tx_sender_config.clone(), replica_pool)
         .with_main_connection_pool(master_pool)
         .with_state_keeper_config(state_keeper_config.clone());
+    if let Some(tx_execution_hints) = tx_execution_hints {
+        tx_sender_builder = tx_sender_builder.with_execution_hints(tx_execution_hints);
+    }
+    if let Some(fork) = fork {
+        tx_sender_builder = tx_sender_builder.with_fork(fork);
+    }
+    if let Some(cache_size) = web3_json_config.validation_cache_size {
+        tx_sender_builder = tx_sender_builder.with_validation_cache(cache_size);
+    }
 
     // Add rate limiter if enabled.
     if let Some(transactions_per_sec_limit) = web3_json_config.transactions_per_sec_limit {
@@ -1043,6 +1754,7 @@ async fn build_tx_sender<G: L1GasPriceProvider>(
 
     let max_concurrency = web3_json_config.vm_concurrency_limit();
     let (vm_concurrency_limiter, vm_barrier) = VmConcurrencyLimiter::new(max_concurrency);
+    let vm_concurrency_limiter = vm_concurrency_limiter.with_thread_pool(vm_thread_pool);
 
     let tx_sender = tx_sender_builder
         .build(
@@ -1057,6 +1769,7 @@ async fn build_tx_sender<G: L1GasPriceProvider>(
 
 #[allow(clippy::too_many_arguments)]
 async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
+    db_config: &DBConfig,
     tx_sender_config: &TxSenderConfig,
     state_keeper_config: &StateKeeperConfig,
     internal_api: &InternalApiConfig,
@@ -1068,24 +1781,41 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     with_debug_namespace: bool,
     with_logs_request_translator_enabled: bool,
     storage_caches: PostgresStorageCaches,
+    tx_execution_hints: Option<TxExecutionHints>,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode: Option<DevModeHandle>,
 ) -> anyhow::Result<(Vec<JoinHandle<anyhow::Result<()>>>, ReactiveHealthCheck)> {
+    let web3_config = &api_config.web3_json_rpc;
+    let vm_thread_pool = build_vm_thread_pool(
+        "api_http",
+        web3_config.vm_thread_pool_size,
+        web3_config.vm_thread_pool_pinned_cores.as_deref(),
+    )
+    .context("failed to build VM thread pool for HTTP API")?;
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
-        &api_config.web3_json_rpc,
+        web3_config,
         state_keeper_config,
         replica_connection_pool.clone(),
         master_connection_pool,
         gas_adjuster,
         storage_caches,
+        vm_thread_pool,
+        tx_execution_hints,
+        dev_mode.as_ref().and_then(DevModeHandle::fork),
     )
     .await;
 
-    let namespaces = if with_debug_namespace {
+    let mut namespaces = if with_debug_namespace {
         Namespace::ALL.to_vec()
     } else {
         Namespace::NON_DEBUG.to_vec()
     };
+    if dev_mode.is_some() {
+        namespaces.extend([Namespace::Hardhat, Namespace::Evm]);
+    }
     let last_miniblock_pool = ConnectionPool::singleton(DbVariant::Replica)
+        .set_db_config(db_config)
         .build()
         .await
         .context("failed to build last_miniblock_pool")?;
@@ -1103,11 +1833,21 @@ async fn run_http_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     if with_logs_request_translator_enabled {
         api_builder = api_builder.enable_request_translator();
     }
+    if api_config.web3_json_rpc.strict_geth_compatibility {
+        api_builder = api_builder.enable_strict_geth_compatibility();
+    }
+    if let Some(sealing_status) = sealing_status {
+        api_builder = api_builder.with_sealing_status(sealing_status);
+    }
+    if let Some(dev_mode) = dev_mode {
+        api_builder = api_builder.with_dev_mode(dev_mode);
+    }
     Ok(api_builder.build(stop_receiver.clone()).await)
 }
 
 #[allow(clippy::too_many_arguments)]
 async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
+    db_config: &DBConfig,
     tx_sender_config: &TxSenderConfig,
     state_keeper_config: &StateKeeperConfig,
     internal_api: &InternalApiConfig,
@@ -1118,22 +1858,40 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
     stop_receiver: watch::Receiver<bool>,
     storage_caches: PostgresStorageCaches,
     with_logs_request_translator_enabled: bool,
+    tx_execution_hints: Option<TxExecutionHints>,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode: Option<DevModeHandle>,
 ) -> anyhow::Result<(Vec<JoinHandle<anyhow::Result<()>>>, ReactiveHealthCheck)> {
+    let web3_config = &api_config.web3_json_rpc;
+    let vm_thread_pool = build_vm_thread_pool(
+        "api_ws",
+        web3_config.vm_thread_pool_size,
+        web3_config.vm_thread_pool_pinned_cores.as_deref(),
+    )
+    .context("failed to build VM thread pool for WS API")?;
     let (tx_sender, vm_barrier) = build_tx_sender(
         tx_sender_config,
-        &api_config.web3_json_rpc,
+        web3_config,
         state_keeper_config,
         replica_connection_pool.clone(),
         master_connection_pool,
         gas_adjuster,
         storage_caches,
+        vm_thread_pool,
+        tx_execution_hints,
+        dev_mode.as_ref().and_then(DevModeHandle::fork),
     )
     .await;
     let last_miniblock_pool = ConnectionPool::singleton(DbVariant::Replica)
+        .set_db_config(db_config)
         .build()
         .await
         .context("failed to build last_miniblock_pool")?;
 
+    let mut namespaces = Namespace::NON_DEBUG.to_vec();
+    if dev_mode.is_some() {
+        namespaces.extend([Namespace::Hardhat, Namespace::Evm]);
+    }
     let mut api_builder =
         web3::ApiBuilder::jsonrpc_backend(internal_api.clone(), replica_connection_pool)
             .ws(api_config.web3_json_rpc.ws_port)
@@ -1150,15 +1908,25 @@ async fn run_ws_api<G: L1GasPriceProvider + Send + Sync + 'static>(
             .with_polling_interval(api_config.web3_json_rpc.pubsub_interval())
             .with_threads(api_config.web3_json_rpc.ws_server_threads())
             .with_tx_sender(tx_sender, vm_barrier)
-            .enable_api_namespaces(Namespace::NON_DEBUG.to_vec());
+            .enable_api_namespaces(namespaces);
 
     if with_logs_request_translator_enabled {
         api_builder = api_builder.enable_request_translator();
     }
+    if api_config.web3_json_rpc.strict_geth_compatibility {
+        api_builder = api_builder.enable_strict_geth_compatibility();
+    }
+    if let Some(sealing_status) = sealing_status {
+        api_builder = api_builder.with_sealing_status(sealing_status);
+    }
+    if let Some(dev_mode) = dev_mode {
+        api_builder = api_builder.with_dev_mode(dev_mode);
+    }
     Ok(api_builder.build(stop_receiver.clone()).await)
 }
 
 async fn circuit_breakers_for_components(
+    db_config: &DBConfig,
     components: &[Component],
     web3_url: &str,
     circuit_breaker_config: &CircuitBreakerConfig,
@@ -1173,6 +1941,7 @@ async fn circuit_breakers_for_components(
         )
     }) {
         let pool = ConnectionPool::singleton(DbVariant::Replica)
+            .set_db_config(db_config)
             .build()
             .await
             .context("failed to build a connection pool")?;
@@ -1201,6 +1970,7 @@ async fn circuit_breakers_for_components(
         )
     }) {
         let pool = ConnectionPool::singleton(DbVariant::Replica)
+            .set_db_config(db_config)
             .build()
             .await?;
         circuit_breakers.push(Box::new(ReplicationLagChecker {
@@ -278,9 +278,9 @@ mod tests {
     use zksync_contracts::BaseSystemContracts;
     use zksync_dal::ConnectionPool;
     use zksync_types::{
-        proofs::PrepareBasicCircuitsJob, protocol_version::L1VerifierConfig,
-        system_contracts::get_system_smart_contracts, Address, L2ChainId, ProtocolVersionId,
-        StorageKey, StorageLogKind,
+        commitment::CommitmentSchemes, proofs::PrepareBasicCircuitsJob,
+        protocol_version::L1VerifierConfig, system_contracts::get_system_smart_contracts, Address,
+        L1BatchCommitmentMode, L2ChainId, ProtocolVersionId, StorageKey, StorageLogKind,
     };
 
     use super::*;
@@ -359,6 +359,8 @@ mod tests {
             system_contracts: get_system_smart_contracts(),
             first_l1_verifier_config: L1VerifierConfig::default(),
             first_verifier_address: Address::zero(),
+            commitment_schemes: CommitmentSchemes::default(),
+            commitment_mode: L1BatchCommitmentMode::Rollup,
         }
     }
 
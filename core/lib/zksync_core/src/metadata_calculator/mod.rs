@@ -15,13 +15,14 @@ use zksync_merkle_tree::domain::TreeMetadata;
 use zksync_object_store::ObjectStoreFactory;
 use zksync_types::{
     block::L1BatchHeader,
-    commitment::{L1BatchCommitment, L1BatchMetadata},
+    commitment::{CommitmentSchemes, L1BatchCommitment, L1BatchMetadata},
+    L1BatchCommitmentMode,
 };
 
 mod helpers;
 mod metrics;
 #[cfg(test)]
-mod tests;
+pub(crate) mod tests;
 mod updater;
 
 pub(crate) use self::helpers::L1BatchWithLogs;
@@ -30,7 +31,7 @@ use self::{
     metrics::{ReportStage, TreeUpdateStage},
     updater::TreeUpdater,
 };
-use crate::gas_tracker::commit_gas_count_for_l1_batch;
+use crate::gas_tracker::{commit_gas_count_for_l1_batch, PubdataDaMode};
 
 /// Part of [`MetadataCalculator`] related to the operation mode of the Merkle tree.
 #[derive(Debug, Clone, Copy)]
@@ -71,6 +72,10 @@ pub struct MetadataCalculatorConfig<'a> {
     pub multi_get_chunk_size: usize,
     /// Capacity of RocksDB block cache in bytes. Reasonable values range from ~100 MB to several GB.
     pub block_cache_capacity: usize,
+    /// DA mode of the chain used to re-estimate L1 gas for committing L1 batches.
+    pub pubdata_da_mode: PubdataDaMode,
+    /// Schemes used to hash L1 batch commitments.
+    pub commitment_schemes: CommitmentSchemes,
 }
 
 impl<'a> MetadataCalculatorConfig<'a> {
@@ -78,6 +83,8 @@ impl<'a> MetadataCalculatorConfig<'a> {
         db_config: &'a DBConfig,
         operation_config: &'a OperationsManagerConfig,
         mode: MetadataCalculatorModeConfig<'a>,
+        pubdata_da_mode: PubdataDaMode,
+        commitment_schemes: CommitmentSchemes,
     ) -> Self {
         Self {
             db_path: &db_config.merkle_tree.path,
@@ -86,6 +93,8 @@ impl<'a> MetadataCalculatorConfig<'a> {
             max_l1_batches_per_iter: db_config.merkle_tree.max_l1_batches_per_iter,
             multi_get_chunk_size: db_config.merkle_tree.multi_get_chunk_size,
             block_cache_capacity: db_config.merkle_tree.block_cache_size(),
+            pubdata_da_mode,
+            commitment_schemes,
         }
     }
 }
@@ -147,6 +156,7 @@ impl MetadataCalculator {
         storage: &mut StorageProcessor<'_>,
         header: &L1BatchHeader,
         metadata: &L1BatchMetadata,
+        da_mode: PubdataDaMode,
     ) {
         let reestimate_gas_cost = TreeUpdateStage::ReestimateGasCost.start();
         let unsorted_factory_deps = storage
@@ -155,7 +165,7 @@ impl MetadataCalculator {
             .await
             .unwrap();
         let commit_gas_cost =
-            commit_gas_count_for_l1_batch(header, &unsorted_factory_deps, metadata);
+            commit_gas_count_for_l1_batch(header, &unsorted_factory_deps, metadata, da_mode);
         storage
             .blocks_dal()
             .update_predicted_l1_batch_commit_gas(header.number, commit_gas_cost)
@@ -164,9 +174,14 @@ impl MetadataCalculator {
         reestimate_gas_cost.report();
     }
 
-    fn build_l1_batch_metadata(
+    /// Builds metadata for an L1 batch. `compressed_state_diffs` must be provided for L1 batches
+    /// publishing state diffs in the V2 format (see [`StateDiffCompressionVersion`](zksync_types::state_diffs::StateDiffCompressionVersion)).
+    pub(crate) fn build_l1_batch_metadata(
         tree_metadata: TreeMetadata,
         header: &L1BatchHeader,
+        commitment_schemes: &CommitmentSchemes,
+        commitment_mode: L1BatchCommitmentMode,
+        compressed_state_diffs: Option<Vec<u8>>,
     ) -> L1BatchMetadata {
         let merkle_root_hash = tree_metadata.root_hash;
 
@@ -178,8 +193,14 @@ impl MetadataCalculator {
             tree_metadata.repeated_writes,
             header.base_system_contracts_hashes.bootloader,
             header.base_system_contracts_hashes.default_aa,
+            commitment_mode,
         );
-        let commitment_hash = commitment.hash();
+        let commitment = match compressed_state_diffs {
+            Some(compressed) => commitment.with_compressed_state_diffs(compressed, commitment_mode),
+            None => commitment,
+        };
+        let commitment_scheme = commitment_schemes.for_protocol_version(header.protocol_version);
+        let commitment_hash = commitment.hash_with_scheme(commitment_scheme);
         tracing::trace!("L1 batch commitment: {commitment:?}");
 
         let metadata = L1BatchMetadata {
@@ -4,7 +4,7 @@ use itertools::Itertools;
 use tempfile::TempDir;
 use tokio::sync::{mpsc, watch};
 
-use std::{future::Future, ops, panic, path::Path, time::Duration};
+use std::{collections::HashMap, future::Future, ops, panic, path::Path, time::Duration};
 
 use zksync_config::{configs::chain::OperationsManagerConfig, DBConfig};
 use zksync_contracts::BaseSystemContracts;
@@ -14,18 +14,23 @@ use zksync_merkle_tree::domain::ZkSyncTree;
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_types::{
     block::{miniblock_hash, BlockGasCount, L1BatchHeader, MiniblockHeader},
+    commitment::CommitmentSchemes,
     proofs::PrepareBasicCircuitsJob,
     protocol_version::L1VerifierConfig,
+    state_diffs::{self, StateDiffCompressionVersion},
     system_contracts::get_system_smart_contracts,
-    AccountTreeId, Address, L1BatchNumber, L2ChainId, MiniblockNumber, ProtocolVersionId,
-    StorageKey, StorageLog, H256,
+    AccountTreeId, Address, L1BatchCommitmentMode, L1BatchNumber, L2ChainId, MiniblockNumber,
+    ProtocolVersionId, StorageKey, StorageLog, H256, U256,
 };
 use zksync_utils::u32_to_h256;
 
 use super::{
     L1BatchWithLogs, MetadataCalculator, MetadataCalculatorConfig, MetadataCalculatorModeConfig,
 };
-use crate::genesis::{ensure_genesis_state, GenesisParams};
+use crate::{
+    gas_tracker::PubdataDaMode,
+    genesis::{ensure_genesis_state, GenesisParams},
+};
 
 const RUN_TIMEOUT: Duration = Duration::from_secs(15);
 
@@ -179,6 +184,74 @@ async fn multi_l1_batch_workflow(pool: ConnectionPool, prover_pool: ConnectionPo
     }
 }
 
+#[db_test]
+async fn state_diffs_format_is_gated_by_protocol_version(
+    pool: ConnectionPool,
+    prover_pool: ConnectionPool,
+) {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let (calculator, _) = setup_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 1).await;
+    // Update some of the slots written in the first L1 batch in an L1 batch using the V2 state diffs format.
+    let logs = gen_storage_logs(0..100, 1).pop().unwrap();
+    let updated_logs: Vec<_> = logs[1..11]
+        .iter()
+        .map(|log| StorageLog::new_write_log(log.key, H256::from_low_u64_be(1_000)))
+        .collect();
+    let mut storage = pool.access_storage().await.unwrap();
+    extend_db_state_with_protocol_version(
+        &mut storage,
+        [updated_logs.clone()],
+        StateDiffCompressionVersion::V2_SINCE,
+    )
+    .await;
+    run_calculator(calculator, pool.clone(), prover_pool).await;
+
+    let v1_metadata = storage
+        .blocks_dal()
+        .get_l1_batch_metadata(L1BatchNumber(1))
+        .await
+        .unwrap()
+        .unwrap()
+        .metadata;
+    let initial_writes_count = &v1_metadata.initial_writes_compressed[..4];
+    assert_eq!(
+        u32::from_be_bytes(initial_writes_count.try_into().unwrap()),
+        99
+    );
+
+    let v2_metadata = storage
+        .blocks_dal()
+        .get_l1_batch_metadata(L1BatchNumber(2))
+        .await
+        .unwrap()
+        .unwrap()
+        .metadata;
+    assert!(v2_metadata.repeated_writes_compressed.is_empty());
+    let state_diffs = state_diffs::decompress_v2(&v2_metadata.initial_writes_compressed).unwrap();
+    assert!(state_diffs.initial_writes.is_empty());
+    assert_eq!(state_diffs.repeated_writes.len(), updated_logs.len());
+
+    let indices: Vec<_> = state_diffs
+        .repeated_writes
+        .iter()
+        .map(|(index, _)| *index)
+        .collect();
+    let hashed_keys = storage
+        .storage_logs_dedup_dal()
+        .get_hashed_keys_for_enumeration_indices(&indices)
+        .await;
+    let previous_values: HashMap<_, _> = logs
+        .iter()
+        .map(|log| (log.key.hashed_key(), log.value))
+        .collect();
+    for (index, value) in &state_diffs.repeated_writes {
+        let previous_value = previous_values[&hashed_keys[index]];
+        let previous_value = U256::from_big_endian(previous_value.as_bytes());
+        assert_eq!(value.apply(previous_value), Some(U256::from(1_000)));
+    }
+}
+
 #[db_test]
 async fn running_metadata_calculator_with_additional_blocks(
     pool: ConnectionPool,
@@ -231,6 +304,56 @@ async fn running_metadata_calculator_with_additional_blocks(
     assert_eq!(root_hash_for_full_tree, updated_root_hash);
 }
 
+#[db_test]
+async fn waiting_for_pending_protective_reads(pool: ConnectionPool, prover_pool: ConnectionPool) {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let mut calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    pool.access_storage()
+        .await
+        .unwrap()
+        .blocks_dal()
+        .mark_protective_reads_as_pending(L1BatchNumber(3), &[])
+        .await
+        .unwrap();
+
+    let (stop_sx, stop_rx) = watch::channel(false);
+    let (delay_sx, mut delay_rx) = mpsc::unbounded_channel();
+    calculator.delayer.delay_notifier = delay_sx;
+    let calculator_handle =
+        tokio::spawn(calculator.run(pool.clone(), prover_pool.clone(), stop_rx));
+
+    // The calculator should stop before the L1 batch with pending protective reads.
+    let (next_l1_batch, _) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+        .await
+        .expect("metadata calculator timed out processing initial blocks")
+        .unwrap();
+    assert_eq!(next_l1_batch, L1BatchNumber(3));
+
+    pool.access_storage()
+        .await
+        .unwrap()
+        .blocks_dal()
+        .mark_protective_reads_as_persisted(L1BatchNumber(3))
+        .await
+        .unwrap();
+    loop {
+        let (next_l1_batch, _) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+            .await
+            .expect("metadata calculator shut down prematurely")
+            .unwrap();
+        if next_l1_batch == L1BatchNumber(6) {
+            stop_sx.send(true).unwrap(); // Shut down the calculator.
+            break;
+        }
+    }
+    tokio::time::timeout(RUN_TIMEOUT, calculator_handle)
+        .await
+        .expect("timed out waiting for calculator")
+        .unwrap()
+        .unwrap();
+}
+
 #[db_test]
 async fn shutting_down_calculator(pool: ConnectionPool, prover_pool: ConnectionPool) {
     let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
@@ -356,7 +479,70 @@ async fn postgres_backup_recovery_with_excluded_metadata(
     test_postgres_backup_recovery(pool, prover_pool, false, true).await;
 }
 
-async fn setup_calculator(
+#[db_test]
+async fn rolling_back_l1_batches_while_running(pool: ConnectionPool, prover_pool: ConnectionPool) {
+    let temp_dir = TempDir::new().expect("failed get temporary directory for RocksDB");
+    let mut calculator = setup_lightweight_calculator(temp_dir.path(), &pool).await;
+    reset_db_state(&pool, 5).await;
+    let expected_root_hash = expected_tree_hash(&pool).await;
+
+    let (stop_sx, stop_rx) = watch::channel(false);
+    let (delay_sx, mut delay_rx) = mpsc::unbounded_channel();
+    calculator.delayer.delay_notifier = delay_sx;
+    let calculator_handle =
+        tokio::spawn(calculator.run(pool.clone(), prover_pool.clone(), stop_rx));
+    let (next_l1_batch, _) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+        .await
+        .expect("metadata calculator timed out processing initial blocks")
+        .unwrap();
+    assert_eq!(next_l1_batch, L1BatchNumber(6));
+
+    // Roll back L1 batches without stopping the calculator. It should truncate the tree.
+    let last_batch_after_rollback = L1BatchNumber(3);
+    let mut storage = pool.access_storage().await.unwrap();
+    let removed_batches = remove_l1_batches(&mut storage, last_batch_after_rollback).await;
+    drop(storage);
+    loop {
+        let (next_l1_batch, _) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+            .await
+            .expect("metadata calculator shut down prematurely")
+            .unwrap();
+        if next_l1_batch == last_batch_after_rollback + 1 {
+            break;
+        }
+    }
+
+    // Re-insert the removed L1 batches; the calculator should process them again.
+    let mut storage = pool.access_storage().await.unwrap();
+    for batch_header in &removed_batches {
+        storage
+            .blocks_dal()
+            .insert_l1_batch(batch_header, &[], BlockGasCount::default())
+            .await
+            .unwrap();
+        insert_initial_writes_for_batch(&mut storage, batch_header.number).await;
+    }
+    drop(storage);
+
+    let root_hash = loop {
+        let (next_l1_batch, root_hash) = tokio::time::timeout(RUN_TIMEOUT, delay_rx.recv())
+            .await
+            .expect("metadata calculator shut down prematurely")
+            .unwrap();
+        if next_l1_batch == L1BatchNumber(6) {
+            stop_sx.send(true).unwrap(); // Shut down the calculator.
+            break root_hash;
+        }
+    };
+    tokio::time::timeout(RUN_TIMEOUT, calculator_handle)
+        .await
+        .expect("timed out waiting for calculator")
+        .unwrap()
+        .unwrap();
+    assert_eq!(root_hash, expected_root_hash);
+}
+
+pub(crate) async fn setup_calculator(
     db_path: &Path,
     pool: &ConnectionPool,
 ) -> (MetadataCalculator, Box<dyn ObjectStore>) {
@@ -391,8 +577,13 @@ async fn setup_calculator_with_options(
     pool: &ConnectionPool,
     mode: MetadataCalculatorModeConfig<'_>,
 ) -> MetadataCalculator {
-    let calculator_config =
-        MetadataCalculatorConfig::for_main_node(db_config, operation_config, mode);
+    let calculator_config = MetadataCalculatorConfig::for_main_node(
+        db_config,
+        operation_config,
+        mode,
+        PubdataDaMode::Calldata,
+        CommitmentSchemes::default(),
+    );
     let metadata_calculator = MetadataCalculator::new(&calculator_config).await;
 
     let mut storage = pool.access_storage().await.unwrap();
@@ -414,6 +605,8 @@ async fn setup_calculator_with_options(
                 system_contracts,
                 first_l1_verifier_config,
                 first_verifier_address,
+                commitment_schemes: CommitmentSchemes::default(),
+                commitment_mode: L1BatchCommitmentMode::Rollup,
             },
         )
         .await
@@ -426,7 +619,7 @@ fn path_to_string(path: &Path) -> String {
     path.to_str().unwrap().to_owned()
 }
 
-async fn run_calculator(
+pub(crate) async fn run_calculator(
     mut calculator: MetadataCalculator,
     pool: ConnectionPool,
     prover_pool: ConnectionPool,
@@ -451,7 +644,7 @@ async fn run_calculator(
     delayer_handle.await.unwrap()
 }
 
-pub(super) async fn reset_db_state(pool: &ConnectionPool, num_batches: usize) {
+pub(crate) async fn reset_db_state(pool: &ConnectionPool, num_batches: usize) {
     let mut storage = pool.access_storage().await.unwrap();
     // Drops all L1 batches (except the L1 batch with number 0) and their storage logs.
     storage
@@ -476,6 +669,14 @@ pub(super) async fn reset_db_state(pool: &ConnectionPool, num_batches: usize) {
 pub(super) async fn extend_db_state(
     storage: &mut StorageProcessor<'_>,
     new_logs: impl IntoIterator<Item = Vec<StorageLog>>,
+) {
+    extend_db_state_with_protocol_version(storage, new_logs, ProtocolVersionId::default()).await;
+}
+
+async fn extend_db_state_with_protocol_version(
+    storage: &mut StorageProcessor<'_>,
+    new_logs: impl IntoIterator<Item = Vec<StorageLog>>,
+    protocol_version: ProtocolVersionId,
 ) {
     let next_l1_batch = storage
         .blocks_dal()
@@ -493,7 +694,7 @@ pub(super) async fn extend_db_state(
             0,
             Address::default(),
             base_system_contracts.hashes(),
-            Default::default(),
+            protocol_version,
         );
         header.is_finished = true;
 
@@ -513,8 +714,9 @@ pub(super) async fn extend_db_state(
             base_fee_per_gas: header.base_fee_per_gas,
             l1_gas_price: 0,
             l2_fair_gas_price: 0,
+            fair_pubdata_price: None,
             base_system_contracts_hashes: base_system_contracts.hashes(),
-            protocol_version: Some(Default::default()),
+            protocol_version: Some(protocol_version),
             virtual_blocks: 0,
         };
 
@@ -572,7 +774,7 @@ async fn insert_initial_writes_for_batch(
         .await;
 }
 
-pub(super) fn gen_storage_logs(
+pub(crate) fn gen_storage_logs(
     indices: ops::Range<u32>,
     num_batches: usize,
 ) -> Vec<Vec<StorageLog>> {
@@ -658,6 +860,8 @@ async fn deduplication_works_as_expected(pool: ConnectionPool) {
             system_contracts,
             first_l1_verifier_config,
             first_verifier_address,
+            commitment_schemes: CommitmentSchemes::default(),
+            commitment_mode: L1BatchCommitmentMode::Rollup,
         },
     )
     .await
@@ -3,20 +3,30 @@ use anyhow::Context as _;
 use futures::{future, FutureExt};
 use tokio::sync::watch;
 
-use std::{ops, time::Instant};
+use std::{collections::HashMap, ops, time::Instant};
 
 use zksync_config::configs::database::MerkleTreeMode;
 use zksync_dal::{ConnectionPool, StorageProcessor};
 use zksync_health_check::HealthUpdater;
 use zksync_merkle_tree::domain::TreeMetadata;
 use zksync_object_store::ObjectStore;
-use zksync_types::{block::L1BatchHeader, writes::InitialStorageWrite, L1BatchNumber, U256};
+use zksync_types::{
+    block::L1BatchHeader,
+    commitment::{CommitmentSchemes, SerializeCommitment},
+    state_diffs::{self, RepeatedWriteDiff, StateDiffCompressionVersion},
+    writes::{InitialStorageWrite, RepeatedStorageWrite},
+    L1BatchNumber, H256, U256,
+};
 
 use super::{
     helpers::{AsyncTree, Delayer, L1BatchWithLogs, TreeHealthCheckDetails},
     metrics::{ReportStage, TreeUpdateStage},
     MetadataCalculator, MetadataCalculatorConfig,
 };
+use crate::gas_tracker::PubdataDaMode;
+
+/// Maximum number of entries in [`TreeUpdater::hashed_keys_cache`].
+const HASHED_KEYS_CACHE_CAPACITY: usize = 100_000;
 
 #[derive(Debug)]
 pub(super) struct TreeUpdater {
@@ -24,6 +34,11 @@ pub(super) struct TreeUpdater {
     tree: AsyncTree,
     max_l1_batches_per_iter: usize,
     object_store: Option<Box<dyn ObjectStore>>,
+    pubdata_da_mode: PubdataDaMode,
+    commitment_schemes: CommitmentSchemes,
+    /// Hashed keys for enumeration indices of recently updated slots. Enumeration indices are immutable
+    /// (L1 batch reverts restart the node), and hot slots are updated in most L1 batches.
+    hashed_keys_cache: HashMap<u64, H256>,
 }
 
 impl TreeUpdater {
@@ -50,6 +65,9 @@ impl TreeUpdater {
             tree,
             max_l1_batches_per_iter: config.max_l1_batches_per_iter,
             object_store,
+            pubdata_da_mode: config.pubdata_da_mode,
+            commitment_schemes: config.commitment_schemes.clone(),
+            hashed_keys_cache: HashMap::new(),
         }
     }
 
@@ -140,10 +158,24 @@ impl TreeUpdater {
                 &metadata.initial_writes,
             )
             .await;
-            let metadata = MetadataCalculator::build_l1_batch_metadata(metadata, &header);
+            let compressed_state_diffs =
+                self.compress_state_diffs(storage, &header, &metadata).await;
+            let metadata = MetadataCalculator::build_l1_batch_metadata(
+                metadata,
+                &header,
+                &self.commitment_schemes,
+                self.pubdata_da_mode.commitment_mode(),
+                compressed_state_diffs,
+            );
             prepare_results_latency.report();
 
-            MetadataCalculator::reestimate_l1_batch_commit_gas(storage, &header, &metadata).await;
+            MetadataCalculator::reestimate_l1_batch_commit_gas(
+                storage,
+                &header,
+                &metadata,
+                self.pubdata_da_mode,
+            )
+            .await;
 
             let save_postgres_latency = TreeUpdateStage::SavePostgres.start();
             storage
@@ -210,6 +242,25 @@ impl TreeUpdater {
         mut prover_storage: StorageProcessor<'_>,
         next_l1_batch_to_seal: &mut L1BatchNumber,
     ) {
+        // L1 batches may be rolled back while the tree is running (e.g., by `evm_revert` in the dev mode).
+        // Since metadata is persisted in Postgres before the tree changes, the tree being ahead of Postgres
+        // indicates such a rollback; the tree must be truncated before processing new L1 batches with the same numbers.
+        let last_l1_batch_with_metadata = storage
+            .blocks_dal()
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .unwrap();
+        if *next_l1_batch_to_seal > last_l1_batch_with_metadata + 1 {
+            tracing::warn!(
+                "Next L1 batch of the tree ({next_l1_batch_to_seal}) is greater than last L1 batch with metadata in Postgres \
+                 ({last_l1_batch_with_metadata}); L1 batches were rolled back. Truncating Merkle tree versions..."
+            );
+            self.tree.revert_logs(last_l1_batch_with_metadata);
+            self.tree.save().await;
+            *next_l1_batch_to_seal = self.tree.next_l1_batch_number();
+            tracing::info!("Truncated Merkle tree to L1 batch #{next_l1_batch_to_seal}");
+        }
+
         let last_sealed_l1_batch = storage
             .blocks_dal()
             .get_sealed_l1_batch_number()
@@ -217,7 +268,18 @@ impl TreeUpdater {
             .unwrap();
         let last_requested_l1_batch =
             next_l1_batch_to_seal.0 + self.max_l1_batches_per_iter as u32 - 1;
-        let last_requested_l1_batch = last_requested_l1_batch.min(last_sealed_l1_batch.0);
+        let mut last_requested_l1_batch = last_requested_l1_batch.min(last_sealed_l1_batch.0);
+        // Protective reads are persisted asynchronously after an L1 batch is sealed, so we must wait for them.
+        let pending_l1_batches = storage
+            .blocks_dal()
+            .get_l1_batches_with_pending_protective_reads()
+            .await
+            .unwrap();
+        if let Some(&first_pending_l1_batch) = pending_l1_batches.first() {
+            tracing::debug!("Waiting for protective reads of L1 batches {pending_l1_batches:?}");
+            last_requested_l1_batch =
+                last_requested_l1_batch.min(first_pending_l1_batch.0.saturating_sub(1));
+        }
         let l1_batch_numbers = next_l1_batch_to_seal.0..=last_requested_l1_batch;
         if l1_batch_numbers.is_empty() {
             tracing::trace!(
@@ -385,4 +447,103 @@ impl TreeUpdater {
             "Leaf indices are not consistent for L1 batch {l1_batch_number}"
         );
     }
+
+    /// Compresses state diffs of an L1 batch if it uses the V2 state diff format; returns `None` for L1 batches
+    /// using the V1 format. Also reports sizes of the state diffs in both formats, so that the savings
+    /// of the V2 format can be monitored.
+    async fn compress_state_diffs(
+        &mut self,
+        connection: &mut StorageProcessor<'_>,
+        header: &L1BatchHeader,
+        tree_metadata: &TreeMetadata,
+    ) -> Option<Vec<u8>> {
+        let protocol_version = header.protocol_version.unwrap_or_default();
+        let version = StateDiffCompressionVersion::for_protocol_version(protocol_version);
+        let v1_size = tree_metadata.initial_writes.len() * InitialStorageWrite::SERIALIZED_SIZE
+            + tree_metadata.repeated_writes.len() * RepeatedStorageWrite::SERIALIZED_SIZE
+            + 8;
+        Self::report_state_diffs_size(StateDiffCompressionVersion::V1, version, v1_size);
+        if version == StateDiffCompressionVersion::V1 {
+            // Previous slot values are only needed for the V2 format, so they aren't loaded for V1 batches.
+            return None;
+        }
+
+        let repeated_writes = self
+            .load_repeated_write_diffs(connection, header.number, &tree_metadata.repeated_writes)
+            .await;
+        let compressed = state_diffs::compress_v2(&tree_metadata.initial_writes, &repeated_writes);
+        Self::report_state_diffs_size(StateDiffCompressionVersion::V2, version, compressed.len());
+        tracing::debug!(
+            "State diffs for L1 batch #{}: {v1_size} bytes (v1), {} bytes (v2)",
+            header.number,
+            compressed.len()
+        );
+        Some(compressed)
+    }
+
+    /// Loads values of slots before the L1 batch for its repeated writes.
+    async fn load_repeated_write_diffs(
+        &mut self,
+        connection: &mut StorageProcessor<'_>,
+        l1_batch_number: L1BatchNumber,
+        repeated_writes: &[RepeatedStorageWrite],
+    ) -> Vec<RepeatedWriteDiff> {
+        let missing_indices: Vec<_> = repeated_writes
+            .iter()
+            .map(|write| write.index)
+            .filter(|index| !self.hashed_keys_cache.contains_key(index))
+            .collect();
+        if !missing_indices.is_empty() {
+            let loaded_keys = connection
+                .storage_logs_dedup_dal()
+                .get_hashed_keys_for_enumeration_indices(&missing_indices)
+                .await;
+            if self.hashed_keys_cache.len() + loaded_keys.len() > HASHED_KEYS_CACHE_CAPACITY {
+                self.hashed_keys_cache.clear();
+            }
+            self.hashed_keys_cache.extend(loaded_keys);
+        }
+
+        let hashed_keys: Vec<_> = repeated_writes
+            .iter()
+            .map(|write| {
+                *self.hashed_keys_cache.get(&write.index).unwrap_or_else(|| {
+                    panic!(
+                        "Hashed key for enumeration index {} is missing in Postgres",
+                        write.index
+                    )
+                })
+            })
+            .collect();
+        let previous_values = connection
+            .storage_logs_dal()
+            .get_previous_storage_values(&hashed_keys, l1_batch_number)
+            .await;
+
+        repeated_writes
+            .iter()
+            .zip(&hashed_keys)
+            .map(|(write, hashed_key)| RepeatedWriteDiff {
+                write: write.clone(),
+                previous_value: previous_values
+                    .get(hashed_key)
+                    .copied()
+                    .flatten()
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn report_state_diffs_size(
+        version: StateDiffCompressionVersion,
+        active_version: StateDiffCompressionVersion,
+        size: usize,
+    ) {
+        metrics::histogram!(
+            "server.metadata_calculator.state_diffs_size",
+            size as f64,
+            "version" => version.as_str(),
+            "active" => if version == active_version { "true" } else { "false" }
+        );
+    }
 }
@@ -0,0 +1,183 @@
+//! Running several hyperchains in a single server process.
+//!
+//! Each chain is configured via the same environment variables as a standalone server. Variables can be
+//! overridden for a specific chain by prefixing them with the chain name (see [`MultiChainConfig`]); e.g.,
+//! chains must use distinct databases (or Postgres schemas set via `DATABASE_SCHEMA`), RocksDB paths,
+//! API / health check ports and chain IDs. Configs of each chain are loaded from a snapshot of
+//! the environment with the chain overrides applied, so the process environment is never modified.
+//! Chains share the L1 client, the object store bucket (objects are separated if the chain overrides
+//! `OBJECT_STORE_KEY_PREFIX`) and the Prometheus exporter.
+//!
+//! # Limitations
+//!
+//! Only initialization metrics are labeled by chain; metrics reported by components are aggregated
+//! across all chains. Witness generators and the house keeper are not supported since they read
+//! their configuration directly from the process environment.
+//!
+//! [`MultiChainConfig`]: zksync_config::configs::MultiChainConfig
+
+use anyhow::Context as _;
+use futures::{channel::oneshot, future};
+use tokio::{sync::watch, task::JoinHandle};
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+    hash::Hash,
+};
+
+use zksync_circuit_breaker::CircuitBreakerError;
+use zksync_config::{configs::chain::NetworkConfig, DBConfig, ETHClientConfig, EnvVars};
+use zksync_eth_client::clients::http::QueryClient;
+
+use crate::{
+    api_server::healthcheck::HealthCheckHandle, initialize_chain_components, ChainOptions,
+    Component,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Checks that all components can be run for several chains in a single process.
+fn check_components(components: &[Component]) -> anyhow::Result<()> {
+    let unsupported_components: Vec<_> = components
+        .iter()
+        .filter(|component| {
+            matches!(
+                component,
+                Component::WitnessGenerator(..) | Component::Housekeeper
+            )
+        })
+        .collect();
+    anyhow::ensure!(
+        unsupported_components.is_empty(),
+        "Components {unsupported_components:?} cannot be run for multiple chains"
+    );
+    Ok(())
+}
+
+/// Records that `chain` uses the specified exclusive `resource`, returning an error if it's used by another chain.
+fn record_exclusive_resource<'a, T: Eq + Hash + fmt::Debug>(
+    used_resources: &mut HashMap<T, &'a str>,
+    resource: T,
+    chain: &'a str,
+    resource_name: &str,
+) -> anyhow::Result<()> {
+    match used_resources.entry(resource) {
+        Entry::Occupied(entry) => anyhow::bail!(
+            "Chains `{}` and `{chain}` use the same {resource_name}: {:?}",
+            entry.get(),
+            entry.key()
+        ),
+        Entry::Vacant(entry) => {
+            entry.insert(chain);
+            Ok(())
+        }
+    }
+}
+
+/// Checks that chains do not share resources that must be exclusive to a single chain.
+fn check_chain_configs(
+    chain_vars: &[(&str, EnvVars)],
+    components: &[Component],
+) -> anyhow::Result<()> {
+    let runs_state_keeper = components.contains(&Component::StateKeeper);
+    let runs_tree =
+        components.contains(&Component::Tree) || components.contains(&Component::TreeLightweight);
+
+    let mut databases = HashMap::new();
+    let mut state_keeper_paths = HashMap::new();
+    let mut tree_paths = HashMap::new();
+    let mut chain_ids = HashMap::new();
+    for (chain, vars) in chain_vars {
+        let db_config = DBConfig::from_env_vars(vars)
+            .with_context(|| format!("failed loading DB config for chain `{chain}`"))?;
+        let network_config = NetworkConfig::from_env_vars(vars)
+            .with_context(|| format!("failed loading network config for chain `{chain}`"))?;
+
+        let database_url = db_config
+            .master_url()
+            .with_context(|| format!("database URL is not set for chain `{chain}`"))?;
+        let database = (database_url.to_owned(), db_config.schema.clone());
+        record_exclusive_resource(&mut databases, database, chain, "database and schema")?;
+        let chain_id = network_config.zksync_network_id;
+        record_exclusive_resource(&mut chain_ids, chain_id, chain, "L2 chain ID")?;
+        if runs_state_keeper {
+            let path = db_config.state_keeper_db_path;
+            record_exclusive_resource(&mut state_keeper_paths, path, chain, "state keeper cache")?;
+        }
+        if runs_tree {
+            let path = db_config.merkle_tree.path;
+            record_exclusive_resource(&mut tree_paths, path, chain, "Merkle tree path")?;
+        }
+    }
+    Ok(())
+}
+
+/// Initializes components for each of the specified chains. Returns task handles for all chains,
+/// a sender stopping all chains, a receiver for the first circuit breaker error across all chains,
+/// and health check handles for all chains.
+pub async fn initialize_chains(
+    chains: &[String],
+    components: Vec<Component>,
+    use_prometheus_push_gateway: bool,
+) -> anyhow::Result<(
+    Vec<JoinHandle<anyhow::Result<()>>>,
+    watch::Sender<bool>,
+    oneshot::Receiver<CircuitBreakerError>,
+    Vec<HealthCheckHandle>,
+)> {
+    anyhow::ensure!(!chains.is_empty(), "no chains specified");
+    check_components(&components)?;
+    let env_vars = EnvVars::from_env();
+    let chain_vars: Vec<_> = chains
+        .iter()
+        .map(|chain| (chain.as_str(), env_vars.for_chain(chain)))
+        .collect();
+    check_chain_configs(&chain_vars, &components)?;
+
+    let eth_client_config =
+        ETHClientConfig::from_env_vars(&env_vars).context("ETHClientConfig::from_env_vars()")?;
+    let l1_client = QueryClient::new(&eth_client_config.web3_url).context("QueryClient::new()")?;
+
+    let mut task_handles = vec![];
+    let mut stop_senders = vec![];
+    let mut cb_receivers = vec![];
+    let mut health_check_handles = vec![];
+    for (i, (chain, env_vars)) in chain_vars.into_iter().enumerate() {
+        tracing::info!("Initializing components for chain `{chain}`");
+        let options = ChainOptions {
+            l1_client: Some(l1_client.clone()),
+            run_prometheus_exporter: i == 0,
+            env_vars,
+            chain: Some(chain.to_owned()),
+        };
+        let (chain_tasks, stop_sender, cb_receiver, health_check_handle) =
+            initialize_chain_components(components.clone(), use_prometheus_push_gateway, options)
+                .await
+                .with_context(|| format!("failed initializing components for chain `{chain}`"))?;
+        task_handles.extend(chain_tasks);
+        stop_senders.push(stop_sender);
+        cb_receivers.push(cb_receiver);
+        health_check_handles.push(health_check_handle);
+    }
+
+    let (stop_sender, mut stop_receiver) = watch::channel(false);
+    tokio::spawn(async move {
+        // If the sender is dropped, the chains are stopped as well.
+        stop_receiver.changed().await.ok();
+        for stop_sender in &stop_senders {
+            stop_sender.send(true).ok();
+        }
+    });
+
+    let (cb_sender, cb_receiver) = oneshot::channel();
+    tokio::spawn(async move {
+        let (first_error, ..) = future::select_all(cb_receivers).await;
+        if let Ok(error) = first_error {
+            cb_sender.send(error).ok();
+        }
+    });
+
+    Ok((task_handles, stop_sender, cb_receiver, health_check_handles))
+}
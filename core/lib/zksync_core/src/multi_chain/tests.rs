@@ -0,0 +1,107 @@
+use zksync_types::proofs::AggregationRound;
+
+use super::*;
+
+const BASE_VARS: &[(&str, &str)] = &[
+    ("DATABASE_URL", "postgres://localhost/zksync_local"),
+    ("CHAIN_ETH_NETWORK", "localhost"),
+    ("CHAIN_ETH_ZKSYNC_NETWORK", "localhost"),
+    ("CHAIN_ETH_ZKSYNC_NETWORK_ID", "270"),
+    ("ERA__CHAIN_ETH_ZKSYNC_NETWORK_ID", "271"),
+];
+
+fn chain_vars<'a>(overrides: &[(&str, &str)], chains: &[&'a str]) -> Vec<(&'a str, EnvVars)> {
+    let vars: EnvVars = BASE_VARS
+        .iter()
+        .chain(overrides)
+        .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+        .collect();
+    chains
+        .iter()
+        .map(|&chain| (chain, vars.for_chain(chain)))
+        .collect()
+}
+
+#[test]
+fn checking_components() {
+    check_components(&[Component::HttpApi, Component::StateKeeper, Component::Tree]).unwrap();
+
+    let err = check_components(&[Component::StateKeeper, Component::Housekeeper])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Housekeeper"), "{err}");
+    let witness_generator = Component::WitnessGenerator(None, AggregationRound::Scheduler);
+    check_components(&[witness_generator]).unwrap_err();
+}
+
+#[test]
+fn checking_valid_chain_configs() {
+    let overrides = [
+        ("ERA__DATABASE_SCHEMA", "era"),
+        (
+            "ERA__DATABASE_STATE_KEEPER_DB_PATH",
+            "./db/era/state_keeper",
+        ),
+        ("ERA__DATABASE_MERKLE_TREE_PATH", "./db/era/tree"),
+    ];
+    let components = [Component::StateKeeper, Component::Tree];
+    check_chain_configs(&chain_vars(&overrides, &["main", "era"]), &components).unwrap();
+}
+
+#[test]
+fn chains_sharing_database_are_rejected() {
+    let components = [Component::HttpApi];
+    let err = check_chain_configs(&chain_vars(&[], &["main", "era"]), &components)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("same database and schema"), "{err}");
+
+    // A database can be shared if chains use different schemas.
+    let overrides = [("ERA__DATABASE_SCHEMA", "era")];
+    check_chain_configs(&chain_vars(&overrides, &["main", "era"]), &components).unwrap();
+    let overrides = [("ERA__DATABASE_URL", "postgres://localhost/era")];
+    check_chain_configs(&chain_vars(&overrides, &["main", "era"]), &components).unwrap();
+}
+
+#[test]
+fn chains_sharing_chain_id_are_rejected() {
+    let overrides = [
+        ("ERA__DATABASE_SCHEMA", "era"),
+        ("ERA__CHAIN_ETH_ZKSYNC_NETWORK_ID", "270"),
+    ];
+    let err = check_chain_configs(&chain_vars(&overrides, &["main", "era"]), &[])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("same L2 chain ID"), "{err}");
+}
+
+#[test]
+fn chains_sharing_rocksdb_paths_are_rejected() {
+    let overrides = [("ERA__DATABASE_SCHEMA", "era")];
+    let chain_vars = chain_vars(&overrides, &["main", "era"]);
+    // Paths are only checked for components using them.
+    check_chain_configs(&chain_vars, &[Component::HttpApi]).unwrap();
+
+    let err = check_chain_configs(&chain_vars, &[Component::StateKeeper])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("same state keeper cache"), "{err}");
+    let err = check_chain_configs(&chain_vars, &[Component::TreeLightweight])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("same Merkle tree path"), "{err}");
+}
+
+#[test]
+fn chain_without_database_url_is_rejected() {
+    let vars: EnvVars = BASE_VARS[1..]
+        .iter()
+        .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+        .collect();
+    let chain_vars = [("main", vars.for_chain("main"))];
+    let err = check_chain_configs(&chain_vars, &[]).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("database URL is not set for chain `main`"),
+        "{err:#}"
+    );
+}
@@ -0,0 +1,414 @@
+//! Profiling of transaction execution in the state keeper. Only available with the `profiling` feature.
+//!
+//! Profiling is triggered via the admin HTTP API: `POST /profile` with a `{ "transactions": N }` JSON body
+//! makes the state keeper profile the next `N` executed transactions using `pprof`. Once these transactions
+//! are executed, the resulting flamegraph is rendered and uploaded to the object store in background.
+//!
+//! `pprof` samples all threads of the process, so the report is filtered to only contain samples
+//! from the thread executing transactions. Since threads are distinguished by name, profiles are most precise
+//! if the state keeper runs on a dedicated VM thread pool (see `vm_thread_pool_size` in the state keeper config);
+//! otherwise, samples from all Tokio blocking threads are included.
+
+use anyhow::Context as _;
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use once_cell::sync::OnceCell;
+use pprof::{ProfilerGuard, ProfilerGuardBuilder};
+use serde::Deserialize;
+use tokio::{runtime::Handle, sync::watch, task::JoinHandle};
+
+use std::{
+    fmt, mem,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use zksync_object_store::{Bucket, ObjectStore};
+
+/// Sampling frequency for the profiler. A prime number is used to avoid lockstep sampling
+/// with periodic activities.
+const SAMPLING_FREQUENCY_HZ: i32 = 997;
+/// Maximum number of transactions that can be profiled in a single request.
+const MAX_PROFILED_TRANSACTIONS: usize = 10_000;
+/// Maximum length of thread names reported by the OS (and thus by `pprof`).
+const MAX_THREAD_NAME_LEN: usize = 15;
+
+static PROFILER: OnceCell<Arc<ExecutionProfiler>> = OnceCell::new();
+
+struct ProfilingSession {
+    guard: ProfilerGuard<'static>,
+    started_at: Instant,
+    /// Name of the thread executing transactions; `None` if the thread is unnamed.
+    thread_name: Option<String>,
+    profiled_txs: usize,
+    target_txs: usize,
+}
+
+#[derive(Default)]
+struct ProfilerState {
+    requested_txs: usize,
+    session: Option<ProfilingSession>,
+}
+
+struct ExecutionProfiler {
+    state: Mutex<ProfilerState>,
+    object_store: Arc<dyn ObjectStore>,
+    runtime: Handle,
+}
+
+impl fmt::Debug for ExecutionProfiler {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ExecutionProfiler")
+            .field("object_store", &self.object_store)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExecutionProfiler {
+    fn new(object_store: Arc<dyn ObjectStore>, runtime: Handle) -> Self {
+        Self {
+            state: Mutex::default(),
+            object_store,
+            runtime,
+        }
+    }
+
+    fn request(&self, tx_count: usize) -> Result<(), &'static str> {
+        let mut state = self.state.lock().expect("profiler state is poisoned");
+        if state.session.is_some() || state.requested_txs > 0 {
+            return Err("profiling is already in progress");
+        }
+        state.requested_txs = tx_count;
+        tracing::info!("Requested profiling of the next {tx_count} transactions");
+        Ok(())
+    }
+
+    fn tx_started(&self) {
+        let mut state = self.state.lock().expect("profiler state is poisoned");
+        if state.session.is_some() || state.requested_txs == 0 {
+            return;
+        }
+
+        let target_txs = mem::take(&mut state.requested_txs);
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(SAMPLING_FREQUENCY_HZ)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build();
+        match guard {
+            Ok(guard) => {
+                let thread_name = thread::current().name().map(os_thread_name);
+                tracing::info!(
+                    "Started profiling {target_txs} transactions on thread {thread_name:?}"
+                );
+                state.session = Some(ProfilingSession {
+                    guard,
+                    started_at: Instant::now(),
+                    thread_name,
+                    profiled_txs: 0,
+                    target_txs,
+                });
+            }
+            Err(err) => {
+                tracing::warn!("Failed starting profiler: {err}");
+            }
+        }
+    }
+
+    /// Returns a handle for the task rendering and uploading the flamegraph if the profiling session has finished.
+    /// The task outputs the object store key of the uploaded flamegraph.
+    fn tx_finished(&self) -> Option<JoinHandle<Option<String>>> {
+        let session = {
+            let mut state = self.state.lock().expect("profiler state is poisoned");
+            let session = state.session.as_mut()?;
+            session.profiled_txs += 1;
+            if session.profiled_txs < session.target_txs {
+                return None;
+            }
+            state.session.take().unwrap()
+            // ^ `unwrap()` is safe: we've checked that the session is present above
+        };
+        let elapsed = session.started_at.elapsed();
+        tracing::info!(
+            "Finished profiling {} transactions in {elapsed:?}",
+            session.profiled_txs
+        );
+
+        // Building the report (which includes symbol resolution) and rendering the flamegraph are expensive,
+        // so we do it outside of the state keeper thread.
+        let object_store = self.object_store.clone();
+        let runtime = self.runtime.clone();
+        Some(self.runtime.spawn(async move {
+            let profiled_txs = session.profiled_txs;
+            let render_result = runtime
+                .spawn_blocking(move || render_flamegraph(session))
+                .await;
+            let flamegraph = match render_result {
+                Ok(Ok(flamegraph)) => flamegraph,
+                Ok(Err(err)) => {
+                    tracing::warn!("Failed rendering flamegraph: {err:#}");
+                    return None;
+                }
+                Err(err) => {
+                    tracing::warn!("Flamegraph rendering task panicked: {err}");
+                    return None;
+                }
+            };
+
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("incorrect system time")
+                .as_secs();
+            let key = format!("state_keeper_{timestamp}_{profiled_txs}txs.svg");
+            tracing::info!("Uploading flamegraph to `{key}`");
+            let put_result = object_store
+                .put_raw(Bucket::ExecutionProfiles, &key, flamegraph)
+                .await;
+            if let Err(err) = put_result {
+                tracing::warn!("Failed uploading flamegraph `{key}` to object store: {err}");
+                return None;
+            }
+            Some(key)
+        }))
+    }
+}
+
+/// Truncates the thread name in the same way the OS does.
+fn os_thread_name(name: &str) -> String {
+    let mut end = name.len().min(MAX_THREAD_NAME_LEN);
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    name[..end].to_owned()
+}
+
+fn render_flamegraph(session: ProfilingSession) -> anyhow::Result<Vec<u8>> {
+    let mut report = session
+        .guard
+        .report()
+        .build()
+        .context("failed building profiling report")?;
+    drop(session.guard);
+    if let Some(thread_name) = &session.thread_name {
+        report
+            .data
+            .retain(|frames, _| frames.thread_name == *thread_name);
+    }
+    anyhow::ensure!(
+        !report.data.is_empty(),
+        "no samples collected for thread {:?}",
+        session.thread_name
+    );
+
+    let mut flamegraph = vec![];
+    report
+        .flamegraph(&mut flamegraph)
+        .context("failed rendering flamegraph")?;
+    Ok(flamegraph)
+}
+
+/// Initializes the global execution profiler. Must be called from the Tokio runtime context.
+///
+/// # Errors
+///
+/// Returns an error if the profiler is already initialized.
+pub fn init(object_store: Box<dyn ObjectStore>) -> anyhow::Result<()> {
+    let profiler = ExecutionProfiler::new(object_store.into(), Handle::current());
+    PROFILER
+        .set(Arc::new(profiler))
+        .map_err(|_| anyhow::anyhow!("execution profiler is already initialized"))
+}
+
+/// Notifies the profiler that the state keeper started executing a transaction.
+/// No-op if the profiler is not initialized.
+pub(crate) fn tx_started() {
+    if let Some(profiler) = PROFILER.get() {
+        profiler.tx_started();
+    }
+}
+
+/// Notifies the profiler that the state keeper finished executing a transaction.
+/// No-op if the profiler is not initialized.
+pub(crate) fn tx_finished() {
+    if let Some(profiler) = PROFILER.get() {
+        profiler.tx_finished();
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileRequest {
+    transactions: usize,
+}
+
+async fn request_profile(
+    State(profiler): State<Arc<ExecutionProfiler>>,
+    Json(request): Json<ProfileRequest>,
+) -> (StatusCode, &'static str) {
+    if request.transactions == 0 || request.transactions > MAX_PROFILED_TRANSACTIONS {
+        return (
+            StatusCode::BAD_REQUEST,
+            "number of transactions must be in 1..=10000",
+        );
+    }
+    match profiler.request(request.transactions) {
+        Ok(()) => (StatusCode::ACCEPTED, "profiling requested"),
+        Err(message) => (StatusCode::CONFLICT, message),
+    }
+}
+
+fn admin_router(profiler: Arc<ExecutionProfiler>) -> Router {
+    Router::new()
+        .route("/profile", post(request_profile))
+        .with_state(profiler)
+}
+
+/// Runs the admin HTTP server used to trigger profiling. The server has no authentication, so it only listens
+/// on the loopback interface.
+///
+/// # Errors
+///
+/// Returns an error if the profiler is not [initialized](init()), or if the server cannot be started.
+pub async fn run_admin_server(
+    port: u16,
+    mut stop_receiver: watch::Receiver<bool>,
+) -> anyhow::Result<()> {
+    let profiler = PROFILER
+        .get()
+        .context("execution profiler is not initialized")?
+        .clone();
+    let bind_address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    tracing::info!("Starting profiling admin server on {bind_address}");
+    axum::Server::try_bind(&bind_address)
+        .with_context(|| format!("failed binding profiling admin server to {bind_address}"))?
+        .serve(admin_router(profiler).into_make_service())
+        .with_graceful_shutdown(async move {
+            if stop_receiver.changed().await.is_err() {
+                tracing::warn!("Stop signal sender for profiling admin server was dropped without sending a signal");
+            }
+            tracing::info!("Stop signal received, profiling admin server is shutting down");
+        })
+        .await
+        .context("profiling admin server failed")?;
+    tracing::info!("Profiling admin server shut down");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt as _;
+
+    use std::time::Duration;
+
+    use zksync_object_store::ObjectStoreFactory;
+
+    use super::*;
+
+    async fn create_profiler() -> (Arc<ExecutionProfiler>, Arc<dyn ObjectStore>) {
+        let object_store: Arc<dyn ObjectStore> =
+            ObjectStoreFactory::mock().create_store().await.into();
+        let profiler = ExecutionProfiler::new(object_store.clone(), Handle::current());
+        (Arc::new(profiler), object_store)
+    }
+
+    async fn send_request(profiler: &Arc<ExecutionProfiler>, body: &str) -> StatusCode {
+        let request = Request::post("/profile")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = admin_router(profiler.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        response.status()
+    }
+
+    fn burn_cpu(duration: Duration) -> u64 {
+        let started_at = Instant::now();
+        let mut acc = 0_u64;
+        while started_at.elapsed() < duration {
+            for i in 0..10_000_u64 {
+                acc = acc.wrapping_mul(31).wrapping_add(i);
+            }
+        }
+        acc
+    }
+
+    #[test]
+    fn truncating_thread_names() {
+        assert_eq!(os_thread_name("test-vm-0"), "test-vm-0");
+        assert_eq!(os_thread_name("tokio-runtime-worker"), "tokio-runtime-w");
+    }
+
+    #[tokio::test]
+    async fn validating_profile_requests() {
+        let (profiler, _) = create_profiler().await;
+        assert_eq!(
+            send_request(&profiler, r#"{ "transactions": 0 }"#).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            send_request(&profiler, r#"{ "transactions": 1000000 }"#).await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            send_request(&profiler, r#"{ "transactions": 2 }"#).await,
+            StatusCode::ACCEPTED
+        );
+        assert_eq!(
+            send_request(&profiler, r#"{ "transactions": 2 }"#).await,
+            StatusCode::CONFLICT
+        );
+    }
+
+    #[tokio::test]
+    async fn no_profiling_without_request() {
+        let (profiler, _) = create_profiler().await;
+        profiler.tx_started();
+        assert!(profiler.state.lock().unwrap().session.is_none());
+        assert!(profiler.tx_finished().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn profiling_transactions() {
+        let (profiler, object_store) = create_profiler().await;
+        profiler.request(2).unwrap();
+
+        let executor_profiler = profiler.clone();
+        let handle = thread::Builder::new()
+            .name("test-vm-0".to_owned())
+            .spawn(move || {
+                let mut handles = vec![];
+                for _ in 0..2 {
+                    executor_profiler.tx_started();
+                    burn_cpu(Duration::from_millis(200));
+                    handles.push(executor_profiler.tx_finished());
+                }
+                handles
+            })
+            .unwrap();
+        let handles = tokio::task::spawn_blocking(|| handle.join().unwrap())
+            .await
+            .unwrap();
+
+        // Only the last transaction should finish the profiling session.
+        assert!(handles[0].is_none());
+        let upload_handle = handles.into_iter().last().unwrap().unwrap();
+        let key = upload_handle
+            .await
+            .unwrap()
+            .expect("flamegraph not uploaded");
+        assert!(key.ends_with("_2txs.svg"), "{key}");
+        let flamegraph = object_store
+            .get_raw(Bucket::ExecutionProfiles, &key)
+            .await
+            .unwrap();
+        let flamegraph = String::from_utf8(flamegraph).unwrap();
+        assert!(flamegraph.contains("<svg"), "{flamegraph}");
+        assert!(flamegraph.contains("test-vm-0"), "{flamegraph}");
+
+        // The profiler should accept new requests after the session is finished.
+        profiler.request(1).unwrap();
+    }
+}
@@ -0,0 +1,395 @@
+//! Chunked transfer of large payloads between the proof data handler and provers: witness inputs
+//! are served via HTTP range requests, and proofs can be uploaded in chunks so that a failed upload
+//! can be resumed instead of restarted from scratch.
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use zksync_types::{prover_server_api::chunk_checksum, L1BatchNumber, H256};
+
+/// Number of witness input descriptions kept in memory so that range requests don't need
+/// to fetch the entire input from the object store to learn its size.
+const WITNESS_INFO_CACHE_CAPACITY: usize = 64;
+/// Upper bound on the number of chunks in a proof upload.
+const MAX_PROOF_CHUNKS: u32 = 1 << 16;
+/// Upper bound on the number of proof uploads in progress at the same time.
+const MAX_PROOF_UPLOADS: usize = 64;
+
+/// Size of a serialized witness input together with checksums of its chunks.
+#[derive(Debug)]
+pub(super) struct WitnessInfo {
+    pub size: u64,
+    pub chunk_size: u64,
+    pub chunk_checksums: Vec<H256>,
+}
+
+impl WitnessInfo {
+    pub fn new(data: &[u8], chunk_size: u64) -> Self {
+        let chunk_checksums = data
+            .chunks(chunk_size as usize)
+            .map(chunk_checksum)
+            .collect();
+        Self {
+            size: data.len() as u64,
+            chunk_size,
+            chunk_checksums,
+        }
+    }
+}
+
+/// LRU cache of witness input descriptions. Inputs themselves are not cached; their ranges
+/// are read from the object store on each request.
+#[derive(Debug, Default)]
+pub(super) struct WitnessInfoCache(Mutex<VecDeque<(L1BatchNumber, Arc<WitnessInfo>)>>);
+
+impl WitnessInfoCache {
+    pub fn get(&self, l1_batch_number: L1BatchNumber) -> Option<Arc<WitnessInfo>> {
+        let mut entries = self.0.lock().expect("witness info cache is poisoned");
+        let position = entries
+            .iter()
+            .position(|(number, _)| *number == l1_batch_number)?;
+        let entry = entries.remove(position)?;
+        let info = entry.1.clone();
+        entries.push_back(entry);
+        Some(info)
+    }
+
+    pub fn insert(&self, l1_batch_number: L1BatchNumber, info: Arc<WitnessInfo>) {
+        let mut entries = self.0.lock().expect("witness info cache is poisoned");
+        entries.retain(|(number, _)| *number != l1_batch_number);
+        if entries.len() >= WITNESS_INFO_CACHE_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back((l1_batch_number, info));
+    }
+}
+
+/// Parses the value of a `Range` header (e.g., `bytes=0-1023`, `bytes=1024-` or `bytes=-512`)
+/// into a byte range. Only single-range requests are supported. Returns `None` if the header
+/// is malformed or the range is not satisfiable for data of the specified size.
+pub(super) fn parse_byte_range(header: &str, data_size: u64) -> Option<Range<u64>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        let suffix_len: u64 = end.parse().ok()?;
+        data_size.saturating_sub(suffix_len)..data_size
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            data_size
+        } else {
+            let inclusive_end: u64 = end.parse().ok()?;
+            inclusive_end.saturating_add(1).min(data_size)
+        };
+        start..end
+    };
+    (range.start < range.end).then_some(range)
+}
+
+/// Checks a proof chunk received from a prover.
+pub(super) fn validate_proof_chunk(
+    chunk_index: u32,
+    total_chunks: u32,
+    checksum: H256,
+    data: &[u8],
+) -> Result<(), String> {
+    if total_chunks == 0 || total_chunks > MAX_PROOF_CHUNKS {
+        return Err(format!(
+            "Number of chunks must be in 1..={MAX_PROOF_CHUNKS}, got {total_chunks}"
+        ));
+    }
+    if chunk_index >= total_chunks {
+        return Err(format!(
+            "Chunk index {chunk_index} is out of bounds for {total_chunks} chunks"
+        ));
+    }
+    let actual_checksum = chunk_checksum(data);
+    if actual_checksum != checksum {
+        return Err(format!(
+            "Checksum mismatch for chunk {chunk_index}: expected {checksum:?}, got {actual_checksum:?}"
+        ));
+    }
+    Ok(())
+}
+
+/// State of a chunked proof upload. Chunks themselves are stored in the object store; the state
+/// is persisted alongside them, so that uploads survive restarts of the proof data handler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(super) struct ProofUpload {
+    received_chunks: Vec<bool>,
+    /// Number of chunk objects that may be present in the object store. Exceeds the current number
+    /// of chunks if the upload was restarted with fewer chunks.
+    stored_chunks: u32,
+    /// UNIX timestamp (in seconds) when the last chunk was received.
+    updated_at: u64,
+}
+
+impl ProofUpload {
+    pub fn total_chunks(&self) -> u32 {
+        self.received_chunks.len() as u32
+    }
+
+    pub fn missing_chunks(&self) -> Vec<u32> {
+        self.received_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &received)| (!received).then_some(index as u32))
+            .collect()
+    }
+
+    /// Returns indices of chunk objects that may be present in the object store.
+    pub fn stored_chunks(&self) -> impl Iterator<Item = u32> + '_ {
+        let received = self
+            .received_chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &received)| received.then_some(index as u32));
+        received.chain(self.total_chunks()..self.stored_chunks)
+    }
+}
+
+/// Status of a chunked proof upload after receiving a chunk.
+#[derive(Debug, PartialEq)]
+pub(super) enum ProofUploadStatus {
+    Incomplete {
+        missing_chunks: Vec<u32>,
+    },
+    /// All chunks are received; the upload is removed from [`ProofUploads`].
+    Complete(ProofUpload),
+}
+
+/// In-progress chunked proof uploads. The number of uploads is bounded, and uploads not receiving
+/// chunks for longer than the timeout are discarded.
+#[derive(Debug)]
+pub(super) struct ProofUploads {
+    uploads: HashMap<L1BatchNumber, ProofUpload>,
+    timeout: Duration,
+}
+
+impl ProofUploads {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            uploads: HashMap::new(),
+            timeout,
+        }
+    }
+
+    fn is_expired(&self, upload: &ProofUpload, now: u64) -> bool {
+        upload.updated_at.saturating_add(self.timeout.as_secs()) < now
+    }
+
+    pub fn get(&self, l1_batch_number: L1BatchNumber) -> Option<&ProofUpload> {
+        self.uploads.get(&l1_batch_number)
+    }
+
+    /// Restores an upload loaded from the object store. Returns `false` if the upload is expired.
+    /// The upload was accounted for when it was started, so it's restored even if the number of
+    /// uploads is at the limit.
+    pub fn restore(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        upload: ProofUpload,
+        now: u64,
+    ) -> bool {
+        if self.is_expired(&upload, now) {
+            return false;
+        }
+        self.uploads.insert(l1_batch_number, upload);
+        true
+    }
+
+    /// Removes uploads that have expired as of `now` and returns them.
+    pub fn remove_expired(&mut self, now: u64) -> Vec<(L1BatchNumber, ProofUpload)> {
+        let expired: Vec<_> = self
+            .uploads
+            .iter()
+            .filter(|(_, upload)| self.is_expired(upload, now))
+            .map(|(&number, _)| number)
+            .collect();
+        expired
+            .into_iter()
+            .map(|number| (number, self.uploads.remove(&number).unwrap()))
+            .collect()
+    }
+
+    /// Marks a chunk as received in the upload for the specified L1 batch. The chunk must be
+    /// validated with [`validate_proof_chunk()`] beforehand. If the number of chunks differs from
+    /// the one in the existing upload, the existing upload is restarted.
+    pub fn add_chunk(
+        &mut self,
+        l1_batch_number: L1BatchNumber,
+        chunk_index: u32,
+        total_chunks: u32,
+        now: u64,
+    ) -> Result<ProofUploadStatus, String> {
+        if !self.uploads.contains_key(&l1_batch_number) && self.uploads.len() >= MAX_PROOF_UPLOADS {
+            return Err(format!(
+                "Too many proof uploads in progress (max {MAX_PROOF_UPLOADS})"
+            ));
+        }
+        let upload = self
+            .uploads
+            .entry(l1_batch_number)
+            .or_insert_with(|| ProofUpload {
+                received_chunks: vec![false; total_chunks as usize],
+                stored_chunks: 0,
+                updated_at: now,
+            });
+        if upload.total_chunks() != total_chunks {
+            tracing::info!(
+                "Restarting proof upload for L1 batch #{l1_batch_number} with {total_chunks} chunks \
+                 (was {})",
+                upload.total_chunks()
+            );
+            upload.stored_chunks = upload.stored_chunks.max(upload.total_chunks());
+            upload.received_chunks = vec![false; total_chunks as usize];
+        }
+        upload.received_chunks[chunk_index as usize] = true;
+        upload.stored_chunks = upload.stored_chunks.max(chunk_index + 1);
+        upload.updated_at = now;
+
+        let missing_chunks = upload.missing_chunks();
+        if !missing_chunks.is_empty() {
+            return Ok(ProofUploadStatus::Incomplete { missing_chunks });
+        }
+        let upload = self.uploads.remove(&l1_batch_number).unwrap();
+        Ok(ProofUploadStatus::Complete(upload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_byte_ranges() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1_000), Some(0..100));
+        assert_eq!(parse_byte_range("bytes=900-1999", 1_000), Some(900..1_000));
+        assert_eq!(parse_byte_range("bytes=500-", 1_000), Some(500..1_000));
+        assert_eq!(parse_byte_range("bytes=-100", 1_000), Some(900..1_000));
+        assert_eq!(parse_byte_range("bytes=-2000", 1_000), Some(0..1_000));
+
+        assert_eq!(parse_byte_range("bytes=1000-", 1_000), None);
+        assert_eq!(parse_byte_range("bytes=100-50", 1_000), None);
+        assert_eq!(parse_byte_range("bytes=0-99,200-299", 1_000), None);
+        assert_eq!(parse_byte_range("items=0-99", 1_000), None);
+        assert_eq!(parse_byte_range("bytes=-0", 1_000), None);
+    }
+
+    #[test]
+    fn witness_info_checksums() {
+        let data: Vec<u8> = (0..=255).collect();
+        let info = WitnessInfo::new(&data, 100);
+        assert_eq!(info.size, 256);
+        assert_eq!(info.chunk_checksums.len(), 3);
+        assert_eq!(info.chunk_checksums[0], chunk_checksum(&data[..100]));
+        assert_eq!(info.chunk_checksums[2], chunk_checksum(&data[200..]));
+    }
+
+    #[test]
+    fn witness_info_cache_evicts_least_recently_used_entries() {
+        let cache = WitnessInfoCache::default();
+        for number in 0..WITNESS_INFO_CACHE_CAPACITY as u32 {
+            let info = WitnessInfo::new(&[number as u8; 10], 4);
+            cache.insert(L1BatchNumber(number), Arc::new(info));
+        }
+        assert!(cache.get(L1BatchNumber(0)).is_some());
+
+        let info = WitnessInfo::new(&[0; 20], 4);
+        cache.insert(L1BatchNumber(1_000), Arc::new(info));
+        assert!(cache.get(L1BatchNumber(1)).is_none());
+        assert_eq!(cache.get(L1BatchNumber(0)).unwrap().size, 10);
+        assert_eq!(cache.get(L1BatchNumber(1_000)).unwrap().size, 20);
+    }
+
+    #[test]
+    fn validating_proof_chunks() {
+        let chunk = b"01";
+        validate_proof_chunk(0, 3, chunk_checksum(chunk), chunk).unwrap();
+
+        let err = validate_proof_chunk(0, 3, H256::zero(), chunk).unwrap_err();
+        assert!(err.contains("Checksum mismatch"), "{err}");
+        let err = validate_proof_chunk(3, 3, chunk_checksum(chunk), chunk).unwrap_err();
+        assert!(err.contains("out of bounds"), "{err}");
+        let err = validate_proof_chunk(0, 0, chunk_checksum(chunk), chunk).unwrap_err();
+        assert!(err.contains("Number of chunks"), "{err}");
+    }
+
+    #[test]
+    fn uploading_proof_in_chunks() {
+        let mut uploads = ProofUploads::new(Duration::from_secs(60));
+        let l1_batch_number = L1BatchNumber(1);
+        assert_eq!(uploads.get(l1_batch_number), None);
+
+        let status = uploads.add_chunk(l1_batch_number, 2, 3, 100).unwrap();
+        assert_eq!(
+            status,
+            ProofUploadStatus::Incomplete {
+                missing_chunks: vec![0, 1]
+            }
+        );
+        let upload = uploads.get(l1_batch_number).unwrap();
+        assert_eq!(upload.missing_chunks(), [0, 1]);
+        assert_eq!(upload.stored_chunks().collect::<Vec<_>>(), [2]);
+
+        for _ in 0..2 {
+            uploads.add_chunk(l1_batch_number, 0, 3, 110).unwrap();
+        }
+        let status = uploads.add_chunk(l1_batch_number, 1, 3, 120).unwrap();
+        let ProofUploadStatus::Complete(upload) = status else {
+            panic!("unexpected status: {status:?}");
+        };
+        assert_eq!(upload.stored_chunks().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(uploads.get(l1_batch_number), None);
+    }
+
+    #[test]
+    fn restarting_proof_upload_with_different_chunking() {
+        let mut uploads = ProofUploads::new(Duration::from_secs(60));
+        let l1_batch_number = L1BatchNumber(1);
+        uploads.add_chunk(l1_batch_number, 2, 3, 100).unwrap();
+
+        let status = uploads.add_chunk(l1_batch_number, 1, 2, 100).unwrap();
+        assert_eq!(
+            status,
+            ProofUploadStatus::Incomplete {
+                missing_chunks: vec![0]
+            }
+        );
+        // The chunk from the previous chunking must still be cleaned up.
+        let upload = uploads.get(l1_batch_number).unwrap();
+        assert_eq!(upload.stored_chunks().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn proof_uploads_are_bounded_and_expire() {
+        let mut uploads = ProofUploads::new(Duration::from_secs(60));
+        for number in 0..MAX_PROOF_UPLOADS as u32 {
+            uploads.add_chunk(L1BatchNumber(number), 0, 2, 100).unwrap();
+        }
+        let err = uploads
+            .add_chunk(L1BatchNumber(1_000), 0, 2, 100)
+            .unwrap_err();
+        assert!(err.contains("Too many proof uploads"), "{err}");
+        // Existing uploads can still be continued.
+        uploads.add_chunk(L1BatchNumber(0), 1, 2, 150).unwrap();
+
+        assert!(uploads.remove_expired(160).is_empty());
+        let expired = uploads.remove_expired(161);
+        assert_eq!(expired.len(), MAX_PROOF_UPLOADS - 1);
+        uploads.add_chunk(L1BatchNumber(1_000), 0, 2, 161).unwrap();
+
+        let upload = uploads.get(L1BatchNumber(1_000)).unwrap().clone();
+        let mut restored_uploads = ProofUploads::new(Duration::from_secs(60));
+        assert!(!restored_uploads.restore(L1BatchNumber(1_000), upload.clone(), 222));
+        assert!(restored_uploads.restore(L1BatchNumber(1_000), upload.clone(), 221));
+        assert_eq!(restored_uploads.get(L1BatchNumber(1_000)), Some(&upload));
+    }
+}
@@ -1,7 +1,12 @@
 use crate::proof_data_handler::request_processor::RequestProcessor;
 use anyhow::Context as _;
-use axum::extract::Path;
-use axum::{routing::post, Json, Router};
+use axum::body::Bytes;
+use axum::extract::{Path, Query};
+use axum::http::HeaderMap;
+use axum::{
+    routing::{get, post},
+    Json, Router,
+};
 use std::net::SocketAddr;
 use tokio::sync::watch;
 use zksync_config::{
@@ -12,15 +17,15 @@ use zksync_dal::ConnectionPool;
 use zksync_object_store::ObjectStore;
 use zksync_types::{
     protocol_version::{L1VerifierConfig, VerifierParams},
-    prover_server_api::{ProofGenerationDataRequest, SubmitProofRequest},
+    prover_server_api::{ProofGenerationDataRequest, SubmitProofChunkParams, SubmitProofRequest},
     H256,
 };
 
+mod chunks;
 mod request_processor;
 
-fn fri_l1_verifier_config_from_env() -> anyhow::Result<L1VerifierConfig> {
-    let config = ContractsConfig::from_env().context("ContractsConfig::from_env()")?;
-    Ok(L1VerifierConfig {
+fn fri_l1_verifier_config(config: &ContractsConfig) -> L1VerifierConfig {
+    L1VerifierConfig {
         params: VerifierParams {
             recursion_node_level_vk_hash: config.fri_recursion_node_level_vk_hash,
             recursion_leaf_level_vk_hash: config.fri_recursion_leaf_level_vk_hash,
@@ -28,26 +33,33 @@ fn fri_l1_verifier_config_from_env() -> anyhow::Result<L1VerifierConfig> {
             recursion_circuits_set_vks_hash: H256::zero(),
         },
         recursion_scheduler_level_vk_hash: config.fri_recursion_scheduler_level_vk_hash,
-    })
+    }
 }
 
 pub(crate) async fn run_server(
     config: ProofDataHandlerConfig,
+    contracts_config: ContractsConfig,
     blob_store: Box<dyn ObjectStore>,
     pool: ConnectionPool,
     mut stop_receiver: watch::Receiver<bool>,
 ) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        config.witness_chunk_size() > 0,
+        "Witness chunk size must be positive"
+    );
     let bind_address = SocketAddr::from(([0, 0, 0, 0], config.http_port));
     tracing::debug!("Starting proof data handler server on {bind_address}");
     let l1_verifier_config: Option<L1VerifierConfig> = match config.protocol_version_loading_mode {
         ProtocolVersionLoadingMode::FromDb => None,
-        ProtocolVersionLoadingMode::FromEnvVar => {
-            Some(fri_l1_verifier_config_from_env().context("fri_l1_verified_config_from_env()")?)
-        }
+        ProtocolVersionLoadingMode::FromEnvVar => Some(fri_l1_verifier_config(&contracts_config)),
     };
     let get_proof_gen_processor =
         RequestProcessor::new(blob_store, pool, config, l1_verifier_config);
+    let get_proof_gen_info_processor = get_proof_gen_processor.clone();
+    let get_proof_gen_range_processor = get_proof_gen_processor.clone();
     let submit_proof_processor = get_proof_gen_processor.clone();
+    let submit_proof_chunk_processor = get_proof_gen_processor.clone();
+    let proof_upload_status_processor = get_proof_gen_processor.clone();
     let app = Router::new()
         .route(
             "/proof_generation_data",
@@ -61,6 +73,28 @@ pub(crate) async fn run_server(
                 },
             ),
         )
+        .route(
+            "/proof_generation_data_info",
+            post(
+                // Same as `/proof_generation_data`, but the witness input is not included
+                // in the response and should be downloaded from the endpoint below.
+                move |payload: Json<ProofGenerationDataRequest>| async move {
+                    get_proof_gen_info_processor
+                        .get_proof_generation_data_info(payload)
+                        .await
+                },
+            ),
+        )
+        .route(
+            "/proof_generation_data/:l1_batch_number",
+            get(
+                move |l1_batch_number: Path<u32>, headers: HeaderMap| async move {
+                    get_proof_gen_range_processor
+                        .get_proof_generation_data_range(l1_batch_number, headers)
+                        .await
+                },
+            ),
+        )
         .route(
             "/submit_proof/:l1_batch_number",
             post(
@@ -70,6 +104,26 @@ pub(crate) async fn run_server(
                         .await
                 },
             ),
+        )
+        .route(
+            "/submit_proof/:l1_batch_number/chunks",
+            get(move |l1_batch_number: Path<u32>| async move {
+                proof_upload_status_processor
+                    .get_proof_upload_status(l1_batch_number)
+                    .await
+            }),
+        )
+        .route(
+            "/submit_proof/:l1_batch_number/chunks/:chunk_index",
+            post(
+                move |path: Path<(u32, u32)>,
+                      params: Query<SubmitProofChunkParams>,
+                      body: Bytes| async move {
+                    submit_proof_chunk_processor
+                        .submit_proof_chunk(path, params, body)
+                        .await
+                },
+            ),
         );
 
     axum::Server::bind(&bind_address)
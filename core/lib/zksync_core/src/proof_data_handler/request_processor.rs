@@ -1,23 +1,42 @@
-use axum::extract::Path;
+use axum::body::Bytes;
+use axum::extract::{Path, Query};
+use axum::http::{header, HeaderMap, HeaderValue};
 use axum::response::Response;
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use std::convert::TryFrom;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use zksync_config::configs::{
     proof_data_handler::ProtocolVersionLoadingMode, ProofDataHandlerConfig,
 };
 
 use zksync_dal::{ConnectionPool, SqlxError};
-use zksync_object_store::{ObjectStore, ObjectStoreError};
+use zksync_object_store::{Bucket, ObjectStore, ObjectStoreError, StoredObject};
 use zksync_types::protocol_version::FriProtocolVersionId;
 use zksync_types::{
+    proofs::PrepareBasicCircuitsJob,
     protocol_version::L1VerifierConfig,
     prover_server_api::{
-        ProofGenerationData, ProofGenerationDataRequest, ProofGenerationDataResponse,
-        SubmitProofRequest, SubmitProofResponse,
+        ProofGenerationData, ProofGenerationDataInfo, ProofGenerationDataInfoResponse,
+        ProofGenerationDataRequest, ProofGenerationDataResponse, SubmitProofChunkParams,
+        SubmitProofChunkResponse, SubmitProofRequest, SubmitProofResponse,
     },
     L1BatchNumber,
 };
+use zksync_utils::time::seconds_since_epoch;
+
+use super::chunks::{
+    parse_byte_range, validate_proof_chunk, ProofUpload, ProofUploadStatus, ProofUploads,
+    WitnessInfo, WitnessInfoCache,
+};
+
+fn proof_upload_key(l1_batch_number: L1BatchNumber) -> String {
+    format!("proof_upload_{l1_batch_number}.json")
+}
+
+fn proof_chunk_key(l1_batch_number: L1BatchNumber, chunk_index: u32) -> String {
+    format!("proof_upload_{l1_batch_number}_chunk_{chunk_index}.bin")
+}
 
 #[derive(Clone)]
 pub(crate) struct RequestProcessor {
@@ -25,10 +44,16 @@ pub(crate) struct RequestProcessor {
     pool: ConnectionPool,
     config: ProofDataHandlerConfig,
     l1_verifier_config: Option<L1VerifierConfig>,
+    witness_info_cache: Arc<WitnessInfoCache>,
+    proof_uploads: Arc<Mutex<ProofUploads>>,
 }
 
 pub(crate) enum RequestProcessorError {
     NoPendingBatches,
+    NoProofUpload,
+    NotPickedByProver,
+    RangeNotSatisfiable { data_size: u64 },
+    InvalidProofChunk(String),
     ObjectStore(ObjectStoreError),
     Sqlx(SqlxError),
 }
@@ -40,6 +65,25 @@ impl IntoResponse for RequestProcessorError {
                 StatusCode::NOT_FOUND,
                 "No pending batches to process".to_owned(),
             ),
+            Self::NoProofUpload => (
+                StatusCode::NOT_FOUND,
+                "No proof upload in progress for L1 batch".to_owned(),
+            ),
+            Self::NotPickedByProver => (
+                StatusCode::CONFLICT,
+                "L1 batch is not being proven".to_owned(),
+            ),
+            Self::RangeNotSatisfiable { data_size } => {
+                let content_range = HeaderValue::from_str(&format!("bytes */{data_size}"))
+                    .expect("invalid Content-Range header");
+                return (
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, content_range)],
+                    "Requested range is not satisfiable",
+                )
+                    .into_response();
+            }
+            Self::InvalidProofChunk(err) => (StatusCode::BAD_REQUEST, err),
             RequestProcessorError::ObjectStore(err) => {
                 tracing::error!("GCS error: {:?}", err);
                 (
@@ -71,11 +115,77 @@ impl RequestProcessor {
         config: ProofDataHandlerConfig,
         l1_verifier_config: Option<L1VerifierConfig>,
     ) -> Self {
+        let proof_uploads = ProofUploads::new(config.proof_generation_timeout());
         Self {
             blob_store: Arc::from(blob_store),
             pool,
             config,
             l1_verifier_config,
+            witness_info_cache: Arc::default(),
+            proof_uploads: Arc::new(Mutex::new(proof_uploads)),
+        }
+    }
+
+    fn protocol_params(&self) -> (FriProtocolVersionId, L1VerifierConfig) {
+        let fri_protocol_version_id =
+            FriProtocolVersionId::try_from(self.config.fri_protocol_version_id)
+                .expect("Invalid FRI protocol version id");
+
+        let l1_verifier_config= match self.config.protocol_version_loading_mode {
+            ProtocolVersionLoadingMode::FromDb => {
+                panic!("Loading protocol version from db is not implemented yet")
+            }
+            ProtocolVersionLoadingMode::FromEnvVar => {
+                self.l1_verifier_config
+                    .expect("l1_verifier_config must be set while running ProtocolVersionLoadingMode::FromEnvVar mode")
+            }
+        };
+        (fri_protocol_version_id, l1_verifier_config)
+    }
+
+    /// Returns the size and chunk checksums of the witness input for the specified L1 batch.
+    /// Computing checksums requires loading the entire input, so the results are cached in memory.
+    async fn witness_info(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<Arc<WitnessInfo>, RequestProcessorError> {
+        if let Some(info) = self.witness_info_cache.get(l1_batch_number) {
+            return Ok(info);
+        }
+
+        let key = PrepareBasicCircuitsJob::encode_key(l1_batch_number);
+        let data = self
+            .blob_store
+            .get_raw(Bucket::WitnessInput, &key)
+            .await
+            .map_err(RequestProcessorError::ObjectStore)?;
+        let chunk_size = self.config.witness_chunk_size();
+        let info = tokio::task::spawn_blocking(move || WitnessInfo::new(&data, chunk_size))
+            .await
+            .expect("computing witness chunk checksums panicked");
+        let info = Arc::new(info);
+        self.witness_info_cache
+            .insert(l1_batch_number, info.clone());
+        Ok(info)
+    }
+
+    async fn ensure_picked_by_prover(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> Result<(), RequestProcessorError> {
+        let is_picked = self
+            .pool
+            .access_storage()
+            .await
+            .unwrap()
+            .proof_generation_dal()
+            .is_picked_by_prover(l1_batch_number)
+            .await
+            .map_err(RequestProcessorError::Sqlx)?;
+        if is_picked {
+            Ok(())
+        } else {
+            Err(RequestProcessorError::NotPickedByProver)
         }
     }
 
@@ -101,20 +211,7 @@ impl RequestProcessor {
             .await
             .map_err(RequestProcessorError::ObjectStore)?;
 
-        let fri_protocol_version_id =
-            FriProtocolVersionId::try_from(self.config.fri_protocol_version_id)
-                .expect("Invalid FRI protocol version id");
-
-        let l1_verifier_config= match self.config.protocol_version_loading_mode {
-            ProtocolVersionLoadingMode::FromDb => {
-                panic!("Loading protocol version from db is not implemented yet")
-            }
-            ProtocolVersionLoadingMode::FromEnvVar => {
-                self.l1_verifier_config
-                    .expect("l1_verifier_config must be set while running ProtocolVersionLoadingMode::FromEnvVar mode")
-            }
-        };
-
+        let (fri_protocol_version_id, l1_verifier_config) = self.protocol_params();
         let proof_gen_data = ProofGenerationData {
             l1_batch_number,
             data: blob,
@@ -125,13 +222,281 @@ impl RequestProcessor {
         Ok(Json(ProofGenerationDataResponse::Success(proof_gen_data)))
     }
 
+    /// Same as [`Self::get_proof_generation_data()`], but returns only metadata of the witness input.
+    /// The input itself should be downloaded using [`Self::get_proof_generation_data_range()`].
+    pub(crate) async fn get_proof_generation_data_info(
+        &self,
+        request: Json<ProofGenerationDataRequest>,
+    ) -> Result<Json<ProofGenerationDataInfoResponse>, RequestProcessorError> {
+        tracing::info!(
+            "Received request for proof generation data info: {:?}",
+            request
+        );
+
+        let l1_batch_number = self
+            .pool
+            .access_storage()
+            .await
+            .unwrap()
+            .proof_generation_dal()
+            .get_next_block_to_be_proven(self.config.proof_generation_timeout())
+            .await
+            .ok_or(RequestProcessorError::NoPendingBatches)?;
+
+        let witness_info = self.witness_info(l1_batch_number).await?;
+        let (fri_protocol_version_id, l1_verifier_config) = self.protocol_params();
+        let info = ProofGenerationDataInfo {
+            l1_batch_number,
+            data_size: witness_info.size,
+            chunk_size: witness_info.chunk_size,
+            chunk_checksums: witness_info.chunk_checksums.clone(),
+            fri_protocol_version_id,
+            l1_verifier_config,
+        };
+        Ok(Json(ProofGenerationDataInfoResponse::Success(info)))
+    }
+
+    /// Returns the serialized witness input for the specified L1 batch. Supports single-range
+    /// `Range` requests, so that the input can be downloaded in chunks and a failed download
+    /// can be resumed. Ranges are read from the object store, so the input is never held in memory
+    /// in full, except for computing chunk checksums once per L1 batch. The L1 batch must be
+    /// handed out to a prover.
+    pub(crate) async fn get_proof_generation_data_range(
+        &self,
+        Path(l1_batch_number): Path<u32>,
+        headers: HeaderMap,
+    ) -> Result<Response, RequestProcessorError> {
+        let l1_batch_number = L1BatchNumber(l1_batch_number);
+        self.ensure_picked_by_prover(l1_batch_number).await?;
+        let key = PrepareBasicCircuitsJob::encode_key(l1_batch_number);
+
+        let Some(range_header) = headers.get(header::RANGE) else {
+            let data = self
+                .blob_store
+                .get_raw(Bucket::WitnessInput, &key)
+                .await
+                .map_err(RequestProcessorError::ObjectStore)?;
+            let headers = [(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"))];
+            return Ok((StatusCode::OK, headers, data).into_response());
+        };
+        let data_size = self.witness_info(l1_batch_number).await?.size;
+        let range = range_header
+            .to_str()
+            .ok()
+            .and_then(|header| parse_byte_range(header, data_size))
+            .ok_or(RequestProcessorError::RangeNotSatisfiable { data_size })?;
+        tracing::debug!(
+            "Serving range {range:?} of witness input for L1 batch #{l1_batch_number} \
+             ({data_size} bytes)"
+        );
+
+        let content_range = format!("bytes {}-{}/{data_size}", range.start, range.end - 1);
+        let content_range =
+            HeaderValue::from_str(&content_range).expect("invalid Content-Range header");
+        let headers = [
+            (header::ACCEPT_RANGES, HeaderValue::from_static("bytes")),
+            (header::CONTENT_RANGE, content_range),
+        ];
+        let chunk = self
+            .blob_store
+            .get_raw_range(Bucket::WitnessInput, &key, range)
+            .await
+            .map_err(RequestProcessorError::ObjectStore)?;
+        Ok((StatusCode::PARTIAL_CONTENT, headers, chunk).into_response())
+    }
+
     pub(crate) async fn submit_proof(
         &self,
         Path(l1_batch_number): Path<u32>,
         Json(payload): Json<SubmitProofRequest>,
     ) -> Result<Json<SubmitProofResponse>, RequestProcessorError> {
         tracing::info!("Received proof for block number: {:?}", l1_batch_number);
+        self.save_proof(L1BatchNumber(l1_batch_number), payload)
+            .await?;
+        Ok(Json(SubmitProofResponse::Success))
+    }
+
+    /// Accepts a chunk of a JSON-serialized [`SubmitProofRequest`]. Chunks are persisted in the object store
+    /// together with the upload state. Once all chunks are received, the request is assembled
+    /// and processed in the same way as in [`Self::submit_proof()`]. The L1 batch must be handed out
+    /// to a prover.
+    pub(crate) async fn submit_proof_chunk(
+        &self,
+        Path((l1_batch_number, chunk_index)): Path<(u32, u32)>,
+        Query(params): Query<SubmitProofChunkParams>,
+        body: Bytes,
+    ) -> Result<Json<SubmitProofChunkResponse>, RequestProcessorError> {
+        tracing::debug!(
+            "Received proof chunk #{chunk_index} out of {} for block number: {l1_batch_number}",
+            params.total_chunks
+        );
+        let l1_batch_number = L1BatchNumber(l1_batch_number);
+        validate_proof_chunk(chunk_index, params.total_chunks, params.checksum, &body)
+            .map_err(RequestProcessorError::InvalidProofChunk)?;
+        self.ensure_picked_by_prover(l1_batch_number).await?;
+        self.blob_store
+            .put_raw(
+                Bucket::ProofUploads,
+                &proof_chunk_key(l1_batch_number, chunk_index),
+                body.to_vec(),
+            )
+            .await
+            .map_err(RequestProcessorError::ObjectStore)?;
+
+        let mut uploads = self.proof_uploads.lock().await;
+        let now = seconds_since_epoch();
+        let expired_uploads = uploads.remove_expired(now);
+        self.restore_proof_upload(&mut uploads, l1_batch_number, now)
+            .await?;
+        let status = uploads
+            .add_chunk(l1_batch_number, chunk_index, params.total_chunks, now)
+            .map_err(RequestProcessorError::InvalidProofChunk)?;
+        if let ProofUploadStatus::Incomplete { .. } = &status {
+            // The upload state is saved under the lock, so that concurrent chunks don't overwrite it
+            // with an outdated state.
+            let upload = uploads.get(l1_batch_number).unwrap();
+            self.save_proof_upload(l1_batch_number, upload).await?;
+        }
+        drop(uploads);
+
+        for (number, upload) in expired_uploads {
+            tracing::info!("Discarding expired proof upload for block number: {number}");
+            self.remove_proof_upload(number, &upload).await;
+        }
+
+        let missing_chunks = match status {
+            ProofUploadStatus::Incomplete { missing_chunks } => missing_chunks,
+            ProofUploadStatus::Complete(upload) => {
+                tracing::info!("Received all proof chunks for block number: {l1_batch_number}");
+                let payload = self.assemble_proof(l1_batch_number, &upload).await;
+                // If chunks cannot be fetched, the upload state is retained in the object store,
+                // so that the upload can be completed by resending the last chunk.
+                if !matches!(payload, Err(RequestProcessorError::ObjectStore(_))) {
+                    self.remove_proof_upload(l1_batch_number, &upload).await;
+                }
+                self.save_proof(l1_batch_number, payload?).await?;
+                vec![]
+            }
+        };
+        Ok(Json(SubmitProofChunkResponse::Success { missing_chunks }))
+    }
+
+    /// Returns chunks missing from the proof upload for the specified L1 batch, allowing a prover
+    /// to resume an interrupted upload.
+    pub(crate) async fn get_proof_upload_status(
+        &self,
+        Path(l1_batch_number): Path<u32>,
+    ) -> Result<Json<SubmitProofChunkResponse>, RequestProcessorError> {
         let l1_batch_number = L1BatchNumber(l1_batch_number);
+        let mut uploads = self.proof_uploads.lock().await;
+        let now = seconds_since_epoch();
+        let expired_uploads = uploads.remove_expired(now);
+        self.restore_proof_upload(&mut uploads, l1_batch_number, now)
+            .await?;
+        let missing_chunks = uploads
+            .get(l1_batch_number)
+            .map(ProofUpload::missing_chunks);
+        drop(uploads);
+
+        for (number, upload) in expired_uploads {
+            tracing::info!("Discarding expired proof upload for block number: {number}");
+            self.remove_proof_upload(number, &upload).await;
+        }
+        let missing_chunks = missing_chunks.ok_or(RequestProcessorError::NoProofUpload)?;
+        Ok(Json(SubmitProofChunkResponse::Success { missing_chunks }))
+    }
+
+    /// Loads the upload state for the specified L1 batch from the object store if it's not in memory
+    /// (e.g., after a restart).
+    async fn restore_proof_upload(
+        &self,
+        uploads: &mut ProofUploads,
+        l1_batch_number: L1BatchNumber,
+        now: u64,
+    ) -> Result<(), RequestProcessorError> {
+        if uploads.get(l1_batch_number).is_some() {
+            return Ok(());
+        }
+        let key = proof_upload_key(l1_batch_number);
+        let upload = match self.blob_store.get_raw(Bucket::ProofUploads, &key).await {
+            Ok(upload) => upload,
+            Err(ObjectStoreError::KeyNotFound(_)) => return Ok(()),
+            Err(err) => return Err(RequestProcessorError::ObjectStore(err)),
+        };
+        let upload: ProofUpload = match serde_json::from_slice(&upload) {
+            Ok(upload) => upload,
+            Err(err) => {
+                tracing::warn!(
+                    "Discarding malformed proof upload state for block number {l1_batch_number}: {err}"
+                );
+                return Ok(());
+            }
+        };
+        if !uploads.restore(l1_batch_number, upload.clone(), now) {
+            tracing::info!("Discarding expired proof upload for block number: {l1_batch_number}");
+            self.remove_proof_upload(l1_batch_number, &upload).await;
+        }
+        Ok(())
+    }
+
+    async fn save_proof_upload(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        upload: &ProofUpload,
+    ) -> Result<(), RequestProcessorError> {
+        let upload = serde_json::to_vec(upload).expect("failed serializing proof upload");
+        self.blob_store
+            .put_raw(
+                Bucket::ProofUploads,
+                &proof_upload_key(l1_batch_number),
+                upload,
+            )
+            .await
+            .map_err(RequestProcessorError::ObjectStore)
+    }
+
+    /// Removes the upload state and chunks from the object store. Errors are logged, since removal
+    /// doesn't influence correctness.
+    async fn remove_proof_upload(&self, l1_batch_number: L1BatchNumber, upload: &ProofUpload) {
+        let keys = upload
+            .stored_chunks()
+            .map(|chunk_index| proof_chunk_key(l1_batch_number, chunk_index))
+            .chain([proof_upload_key(l1_batch_number)]);
+        for key in keys {
+            match self.blob_store.remove_raw(Bucket::ProofUploads, &key).await {
+                Ok(()) | Err(ObjectStoreError::KeyNotFound(_)) => {}
+                Err(err) => tracing::warn!("Failed removing proof upload object `{key}`: {err}"),
+            }
+        }
+    }
+
+    async fn assemble_proof(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        upload: &ProofUpload,
+    ) -> Result<SubmitProofRequest, RequestProcessorError> {
+        let mut payload = vec![];
+        for chunk_index in 0..upload.total_chunks() {
+            let key = proof_chunk_key(l1_batch_number, chunk_index);
+            let chunk = self
+                .blob_store
+                .get_raw(Bucket::ProofUploads, &key)
+                .await
+                .map_err(RequestProcessorError::ObjectStore)?;
+            payload.extend_from_slice(&chunk);
+        }
+        serde_json::from_slice(&payload).map_err(|err| {
+            RequestProcessorError::InvalidProofChunk(format!(
+                "Cannot deserialize assembled proof: {err}"
+            ))
+        })
+    }
+
+    async fn save_proof(
+        &self,
+        l1_batch_number: L1BatchNumber,
+        payload: SubmitProofRequest,
+    ) -> Result<(), RequestProcessorError> {
         match payload {
             SubmitProofRequest::Proof(proof) => {
                 let blob_url = self
@@ -158,7 +523,6 @@ impl RequestProcessor {
                     .map_err(RequestProcessorError::Sqlx)?;
             }
         }
-
-        Ok(Json(SubmitProofResponse::Success))
+        Ok(())
     }
 }
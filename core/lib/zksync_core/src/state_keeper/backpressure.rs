@@ -0,0 +1,243 @@
+//! Back-pressure from the downstream pipeline (Merkle tree, proofs, L1 commits) into the state keeper.
+//!
+//! If the number of sealed L1 batches not yet processed by a downstream component exceeds the configured limit,
+//! the state keeper stops accepting L2 transactions (the current L1 batch is still sealed by timeout),
+//! so that sequenced state doesn't diverge from finalized state indefinitely. L1 priority operations are still
+//! executed since they have deadlines on L1, and their rate is limited by L1 anyway.
+
+use anyhow::Context as _;
+use tokio::sync::watch;
+
+use std::{fmt, time::Duration};
+
+use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_types::L1BatchNumber;
+
+/// Interval between pipeline lag checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Downstream component that may lag behind the state keeper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LaggingComponent {
+    Tree,
+    Prover,
+    EthSender,
+}
+
+impl LaggingComponent {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Tree => "tree",
+            Self::Prover => "prover",
+            Self::EthSender => "eth_sender",
+        }
+    }
+}
+
+impl fmt::Display for LaggingComponent {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+/// Limits on the number of sealed L1 batches not processed by downstream components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct PipelineLagLimits {
+    pub without_metadata: Option<u32>,
+    pub without_proof: Option<u32>,
+    pub uncommitted: Option<u32>,
+}
+
+impl PipelineLagLimits {
+    pub fn new(config: &StateKeeperConfig) -> Self {
+        Self {
+            without_metadata: config.max_l1_batches_without_metadata,
+            without_proof: config.max_l1_batches_without_proof,
+            uncommitted: config.max_uncommitted_l1_batches,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.without_metadata.is_some()
+            || self.without_proof.is_some()
+            || self.uncommitted.is_some()
+    }
+}
+
+/// Number of sealed L1 batches not processed by each of downstream components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PipelineLag {
+    pub without_metadata: u32,
+    pub without_proof: u32,
+    pub uncommitted: u32,
+}
+
+impl PipelineLag {
+    async fn load(storage: &mut StorageProcessor<'_>) -> anyhow::Result<Self> {
+        let mut blocks_dal = storage.blocks_dal();
+        let sealed = blocks_dal
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        let with_metadata = blocks_dal
+            .get_last_l1_batch_number_with_metadata()
+            .await
+            .context("get_last_l1_batch_number_with_metadata()")?;
+        let committed = blocks_dal
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_committed_on_eth()")?
+            .unwrap_or(L1BatchNumber(0));
+        // Prover lag is measured from the proof generation state rather than from L1 prove transactions,
+        // which also require L1 batches to be committed (i.e., would include the eth_sender lag).
+        // If proof generation isn't tracked (e.g., proofs aren't generated for the chain), there's no prover lag.
+        let proven = storage
+            .proof_generation_dal()
+            .get_last_proven_l1_batch()
+            .await
+            .context("get_last_proven_l1_batch()")?;
+
+        Ok(Self {
+            without_metadata: sealed.0.saturating_sub(with_metadata.0),
+            without_proof: proven.map_or(0, |proven| sealed.0.saturating_sub(proven.0)),
+            uncommitted: sealed.0.saturating_sub(committed.0),
+        })
+    }
+
+    /// Returns the first component exceeding its lag limit, checking components in the pipeline order.
+    fn lagging_component(&self, limits: &PipelineLagLimits) -> Option<LaggingComponent> {
+        let exceeds = |lag: u32, limit: Option<u32>| limit.map_or(false, |limit| lag > limit);
+        if exceeds(self.without_metadata, limits.without_metadata) {
+            Some(LaggingComponent::Tree)
+        } else if exceeds(self.without_proof, limits.without_proof) {
+            Some(LaggingComponent::Prover)
+        } else if exceeds(self.uncommitted, limits.uncommitted) {
+            Some(LaggingComponent::EthSender)
+        } else {
+            None
+        }
+    }
+}
+
+/// Handle allowing the state keeper to check whether it should stop accepting transactions.
+#[derive(Debug, Clone)]
+pub(crate) struct BackpressureHandle(watch::Receiver<Option<LaggingComponent>>);
+
+impl BackpressureHandle {
+    /// Returns the component the state keeper is waiting for, or `None` if the state keeper is not throttled.
+    pub fn lagging_component(&self) -> Option<LaggingComponent> {
+        *self.0.borrow()
+    }
+}
+
+#[cfg(test)]
+impl BackpressureHandle {
+    /// Creates a handle controlled by the returned sender rather than by a [`BackpressureMonitor`].
+    pub(crate) fn mock() -> (watch::Sender<Option<LaggingComponent>>, Self) {
+        let (sender, receiver) = watch::channel(None);
+        (sender, Self(receiver))
+    }
+}
+
+/// Component periodically checking the pipeline lag.
+#[derive(Debug)]
+pub(crate) struct BackpressureMonitor {
+    pool: ConnectionPool,
+    limits: PipelineLagLimits,
+    sender: watch::Sender<Option<LaggingComponent>>,
+}
+
+impl BackpressureMonitor {
+    pub fn new(pool: ConnectionPool, limits: PipelineLagLimits) -> (Self, BackpressureHandle) {
+        let (sender, receiver) = watch::channel(None);
+        let this = Self {
+            pool,
+            limits,
+            sender,
+        };
+        (this, BackpressureHandle(receiver))
+    }
+
+    pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        loop {
+            if *stop_receiver.borrow() {
+                tracing::info!("Stop signal received, backpressure monitor is shutting down");
+                break;
+            }
+
+            let mut storage = self.pool.access_storage_tagged("state_keeper").await?;
+            let lag = PipelineLag::load(&mut storage).await?;
+            drop(storage);
+            let stages = [
+                ("tree", lag.without_metadata),
+                ("prover", lag.without_proof),
+                ("eth_sender", lag.uncommitted),
+            ];
+            for (stage, lag) in stages {
+                metrics::gauge!("server.state_keeper.pipeline_lag", lag as f64, "stage" => stage);
+            }
+
+            let lagging_component = lag.lagging_component(&self.limits);
+            let prev_lagging_component = *self.sender.borrow();
+            if lagging_component != prev_lagging_component {
+                match lagging_component {
+                    Some(component) => tracing::warn!(
+                        "Pausing transaction intake in state keeper since {component} lags behind: \
+                         {lag:?}, limits: {:?}",
+                        self.limits
+                    ),
+                    None => tracing::info!("Resuming transaction intake in state keeper: {lag:?}"),
+                }
+                self.sender.send_replace(lagging_component);
+            }
+            let throttled = if lagging_component.is_some() {
+                1.0
+            } else {
+                0.0
+            };
+            metrics::gauge!("server.state_keeper.backpressure", throttled);
+
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detecting_lagging_component() {
+        let lag = PipelineLag {
+            without_metadata: 2,
+            without_proof: 20,
+            uncommitted: 10,
+        };
+        assert_eq!(lag.lagging_component(&PipelineLagLimits::default()), None);
+
+        let limits = PipelineLagLimits {
+            without_metadata: Some(2),
+            without_proof: None,
+            uncommitted: Some(10),
+        };
+        assert_eq!(lag.lagging_component(&limits), None);
+
+        let limits = PipelineLagLimits {
+            uncommitted: Some(5),
+            ..limits
+        };
+        assert_eq!(
+            lag.lagging_component(&limits),
+            Some(LaggingComponent::EthSender)
+        );
+
+        let limits = PipelineLagLimits {
+            without_metadata: Some(1),
+            without_proof: Some(10),
+            uncommitted: Some(5),
+        };
+        assert_eq!(lag.lagging_component(&limits), Some(LaggingComponent::Tree));
+    }
+}
@@ -1,3 +1,5 @@
+use anyhow::Context as _;
+
 use std::fmt;
 use std::sync::Arc;
 use std::time::Instant;
@@ -15,8 +17,14 @@ use vm::{
     SystemEnv, VmExecutionResultAndLogs,
 };
 use zksync_dal::ConnectionPool;
-use zksync_state::{ReadStorage, RocksdbStorage, StorageView};
-use zksync_types::{vm_trace::Call, witness_block_state::WitnessBlockState, Transaction, U256};
+use zksync_state::{
+    ForkErrorSlot, ForkStorage, ForkedState, ReadStorage, RocksdbCatchUpMode, RocksdbStorage,
+    StorageView,
+};
+use zksync_types::{
+    vm_trace::Call, witness_block_state::WitnessBlockState, L1BatchNumber, MiniblockNumber,
+    Transaction, U256,
+};
 
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
@@ -24,8 +32,10 @@ use zksync_utils::bytecode::CompressedBytecodeInfo;
 mod tests;
 
 use crate::{
-    gas_tracker::{gas_count_from_metrics, gas_count_from_tx_and_metrics},
-    state_keeper::types::ExecutionMetricsForCriteria,
+    execution_diff::diff_executions,
+    gas_tracker::{gas_count_from_metrics, gas_count_from_tx_and_metrics, PubdataDaMode},
+    state_keeper::{execution_hints::TxExecutionHints, types::ExecutionMetricsForCriteria},
+    vm_thread_pool::VmThreadPool,
 };
 
 /// Representation of a transaction executed in the virtual machine.
@@ -75,6 +85,14 @@ pub trait L1BatchExecutorBuilder: 'static + Send + Sync + fmt::Debug {
     ) -> BatchExecutorHandle;
 }
 
+/// Factory of storage backends for batches executed by [`MainBatchExecutorBuilder`]. Allows executing
+/// batches on top of storage other than the state keeper RocksDB cache, e.g. in-memory or remote storage.
+#[async_trait]
+pub trait L1BatchStorageFactory: 'static + Send + Sync + fmt::Debug {
+    /// Creates storage reflecting the state before the specified L1 batch.
+    async fn create_storage(&self, l1_batch_number: L1BatchNumber) -> Box<dyn ReadStorage + Send>;
+}
+
 /// The default implementation of [`L1BatchExecutorBuilder`].
 /// Creates a "real" batch executor which maintains the VM (as opposed to the test builder which doesn't use the VM).
 #[derive(Debug, Clone)]
@@ -84,6 +102,12 @@ pub struct MainBatchExecutorBuilder {
     save_call_traces: bool,
     max_allowed_tx_gas_limit: U256,
     upload_witness_inputs_to_gcs: bool,
+    pubdata_da_mode: PubdataDaMode,
+    thread_pool: VmThreadPool,
+    execution_hints: Option<TxExecutionHints>,
+    rocksdb_catch_up_mode: RocksdbCatchUpMode,
+    storage_factory: Option<Arc<dyn L1BatchStorageFactory>>,
+    fork: Option<ForkedState>,
 }
 
 impl MainBatchExecutorBuilder {
@@ -93,6 +117,7 @@ impl MainBatchExecutorBuilder {
         max_allowed_tx_gas_limit: U256,
         save_call_traces: bool,
         upload_witness_inputs_to_gcs: bool,
+        pubdata_da_mode: PubdataDaMode,
     ) -> Self {
         Self {
             state_keeper_db_path,
@@ -100,37 +125,103 @@ impl MainBatchExecutorBuilder {
             save_call_traces,
             max_allowed_tx_gas_limit,
             upload_witness_inputs_to_gcs,
+            pubdata_da_mode,
+            thread_pool: VmThreadPool::default(),
+            execution_hints: None,
+            rocksdb_catch_up_mode: RocksdbCatchUpMode::default(),
+            storage_factory: None,
+            fork: None,
         }
     }
-}
 
-#[async_trait]
-impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
-    async fn init_batch(
-        &self,
-        l1_batch_params: L1BatchEnv,
-        system_env: SystemEnv,
-    ) -> BatchExecutorHandle {
-        let mut secondary_storage = RocksdbStorage::new(self.state_keeper_db_path.as_ref());
+    /// Sets the mode used to catch up the state keeper cache with Postgres.
+    #[must_use]
+    pub fn with_rocksdb_catch_up_mode(mut self, mode: RocksdbCatchUpMode) -> Self {
+        self.rocksdb_catch_up_mode = mode;
+        self
+    }
+
+    /// Sets the factory of storage to execute batches on. If not called, batches are executed
+    /// on the RocksDB cache at `state_keeper_db_path`, which is caught up with Postgres before each batch.
+    #[must_use]
+    pub fn with_storage_factory(mut self, factory: Arc<dyn L1BatchStorageFactory>) -> Self {
+        self.storage_factory = Some(factory);
+        self
+    }
+
+    /// Sets the cache of hints obtained during API admission of transactions. Hints are used to speed up
+    /// transaction execution; they are validated against the actual execution and thus cannot influence its result.
+    #[must_use]
+    pub fn with_execution_hints(mut self, execution_hints: TxExecutionHints) -> Self {
+        self.execution_hints = Some(execution_hints);
+        self
+    }
+
+    /// Sets the remote state to lazily fetch storage slots and bytecodes missing from the state keeper cache.
+    /// Only used in the dev mode; see [`crate::dev_mode`] for details.
+    #[must_use]
+    pub fn with_fork(mut self, fork: ForkedState) -> Self {
+        self.fork = Some(fork);
+        self
+    }
+
+    /// Sets the thread pool to run batch executors on. If not called, batch executors
+    /// are run on the blocking tokio threadpool.
+    #[must_use]
+    pub fn with_thread_pool(mut self, thread_pool: VmThreadPool) -> Self {
+        self.thread_pool = thread_pool;
+        self
+    }
+
+    async fn load_rocksdb_storage(&self) -> RocksdbStorage {
+        let mut secondary_storage = RocksdbStorage::new(self.state_keeper_db_path.as_ref())
+            .with_catch_up_mode(self.rocksdb_catch_up_mode);
         let mut conn = self
             .pool
             .access_storage_tagged("state_keeper")
             .await
             .unwrap();
         secondary_storage.update_from_postgres(&mut conn).await;
-        drop(conn);
+        secondary_storage
+    }
 
+    fn create_handle<S: ReadStorage + Send + 'static>(
+        &self,
+        secondary_storage: S,
+        l1_batch_params: L1BatchEnv,
+        system_env: SystemEnv,
+    ) -> BatchExecutorHandle {
         BatchExecutorHandle::new(
             self.save_call_traces,
             self.max_allowed_tx_gas_limit,
-            secondary_storage,
+            ForkStorage::new(secondary_storage, self.fork.clone()),
             l1_batch_params,
             system_env,
             self.upload_witness_inputs_to_gcs,
+            self.pubdata_da_mode,
+            &self.thread_pool,
+            self.execution_hints.clone(),
         )
     }
 }
 
+#[async_trait]
+impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
+    async fn init_batch(
+        &self,
+        l1_batch_params: L1BatchEnv,
+        system_env: SystemEnv,
+    ) -> BatchExecutorHandle {
+        if let Some(factory) = &self.storage_factory {
+            let secondary_storage = factory.create_storage(l1_batch_params.number).await;
+            self.create_handle(secondary_storage, l1_batch_params, system_env)
+        } else {
+            let secondary_storage = self.load_rocksdb_storage().await;
+            self.create_handle(secondary_storage, l1_batch_params, system_env)
+        }
+    }
+}
+
 /// A public interface for interaction with the `BatchExecutor`.
 /// `BatchExecutorHandle` is stored in the state keeper and is used to invoke or rollback transactions, and also seal
 /// the batches.
@@ -138,29 +229,39 @@ impl L1BatchExecutorBuilder for MainBatchExecutorBuilder {
 pub struct BatchExecutorHandle {
     handle: JoinHandle<()>,
     commands: mpsc::Sender<Command>,
+    /// Errors fetching state from the fork in the dev mode.
+    fork_errors: ForkErrorSlot,
 }
 
 impl BatchExecutorHandle {
     // TODO: to be removed once testing in stage2 is done
     #[allow(clippy::too_many_arguments)]
-    pub(super) fn new(
+    pub(super) fn new<S: ReadStorage + Send + 'static>(
         save_call_traces: bool,
         max_allowed_tx_gas_limit: U256,
-        secondary_storage: RocksdbStorage,
+        secondary_storage: ForkStorage<S>,
         l1_batch_env: L1BatchEnv,
         system_env: SystemEnv,
         upload_witness_inputs_to_gcs: bool,
+        pubdata_da_mode: PubdataDaMode,
+        thread_pool: &VmThreadPool,
+        execution_hints: Option<TxExecutionHints>,
     ) -> Self {
         // Since we process `BatchExecutor` commands one-by-one (the next command is never enqueued
         // until a previous command is processed), capacity 1 is enough for the commands channel.
         let (commands_sender, commands_receiver) = mpsc::channel(1);
+        let fork_errors = secondary_storage.error_slot();
         let executor = BatchExecutor {
             save_call_traces,
             max_allowed_tx_gas_limit,
+            pubdata_da_mode,
+            execution_hints,
+            current_miniblock: MiniblockNumber(l1_batch_env.first_l2_block.number),
+            tx_snapshots_in_miniblock: 0,
             commands: commands_receiver,
         };
 
-        let handle = tokio::task::spawn_blocking(move || {
+        let handle = thread_pool.spawn(move || {
             executor.run(
                 secondary_storage,
                 l1_batch_env,
@@ -171,6 +272,7 @@ impl BatchExecutorHandle {
         Self {
             handle,
             commands: commands_sender,
+            fork_errors,
         }
     }
 
@@ -178,10 +280,17 @@ impl BatchExecutorHandle {
     /// Can be used to inject an alternative batch executor implementation.
     #[cfg(test)]
     pub(super) fn from_raw(handle: JoinHandle<()>, commands: mpsc::Sender<Command>) -> Self {
-        Self { handle, commands }
+        Self {
+            handle,
+            commands,
+            fork_errors: ForkErrorSlot::default(),
+        }
     }
 
-    pub(super) async fn execute_tx(&self, tx: Transaction) -> TxExecutionResult {
+    /// Executes a transaction. Returns an error if the state necessary for execution could not be fetched
+    /// from the fork; in this case, the execution result is meaningless, and the batch should be restarted.
+    pub(super) async fn execute_tx(&self, tx: Transaction) -> anyhow::Result<TxExecutionResult> {
+        let tx_hash = tx.hash();
         let tx_gas_limit = tx.gas_limit().as_u32();
 
         let (response_sender, response_receiver) = oneshot::channel();
@@ -193,6 +302,9 @@ impl BatchExecutorHandle {
         let start = Instant::now();
         let res = response_receiver.await.unwrap();
         let elapsed = start.elapsed();
+        self.fork_errors
+            .check()
+            .with_context(|| format!("failed executing transaction {tx_hash:?}"))?;
 
         metrics::histogram!("state_keeper.batch_executor.command_response_time", elapsed, "command" => "execute_tx");
 
@@ -212,7 +324,7 @@ impl BatchExecutorHandle {
             );
         }
 
-        res
+        Ok(res)
     }
 
     pub(super) async fn start_next_miniblock(&self, miniblock_info: L2BlockEnv) {
@@ -241,6 +353,19 @@ impl BatchExecutorHandle {
         metrics::histogram!("state_keeper.batch_executor.command_response_time", start.elapsed(), "command" => "rollback_last_tx");
     }
 
+    /// Rolls back all transactions executed in the current miniblock. The miniblock itself stays open,
+    /// so new transactions can be executed in it.
+    pub async fn rollback_to_miniblock_start(&self) {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.commands
+            .send(Command::RollbackToMiniblockStart(response_sender))
+            .await
+            .unwrap();
+        let start = Instant::now();
+        response_receiver.await.unwrap();
+        metrics::histogram!("state_keeper.batch_executor.command_response_time", start.elapsed(), "command" => "rollback_to_miniblock_start");
+    }
+
     pub(super) async fn finish_batch(self) -> (FinishedL1Batch, Option<WitnessBlockState>) {
         let (response_sender, response_receiver) = oneshot::channel();
         self.commands
@@ -260,6 +385,7 @@ pub(super) enum Command {
     ExecuteTx(Box<Transaction>, oneshot::Sender<TxExecutionResult>),
     StartNextMiniblock(L2BlockEnv, oneshot::Sender<()>),
     RollbackLastTx(oneshot::Sender<()>),
+    RollbackToMiniblockStart(oneshot::Sender<()>),
     FinishBatch(oneshot::Sender<(FinishedL1Batch, Option<WitnessBlockState>)>),
 }
 
@@ -273,13 +399,19 @@ pub(super) enum Command {
 pub(super) struct BatchExecutor {
     save_call_traces: bool,
     max_allowed_tx_gas_limit: U256,
+    pubdata_da_mode: PubdataDaMode,
+    execution_hints: Option<TxExecutionHints>,
+    current_miniblock: MiniblockNumber,
+    /// Number of VM snapshots taken for transactions since the start of the current miniblock.
+    /// These snapshots are stacked on top of the snapshot taken at the miniblock start.
+    tx_snapshots_in_miniblock: usize,
     commands: mpsc::Receiver<Command>,
 }
 
 impl BatchExecutor {
-    pub(super) fn run(
+    pub(super) fn run<S: ReadStorage>(
         mut self,
-        secondary_storage: RocksdbStorage,
+        secondary_storage: ForkStorage<S>,
         l1_batch_params: L1BatchEnv,
         system_env: SystemEnv,
         upload_witness_inputs_to_gcs: bool,
@@ -291,17 +423,28 @@ impl BatchExecutor {
         let mut instance_data =
             VmInstanceData::new(storage_view.clone(), &system_env, HistoryEnabled);
         let mut vm = VmInstance::new(l1_batch_params, system_env, &mut instance_data);
+        // Snapshot for the first miniblock in the batch; snapshots for other miniblocks are taken
+        // when they are started.
+        vm.make_snapshot();
 
         while let Some(cmd) = self.commands.blocking_recv() {
             match cmd {
                 Command::ExecuteTx(tx, resp) => {
+                    #[cfg(feature = "profiling")]
+                    crate::profiling::tx_started();
                     let result = self.execute_tx(&tx, &mut vm);
+                    #[cfg(feature = "profiling")]
+                    crate::profiling::tx_finished();
                     resp.send(result).unwrap();
                 }
                 Command::RollbackLastTx(resp) => {
                     self.rollback_last_tx(&mut vm);
                     resp.send(()).unwrap();
                 }
+                Command::RollbackToMiniblockStart(resp) => {
+                    self.rollback_to_miniblock_start(&mut vm);
+                    resp.send(()).unwrap();
+                }
                 Command::StartNextMiniblock(l2_block_env, resp) => {
                     self.start_next_miniblock(l2_block_env, &mut vm);
                     resp.send(()).unwrap();
@@ -338,12 +481,13 @@ impl BatchExecutor {
     }
 
     fn execute_tx<S: ReadStorage>(
-        &self,
+        &mut self,
         tx: &Transaction,
         vm: &mut VmInstance<'_, S, HistoryEnabled>,
     ) -> TxExecutionResult {
         // Save pre-`execute_next_tx` VM snapshot.
         vm.make_snapshot();
+        self.tx_snapshots_in_miniblock += 1;
 
         // Reject transactions with too big gas limit.
         // They are also rejected on the API level, but
@@ -384,7 +528,7 @@ impl BatchExecutor {
             };
         }
 
-        let tx_metrics = Self::get_execution_metrics(Some(tx), &tx_result);
+        let tx_metrics = self.get_execution_metrics(Some(tx), &tx_result);
 
         let (bootloader_dry_run_result, bootloader_dry_run_metrics) = self.dryrun_block_tip(vm);
         match &bootloader_dry_run_result.result {
@@ -410,9 +554,10 @@ impl BatchExecutor {
         }
     }
 
-    fn rollback_last_tx<S: ReadStorage>(&self, vm: &mut VmInstance<'_, S, HistoryEnabled>) {
+    fn rollback_last_tx<S: ReadStorage>(&mut self, vm: &mut VmInstance<'_, S, HistoryEnabled>) {
         let stage_started_at = Instant::now();
         vm.rollback_to_the_latest_snapshot();
+        self.tx_snapshots_in_miniblock -= 1;
         metrics::histogram!(
             "server.state_keeper.tx_execution_time",
             stage_started_at.elapsed(),
@@ -420,12 +565,36 @@ impl BatchExecutor {
         );
     }
 
+    fn rollback_to_miniblock_start<S: ReadStorage>(
+        &mut self,
+        vm: &mut VmInstance<'_, S, HistoryEnabled>,
+    ) {
+        let stage_started_at = Instant::now();
+        // Rolling back to the miniblock snapshot reverts all changes made after it, so transaction snapshots
+        // can be discarded without rolling back to them.
+        for _ in 0..self.tx_snapshots_in_miniblock {
+            vm.pop_snapshot_no_rollback();
+        }
+        self.tx_snapshots_in_miniblock = 0;
+        vm.rollback_to_the_latest_snapshot();
+        // Retain the snapshot so that the miniblock can be rolled back again.
+        vm.make_snapshot();
+        metrics::histogram!(
+            "server.state_keeper.tx_execution_time",
+            stage_started_at.elapsed(),
+            "stage" => "miniblock_rollback"
+        );
+    }
+
     fn start_next_miniblock<S: ReadStorage>(
-        &self,
+        &mut self,
         l2_block_env: L2BlockEnv,
         vm: &mut VmInstance<'_, S, HistoryEnabled>,
     ) {
+        self.current_miniblock = MiniblockNumber(l2_block_env.number);
         vm.start_new_l2_block(l2_block_env);
+        vm.make_snapshot();
+        self.tx_snapshots_in_miniblock = 0;
     }
 
     fn finish_batch<S: ReadStorage>(
@@ -466,15 +635,43 @@ impl BatchExecutor {
         // Saving the snapshot before executing
         vm.make_snapshot();
 
+        // If bytecode compression failed when the transaction was simulated by the API server, the compression attempt
+        // will likely fail as well, so we don't set up tracers for it. If the hint turns out to be wrong, the attempt
+        // is repeated with tracers, so that the execution result doesn't depend on hints.
+        let expect_compression_failure =
+            self.save_call_traces && self.expect_compression_failure(tx);
         let call_tracer_result = Arc::new(OnceCell::default());
-        let custom_tracers = if self.save_call_traces {
+        let custom_tracers = if self.save_call_traces && !expect_compression_failure {
             vec![CallTracer::new(call_tracer_result.clone(), HistoryEnabled).into_boxed()]
         } else {
             vec![]
         };
-        if let Ok(result) =
+        if let Ok(mut result) =
             vm.inspect_transaction_with_bytecode_compression(custom_tracers, tx.clone(), true)
         {
+            if expect_compression_failure {
+                metrics::increment_counter!("server.state_keeper.execution_hints", "outcome" => "mispredicted");
+                vm.rollback_to_the_latest_snapshot();
+                vm.make_snapshot();
+                let custom_tracers =
+                    vec![CallTracer::new(call_tracer_result.clone(), HistoryEnabled).into_boxed()];
+                let retried_result = vm
+                    .inspect_transaction_with_bytecode_compression(custom_tracers, tx.clone(), true)
+                    .expect(
+                        "Bytecode compression succeeded on previous attempt, but failed on retry",
+                    );
+                // Tracers must not influence execution, so both executions should be equivalent. If they aren't,
+                // the retried result is used since it corresponds to the execution with tracers.
+                let diff = diff_executions(&result, &retried_result);
+                if !diff.is_empty() {
+                    metrics::increment_counter!("server.state_keeper.diverged_reexecutions");
+                    tracing::error!(
+                        "Re-execution of transaction {:?} diverged from the initial execution: {diff:?}",
+                        tx.hash()
+                    );
+                }
+                result = retried_result;
+            }
             let compressed_bytecodes = vm.get_last_tx_compressed_bytecodes();
             vm.pop_snapshot_no_rollback();
 
@@ -485,6 +682,9 @@ impl BatchExecutor {
             return (result, compressed_bytecodes, trace);
         }
 
+        if expect_compression_failure {
+            metrics::increment_counter!("server.state_keeper.execution_hints", "outcome" => "hit");
+        }
         let call_tracer_result = Arc::new(OnceCell::default());
         let custom_tracers = if self.save_call_traces {
             vec![CallTracer::new(call_tracer_result.clone(), HistoryEnabled).into_boxed()]
@@ -506,6 +706,26 @@ impl BatchExecutor {
         (result, compressed_bytecodes, trace)
     }
 
+    /// Checks whether the bytecode compression attempt for the transaction is expected to fail
+    /// based on the API simulation hint.
+    fn expect_compression_failure(&self, tx: &Transaction) -> bool {
+        let has_factory_deps = tx
+            .execute
+            .factory_deps
+            .as_ref()
+            .map_or(false, |deps| !deps.is_empty());
+        if !has_factory_deps {
+            // Compression cannot fail if there are no bytecodes to publish.
+            return false;
+        }
+        let Some(execution_hints) = &self.execution_hints else {
+            return false;
+        };
+        execution_hints
+            .take(tx.hash(), self.current_miniblock)
+            .map_or(false, |hint| hint.bytecode_compression_failed)
+    }
+
     fn dryrun_block_tip<S: ReadStorage>(
         &self,
         vm: &mut VmInstance<'_, S, HistoryEnabled>,
@@ -532,7 +752,7 @@ impl BatchExecutor {
         );
         stage_started_at = Instant::now();
 
-        let metrics = Self::get_execution_metrics(None, &block_tip_result);
+        let metrics = self.get_execution_metrics(None, &block_tip_result);
 
         metrics::histogram!(
             "server.state_keeper.tx_execution_time",
@@ -560,13 +780,14 @@ impl BatchExecutor {
     }
 
     fn get_execution_metrics(
+        &self,
         tx: Option<&Transaction>,
         execution_result: &VmExecutionResultAndLogs,
     ) -> ExecutionMetricsForCriteria {
         let execution_metrics = execution_result.get_execution_metrics(tx);
         let l1_gas = match tx {
-            Some(tx) => gas_count_from_tx_and_metrics(tx, &execution_metrics),
-            None => gas_count_from_metrics(&execution_metrics),
+            Some(tx) => gas_count_from_tx_and_metrics(tx, &execution_metrics, self.pubdata_da_mode),
+            None => gas_count_from_metrics(&execution_metrics, self.pubdata_da_mode),
         };
 
         ExecutionMetricsForCriteria {
@@ -1,14 +1,22 @@
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use db_test_macro::db_test;
 
+use std::sync::{Arc, Mutex};
+
 use zksync_dal::ConnectionPool;
-use zksync_types::PriorityOpId;
+use zksync_state::ReadStorage;
+use zksync_types::{Address, L1BatchNumber, MiniblockNumber, PriorityOpId};
 
 mod tester;
 
 use self::tester::Tester;
-use super::TxExecutionResult;
-use crate::state_keeper::batch_executor::tests::tester::{AccountLoadNextExecutable, TestConfig};
+use super::{L1BatchStorageFactory, TxExecutionResult};
+use crate::state_keeper::batch_executor::tests::tester::{
+    funded_in_memory_storage, AccountLoadNextExecutable, TestConfig,
+};
+use crate::state_keeper::execution_hints::{TxExecutionHint, TxExecutionHints};
+use crate::vm_thread_pool::VmThreadPool;
 
 use zksync_test_account::Account;
 
@@ -43,9 +51,86 @@ async fn execute_l2_tx(connection_pool: ConnectionPool) {
     tester.fund(&[alice.address()]).await;
     let executor = tester.create_batch_executor().await;
 
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
+    assert_executed(&res);
+    executor.finish_batch().await;
+}
+
+/// Checks that the batch executor can be run on a dedicated VM thread pool.
+#[db_test]
+async fn execute_l2_tx_on_dedicated_thread_pool(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+
+    let mut config = TestConfig::new();
+    config.thread_pool = VmThreadPool::new("test", 1, &[]).unwrap();
+    let tester = Tester::with_config(connection_pool, config);
+
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
+    assert_executed(&res);
+    executor.finish_batch().await;
+}
+
+/// Checks that the batch executor can run on top of storage other than the RocksDB cache.
+#[db_test]
+async fn execute_l2_tx_with_in_memory_storage(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+
+    let tester = Tester::new(connection_pool);
+    let storage = funded_in_memory_storage(&[alice.address()]);
+    let executor = tester.create_batch_executor_with_storage(storage);
+
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
+    assert_executed(&res);
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
+    assert_executed(&res);
+    executor.finish_batch().await;
+}
+
+/// Storage factory creating funded in-memory storage and recording the L1 batches it was requested for.
+#[derive(Debug)]
+struct InMemoryStorageFactory {
+    funded_addresses: Vec<Address>,
+    requested_l1_batches: Mutex<Vec<L1BatchNumber>>,
+}
+
+#[async_trait]
+impl L1BatchStorageFactory for InMemoryStorageFactory {
+    async fn create_storage(&self, l1_batch_number: L1BatchNumber) -> Box<dyn ReadStorage + Send> {
+        self.requested_l1_batches
+            .lock()
+            .unwrap()
+            .push(l1_batch_number);
+        Box::new(funded_in_memory_storage(&self.funded_addresses))
+    }
+}
+
+/// Checks that the main batch executor builder executes batches on storage created by the provided factory.
+#[db_test]
+async fn execute_l2_tx_with_storage_factory(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+
+    // Genesis isn't performed, so the transactions can only be executed on the factory storage.
+    let tester = Tester::new(connection_pool);
+    let factory = Arc::new(InMemoryStorageFactory {
+        funded_addresses: vec![alice.address()],
+        requested_l1_batches: Mutex::default(),
+    });
+    let executor = tester
+        .create_batch_executor_with_storage_factory(factory.clone())
+        .await;
+
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
+    assert_executed(&res);
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
     executor.finish_batch().await;
+
+    let requested_l1_batches = factory.requested_l1_batches.lock().unwrap();
+    assert_eq!(*requested_l1_batches, [L1BatchNumber(1)]);
 }
 
 /// Checks that we can successfully execute a single L1 tx in batch executor.
@@ -59,7 +144,10 @@ async fn execute_l1_tx(connection_pool: ConnectionPool) {
     tester.fund(&[alice.address()]).await;
     let executor = tester.create_batch_executor().await;
 
-    let res = executor.execute_tx(alice.l1_execute(PriorityOpId(1))).await;
+    let res = executor
+        .execute_tx(alice.l1_execute(PriorityOpId(1)))
+        .await
+        .unwrap();
     assert_executed(&res);
     executor.finish_batch().await;
 }
@@ -74,10 +162,13 @@ async fn execute_l2_and_l1_txs(connection_pool: ConnectionPool) {
     tester.fund(&[alice.address()]).await;
     let executor = tester.create_batch_executor().await;
 
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
 
-    let res = executor.execute_tx(alice.l1_execute(PriorityOpId(1))).await;
+    let res = executor
+        .execute_tx(alice.l1_execute(PriorityOpId(1)))
+        .await
+        .unwrap();
     assert_executed(&res);
 
     executor.finish_batch().await;
@@ -95,13 +186,13 @@ async fn rollback(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     let tx = alice.execute();
-    let res_old = executor.execute_tx(tx.clone()).await;
+    let res_old = executor.execute_tx(tx.clone()).await.unwrap();
     assert_executed(&res_old);
 
     executor.rollback_last_tx().await;
 
     // Execute the same transaction, it must succeed.
-    let res_new = executor.execute_tx(tx).await;
+    let res_new = executor.execute_tx(tx).await.unwrap();
     assert_executed(&res_new);
 
     let (
@@ -126,6 +217,42 @@ async fn rollback(connection_pool: ConnectionPool) {
     executor.finish_batch().await;
 }
 
+/// Checks that all transactions in a miniblock can be rolled back and executed once again.
+#[db_test]
+async fn rollback_to_miniblock_start(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+
+    let tester = Tester::new(connection_pool);
+
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let txs = [alice.execute(), alice.execute(), alice.execute()];
+    for tx in &txs {
+        let res = executor.execute_tx(tx.clone()).await.unwrap();
+        assert_executed(&res);
+    }
+    // Rolled back transactions must not influence the miniblock rollback.
+    executor.rollback_last_tx().await;
+    executor.rollback_to_miniblock_start().await;
+
+    // The second transaction cannot be executed before the first one, since it has a greater nonce.
+    let res = executor.execute_tx(txs[1].clone()).await.unwrap();
+    assert_rejected(&res);
+    executor.rollback_last_tx().await;
+
+    for tx in &txs {
+        let res = executor.execute_tx(tx.clone()).await.unwrap();
+        assert_executed(&res);
+    }
+    // The miniblock can be rolled back repeatedly.
+    executor.rollback_to_miniblock_start().await;
+    let res = executor.execute_tx(txs[0].clone()).await.unwrap();
+    assert_executed(&res);
+    executor.finish_batch().await;
+}
+
 /// Checks that incorrect transactions are marked as rejected.
 #[db_test]
 async fn reject_tx(connection_pool: ConnectionPool) {
@@ -137,7 +264,7 @@ async fn reject_tx(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     // Wallet is not funded, it can't pay for fees.
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_rejected(&res);
 }
 
@@ -153,11 +280,11 @@ async fn too_big_gas_limit(connection_pool: ConnectionPool) {
 
     let bad_tx = alice.execute_with_gas_limit(u32::MAX);
 
-    let res_old = executor.execute_tx(bad_tx.clone()).await;
+    let res_old = executor.execute_tx(bad_tx.clone()).await.unwrap();
     assert_rejected(&res_old);
 
     executor.rollback_last_tx().await;
-    let res_new = executor.execute_tx(bad_tx).await;
+    let res_new = executor.execute_tx(bad_tx).await.unwrap();
     assert_rejected(&res_new);
     executor.rollback_last_tx().await;
 
@@ -182,7 +309,7 @@ async fn too_big_gas_limit(connection_pool: ConnectionPool) {
     // Ensure that now we can execute a valid tx.
     alice.nonce -= 1; // Reset the nonce.
 
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
     executor.finish_batch().await;
 }
@@ -198,11 +325,11 @@ async fn tx_cant_be_reexecuted(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     let tx = alice.execute();
-    let res1 = executor.execute_tx(tx.clone()).await;
+    let res1 = executor.execute_tx(tx.clone()).await.unwrap();
     assert_executed(&res1);
 
     // Nonce is used for the second tx.
-    let res2 = executor.execute_tx(tx).await;
+    let res2 = executor.execute_tx(tx).await.unwrap();
     assert_rejected(&res2);
 }
 
@@ -217,17 +344,113 @@ async fn deploy_and_call_loadtest(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     let tx = alice.deploy_loadnext_tx();
-    assert_executed(&executor.execute_tx(tx.tx).await);
+    assert_executed(&executor.execute_tx(tx.tx).await.unwrap());
     assert_executed(
         &executor
             .execute_tx(alice.loadnext_custom_gas_call(tx.address, 10, 10_000_000))
-            .await,
+            .await
+            .unwrap(),
     );
     assert_executed(
         &executor
             .execute_tx(alice.loadnext_custom_writes_call(tx.address, 1, 500_000_000))
-            .await,
+            .await
+            .unwrap(),
+    );
+    executor.finish_batch().await;
+}
+
+/// Checks that an incorrect execution hint doesn't influence the transaction execution result.
+#[db_test]
+async fn deploy_with_mispredicted_execution_hint(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+    let tx = alice.deploy_loadnext_tx();
+    let execution_hints = TxExecutionHints::new(10);
+    let hint = TxExecutionHint {
+        simulated_in: MiniblockNumber(1),
+        bytecode_compression_failed: true,
+    };
+    execution_hints.insert(tx.tx.hash(), hint);
+
+    let mut config = TestConfig::new();
+    config.save_call_traces = true;
+    config.execution_hints = Some(execution_hints);
+    let tester = Tester::with_config(connection_pool, config);
+    tester.genesis().await;
+    tester.fund(&[alice.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    let res = executor.execute_tx(tx.tx).await.unwrap();
+    assert_executed(&res);
+    assert_matches!(
+        res,
+        TxExecutionResult::Success { call_tracer_result, .. } if !call_tracer_result.is_empty()
+    );
+    executor.finish_batch().await;
+}
+
+/// Checks that a correct execution hint for a transaction with failing bytecode compression is used,
+/// and that the transaction is executed in the same way as without the hint.
+#[db_test]
+async fn deploy_with_failing_compression_and_execution_hint(connection_pool: ConnectionPool) {
+    let mut alice = Account::random();
+    let mut bob = Account::random();
+    // The gas limit is enough to pass validation, but not enough to publish the compressed bytecodes.
+    let gas_limit = 1_000_000;
+    let alice_tx = alice.deploy_loadnext_tx_with_gas_limit(gas_limit);
+    let bob_tx = bob.deploy_loadnext_tx_with_gas_limit(gas_limit);
+    let execution_hints = TxExecutionHints::new(10);
+    let hint = TxExecutionHint {
+        simulated_in: MiniblockNumber(1),
+        bytecode_compression_failed: true,
+    };
+    execution_hints.insert(alice_tx.hash(), hint);
+
+    let mut config = TestConfig::new();
+    config.save_call_traces = true;
+    config.execution_hints = Some(execution_hints.clone());
+    let tester = Tester::with_config(connection_pool, config);
+    tester.genesis().await;
+    tester.fund(&[alice.address(), bob.address()]).await;
+    let executor = tester.create_batch_executor().await;
+
+    // Alice's transaction is executed using the hint, and Bob's one (which is equivalent) without it.
+    let alice_res = executor.execute_tx(alice_tx.clone()).await.unwrap();
+    assert_reverted(&alice_res);
+    let bob_res = executor.execute_tx(bob_tx).await.unwrap();
+    assert_reverted(&bob_res);
+    // The hint must be consumed.
+    assert_eq!(
+        execution_hints.take(alice_tx.hash(), MiniblockNumber(1)),
+        None
+    );
+
+    let (
+        TxExecutionResult::Success {
+            tx_result: alice_result,
+            compressed_bytecodes: alice_bytecodes,
+            call_tracer_result: alice_traces,
+            ..
+        },
+        TxExecutionResult::Success {
+            tx_result: bob_result,
+            compressed_bytecodes: bob_bytecodes,
+            call_tracer_result: bob_traces,
+            ..
+        },
+    ) = (alice_res, bob_res)
+    else {
+        unreachable!();
+    };
+    // Compression has failed, so no compressed bytecodes must be published.
+    assert!(alice_bytecodes.is_empty());
+    assert!(bob_bytecodes.is_empty());
+    assert_eq!(
+        alice_result.statistics.gas_used,
+        bob_result.statistics.gas_used
     );
+    assert!(!alice_traces.is_empty());
+    assert_eq!(alice_traces.len(), bob_traces.len());
     executor.finish_batch().await;
 }
 
@@ -243,7 +466,7 @@ async fn execute_reverted_tx(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     let tx = alice.deploy_loadnext_tx();
-    assert_executed(&executor.execute_tx(tx.tx).await);
+    assert_executed(&executor.execute_tx(tx.tx).await.unwrap());
 
     assert_reverted(
         &executor
@@ -251,7 +474,8 @@ async fn execute_reverted_tx(connection_pool: ConnectionPool) {
                 tx.address, 1,
                 1_000_000, // We provide enough gas for tx to be executed, but not enough for the call to be successful.
             ))
-            .await,
+            .await
+            .unwrap(),
     );
     executor.finish_batch().await;
 }
@@ -271,36 +495,45 @@ async fn execute_realistic_scenario(connection_pool: ConnectionPool) {
     let executor = tester.create_batch_executor().await;
 
     // A good tx should be executed successfully.
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
 
     // Execute a good tx successfully, roll if back, and execute it again.
     let tx_to_be_rolled_back = alice.execute();
-    let res = executor.execute_tx(tx_to_be_rolled_back.clone()).await;
+    let res = executor
+        .execute_tx(tx_to_be_rolled_back.clone())
+        .await
+        .unwrap();
     assert_executed(&res);
 
     executor.rollback_last_tx().await;
 
-    let res = executor.execute_tx(tx_to_be_rolled_back.clone()).await;
+    let res = executor
+        .execute_tx(tx_to_be_rolled_back.clone())
+        .await
+        .unwrap();
     assert_executed(&res);
 
     // A good tx from a different account should be executed successfully.
-    let res = executor.execute_tx(bob.execute()).await;
+    let res = executor.execute_tx(bob.execute()).await.unwrap();
     assert_executed(&res);
 
     // If we try to execute an already executed again it should be rejected.
-    let res = executor.execute_tx(tx_to_be_rolled_back).await;
+    let res = executor.execute_tx(tx_to_be_rolled_back).await.unwrap();
     assert_rejected(&res);
 
     // An unrelated good tx should be executed successfully.
     executor.rollback_last_tx().await; // Roll back the vm to the pre-rejected-tx state.
 
     // No need to reset the nonce because a tx with the current nonce was indeed executed.
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
 
     // A good L1 tx should also be executed successfully.
-    let res = executor.execute_tx(alice.l1_execute(PriorityOpId(1))).await;
+    let res = executor
+        .execute_tx(alice.l1_execute(PriorityOpId(1)))
+        .await
+        .unwrap();
     assert_executed(&res);
 
     executor.finish_batch().await;
@@ -319,6 +552,7 @@ async fn bootloader_out_of_gas_for_any_tx(connection_pool: ConnectionPool) {
             max_allowed_tx_gas_limit: u32::MAX,
             validation_computational_gas_limit: u32::MAX,
             upload_witness_inputs_to_gcs: false,
+            thread_pool: VmThreadPool::default(),
         },
     );
 
@@ -326,7 +560,7 @@ async fn bootloader_out_of_gas_for_any_tx(connection_pool: ConnectionPool) {
     tester.fund(&[alice.address()]).await;
     let executor = tester.create_batch_executor().await;
 
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_matches!(res, TxExecutionResult::BootloaderOutOfGasForTx);
 }
 
@@ -342,7 +576,7 @@ async fn bootloader_tip_out_of_gas(connection_pool: ConnectionPool) {
     tester.fund(&[alice.address()]).await;
     let executor = tester.create_batch_executor().await;
 
-    let res = executor.execute_tx(alice.execute()).await;
+    let res = executor.execute_tx(alice.execute()).await.unwrap();
     assert_executed(&res);
 
     let (vm_block_res, _witness_block_state) = executor.finish_batch().await;
@@ -355,10 +589,11 @@ async fn bootloader_tip_out_of_gas(connection_pool: ConnectionPool) {
         max_allowed_tx_gas_limit: u32::MAX,
         validation_computational_gas_limit: u32::MAX,
         upload_witness_inputs_to_gcs: false,
+        thread_pool: VmThreadPool::default(),
     });
 
     let second_executor = tester.create_batch_executor().await;
 
-    let res = second_executor.execute_tx(alice.execute()).await;
+    let res = second_executor.execute_tx(alice.execute()).await.unwrap();
     assert_matches!(res, TxExecutionResult::BootloaderOutOfGasForTx);
 }
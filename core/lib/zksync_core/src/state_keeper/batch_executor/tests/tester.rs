@@ -3,6 +3,8 @@
 
 use tempfile::TempDir;
 
+use std::sync::Arc;
+
 use vm::{
     constants::INITIAL_STORAGE_WRITE_PUBDATA_BYTES,
     {L1BatchEnv, SystemEnv},
@@ -11,7 +13,7 @@ use vm::{
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_contracts::{get_loadnext_contract, test_contracts::LoadnextContractExecutionParams};
 use zksync_dal::ConnectionPool;
-use zksync_state::RocksdbStorage;
+use zksync_state::{ForkStorage, InMemoryStorage, ReadStorage, RocksdbStorage};
 use zksync_test_account::{Account, DeployContractsTx, TxType};
 use zksync_types::{
     ethabi::Token, fee::Fee, system_contracts::get_system_smart_contracts,
@@ -19,13 +21,19 @@ use zksync_types::{
     L2ChainId, MiniblockNumber, PriorityOpId, ProtocolVersionId, StorageLog, Transaction, H256,
     L2_ETH_TOKEN_ADDRESS, SYSTEM_CONTEXT_MINIMAL_BASE_FEE, U256,
 };
-use zksync_utils::u256_to_h256;
+use zksync_utils::{bytecode::hash_bytecode, u256_to_h256};
 
+use crate::gas_tracker::PubdataDaMode;
 use crate::genesis::create_genesis_l1_batch;
 use crate::state_keeper::{
-    batch_executor::BatchExecutorHandle,
+    batch_executor::{
+        BatchExecutorHandle, L1BatchExecutorBuilder, L1BatchStorageFactory,
+        MainBatchExecutorBuilder,
+    },
+    execution_hints::TxExecutionHints,
     tests::{default_l1_batch_env, default_system_env, BASE_SYSTEM_CONTRACTS},
 };
+use crate::vm_thread_pool::VmThreadPool;
 
 const DEFAULT_GAS_PER_PUBDATA: u32 = 100;
 const CHAIN_ID: L2ChainId = L2ChainId(270);
@@ -39,6 +47,8 @@ pub(super) struct TestConfig {
     pub(super) max_allowed_tx_gas_limit: u32,
     pub(super) validation_computational_gas_limit: u32,
     pub(super) upload_witness_inputs_to_gcs: bool,
+    pub(super) thread_pool: VmThreadPool,
+    pub(super) execution_hints: Option<TxExecutionHints>,
 }
 
 impl TestConfig {
@@ -52,6 +62,8 @@ impl TestConfig {
             max_allowed_tx_gas_limit: config.max_allowed_l2_tx_gas_limit,
             validation_computational_gas_limit: config.validation_computational_gas_limit,
             upload_witness_inputs_to_gcs: false,
+            thread_pool: VmThreadPool::default(),
+            execution_hints: None,
         }
     }
 }
@@ -87,13 +99,6 @@ impl Tester {
     /// Creates a batch executor instance.
     /// This function intentionally uses sensible defaults to not introduce boilerplate.
     pub(super) async fn create_batch_executor(&self) -> BatchExecutorHandle {
-        // Not really important for the batch executor - it operates over a single batch.
-        let (l1_batch, system_env) = self.batch_params(
-            L1BatchNumber(1),
-            100,
-            self.config.validation_computational_gas_limit,
-        );
-
         let mut secondary_storage = RocksdbStorage::new(self.db_dir.path());
         let mut conn = self
             .pool
@@ -103,16 +108,63 @@ impl Tester {
         secondary_storage.update_from_postgres(&mut conn).await;
         drop(conn);
 
+        self.create_batch_executor_with_storage(secondary_storage)
+    }
+
+    /// Creates a batch executor instance on top of the specified storage.
+    pub(super) fn create_batch_executor_with_storage<S: ReadStorage + Send + 'static>(
+        &self,
+        secondary_storage: S,
+    ) -> BatchExecutorHandle {
+        // Not really important for the batch executor - it operates over a single batch.
+        let (l1_batch, system_env) = self.batch_params(
+            L1BatchNumber(1),
+            100,
+            self.config.validation_computational_gas_limit,
+        );
+
         // We don't use the builder because it would require us to clone the `ConnectionPool`, which is forbidden
         // for the test pool (see the doc-comment on `TestPool` for details).
         BatchExecutorHandle::new(
             self.config.save_call_traces,
             self.config.max_allowed_tx_gas_limit.into(),
-            secondary_storage,
+            ForkStorage::new(secondary_storage, None),
             l1_batch,
             system_env,
             self.config.upload_witness_inputs_to_gcs,
+            PubdataDaMode::Calldata,
+            &self.config.thread_pool,
+            self.config.execution_hints.clone(),
+        )
+    }
+
+    /// Creates a batch executor instance using the main builder with the specified storage factory.
+    pub(super) async fn create_batch_executor_with_storage_factory(
+        &self,
+        factory: Arc<dyn L1BatchStorageFactory>,
+    ) -> BatchExecutorHandle {
+        let (l1_batch, system_env) = self.batch_params(
+            L1BatchNumber(1),
+            100,
+            self.config.validation_computational_gas_limit,
+        );
+
+        // The builder never accesses the connection pool if a storage factory is set,
+        // so the test pool isn't used concurrently.
+        let mut builder = MainBatchExecutorBuilder::new(
+            self.db_dir.path().to_str().unwrap().to_owned(),
+            self.pool.clone(),
+            self.config.max_allowed_tx_gas_limit.into(),
+            self.config.save_call_traces,
+            self.config.upload_witness_inputs_to_gcs,
+            PubdataDaMode::Calldata,
         )
+        .with_thread_pool(self.config.thread_pool.clone())
+        .with_storage_factory(factory);
+        if let Some(execution_hints) = &self.config.execution_hints {
+            builder = builder.with_execution_hints(execution_hints.clone());
+        }
+        builder.init_batch(l1_batch, system_env).await
     }
 
     /// Creates test batch params that can be fed into the VM.
@@ -186,8 +238,25 @@ impl Tester {
     }
 }
 
+/// Creates in-memory storage with system contracts and funds for the specified accounts.
+pub(super) fn funded_in_memory_storage(addresses: &[Address]) -> InMemoryStorage {
+    let mut storage = InMemoryStorage::with_system_contracts(hash_bytecode);
+    let eth_amount = U256::from(10u32).pow(U256::from(32)); //10^32 wei
+    for address in addresses {
+        let key = storage_key_for_standard_token_balance(
+            AccountTreeId::new(L2_ETH_TOKEN_ADDRESS),
+            address,
+        );
+        storage.set_value(key, u256_to_h256(eth_amount));
+    }
+    storage
+}
+
 pub trait AccountLoadNextExecutable {
     fn deploy_loadnext_tx(&mut self) -> DeployContractsTx;
+    /// Returns a transaction deploying the loadnext contract with a custom gas limit.
+    /// Increments the account nonce.
+    fn deploy_loadnext_tx_with_gas_limit(&mut self, gas_limit: u32) -> Transaction;
 
     fn l1_execute(&mut self, serial_id: PriorityOpId) -> Transaction;
     /// Returns a valid `execute` transaction.
@@ -223,6 +292,11 @@ impl AccountLoadNextExecutable for Account {
             TxType::L2,
         )
     }
+    fn deploy_loadnext_tx_with_gas_limit(&mut self, gas_limit: u32) -> Transaction {
+        let deploy_tx = self.deploy_loadnext_tx().tx;
+        let nonce = deploy_tx.nonce().unwrap();
+        self.get_l2_tx_for_execute_with_nonce(deploy_tx.execute, Some(fee(gas_limit)), nonce)
+    }
     fn l1_execute(&mut self, serial_id: PriorityOpId) -> Transaction {
         self.get_l1_tx(
             Execute {
@@ -0,0 +1,109 @@
+//! Hints about transaction execution shared between the API server and the state keeper.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use zksync_types::{MiniblockNumber, H256};
+
+/// Maximum difference between the miniblock a transaction is executed in by the state keeper and the miniblock
+/// it was simulated in by the API server for the simulation hint to be used.
+const MAX_HINT_AGE_IN_MINIBLOCKS: u32 = 10;
+
+/// Hint about a transaction obtained when the transaction was simulated during API admission.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TxExecutionHint {
+    /// Pending miniblock the transaction was simulated in.
+    pub simulated_in: MiniblockNumber,
+    /// Whether the simulated execution failed to publish bytecodes of the transaction in the compressed form.
+    pub bytecode_compression_failed: bool,
+}
+
+#[derive(Debug)]
+struct HintsInner {
+    capacity: usize,
+    hints: HashMap<H256, TxExecutionHint>,
+    insertion_order: VecDeque<H256>,
+}
+
+/// Bounded cache of [`TxExecutionHint`]s keyed by the transaction hash.
+///
+/// Hints are only used as an optimization; the batch executor validates them against the actual execution,
+/// so that an incorrect or outdated hint cannot influence the execution result.
+#[derive(Debug, Clone)]
+pub struct TxExecutionHints(Arc<Mutex<HintsInner>>);
+
+impl TxExecutionHints {
+    /// Creates a cache that holds at most `capacity` hints; older hints are evicted first.
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(HintsInner {
+            capacity,
+            hints: HashMap::with_capacity(capacity),
+            insertion_order: VecDeque::with_capacity(capacity),
+        })))
+    }
+
+    pub(crate) fn insert(&self, tx_hash: H256, hint: TxExecutionHint) {
+        let mut inner = self.0.lock().expect("execution hints are poisoned");
+        if inner.hints.insert(tx_hash, hint).is_none() {
+            inner.insertion_order.push_back(tx_hash);
+        }
+        while inner.insertion_order.len() > inner.capacity {
+            let evicted_hash = inner.insertion_order.pop_front().unwrap();
+            inner.hints.remove(&evicted_hash);
+        }
+    }
+
+    /// Removes the hint for the specified transaction and returns it if it isn't outdated
+    /// with respect to the `current_miniblock`.
+    pub(crate) fn take(
+        &self,
+        tx_hash: H256,
+        current_miniblock: MiniblockNumber,
+    ) -> Option<TxExecutionHint> {
+        let hint = self
+            .0
+            .lock()
+            .expect("execution hints are poisoned")
+            .hints
+            .remove(&tx_hash);
+        let Some(hint) = hint else {
+            metrics::increment_counter!("server.state_keeper.execution_hints", "outcome" => "missing");
+            return None;
+        };
+        if current_miniblock.0.saturating_sub(hint.simulated_in.0) > MAX_HINT_AGE_IN_MINIBLOCKS {
+            metrics::increment_counter!("server.state_keeper.execution_hints", "outcome" => "outdated");
+            return None;
+        }
+        Some(hint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn execution_hints_basics() {
+        let hints = TxExecutionHints::new(2);
+        let hint = TxExecutionHint {
+            simulated_in: MiniblockNumber(5),
+            bytecode_compression_failed: true,
+        };
+        for i in 0..3 {
+            hints.insert(H256::repeat_byte(i), hint);
+        }
+
+        // The first hint should be evicted.
+        assert_eq!(hints.take(H256::repeat_byte(0), MiniblockNumber(5)), None);
+        assert_eq!(
+            hints.take(H256::repeat_byte(1), MiniblockNumber(6)),
+            Some(hint)
+        );
+        // Hints are removed once taken.
+        assert_eq!(hints.take(H256::repeat_byte(1), MiniblockNumber(6)), None);
+        // Outdated hints are ignored.
+        assert_eq!(hints.take(H256::repeat_byte(2), MiniblockNumber(100)), None);
+    }
+}
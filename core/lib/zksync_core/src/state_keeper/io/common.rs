@@ -20,6 +20,7 @@ pub(crate) fn l1_batch_params(
     l1_batch_timestamp: u64,
     previous_batch_hash: U256,
     l1_gas_price: u64,
+    fair_pubdata_price: u64,
     fair_l2_gas_price: u64,
     first_miniblock_number: MiniblockNumber,
     prev_miniblock_hash: H256,
@@ -44,6 +45,7 @@ pub(crate) fn l1_batch_params(
             number: current_l1_batch_number,
             timestamp: l1_batch_timestamp,
             l1_gas_price,
+            fair_pubdata_price,
             fair_l2_gas_price,
             fee_account,
             enforced_base_fee: None,
@@ -123,6 +125,7 @@ pub(crate) async fn load_pending_batch(
         pending_miniblock_header.timestamp,
         previous_l1_batch_hash,
         pending_miniblock_header.l1_gas_price,
+        pending_miniblock_header.fair_pubdata_price(),
         pending_miniblock_header.l2_fair_gas_price,
         pending_miniblock_number,
         prev_miniblock_hash,
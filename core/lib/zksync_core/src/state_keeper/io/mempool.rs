@@ -10,7 +10,7 @@ use std::{
 
 use vm::{utils::fee::derive_base_fee_and_gas_per_pubdata, FinishedL1Batch, L1BatchEnv, SystemEnv};
 
-use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_config::{configs::chain::StateKeeperConfig, ObjectStoreConfig};
 use zksync_dal::ConnectionPool;
 use zksync_mempool::L2TxFilter;
 use zksync_object_store::ObjectStoreFactory;
@@ -23,12 +23,15 @@ use zksync_types::{
 use zksync_utils::time::millis_since_epoch;
 
 use crate::{
+    dev_mode::{DevModeClock, StateReverter},
     l1_gas_price::L1GasPriceProvider,
     state_keeper::{
+        backpressure::BackpressureHandle,
         extractors,
         io::{
             common::{l1_batch_params, load_pending_batch, poll_iters},
-            MiniblockSealerHandle, PendingBatchData, StateKeeperIO,
+            seal_logic::persist_pending_protective_reads,
+            MiniblockSealerHandle, PendingBatchData, ProtectiveReadsWriterHandle, StateKeeperIO,
         },
         mempool_actor::l2_tx_filter,
         updates::UpdatesManager,
@@ -49,6 +52,7 @@ pub(crate) struct MempoolIO<G> {
     filter: L2TxFilter,
     current_miniblock_number: MiniblockNumber,
     miniblock_sealer_handle: MiniblockSealerHandle,
+    protective_reads_writer_handle: ProtectiveReadsWriterHandle,
     current_l1_batch_number: L1BatchNumber,
     fee_account: Address,
     fair_l2_gas_price: u64,
@@ -61,6 +65,10 @@ pub(crate) struct MempoolIO<G> {
 
     virtual_blocks_interval: u32,
     virtual_blocks_per_miniblock: u32,
+    dev_mode_clock: Option<DevModeClock>,
+    dev_mode_reverter: Option<StateReverter>,
+    backpressure: Option<BackpressureHandle>,
+    object_store_config: Option<ObjectStoreConfig>,
 }
 
 #[async_trait]
@@ -120,6 +128,13 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
         // Block until at least one transaction in the mempool can match the filter (or timeout happens).
         // This is needed to ensure that block timestamp is not too old.
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
+            self.process_revert_requests().await;
+            // L1 priority operations have deadlines on L1, so they are executed even if the pipeline lags behind.
+            if self.is_throttled() && !self.mempool.has_next_l1_transaction() {
+                tokio::time::sleep(self.delay_interval).await;
+                continue;
+            }
+
             // We create a new filter each time, since parameters may change and a previously
             // ignored transaction in the mempool may be scheduled for the execution.
             self.filter = l2_tx_filter(self.l1_gas_price_provider.as_ref(), self.fair_l2_gas_price);
@@ -142,14 +157,18 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
             // We can use `timeout_at` since `sleep_past` is cancel-safe; it only uses `sleep()` async calls.
             let current_timestamp = tokio::time::timeout_at(
                 deadline.into(),
-                sleep_past(prev_miniblock_timestamp, self.current_miniblock_number),
+                self.next_timestamp(prev_miniblock_timestamp),
             );
             let current_timestamp = current_timestamp.await.ok()?;
 
+            let fair_pubdata_price = self
+                .l1_gas_price_provider
+                .estimate_effective_pubdata_price();
             tracing::info!(
-                "(l1_gas_price, fair_l2_gas_price) for L1 batch #{} is ({}, {})",
+                "(l1_gas_price, fair_pubdata_price, fair_l2_gas_price) for L1 batch #{} is ({}, {}, {})",
                 self.current_l1_batch_number.0,
                 self.filter.l1_gas_price,
+                fair_pubdata_price,
                 self.fair_l2_gas_price
             );
             let mut storage = self.pool.access_storage().await.unwrap();
@@ -164,6 +183,7 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
                 current_timestamp,
                 prev_l1_batch_hash,
                 self.filter.l1_gas_price,
+                fair_pubdata_price,
                 self.fair_l2_gas_price,
                 self.current_miniblock_number,
                 prev_miniblock_hash,
@@ -185,13 +205,35 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
     ) -> Option<MiniblockParams> {
         // We must provide different timestamps for each miniblock.
         // If miniblock sealing interval is greater than 1 second then `sleep_past` won't actually sleep.
-        let timestamp = tokio::time::timeout(
-            max_wait,
-            sleep_past(prev_miniblock_timestamp, self.current_miniblock_number),
-        )
-        .await
-        .ok()?;
+        let timestamp =
+            tokio::time::timeout(max_wait, self.next_timestamp(prev_miniblock_timestamp))
+                .await
+                .ok()?;
+
+        let virtual_blocks = self.get_virtual_blocks_count(false, self.current_miniblock_number.0);
 
+        Some(MiniblockParams {
+            timestamp,
+            virtual_blocks,
+        })
+    }
+
+    async fn new_miniblock_params_without_waiting(
+        &mut self,
+        prev_miniblock_timestamp: u64,
+    ) -> Option<MiniblockParams> {
+        let timestamp = if let Some(clock) = &self.dev_mode_clock {
+            clock.next_timestamp(prev_miniblock_timestamp)
+        } else {
+            // The timestamp is allowed to run ahead of the wall clock by at most 1 second, so that miniblocks
+            // sealed early in quick succession don't shift timestamps indefinitely.
+            let current_timestamp = millis_since_epoch() / 1_000;
+            let current_timestamp = current_timestamp as u64;
+            if prev_miniblock_timestamp > current_timestamp {
+                return None;
+            }
+            cmp::max(current_timestamp, prev_miniblock_timestamp + 1)
+        };
         let virtual_blocks = self.get_virtual_blocks_count(false, self.current_miniblock_number.0);
 
         Some(MiniblockParams {
@@ -203,7 +245,12 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
     async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction> {
         for _ in 0..poll_iters(self.delay_interval, max_wait) {
             let started_at = Instant::now();
-            let res = self.mempool.next_transaction(&self.filter);
+            let res = if self.is_throttled() {
+                // L1 priority operations have deadlines on L1, so they are executed even if the pipeline lags behind.
+                self.mempool.next_l1_transaction()
+            } else {
+                self.mempool.next_transaction(&self.filter)
+            };
             metrics::histogram!(
                 "server.state_keeper.get_tx_from_mempool",
                 started_at.elapsed(),
@@ -281,10 +328,11 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
         self.miniblock_sealer_handle.wait_for_all_commands().await;
 
         if let Some(witness_witness_block_state) = witness_block_state {
-            let object_store = ObjectStoreFactory::from_env()
-                .context("ObjectsStoreFactor::from_env()")?
-                .create_store()
-                .await;
+            let store_factory = match &self.object_store_config {
+                Some(config) => ObjectStoreFactory::new(config.clone()),
+                None => ObjectStoreFactory::from_env().context("ObjectsStoreFactor::from_env()")?,
+            };
+            let object_store = store_factory.create_store().await;
             let mut upload_successful_metric = 1.0;
             match object_store
                 .put(self.current_l1_batch_number(), &witness_witness_block_state)
@@ -309,7 +357,7 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
         let pool = self.pool.clone();
         let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
 
-        updates_manager
+        let protective_reads_command = updates_manager
             .seal_l1_batch(
                 &mut storage,
                 self.current_miniblock_number,
@@ -318,6 +366,9 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
                 self.l2_erc20_bridge_addr,
             )
             .await;
+        self.protective_reads_writer_handle
+            .submit(protective_reads_command)
+            .await;
         self.current_miniblock_number += 1; // Due to fictive miniblock being sealed.
         self.current_l1_batch_number += 1;
         Ok(())
@@ -342,6 +393,13 @@ impl<G: L1GasPriceProvider + 'static + Send + Sync> StateKeeperIO for MempoolIO<
             .get_protocol_upgrade_tx(version_id)
             .await
     }
+
+    async fn wait_for_pending_writes(&mut self) {
+        tracing::info!("Waiting for protective reads of sealed L1 batches to be persisted");
+        self.protective_reads_writer_handle
+            .wait_for_all_commands()
+            .await;
+    }
 }
 
 /// Sleeps until the current timestamp is larger than the provided `timestamp`.
@@ -359,6 +417,14 @@ async fn sleep_past(timestamp: u64, miniblock: MiniblockNumber) -> u64 {
                 extractors::display_timestamp(current_timestamp)
             );
         }
+        cmp::Ordering::Greater if timestamp == current_timestamp + 1 => {
+            // The previous miniblock was sealed early (see `new_miniblock_params_without_waiting()`).
+            tracing::debug!(
+                "Previous miniblock timestamp {} is ahead of the current timestamp for miniblock #{miniblock}; \
+                 waiting until timestamp increases",
+                extractors::display_timestamp(timestamp)
+            );
+        }
         cmp::Ordering::Greater => {
             // This situation can be triggered if the system keeper is started on a pod with a different
             // system time, or if it is buggy. Thus, a one-time error could require no actions if L1 batches
@@ -396,6 +462,7 @@ impl<G: L1GasPriceProvider> MempoolIO<G> {
     pub(in crate::state_keeper) async fn new(
         mempool: MempoolGuard,
         miniblock_sealer_handle: MiniblockSealerHandle,
+        protective_reads_writer_handle: ProtectiveReadsWriterHandle,
         l1_gas_price_provider: Arc<G>,
         pool: ConnectionPool,
         config: &StateKeeperConfig,
@@ -403,7 +470,7 @@ impl<G: L1GasPriceProvider> MempoolIO<G> {
         l2_erc20_bridge_addr: Address,
         validation_computational_gas_limit: u32,
         chain_id: L2ChainId,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         assert!(
             config.virtual_blocks_interval > 0,
             "Virtual blocks interval must be positive"
@@ -414,6 +481,9 @@ impl<G: L1GasPriceProvider> MempoolIO<G> {
         );
 
         let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
+        persist_pending_protective_reads(&mut storage)
+            .await
+            .context("failed persisting pending protective reads")?;
         let last_sealed_l1_batch_header = storage
             .blocks_dal()
             .get_newest_l1_batch_header()
@@ -427,13 +497,14 @@ impl<G: L1GasPriceProvider> MempoolIO<G> {
 
         drop(storage);
 
-        Self {
+        Ok(Self {
             mempool,
             pool,
             filter: L2TxFilter::default(),
             // ^ Will be initialized properly on the first newly opened batch
             current_l1_batch_number: last_sealed_l1_batch_header.number + 1,
             miniblock_sealer_handle,
+            protective_reads_writer_handle,
             current_miniblock_number: last_miniblock_number + 1,
             fee_account: config.fee_account_addr,
             fair_l2_gas_price: config.fair_l2_gas_price,
@@ -444,6 +515,132 @@ impl<G: L1GasPriceProvider> MempoolIO<G> {
             chain_id,
             virtual_blocks_interval: config.virtual_blocks_interval,
             virtual_blocks_per_miniblock: config.virtual_blocks_per_miniblock,
+            // Replaced with the clock shared with the API server if it runs in the same process.
+            dev_mode_clock: config.dev_mode.then(DevModeClock::default),
+            dev_mode_reverter: None,
+            backpressure: None,
+            object_store_config: None,
+        })
+    }
+
+    /// Makes the IO stop accepting transactions while the downstream pipeline lags behind.
+    pub(in crate::state_keeper) fn with_backpressure(mut self, handle: BackpressureHandle) -> Self {
+        self.backpressure = Some(handle);
+        self
+    }
+
+    /// Sets the config of the object store witness inputs are uploaded to. If not set, the config
+    /// is read from the environment.
+    pub(in crate::state_keeper) fn with_object_store_config(
+        mut self,
+        config: ObjectStoreConfig,
+    ) -> Self {
+        self.object_store_config = Some(config);
+        self
+    }
+
+    fn is_throttled(&self) -> bool {
+        self.backpressure
+            .as_ref()
+            .map_or(false, |handle| handle.lagging_component().is_some())
+    }
+
+    /// Sets the clock used to assign block timestamps in the dev mode.
+    pub(in crate::state_keeper) fn with_dev_mode_clock(mut self, clock: DevModeClock) -> Self {
+        self.dev_mode_clock = Some(clock);
+        self
+    }
+
+    /// Sets the reverter processing `evm_revert` requests in the dev mode.
+    pub(in crate::state_keeper) fn with_dev_mode_reverter(
+        mut self,
+        reverter: StateReverter,
+    ) -> Self {
+        self.dev_mode_reverter = Some(reverter);
+        self
+    }
+
+    /// Processes pending state revert requests in the dev mode. Must only be called between L1 batches,
+    /// when there is no open batch executor.
+    async fn process_revert_requests(&mut self) {
+        while let Some(request) = self
+            .dev_mode_reverter
+            .as_ref()
+            .and_then(StateReverter::next_request)
+        {
+            if request.response.is_closed() {
+                continue; // The requester has given up waiting
+            }
+            let result = self.revert_state(request.last_miniblock_to_keep).await;
+            if let Err(err) = &result {
+                tracing::error!("Failed reverting state in dev mode: {err:#}");
+            }
+            request.response.send(result).ok();
+        }
+    }
+
+    async fn revert_state(
+        &mut self,
+        last_miniblock_to_keep: MiniblockNumber,
+    ) -> anyhow::Result<()> {
+        // Protective reads for the last L1 batch may be still being written.
+        self.protective_reads_writer_handle
+            .wait_for_all_commands()
+            .await;
+        let reverter = self
+            .dev_mode_reverter
+            .as_ref()
+            .context("state reverter is not configured")?;
+
+        let mut storage = self.pool.access_storage_tagged("state_keeper").await?;
+        let last_l1_batch_to_keep = storage
+            .blocks_web3_dal()
+            .get_l1_batch_number_of_miniblock(last_miniblock_to_keep)
+            .await
+            .context("get_l1_batch_number_of_miniblock()")?
+            .with_context(|| format!("miniblock #{last_miniblock_to_keep} is not sealed"))?;
+        if last_l1_batch_to_keep + 1 >= self.current_l1_batch_number {
+            return Ok(()); // Nothing to revert
+        }
+        let (_, last_miniblock_to_keep) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(last_l1_batch_to_keep)
+            .await
+            .context("get_miniblock_range_of_l1_batch()")?
+            .context("L1 batch should contain at least one miniblock")?;
+
+        tracing::info!(
+            "Reverting state to L1 batch #{last_l1_batch_to_keep} (miniblock #{last_miniblock_to_keep})"
+        );
+        // Remove reverted transactions first, so that they are not returned to the mempool by the reverter.
+        let removed_tx_count = storage
+            .transactions_dal()
+            .remove_txs_after_miniblock(last_miniblock_to_keep)
+            .await;
+        tracing::info!("Removed {removed_tx_count} reverted transactions");
+        drop(storage);
+        reverter.rollback_db(last_l1_batch_to_keep).await;
+
+        // Nonces of accounts and the next priority operation ID in the mempool are no longer valid,
+        // so the mempool is re-populated from Postgres.
+        let mut storage = self.pool.access_storage_tagged("state_keeper").await?;
+        let next_priority_id = storage.transactions_dal().next_priority_id().await;
+        storage.transactions_dal().reset_mempool().await;
+        self.mempool.reset(next_priority_id);
+
+        self.current_l1_batch_number = last_l1_batch_to_keep + 1;
+        self.current_miniblock_number = last_miniblock_to_keep + 1;
+        Ok(())
+    }
+
+    /// Returns the timestamp for the next miniblock, which is guaranteed to be larger than `prev_miniblock_timestamp`.
+    /// In the dev mode, miniblocks are sealed more often than once per second, and timestamps can be adjusted
+    /// via the API, so instead of waiting for the wall clock to catch up, the timestamp is taken from the dev mode clock.
+    async fn next_timestamp(&self, prev_miniblock_timestamp: u64) -> u64 {
+        if let Some(clock) = &self.dev_mode_clock {
+            clock.next_timestamp(prev_miniblock_timestamp)
+        } else {
+            sleep_past(prev_miniblock_timestamp, self.current_miniblock_number).await
         }
     }
 
@@ -1,5 +1,6 @@
+use anyhow::Context as _;
 use async_trait::async_trait;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
 
 use std::{
     fmt,
@@ -22,6 +23,7 @@ pub(crate) mod seal_logic;
 
 pub(crate) use self::mempool::MempoolIO;
 
+use self::seal_logic::{clear_unsealed_miniblock_data, ProtectiveReadsCommand};
 use super::updates::{MiniblockSealCommand, UpdatesManager};
 
 #[cfg(test)]
@@ -84,6 +86,16 @@ pub trait StateKeeperIO: 'static + Send {
         max_wait: Duration,
         prev_miniblock_timestamp: u64,
     ) -> Option<MiniblockParams>;
+    /// Returns parameters for the next miniblock without waiting for the wall clock to pass
+    /// `prev_miniblock_timestamp`. Used for miniblocks sealed early (e.g., by the miniblock gas limit), so that
+    /// the state keeper isn't blocked until the next second. Returns `None` if parameters cannot be provided
+    /// right away; in this case, the state keeper falls back to [`Self::wait_for_new_miniblock_params()`].
+    async fn new_miniblock_params_without_waiting(
+        &mut self,
+        _prev_miniblock_timestamp: u64,
+    ) -> Option<MiniblockParams> {
+        None
+    }
     /// Blocks for up to `max_wait` until the next transaction is available for execution.
     /// Returns `None` if no transaction became available until the timeout.
     async fn wait_for_next_tx(&mut self, max_wait: Duration) -> Option<Transaction>;
@@ -107,6 +119,9 @@ pub trait StateKeeperIO: 'static + Send {
     /// Loads protocol upgrade tx for given version.
     async fn load_upgrade_tx(&mut self, version_id: ProtocolVersionId)
         -> Option<ProtocolUpgradeTx>;
+    /// Waits until data submitted for asynchronous persistence (if any) is persisted. Called once the state keeper
+    /// is stopped, so that the node can be shut down without losing this data.
+    async fn wait_for_pending_writes(&mut self) {}
 }
 
 impl fmt::Debug for dyn StateKeeperIO {
@@ -221,12 +236,19 @@ impl MiniblockSealerHandle {
 pub(crate) struct MiniblockSealer {
     pool: ConnectionPool,
     is_sync: bool,
+    is_parallel: bool,
     // Weak sender handle to get queue capacity stats.
     commands_sender: mpsc::WeakSender<Completable<MiniblockSealCommand>>,
     commands_receiver: mpsc::Receiver<Completable<MiniblockSealCommand>>,
+    // Notified with the number of each sealed miniblock, if set.
+    sealed_miniblock_sender: Option<watch::Sender<MiniblockNumber>>,
 }
 
 impl MiniblockSealer {
+    /// Number of connections used by a parallel sealer: one per independent part of miniblock data,
+    /// plus one for the miniblock header.
+    pub(crate) const PARALLEL_POOL_SIZE: u32 = seal_logic::PARALLEL_SEAL_CONNECTIONS;
+
     /// Creates a sealer that will use the provided Postgres connection and will have the specified
     /// `command_capacity` for unprocessed sealing commands.
     pub(crate) fn new(
@@ -240,8 +262,10 @@ impl MiniblockSealer {
         let this = Self {
             pool,
             is_sync,
+            is_parallel: false,
             commands_sender: commands_sender.downgrade(),
             commands_receiver,
+            sealed_miniblock_sender: None,
         };
         let handle = MiniblockSealerHandle {
             commands_sender,
@@ -251,6 +275,25 @@ impl MiniblockSealer {
         (this, handle)
     }
 
+    /// Creates a sealer that will persist independent miniblock data in parallel connections
+    /// (see [`MiniblockSealCommand::seal_parallel()`]). The `pool` must have at least
+    /// [`Self::PARALLEL_POOL_SIZE`] connections.
+    pub(crate) fn parallel(
+        pool: ConnectionPool,
+        command_capacity: usize,
+    ) -> (Self, MiniblockSealerHandle) {
+        let (mut this, handle) = Self::new(pool, command_capacity);
+        this.is_parallel = true;
+        (this, handle)
+    }
+
+    /// Sets the sender that will be notified with the number of each sealed miniblock after
+    /// the miniblock is persisted.
+    pub fn with_sealed_miniblock_sender(mut self, sender: watch::Sender<MiniblockNumber>) -> Self {
+        self.sealed_miniblock_sender = Some(sender);
+        self
+    }
+
     /// Seals miniblocks as they are received from the [`MiniblockSealerHandle`]. This should be run
     /// on a separate Tokio task.
     pub async fn run(mut self) -> anyhow::Result<()> {
@@ -265,21 +308,69 @@ impl MiniblockSealer {
             tracing::warn!("Miniblock sealer not started, since its handle is already dropped");
         }
 
+        // Data of a miniblock that was being sealed in parallel when the node was stopped may be partially persisted
+        // (the node could've used a different sealing mode before the restart, so this is performed unconditionally).
+        let mut conn = self
+            .pool
+            .access_storage_tagged("state_keeper")
+            .await
+            .unwrap();
+        clear_unsealed_miniblock_data(&mut conn).await;
+        // Parallel sealing persists miniblock data before the header, which is incompatible with foreign keys
+        // referencing miniblocks. Keys are managed by the operator (`zksync_server --miniblock-data-foreign-keys`)
+        // rather than the sealer, since changing them locks the largest tables.
+        let foreign_keys = conn
+            .blocks_dal()
+            .get_miniblock_data_foreign_keys()
+            .await
+            .context("get_miniblock_data_foreign_keys()")?;
+        drop(conn);
+        if self.is_parallel {
+            let existing_keys: Vec<_> = foreign_keys
+                .iter()
+                .filter_map(|(name, &exists)| exists.then_some(name))
+                .collect();
+            anyhow::ensure!(
+                existing_keys.is_empty(),
+                "Parallel miniblock sealing requires foreign keys to miniblocks to be dropped, but {existing_keys:?} exist; \
+                 drop them using `zksync_server --miniblock-data-foreign-keys=drop`"
+            );
+        } else {
+            let missing_keys: Vec<_> = foreign_keys
+                .iter()
+                .filter_map(|(name, &exists)| (!exists).then_some(name))
+                .collect();
+            if !missing_keys.is_empty() {
+                tracing::warn!(
+                    "Foreign keys {missing_keys:?} to miniblocks are missing; if parallel miniblock sealing is disabled \
+                     permanently, restore them using `zksync_server --miniblock-data-foreign-keys=restore`"
+                );
+            }
+        }
+
         let mut miniblock_seal_delta: Option<Instant> = None;
         // Commands must be processed sequentially: a later miniblock cannot be saved before
         // an earlier one.
         while let Some(completable) = self.next_command().await {
-            let mut conn = self
-                .pool
-                .access_storage_tagged("state_keeper")
-                .await
-                .unwrap();
-            completable.command.seal(&mut conn).await;
+            let miniblock_number = completable.command.miniblock_number;
+            if self.is_parallel {
+                completable.command.seal_parallel(&self.pool).await;
+            } else {
+                let mut conn = self
+                    .pool
+                    .access_storage_tagged("state_keeper")
+                    .await
+                    .unwrap();
+                completable.command.seal(&mut conn).await;
+            }
             if let Some(delta) = miniblock_seal_delta {
                 metrics::histogram!("server.state_keeper.miniblock.seal_delta", delta.elapsed());
             }
             miniblock_seal_delta = Some(Instant::now());
 
+            if let Some(sender) = &self.sealed_miniblock_sender {
+                sender.send_replace(miniblock_number);
+            }
             completable.completion_sender.send(()).ok();
             // ^ We don't care whether anyone listens to the processing progress
         }
@@ -316,3 +407,95 @@ impl MiniblockSealer {
         command
     }
 }
+
+/// Handle for [`ProtectiveReadsWriter`] allowing to submit [`ProtectiveReadsCommand`]s.
+#[derive(Debug)]
+pub(crate) struct ProtectiveReadsWriterHandle {
+    commands_sender: mpsc::Sender<Completable<ProtectiveReadsCommand>>,
+    latest_completion_receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl ProtectiveReadsWriterHandle {
+    const SHUTDOWN_MSG: &'static str = "protective reads writer unexpectedly shut down";
+
+    /// Submits protective reads of a sealed L1 batch to the writer. If the writer hasn't persisted
+    /// protective reads of the previous L1 batch yet, this method will wait until it does.
+    pub async fn submit(&mut self, command: ProtectiveReadsCommand) {
+        let l1_batch_number = command.l1_batch_number;
+        let start = Instant::now();
+        let (completion_sender, completion_receiver) = oneshot::channel();
+        self.latest_completion_receiver = Some(completion_receiver);
+        let command = Completable {
+            command,
+            completion_sender,
+        };
+        self.commands_sender
+            .send(command)
+            .await
+            .expect(Self::SHUTDOWN_MSG);
+
+        let elapsed = start.elapsed();
+        tracing::debug!(
+            "Enqueued protective reads for L1 batch #{l1_batch_number} (took {elapsed:?})"
+        );
+        metrics::histogram!(
+            "server.state_keeper.protective_reads.queue_latency",
+            elapsed,
+            "stage" => "submit"
+        );
+    }
+
+    /// Waits until all previously submitted protective reads are persisted.
+    pub async fn wait_for_all_commands(&mut self) {
+        if let Some(completion_receiver) = self.latest_completion_receiver.take() {
+            completion_receiver.await.expect(Self::SHUTDOWN_MSG);
+        }
+    }
+}
+
+/// Component responsible for persisting protective reads of sealed L1 batches off the state keeper
+/// critical path.
+#[derive(Debug)]
+pub(crate) struct ProtectiveReadsWriter {
+    pool: ConnectionPool,
+    commands_receiver: mpsc::Receiver<Completable<ProtectiveReadsCommand>>,
+}
+
+impl ProtectiveReadsWriter {
+    /// Number of L1 batches which protective reads may wait to be persisted. Limiting this number
+    /// ensures that the writer doesn't fall behind the state keeper; also, pending protective reads
+    /// have to be persisted on the node restart, so the fewer of them are pending, the better.
+    const COMMAND_CAPACITY: usize = 1;
+
+    pub(crate) fn new(pool: ConnectionPool) -> (Self, ProtectiveReadsWriterHandle) {
+        let (commands_sender, commands_receiver) = mpsc::channel(Self::COMMAND_CAPACITY);
+        let this = Self {
+            pool,
+            commands_receiver,
+        };
+        let handle = ProtectiveReadsWriterHandle {
+            commands_sender,
+            latest_completion_receiver: None,
+        };
+        (this, handle)
+    }
+
+    /// Persists protective reads as they are received from the [`ProtectiveReadsWriterHandle`].
+    /// This should be run on a separate Tokio task. Exits after the handle is dropped and all
+    /// submitted commands are processed.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        tracing::info!("Starting protective reads writer");
+        while let Some(completable) = self.commands_receiver.recv().await {
+            let mut conn = self
+                .pool
+                .access_storage_tagged("state_keeper")
+                .await
+                .unwrap();
+            completable.command.persist(&mut conn).await;
+            completable.completion_sender.send(()).ok();
+            // ^ We don't care whether anyone listens to the processing progress
+        }
+        tracing::info!("Protective reads writer is shut down");
+        Ok(())
+    }
+}
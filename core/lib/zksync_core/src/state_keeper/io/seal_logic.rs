@@ -1,6 +1,7 @@
 //! This module is a source-of-truth on what is expected to be done when sealing a block.
 //! It contains the logic of the block sealing, which is used by both the mempool-based and external node IO.
 
+use futures::future;
 use itertools::Itertools;
 
 use std::{
@@ -11,7 +12,8 @@ use std::{
 use vm::{FinishedL1Batch, L1BatchEnv};
 
 use zksync_config::constants::ACCOUNT_CODE_STORAGE_ADDRESS;
-use zksync_dal::StorageProcessor;
+use zksync_dal::{ConnectionPool, StorageProcessor};
+use zksync_mini_merkle_tree::MiniMerkleTree;
 
 use zksync_types::{
     block::unpack_block_info, CURRENT_VIRTUAL_BLOCK_INFO_POSITION, SYSTEM_CONTEXT_ADDRESS,
@@ -21,6 +23,7 @@ use zksync_types::{
     event::{extract_added_tokens, extract_long_l2_to_l1_messages},
     l2_to_l1_log::L2ToL1Log,
     storage_writes_deduplicator::{ModifiedSlot, StorageWritesDeduplicator},
+    tokens::TokenProvenance,
     tx::{
         tx_execution_info::DeduplicatedWritesMetrics, IncludedTxLocation,
         TransactionExecutionResult,
@@ -123,7 +126,8 @@ impl SealProgress {
 impl UpdatesManager {
     /// Persists an L1 batch in the storage.
     /// This action includes a creation of an empty "fictive" miniblock that contains
-    /// the events generated during the bootloader "tip phase".
+    /// the events generated during the bootloader "tip phase". Protective reads of the L1 batch
+    /// are not persisted; instead, they are returned as a command to be persisted afterwards.
     pub(crate) async fn seal_l1_batch(
         mut self,
         storage: &mut StorageProcessor<'_>,
@@ -131,7 +135,7 @@ impl UpdatesManager {
         l1_batch_env: &L1BatchEnv,
         finished_batch: FinishedL1Batch,
         l2_erc20_bridge_addr: Address,
-    ) {
+    ) -> ProtectiveReadsCommand {
         let started_at = Instant::now();
         let mut progress = SealProgress::for_l1_batch();
         let mut transaction = storage.start_transaction().await.unwrap();
@@ -221,6 +225,30 @@ impl UpdatesManager {
             .unwrap();
         progress.end_stage("insert_l1_batch_header", None);
 
+        transaction
+            .blocks_dal()
+            .insert_events_queue(
+                l1_batch_env.number,
+                &finished_batch
+                    .final_execution_state
+                    .deduplicated_events_logs,
+            )
+            .await
+            .unwrap();
+        progress.end_stage("insert_events_queue", None);
+
+        // The tree is persisted so that the API server doesn't need to rehash it on each log proof request.
+        let l2_to_l1_log_leaves = l1_batch.l2_to_l1_logs.iter().map(L2ToL1Log::to_bytes);
+        let l2_to_l1_log_tree =
+            MiniMerkleTree::new(l2_to_l1_log_leaves, L2ToL1Log::LIMIT_PER_L1_BATCH).into_layers();
+        let l2_to_l1_log_tree_layers: Vec<_> = l2_to_l1_log_tree.layers().collect();
+        transaction
+            .blocks_dal()
+            .insert_l2_to_l1_log_tree(l1_batch_env.number, &l2_to_l1_log_tree_layers)
+            .await
+            .unwrap();
+        progress.end_stage("insert_l2_to_l1_log_tree", None);
+
         transaction
             .blocks_dal()
             .mark_miniblocks_as_executed_in_l1_batch(l1_batch_env.number)
@@ -240,11 +268,13 @@ impl UpdatesManager {
         let (deduplicated_writes, protective_reads): (Vec<_>, Vec<_>) = deduped_log_queries
             .into_iter()
             .partition(|log_query| log_query.rw_flag);
+        // Protective reads are persisted off the critical path; see `ProtectiveReadsCommand`.
         transaction
-            .storage_logs_dedup_dal()
-            .insert_protective_reads(l1_batch_env.number, &protective_reads)
-            .await;
-        progress.end_stage("insert_protective_reads", Some(protective_reads.len()));
+            .blocks_dal()
+            .mark_protective_reads_as_pending(l1_batch_env.number, &protective_reads)
+            .await
+            .unwrap();
+        progress.end_stage("mark_protective_reads_as_pending", None);
 
         let deduplicated_writes_hashed_keys: Vec<_> = deduplicated_writes
             .iter()
@@ -292,6 +322,11 @@ impl UpdatesManager {
             l1_batch_env.timestamp,
             &writes_metrics,
         );
+
+        ProtectiveReadsCommand {
+            l1_batch_number: l1_batch_env.number,
+            protective_reads,
+        }
     }
 
     fn report_l1_batch_metrics(
@@ -315,7 +350,7 @@ impl UpdatesManager {
             self.l1_batch.executed_transactions.len() as f64
         );
         let l1_batch_latency =
-            ((millis_since_epoch() - block_timestamp as u128 * 1_000) as f64) / 1_000.0;
+            (millis_since_epoch().saturating_sub(block_timestamp as u128 * 1_000) as f64) / 1_000.0;
         metrics::histogram!(
             "server.l1_batch.latency",
             l1_batch_latency,
@@ -333,11 +368,183 @@ impl UpdatesManager {
     }
 }
 
+/// Protective reads of a sealed L1 batch that are yet to be persisted. Protective reads are only needed
+/// by the Merkle tree, so they are persisted outside the L1 batch sealing transaction to keep
+/// the state keeper loop fast; the metadata calculator doesn't process L1 batches with pending
+/// protective reads.
+#[derive(Debug)]
+pub(crate) struct ProtectiveReadsCommand {
+    pub l1_batch_number: L1BatchNumber,
+    pub protective_reads: Vec<LogQuery>,
+}
+
+impl ProtectiveReadsCommand {
+    pub async fn persist(&self, storage: &mut StorageProcessor<'_>) {
+        let started_at = Instant::now();
+        let l1_batch_number = self.l1_batch_number;
+        let mut transaction = storage.start_transaction().await.unwrap();
+        transaction
+            .storage_logs_dedup_dal()
+            .insert_protective_reads(l1_batch_number, &self.protective_reads)
+            .await;
+        transaction
+            .blocks_dal()
+            .mark_protective_reads_as_persisted(l1_batch_number)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        let elapsed = started_at.elapsed();
+        metrics::histogram!(
+            "server.state_keeper.protective_reads.persist_latency",
+            elapsed
+        );
+        metrics::histogram!(
+            "server.state_keeper.protective_reads.count",
+            self.protective_reads.len() as f64
+        );
+        tracing::debug!(
+            "Persisted {} protective reads for L1 batch #{l1_batch_number} in {elapsed:?}",
+            self.protective_reads.len()
+        );
+    }
+}
+
+/// Independent parts of miniblock data that can be persisted in parallel connections.
+#[derive(Debug, Clone, Copy)]
+enum MiniblockDataSubtask {
+    StorageLogs,
+    FactoryDeps,
+    Events,
+    L2ToL1Logs,
+}
+
+impl MiniblockDataSubtask {
+    const ALL: [Self; 4] = [
+        Self::StorageLogs,
+        Self::FactoryDeps,
+        Self::Events,
+        Self::L2ToL1Logs,
+    ];
+}
+
+/// Number of connections used by [`MiniblockSealCommand::seal_parallel()`].
+pub(crate) const PARALLEL_SEAL_CONNECTIONS: u32 = MiniblockDataSubtask::ALL.len() as u32 + 1;
+
+/// Removes data left over from a miniblock that failed to be sealed in parallel connections
+/// (i.e., data for miniblocks following the last miniblock with a persisted header).
+pub(crate) async fn clear_unsealed_miniblock_data(storage: &mut StorageProcessor<'_>) {
+    let mut transaction = storage.start_transaction().await.unwrap();
+    let last_sealed_miniblock = transaction
+        .blocks_dal()
+        .get_sealed_miniblock_number()
+        .await
+        .unwrap();
+    tracing::info!(
+        "Clearing data for miniblocks after the last sealed miniblock #{last_sealed_miniblock}"
+    );
+
+    transaction
+        .storage_logs_dal()
+        .rollback_storage_logs(last_sealed_miniblock)
+        .await;
+    transaction
+        .storage_dal()
+        .rollback_factory_deps(last_sealed_miniblock)
+        .await;
+    transaction
+        .events_dal()
+        .rollback_events(last_sealed_miniblock)
+        .await;
+    transaction
+        .events_dal()
+        .rollback_l2_to_l1_logs(last_sealed_miniblock)
+        .await;
+    transaction.commit().await.unwrap();
+}
+
+/// Persists protective reads of sealed L1 batches that were not persisted before the node was stopped
+/// (e.g., if the node was stopped abruptly). Such protective reads are restored from their compact form
+/// stored together with the L1 batch.
+pub(crate) async fn persist_pending_protective_reads(
+    storage: &mut StorageProcessor<'_>,
+) -> anyhow::Result<()> {
+    let pending_l1_batches = storage
+        .blocks_dal()
+        .get_l1_batches_with_pending_protective_reads()
+        .await?;
+    for l1_batch_number in pending_l1_batches {
+        let mut transaction = storage.start_transaction().await?;
+        let protective_reads = transaction
+            .blocks_dal()
+            .get_pending_protective_reads(l1_batch_number)
+            .await?;
+        // The reads may have been persisted since the pending L1 batches were listed.
+        let Some(protective_reads) = protective_reads else {
+            continue;
+        };
+        transaction
+            .storage_logs_dedup_dal()
+            .insert_protective_read_keys(l1_batch_number, &protective_reads)
+            .await;
+        transaction
+            .blocks_dal()
+            .mark_protective_reads_as_persisted(l1_batch_number)
+            .await?;
+        transaction.commit().await?;
+        tracing::info!(
+            "Persisted {} pending protective reads for L1 batch #{l1_batch_number}",
+            protective_reads.len()
+        );
+    }
+    Ok(())
+}
+
 impl MiniblockSealCommand {
     pub async fn seal(&self, storage: &mut StorageProcessor<'_>) {
         self.seal_inner(storage, false).await;
     }
 
+    /// Seals a (non-fictive) miniblock, persisting its independent data (storage logs, factory deps, events
+    /// and L2-to-L1 logs) in parallel connections acquired from `pool`. The miniblock header is committed last
+    /// together with the data that must be updated atomically with it (transaction statuses, latest storage values
+    /// and added tokens), so a miniblock is considered sealed only once its header is persisted.
+    ///
+    /// If sealing fails midway, the leftover data must be removed using [`clear_unsealed_miniblock_data()`]
+    /// before any other miniblocks are sealed.
+    pub async fn seal_parallel(&self, pool: &ConnectionPool) {
+        self.assert_valid_miniblock(false);
+
+        let started_at = Instant::now();
+        self.log_sealing_start();
+        let write_logs = self.extract_deduplicated_write_logs(false);
+
+        let data_subtasks = MiniblockDataSubtask::ALL.map(|subtask| {
+            let write_logs = &write_logs;
+            async move {
+                let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
+                let mut transaction = storage.start_transaction().await.unwrap();
+                self.insert_data(subtask, &mut transaction, write_logs, false)
+                    .await;
+                transaction.commit().await.unwrap();
+            }
+        });
+
+        let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
+        let mut transaction = storage.start_transaction().await.unwrap();
+        let (_, current_l2_virtual_block_number) = future::join(
+            future::join_all(data_subtasks),
+            self.insert_header_and_state(&mut transaction, &write_logs, false),
+        )
+        .await;
+
+        // Committing the header only after all other data is persisted.
+        let mut progress = SealProgress::for_miniblock(false);
+        transaction.commit().await.unwrap();
+        progress.end_stage("commit_miniblock", None);
+        self.report_miniblock_metrics(started_at, current_l2_virtual_block_number);
+    }
+
     /// Seals a miniblock with the given number.
     ///
     /// If `is_fictive` flag is set to true, then it is assumed that we should seal a fictive miniblock
@@ -350,11 +557,26 @@ impl MiniblockSealCommand {
     async fn seal_inner(&self, storage: &mut StorageProcessor<'_>, is_fictive: bool) {
         self.assert_valid_miniblock(is_fictive);
 
-        let l1_batch_number = self.l1_batch_number;
-        let miniblock_number = self.miniblock_number;
         let started_at = Instant::now();
+        self.log_sealing_start();
+        let write_logs = self.extract_deduplicated_write_logs(is_fictive);
+
+        let mut transaction = storage.start_transaction().await.unwrap();
+        let current_l2_virtual_block_number = self
+            .insert_header_and_state(&mut transaction, &write_logs, is_fictive)
+            .await;
+        for subtask in MiniblockDataSubtask::ALL {
+            self.insert_data(subtask, &mut transaction, &write_logs, is_fictive)
+                .await;
+        }
+
         let mut progress = SealProgress::for_miniblock(is_fictive);
+        transaction.commit().await.unwrap();
+        progress.end_stage("commit_miniblock", None);
+        self.report_miniblock_metrics(started_at, current_l2_virtual_block_number);
+    }
 
+    fn log_sealing_start(&self) {
         let (l1_tx_count, l2_tx_count) = l1_l2_tx_count(&self.miniblock.executed_transactions);
         let (writes_count, reads_count) =
             storage_log_query_write_read_counts(&self.miniblock.storage_logs);
@@ -362,11 +584,25 @@ impl MiniblockSealCommand {
             "Sealing miniblock {miniblock_number} (L1 batch {l1_batch_number}) \
              with {total_tx_count} ({l2_tx_count} L2 + {l1_tx_count} L1) txs, {event_count} events, \
              {reads_count} reads, {writes_count} writes",
+            miniblock_number = self.miniblock_number,
+            l1_batch_number = self.l1_batch_number,
             total_tx_count = l1_tx_count + l2_tx_count,
             event_count = self.miniblock.events.len()
         );
+    }
 
-        let mut transaction = storage.start_transaction().await.unwrap();
+    /// Inserts the miniblock header and updates data that must be changed atomically with it.
+    /// Returns the current L2 virtual block number.
+    async fn insert_header_and_state(
+        &self,
+        transaction: &mut StorageProcessor<'_>,
+        write_logs: &[(H256, Vec<StorageLog>)],
+        is_fictive: bool,
+    ) -> u64 {
+        let miniblock_number = self.miniblock_number;
+        let mut progress = SealProgress::for_miniblock(is_fictive);
+
+        let (l1_tx_count, l2_tx_count) = l1_l2_tx_count(&self.miniblock.executed_transactions);
         let miniblock_header = MiniblockHeader {
             number: miniblock_number,
             timestamp: self.miniblock.timestamp,
@@ -376,6 +612,7 @@ impl MiniblockSealCommand {
             base_fee_per_gas: self.base_fee_per_gas,
             l1_gas_price: self.l1_gas_price,
             l2_fair_gas_price: self.fair_l2_gas_price,
+            fair_pubdata_price: Some(self.fair_pubdata_price),
             base_system_contracts_hashes: self.base_system_contracts_hashes,
             protocol_version: self.protocol_version,
             virtual_blocks: self.miniblock.virtual_blocks,
@@ -401,32 +638,20 @@ impl MiniblockSealCommand {
             Some(self.miniblock.executed_transactions.len()),
         );
 
-        let write_logs = self.extract_deduplicated_write_logs(is_fictive);
-        let write_log_count = write_logs.iter().map(|(_, logs)| logs.len()).sum();
-
         transaction
-            .storage_logs_dal()
-            .insert_storage_logs(miniblock_number, &write_logs)
-            .await;
-        progress.end_stage("insert_storage_logs", Some(write_log_count));
+            .tx_lifecycle_dal()
+            .record_miniblock_inclusion(miniblock_number)
+            .await
+            .unwrap();
+        progress.end_stage("record_tx_lifecycle_events", None);
 
+        let write_log_count = write_logs.iter().map(|(_, logs)| logs.len()).sum();
         let unique_updates = transaction
             .storage_dal()
-            .apply_storage_logs(&write_logs)
+            .apply_storage_logs(write_logs)
             .await;
         progress.end_stage("apply_storage_logs", Some(write_log_count));
 
-        let new_factory_deps = &self.miniblock.new_factory_deps;
-        let new_factory_deps_count = new_factory_deps.len();
-        if !new_factory_deps.is_empty() {
-            transaction
-                .storage_dal()
-                .insert_factory_deps(miniblock_number, new_factory_deps)
-                .await;
-        }
-        progress.end_stage("insert_factory_deps", Some(new_factory_deps_count));
-
-        // Factory deps should be inserted before using `count_deployed_contracts`.
         let deployed_contract_count = Self::count_deployed_contracts(&unique_updates);
         progress.end_stage("extract_contracts_deployed", Some(deployed_contract_count));
 
@@ -434,34 +659,17 @@ impl MiniblockSealCommand {
         progress.end_stage("extract_added_tokens", Some(added_tokens.len()));
         let added_tokens_len = added_tokens.len();
         if !added_tokens.is_empty() {
-            transaction.tokens_dal().add_tokens(added_tokens).await;
+            let provenance = TokenProvenance {
+                bridge_address: self.l2_erc20_bridge_addr,
+                added_in_miniblock: self.miniblock_number,
+            };
+            transaction
+                .tokens_dal()
+                .add_tokens(added_tokens, Some(provenance))
+                .await;
         }
         progress.end_stage("insert_tokens", Some(added_tokens_len));
 
-        let miniblock_events = self.extract_events(is_fictive);
-        let miniblock_event_count = miniblock_events
-            .iter()
-            .map(|(_, events)| events.len())
-            .sum();
-        progress.end_stage("extract_events", Some(miniblock_event_count));
-        transaction
-            .events_dal()
-            .save_events(miniblock_number, &miniblock_events)
-            .await;
-        progress.end_stage("insert_events", Some(miniblock_event_count));
-
-        let l2_to_l1_logs = self.extract_l2_to_l1_logs(is_fictive);
-        let l2_to_l1_log_count = l2_to_l1_logs
-            .iter()
-            .map(|(_, l2_to_l1_logs)| l2_to_l1_logs.len())
-            .sum();
-        progress.end_stage("extract_l2_to_l1_logs", Some(l2_to_l1_log_count));
-        transaction
-            .events_dal()
-            .save_l2_to_l1_logs(miniblock_number, &l2_to_l1_logs)
-            .await;
-        progress.end_stage("insert_l2_to_l1_logs", Some(l2_to_l1_log_count));
-
         let current_l2_virtual_block_info = transaction
             .storage_dal()
             .get_by_key(&StorageKey::new(
@@ -472,10 +680,67 @@ impl MiniblockSealCommand {
             .unwrap_or_default();
         let (current_l2_virtual_block_number, _) =
             unpack_block_info(h256_to_u256(current_l2_virtual_block_info));
+        current_l2_virtual_block_number
+    }
 
-        transaction.commit().await.unwrap();
-        progress.end_stage("commit_miniblock", None);
-        self.report_miniblock_metrics(started_at, current_l2_virtual_block_number);
+    /// Persists a part of the miniblock data independent of other parts.
+    async fn insert_data(
+        &self,
+        subtask: MiniblockDataSubtask,
+        storage: &mut StorageProcessor<'_>,
+        write_logs: &[(H256, Vec<StorageLog>)],
+        is_fictive: bool,
+    ) {
+        let miniblock_number = self.miniblock_number;
+        let mut progress = SealProgress::for_miniblock(is_fictive);
+
+        match subtask {
+            MiniblockDataSubtask::StorageLogs => {
+                let write_log_count = write_logs.iter().map(|(_, logs)| logs.len()).sum();
+                storage
+                    .storage_logs_dal()
+                    .insert_storage_logs(miniblock_number, write_logs)
+                    .await;
+                progress.end_stage("insert_storage_logs", Some(write_log_count));
+            }
+            MiniblockDataSubtask::FactoryDeps => {
+                let new_factory_deps = &self.miniblock.new_factory_deps;
+                let new_factory_deps_count = new_factory_deps.len();
+                if !new_factory_deps.is_empty() {
+                    storage
+                        .storage_dal()
+                        .insert_factory_deps(miniblock_number, new_factory_deps)
+                        .await;
+                }
+                progress.end_stage("insert_factory_deps", Some(new_factory_deps_count));
+            }
+            MiniblockDataSubtask::Events => {
+                let miniblock_events = self.extract_events(is_fictive);
+                let miniblock_event_count = miniblock_events
+                    .iter()
+                    .map(|(_, events)| events.len())
+                    .sum();
+                progress.end_stage("extract_events", Some(miniblock_event_count));
+                storage
+                    .events_dal()
+                    .save_events(miniblock_number, &miniblock_events)
+                    .await;
+                progress.end_stage("insert_events", Some(miniblock_event_count));
+            }
+            MiniblockDataSubtask::L2ToL1Logs => {
+                let l2_to_l1_logs = self.extract_l2_to_l1_logs(is_fictive);
+                let l2_to_l1_log_count = l2_to_l1_logs
+                    .iter()
+                    .map(|(_, l2_to_l1_logs)| l2_to_l1_logs.len())
+                    .sum();
+                progress.end_stage("extract_l2_to_l1_logs", Some(l2_to_l1_log_count));
+                storage
+                    .events_dal()
+                    .save_l2_to_l1_logs(miniblock_number, &l2_to_l1_logs)
+                    .await;
+                progress.end_stage("insert_l2_to_l1_logs", Some(l2_to_l1_log_count));
+            }
+        }
     }
 
     /// Performs several sanity checks to make sure that the miniblock is valid.
@@ -605,7 +870,8 @@ impl MiniblockSealCommand {
             self.miniblock.executed_transactions.len() as f64
         );
         let miniblock_latency =
-            ((millis_since_epoch() - self.miniblock.timestamp as u128 * 1_000) as f64) / 1_000.0;
+            (millis_since_epoch().saturating_sub(self.miniblock.timestamp as u128 * 1_000) as f64)
+                / 1_000.0;
         metrics::histogram!(
             "server.miniblock.latency",
             miniblock_latency,
@@ -1,6 +1,9 @@
 use futures::FutureExt;
 
-use std::time::Duration;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use db_test_macro::db_test;
 
@@ -9,15 +12,23 @@ use zksync_contracts::BaseSystemContractsHashes;
 use zksync_dal::ConnectionPool;
 use zksync_mempool::L2TxFilter;
 use zksync_types::{
-    block::BlockGasCount, tx::ExecutionMetrics, AccountTreeId, Address, L1BatchNumber,
-    MiniblockNumber, ProtocolVersionId, StorageKey, VmEvent, H256, U256,
+    block::BlockGasCount,
+    l1::{L1Tx, L1TxCommonData},
+    tx::ExecutionMetrics,
+    AccountTreeId, Address, Execute, L1BatchNumber, MiniblockNumber, PriorityOpId,
+    ProtocolVersionId, StorageKey, StorageLog, Transaction, VmEvent, H256, L1_GAS_PER_PUBDATA_BYTE,
+    U256,
 };
 use zksync_utils::time::seconds_since_epoch;
 
 use crate::state_keeper::tests::{create_l1_batch_metadata, default_l1_batch_env};
 
 use crate::state_keeper::{
-    io::{MiniblockParams, MiniblockSealer, StateKeeperIO},
+    backpressure::{BackpressureHandle, LaggingComponent},
+    io::{
+        seal_logic::{clear_unsealed_miniblock_data, ProtectiveReadsCommand},
+        MiniblockParams, MiniblockSealer, ProtectiveReadsWriter, StateKeeperIO,
+    },
     mempool_actor::l2_tx_filter,
     tests::{
         create_execution_result, create_transaction, create_updates_manager,
@@ -125,6 +136,73 @@ async fn test_filter_with_no_pending_batch(connection_pool: ConnectionPool) {
     assert_eq!(mempool.filter(), &want_filter);
 }
 
+fn create_l1_transaction(serial_id: PriorityOpId) -> Transaction {
+    let tx = L1Tx {
+        execute: Execute {
+            contract_address: Address::repeat_byte(0x11),
+            calldata: vec![],
+            value: U256::zero(),
+            factory_deps: None,
+        },
+        common_data: L1TxCommonData {
+            serial_id,
+            ..L1TxCommonData::default()
+        },
+        received_timestamp_ms: 0,
+    };
+    tx.into()
+}
+
+/// Ensure that the IO doesn't accept L2 transactions while the downstream pipeline lags behind,
+/// but still executes L1 priority operations.
+#[db_test]
+async fn throttled_mempool_io_only_accepts_priority_operations(connection_pool: ConnectionPool) {
+    let tester = Tester::new();
+    tester.genesis(&connection_pool).await;
+    tester
+        .insert_miniblock(&connection_pool, 1, 5, 55, 555)
+        .await;
+    tester.insert_sealed_batch(&connection_pool, 1).await;
+
+    let (backpressure_sender, backpressure) = BackpressureHandle::mock();
+    backpressure_sender.send_replace(Some(LaggingComponent::Prover));
+    let (mempool, mut guard) = tester.create_test_mempool_io(connection_pool, 1).await;
+    let mut mempool = mempool.with_backpressure(backpressure);
+    let tx_filter = l2_tx_filter(
+        &tester.create_gas_adjuster().await,
+        tester.fair_l2_gas_price(),
+    );
+    tester.insert_tx(&mut guard, tx_filter.fee_per_gas, tx_filter.gas_per_pubdata);
+
+    let batch_params = mempool
+        .wait_for_new_batch_params(Duration::from_secs(1))
+        .await;
+    assert!(batch_params.is_none());
+
+    guard.insert(
+        vec![create_l1_transaction(PriorityOpId(0))],
+        Default::default(),
+    );
+    mempool
+        .wait_for_new_batch_params(Duration::from_secs(10))
+        .await
+        .expect("No batch params in the test mempool");
+    let tx = mempool
+        .wait_for_next_tx(Duration::from_secs(1))
+        .await
+        .expect("Priority operation was not returned");
+    assert!(tx.is_l1());
+    let tx = mempool.wait_for_next_tx(Duration::from_secs(1)).await;
+    assert!(tx.is_none(), "{tx:?}");
+
+    backpressure_sender.send_replace(None);
+    let tx = mempool
+        .wait_for_next_tx(Duration::from_secs(1))
+        .await
+        .expect("L2 transaction was not returned");
+    assert!(!tx.is_l1());
+}
+
 async fn test_timestamps_are_distinct(
     connection_pool: ConnectionPool,
     prev_miniblock_timestamp: u64,
@@ -233,9 +311,10 @@ async fn processing_storage_logs_when_sealing_miniblock(connection_pool: Connect
     let seal_command = MiniblockSealCommand {
         l1_batch_number,
         miniblock_number: MiniblockNumber(3),
-        miniblock,
+        miniblock: Arc::new(miniblock),
         first_tx_index: 0,
         l1_gas_price: 100,
+        fair_pubdata_price: 100 * u64::from(L1_GAS_PER_PUBDATA_BYTE),
         fair_l2_gas_price: 100,
         base_fee_per_gas: 10,
         base_system_contracts_hashes: BaseSystemContractsHashes::default(),
@@ -308,9 +387,10 @@ async fn processing_events_when_sealing_miniblock(pool: ConnectionPool) {
     let seal_command = MiniblockSealCommand {
         l1_batch_number,
         miniblock_number,
-        miniblock,
+        miniblock: Arc::new(miniblock),
         first_tx_index: 0,
         l1_gas_price: 100,
+        fair_pubdata_price: 100 * u64::from(L1_GAS_PER_PUBDATA_BYTE),
         fair_l2_gas_price: 100,
         base_fee_per_gas: 10,
         base_system_contracts_hashes: BaseSystemContractsHashes::default(),
@@ -336,6 +416,173 @@ async fn processing_events_when_sealing_miniblock(pool: ConnectionPool) {
     }
 }
 
+fn create_seal_command_with_event(miniblock_number: MiniblockNumber) -> MiniblockSealCommand {
+    let l1_batch_number = L1BatchNumber(2);
+    let mut miniblock =
+        MiniblockUpdates::new(0, 1, H256::zero(), 1, Some(ProtocolVersionId::latest()));
+    let tx = create_transaction(10, 100);
+    let mut execution_result =
+        create_execution_result(0, [(U256::from(1), Query::InitialWrite(U256::from(2)))]);
+    execution_result.logs.events = vec![VmEvent {
+        location: (l1_batch_number, 0),
+        ..VmEvent::default()
+    }];
+    miniblock.extend_from_executed_transaction(
+        tx,
+        execution_result,
+        BlockGasCount::default(),
+        ExecutionMetrics::default(),
+        vec![],
+        vec![],
+    );
+
+    MiniblockSealCommand {
+        l1_batch_number,
+        miniblock_number,
+        miniblock: Arc::new(miniblock),
+        first_tx_index: 0,
+        l1_gas_price: 100,
+        fair_pubdata_price: 100 * u64::from(L1_GAS_PER_PUBDATA_BYTE),
+        fair_l2_gas_price: 100,
+        base_fee_per_gas: 10,
+        base_system_contracts_hashes: BaseSystemContractsHashes::default(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+        l2_erc20_bridge_addr: Address::default(),
+    }
+}
+
+/// Emulates parallel sealing of the specified miniblock failing after its data is persisted,
+/// but before its header is committed.
+async fn persist_unsealed_miniblock_data(pool: &ConnectionPool, miniblock_number: MiniblockNumber) {
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    conn.protocol_versions_dal()
+        .save_protocol_version_with_tx(Default::default())
+        .await;
+    conn.blocks_dal()
+        .drop_miniblock_data_foreign_keys()
+        .await
+        .unwrap();
+    create_seal_command_with_event(miniblock_number)
+        .seal(&mut conn)
+        .await;
+    conn.blocks_dal()
+        .delete_miniblocks(miniblock_number - 1)
+        .await
+        .unwrap();
+}
+
+async fn assert_no_miniblock_data(pool: &ConnectionPool, miniblock_number: MiniblockNumber) {
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    let logs = conn
+        .events_web3_dal()
+        .get_all_logs(MiniblockNumber(0))
+        .await
+        .unwrap();
+    assert!(logs.is_empty(), "{logs:?}");
+    let written_key = StorageKey::new(AccountTreeId::default(), H256::from_low_u64_be(1));
+    let written_key = written_key.hashed_key();
+    let values = conn
+        .storage_logs_dal()
+        .get_storage_values(&[written_key], miniblock_number)
+        .await;
+    assert_eq!(values[&written_key], None);
+}
+
+#[db_test]
+async fn clearing_data_of_unsealed_miniblock(pool: ConnectionPool) {
+    let miniblock_number = MiniblockNumber(3);
+    persist_unsealed_miniblock_data(&pool, miniblock_number).await;
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    clear_unsealed_miniblock_data(&mut conn).await;
+    drop(conn);
+
+    assert_no_miniblock_data(&pool, miniblock_number).await;
+}
+
+#[db_test]
+async fn miniblock_sealer_recovers_from_failed_parallel_sealing(pool: ConnectionPool) {
+    let miniblock_number = MiniblockNumber(3);
+    persist_unsealed_miniblock_data(&pool, miniblock_number).await;
+
+    // The sealer will exit immediately after the startup since its handle is dropped.
+    let (sealer, _) = MiniblockSealer::parallel(pool.clone(), 1);
+    sealer.run().await.unwrap();
+    assert_no_miniblock_data(&pool, miniblock_number).await;
+
+    // The miniblock can be sealed again once its leftover data is cleared.
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    create_seal_command_with_event(miniblock_number)
+        .seal(&mut conn)
+        .await;
+    let logs = conn
+        .events_web3_dal()
+        .get_all_logs(MiniblockNumber(0))
+        .await
+        .unwrap();
+    assert_eq!(logs.len(), 1, "{logs:?}");
+    drop(conn);
+
+    // Switching to sequential sealing doesn't restore foreign keys to miniblocks; this is an operator action.
+    let (sealer, _) = MiniblockSealer::new(pool.clone(), 1);
+    sealer.run().await.unwrap();
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    let foreign_keys = conn
+        .blocks_dal()
+        .get_miniblock_data_foreign_keys()
+        .await
+        .unwrap();
+    assert!(
+        foreign_keys.values().all(|&exists| !exists),
+        "{foreign_keys:?}"
+    );
+
+    // Restored keys are validated, so that the header of a miniblock with events cannot be removed separately.
+    conn.blocks_dal()
+        .restore_miniblock_data_foreign_keys()
+        .await
+        .unwrap();
+    let foreign_keys = conn
+        .blocks_dal()
+        .get_miniblock_data_foreign_keys()
+        .await
+        .unwrap();
+    assert!(
+        foreign_keys.values().all(|&exists| exists),
+        "{foreign_keys:?}"
+    );
+    conn.blocks_dal()
+        .delete_miniblocks(miniblock_number - 1)
+        .await
+        .unwrap_err();
+}
+
+#[db_test]
+async fn parallel_miniblock_sealer_requires_dropped_foreign_keys(pool: ConnectionPool) {
+    let (sealer, _) = MiniblockSealer::parallel(pool.clone(), 1);
+    let err = sealer.run().await.unwrap_err().to_string();
+    assert!(err.contains("foreign keys"), "{err}");
+
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    conn.blocks_dal()
+        .drop_miniblock_data_foreign_keys()
+        .await
+        .unwrap();
+    drop(conn);
+    let (sealer, _) = MiniblockSealer::parallel(pool.clone(), 1);
+    sealer.run().await.unwrap();
+}
+
+#[db_test]
+async fn restoring_foreign_keys_fails_on_orphaned_miniblock_data(pool: ConnectionPool) {
+    persist_unsealed_miniblock_data(&pool, MiniblockNumber(3)).await;
+
+    let mut conn = pool.access_storage_tagged("state_keeper").await.unwrap();
+    conn.blocks_dal()
+        .restore_miniblock_data_foreign_keys()
+        .await
+        .unwrap_err();
+}
+
 async fn test_miniblock_and_l1_batch_processing(
     pool: ConnectionPool,
     miniblock_sealer_capacity: usize,
@@ -493,6 +740,100 @@ async fn miniblock_sealer_handle_parallel_processing(pool: ConnectionPool) {
     sealer_handle.wait_for_all_commands().await;
 }
 
+#[db_test]
+async fn persisting_protective_reads(pool: ConnectionPool) {
+    let tester = Tester::new();
+    tester.genesis(&pool).await;
+    tester.insert_sealed_batch(&pool, 1).await;
+    let read_key = StorageKey::new(AccountTreeId::new(Address::repeat_byte(1)), H256::zero());
+    let read_log = StorageLog::new_read_log(read_key, H256::repeat_byte(0xff));
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .mark_protective_reads_as_pending(L1BatchNumber(1), &[read_log.to_test_log_query()])
+        .await
+        .unwrap();
+    let pending_l1_batches = storage
+        .blocks_dal()
+        .get_l1_batches_with_pending_protective_reads()
+        .await
+        .unwrap();
+    assert_eq!(pending_l1_batches, [L1BatchNumber(1)]);
+    drop(storage);
+
+    let (writer, mut writer_handle) = ProtectiveReadsWriter::new(pool.clone());
+    let writer_task = tokio::spawn(writer.run());
+    writer_handle
+        .submit(ProtectiveReadsCommand {
+            l1_batch_number: L1BatchNumber(1),
+            protective_reads: vec![read_log.to_test_log_query()],
+        })
+        .await;
+    writer_handle.wait_for_all_commands().await;
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let pending_l1_batches = storage
+        .blocks_dal()
+        .get_l1_batches_with_pending_protective_reads()
+        .await
+        .unwrap();
+    assert!(pending_l1_batches.is_empty(), "{pending_l1_batches:?}");
+    let protective_reads = storage
+        .storage_logs_dedup_dal()
+        .get_protective_reads_for_l1_batch(L1BatchNumber(1))
+        .await;
+    assert_eq!(protective_reads, [read_key].into());
+    drop(storage);
+
+    // The writer should shut down after its handle is dropped.
+    drop(writer_handle);
+    writer_task.await.unwrap().unwrap();
+}
+
+#[db_test]
+async fn persisting_pending_protective_reads_on_restart(pool: ConnectionPool) {
+    let tester = Tester::new();
+    tester.genesis(&pool).await;
+    tester.insert_sealed_batch(&pool, 1).await;
+    let read_keys: Vec<_> = (1..=3)
+        .map(|i| StorageKey::new(AccountTreeId::new(Address::repeat_byte(i)), H256::zero()))
+        .collect();
+    let read_logs: Vec<_> = read_keys
+        .iter()
+        .map(|&key| StorageLog::new_read_log(key, H256::repeat_byte(0xff)).to_test_log_query())
+        .collect();
+    // Emulate the node being stopped before protective reads of the sealed L1 batch are persisted.
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .mark_protective_reads_as_pending(L1BatchNumber(1), &read_logs)
+        .await
+        .unwrap();
+    drop(storage);
+
+    // Protective reads should be persisted when the state keeper IO is initialized.
+    tester.create_test_mempool_io(pool.clone(), 1).await;
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let pending_l1_batches = storage
+        .blocks_dal()
+        .get_l1_batches_with_pending_protective_reads()
+        .await
+        .unwrap();
+    assert!(pending_l1_batches.is_empty(), "{pending_l1_batches:?}");
+    let pending_reads = storage
+        .blocks_dal()
+        .get_pending_protective_reads(L1BatchNumber(1))
+        .await
+        .unwrap();
+    assert_eq!(pending_reads, None);
+    let protective_reads = storage
+        .storage_logs_dedup_dal()
+        .get_protective_reads_for_l1_batch(L1BatchNumber(1))
+        .await;
+    assert_eq!(protective_reads, read_keys.into_iter().collect());
+}
+
 /// Ensure that subsequent miniblocks that belong to the same L1 batch have different timestamps
 #[db_test]
 async fn different_timestamp_for_miniblocks_in_same_batch(connection_pool: ConnectionPool) {
@@ -511,3 +852,30 @@ async fn different_timestamp_for_miniblocks_in_same_batch(connection_pool: Conne
         .unwrap();
     assert!(next_timestamp > current_timestamp);
 }
+
+/// Ensure that miniblocks sealed early can be started without waiting for the next second.
+#[db_test]
+async fn starting_miniblock_without_waiting(connection_pool: ConnectionPool) {
+    let tester = Tester::new();
+    tester.genesis(&connection_pool).await;
+    let (mut mempool, _) = tester.create_test_mempool_io(connection_pool, 1).await;
+
+    let current_timestamp = seconds_since_epoch();
+    let started_at = Instant::now();
+    let MiniblockParams {
+        timestamp: next_timestamp,
+        ..
+    } = mempool
+        .new_miniblock_params_without_waiting(current_timestamp)
+        .await
+        .unwrap();
+    assert!(started_at.elapsed() < Duration::from_millis(500));
+    assert!(next_timestamp > current_timestamp);
+    assert!(next_timestamp <= current_timestamp + 2);
+
+    // The timestamp may run ahead of the wall clock by at most 1 second.
+    let params = mempool
+        .new_miniblock_params_without_waiting(current_timestamp + 5)
+        .await;
+    assert!(params.is_none());
+}
@@ -16,9 +16,14 @@ use zksync_types::{
 };
 
 use crate::{
+    gas_tracker::PubdataDaMode,
     genesis::create_genesis_l1_batch,
     l1_gas_price::GasAdjuster,
-    state_keeper::{io::MiniblockSealer, tests::create_transaction, MempoolGuard, MempoolIO},
+    state_keeper::{
+        io::{MiniblockSealer, ProtectiveReadsWriter},
+        tests::create_transaction,
+        MempoolGuard, MempoolIO,
+    },
 };
 
 #[derive(Debug)]
@@ -49,9 +54,10 @@ impl Tester {
             internal_enforced_l1_gas_price: None,
             poll_period: 10,
             max_l1_gas_price: None,
+            external_da_price_per_pubdata_byte: 0,
         };
 
-        GasAdjuster::new(eth_client, gas_adjuster_config)
+        GasAdjuster::new(eth_client, gas_adjuster_config, PubdataDaMode::Calldata)
             .await
             .unwrap()
     }
@@ -71,6 +77,9 @@ impl Tester {
         let (miniblock_sealer, miniblock_sealer_handle) =
             MiniblockSealer::new(pool.clone(), miniblock_sealer_capacity);
         tokio::spawn(miniblock_sealer.run());
+        let (protective_reads_writer, protective_reads_writer_handle) =
+            ProtectiveReadsWriter::new(pool.clone());
+        tokio::spawn(protective_reads_writer.run());
 
         let base_contract_hashes = self.base_system_contracts.hashes();
         let config = StateKeeperConfig {
@@ -85,6 +94,7 @@ impl Tester {
         let io = MempoolIO::new(
             mempool.clone(),
             miniblock_sealer_handle,
+            protective_reads_writer_handle,
             gas_adjuster,
             pool,
             &config,
@@ -93,7 +103,8 @@ impl Tester {
             BLOCK_GAS_LIMIT,
             L2ChainId(270),
         )
-        .await;
+        .await
+        .unwrap();
 
         (io, mempool)
     }
@@ -139,6 +150,7 @@ impl Tester {
                 base_fee_per_gas,
                 l1_gas_price,
                 l2_fair_gas_price,
+                fair_pubdata_price: None,
                 base_system_contracts_hashes: self.base_system_contracts.hashes(),
                 protocol_version: Some(ProtocolVersionId::latest()),
                 virtual_blocks: 0,
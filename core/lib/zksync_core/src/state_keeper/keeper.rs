@@ -84,6 +84,7 @@ impl ZkSyncStateKeeper {
             Err(Error::Fatal(err)) => Err(err).context("state_keeper failed"),
             Err(Error::Canceled) => {
                 tracing::info!("Stop signal received, state keeper is shutting down");
+                self.io.wait_for_pending_writes().await;
                 Ok(())
             }
         }
@@ -255,6 +256,41 @@ impl ZkSyncStateKeeper {
         Err(Error::Canceled)
     }
 
+    /// Seals the current miniblock and starts the next one. If the miniblock is `sealed_early` (i.e., before
+    /// its deadline), the next miniblock is started without waiting for the next timestamp if the IO allows it.
+    async fn seal_miniblock_and_start_next(
+        &mut self,
+        batch_executor: &BatchExecutorHandle,
+        updates_manager: &mut UpdatesManager,
+        sealed_early: bool,
+    ) -> Result<(), Error> {
+        self.io.seal_miniblock(updates_manager).await;
+
+        let prev_miniblock_timestamp = updates_manager.miniblock.timestamp;
+        let immediate_params = if sealed_early {
+            self.io
+                .new_miniblock_params_without_waiting(prev_miniblock_timestamp)
+                .await
+        } else {
+            None
+        };
+        let new_miniblock_params = if let Some(params) = immediate_params {
+            params
+        } else {
+            self.wait_for_new_miniblock_params(prev_miniblock_timestamp)
+                .await
+                .map_err(|e| e.context("wait_for_new_miniblock_params"))?
+        };
+        tracing::debug!(
+            "Initialized new miniblock #{} (L1 batch #{}) with timestamp {}",
+            self.io.current_miniblock_number(),
+            self.io.current_l1_batch_number(),
+            extractors::display_timestamp(new_miniblock_params.timestamp)
+        );
+        Self::start_next_miniblock(new_miniblock_params, updates_manager, batch_executor).await;
+        Ok(())
+    }
+
     async fn start_next_miniblock(
         params: MiniblockParams,
         updates_manager: &mut UpdatesManager,
@@ -301,7 +337,7 @@ impl ZkSyncStateKeeper {
                 miniblock_number
             );
             for tx in miniblock.txs {
-                let result = batch_executor.execute_tx(tx.clone()).await;
+                let result = batch_executor.execute_tx(tx.clone()).await?;
 
                 let TxExecutionResult::Success {
                     tx_result,
@@ -369,7 +405,7 @@ impl ZkSyncStateKeeper {
     ) -> Result<(), Error> {
         if let Some(protocol_upgrade_tx) = protocol_upgrade_tx {
             self.process_upgrade_tx(batch_executor, updates_manager, protocol_upgrade_tx)
-                .await;
+                .await?;
         }
 
         while !self.is_canceled() {
@@ -390,20 +426,8 @@ impl ZkSyncStateKeeper {
                     self.io.current_miniblock_number(),
                     self.io.current_l1_batch_number()
                 );
-                self.io.seal_miniblock(updates_manager).await;
-
-                let new_miniblock_params = self
-                    .wait_for_new_miniblock_params(updates_manager.miniblock.timestamp)
-                    .await
-                    .map_err(|e| e.context("wait_for_new_miniblock_params"))?;
-                tracing::debug!(
-                    "Initialized new miniblock #{} (L1 batch #{}) with timestamp {}",
-                    self.io.current_miniblock_number(),
-                    self.io.current_l1_batch_number(),
-                    extractors::display_timestamp(new_miniblock_params.timestamp)
-                );
-                Self::start_next_miniblock(new_miniblock_params, updates_manager, batch_executor)
-                    .await;
+                self.seal_miniblock_and_start_next(batch_executor, updates_manager, false)
+                    .await?;
             }
 
             let started_waiting = Instant::now();
@@ -425,7 +449,7 @@ impl ZkSyncStateKeeper {
             let tx_hash = tx.hash();
             let (seal_resolution, exec_result) = self
                 .process_one_tx(batch_executor, updates_manager, tx.clone())
-                .await;
+                .await?;
 
             match &seal_resolution {
                 SealResolution::NoSeal | SealResolution::IncludeAndSeal => {
@@ -441,6 +465,34 @@ impl ZkSyncStateKeeper {
                             "Tx inclusion seal resolution must be a result of a successful tx execution",
                         );
                     };
+
+                    // Gas limits are not bounded by `u64` for L1 transactions, so the conversion saturates.
+                    let tx_gas_used = tx
+                        .gas_limit()
+                        .min(u64::MAX.into())
+                        .as_u64()
+                        .saturating_sub(tx_result.refunds.gas_refunded.into());
+                    if self
+                        .sealer
+                        .should_seal_miniblock_before_tx(updates_manager, tx_gas_used)
+                    {
+                        tracing::debug!(
+                            "Miniblock #{} (L1 batch #{}) should be sealed before transaction {tx_hash} \
+                             using {tx_gas_used} gas to stay within the miniblock gas limit",
+                            self.io.current_miniblock_number(),
+                            self.io.current_l1_batch_number()
+                        );
+                        metrics::increment_counter!(
+                            "server.state_keeper.miniblock_gas_limit_seals"
+                        );
+                        batch_executor.rollback_last_tx().await;
+                        self.io.rollback(tx).await;
+                        // The transaction will be re-executed in the next miniblock.
+                        self.seal_miniblock_and_start_next(batch_executor, updates_manager, true)
+                            .await?;
+                        continue;
+                    }
+
                     let ExecutionMetricsForCriteria {
                         l1_gas: tx_l1_gas_this_tx,
                         execution_metrics: tx_execution_metrics,
@@ -481,14 +533,14 @@ impl ZkSyncStateKeeper {
         batch_executor: &BatchExecutorHandle,
         updates_manager: &mut UpdatesManager,
         protocol_upgrade_tx: ProtocolUpgradeTx,
-    ) {
+    ) -> Result<(), Error> {
         // Sanity check: protocol upgrade tx must be the first one in the batch.
         assert_eq!(updates_manager.pending_executed_transactions_len(), 0);
 
         let tx: Transaction = protocol_upgrade_tx.into();
         let (seal_resolution, exec_result) = self
             .process_one_tx(batch_executor, updates_manager, tx.clone())
-            .await;
+            .await?;
 
         match &seal_resolution {
             SealResolution::NoSeal | SealResolution::IncludeAndSeal => {
@@ -535,6 +587,7 @@ impl ZkSyncStateKeeper {
                 );
             }
         };
+        Ok(())
     }
 
     /// Executes one transaction in the batch executor, and then decides whether the batch should be sealed.
@@ -549,8 +602,8 @@ impl ZkSyncStateKeeper {
         batch_executor: &BatchExecutorHandle,
         updates_manager: &mut UpdatesManager,
         tx: Transaction,
-    ) -> (SealResolution, TxExecutionResult) {
-        let exec_result = batch_executor.execute_tx(tx.clone()).await;
+    ) -> Result<(SealResolution, TxExecutionResult), Error> {
+        let exec_result = batch_executor.execute_tx(tx.clone()).await?;
         let resolution = match &exec_result {
             TxExecutionResult::BootloaderOutOfGasForTx => {
                 metrics::increment_counter!(
@@ -616,6 +669,7 @@ impl ZkSyncStateKeeper {
                 } = *bootloader_dry_run_metrics;
 
                 let encoding_len = tx.encoding_len();
+                let da_mode = self.sealer.pubdata_da_mode();
 
                 let logs_to_apply_iter = tx_result
                     .logs
@@ -625,11 +679,11 @@ impl ZkSyncStateKeeper {
                 let block_writes_metrics = updates_manager
                     .storage_writes_deduplicator
                     .apply_and_rollback(logs_to_apply_iter.clone());
-                let block_writes_l1_gas = gas_count_from_writes(&block_writes_metrics);
+                let block_writes_l1_gas = gas_count_from_writes(&block_writes_metrics, da_mode);
 
                 let tx_writes_metrics =
                     StorageWritesDeduplicator::apply_on_empty_state(logs_to_apply_iter);
-                let tx_writes_l1_gas = gas_count_from_writes(&tx_writes_metrics);
+                let tx_writes_l1_gas = gas_count_from_writes(&tx_writes_metrics, da_mode);
                 let tx_gas_excluding_writes = tx_l1_gas_this_tx + finish_block_l1_gas;
 
                 let tx_data = SealData {
@@ -657,6 +711,6 @@ impl ZkSyncStateKeeper {
                 )
             }
         };
-        (resolution, exec_result)
+        Ok((resolution, exec_result))
     }
 }
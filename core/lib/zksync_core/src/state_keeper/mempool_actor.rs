@@ -3,12 +3,14 @@ use crate::l1_gas_price::L1GasPriceProvider;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
+
 use tokio::sync::watch;
 use vm::utils::fee::derive_base_fee_and_gas_per_pubdata;
 use zksync_config::configs::chain::MempoolConfig;
 
 use zksync_dal::ConnectionPool;
 use zksync_mempool::L2TxFilter;
+use zksync_types::Transaction;
 
 /// Creates a mempool filter for L2 transactions based on the current L1 gas price.
 /// The filter is used to filter out transactions from the mempool that do not cover expenses
@@ -28,12 +30,18 @@ pub fn l2_tx_filter<G: L1GasPriceProvider>(
     }
 }
 
+/// Interval between pruning transaction lifecycle events past their retention period.
+const TX_LIFECYCLE_EVENTS_PRUNING_INTERVAL: Duration = Duration::from_secs(600);
+/// Maximum number of transaction lifecycle events removed by a single DB query.
+const TX_LIFECYCLE_EVENTS_PRUNING_CHUNK_SIZE: usize = 10_000;
+
 #[derive(Debug)]
 pub struct MempoolFetcher<G> {
     mempool: MempoolGuard,
     l1_gas_price_provider: Arc<G>,
     sync_interval: Duration,
     sync_batch_size: usize,
+    tx_lifecycle_events_retention: Duration,
 }
 
 impl<G: L1GasPriceProvider> MempoolFetcher<G> {
@@ -47,6 +55,26 @@ impl<G: L1GasPriceProvider> MempoolFetcher<G> {
             l1_gas_price_provider,
             sync_interval: config.sync_interval(),
             sync_batch_size: config.sync_batch_size,
+            tx_lifecycle_events_retention: config.tx_lifecycle_events_retention(),
+        }
+    }
+
+    /// Prunes transaction lifecycle events past their retention period. Returns the number of removed events.
+    async fn prune_tx_lifecycle_events(&self, pool: &ConnectionPool) -> anyhow::Result<u64> {
+        let mut storage = pool.access_storage_tagged("state_keeper").await?;
+        let mut total_removed_events = 0;
+        loop {
+            let removed_events = storage
+                .tx_lifecycle_dal()
+                .prune_events(
+                    self.tx_lifecycle_events_retention,
+                    TX_LIFECYCLE_EVENTS_PRUNING_CHUNK_SIZE,
+                )
+                .await?;
+            total_removed_events += removed_events;
+            if removed_events < TX_LIFECYCLE_EVENTS_PRUNING_CHUNK_SIZE as u64 {
+                return Ok(total_removed_events);
+            }
         }
     }
 
@@ -70,12 +98,29 @@ impl<G: L1GasPriceProvider> MempoolFetcher<G> {
             storage.transactions_dal().reset_mempool().await;
         }
 
+        let mut last_pruned_at: Option<Instant> = None;
         loop {
             if *stop_receiver.borrow() {
                 tracing::info!("Stop signal received, mempool is shutting down");
                 break;
             }
             let started_at = Instant::now();
+            let should_prune = last_pruned_at.map_or(true, |pruned_at| {
+                pruned_at.elapsed() >= TX_LIFECYCLE_EVENTS_PRUNING_INTERVAL
+            });
+            if should_prune {
+                last_pruned_at = Some(started_at);
+                // Lifecycle events are informational, so pruning errors are not fatal.
+                match self.prune_tx_lifecycle_events(&pool).await {
+                    Ok(removed_events) => {
+                        tracing::debug!("Pruned {removed_events} transaction lifecycle events");
+                    }
+                    Err(err) => {
+                        tracing::warn!("Failed pruning transaction lifecycle events: {err:#}");
+                    }
+                }
+            }
+
             let mut storage = pool.access_storage_tagged("state_keeper").await.unwrap();
             let mempool_info = self.mempool.get_mempool_info();
             let l2_tx_filter = l2_tx_filter(self.l1_gas_price_provider.as_ref(), fair_l2_gas_price);
@@ -91,6 +136,15 @@ impl<G: L1GasPriceProvider> MempoolFetcher<G> {
                 )
                 .await;
             let all_transactions_loaded = transactions.len() < self.sync_batch_size;
+            let tx_hashes: Vec<_> = transactions.iter().map(Transaction::hash).collect();
+            // Lifecycle events are informational, so failing to record them shouldn't stop the fetcher.
+            if let Err(err) = storage
+                .tx_lifecycle_dal()
+                .record_entered_mempool(&tx_hashes)
+                .await
+            {
+                tracing::warn!("Failed recording transactions entering mempool: {err}");
+            }
             self.mempool.insert(transactions, nonces);
             metrics::histogram!("server.state_keeper.mempool_sync", started_at.elapsed());
             if all_transactions_loaded {
@@ -5,12 +5,15 @@ use std::sync::Arc;
 use zksync_config::{
     configs::chain::{MempoolConfig, NetworkConfig, StateKeeperConfig},
     constants::MAX_TXS_IN_BLOCK,
-    ContractsConfig, DBConfig,
+    ContractsConfig, DBConfig, ObjectStoreConfig,
 };
 use zksync_dal::ConnectionPool;
+use zksync_state::RocksdbCatchUpMode;
 use zksync_types::L2ChainId;
 
+pub(crate) mod backpressure;
 mod batch_executor;
+pub(crate) mod execution_hints;
 pub(crate) mod extractors;
 pub(crate) mod io;
 mod keeper;
@@ -22,14 +25,28 @@ pub(crate) mod types;
 pub(crate) mod updates;
 
 pub use self::{
-    batch_executor::{L1BatchExecutorBuilder, MainBatchExecutorBuilder},
+    batch_executor::{L1BatchExecutorBuilder, L1BatchStorageFactory, MainBatchExecutorBuilder},
+    execution_hints::TxExecutionHints,
     keeper::ZkSyncStateKeeper,
-    seal_criteria::SealManager,
+    seal_criteria::{SealManager, SealingStatusHandle},
+};
+pub(crate) use self::{
+    io::{MiniblockSealer, ProtectiveReadsWriter},
+    mempool_actor::MempoolFetcher,
+    types::MempoolGuard,
 };
-pub(crate) use self::{io::MiniblockSealer, mempool_actor::MempoolFetcher, types::MempoolGuard};
 
-use self::io::{MempoolIO, MiniblockSealerHandle};
-use crate::l1_gas_price::L1GasPriceProvider;
+use self::{
+    backpressure::BackpressureHandle,
+    io::{MempoolIO, MiniblockSealerHandle, ProtectiveReadsWriterHandle},
+};
+use crate::{
+    block_reverter::{BlockReverter, L1ExecutedBatchesRevert},
+    dev_mode::DevModeHandle,
+    gas_tracker::PubdataDaMode,
+    l1_gas_price::L1GasPriceProvider,
+    vm_thread_pool::VmThreadPool,
+};
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn create_state_keeper<G>(
@@ -38,12 +55,19 @@ pub(crate) async fn create_state_keeper<G>(
     db_config: &DBConfig,
     network_config: &NetworkConfig,
     mempool_config: &MempoolConfig,
+    object_store_config: &ObjectStoreConfig,
     pool: ConnectionPool,
     mempool: MempoolGuard,
     l1_gas_price_provider: Arc<G>,
     miniblock_sealer_handle: MiniblockSealerHandle,
+    protective_reads_writer_handle: ProtectiveReadsWriterHandle,
+    batch_executor_thread_pool: VmThreadPool,
+    execution_hints: Option<TxExecutionHints>,
+    sealing_status: Option<SealingStatusHandle>,
+    dev_mode: Option<&DevModeHandle>,
+    backpressure: Option<BackpressureHandle>,
     stop_receiver: watch::Receiver<bool>,
-) -> ZkSyncStateKeeper
+) -> anyhow::Result<ZkSyncStateKeeper>
 where
     G: L1GasPriceProvider + 'static + Send + Sync,
 {
@@ -54,32 +78,69 @@ where
         MAX_TXS_IN_BLOCK
     );
 
-    let batch_executor_base = MainBatchExecutorBuilder::new(
+    let pubdata_da_mode = PubdataDaMode::new(
+        state_keeper_config.l1_batch_commitment_mode,
+        state_keeper_config.pubdata_sending_mode,
+    );
+    let mut batch_executor_base = MainBatchExecutorBuilder::new(
         db_config.state_keeper_db_path.clone(),
         pool.clone(),
         state_keeper_config.max_allowed_l2_tx_gas_limit.into(),
         state_keeper_config.save_call_traces,
         state_keeper_config.upload_witness_inputs_to_gcs,
-    );
+        pubdata_da_mode,
+    )
+    .with_thread_pool(batch_executor_thread_pool);
+    if let Some(execution_hints) = execution_hints {
+        batch_executor_base = batch_executor_base.with_execution_hints(execution_hints);
+    }
+    if db_config.state_keeper_db_bulk_load {
+        batch_executor_base =
+            batch_executor_base.with_rocksdb_catch_up_mode(RocksdbCatchUpMode::BulkLoad);
+    }
+    if let Some(fork) = dev_mode.and_then(|dev_mode| dev_mode.fork()) {
+        batch_executor_base = batch_executor_base.with_fork(fork);
+    }
 
-    let io = MempoolIO::new(
+    let mut io = MempoolIO::new(
         mempool,
         miniblock_sealer_handle,
+        protective_reads_writer_handle,
         l1_gas_price_provider,
-        pool,
+        pool.clone(),
         &state_keeper_config,
         mempool_config.delay_interval(),
         contracts_config.l2_erc20_bridge_addr,
         state_keeper_config.validation_computational_gas_limit,
         L2ChainId(network_config.zksync_network_id),
     )
-    .await;
+    .await?
+    .with_object_store_config(object_store_config.clone());
+    if let Some(dev_mode) = dev_mode {
+        let block_reverter = BlockReverter::new(
+            db_config.state_keeper_db_path.clone(),
+            db_config.merkle_tree.path.clone(),
+            None,
+            pool,
+            // L1 batches are marked as executed without sending anything to L1 in the dev mode.
+            L1ExecutedBatchesRevert::Allowed,
+        );
+        io = io
+            .with_dev_mode_clock(dev_mode.clock())
+            .with_dev_mode_reverter(dev_mode.state_reverter(block_reverter));
+    }
+    if let Some(backpressure) = backpressure {
+        io = io.with_backpressure(backpressure);
+    }
 
-    let sealer = SealManager::new(state_keeper_config);
-    ZkSyncStateKeeper::new(
+    let mut sealer = SealManager::new(state_keeper_config);
+    if let Some(sealing_status) = sealing_status {
+        sealer = sealer.with_sealing_status(sealing_status);
+    }
+    Ok(ZkSyncStateKeeper::new(
         stop_receiver,
         Box::new(io),
         Box::new(batch_executor_base),
         sealer,
-    )
+    ))
 }
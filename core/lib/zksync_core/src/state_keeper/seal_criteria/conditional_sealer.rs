@@ -4,6 +4,7 @@
 //! which unconditionally follows the instructions from the main node).
 
 use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_types::api::SealCriterionUtilization;
 
 use super::{criteria, SealCriterion, SealData, SealResolution};
 
@@ -91,6 +92,18 @@ impl ConditionalSealer {
         final_seal_resolution
     }
 
+    /// Returns utilization of all criteria by an L1 batch with the specified data.
+    pub(super) fn utilization(
+        &self,
+        tx_count: usize,
+        block_data: &SealData,
+    ) -> Vec<SealCriterionUtilization> {
+        self.sealers
+            .iter()
+            .map(|sealer| sealer.utilization(&self.config, tx_count, block_data))
+            .collect()
+    }
+
     fn default_sealers() -> Vec<Box<dyn SealCriterion>> {
         vec![
             Box::new(criteria::SlotsCriterion),
@@ -1,3 +1,5 @@
+use zksync_types::api::SealCriterionUtilization;
+
 use crate::{
     gas_tracker::new_block_gas_count,
     state_keeper::seal_criteria::{SealCriterion, SealData, SealResolution, StateKeeperConfig},
@@ -40,6 +42,19 @@ impl SealCriterion for GasCriterion {
         }
     }
 
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+    ) -> SealCriterionUtilization {
+        let block_bound =
+            (config.max_single_tx_gas as f64 * config.close_block_at_gas_percentage).round() as u64;
+        let gas_count = &block_data.gas_count;
+        let used = gas_count.commit.max(gas_count.prove).max(gas_count.execute);
+        SealCriterionUtilization::new(self.prom_criterion_name(), used.into(), block_bound)
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "gas"
     }
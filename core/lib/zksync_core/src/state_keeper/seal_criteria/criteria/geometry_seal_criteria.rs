@@ -2,6 +2,7 @@ use std::fmt;
 use vm::constants::{ERGS_PER_CIRCUIT, MAX_CYCLES_FOR_TX};
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::{
+    api::SealCriterionUtilization,
     circuit::{GEOMETRY_CONFIG, SCHEDULER_UPPER_BOUND},
     tx::tx_execution_info::{DeduplicatedWritesMetrics, ExecutionMetrics},
 };
@@ -59,6 +60,22 @@ where
         }
     }
 
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+    ) -> SealCriterionUtilization {
+        let close_bound =
+            (T::limit_per_block() as f64 * config.close_block_at_geometry_percentage).round();
+        let used = T::extract(&block_data.execution_metrics, &block_data.writes_metrics);
+        SealCriterionUtilization::new(
+            T::PROM_METRIC_CRITERION_NAME,
+            used as u64,
+            close_bound as u64,
+        )
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         T::PROM_METRIC_CRITERION_NAME
     }
@@ -1,4 +1,4 @@
-use zksync_types::MAX_PUBDATA_PER_L1_BATCH;
+use zksync_types::{api::SealCriterionUtilization, MAX_PUBDATA_PER_L1_BATCH};
 
 use crate::state_keeper::seal_criteria::{
     SealCriterion, SealData, SealResolution, StateKeeperConfig,
@@ -36,6 +36,22 @@ impl SealCriterion for PubDataBytesCriterion {
         }
     }
 
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+    ) -> SealCriterionUtilization {
+        let include_and_seal_bound =
+            (MAX_PUBDATA_PER_L1_BATCH as f64 * config.close_block_at_eth_params_percentage).round();
+        let block_size = block_data.execution_metrics.size() + block_data.writes_metrics.size();
+        SealCriterionUtilization::new(
+            self.prom_criterion_name(),
+            block_size as u64,
+            include_and_seal_bound as u64,
+        )
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "pub_data_size"
     }
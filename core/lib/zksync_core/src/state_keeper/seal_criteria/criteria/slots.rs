@@ -1,3 +1,5 @@
+use zksync_types::api::SealCriterionUtilization;
+
 use crate::state_keeper::seal_criteria::{
     SealCriterion, SealData, SealResolution, StateKeeperConfig,
 };
@@ -22,6 +24,19 @@ impl SealCriterion for SlotsCriterion {
         }
     }
 
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        tx_count: usize,
+        _block_data: &SealData,
+    ) -> SealCriterionUtilization {
+        SealCriterionUtilization::new(
+            self.prom_criterion_name(),
+            tx_count as u64,
+            config.transaction_slots as u64,
+        )
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "slots"
     }
@@ -1,4 +1,5 @@
 use vm::constants::BOOTLOADER_TX_ENCODING_SPACE;
+use zksync_types::api::SealCriterionUtilization;
 
 use crate::state_keeper::seal_criteria::{
     SealCriterion, SealData, SealResolution, StateKeeperConfig,
@@ -34,6 +35,22 @@ impl SealCriterion for TxEncodingSizeCriterion {
         }
     }
 
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        _tx_count: usize,
+        block_data: &SealData,
+    ) -> SealCriterionUtilization {
+        let include_and_seal_bound = (BOOTLOADER_TX_ENCODING_SPACE as f64
+            * config.close_block_at_geometry_percentage)
+            .round();
+        SealCriterionUtilization::new(
+            self.prom_criterion_name(),
+            block_data.cumulative_size as u64,
+            include_and_seal_bound as u64,
+        )
+    }
+
     fn prom_criterion_name(&self) -> &'static str {
         "tx_encoding_size"
     }
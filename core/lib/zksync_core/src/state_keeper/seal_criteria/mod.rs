@@ -15,19 +15,23 @@ use vm::TransactionVmExt;
 
 use zksync_config::configs::chain::StateKeeperConfig;
 use zksync_types::{
+    api::SealCriterionUtilization,
     block::BlockGasCount,
     fee::TransactionExecutionMetrics,
     tx::tx_execution_info::{DeduplicatedWritesMetrics, ExecutionMetrics},
-    Transaction,
+    L1BatchNumber, Transaction,
 };
 use zksync_utils::time::millis_since;
 
 mod conditional_sealer;
 pub(super) mod criteria;
+mod status;
 
 pub(crate) use self::conditional_sealer::ConditionalSealer;
+use self::status::PendingL1BatchStatus;
+pub use self::status::SealingStatusHandle;
 use super::{extractors, updates::UpdatesManager};
-use crate::gas_tracker::{gas_count_from_tx_and_metrics, gas_count_from_writes};
+use crate::gas_tracker::{gas_count_from_tx_and_metrics, gas_count_from_writes, PubdataDaMode};
 
 /// Reported decision regarding block sealing.
 #[derive(Debug, Clone, PartialEq)]
@@ -99,11 +103,12 @@ impl SealData {
     pub(crate) fn for_transaction(
         transaction: Transaction,
         tx_metrics: &TransactionExecutionMetrics,
+        da_mode: PubdataDaMode,
     ) -> Self {
         let execution_metrics = ExecutionMetrics::from_tx_metrics(tx_metrics);
         let writes_metrics = DeduplicatedWritesMetrics::from_tx_metrics(tx_metrics);
-        let gas_count = gas_count_from_tx_and_metrics(&transaction, &execution_metrics)
-            + gas_count_from_writes(&writes_metrics);
+        let gas_count = gas_count_from_tx_and_metrics(&transaction, &execution_metrics, da_mode)
+            + gas_count_from_writes(&writes_metrics, da_mode);
         Self {
             execution_metrics,
             gas_count,
@@ -123,6 +128,14 @@ pub(super) trait SealCriterion: fmt::Debug + Send + 'static {
         tx_data: &SealData,
     ) -> SealResolution;
 
+    /// Returns utilization of this criterion by an L1 batch with the specified data.
+    fn utilization(
+        &self,
+        config: &StateKeeperConfig,
+        tx_count: usize,
+        block_data: &SealData,
+    ) -> SealCriterionUtilization;
+
     // We need self here only for rust restrictions for creating an object from trait
     // https://doc.rust-lang.org/reference/items/traits.html#object-safety
     fn prom_criterion_name(&self) -> &'static str;
@@ -144,12 +157,20 @@ pub struct SealManager {
     /// Miniblock sealer function used to determine if we should seal the miniblock.
     /// If any of the miniblock sealers returns `true`, the miniblock will be sealed.
     miniblock_sealers: Vec<Box<SealerFn>>,
+    /// Maximum gas used by transactions in a miniblock.
+    max_gas_per_miniblock: Option<u64>,
+    /// DA mode of the chain used to estimate L1 gas spent on publishing storage writes.
+    pubdata_da_mode: PubdataDaMode,
+    /// Handle used to report utilization of seal criteria for the pending L1 batch.
+    sealing_status: Option<SealingStatusHandle>,
 }
 
 impl fmt::Debug for SealManager {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter
             .debug_struct("SealManager")
+            .field("max_gas_per_miniblock", &self.max_gas_per_miniblock)
+            .field("pubdata_da_mode", &self.pubdata_da_mode)
             .finish_non_exhaustive()
     }
 }
@@ -157,21 +178,37 @@ impl fmt::Debug for SealManager {
 impl SealManager {
     /// Creates a default pre-configured seal manager for the main node.
     pub(super) fn new(config: StateKeeperConfig) -> Self {
-        let timeout_batch_sealer = Self::timeout_batch_sealer(config.block_commit_deadline_ms);
-        let timeout_miniblock_sealer =
-            Self::timeout_miniblock_sealer(config.miniblock_commit_deadline_ms);
-        // Currently, it's assumed that timeout is the only criterion for miniblock sealing.
-        // If this doesn't hold and some miniblocks are sealed in less than 1 second,
-        // then state keeper will be blocked waiting for the miniblock timestamp to be changed.
-        let miniblock_sealers = vec![timeout_miniblock_sealer];
+        let (batch_sealer, miniblock_sealer) = if config.dev_mode {
+            // In the dev mode, each transaction is sealed in its own L1 batch. This way, block timestamps
+            // are chosen when a transaction arrives (rather than when the previous block is sealed),
+            // so that they reflect time adjustments made via the API in between.
+            (
+                Self::instant_batch_sealer(),
+                Self::instant_miniblock_sealer(),
+            )
+        } else {
+            // Besides timeout, miniblocks can be sealed by the gas limit (see `should_seal_miniblock_before_tx()`).
+            // If some miniblocks are sealed in less than 1 second, then state keeper will be blocked
+            // waiting for the miniblock timestamp to be changed.
+            (
+                Self::timeout_batch_sealer(config.block_commit_deadline_ms),
+                Self::timeout_miniblock_sealer(config.miniblock_commit_deadline_ms),
+            )
+        };
+        let miniblock_sealers = vec![miniblock_sealer];
+        let pubdata_da_mode =
+            PubdataDaMode::new(config.l1_batch_commitment_mode, config.pubdata_sending_mode);
+        let max_gas_per_miniblock = config.max_gas_per_miniblock;
 
         let conditional_sealer = ConditionalSealer::new(config);
 
         Self::custom(
             Some(conditional_sealer),
-            vec![timeout_batch_sealer],
+            vec![batch_sealer],
             miniblock_sealers,
         )
+        .with_pubdata_da_mode(pubdata_da_mode)
+        .with_max_gas_per_miniblock(max_gas_per_miniblock)
     }
 
     /// Allows to create a seal manager object from externally-defined sealers.
@@ -184,9 +221,39 @@ impl SealManager {
             conditional_sealer,
             unconditional_sealers,
             miniblock_sealers,
+            max_gas_per_miniblock: None,
+            pubdata_da_mode: PubdataDaMode::default(),
+            sealing_status: None,
         }
     }
 
+    /// Sets the DA mode used to estimate L1 gas for storage writes. By default, pubdata
+    /// is assumed to be sent as calldata.
+    #[must_use]
+    pub fn with_pubdata_da_mode(mut self, pubdata_da_mode: PubdataDaMode) -> Self {
+        self.pubdata_da_mode = pubdata_da_mode;
+        self
+    }
+
+    /// Sets the maximum gas used by transactions in a miniblock. By default, miniblock gas is not limited.
+    #[must_use]
+    pub fn with_max_gas_per_miniblock(mut self, max_gas_per_miniblock: Option<u64>) -> Self {
+        self.max_gas_per_miniblock = max_gas_per_miniblock;
+        self
+    }
+
+    /// Makes the seal manager report utilization of seal criteria for the pending L1 batch
+    /// to the provided handle.
+    #[must_use]
+    pub fn with_sealing_status(mut self, sealing_status: SealingStatusHandle) -> Self {
+        self.sealing_status = Some(sealing_status);
+        self
+    }
+
+    pub(super) fn pubdata_da_mode(&self) -> PubdataDaMode {
+        self.pubdata_da_mode
+    }
+
     /// Creates a sealer function that would seal the batch because of the timeout.
     fn timeout_batch_sealer(block_commit_deadline_ms: u64) -> Box<SealerFn> {
         const RULE_NAME: &str = "no_txs_timeout";
@@ -208,6 +275,11 @@ impl SealManager {
         })
     }
 
+    /// Seals every non-empty batch.
+    fn instant_batch_sealer() -> Box<SealerFn> {
+        Box::new(|_| true)
+    }
+
     /// Creates a sealer function that would seal the miniblock because of the timeout.
     /// Will only trigger for the non-empty miniblocks.
     fn timeout_miniblock_sealer(miniblock_commit_deadline_ms: u64) -> Box<SealerFn> {
@@ -221,6 +293,11 @@ impl SealManager {
         })
     }
 
+    /// Seals every non-empty miniblock, i.e., each miniblock contains exactly one transaction.
+    fn instant_miniblock_sealer() -> Box<SealerFn> {
+        Box::new(|manager| !manager.miniblock.executed_transactions.is_empty())
+    }
+
     pub(super) fn should_seal_l1_batch(
         &self,
         l1_batch_number: u32,
@@ -229,17 +306,28 @@ impl SealManager {
         block_data: &SealData,
         tx_data: &SealData,
     ) -> SealResolution {
-        if let Some(sealer) = &self.conditional_sealer {
-            sealer.should_seal_l1_batch(
-                l1_batch_number,
-                block_open_timestamp_ms,
+        let Some(sealer) = &self.conditional_sealer else {
+            return SealResolution::NoSeal;
+        };
+        let resolution = sealer.should_seal_l1_batch(
+            l1_batch_number,
+            block_open_timestamp_ms,
+            tx_count,
+            block_data,
+            tx_data,
+        );
+
+        if let Some(sealing_status) = &self.sealing_status {
+            // If the batch is sealed, the next one is empty until a transaction is executed in it.
+            let status = (resolution == SealResolution::NoSeal).then(|| PendingL1BatchStatus {
+                l1_batch_number: L1BatchNumber(l1_batch_number),
+                batch_timestamp: (block_open_timestamp_ms / 1_000) as u64,
                 tx_count,
-                block_data,
-                tx_data,
-            )
-        } else {
-            SealResolution::NoSeal
+                criteria: sealer.utilization(tx_count, block_data),
+            });
+            sealing_status.set(status);
         }
+        resolution
     }
 
     pub(super) fn should_seal_l1_batch_unconditionally(
@@ -247,11 +335,17 @@ impl SealManager {
         updates_manager: &UpdatesManager,
     ) -> bool {
         // Regardless of which sealers are provided, we never want to seal an empty batch.
-        updates_manager.pending_executed_transactions_len() != 0
+        let should_seal = updates_manager.pending_executed_transactions_len() != 0
             && self
                 .unconditional_sealers
                 .iter()
-                .any(|sealer| (sealer)(updates_manager))
+                .any(|sealer| (sealer)(updates_manager));
+        if should_seal {
+            if let Some(sealing_status) = &self.sealing_status {
+                sealing_status.set(None);
+            }
+        }
+        should_seal
     }
 
     pub(super) fn should_seal_miniblock(&self, updates_manager: &UpdatesManager) -> bool {
@@ -264,10 +358,28 @@ impl SealManager {
             .iter()
             .any(|sealer| (sealer)(updates_manager))
     }
+
+    /// Checks whether the miniblock should be sealed before including the last executed transaction,
+    /// which used `tx_gas_used` gas, so that the miniblock doesn't exceed the gas limit. An empty miniblock
+    /// is never sealed; a transaction exceeding the limit on its own is included into a dedicated miniblock.
+    pub(super) fn should_seal_miniblock_before_tx(
+        &self,
+        updates_manager: &UpdatesManager,
+        tx_gas_used: u64,
+    ) -> bool {
+        let Some(max_gas) = self.max_gas_per_miniblock else {
+            return false;
+        };
+        let miniblock = &updates_manager.miniblock;
+        !miniblock.executed_transactions.is_empty()
+            && miniblock.gas_used().saturating_add(tx_gas_used) > max_gas
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use zksync_utils::time::seconds_since_epoch;
 
     use super::*;
@@ -294,7 +406,7 @@ mod tests {
 
         let mut manager = create_updates_manager();
         // Empty miniblock should not trigger.
-        manager.miniblock.timestamp = seconds_since_epoch() - 10;
+        Arc::make_mut(&mut manager.miniblock).timestamp = seconds_since_epoch() - 10;
         assert!(
             !timeout_miniblock_sealer(&manager),
             "Empty miniblock shouldn't be sealed"
@@ -310,10 +422,103 @@ mod tests {
         // Check the timestamp logic. This relies on the fact that the test shouldn't run
         // for more than 10 seconds (while the test itself is trivial, it may be preempted
         // by other tests).
-        manager.miniblock.timestamp = seconds_since_epoch();
+        Arc::make_mut(&mut manager.miniblock).timestamp = seconds_since_epoch();
         assert!(
             !timeout_miniblock_sealer(&manager),
             "Non-empty miniblock with too recent timestamp shouldn't be sealed"
         );
     }
+
+    #[test]
+    fn sealing_miniblock_by_gas_limit() {
+        let sealer = SealManager::custom(None, vec![], vec![]);
+        let mut manager = create_updates_manager();
+        apply_tx_to_manager(&mut manager);
+        assert!(!sealer.should_seal_miniblock_before_tx(&manager, u64::MAX));
+
+        // Each transaction created by `apply_tx_to_manager()` uses 1,000 gas.
+        let sealer = sealer.with_max_gas_per_miniblock(Some(2_500));
+        assert_eq!(manager.miniblock.gas_used(), 1_000);
+        assert!(!sealer.should_seal_miniblock_before_tx(&manager, 1_500));
+        assert!(sealer.should_seal_miniblock_before_tx(&manager, 1_501));
+
+        // An empty miniblock is never sealed.
+        let empty_manager = create_updates_manager();
+        assert!(!sealer.should_seal_miniblock_before_tx(&empty_manager, 10_000));
+    }
+
+    #[test]
+    fn reporting_sealing_status() {
+        let config = StateKeeperConfig::from_env().unwrap();
+        let sealing_status = SealingStatusHandle::new(&config);
+        let conditional_sealer = ConditionalSealer::new(config.clone());
+        let sealer = SealManager::custom(Some(conditional_sealer), vec![], vec![])
+            .with_sealing_status(sealing_status.clone());
+        assert!(sealing_status.get().is_none());
+
+        let block_open_timestamp_ms = u128::from(seconds_since_epoch()) * 1_000;
+        let block_data = SealData {
+            cumulative_size: 1_000,
+            ..SealData::default()
+        };
+        let tx_count = config.transaction_slots - 1;
+        let resolution = sealer.should_seal_l1_batch(
+            1,
+            block_open_timestamp_ms,
+            tx_count,
+            &block_data,
+            &SealData::default(),
+        );
+        assert_eq!(resolution, SealResolution::NoSeal);
+
+        let status = sealing_status.get().unwrap();
+        assert_eq!(status.l1_batch_number, L1BatchNumber(1));
+        assert_eq!(status.tx_count, tx_count);
+        let criterion = |name: &str| {
+            status
+                .criteria
+                .iter()
+                .find(|criterion| criterion.criterion == name)
+                .unwrap()
+        };
+        assert_eq!(criterion("slots").used, tx_count as u64);
+        assert_eq!(
+            criterion("slots").seal_threshold,
+            config.transaction_slots as u64
+        );
+        assert_eq!(criterion("tx_encoding_size").used, 1_000);
+        assert_eq!(
+            criterion("timeout").seal_threshold,
+            config.block_commit_deadline_ms
+        );
+        assert_eq!(status.next_seal_criterion.as_deref(), Some("slots"));
+
+        // The status should be reset once the batch is sealed.
+        let resolution = sealer.should_seal_l1_batch(
+            1,
+            block_open_timestamp_ms,
+            config.transaction_slots,
+            &block_data,
+            &SealData::default(),
+        );
+        assert_eq!(resolution, SealResolution::IncludeAndSeal);
+        assert!(sealing_status.get().is_none());
+    }
+
+    #[test]
+    fn instant_miniblock_sealer() {
+        let instant_miniblock_sealer = SealManager::instant_miniblock_sealer();
+
+        let mut manager = create_updates_manager();
+        Arc::make_mut(&mut manager.miniblock).timestamp = seconds_since_epoch();
+        assert!(
+            !instant_miniblock_sealer(&manager),
+            "Empty miniblock shouldn't be sealed"
+        );
+        apply_tx_to_manager(&mut manager);
+        assert!(
+            instant_miniblock_sealer(&manager),
+            "Non-empty miniblock should be sealed regardless of its timestamp"
+        );
+    }
 }
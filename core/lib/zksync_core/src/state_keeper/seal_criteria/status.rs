@@ -0,0 +1,71 @@
+//! Sealing status of the pending L1 batch shared between the state keeper and the API server.
+
+use std::sync::{Arc, RwLock};
+
+use zksync_config::configs::chain::StateKeeperConfig;
+use zksync_types::{
+    api::{L1BatchSealingStatus, SealCriterionUtilization},
+    L1BatchNumber,
+};
+use zksync_utils::time::millis_since;
+
+/// Name of the criterion sealing L1 batches by timeout, reported alongside conditional criteria.
+const TIMEOUT_CRITERION_NAME: &str = "timeout";
+
+/// Criteria utilization as seen by the conditional sealer after executing the last transaction.
+#[derive(Debug)]
+pub(super) struct PendingL1BatchStatus {
+    pub l1_batch_number: L1BatchNumber,
+    /// Timestamp of the L1 batch in seconds.
+    pub batch_timestamp: u64,
+    pub tx_count: usize,
+    pub criteria: Vec<SealCriterionUtilization>,
+}
+
+/// Handle allowing to inspect utilization of seal criteria for the L1 batch currently executed
+/// by the state keeper.
+#[derive(Debug, Clone)]
+pub struct SealingStatusHandle {
+    block_commit_deadline_ms: u64,
+    inner: Arc<RwLock<Option<PendingL1BatchStatus>>>,
+}
+
+impl SealingStatusHandle {
+    pub fn new(config: &StateKeeperConfig) -> Self {
+        Self {
+            block_commit_deadline_ms: config.block_commit_deadline_ms,
+            inner: Arc::default(),
+        }
+    }
+
+    pub(super) fn set(&self, status: Option<PendingL1BatchStatus>) {
+        *self.inner.write().expect("sealing status is poisoned") = status;
+    }
+
+    /// Returns the sealing status of the pending L1 batch, or `None` if the pending L1 batch
+    /// has no executed transactions yet.
+    pub fn get(&self) -> Option<L1BatchSealingStatus> {
+        let inner = self.inner.read().expect("sealing status is poisoned");
+        let status = inner.as_ref()?;
+
+        let elapsed_ms = millis_since(status.batch_timestamp);
+        let timeout = SealCriterionUtilization::new(
+            TIMEOUT_CRITERION_NAME,
+            elapsed_ms,
+            self.block_commit_deadline_ms,
+        );
+        let mut criteria = status.criteria.clone();
+        criteria.push(timeout);
+        let next_seal_criterion = criteria
+            .iter()
+            .max_by(|a, b| a.utilization.total_cmp(&b.utilization))
+            .map(|utilization| utilization.criterion.clone());
+
+        Some(L1BatchSealingStatus {
+            l1_batch_number: status.l1_batch_number,
+            tx_count: status.tx_count,
+            criteria,
+            next_seal_criterion,
+        })
+    }
+}
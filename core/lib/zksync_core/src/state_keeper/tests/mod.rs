@@ -27,7 +27,8 @@ use zksync_types::{
     transaction_request::PaymasterParams,
     tx::tx_execution_info::VmExecutionLogs,
     Address, L1BatchNumber, L2ChainId, LogQuery, MiniblockNumber, Nonce, ProtocolVersionId,
-    StorageLogQuery, StorageLogQueryType, Timestamp, Transaction, H256, U256,
+    StorageLogQuery, StorageLogQueryType, Timestamp, Transaction, H256, L1_GAS_PER_PUBDATA_BYTE,
+    U256,
 };
 
 use self::tester::{
@@ -72,6 +73,7 @@ pub(super) fn default_l1_batch_env(
         number: L1BatchNumber(number),
         timestamp,
         l1_gas_price: 1,
+        fair_pubdata_price: u64::from(L1_GAS_PER_PUBDATA_BYTE),
         fair_l2_gas_price: 1,
         fee_account,
         enforced_base_fee: None,
@@ -115,6 +117,7 @@ pub(super) fn default_vm_block_result() -> FinishedL1Batch {
         },
         final_execution_state: CurrentExecutionState {
             events: vec![],
+            deduplicated_events_logs: vec![],
             storage_log_queries: vec![],
             used_contract_hashes: vec![],
             l2_to_l1_logs: vec![],
@@ -263,6 +266,53 @@ async fn sealed_by_number_of_txs() {
         .await;
 }
 
+#[tokio::test]
+async fn miniblock_sealed_by_gas_limit() {
+    let config = StateKeeperConfig {
+        transaction_slots: 3,
+        ..Default::default()
+    };
+    let conditional_sealer = Some(ConditionalSealer::with_sealers(
+        config,
+        vec![Box::new(SlotsCriterion)],
+    ));
+    // Each transaction created by `random_tx()` uses 1,000 gas.
+    let sealer = SealManager::custom(conditional_sealer, vec![Box::new(|_| false)], vec![])
+        .with_max_gas_per_miniblock(Some(2_500));
+
+    let third_tx = random_tx(3);
+    // The miniblock is sealed in less than a second, so the next miniblock must be started
+    // without waiting for the next timestamp.
+    TestScenario::new()
+        .forbid_waiting_for_miniblock_params()
+        .next_tx("First tx", random_tx(1), successful_exec())
+        .next_tx("Second tx", random_tx(2), successful_exec())
+        .next_tx(
+            "Third tx exceeding miniblock gas",
+            third_tx.clone(),
+            successful_exec(),
+        )
+        .tx_rollback(
+            "Third tx rolled back to seal the miniblock",
+            third_tx.clone(),
+        )
+        .miniblock_sealed_with("Miniblock with 2 txs", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 2);
+            assert_eq!(updates.miniblock.gas_used(), 2_000);
+        })
+        .next_tx(
+            "Third tx in the next miniblock",
+            third_tx,
+            successful_exec(),
+        )
+        .miniblock_sealed_with("Miniblock with the third tx", |updates| {
+            assert_eq!(updates.miniblock.executed_transactions.len(), 1);
+        })
+        .batch_sealed("Batch with 3 txs")
+        .run(sealer)
+        .await;
+}
+
 #[tokio::test]
 async fn sealed_by_gas() {
     let config = StateKeeperConfig {
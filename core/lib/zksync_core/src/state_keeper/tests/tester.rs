@@ -15,7 +15,7 @@ use vm::{
 use zksync_types::{
     block::MiniblockReexecuteData, protocol_version::ProtocolUpgradeTx,
     witness_block_state::WitnessBlockState, Address, L1BatchNumber, L2ChainId, MiniblockNumber,
-    ProtocolVersionId, Transaction, H256,
+    ProtocolVersionId, Transaction, H256, L1_GAS_PER_PUBDATA_BYTE,
 };
 
 use crate::state_keeper::{
@@ -48,6 +48,8 @@ const FEE_ACCOUNT: Address = Address::repeat_byte(0x11);
 pub(crate) struct TestScenario {
     actions: VecDeque<ScenarioItem>,
     pending_batch: Option<PendingBatchData>,
+    /// If set, the IO panics if the state keeper waits for the next miniblock timestamp.
+    forbid_waiting_for_miniblock_params: bool,
 }
 
 impl TestScenario {
@@ -55,6 +57,7 @@ impl TestScenario {
         Self {
             actions: VecDeque::new(),
             pending_batch: None,
+            forbid_waiting_for_miniblock_params: false,
         }
     }
 
@@ -66,6 +69,13 @@ impl TestScenario {
         self
     }
 
+    /// Configures scenario to fail if the state keeper waits for the next miniblock timestamp, rather than
+    /// starting a miniblock right away.
+    pub(crate) fn forbid_waiting_for_miniblock_params(mut self) -> Self {
+        self.forbid_waiting_for_miniblock_params = true;
+        self
+    }
+
     /// Configures scenario to repeatedly return `None` to tx requests until the next action from the scenario happens.
     pub(crate) fn no_txs_until_next_action(mut self, description: &'static str) -> Self {
         self.actions
@@ -494,6 +504,9 @@ impl TestBatchExecutor {
                     // It's OK to not update `last_executed_tx`, since state keeper never should rollback more than 1
                     // tx in a row, and it's going to cause a panic anyway.
                 }
+                Command::RollbackToMiniblockStart(_) => {
+                    panic!("Miniblock rollbacks are not expected in state keeper tests");
+                }
                 Command::FinishBatch(resp) => {
                     // Blanket result, it doesn't really matter.
                     resp.send((default_vm_block_result(), None)).unwrap();
@@ -608,6 +621,7 @@ impl StateKeeperIO for TestIO {
                 number: self.batch_number,
                 timestamp: self.timestamp,
                 l1_gas_price: self.l1_gas_price,
+                fair_pubdata_price: self.l1_gas_price * u64::from(L1_GAS_PER_PUBDATA_BYTE),
                 fair_l2_gas_price: self.fair_l2_gas_price,
                 fee_account: self.fee_account,
                 enforced_base_fee: None,
@@ -620,6 +634,21 @@ impl StateKeeperIO for TestIO {
         &mut self,
         _max_wait: Duration,
         _prev_miniblock_timestamp: u64,
+    ) -> Option<MiniblockParams> {
+        assert!(
+            !self.scenario.forbid_waiting_for_miniblock_params,
+            "State keeper waited for the next miniblock timestamp"
+        );
+        Some(MiniblockParams {
+            timestamp: self.timestamp,
+            // 1 is just a constant used for tests.
+            virtual_blocks: 1,
+        })
+    }
+
+    async fn new_miniblock_params_without_waiting(
+        &mut self,
+        _prev_miniblock_timestamp: u64,
     ) -> Option<MiniblockParams> {
         Some(MiniblockParams {
             timestamp: self.timestamp,
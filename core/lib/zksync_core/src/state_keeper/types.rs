@@ -33,6 +33,20 @@ impl MempoolGuard {
             .has_next(filter)
     }
 
+    pub fn has_next_l1_transaction(&self) -> bool {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .has_next_l1_transaction()
+    }
+
+    pub fn next_l1_transaction(&mut self) -> Option<Transaction> {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .next_l1_transaction()
+    }
+
     pub fn next_transaction(&mut self, filter: &L2TxFilter) -> Option<Transaction> {
         self.0
             .lock()
@@ -47,6 +61,13 @@ impl MempoolGuard {
             .rollback(rejected);
     }
 
+    pub fn reset(&mut self, next_priority_id: PriorityOpId) {
+        self.0
+            .lock()
+            .expect("failed to acquire mempool lock")
+            .reset(next_priority_id);
+    }
+
     pub fn get_mempool_info(&mut self) -> MempoolInfo {
         self.0
             .lock()
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use super::miniblock_updates::MiniblockUpdates;
 use crate::gas_tracker::new_block_gas_count;
 use zksync_types::block::BlockGasCount;
@@ -26,19 +28,31 @@ impl L1BatchUpdates {
         }
     }
 
-    pub(crate) fn extend_from_sealed_miniblock(&mut self, miniblock_updates: MiniblockUpdates) {
+    /// Extends this L1 batch with a sealed miniblock. Only executed transactions are retained from
+    /// the miniblock data; they are moved if the miniblock data is not shared (e.g., with a miniblock
+    /// sealing command that is still being processed), and cloned otherwise.
+    pub(crate) fn extend_from_sealed_miniblock(
+        &mut self,
+        miniblock_updates: Arc<MiniblockUpdates>,
+    ) {
         for tx in &miniblock_updates.executed_transactions {
             if let ExecuteTransactionCommon::L1(data) = &tx.transaction.common_data {
                 let onchain_metadata = data.onchain_metadata().onchain_data;
                 self.priority_ops_onchain_data.push(onchain_metadata);
             }
         }
-        self.executed_transactions
-            .extend(miniblock_updates.executed_transactions);
-
         self.l1_gas_count += miniblock_updates.l1_gas_count;
         self.block_execution_metrics += miniblock_updates.block_execution_metrics;
         self.txs_encoding_size += miniblock_updates.txs_encoding_size;
+
+        match Arc::try_unwrap(miniblock_updates) {
+            Ok(miniblock_updates) => self
+                .executed_transactions
+                .extend(miniblock_updates.executed_transactions),
+            Err(miniblock_updates) => self
+                .executed_transactions
+                .extend_from_slice(&miniblock_updates.executed_transactions),
+        }
     }
 }
 
@@ -70,7 +84,7 @@ mod tests {
         );
 
         let mut l1_batch_accumulator = L1BatchUpdates::new();
-        l1_batch_accumulator.extend_from_sealed_miniblock(miniblock_accumulator);
+        l1_batch_accumulator.extend_from_sealed_miniblock(Arc::new(miniblock_accumulator));
 
         assert_eq!(l1_batch_accumulator.executed_transactions.len(), 1);
         assert_eq!(l1_batch_accumulator.l1_gas_count, new_block_gas_count());
@@ -58,6 +58,17 @@ impl MiniblockUpdates {
         }
     }
 
+    /// Returns gas used by transactions in this miniblock, as reported in transaction receipts.
+    pub(crate) fn gas_used(&self) -> u64 {
+        self.executed_transactions
+            .iter()
+            .map(|tx| {
+                let gas_limit = tx.transaction.gas_limit().min(u64::MAX.into()).as_u64();
+                gas_limit.saturating_sub(tx.refunded_gas.into())
+            })
+            .fold(0, u64::saturating_add)
+    }
+
     pub(crate) fn extend_from_fictive_transaction(&mut self, result: VmExecutionResultAndLogs) {
         self.events.extend(result.logs.events);
         self.storage_logs.extend(result.logs.storage_logs);
@@ -161,8 +172,11 @@ impl MiniblockUpdates {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state_keeper::tests::{create_execution_result, create_transaction};
+    use crate::state_keeper::tests::{
+        create_execution_result, create_l2_transaction, create_transaction,
+    };
     use vm::TransactionVmExt;
+    use zksync_types::U256;
 
     #[test]
     fn apply_empty_l2_tx() {
@@ -188,4 +202,23 @@ mod tests {
         assert_eq!(accumulator.block_execution_metrics.l2_l1_logs, 0);
         assert_eq!(accumulator.txs_encoding_size, bootloader_encoding_size);
     }
+
+    #[test]
+    fn gas_used_saturates_for_large_gas_limits() {
+        let mut accumulator =
+            MiniblockUpdates::new(0, 0, H256::random(), 0, Some(ProtocolVersionId::latest()));
+        for tx_index in 0..2 {
+            let mut tx = create_l2_transaction(10, 100);
+            tx.common_data.fee.gas_limit = U256::MAX;
+            accumulator.extend_from_executed_transaction(
+                tx.into(),
+                create_execution_result(tx_index, []),
+                BlockGasCount::default(),
+                ExecutionMetrics::default(),
+                vec![],
+                vec![],
+            );
+        }
+        assert_eq!(accumulator.gas_used(), u64::MAX);
+    }
 }
@@ -1,5 +1,7 @@
 use vm::{L1BatchEnv, VmExecutionResultAndLogs};
 
+use std::sync::Arc;
+
 use zksync_contracts::BaseSystemContractsHashes;
 use zksync_types::vm_trace::Call;
 use zksync_types::{
@@ -20,18 +22,21 @@ use super::io::MiniblockParams;
 /// things that are not captured there are accumulated externally.
 /// `MiniblockUpdates` keeps updates for the pending mini-block.
 /// `L1BatchUpdates` keeps updates for the already sealed mini-blocks of the pending L1 batch.
+/// `MiniblockUpdates` are shared with [`MiniblockSealCommand`]s, so that bulky miniblock data
+/// (storage logs, events etc.) is not copied when sealing a miniblock.
 /// `UpdatesManager` manages the state of both of these accumulators to be consistent
 /// and provides information about the pending state of the current L1 batch.
 #[derive(Debug, Clone, PartialEq)]
 pub struct UpdatesManager {
     batch_timestamp: u64,
     l1_gas_price: u64,
+    fair_pubdata_price: u64,
     fair_l2_gas_price: u64,
     base_fee_per_gas: u64,
     base_system_contract_hashes: BaseSystemContractsHashes,
     protocol_version: ProtocolVersionId,
     pub l1_batch: L1BatchUpdates,
-    pub miniblock: MiniblockUpdates,
+    pub miniblock: Arc<MiniblockUpdates>,
     pub storage_writes_deduplicator: StorageWritesDeduplicator,
 }
 
@@ -44,18 +49,19 @@ impl UpdatesManager {
         Self {
             batch_timestamp: l1_batch_env.timestamp,
             l1_gas_price: l1_batch_env.l1_gas_price,
+            fair_pubdata_price: l1_batch_env.fair_pubdata_price,
             fair_l2_gas_price: l1_batch_env.fair_l2_gas_price,
             base_fee_per_gas: l1_batch_env.base_fee(),
             protocol_version,
             base_system_contract_hashes,
             l1_batch: L1BatchUpdates::new(),
-            miniblock: MiniblockUpdates::new(
+            miniblock: Arc::new(MiniblockUpdates::new(
                 l1_batch_env.first_l2_block.timestamp,
                 l1_batch_env.first_l2_block.number,
                 l1_batch_env.first_l2_block.prev_block_hash,
                 l1_batch_env.first_l2_block.max_virtual_blocks_to_create,
                 Some(protocol_version),
-            ),
+            )),
             storage_writes_deduplicator: StorageWritesDeduplicator::new(),
         }
     }
@@ -88,6 +94,7 @@ impl UpdatesManager {
             miniblock: self.miniblock.clone(),
             first_tx_index: self.l1_batch.executed_transactions.len(),
             l1_gas_price: self.l1_gas_price,
+            fair_pubdata_price: self.fair_pubdata_price,
             fair_l2_gas_price: self.fair_l2_gas_price,
             base_fee_per_gas: self.base_fee_per_gas,
             base_system_contracts_hashes: self.base_system_contract_hashes,
@@ -111,7 +118,7 @@ impl UpdatesManager {
     ) {
         self.storage_writes_deduplicator
             .apply(&tx_execution_result.logs.storage_logs);
-        self.miniblock.extend_from_executed_transaction(
+        Arc::make_mut(&mut self.miniblock).extend_from_executed_transaction(
             tx,
             tx_execution_result,
             tx_l1_gas_this_tx,
@@ -124,7 +131,7 @@ impl UpdatesManager {
     pub(crate) fn extend_from_fictive_transaction(&mut self, result: VmExecutionResultAndLogs) {
         self.storage_writes_deduplicator
             .apply(&result.logs.storage_logs);
-        self.miniblock.extend_from_fictive_transaction(result);
+        Arc::make_mut(&mut self.miniblock).extend_from_fictive_transaction(result);
     }
 
     /// Pushes a new miniblock with the specified timestamp into this manager. The previously
@@ -137,7 +144,8 @@ impl UpdatesManager {
             miniblock_params.virtual_blocks,
             Some(self.protocol_version),
         );
-        let old_miniblock_updates = std::mem::replace(&mut self.miniblock, new_miniblock_updates);
+        let old_miniblock_updates =
+            std::mem::replace(&mut self.miniblock, Arc::new(new_miniblock_updates));
         self.l1_batch
             .extend_from_sealed_miniblock(old_miniblock_updates);
     }
@@ -164,9 +172,10 @@ impl UpdatesManager {
 pub(crate) struct MiniblockSealCommand {
     pub l1_batch_number: L1BatchNumber,
     pub miniblock_number: MiniblockNumber,
-    pub miniblock: MiniblockUpdates,
+    pub miniblock: Arc<MiniblockUpdates>,
     pub first_tx_index: usize,
     pub l1_gas_price: u64,
+    pub fair_pubdata_price: u64,
     pub fair_l2_gas_price: u64,
     pub base_fee_per_gas: u64,
     pub base_system_contracts_hashes: BaseSystemContractsHashes,
@@ -218,4 +227,40 @@ mod tests {
         assert_eq!(updates_manager.miniblock.executed_transactions.len(), 0);
         assert_eq!(updates_manager.l1_batch.executed_transactions.len(), 1);
     }
+
+    #[test]
+    fn seal_command_shares_miniblock_data() {
+        let mut updates_manager = create_updates_manager();
+        let tx = create_transaction(10, 100);
+        updates_manager.extend_from_executed_transaction(
+            tx,
+            create_execution_result(0, []),
+            vec![],
+            new_block_gas_count(),
+            ExecutionMetrics::default(),
+            vec![],
+        );
+
+        let seal_command = updates_manager.seal_miniblock_command(
+            L1BatchNumber(1),
+            MiniblockNumber(1),
+            Address::default(),
+        );
+        assert!(Arc::ptr_eq(
+            &seal_command.miniblock,
+            &updates_manager.miniblock
+        ));
+
+        updates_manager.push_miniblock(MiniblockParams {
+            timestamp: 2,
+            virtual_blocks: 1,
+        });
+        assert_eq!(seal_command.miniblock.executed_transactions.len(), 1);
+        assert_eq!(updates_manager.l1_batch.executed_transactions.len(), 1);
+        assert_eq!(updates_manager.miniblock.executed_transactions.len(), 0);
+        assert!(!Arc::ptr_eq(
+            &seal_command.miniblock,
+            &updates_manager.miniblock
+        ));
+    }
 }
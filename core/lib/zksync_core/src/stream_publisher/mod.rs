@@ -0,0 +1,335 @@
+//! Streaming publisher emitting sealed miniblocks (header, transactions, receipts and logs)
+//! to a message broker (NATS or Kafka).
+//!
+//! The publisher provides at-least-once delivery: progress is persisted in Postgres only after
+//! the broker has acknowledged the published miniblocks, so after a restart, the publisher may
+//! re-publish some miniblocks, but never skips any. Consumers should deduplicate messages
+//! by the message key, which is unique for the miniblock contents. The hash of the last published miniblock
+//! is persisted together with its number; if it doesn't match the stored miniblock, miniblocks were reverted,
+//! and the publisher restarts from the last miniblock that cannot be reverted (i.e., the last miniblock
+//! in an executed L1 batch). Miniblocks that weren't changed by the revert are re-published with the same keys,
+//! and the re-sealed miniblocks are published with the same numbers but different keys.
+
+use anyhow::Context as _;
+use futures::future;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use std::{
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+use zksync_config::configs::StreamPublisherConfig;
+use zksync_dal::{stream_publisher_dal::PublishedMiniblock, ConnectionPool, StorageProcessor};
+use zksync_types::{api, L1BatchNumber, L2ChainId, MiniblockNumber, H256};
+
+pub use self::sinks::{connect_sink, StreamSink};
+
+mod sinks;
+#[cfg(test)]
+mod tests;
+
+/// Version of the message schema. Should be incremented on each breaking change
+/// in [`MiniblockMessage`]; the version is a part of the topic name.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Message published for each sealed miniblock.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiniblockMessage {
+    pub schema_version: u32,
+    /// Miniblock header together with full transactions.
+    pub miniblock: api::Block<api::TransactionVariant>,
+    /// Receipts of all transactions in the miniblock (including logs), in the execution order.
+    pub receipts: Vec<api::TransactionReceipt>,
+}
+
+impl MiniblockMessage {
+    /// Returns the message key, which is unique for the miniblock contents.
+    fn key(&self) -> String {
+        format!("{}:{:?}", self.miniblock.number, self.miniblock.hash)
+    }
+}
+
+/// Publisher of sealed miniblocks to a message broker.
+#[derive(Debug)]
+pub struct StreamPublisher {
+    pool: ConnectionPool,
+    sink: Box<dyn StreamSink>,
+    topic: String,
+    chain_id: L2ChainId,
+    poll_interval: Duration,
+    max_miniblocks_per_iteration: u32,
+    first_miniblock: MiniblockNumber,
+    sealed_miniblocks: Option<watch::Receiver<MiniblockNumber>>,
+}
+
+impl StreamPublisher {
+    pub fn new(
+        config: &StreamPublisherConfig,
+        sink: Box<dyn StreamSink>,
+        pool: ConnectionPool,
+        chain_id: L2ChainId,
+    ) -> Self {
+        Self {
+            pool,
+            sink,
+            topic: format!("{}.v{SCHEMA_VERSION}", config.topic_prefix),
+            chain_id,
+            poll_interval: config.poll_interval(),
+            max_miniblocks_per_iteration: config.max_miniblocks_per_iteration.max(1),
+            first_miniblock: MiniblockNumber(config.first_miniblock.unwrap_or(0)),
+            sealed_miniblocks: None,
+        }
+    }
+
+    /// Subscribes the publisher to notifications about sealed miniblocks from the state keeper,
+    /// so that miniblocks are published without the polling delay.
+    pub fn with_sealed_miniblocks(mut self, receiver: watch::Receiver<MiniblockNumber>) -> Self {
+        self.sealed_miniblocks = Some(receiver);
+        self
+    }
+
+    /// Returns the topic the miniblocks are published to.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub async fn run(mut self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!("Starting stream publisher for topic `{}`", self.topic);
+        while !*stop_receiver.borrow() {
+            let published_count = self.publish_next_miniblocks().await?;
+            if published_count == 0 {
+                self.wait_for_new_miniblocks(&mut stop_receiver).await;
+            }
+        }
+        tracing::info!("Stop signal received, stream publisher is shutting down");
+        Ok(())
+    }
+
+    async fn wait_for_new_miniblocks(&mut self, stop_receiver: &mut watch::Receiver<bool>) {
+        let sealed_miniblocks = &mut self.sealed_miniblocks;
+        let sealed_miniblock_changed = async {
+            match sealed_miniblocks {
+                // If the sender is dropped, we fall back to polling.
+                Some(receiver) if receiver.changed().await.is_ok() => {}
+                _ => future::pending().await,
+            }
+        };
+        tokio::select! {
+            () = tokio::time::sleep(self.poll_interval) => {}
+            () = sealed_miniblock_changed => {}
+            _ = stop_receiver.changed() => {}
+        }
+    }
+
+    /// Publishes the next chunk of sealed miniblocks and persists the progress.
+    /// Returns the number of published miniblocks.
+    async fn publish_next_miniblocks(&self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged("stream_publisher").await?;
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        let last_published = storage
+            .stream_publisher_dal()
+            .get_last_published_miniblock(&self.topic)
+            .await
+            .context("get_last_published_miniblock()")?;
+
+        let first_miniblock = match last_published {
+            None => {
+                tracing::info!(
+                    "Nothing was published to topic `{}` yet; starting from miniblock #{}",
+                    self.topic,
+                    self.first_miniblock
+                );
+                self.first_miniblock
+            }
+            Some(last_published) => {
+                if Self::is_reverted(&mut storage, last_published).await? {
+                    let (number, hash) = Self::last_irreversible_miniblock(&mut storage).await?;
+                    let number = number.min(last_published.number);
+                    tracing::warn!(
+                        "Last published miniblock #{} was reverted; restarting publishing to topic `{}` \
+                         after miniblock #{number}",
+                        last_published.number,
+                        self.topic
+                    );
+                    metrics::increment_counter!("server.stream_publisher.reverts");
+                    self.save_progress(&mut storage, number, hash).await?;
+                    return Ok(0);
+                }
+                last_published.number + 1
+            }
+        };
+        if first_miniblock > sealed_miniblock {
+            return Ok(0);
+        }
+
+        let last_miniblock = MiniblockNumber(
+            sealed_miniblock
+                .0
+                .min(first_miniblock.0 + self.max_miniblocks_per_iteration - 1),
+        );
+        let last_hash = self
+            .publish_range(&mut storage, first_miniblock..=last_miniblock)
+            .await?;
+        self.save_progress(&mut storage, last_miniblock, last_hash)
+            .await?;
+        Ok((last_miniblock.0 - first_miniblock.0 + 1) as usize)
+    }
+
+    /// Checks whether the last published miniblock was reverted (possibly re-sealed with a different hash).
+    async fn is_reverted(
+        storage: &mut StorageProcessor<'_>,
+        last_published: PublishedMiniblock,
+    ) -> anyhow::Result<bool> {
+        let stored_hash = storage
+            .blocks_web3_dal()
+            .get_miniblock_hash(last_published.number)
+            .await
+            .context("get_miniblock_hash()")?;
+        Ok(match (last_published.hash, stored_hash) {
+            (_, None) => true,
+            (Some(published_hash), Some(stored_hash)) => published_hash != stored_hash,
+            // The hash wasn't persisted for the miniblock, so we can only detect reverts by the miniblock number.
+            (None, Some(_)) => false,
+        })
+    }
+
+    /// Returns the number and hash of the last miniblock in the last executed L1 batch. Such miniblocks
+    /// cannot be reverted.
+    async fn last_irreversible_miniblock(
+        storage: &mut StorageProcessor<'_>,
+    ) -> anyhow::Result<(MiniblockNumber, H256)> {
+        let executed_l1_batch = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?
+            .unwrap_or(L1BatchNumber(0));
+        let (_, last_miniblock) = storage
+            .blocks_dal()
+            .get_miniblock_range_of_l1_batch(executed_l1_batch)
+            .await
+            .context("get_miniblock_range_of_l1_batch()")?
+            .with_context(|| format!("L1 batch #{executed_l1_batch} has no miniblocks"))?;
+        let hash = storage
+            .blocks_web3_dal()
+            .get_miniblock_hash(last_miniblock)
+            .await
+            .context("get_miniblock_hash()")?
+            .with_context(|| format!("miniblock #{last_miniblock} is not in the database"))?;
+        Ok((last_miniblock, hash))
+    }
+
+    /// Re-publishes the specified range of miniblocks without affecting the persisted progress.
+    /// This is useful to backfill consumers that have lost data.
+    pub async fn replay(&self, range: RangeInclusive<MiniblockNumber>) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("stream_publisher").await?;
+        let sealed_miniblock = storage
+            .blocks_web3_dal()
+            .get_sealed_miniblock_number()
+            .await
+            .context("get_sealed_miniblock_number()")?;
+        anyhow::ensure!(
+            *range.end() <= sealed_miniblock,
+            "Cannot replay miniblocks up to #{}: the last sealed miniblock is #{sealed_miniblock}",
+            range.end()
+        );
+        tracing::info!(
+            "Replaying miniblocks #{}..=#{} to topic `{}`",
+            range.start(),
+            range.end(),
+            self.topic
+        );
+        self.publish_range(&mut storage, range).await?;
+        Ok(())
+    }
+
+    /// Publishes the specified non-empty range of miniblocks. Returns the hash of the last published miniblock.
+    async fn publish_range(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        range: RangeInclusive<MiniblockNumber>,
+    ) -> anyhow::Result<H256> {
+        anyhow::ensure!(!range.is_empty(), "cannot publish empty range {range:?}");
+        let mut last_hash = H256::zero();
+        for number in range.start().0..=range.end().0 {
+            let number = MiniblockNumber(number);
+            let started_at = Instant::now();
+            let message = self.load_message(storage, number).await?;
+            let payload =
+                serde_json::to_vec(&message).context("failed serializing miniblock message")?;
+            self.sink
+                .publish(&self.topic, &message.key(), payload)
+                .await
+                .with_context(|| format!("failed publishing miniblock #{number}"))?;
+
+            tracing::debug!("Published miniblock #{number} to topic `{}`", self.topic);
+            metrics::histogram!(
+                "server.stream_publisher.publish_latency",
+                started_at.elapsed()
+            );
+            metrics::increment_counter!("server.stream_publisher.published_miniblocks");
+            last_hash = message.miniblock.hash;
+        }
+        Ok(last_hash)
+    }
+
+    async fn load_message(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        number: MiniblockNumber,
+    ) -> anyhow::Result<MiniblockMessage> {
+        let block_id = api::BlockId::Number(api::BlockNumber::Number(number.0.into()));
+        let miniblock = storage
+            .blocks_web3_dal()
+            .get_block_by_web3_block_id(block_id, true, self.chain_id)
+            .await
+            .context("get_block_by_web3_block_id()")?
+            .with_context(|| format!("miniblock #{number} is not in the database"))?;
+
+        let mut receipts = Vec::with_capacity(miniblock.transactions.len());
+        for tx in &miniblock.transactions {
+            let tx_hash = match tx {
+                api::TransactionVariant::Full(tx) => tx.hash,
+                api::TransactionVariant::Hash(hash) => *hash,
+            };
+            let receipt = storage
+                .transactions_web3_dal()
+                .get_transaction_receipt(tx_hash)
+                .await
+                .context("get_transaction_receipt()")?
+                .with_context(|| format!("receipt for transaction {tx_hash:?} is missing"))?;
+            receipts.push(receipt);
+        }
+
+        Ok(MiniblockMessage {
+            schema_version: SCHEMA_VERSION,
+            miniblock,
+            receipts,
+        })
+    }
+
+    async fn save_progress(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        last_published: MiniblockNumber,
+        last_published_hash: H256,
+    ) -> anyhow::Result<()> {
+        storage
+            .stream_publisher_dal()
+            .set_last_published_miniblock(&self.topic, last_published, last_published_hash)
+            .await
+            .context("set_last_published_miniblock()")?;
+        metrics::gauge!(
+            "server.stream_publisher.last_published_miniblock",
+            last_published.0 as f64
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Message broker sinks for the streaming publisher.
+
+use async_trait::async_trait;
+
+use std::fmt;
+
+use zksync_config::configs::stream_publisher::{StreamBackend, StreamPublisherConfig};
+
+/// Message broker sink. Implementations must only return from [`Self::publish()`] after
+/// the broker has acknowledged the message, so that the publisher can provide at-least-once
+/// delivery guarantees.
+#[async_trait]
+pub trait StreamSink: fmt::Debug + Send + Sync {
+    /// Publishes a message to the specified topic and waits for the broker acknowledgement.
+    async fn publish(&self, topic: &str, key: &str, payload: Vec<u8>) -> anyhow::Result<()>;
+}
+
+/// Connects to the message broker specified in the config.
+pub async fn connect_sink(config: &StreamPublisherConfig) -> anyhow::Result<Box<dyn StreamSink>> {
+    match config.backend {
+        #[cfg(feature = "nats")]
+        StreamBackend::Nats => Ok(Box::new(nats::NatsSink::connect(&config.url).await?)),
+        #[cfg(not(feature = "nats"))]
+        StreamBackend::Nats => {
+            anyhow::bail!(
+                "NATS stream backend requires the server to be built with the `nats` feature"
+            )
+        }
+        #[cfg(feature = "kafka")]
+        StreamBackend::Kafka => Ok(Box::new(kafka::KafkaSink::new(&config.url)?)),
+        #[cfg(not(feature = "kafka"))]
+        StreamBackend::Kafka => {
+            anyhow::bail!(
+                "Kafka stream backend requires the server to be built with the `kafka` feature"
+            )
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+mod nats {
+    use anyhow::Context as _;
+    use async_trait::async_trait;
+
+    use std::fmt;
+
+    use super::StreamSink;
+
+    /// Sink publishing messages to NATS JetStream. Message keys are used as JetStream message IDs,
+    /// so that messages re-published within the stream deduplication window are discarded.
+    pub(super) struct NatsSink {
+        jetstream: async_nats::jetstream::Context,
+    }
+
+    impl fmt::Debug for NatsSink {
+        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.debug_struct("NatsSink").finish_non_exhaustive()
+        }
+    }
+
+    impl NatsSink {
+        pub(super) async fn connect(url: &str) -> anyhow::Result<Self> {
+            let client = async_nats::connect(url)
+                .await
+                .with_context(|| format!("failed connecting to NATS server at {url}"))?;
+            Ok(Self {
+                jetstream: async_nats::jetstream::new(client),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StreamSink for NatsSink {
+        async fn publish(&self, topic: &str, key: &str, payload: Vec<u8>) -> anyhow::Result<()> {
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert("Nats-Msg-Id", key);
+            let ack = self
+                .jetstream
+                .publish_with_headers(topic.to_owned(), headers, payload.into())
+                .await
+                .with_context(|| format!("failed publishing message `{key}` to NATS"))?;
+            ack.await
+                .with_context(|| format!("NATS did not acknowledge message `{key}`"))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use anyhow::Context as _;
+    use async_trait::async_trait;
+    use rdkafka::{
+        producer::{FutureProducer, FutureRecord},
+        util::Timeout,
+        ClientConfig,
+    };
+
+    use std::fmt;
+
+    use super::StreamSink;
+
+    /// Sink publishing messages to Kafka. The producer is idempotent and waits for all in-sync
+    /// replicas to acknowledge each message.
+    pub(super) struct KafkaSink {
+        producer: FutureProducer,
+    }
+
+    impl fmt::Debug for KafkaSink {
+        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.debug_struct("KafkaSink").finish_non_exhaustive()
+        }
+    }
+
+    impl KafkaSink {
+        pub(super) fn new(bootstrap_servers: &str) -> anyhow::Result<Self> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", bootstrap_servers)
+                .set("acks", "all")
+                .set("enable.idempotence", "true")
+                .create()
+                .with_context(|| {
+                    format!("failed creating Kafka producer for {bootstrap_servers}")
+                })?;
+            Ok(Self { producer })
+        }
+    }
+
+    #[async_trait]
+    impl StreamSink for KafkaSink {
+        async fn publish(&self, topic: &str, key: &str, payload: Vec<u8>) -> anyhow::Result<()> {
+            let record = FutureRecord::to(topic).key(key).payload(&payload);
+            self.producer
+                .send(record, Timeout::Never)
+                .await
+                .map_err(|(err, _)| err)
+                .with_context(|| format!("failed publishing message `{key}` to Kafka"))?;
+            Ok(())
+        }
+    }
+}
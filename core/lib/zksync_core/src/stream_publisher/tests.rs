@@ -0,0 +1,266 @@
+//! Tests for the streaming publisher.
+
+use async_trait::async_trait;
+use db_test_macro::db_test;
+
+use std::sync::{Arc, Mutex};
+
+use zksync_config::configs::stream_publisher::StreamBackend;
+use zksync_contracts::BaseSystemContracts;
+use zksync_types::{
+    block::{miniblock_hash, MiniblockHeader},
+    commitment::CommitmentSchemes,
+    protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts,
+    Address, L1BatchCommitmentMode, ProtocolVersionId, H256,
+};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+/// Sink recording keys of published messages.
+#[derive(Debug, Default, Clone)]
+struct MockSink {
+    keys: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockSink {
+    fn take_keys(&self) -> Vec<String> {
+        std::mem::take(&mut *self.keys.lock().unwrap())
+    }
+}
+
+#[async_trait]
+impl StreamSink for MockSink {
+    async fn publish(&self, topic: &str, key: &str, payload: Vec<u8>) -> anyhow::Result<()> {
+        assert_eq!(topic, "zksync.miniblocks.v1");
+        let message: serde_json::Value = serde_json::from_slice(&payload)?;
+        assert_eq!(message["schemaVersion"], SCHEMA_VERSION);
+        self.keys.lock().unwrap().push(key.to_owned());
+        Ok(())
+    }
+}
+
+/// Creates a publisher starting from miniblock #1.
+fn create_publisher(pool: &ConnectionPool, sink: &MockSink) -> StreamPublisher {
+    create_publisher_with_config(pool, sink, &mock_config())
+}
+
+fn create_publisher_with_config(
+    pool: &ConnectionPool,
+    sink: &MockSink,
+    config: &StreamPublisherConfig,
+) -> StreamPublisher {
+    let sink = Box::new(sink.clone());
+    StreamPublisher::new(config, sink, pool.clone(), L2ChainId(270))
+}
+
+fn mock_config() -> StreamPublisherConfig {
+    StreamPublisherConfig {
+        backend: StreamBackend::Nats,
+        url: "nats://127.0.0.1:4222".to_owned(),
+        topic_prefix: "zksync.miniblocks".to_owned(),
+        poll_interval_ms: 10,
+        max_miniblocks_per_iteration: 2,
+        first_miniblock: Some(1),
+    }
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+        .await
+        .unwrap();
+}
+
+async fn seal_miniblock(pool: &ConnectionPool, number: u32, timestamp: u64) {
+    let number = MiniblockNumber(number);
+    let miniblock_header = MiniblockHeader {
+        number,
+        timestamp,
+        hash: miniblock_hash(number, timestamp, H256::zero(), H256::zero()),
+        l1_tx_count: 0,
+        l2_tx_count: 0,
+        base_fee_per_gas: 100,
+        l1_gas_price: 100,
+        l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
+        base_system_contracts_hashes: BaseSystemContracts::load_from_disk().hashes(),
+        protocol_version: Some(ProtocolVersionId::latest()),
+        virtual_blocks: 1,
+    };
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+}
+
+fn expected_key(number: u32, timestamp: u64) -> String {
+    let number = MiniblockNumber(number);
+    let hash = miniblock_hash(number, timestamp, H256::zero(), H256::zero());
+    format!("{number}:{hash:?}")
+}
+
+#[db_test]
+async fn publishing_sealed_miniblocks(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let sink = MockSink::default();
+    let publisher = create_publisher(&pool, &sink);
+    assert_eq!(publisher.topic(), "zksync.miniblocks.v1");
+
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 0);
+    assert!(sink.take_keys().is_empty());
+
+    for number in 1..=3 {
+        seal_miniblock(&pool, number, number.into()).await;
+    }
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 1);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 0);
+    assert_eq!(
+        sink.take_keys(),
+        [expected_key(1, 1), expected_key(2, 2), expected_key(3, 3)]
+    );
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let last_published = storage
+        .stream_publisher_dal()
+        .get_last_published_miniblock(publisher.topic())
+        .await
+        .unwrap();
+    let last_published = last_published.unwrap();
+    assert_eq!(last_published.number, MiniblockNumber(3));
+    assert_eq!(
+        last_published.hash,
+        Some(miniblock_hash(
+            MiniblockNumber(3),
+            3,
+            H256::zero(),
+            H256::zero()
+        ))
+    );
+}
+
+#[db_test]
+async fn publishing_from_genesis(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    seal_miniblock(&pool, 1, 1).await;
+    let sink = MockSink::default();
+    let config = StreamPublisherConfig {
+        first_miniblock: None,
+        ..mock_config()
+    };
+    let publisher = create_publisher_with_config(&pool, &sink, &config);
+
+    // Miniblocks sealed before the publisher has started are published as well.
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    let mut storage = pool.access_storage().await.unwrap();
+    let genesis_hash = storage
+        .blocks_web3_dal()
+        .get_miniblock_hash(MiniblockNumber(0))
+        .await
+        .unwrap()
+        .unwrap();
+    drop(storage);
+    assert_eq!(
+        sink.take_keys(),
+        [format!("0:{genesis_hash:?}"), expected_key(1, 1)]
+    );
+}
+
+#[db_test]
+async fn publishing_after_revert(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let sink = MockSink::default();
+    let publisher = create_publisher(&pool, &sink);
+    publisher.publish_next_miniblocks().await.unwrap();
+    for number in 1..=2 {
+        seal_miniblock(&pool, number, number.into()).await;
+    }
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    sink.take_keys();
+
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(1))
+        .await
+        .unwrap();
+    drop(storage);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 0);
+
+    // Publishing restarts after the genesis miniblock, since no L1 batches are executed. The retained miniblock
+    // is re-published with the same key, and the re-sealed miniblock is published with a new key.
+    seal_miniblock(&pool, 2, 10).await;
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    assert_eq!(sink.take_keys(), [expected_key(1, 1), expected_key(2, 10)]);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 0);
+}
+
+#[db_test]
+async fn publishing_after_revert_and_reseal_past_last_published(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let sink = MockSink::default();
+    let publisher = create_publisher(&pool, &sink);
+    for number in 1..=2 {
+        seal_miniblock(&pool, number, number.into()).await;
+    }
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    sink.take_keys();
+
+    // Miniblock #2 is reverted, and miniblocks #2 and #3 are sealed before the publisher notices the revert.
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .blocks_dal()
+        .delete_miniblocks(MiniblockNumber(1))
+        .await
+        .unwrap();
+    drop(storage);
+    seal_miniblock(&pool, 2, 10).await;
+    seal_miniblock(&pool, 3, 11).await;
+
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 0);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 1);
+    assert_eq!(
+        sink.take_keys(),
+        [expected_key(1, 1), expected_key(2, 10), expected_key(3, 11)]
+    );
+}
+
+#[db_test]
+async fn replaying_miniblocks(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let sink = MockSink::default();
+    let publisher = create_publisher(&pool, &sink);
+    publisher.publish_next_miniblocks().await.unwrap();
+    for number in 1..=3 {
+        seal_miniblock(&pool, number, number.into()).await;
+    }
+
+    publisher
+        .replay(MiniblockNumber(1)..=MiniblockNumber(2))
+        .await
+        .unwrap();
+    assert_eq!(sink.take_keys(), [expected_key(1, 1), expected_key(2, 2)]);
+    publisher
+        .replay(MiniblockNumber(3)..=MiniblockNumber(4))
+        .await
+        .unwrap_err();
+
+    // Replaying doesn't influence the persisted progress.
+    assert_eq!(publisher.publish_next_miniblocks().await.unwrap(), 2);
+    assert_eq!(sink.take_keys(), [expected_key(1, 1), expected_key(2, 2)]);
+}
@@ -392,6 +392,7 @@ impl StateKeeperIO for ExternalIO {
                     number,
                     timestamp,
                     l1_gas_price,
+                    fair_pubdata_price,
                     l2_fair_gas_price,
                     operator_address,
                     protocol_version,
@@ -415,6 +416,7 @@ impl StateKeeperIO for ExternalIO {
                         timestamp,
                         previous_l1_batch_hash,
                         l1_gas_price,
+                        fair_pubdata_price,
                         l2_fair_gas_price,
                         miniblock_number,
                         prev_miniblock_hash,
@@ -602,15 +604,20 @@ impl StateKeeperIO for ExternalIO {
         };
 
         let mut storage = self.pool.access_storage_tagged("sync_layer").await.unwrap();
-        updates_manager
+        // The external node doesn't have a tight latency budget for sealing, so protective reads
+        // are persisted in the same transaction as the L1 batch.
+        let mut transaction = storage.start_transaction().await.unwrap();
+        let protective_reads_command = updates_manager
             .seal_l1_batch(
-                &mut storage,
+                &mut transaction,
                 self.current_miniblock_number,
                 l1_batch_env,
                 finished_batch,
                 self.l2_erc20_bridge_addr,
             )
             .await;
+        protective_reads_command.persist(&mut transaction).await;
+        transaction.commit().await.unwrap();
 
         tracing::info!("Batch {} is sealed", self.current_l1_batch_number);
 
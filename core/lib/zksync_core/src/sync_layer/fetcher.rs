@@ -4,7 +4,7 @@ use tokio::sync::watch::Receiver;
 
 use crate::sync_layer::sync_action::{ActionQueue, SyncAction};
 use zksync_dal::ConnectionPool;
-use zksync_types::{L1BatchNumber, MiniblockNumber, H256};
+use zksync_types::{L1BatchNumber, MiniblockNumber, H256, L1_GAS_PER_PUBDATA_BYTE};
 use zksync_web3_decl::jsonrpsee::core::Error as RpcError;
 use zksync_web3_decl::RpcResult;
 
@@ -175,6 +175,10 @@ impl MainNodeFetcher {
                 number: block.l1_batch_number,
                 timestamp: block.timestamp,
                 l1_gas_price: block.l1_gas_price,
+                // Main nodes not reporting the pubdata price use the calldata price.
+                fair_pubdata_price: block
+                    .fair_pubdata_price
+                    .unwrap_or(block.l1_gas_price * u64::from(L1_GAS_PER_PUBDATA_BYTE)),
                 l2_fair_gas_price: block.l2_fair_gas_price,
                 operator_address: block.operator_address,
                 protocol_version: block.protocol_version,
@@ -4,9 +4,10 @@ use anyhow::Context;
 use zksync_contracts::{BaseSystemContracts, BaseSystemContractsHashes, SystemContractCode};
 use zksync_dal::StorageProcessor;
 use zksync_types::{
-    api, block::DeployedContract, get_code_key, protocol_version::L1VerifierConfig,
-    system_contracts::get_system_smart_contracts, AccountTreeId, Address, L1BatchNumber, L2ChainId,
-    MiniblockNumber, ProtocolVersionId, ACCOUNT_CODE_STORAGE_ADDRESS, H256, U64,
+    api, block::DeployedContract, commitment::CommitmentSchemes, get_code_key,
+    protocol_version::L1VerifierConfig, system_contracts::get_system_smart_contracts,
+    AccountTreeId, Address, L1BatchCommitmentMode, L1BatchNumber, L2ChainId, MiniblockNumber,
+    ProtocolVersionId, ACCOUNT_CODE_STORAGE_ADDRESS, H256, U64,
 };
 use zksync_utils::h256_to_u256;
 use zksync_web3_decl::{
@@ -18,12 +19,15 @@ pub async fn perform_genesis_if_needed(
     storage: &mut StorageProcessor<'_>,
     zksync_chain_id: L2ChainId,
     main_node_url: String,
+    commitment_schemes: CommitmentSchemes,
+    commitment_mode: L1BatchCommitmentMode,
 ) -> anyhow::Result<()> {
     let mut transaction = storage.start_transaction().await.unwrap();
     // We want to check whether the genesis is needed before we create genesis params to not
     // make the node startup slower.
     let genesis_block_hash = if transaction.blocks_dal().is_genesis_needed().await.unwrap() {
-        let genesis_params = create_genesis_params(&main_node_url).await?;
+        let genesis_params =
+            create_genesis_params(&main_node_url, commitment_schemes, commitment_mode).await?;
         ensure_genesis_state(&mut transaction, zksync_chain_id, &genesis_params)
             .await
             .context("ensure_genesis_state")?
@@ -42,7 +46,11 @@ pub async fn perform_genesis_if_needed(
     Ok(())
 }
 
-async fn create_genesis_params(main_node_url: &str) -> anyhow::Result<GenesisParams> {
+async fn create_genesis_params(
+    main_node_url: &str,
+    commitment_schemes: CommitmentSchemes,
+    commitment_mode: L1BatchCommitmentMode,
+) -> anyhow::Result<GenesisParams> {
     let base_system_contracts_hashes = fetch_genesis_system_contracts(main_node_url)
         .await
         .context("Unable to fetch genesis system contracts hashes")?;
@@ -123,6 +131,8 @@ async fn create_genesis_params(main_node_url: &str) -> anyhow::Result<GenesisPar
         first_validator,
         first_l1_verifier_config,
         first_verifier_address,
+        commitment_schemes,
+        commitment_mode,
     })
 }
 
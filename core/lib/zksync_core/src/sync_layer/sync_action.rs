@@ -129,6 +129,7 @@ pub(crate) enum SyncAction {
         number: L1BatchNumber,
         timestamp: u64,
         l1_gas_price: u64,
+        fair_pubdata_price: u64,
         l2_fair_gas_price: u64,
         operator_address: Address,
         protocol_version: ProtocolVersionId,
@@ -171,6 +172,7 @@ mod tests {
             number: 1.into(),
             timestamp: 1,
             l1_gas_price: 1,
+            fair_pubdata_price: 1,
             l2_fair_gas_price: 1,
             operator_address: Default::default(),
             protocol_version: ProtocolVersionId::latest(),
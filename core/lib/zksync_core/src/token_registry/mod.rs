@@ -0,0 +1,355 @@
+//! Token registry: the policy controlling which tokens are supported by the node, a component
+//! keeping metadata of bridged tokens in sync with their L2 contracts, and the ETH / base token conversion
+//! rate used by the fee model.
+
+use anyhow::Context as _;
+use num::{rational::Ratio, BigUint, ToPrimitive};
+use tokio::sync::watch;
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use vm::{constants::BLOCK_GAS_LIMIT, ExecutionResult};
+use zksync_config::configs::TokenRegistryConfig;
+use zksync_dal::ConnectionPool;
+use zksync_state::PostgresStorageCaches;
+use zksync_types::{
+    api::{BlockId, BlockNumber},
+    ethabi::{self, ParamType, Token},
+    l2::L2Tx,
+    tokens::{TokenMetadata, ETHEREUM_ADDRESS},
+    transaction_request::CallRequest,
+    AccountTreeId, Address, L2ChainId, L1_GAS_PER_PUBDATA_BYTE, L2_ETH_TOKEN_ADDRESS,
+    USED_BOOTLOADER_MEMORY_BYTES,
+};
+
+use crate::{
+    api_server::{
+        execution_sandbox::{execute_tx_eth_call, BlockArgs, TxSharedArgs, VmConcurrencyLimiter},
+        tx_sender::ApiContracts,
+    },
+    fee_ticker::FeeTicker,
+};
+
+#[cfg(test)]
+mod tests;
+
+const COMPONENT: &str = "token_registry";
+
+/// Policy determining tokens exposed via the API and supported by the fee model. ETH is always supported.
+/// By default, all tokens are supported.
+#[derive(Debug, Clone, Default)]
+pub struct TokenPolicy {
+    allowlist: Option<HashSet<Address>>,
+    denylist: HashSet<Address>,
+}
+
+impl TokenPolicy {
+    pub fn new(config: &TokenRegistryConfig) -> Self {
+        Self {
+            allowlist: config
+                .allowlist
+                .as_ref()
+                .map(|addresses| addresses.iter().copied().collect()),
+            denylist: config.denylist.iter().flatten().copied().collect(),
+        }
+    }
+
+    /// Checks whether a token with the specified L2 address is supported.
+    pub fn is_allowed(&self, l2_address: &Address) -> bool {
+        if *l2_address == ETHEREUM_ADDRESS || *l2_address == L2_ETH_TOKEN_ADDRESS {
+            return true;
+        }
+        if self.denylist.contains(l2_address) {
+            return false;
+        }
+        self.allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.contains(l2_address))
+    }
+}
+
+/// Component periodically refreshing token metadata (name, symbol and decimals) from the token contracts on L2.
+/// Metadata extracted from bridge events when a token is added may be outdated or incomplete; refreshing it
+/// also covers tokens with metadata changed after deployment.
+///
+/// Metadata getters are executed in the VM against the latest sealed miniblock, in the same way as `eth_call`s
+/// in the API server, but without requiring the API server to run.
+#[derive(Debug)]
+pub struct TokenRegistry {
+    pool: ConnectionPool,
+    vm_concurrency_limiter: VmConcurrencyLimiter,
+    api_contracts: ApiContracts,
+    storage_caches: PostgresStorageCaches,
+    fair_l2_gas_price: u64,
+    chain_id: L2ChainId,
+    refresh_interval: Duration,
+    batch_size: usize,
+}
+
+impl TokenRegistry {
+    /// Capacity of storage caches used by the VM (in bytes). Getters only touch a handful of slots and
+    /// bytecodes, so the caches can be small.
+    const CACHE_CAPACITY: u64 = 16 << 20;
+
+    pub fn new(
+        config: &TokenRegistryConfig,
+        pool: ConnectionPool,
+        fair_l2_gas_price: u64,
+        chain_id: L2ChainId,
+    ) -> Self {
+        // Getters are executed sequentially, so the barrier isn't needed.
+        let (vm_concurrency_limiter, _) = VmConcurrencyLimiter::new(1);
+        Self {
+            pool,
+            vm_concurrency_limiter,
+            api_contracts: ApiContracts::load_from_disk(),
+            storage_caches: PostgresStorageCaches::new(Self::CACHE_CAPACITY, Self::CACHE_CAPACITY),
+            fair_l2_gas_price,
+            chain_id,
+            refresh_interval: config.metadata_refresh_interval(),
+            batch_size: config.metadata_refresh_batch_size,
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            match self.refresh_metadata().await {
+                Ok(refreshed_count) => {
+                    metrics::counter!(
+                        "server.token_registry.refreshed_tokens",
+                        refreshed_count as u64
+                    );
+                }
+                Err(err) => {
+                    // Errors are most likely caused by transient Postgres issues, so we just retry later.
+                    tracing::warn!("Error refreshing token metadata: {err:#}");
+                    metrics::increment_counter!("server.token_registry.errors");
+                }
+            }
+
+            let stop_signal =
+                tokio::time::timeout(self.refresh_interval, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for token registry was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, token registry is shutting down");
+        Ok(())
+    }
+
+    /// Refreshes metadata for the least recently refreshed tokens. Returns the number of refreshed tokens.
+    async fn refresh_metadata(&self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let tokens = storage
+            .tokens_dal()
+            .get_tokens_for_metadata_refresh(self.batch_size)
+            .await;
+        // All tokens in a batch are read at the same miniblock.
+        let block_args = BlockArgs::new(&mut storage, BlockId::Number(BlockNumber::Latest))
+            .await
+            .context("failed resolving latest miniblock")?
+            .context("no sealed miniblocks")?;
+        drop(storage);
+
+        for token in &tokens {
+            let metadata = self.fetch_metadata(*token, block_args).await?;
+            if metadata.is_none() {
+                tracing::info!(
+                    "Token {token:?} does not expose ERC20 metadata; keeping the stored one"
+                );
+            }
+            let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+            storage
+                .tokens_dal()
+                .refresh_token_metadata(token, metadata.as_ref())
+                .await;
+        }
+        Ok(tokens.len())
+    }
+
+    /// Reads metadata from the token contract. Returns `None` if the contract does not implement
+    /// optional ERC20 metadata methods (or implements them in a non-standard way).
+    async fn fetch_metadata(
+        &self,
+        token: Address,
+        block_args: BlockArgs,
+    ) -> anyhow::Result<Option<TokenMetadata>> {
+        let Some(name) = self
+            .call_getter(token, "name", ParamType::String, block_args)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(symbol) = self
+            .call_getter(token, "symbol", ParamType::String, block_args)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(decimals) = self
+            .call_getter(token, "decimals", ParamType::Uint(8), block_args)
+            .await?
+        else {
+            return Ok(None);
+        };
+        Ok(parse_metadata(name, symbol, decimals))
+    }
+
+    async fn call_getter(
+        &self,
+        token: Address,
+        name: &str,
+        output_type: ParamType,
+        block_args: BlockArgs,
+    ) -> anyhow::Result<Option<Token>> {
+        let request = CallRequest::builder()
+            .to(token)
+            .data(ethabi::short_signature(name, &[]).to_vec().into())
+            .build();
+        let tx = L2Tx::from_request(request.into(), USED_BOOTLOADER_MEMORY_BYTES)
+            .with_context(|| format!("failed creating call {token:?}.{name}()"))?;
+        let vm_permit = self
+            .vm_concurrency_limiter
+            .acquire()
+            .await
+            .context("VM concurrency limiter is closed")?;
+        let result = execute_tx_eth_call(
+            vm_permit,
+            self.shared_args(),
+            self.pool.clone(),
+            tx,
+            block_args,
+            None,
+            vec![],
+        )
+        .await
+        .with_context(|| format!("failed executing {token:?}.{name}()"))?;
+
+        let output = match result.result {
+            ExecutionResult::Success { output } => output,
+            // The call has reverted or halted (e.g., the method is not implemented).
+            ExecutionResult::Revert { .. } | ExecutionResult::Halt { .. } => return Ok(None),
+        };
+        Ok(ethabi::decode(&[output_type], &output)
+            .ok()
+            .and_then(|mut tokens| tokens.pop()))
+    }
+
+    fn shared_args(&self) -> TxSharedArgs {
+        TxSharedArgs {
+            operator_account: AccountTreeId::default(),
+            l1_gas_price: 100_000,
+            fair_pubdata_price: 100_000 * u64::from(L1_GAS_PER_PUBDATA_BYTE),
+            fair_l2_gas_price: self.fair_l2_gas_price,
+            base_system_contracts: self.api_contracts.eth_call.clone(),
+            caches: self.storage_caches.clone(),
+            fork: None,
+            validation_computational_gas_limit: BLOCK_GAS_LIMIT,
+            chain_id: self.chain_id,
+        }
+    }
+}
+
+/// Conversion rate from ETH to the base token of the chain, used by the fee model to express L1 gas and pubdata prices
+/// in the base token. The rate is derived from USD prices of both tokens stored in Postgres and is periodically refreshed
+/// by [`Self::run()`].
+#[derive(Debug, Clone)]
+pub struct BaseTokenConversion {
+    base_token: Address,
+    /// Number of the smallest base token units per wei.
+    rate: Arc<RwLock<Ratio<BigUint>>>,
+}
+
+impl BaseTokenConversion {
+    /// Loads the current conversion rate. Fails if the USD price of ETH or the base token is not tracked.
+    pub async fn new(pool: &ConnectionPool, base_token: Address) -> anyhow::Result<Self> {
+        let rate = Self::load_rate(pool, base_token).await?;
+        tracing::info!("Initial ETH / base token {base_token:?} conversion rate: {rate}");
+        Ok(Self::with_rate(base_token, rate))
+    }
+
+    fn with_rate(base_token: Address, rate: Ratio<BigUint>) -> Self {
+        Self {
+            base_token,
+            rate: Arc::new(RwLock::new(rate)),
+        }
+    }
+
+    async fn load_rate(
+        pool: &ConnectionPool,
+        base_token: Address,
+    ) -> anyhow::Result<Ratio<BigUint>> {
+        let mut storage = pool.access_storage_tagged(COMPONENT).await?;
+        let rate = FeeTicker::base_token_units_per_wei(&mut storage.tokens_web3_dal(), &base_token)
+            .await
+            .with_context(|| {
+                format!("failed getting ETH / base token {base_token:?} conversion rate")
+            })?;
+        Ok(rate)
+    }
+
+    /// Converts a value in wei to the smallest base token units, rounding up. Saturates at `u64::MAX`.
+    pub fn convert(&self, wei_value: u64) -> u64 {
+        let rate = self
+            .rate
+            .read()
+            .expect("base token conversion rate is poisoned");
+        let converted = (rate.clone() * Ratio::from_integer(BigUint::from(wei_value)))
+            .ceil()
+            .to_integer();
+        converted.to_u64().unwrap_or(u64::MAX)
+    }
+
+    /// Periodically refreshes the conversion rate. If the rate cannot be refreshed, the last known rate is used.
+    pub async fn run(
+        self,
+        pool: ConnectionPool,
+        refresh_interval: Duration,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        while !*stop_receiver.borrow() {
+            match Self::load_rate(&pool, self.base_token).await {
+                Ok(rate) => {
+                    tracing::debug!(
+                        "Refreshed ETH / base token {:?} conversion rate: {rate}",
+                        self.base_token
+                    );
+                    *self
+                        .rate
+                        .write()
+                        .expect("base token conversion rate is poisoned") = rate;
+                }
+                Err(err) => {
+                    tracing::warn!("Error refreshing base token conversion rate: {err:#}");
+                    metrics::increment_counter!("server.token_registry.base_token_rate_errors");
+                }
+            }
+
+            let stop_signal = tokio::time::timeout(refresh_interval, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for base token conversion was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, base token conversion is shutting down");
+        Ok(())
+    }
+}
+
+fn parse_metadata(name: Token, symbol: Token, decimals: Token) -> Option<TokenMetadata> {
+    let decimals = decimals.into_uint()?;
+    Some(TokenMetadata {
+        name: name.into_string()?,
+        symbol: symbol.into_string()?,
+        decimals: u8::try_from(decimals).ok()?,
+    })
+}
@@ -0,0 +1,118 @@
+use chrono::Utc;
+use db_test_macro::db_test;
+
+use zksync_types::tokens::{TokenInfo, TokenPrice};
+
+use super::*;
+use crate::genesis::add_eth_token;
+
+#[test]
+fn token_policy_basics() {
+    let token = Address::repeat_byte(1);
+    let other_token = Address::repeat_byte(2);
+    let denied_token = Address::repeat_byte(3);
+
+    let policy = TokenPolicy::default();
+    assert!(policy.is_allowed(&token));
+    assert!(policy.is_allowed(&ETHEREUM_ADDRESS));
+
+    let mut config = TokenRegistryConfig {
+        allowlist: None,
+        denylist: Some(vec![denied_token]),
+        base_token: None,
+        base_token_rate_refresh_interval_ms: 1_000,
+        metadata_refresh_interval_ms: 1_000,
+        metadata_refresh_batch_size: 10,
+    };
+    let policy = TokenPolicy::new(&config);
+    assert!(policy.is_allowed(&token));
+    assert!(policy.is_allowed(&other_token));
+    assert!(!policy.is_allowed(&denied_token));
+
+    // The denylist takes precedence over the allowlist; ETH is always allowed.
+    config.allowlist = Some(vec![token, denied_token]);
+    let policy = TokenPolicy::new(&config);
+    assert!(policy.is_allowed(&token));
+    assert!(!policy.is_allowed(&other_token));
+    assert!(!policy.is_allowed(&denied_token));
+    assert!(policy.is_allowed(&ETHEREUM_ADDRESS));
+    assert!(policy.is_allowed(&L2_ETH_TOKEN_ADDRESS));
+}
+
+#[test]
+fn parsing_token_metadata() {
+    let metadata = parse_metadata(
+        Token::String("USD Coin".to_owned()),
+        Token::String("USDC".to_owned()),
+        Token::Uint(6.into()),
+    )
+    .unwrap();
+    assert_eq!(metadata.name, "USD Coin");
+    assert_eq!(metadata.symbol, "USDC");
+    assert_eq!(metadata.decimals, 6);
+
+    let metadata = parse_metadata(
+        Token::String("Token".to_owned()),
+        Token::String("TKN".to_owned()),
+        Token::Uint(256.into()),
+    );
+    assert!(metadata.is_none());
+}
+
+#[test]
+fn converting_to_base_token() {
+    let base_token = Address::repeat_byte(1);
+    let conversion =
+        BaseTokenConversion::with_rate(base_token, Ratio::new(3_u32.into(), 2_u32.into()));
+    assert_eq!(conversion.convert(0), 0);
+    assert_eq!(conversion.convert(2), 3);
+    // Converted values are rounded up.
+    assert_eq!(conversion.convert(3), 5);
+    assert_eq!(conversion.convert(u64::MAX), u64::MAX);
+}
+
+#[db_test]
+async fn loading_base_token_conversion(pool: ConnectionPool) {
+    let base_token = TokenInfo {
+        l1_address: Address::repeat_byte(1),
+        l2_address: Address::repeat_byte(2),
+        metadata: TokenMetadata {
+            name: "Test".to_owned(),
+            symbol: "TST".to_owned(),
+            decimals: 6,
+        },
+    };
+    let mut storage = pool.access_storage().await.unwrap();
+    add_eth_token(&mut storage).await;
+    storage
+        .tokens_dal()
+        .add_tokens(vec![base_token.clone()], None)
+        .await;
+    drop(storage);
+
+    // The base token price is not tracked yet.
+    let err = BaseTokenConversion::new(&pool, base_token.l2_address)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("not being tracked"), "{err:#}");
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let prices = [(ETHEREUM_ADDRESS, 2_000_u32), (base_token.l1_address, 4)];
+    for (l1_address, usd_price) in prices {
+        let price = TokenPrice {
+            usd_price: Ratio::from_integer(usd_price.into()),
+            last_updated: Utc::now(),
+        };
+        storage
+            .tokens_dal()
+            .set_l1_token_price(&l1_address, price)
+            .await;
+    }
+    drop(storage);
+
+    // 1 ETH = 500 base tokens, i.e. 10^18 wei = 500 * 10^6 base token units.
+    let conversion = BaseTokenConversion::new(&pool, base_token.l2_address)
+        .await
+        .unwrap();
+    assert_eq!(conversion.convert(2_000_000_000_000), 1_000);
+}
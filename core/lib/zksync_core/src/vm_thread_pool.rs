@@ -0,0 +1,271 @@
+//! Dedicated thread pool for blocking VM execution.
+//!
+//! By default, VM execution (both in the state keeper and in the API sandbox) is performed
+//! via [`tokio::task::spawn_blocking()`]. This means that VM invocations compete for the shared
+//! Tokio blocking pool with DB and filesystem tasks; if the pool is saturated, VM invocations
+//! (or, even worse, I/O tasks the VM depends on) may be delayed. [`VmThreadPool`] isolates
+//! VM execution on a fixed set of OS threads that can be optionally pinned to CPU cores.
+
+use anyhow::Context as _;
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use std::{
+    any::Any,
+    fmt, panic,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct QueuedJob {
+    job: Job,
+    queued_at: Instant,
+}
+
+#[derive(Debug)]
+struct PoolStats {
+    name: &'static str,
+    queued_jobs: AtomicUsize,
+    busy_threads: AtomicUsize,
+}
+
+impl PoolStats {
+    fn report(&self) {
+        let queued_jobs = self.queued_jobs.load(Ordering::Relaxed);
+        let busy_threads = self.busy_threads.load(Ordering::Relaxed);
+        metrics::gauge!("server.vm_thread_pool.queued_jobs", queued_jobs as f64, "pool" => self.name);
+        metrics::gauge!("server.vm_thread_pool.busy_threads", busy_threads as f64, "pool" => self.name);
+    }
+}
+
+struct PoolInner {
+    jobs_sender: mpsc::Sender<QueuedJob>,
+    stats: Arc<PoolStats>,
+    thread_count: usize,
+}
+
+impl fmt::Debug for PoolInner {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PoolInner")
+            .field("stats", &self.stats)
+            .field("thread_count", &self.thread_count)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Thread pool used to run blocking VM code. Cheaply cloneable.
+///
+/// The default pool does not spawn any threads and delegates to [`tokio::task::spawn_blocking()`].
+/// Threads of a dedicated pool are terminated once all clones of the pool are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct VmThreadPool {
+    inner: Option<Arc<PoolInner>>,
+}
+
+impl VmThreadPool {
+    /// Creates a dedicated pool with the specified number of threads. If `pinned_cores` are specified,
+    /// threads are pinned to the specified CPU cores in the round-robin order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of `pinned_cores` is not available on the machine, or if
+    /// spawning a thread fails.
+    pub fn new(
+        name: &'static str,
+        thread_count: usize,
+        pinned_cores: &[usize],
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            thread_count > 0,
+            "VM thread pool `{name}` must have at least 1 thread"
+        );
+        let pinned_cores = Self::resolve_cores(pinned_cores)
+            .with_context(|| format!("failed resolving CPU cores for VM thread pool `{name}`"))?;
+        tracing::info!(
+            "Initializing VM thread pool `{name}` with {thread_count} threads; pinned cores: {pinned_cores:?}"
+        );
+
+        let (jobs_sender, jobs_receiver) = mpsc::channel::<QueuedJob>();
+        let jobs_receiver = Arc::new(Mutex::new(jobs_receiver));
+        let stats = Arc::new(PoolStats {
+            name,
+            queued_jobs: AtomicUsize::new(0),
+            busy_threads: AtomicUsize::new(0),
+        });
+
+        for i in 0..thread_count {
+            let jobs_receiver = Arc::clone(&jobs_receiver);
+            let stats = Arc::clone(&stats);
+            let core_id = if pinned_cores.is_empty() {
+                None
+            } else {
+                Some(pinned_cores[i % pinned_cores.len()])
+            };
+            thread::Builder::new()
+                .name(format!("{name}-vm-{i}"))
+                .spawn(move || Self::run_worker(&jobs_receiver, &stats, core_id))
+                .with_context(|| {
+                    format!("failed spawning thread #{i} for VM thread pool `{name}`")
+                })?;
+        }
+
+        Ok(Self {
+            inner: Some(Arc::new(PoolInner {
+                jobs_sender,
+                stats,
+                thread_count,
+            })),
+        })
+    }
+
+    fn resolve_cores(pinned_cores: &[usize]) -> anyhow::Result<Vec<core_affinity::CoreId>> {
+        if pinned_cores.is_empty() {
+            return Ok(vec![]);
+        }
+        let available_cores =
+            core_affinity::get_core_ids().context("cannot get CPU cores on this platform")?;
+        pinned_cores
+            .iter()
+            .map(|&id| {
+                available_cores
+                    .iter()
+                    .copied()
+                    .find(|core| core.id == id)
+                    .with_context(|| format!("CPU core #{id} is not available"))
+            })
+            .collect()
+    }
+
+    fn run_worker(
+        jobs_receiver: &Mutex<mpsc::Receiver<QueuedJob>>,
+        stats: &PoolStats,
+        core_id: Option<core_affinity::CoreId>,
+    ) {
+        if let Some(core_id) = core_id {
+            if !core_affinity::set_for_current(core_id) {
+                tracing::warn!(
+                    "Failed pinning thread `{}` to CPU core #{}",
+                    thread::current().name().unwrap_or_default(),
+                    core_id.id
+                );
+            }
+        }
+
+        loop {
+            // The lock is only held while waiting for the next job, so that other threads can pick up
+            // subsequent jobs while this one is being executed.
+            let next_job = jobs_receiver
+                .lock()
+                .expect("VM thread pool receiver is poisoned")
+                .recv();
+            let Ok(QueuedJob { job, queued_at }) = next_job else {
+                return; // All pool handles are dropped
+            };
+
+            metrics::histogram!("server.vm_thread_pool.queue_latency", queued_at.elapsed(), "pool" => stats.name);
+            stats.queued_jobs.fetch_sub(1, Ordering::Relaxed);
+            stats.busy_threads.fetch_add(1, Ordering::Relaxed);
+            stats.report();
+
+            let started_at = Instant::now();
+            job(); // Panics are caught when wrapping the job, so this never unwinds.
+            metrics::histogram!("server.vm_thread_pool.execution_latency", started_at.elapsed(), "pool" => stats.name);
+
+            stats.busy_threads.fetch_sub(1, Ordering::Relaxed);
+            stats.report();
+        }
+    }
+
+    /// Returns the number of threads in this pool, or `None` if this pool delegates to the Tokio blocking pool.
+    pub fn thread_count(&self) -> Option<usize> {
+        self.inner.as_ref().map(|inner| inner.thread_count)
+    }
+
+    /// Runs the provided closure on this pool. Semantics is identical to [`tokio::task::spawn_blocking()`];
+    /// in particular, a panic in the closure is propagated to the returned handle as a `JoinError`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the Tokio runtime context.
+    pub fn spawn<T, F>(&self, f: F) -> JoinHandle<T>
+    where
+        T: 'static + Send,
+        F: 'static + Send + FnOnce() -> T,
+    {
+        let Some(inner) = &self.inner else {
+            return tokio::task::spawn_blocking(f);
+        };
+
+        let (result_sender, result_receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+            result_sender.send(result).ok();
+        });
+        inner.stats.queued_jobs.fetch_add(1, Ordering::Relaxed);
+        let send_result = inner.jobs_sender.send(QueuedJob {
+            job,
+            queued_at: Instant::now(),
+        });
+        if send_result.is_err() {
+            inner.stats.queued_jobs.fetch_sub(1, Ordering::Relaxed);
+        }
+
+        tokio::spawn(async move {
+            let result: Result<T, Box<dyn Any + Send>> = result_receiver
+                .await
+                .expect("VM thread pool terminated before completing the job");
+            match result {
+                Ok(value) => value,
+                Err(panic_payload) => panic::resume_unwind(panic_payload),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn executing_jobs_on_dedicated_pool() {
+        let pool = VmThreadPool::new("test", 2, &[]).unwrap();
+        assert_eq!(pool.thread_count(), Some(2));
+
+        let handles = (0..10).map(|i| {
+            pool.spawn(move || {
+                let thread_name = thread::current().name().unwrap().to_owned();
+                (i * 2, thread_name)
+            })
+        });
+        for (i, handle) in handles.enumerate() {
+            let (value, thread_name) = handle.await.unwrap();
+            assert_eq!(value, i * 2);
+            assert!(thread_name.starts_with("test-vm-"), "{thread_name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn propagating_panics_from_dedicated_pool() {
+        let pool = VmThreadPool::new("test", 1, &[]).unwrap();
+        let err = pool.spawn(|| panic!("oops")).await.unwrap_err();
+        assert!(err.is_panic());
+
+        // The pool should remain operational after a panic.
+        let value = pool.spawn(|| 42).await.unwrap();
+        assert_eq!(value, 42);
+    }
+
+    #[tokio::test]
+    async fn default_pool_delegates_to_tokio() {
+        let pool = VmThreadPool::default();
+        assert_eq!(pool.thread_count(), None);
+        let value = pool.spawn(|| 42).await.unwrap();
+        assert_eq!(value, 42);
+    }
+}
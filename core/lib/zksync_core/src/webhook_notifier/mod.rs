@@ -0,0 +1,588 @@
+//! Webhook notifier that reports L1 batch lifecycle transitions (sealing, commitment, proving,
+//! execution and reverts) to external HTTP endpoints, so that downstream systems don't need
+//! to poll the API.
+//!
+//! Each event is POSTed as a JSON object to all endpoints subscribed to it. Payloads are signed
+//! with HMAC-SHA256 over `{timestamp}.{body}`; the signature and the timestamp are passed
+//! in the `X-Webhook-Signature` and `X-Webhook-Timestamp` headers respectively.
+//!
+//! Events are delivered to each endpoint in order. L1 batch statuses delivered to an endpoint are persisted
+//! in Postgres after each event, so that the notifier resumes from where it left off after a restart.
+//! Sealed L1 batches are identified by the hash of their last miniblock; this allows detecting reverts
+//! even if reverted L1 batches were re-sealed between polls.
+
+use anyhow::Context as _;
+use futures::future;
+use hmac::{Hmac, Mac};
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::watch;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use zksync_config::configs::WebhookNotifierConfig;
+use zksync_dal::{webhook_notifier_dal::WebhookCursor, ConnectionPool, StorageProcessor};
+use zksync_types::{L1BatchNumber, H256};
+
+#[cfg(test)]
+mod tests;
+
+/// Upper bound for the backoff between delivery retries.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+
+/// Kind of an L1 batch lifecycle event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    /// L1 batch is sealed by the state keeper.
+    Sealed,
+    /// Commit transaction for the L1 batch is confirmed on L1.
+    Committed,
+    /// Prove transaction for the L1 batch is confirmed on L1.
+    Proven,
+    /// Execute transaction for the L1 batch is confirmed on L1.
+    Executed,
+    /// A lifecycle stage was reverted for L1 batches after the specified one.
+    Reverted,
+}
+
+impl FromStr for WebhookEventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "sealed" => Self::Sealed,
+            "committed" => Self::Committed,
+            "proven" => Self::Proven,
+            "executed" => Self::Executed,
+            "reverted" => Self::Reverted,
+            _ => anyhow::bail!("unknown webhook event `{s}`"),
+        })
+    }
+}
+
+/// Webhook event delivered to endpoints. For [`WebhookEventKind::Reverted`] events, `reverted_stage`
+/// specifies the reverted lifecycle stage, and `l1_batch_number` is the number of the last L1 batch
+/// retaining this stage (0 if no L1 batches retain it).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct WebhookEvent {
+    event: WebhookEventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reverted_stage: Option<WebhookEventKind>,
+    l1_batch_number: u32,
+    /// Hash of the last miniblock in the L1 batch; only set for sealed L1 batches. Used to identify L1 batches
+    /// in the persisted cursor.
+    #[serde(skip)]
+    l1_batch_hash: Option<H256>,
+}
+
+impl WebhookEvent {
+    fn new(event: WebhookEventKind, l1_batch_number: L1BatchNumber) -> Self {
+        Self {
+            event,
+            reverted_stage: None,
+            l1_batch_number: l1_batch_number.0,
+            l1_batch_hash: None,
+        }
+    }
+
+    fn sealed(l1_batch_number: L1BatchNumber, l1_batch_hash: H256) -> Self {
+        Self {
+            l1_batch_hash: Some(l1_batch_hash),
+            ..Self::new(WebhookEventKind::Sealed, l1_batch_number)
+        }
+    }
+
+    fn reverted(stage: WebhookEventKind, last_retained: Option<L1BatchNumber>) -> Self {
+        Self {
+            reverted_stage: Some(stage),
+            ..Self::new(
+                WebhookEventKind::Reverted,
+                last_retained.unwrap_or(L1BatchNumber(0)),
+            )
+        }
+    }
+
+    /// Updates the cursor after the event is delivered.
+    fn apply(&self, cursor: &mut WebhookCursor) {
+        let number = L1BatchNumber(self.l1_batch_number);
+        match self.event {
+            WebhookEventKind::Sealed => {
+                let hash = self.l1_batch_hash.expect("no hash for sealed L1 batch");
+                cursor.sealed_l1_batches.push((number, hash));
+            }
+            WebhookEventKind::Committed => cursor.committed_l1_batch = Some(number),
+            WebhookEventKind::Proven => cursor.proven_l1_batch = Some(number),
+            WebhookEventKind::Executed => {
+                cursor.executed_l1_batch = Some(number);
+                // Executed L1 batches cannot be reverted, so there's no need to keep their hashes
+                // (other than for the last executed batch, which anchors revert detection).
+                cursor
+                    .sealed_l1_batches
+                    .retain(|&(sealed_number, _)| sealed_number >= number);
+            }
+            WebhookEventKind::Reverted => {
+                let stage = self.reverted_stage.expect("no stage for revert event");
+                let retained = (self.l1_batch_number > 0).then_some(number);
+                match stage {
+                    WebhookEventKind::Sealed => {
+                        cursor
+                            .sealed_l1_batches
+                            .retain(|&(sealed_number, _)| sealed_number <= number);
+                        if cursor.sealed_l1_batches.is_empty() {
+                            if let Some(hash) = self.l1_batch_hash {
+                                cursor.sealed_l1_batches.push((number, hash));
+                            }
+                        }
+                    }
+                    WebhookEventKind::Committed => cursor.committed_l1_batch = retained,
+                    WebhookEventKind::Proven => cursor.proven_l1_batch = retained,
+                    WebhookEventKind::Executed => cursor.executed_l1_batch = retained,
+                    WebhookEventKind::Reverted => unreachable!("reverts cannot be reverted"),
+                }
+            }
+        }
+    }
+}
+
+/// Webhook endpoint together with the events it's subscribed to.
+#[derive(Debug, Clone, PartialEq)]
+struct WebhookEndpoint {
+    url: String,
+    /// `None` means that the endpoint is subscribed to all events.
+    events: Option<HashSet<WebhookEventKind>>,
+}
+
+impl WebhookEndpoint {
+    fn accepts(&self, event: WebhookEventKind) -> bool {
+        self.events
+            .as_ref()
+            .map_or(true, |events| events.contains(&event))
+    }
+}
+
+impl FromStr for WebhookEndpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (url, events) = match s.split_once('#') {
+            Some((url, events)) => {
+                let events = events
+                    .split('+')
+                    .map(str::parse)
+                    .collect::<anyhow::Result<HashSet<_>>>()
+                    .with_context(|| {
+                        format!("invalid event filter for webhook endpoint `{url}`")
+                    })?;
+                (url, Some(events))
+            }
+            None => (s, None),
+        };
+        reqwest::Url::parse(url).with_context(|| format!("invalid webhook URL `{url}`"))?;
+        Ok(Self {
+            url: url.to_owned(),
+            events,
+        })
+    }
+}
+
+/// Snapshot of L1 batch statuses loaded from Postgres.
+#[derive(Debug, Clone, PartialEq)]
+struct L1BatchStatuses {
+    sealed: L1BatchNumber,
+    committed: Option<L1BatchNumber>,
+    proven: Option<L1BatchNumber>,
+    executed: Option<L1BatchNumber>,
+    /// Hashes of sealed L1 batches starting from the first L1 batch in the cursor the statuses are loaded for.
+    hashes: HashMap<L1BatchNumber, H256>,
+}
+
+impl L1BatchStatuses {
+    async fn load(
+        storage: &mut StorageProcessor<'_>,
+        cursor: Option<&WebhookCursor>,
+    ) -> anyhow::Result<Self> {
+        let mut blocks_dal = storage.blocks_dal();
+        let sealed = blocks_dal
+            .get_sealed_l1_batch_number()
+            .await
+            .context("get_sealed_l1_batch_number()")?;
+        let committed = blocks_dal
+            .get_number_of_last_l1_batch_committed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_committed_on_eth()")?;
+        let proven = blocks_dal
+            .get_number_of_last_l1_batch_proven_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_proven_on_eth()")?;
+        let executed = blocks_dal
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?;
+
+        let first_l1_batch = match cursor {
+            // Also load the hash of the L1 batch preceding the cursor, so that it's known if the first L1 batch
+            // in the cursor is reverted.
+            Some(cursor) => cursor
+                .sealed_l1_batches
+                .first()
+                .map_or(sealed, |&(number, _)| {
+                    L1BatchNumber(number.0.saturating_sub(1))
+                }),
+            None => executed.unwrap_or(L1BatchNumber(0)),
+        };
+        let hashes = storage
+            .webhook_notifier_dal()
+            .get_l1_batch_hashes(first_l1_batch.min(sealed)..=sealed)
+            .await
+            .context("get_l1_batch_hashes()")?;
+        Ok(Self {
+            sealed,
+            committed,
+            proven,
+            executed,
+            hashes: hashes.into_iter().collect(),
+        })
+    }
+
+    /// Creates a cursor for an endpoint that didn't receive any events yet.
+    fn initial_cursor(&self) -> WebhookCursor {
+        let first_l1_batch = self.executed.unwrap_or(L1BatchNumber(0));
+        let mut sealed_l1_batches: Vec<_> = self
+            .hashes
+            .iter()
+            .filter(|(&number, _)| number >= first_l1_batch && number <= self.sealed)
+            .map(|(&number, &hash)| (number, hash))
+            .collect();
+        sealed_l1_batches.sort_unstable_by_key(|&(number, _)| number);
+        WebhookCursor {
+            sealed_l1_batches,
+            committed_l1_batch: self.committed,
+            proven_l1_batch: self.proven,
+            executed_l1_batch: self.executed,
+        }
+    }
+
+    /// Returns events corresponding to the transition from the `cursor` to these statuses.
+    /// Reverts are reported first, followed by lifecycle transitions in the order of stages.
+    fn events_since(&self, cursor: &WebhookCursor) -> Vec<WebhookEvent> {
+        let mut reverts = vec![];
+        let mut transitions = vec![];
+
+        // The first sealed L1 batch with a changed hash was reverted (and possibly re-sealed).
+        let first_reverted = cursor
+            .sealed_l1_batches
+            .iter()
+            .find(|(number, hash)| self.hashes.get(number) != Some(hash));
+        let last_sealed = if let Some(&(first_reverted, _)) = first_reverted {
+            let last_retained = L1BatchNumber(first_reverted.0.saturating_sub(1));
+            let mut revert = WebhookEvent::reverted(WebhookEventKind::Sealed, Some(last_retained));
+            revert.l1_batch_hash = self.hashes.get(&last_retained).copied();
+            reverts.push(revert);
+            Some(last_retained)
+        } else {
+            cursor.sealed_l1_batches.last().map(|&(number, _)| number)
+        };
+        let first_new_sealed = last_sealed.map_or(0, |number| number.0 + 1);
+        for number in (first_new_sealed..=self.sealed.0).map(L1BatchNumber) {
+            let Some(&hash) = self.hashes.get(&number) else {
+                // The L1 batch is being sealed; it will be reported on the next poll.
+                break;
+            };
+            transitions.push(WebhookEvent::sealed(number, hash));
+        }
+
+        let last_retained_sealed = first_reverted.map(|&(number, _)| number.0.saturating_sub(1));
+        let l1_stages = [
+            (
+                cursor.committed_l1_batch,
+                self.committed,
+                WebhookEventKind::Committed,
+            ),
+            (
+                cursor.proven_l1_batch,
+                self.proven,
+                WebhookEventKind::Proven,
+            ),
+            (
+                cursor.executed_l1_batch,
+                self.executed,
+                WebhookEventKind::Executed,
+            ),
+        ];
+        for (reported, actual, kind) in l1_stages {
+            let mut retained = reported;
+            if let Some(last_retained_sealed) = last_retained_sealed {
+                // L1 stages of reverted L1 batches are reverted as well, even if they were reached again.
+                if retained.map_or(false, |number| number.0 > last_retained_sealed) {
+                    retained = Some(L1BatchNumber(last_retained_sealed));
+                }
+            }
+            if actual < retained {
+                retained = actual;
+            }
+            if retained != reported {
+                reverts.push(WebhookEvent::reverted(kind, retained));
+            }
+
+            if let Some(actual) = actual {
+                // The genesis L1 batch is never committed to L1, so the first L1 batch for L1 stages is 1.
+                let start = retained.map_or(1, |number| number.0 + 1);
+                transitions.extend(
+                    (start..=actual.0).map(|number| WebhookEvent::new(kind, L1BatchNumber(number))),
+                );
+            }
+        }
+
+        reverts.extend(transitions);
+        reverts
+    }
+}
+
+/// Component polling L1 batch statuses and delivering webhook events on their changes.
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    pool: ConnectionPool,
+    endpoints: Vec<WebhookEndpoint>,
+    poll_interval: Duration,
+    delivery: DeliveryParams,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &WebhookNotifierConfig, pool: ConnectionPool) -> anyhow::Result<Self> {
+        let endpoints: Vec<WebhookEndpoint> = config
+            .endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.is_empty())
+            .map(|endpoint| endpoint.parse())
+            .collect::<anyhow::Result<_>>()?;
+        let mut urls = HashSet::with_capacity(endpoints.len());
+        for endpoint in &endpoints {
+            // URLs are used as keys for persisted cursors.
+            anyhow::ensure!(
+                urls.insert(&endpoint.url),
+                "webhook endpoint `{}` is specified multiple times",
+                endpoint.url
+            );
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout())
+            .build()
+            .context("failed building HTTP client for webhooks")?;
+
+        Ok(Self {
+            pool,
+            endpoints,
+            poll_interval: config.poll_interval(),
+            delivery: DeliveryParams {
+                client,
+                signing_secret: config.signing_secret.clone().into_bytes().into(),
+                max_retries: config.max_retries,
+                initial_retry_backoff: config.initial_retry_backoff(),
+            },
+        })
+    }
+
+    pub async fn run(self, stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        if self.endpoints.is_empty() {
+            tracing::warn!("No webhook endpoints are configured; webhook notifier is idle");
+        }
+
+        let endpoint_tasks = self
+            .endpoints
+            .iter()
+            .map(|endpoint| self.run_for_endpoint(endpoint, stop_receiver.clone()));
+        future::try_join_all(endpoint_tasks).await?;
+        tracing::info!("Stop signal received, webhook notifier is shutting down");
+        Ok(())
+    }
+
+    async fn run_for_endpoint(
+        &self,
+        endpoint: &WebhookEndpoint,
+        mut stop_receiver: watch::Receiver<bool>,
+    ) -> anyhow::Result<()> {
+        let url = &endpoint.url;
+        let mut cursor = self.load_cursor(url).await?;
+        tracing::info!("Starting webhook delivery to `{url}` with cursor {cursor:?}");
+
+        while !*stop_receiver.borrow() {
+            let mut storage = self.pool.access_storage_tagged("webhook_notifier").await?;
+            let statuses = L1BatchStatuses::load(&mut storage, Some(&cursor)).await?;
+            drop(storage);
+
+            for event in statuses.events_since(&cursor) {
+                if endpoint.accepts(event.event) {
+                    tracing::debug!("Delivering webhook event {event:?} to `{url}`");
+                    let outcome = self.delivery.deliver(url, event, &mut stop_receiver).await;
+                    metrics::increment_counter!("server.webhook_notifier.delivery", "outcome" => outcome);
+                    if outcome == "cancelled" {
+                        return Ok(());
+                    }
+                }
+                event.apply(&mut cursor);
+                self.save_cursor(url, &cursor).await?;
+            }
+
+            let stop_signal =
+                tokio::time::timeout(self.poll_interval, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for webhook notifier was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the persisted cursor for the endpoint. If there's no cursor, only transitions that happen
+    /// after the notifier has started are reported.
+    async fn load_cursor(&self, url: &str) -> anyhow::Result<WebhookCursor> {
+        let mut storage = self.pool.access_storage_tagged("webhook_notifier").await?;
+        let cursor = storage
+            .webhook_notifier_dal()
+            .get_cursor(url)
+            .await
+            .with_context(|| format!("failed loading cursor for webhook endpoint `{url}`"))?;
+        if let Some(cursor) = cursor {
+            return Ok(cursor);
+        }
+
+        let statuses = L1BatchStatuses::load(&mut storage, None).await?;
+        let cursor = statuses.initial_cursor();
+        drop(storage);
+        self.save_cursor(url, &cursor).await?;
+        Ok(cursor)
+    }
+
+    async fn save_cursor(&self, url: &str, cursor: &WebhookCursor) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged("webhook_notifier").await?;
+        storage
+            .webhook_notifier_dal()
+            .save_cursor(url, cursor)
+            .await
+            .with_context(|| format!("failed saving cursor for webhook endpoint `{url}`"))
+    }
+}
+
+#[derive(Clone)]
+struct DeliveryParams {
+    client: reqwest::Client,
+    signing_secret: Arc<[u8]>,
+    max_retries: u32,
+    initial_retry_backoff: Duration,
+}
+
+impl fmt::Debug for DeliveryParams {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("DeliveryParams")
+            .field("max_retries", &self.max_retries)
+            .field("initial_retry_backoff", &self.initial_retry_backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeliveryParams {
+    async fn deliver(
+        &self,
+        url: &str,
+        event: WebhookEvent,
+        stop_receiver: &mut watch::Receiver<bool>,
+    ) -> &'static str {
+        let body = serde_json::to_vec(&event).expect("failed serializing webhook event");
+        let mut backoff = self.initial_retry_backoff;
+        for attempt in 0..=self.max_retries {
+            match self.send(url, &body).await {
+                Ok(()) => return "delivered",
+                Err(DeliveryError::Permanent(err)) => {
+                    tracing::warn!(
+                        "Webhook endpoint `{url}` rejected event {event:?}, not retrying: {err:#}"
+                    );
+                    return "rejected";
+                }
+                Err(DeliveryError::Transient(err)) => {
+                    tracing::info!(
+                        "Failed delivering event {event:?} to webhook endpoint `{url}` \
+                         (attempt {attempt}): {err:#}"
+                    );
+                }
+            }
+
+            if attempt < self.max_retries {
+                if tokio::time::timeout(backoff, stop_receiver.changed())
+                    .await
+                    .is_ok()
+                {
+                    return "cancelled";
+                }
+                backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+            }
+        }
+        tracing::warn!(
+            "Giving up delivering event {event:?} to webhook endpoint `{url}` after {} retries",
+            self.max_retries
+        );
+        "failed"
+    }
+
+    async fn send(&self, url: &str, body: &[u8]) -> Result<(), DeliveryError> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("incorrect system time")
+            .as_secs();
+        let signature = sign_payload(&self.signing_secret, timestamp, body);
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp)
+            .header(SIGNATURE_HEADER, format!("sha256={signature}"))
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|err| DeliveryError::Transient(err.into()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Err(DeliveryError::Transient(anyhow::anyhow!(
+                "endpoint responded with {status}"
+            )))
+        } else {
+            Err(DeliveryError::Permanent(anyhow::anyhow!(
+                "endpoint responded with {status}"
+            )))
+        }
+    }
+}
+
+#[derive(Debug)]
+enum DeliveryError {
+    /// Error that may go away on retry (e.g., a network error or a 5xx response).
+    Transient(anyhow::Error),
+    /// Error that won't go away on retry (e.g., a 4xx response).
+    Permanent(anyhow::Error),
+}
+
+/// Computes a hex-encoded HMAC-SHA256 signature of the webhook payload.
+fn sign_payload(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC can take keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
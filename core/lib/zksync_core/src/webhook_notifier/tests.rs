@@ -0,0 +1,520 @@
+//! Tests for the webhook notifier.
+
+use axum::{extract::State, routing::post, Json, Router};
+use db_test_macro::db_test;
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    sync::Mutex,
+};
+
+use zksync_contracts::BaseSystemContracts;
+use zksync_types::{
+    block::{miniblock_hash, BlockGasCount, L1BatchHeader, MiniblockHeader},
+    commitment::CommitmentSchemes,
+    protocol_version::L1VerifierConfig,
+    system_contracts::get_system_smart_contracts,
+    Address, L1BatchCommitmentMode, L2ChainId, MiniblockNumber, ProtocolVersionId,
+};
+
+use super::*;
+use crate::genesis::{ensure_genesis_state, GenesisParams};
+
+fn hash(byte: u8) -> H256 {
+    H256::repeat_byte(byte)
+}
+
+fn to_option(number: u32) -> Option<L1BatchNumber> {
+    (number > 0).then_some(L1BatchNumber(number))
+}
+
+fn cursor(
+    sealed_l1_batches: &[(u32, u8)],
+    committed: u32,
+    proven: u32,
+    executed: u32,
+) -> WebhookCursor {
+    WebhookCursor {
+        sealed_l1_batches: sealed_l1_batches
+            .iter()
+            .map(|&(number, byte)| (L1BatchNumber(number), hash(byte)))
+            .collect(),
+        committed_l1_batch: to_option(committed),
+        proven_l1_batch: to_option(proven),
+        executed_l1_batch: to_option(executed),
+    }
+}
+
+fn statuses(hashes: &[(u32, u8)], committed: u32, proven: u32, executed: u32) -> L1BatchStatuses {
+    L1BatchStatuses {
+        sealed: L1BatchNumber(hashes.last().unwrap().0),
+        committed: to_option(committed),
+        proven: to_option(proven),
+        executed: to_option(executed),
+        hashes: hashes
+            .iter()
+            .map(|&(number, byte)| (L1BatchNumber(number), hash(byte)))
+            .collect(),
+    }
+}
+
+fn reverted(stage: WebhookEventKind, number: u32) -> WebhookEvent {
+    WebhookEvent::reverted(stage, to_option(number))
+}
+
+/// Applies events to the cursor and checks that the cursor is up to date afterwards.
+fn apply_events(statuses: &L1BatchStatuses, cursor: &mut WebhookCursor, events: &[WebhookEvent]) {
+    for event in events {
+        event.apply(cursor);
+    }
+    assert_eq!(statuses.events_since(cursor), []);
+}
+
+#[test]
+fn parsing_endpoints() {
+    let endpoint: WebhookEndpoint = "http://127.0.0.1:8080/hook".parse().unwrap();
+    assert_eq!(endpoint.url, "http://127.0.0.1:8080/hook");
+    assert!(endpoint.accepts(WebhookEventKind::Sealed));
+    assert!(endpoint.accepts(WebhookEventKind::Reverted));
+
+    let endpoint: WebhookEndpoint = "https://example.com/hook#committed+reverted"
+        .parse()
+        .unwrap();
+    assert_eq!(endpoint.url, "https://example.com/hook");
+    assert!(endpoint.accepts(WebhookEventKind::Committed));
+    assert!(endpoint.accepts(WebhookEventKind::Reverted));
+    assert!(!endpoint.accepts(WebhookEventKind::Sealed));
+    assert!(!endpoint.accepts(WebhookEventKind::Executed));
+
+    "https://example.com/hook#committed+finalized"
+        .parse::<WebhookEndpoint>()
+        .unwrap_err();
+    "not a URL".parse::<WebhookEndpoint>().unwrap_err();
+}
+
+#[test]
+fn serializing_events() {
+    let event = WebhookEvent::sealed(L1BatchNumber(5), hash(5));
+    assert_eq!(
+        serde_json::to_value(event).unwrap(),
+        serde_json::json!({ "event": "sealed", "l1_batch_number": 5 })
+    );
+
+    let event = reverted(WebhookEventKind::Committed, 3);
+    assert_eq!(
+        serde_json::to_value(event).unwrap(),
+        serde_json::json!({
+            "event": "reverted",
+            "reverted_stage": "committed",
+            "l1_batch_number": 3,
+        })
+    );
+}
+
+#[test]
+fn events_for_status_transitions() {
+    let mut cursor = cursor(&[(4, 4), (5, 5)], 3, 2, 0);
+    let old = statuses(&[(4, 4), (5, 5)], 3, 2, 0);
+    assert_eq!(old.events_since(&cursor), []);
+
+    let new = statuses(&[(4, 4), (5, 5), (6, 6), (7, 7)], 4, 2, 1);
+    let events = new.events_since(&cursor);
+    assert_eq!(
+        events,
+        [
+            WebhookEvent::sealed(L1BatchNumber(6), hash(6)),
+            WebhookEvent::sealed(L1BatchNumber(7), hash(7)),
+            WebhookEvent::new(WebhookEventKind::Committed, L1BatchNumber(4)),
+            WebhookEvent::new(WebhookEventKind::Executed, L1BatchNumber(1)),
+        ]
+    );
+    apply_events(&new, &mut cursor, &events);
+}
+
+#[test]
+fn sealed_l1_batch_without_hash_is_postponed() {
+    let cursor = cursor(&[(4, 4)], 0, 0, 0);
+    let mut new = statuses(&[(4, 4), (5, 5), (6, 6)], 0, 0, 0);
+    new.hashes.remove(&L1BatchNumber(6));
+    assert_eq!(
+        new.events_since(&cursor),
+        [WebhookEvent::sealed(L1BatchNumber(5), hash(5))]
+    );
+}
+
+#[test]
+fn events_for_sealed_revert() {
+    let mut cursor = cursor(&[(4, 4), (5, 5), (6, 6), (7, 7), (8, 8)], 8, 6, 4);
+    let new = statuses(&[(4, 4), (5, 5), (6, 6)], 6, 6, 4);
+    let events = new.events_since(&cursor);
+    let mut sealed_revert = reverted(WebhookEventKind::Sealed, 6);
+    sealed_revert.l1_batch_hash = Some(hash(6));
+    assert_eq!(
+        events,
+        [sealed_revert, reverted(WebhookEventKind::Committed, 6)]
+    );
+    apply_events(&new, &mut cursor, &events);
+    assert_eq!(
+        cursor.sealed_l1_batches.last(),
+        Some(&(L1BatchNumber(6), hash(6)))
+    );
+}
+
+#[test]
+fn events_for_revert_with_reseal() {
+    // L1 batches 7 and 8 are reverted and then re-sealed (and even re-committed) between polls.
+    let mut cursor = cursor(&[(4, 4), (5, 5), (6, 6), (7, 7), (8, 8)], 8, 6, 4);
+    let new = statuses(
+        &[(4, 4), (5, 5), (6, 6), (7, 17), (8, 18), (9, 19)],
+        8,
+        6,
+        4,
+    );
+    let events = new.events_since(&cursor);
+    let mut sealed_revert = reverted(WebhookEventKind::Sealed, 6);
+    sealed_revert.l1_batch_hash = Some(hash(6));
+    assert_eq!(
+        events,
+        [
+            sealed_revert,
+            reverted(WebhookEventKind::Committed, 6),
+            WebhookEvent::sealed(L1BatchNumber(7), hash(17)),
+            WebhookEvent::sealed(L1BatchNumber(8), hash(18)),
+            WebhookEvent::sealed(L1BatchNumber(9), hash(19)),
+            WebhookEvent::new(WebhookEventKind::Committed, L1BatchNumber(7)),
+            WebhookEvent::new(WebhookEventKind::Committed, L1BatchNumber(8)),
+        ]
+    );
+    apply_events(&new, &mut cursor, &events);
+}
+
+#[test]
+fn events_for_revert_of_first_l1_batch_in_cursor() {
+    let mut cursor = cursor(&[(4, 4), (5, 5)], 0, 0, 4);
+    let new = statuses(&[(3, 3), (4, 14)], 0, 0, 3);
+    let events = new.events_since(&cursor);
+    let mut sealed_revert = reverted(WebhookEventKind::Sealed, 3);
+    sealed_revert.l1_batch_hash = Some(hash(3));
+    assert_eq!(
+        events,
+        [
+            sealed_revert,
+            reverted(WebhookEventKind::Executed, 3),
+            WebhookEvent::sealed(L1BatchNumber(4), hash(14)),
+        ]
+    );
+    apply_events(&new, &mut cursor, &events);
+    assert_eq!(
+        cursor.sealed_l1_batches,
+        [(L1BatchNumber(3), hash(3)), (L1BatchNumber(4), hash(14))]
+    );
+}
+
+#[test]
+fn events_for_l1_stage_reverts() {
+    let sealed = [(6, 6), (7, 7), (8, 8), (9, 9), (10, 10)];
+    let mut cursor = cursor(&sealed, 9, 8, 7);
+    let new = statuses(&sealed, 8, 6, 6);
+    let events = new.events_since(&cursor);
+    assert_eq!(
+        events,
+        [
+            reverted(WebhookEventKind::Committed, 8),
+            reverted(WebhookEventKind::Proven, 6),
+            reverted(WebhookEventKind::Executed, 6),
+        ]
+    );
+    apply_events(&new, &mut cursor, &events);
+
+    // Reverting a stage for all L1 batches.
+    let new = statuses(&sealed, 8, 0, 6);
+    let events = new.events_since(&cursor);
+    assert_eq!(events, [reverted(WebhookEventKind::Proven, 0)]);
+    apply_events(&new, &mut cursor, &events);
+    assert_eq!(cursor.proven_l1_batch, None);
+
+    // Stage reached again after the revert.
+    let new = statuses(&sealed, 9, 7, 6);
+    let events = new.events_since(&cursor);
+    assert_eq!(
+        events,
+        [
+            WebhookEvent::new(WebhookEventKind::Committed, L1BatchNumber(9)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(1)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(2)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(3)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(4)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(5)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(6)),
+            WebhookEvent::new(WebhookEventKind::Proven, L1BatchNumber(7)),
+        ]
+    );
+    apply_events(&new, &mut cursor, &events);
+}
+
+#[test]
+fn executing_l1_batches_prunes_cursor() {
+    let mut cursor = cursor(&[(4, 4), (5, 5), (6, 6)], 6, 6, 4);
+    WebhookEvent::new(WebhookEventKind::Executed, L1BatchNumber(5)).apply(&mut cursor);
+    assert_eq!(
+        cursor.sealed_l1_batches,
+        [(L1BatchNumber(5), hash(5)), (L1BatchNumber(6), hash(6))]
+    );
+    assert_eq!(cursor.executed_l1_batch, Some(L1BatchNumber(5)));
+}
+
+#[test]
+fn signing_payload() {
+    let body = br#"{"event":"sealed","l1_batch_number":1}"#;
+    let signature = sign_payload(b"secret", 1_700_000_000, body);
+    assert_eq!(signature.len(), 64);
+    assert_eq!(signature, sign_payload(b"secret", 1_700_000_000, body));
+    assert_ne!(signature, sign_payload(b"other", 1_700_000_000, body));
+    assert_ne!(signature, sign_payload(b"secret", 1_700_000_001, body));
+}
+
+async fn prepare_storage(pool: &ConnectionPool) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let params = GenesisParams {
+        first_validator: Address::repeat_byte(0x01),
+        protocol_version: ProtocolVersionId::latest(),
+        base_system_contracts: BaseSystemContracts::load_from_disk(),
+        system_contracts: get_system_smart_contracts(),
+        first_l1_verifier_config: L1VerifierConfig::default(),
+        first_verifier_address: Address::zero(),
+        commitment_schemes: CommitmentSchemes::default(),
+        commitment_mode: L1BatchCommitmentMode::Rollup,
+    };
+    ensure_genesis_state(&mut storage, L2ChainId(270), &params)
+        .await
+        .unwrap();
+}
+
+/// Seals an L1 batch consisting of a single miniblock with the same number. The hash of the L1 batch
+/// depends on the `timestamp`.
+async fn seal_l1_batch(pool: &ConnectionPool, number: u32, timestamp: u64) -> H256 {
+    let base_system_contracts_hashes = BaseSystemContracts::load_from_disk().hashes();
+    let miniblock_number = MiniblockNumber(number);
+    let miniblock_hash = miniblock_hash(miniblock_number, timestamp, H256::zero(), H256::zero());
+    let miniblock_header = MiniblockHeader {
+        number: miniblock_number,
+        timestamp,
+        hash: miniblock_hash,
+        l1_tx_count: 0,
+        l2_tx_count: 0,
+        base_fee_per_gas: 100,
+        l1_gas_price: 100,
+        l2_fair_gas_price: 100,
+        fair_pubdata_price: None,
+        base_system_contracts_hashes,
+        protocol_version: Some(ProtocolVersionId::latest()),
+        virtual_blocks: 1,
+    };
+    let l1_batch_header = L1BatchHeader::new(
+        L1BatchNumber(number),
+        timestamp,
+        Address::zero(),
+        base_system_contracts_hashes,
+        ProtocolVersionId::latest(),
+    );
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let mut blocks_dal = storage.blocks_dal();
+    blocks_dal
+        .insert_miniblock(&miniblock_header)
+        .await
+        .unwrap();
+    blocks_dal
+        .insert_l1_batch(&l1_batch_header, &[], BlockGasCount::default())
+        .await
+        .unwrap();
+    blocks_dal
+        .mark_miniblocks_as_executed_in_l1_batch(L1BatchNumber(number))
+        .await
+        .unwrap();
+    miniblock_hash
+}
+
+async fn revert_l1_batches(pool: &ConnectionPool, last_retained: u32) {
+    let mut storage = pool.access_storage().await.unwrap();
+    let mut blocks_dal = storage.blocks_dal();
+    blocks_dal
+        .delete_l1_batches(L1BatchNumber(last_retained))
+        .await
+        .unwrap();
+    blocks_dal
+        .delete_miniblocks(MiniblockNumber(last_retained))
+        .await
+        .unwrap();
+}
+
+async fn load_statuses(pool: &ConnectionPool, cursor: Option<&WebhookCursor>) -> L1BatchStatuses {
+    let mut storage = pool.access_storage().await.unwrap();
+    L1BatchStatuses::load(&mut storage, cursor).await.unwrap()
+}
+
+#[db_test]
+async fn detecting_revert_with_reseal_in_storage(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    for number in 1..=3 {
+        seal_l1_batch(&pool, number, number.into()).await;
+    }
+    let mut cursor = load_statuses(&pool, None).await.initial_cursor();
+    assert_eq!(cursor.sealed_l1_batches.len(), 4);
+    assert_eq!(cursor.sealed_l1_batches[0].0, L1BatchNumber(0));
+
+    revert_l1_batches(&pool, 1).await;
+    let mut new_hashes = vec![];
+    for number in 2..=4 {
+        new_hashes.push(seal_l1_batch(&pool, number, u64::from(number) + 100).await);
+    }
+
+    let statuses = load_statuses(&pool, Some(&cursor)).await;
+    assert_eq!(statuses.sealed, L1BatchNumber(4));
+    let events = statuses.events_since(&cursor);
+    assert_eq!(events.len(), 4, "{events:?}");
+    assert_eq!(events[0].event, WebhookEventKind::Reverted);
+    assert_eq!(events[0].reverted_stage, Some(WebhookEventKind::Sealed));
+    assert_eq!(events[0].l1_batch_number, 1);
+    for (event, (number, hash)) in events[1..].iter().zip((2..).zip(new_hashes)) {
+        assert_eq!(*event, WebhookEvent::sealed(L1BatchNumber(number), hash));
+    }
+    apply_events(&statuses, &mut cursor, &events);
+}
+
+#[db_test]
+async fn persisting_cursor(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    seal_l1_batch(&pool, 1, 1).await;
+    let cursor = load_statuses(&pool, None).await.initial_cursor();
+
+    let mut storage = pool.access_storage().await.unwrap();
+    let mut dal = storage.webhook_notifier_dal();
+    assert_eq!(dal.get_cursor("http://localhost/").await.unwrap(), None);
+    dal.save_cursor("http://localhost/", &cursor).await.unwrap();
+    assert_eq!(
+        dal.get_cursor("http://localhost/").await.unwrap(),
+        Some(cursor.clone())
+    );
+
+    let mut updated_cursor = cursor;
+    updated_cursor.sealed_l1_batches.remove(0);
+    updated_cursor.committed_l1_batch = Some(L1BatchNumber(1));
+    dal.save_cursor("http://localhost/", &updated_cursor)
+        .await
+        .unwrap();
+    assert_eq!(
+        dal.get_cursor("http://localhost/").await.unwrap(),
+        Some(updated_cursor)
+    );
+    assert_eq!(
+        dal.get_cursor("http://localhost/other").await.unwrap(),
+        None
+    );
+}
+
+type ReceivedEvents = Arc<Mutex<Vec<serde_json::Value>>>;
+
+/// Starts a webhook endpoint recording received events.
+fn start_endpoint() -> (String, ReceivedEvents) {
+    let events = ReceivedEvents::default();
+    let router = Router::new()
+        .route(
+            "/hook",
+            post(
+                |State(events): State<ReceivedEvents>, Json(event): Json<serde_json::Value>| async move {
+                    events.lock().unwrap().push(event);
+                },
+            ),
+        )
+        .with_state(events.clone());
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(router.into_make_service());
+    tokio::spawn(server);
+    (url, events)
+}
+
+fn mock_config(url: String) -> WebhookNotifierConfig {
+    WebhookNotifierConfig {
+        endpoints: vec![url],
+        signing_secret: "secret".to_owned(),
+        poll_interval_ms: 10,
+        request_timeout_ms: 5_000,
+        max_retries: 0,
+        initial_retry_backoff_ms: 10,
+    }
+}
+
+async fn wait_for_events(events: &ReceivedEvents, count: usize) -> Vec<serde_json::Value> {
+    loop {
+        {
+            let events = events.lock().unwrap();
+            if events.len() >= count {
+                return events.clone();
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[db_test]
+async fn delivering_events_across_restarts(pool: ConnectionPool) {
+    prepare_storage(&pool).await;
+    let (url, events) = start_endpoint();
+    let config = mock_config(url);
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let notifier = WebhookNotifier::new(&config, pool.clone()).unwrap();
+    let notifier_task = tokio::spawn(notifier.run(stop_receiver));
+    // Wait until the initial cursor is persisted, so that the genesis L1 batch isn't reported.
+    loop {
+        let mut storage = pool.access_storage().await.unwrap();
+        let cursor = storage
+            .webhook_notifier_dal()
+            .get_cursor(&config.endpoints[0])
+            .await
+            .unwrap();
+        if cursor.is_some() {
+            break;
+        }
+        drop(storage);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    seal_l1_batch(&pool, 1, 1).await;
+    let received = wait_for_events(&events, 1).await;
+    assert_eq!(
+        received,
+        [serde_json::json!({ "event": "sealed", "l1_batch_number": 1 })]
+    );
+    stop_sender.send_replace(true);
+    notifier_task.await.unwrap().unwrap();
+
+    // Transitions happening while the notifier is stopped must not be lost, including a revert
+    // of a previously reported L1 batch that is re-sealed.
+    seal_l1_batch(&pool, 2, 2).await;
+    revert_l1_batches(&pool, 0).await;
+    seal_l1_batch(&pool, 1, 101).await;
+
+    let (stop_sender, stop_receiver) = watch::channel(false);
+    let notifier = WebhookNotifier::new(&config, pool.clone()).unwrap();
+    let notifier_task = tokio::spawn(notifier.run(stop_receiver));
+    let received = wait_for_events(&events, 3).await;
+    assert_eq!(
+        received[1..],
+        [
+            serde_json::json!({
+                "event": "reverted",
+                "reverted_stage": "sealed",
+                "l1_batch_number": 0,
+            }),
+            serde_json::json!({ "event": "sealed", "l1_batch_number": 1 }),
+        ]
+    );
+    stop_sender.send_replace(true);
+    notifier_task.await.unwrap().unwrap();
+    assert_eq!(events.lock().unwrap().len(), 3);
+}
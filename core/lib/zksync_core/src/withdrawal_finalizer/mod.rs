@@ -0,0 +1,816 @@
+//! Withdrawal finalizer that watches executed L1 batches for withdrawals and automatically finalizes them on L1
+//! on behalf of their receivers.
+//!
+//! Withdrawals are recorded in Postgres once their L1 batch is executed, together with the Merkle proof
+//! of the corresponding L2->L1 message. Finalization transactions are sent from a dedicated L1 account;
+//! the gas used and the fee paid for each of them are persisted for accounting purposes.
+
+use anyhow::Context as _;
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+use std::{collections::HashSet, time::Duration};
+
+use zksync_config::{configs::WithdrawalFinalizerConfig, ContractsConfig};
+use zksync_contracts::{l1_bridge_contract, zksync_contract};
+use zksync_dal::{
+    withdrawal_finalizer_dal::{FinalizationTx, SentWithdrawal},
+    ConnectionPool, StorageProcessor,
+};
+use zksync_eth_client::BoundEthInterface;
+use zksync_mini_merkle_tree::MiniMerkleTree;
+use zksync_types::{
+    ethabi::{self, Contract, ParamType, Token},
+    l2_to_l1_log::L2ToL1Log,
+    web3::{contract::Options, signing::keccak256},
+    withdrawals::{Withdrawal, WithdrawalStatus},
+    Address, L1BatchNumber, H256, L1_MESSENGER_ADDRESS, L2_ETH_TOKEN_ADDRESS, U256,
+};
+use zksync_utils::h256_to_account_address;
+
+#[cfg(test)]
+mod tests;
+
+const COMPONENT: &str = "withdrawal_finalizer";
+/// Percentage by which fees of stuck finalization transactions are increased.
+const FEE_BUMP_PERCENT: u64 = 20;
+
+/// ETH withdrawal messages start with the selector of `finalizeEthWithdrawal` on the zkSync contract.
+static FINALIZE_ETH_WITHDRAWAL_SELECTOR: Lazy<[u8; 4]> =
+    Lazy::new(|| ethabi::short_signature("finalizeEthWithdrawal", &finalize_params()));
+/// ERC-20 withdrawal messages start with the selector of `finalizeWithdrawal` on the L1 bridge.
+static FINALIZE_WITHDRAWAL_SELECTOR: Lazy<[u8; 4]> =
+    Lazy::new(|| ethabi::short_signature("finalizeWithdrawal", &finalize_params()));
+
+/// Parameter types of `finalizeEthWithdrawal` and `finalizeWithdrawal` methods of the zkSync and L1 bridge contracts.
+fn finalize_params() -> [ParamType; 5] {
+    [
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(16),
+        ParamType::Bytes,
+        ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+    ]
+}
+
+/// Withdrawal parameters encoded in an L2->L1 message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct WithdrawalMessage {
+    pub l1_receiver: Address,
+    pub l1_token: Address,
+    pub amount: U256,
+}
+
+impl WithdrawalMessage {
+    /// Parses a message sent by `sender`. Returns `None` if the message does not correspond to a withdrawal.
+    pub fn parse(sender: Address, message: &[u8], l2_erc20_bridge_addr: Address) -> Option<Self> {
+        if sender == L2_ETH_TOKEN_ADDRESS {
+            // `abi.encodePacked(selector, l1Receiver, amount)`
+            let args = message.strip_prefix(FINALIZE_ETH_WITHDRAWAL_SELECTOR.as_slice())?;
+            (args.len() == 52).then(|| Self {
+                l1_receiver: Address::from_slice(&args[..20]),
+                l1_token: Address::zero(),
+                amount: U256::from_big_endian(&args[20..]),
+            })
+        } else if sender == l2_erc20_bridge_addr {
+            // `abi.encodePacked(selector, l1Receiver, l1Token, amount)`
+            let args = message.strip_prefix(FINALIZE_WITHDRAWAL_SELECTOR.as_slice())?;
+            (args.len() == 72).then(|| Self {
+                l1_receiver: Address::from_slice(&args[..20]),
+                l1_token: Address::from_slice(&args[20..40]),
+                amount: U256::from_big_endian(&args[40..]),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Withdrawal message sent via the L1 messenger, together with its L2->L1 log.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WithdrawalLogMessage<'a> {
+    /// Index of the log among all L2->L1 logs in the L1 batch.
+    pub l2_message_index: u32,
+    pub log: &'a L2ToL1Log,
+    pub raw_message: &'a [u8],
+    pub message: WithdrawalMessage,
+}
+
+/// Extracts withdrawal messages from L2->L1 logs and messages of an L1 batch. Messages are matched with
+/// the L1 messenger logs by order and checked against the hashes in these logs.
+pub(crate) fn withdrawal_messages<'a>(
+    l1_batch_number: L1BatchNumber,
+    l2_to_l1_logs: &'a [L2ToL1Log],
+    l2_to_l1_messages: &'a [Vec<u8>],
+    l2_erc20_bridge_addr: Address,
+) -> anyhow::Result<Vec<WithdrawalLogMessage<'a>>> {
+    let messenger_logs = l2_to_l1_logs
+        .iter()
+        .enumerate()
+        .filter(|(_, log)| log.sender == L1_MESSENGER_ADDRESS);
+    let messenger_log_count = messenger_logs.clone().count();
+    anyhow::ensure!(
+        messenger_log_count == l2_to_l1_messages.len(),
+        "number of L1 messenger logs ({messenger_log_count}) differs from the number of L2->L1 messages ({}) \
+         in L1 batch #{l1_batch_number}",
+        l2_to_l1_messages.len()
+    );
+
+    let mut withdrawals = vec![];
+    for ((index, log), message) in messenger_logs.zip(l2_to_l1_messages) {
+        anyhow::ensure!(
+            H256(keccak256(message)) == log.value,
+            "L2->L1 message does not match L2->L1 log #{index} in L1 batch #{l1_batch_number}"
+        );
+        let sender = h256_to_account_address(&log.key);
+        if let Some(parsed) = WithdrawalMessage::parse(sender, message, l2_erc20_bridge_addr) {
+            withdrawals.push(WithdrawalLogMessage {
+                l2_message_index: index as u32,
+                log,
+                raw_message: message,
+                message: parsed,
+            });
+        }
+    }
+    Ok(withdrawals)
+}
+
+/// Receivers and tokens for which withdrawals are finalized.
+#[derive(Debug, Clone, Default)]
+struct WithdrawalFilter {
+    receivers: Option<HashSet<Address>>,
+    tokens: Option<HashSet<Address>>,
+}
+
+impl WithdrawalFilter {
+    fn new(config: &WithdrawalFinalizerConfig) -> Self {
+        Self {
+            receivers: config
+                .receivers
+                .as_ref()
+                .map(|addresses| addresses.iter().copied().collect()),
+            tokens: config
+                .tokens
+                .as_ref()
+                .map(|addresses| addresses.iter().copied().collect()),
+        }
+    }
+
+    fn accepts(&self, message: &WithdrawalMessage) -> bool {
+        let receiver_matches = self
+            .receivers
+            .as_ref()
+            .map_or(true, |receivers| receivers.contains(&message.l1_receiver));
+        let token_matches = self
+            .tokens
+            .as_ref()
+            .map_or(true, |tokens| tokens.contains(&message.l1_token));
+        receiver_matches && token_matches
+    }
+}
+
+/// Data of an executed L1 batch necessary to extract withdrawals from it.
+#[derive(Debug)]
+struct L1BatchWithdrawalData {
+    number: L1BatchNumber,
+    l2_to_l1_logs: Vec<L2ToL1Log>,
+    /// Messages sent via the L1 messenger, in the order of their L2->L1 logs.
+    l2_to_l1_messages: Vec<Vec<u8>>,
+    /// L2 transaction hashes indexed by the transaction position in the L1 batch.
+    tx_hashes: Vec<(u16, H256)>,
+}
+
+impl L1BatchWithdrawalData {
+    fn extract_withdrawals(
+        &self,
+        filter: &WithdrawalFilter,
+        l2_erc20_bridge_addr: Address,
+    ) -> anyhow::Result<Vec<Withdrawal>> {
+        let messages = withdrawal_messages(
+            self.number,
+            &self.l2_to_l1_logs,
+            &self.l2_to_l1_messages,
+            l2_erc20_bridge_addr,
+        )?;
+
+        let mut tree_layers = None;
+        let mut withdrawals = vec![];
+        for WithdrawalLogMessage {
+            l2_message_index,
+            log,
+            raw_message,
+            message: parsed,
+        } in messages
+        {
+            if !filter.accepts(&parsed) {
+                continue;
+            }
+
+            let tree_layers = tree_layers.get_or_insert_with(|| {
+                let leaves = self.l2_to_l1_logs.iter().map(L2ToL1Log::to_bytes);
+                MiniMerkleTree::new(leaves, L2ToL1Log::LIMIT_PER_L1_BATCH).into_layers()
+            });
+            let (_, merkle_proof) = tree_layers.merkle_root_and_path(l2_message_index as usize);
+            let l2_tx_hash = self
+                .tx_hashes
+                .iter()
+                .find_map(|&(tx_index, hash)| (tx_index == log.tx_number_in_block).then_some(hash))
+                .with_context(|| {
+                    format!(
+                        "transaction #{} in L1 batch #{} is missing",
+                        log.tx_number_in_block, self.number
+                    )
+                })?;
+
+            withdrawals.push(Withdrawal {
+                l1_batch_number: self.number,
+                l2_message_index,
+                l2_tx_number_in_batch: log.tx_number_in_block,
+                l2_tx_hash,
+                l1_receiver: parsed.l1_receiver,
+                l1_token: parsed.l1_token,
+                amount: parsed.amount,
+                message: raw_message.to_vec(),
+                merkle_proof,
+            });
+        }
+        Ok(withdrawals)
+    }
+}
+
+/// Component finalizing withdrawals on L1.
+#[derive(Debug)]
+pub struct WithdrawalFinalizer<E> {
+    pool: ConnectionPool,
+    eth_client: E,
+    filter: WithdrawalFilter,
+    start_from_l1_batch: Option<L1BatchNumber>,
+    poll_interval: Duration,
+    max_l1_batches_per_iteration: u32,
+    max_txs_in_flight: usize,
+    finalize_tx_gas_limit: U256,
+    priority_fee_per_gas: u64,
+    max_fee_per_gas: u64,
+    stuck_tx_timeout_blocks: u64,
+    diamond_proxy_addr: Address,
+    zksync_contract: Contract,
+    l1_erc20_bridge_addr: Address,
+    l2_erc20_bridge_addr: Address,
+    l1_bridge_contract: Contract,
+}
+
+impl<E: BoundEthInterface> WithdrawalFinalizer<E> {
+    pub fn new(
+        config: &WithdrawalFinalizerConfig,
+        contracts_config: &ContractsConfig,
+        pool: ConnectionPool,
+        eth_client: E,
+    ) -> Self {
+        Self {
+            pool,
+            eth_client,
+            filter: WithdrawalFilter::new(config),
+            start_from_l1_batch: config.start_from_l1_batch.map(L1BatchNumber),
+            poll_interval: config.poll_interval(),
+            max_l1_batches_per_iteration: config.max_l1_batches_per_iteration,
+            max_txs_in_flight: config.max_txs_in_flight as usize,
+            finalize_tx_gas_limit: config.finalize_tx_gas_limit.into(),
+            priority_fee_per_gas: config.priority_fee_per_gas,
+            max_fee_per_gas: config.max_fee_per_gas,
+            stuck_tx_timeout_blocks: config.stuck_tx_timeout_blocks,
+            diamond_proxy_addr: contracts_config.diamond_proxy_addr,
+            zksync_contract: zksync_contract(),
+            l1_erc20_bridge_addr: contracts_config.l1_erc20_bridge_proxy_addr,
+            l2_erc20_bridge_addr: contracts_config.l2_erc20_bridge_addr,
+            l1_bridge_contract: l1_bridge_contract(),
+        }
+    }
+
+    pub async fn run(self, mut stop_receiver: watch::Receiver<bool>) -> anyhow::Result<()> {
+        tracing::info!(
+            "Starting withdrawal finalizer with account {:?}",
+            self.eth_client.sender_account()
+        );
+        while !*stop_receiver.borrow() {
+            if let Err(err) = self.iteration().await {
+                // Errors are most likely caused by L1 connectivity issues, so we just retry later.
+                tracing::warn!("Error in withdrawal finalizer: {err:#}");
+                metrics::increment_counter!("server.withdrawal_finalizer.errors");
+            }
+
+            let stop_signal =
+                tokio::time::timeout(self.poll_interval, stop_receiver.changed()).await;
+            if matches!(stop_signal, Ok(Err(_))) {
+                tracing::warn!(
+                    "Stop signal sender for withdrawal finalizer was dropped without sending a signal"
+                );
+                break;
+            }
+        }
+        tracing::info!("Stop signal received, withdrawal finalizer is shutting down");
+        Ok(())
+    }
+
+    async fn iteration(&self) -> anyhow::Result<()> {
+        self.scan_executed_l1_batches().await?;
+        self.resend_unsent_txs().await?;
+        let txs_in_flight = self.check_sent_withdrawals().await?;
+        self.send_finalizations(txs_in_flight).await?;
+
+        let balance = self.eth_client.sender_eth_balance(COMPONENT).await?;
+        metrics::gauge!(
+            "server.withdrawal_finalizer.balance_eth",
+            balance.as_u128() as f64 / 1e18
+        );
+        Ok(())
+    }
+
+    /// Records withdrawals from L1 batches executed since the previous iteration.
+    async fn scan_executed_l1_batches(&self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let Some(last_executed) = storage
+            .blocks_dal()
+            .get_number_of_last_l1_batch_executed_on_eth()
+            .await
+            .context("get_number_of_last_l1_batch_executed_on_eth()")?
+        else {
+            return Ok(());
+        };
+        let last_processed = storage
+            .withdrawal_finalizer_dal()
+            .get_last_processed_l1_batch()
+            .await
+            .context("get_last_processed_l1_batch()")?;
+        let last_processed = match (last_processed, self.start_from_l1_batch) {
+            (Some(number), _) => number,
+            (None, Some(start)) => start
+                .0
+                .checked_sub(1)
+                .map_or(L1BatchNumber(0), L1BatchNumber),
+            (None, None) => {
+                tracing::info!(
+                    "Withdrawal finalizer is launched for the first time; skipping withdrawals \
+                     from L1 batches up to #{last_executed}"
+                );
+                storage
+                    .withdrawal_finalizer_dal()
+                    .insert_withdrawals(last_executed, &[])
+                    .await
+                    .context("insert_withdrawals()")?;
+                return Ok(());
+            }
+        };
+
+        let to_batch = last_executed
+            .0
+            .min(last_processed.0 + self.max_l1_batches_per_iteration);
+        for number in (last_processed.0 + 1)..=to_batch {
+            let number = L1BatchNumber(number);
+            let l1_batch = storage
+                .blocks_dal()
+                .get_l1_batch_header(number)
+                .await
+                .context("get_l1_batch_header()")?
+                .with_context(|| format!("executed L1 batch #{number} is missing"))?;
+            let tx_hashes = storage
+                .transactions_dal()
+                .get_tx_locations(number)
+                .await
+                .into_iter()
+                .flat_map(|(_, txs)| txs)
+                .map(|(hash, _, index_in_l1_batch)| (index_in_l1_batch, hash))
+                .collect();
+            let data = L1BatchWithdrawalData {
+                number,
+                l2_to_l1_logs: l1_batch.l2_to_l1_logs,
+                l2_to_l1_messages: l1_batch.l2_to_l1_messages,
+                tx_hashes,
+            };
+            let withdrawals = data.extract_withdrawals(&self.filter, self.l2_erc20_bridge_addr)?;
+
+            tracing::debug!(
+                "Found {} withdrawals to finalize in L1 batch #{number}",
+                withdrawals.len()
+            );
+            metrics::counter!(
+                "server.withdrawal_finalizer.discovered",
+                withdrawals.len() as u64
+            );
+            storage
+                .withdrawal_finalizer_dal()
+                .insert_withdrawals(number, &withdrawals)
+                .await
+                .context("insert_withdrawals()")?;
+        }
+        Ok(())
+    }
+
+    /// Sends the latest finalization transactions of withdrawals that were persisted, but not sent to L1
+    /// (e.g., because the finalizer was restarted, or L1 was unavailable).
+    async fn resend_unsent_txs(&self) -> anyhow::Result<()> {
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let unsent_txs = storage
+            .withdrawal_finalizer_dal()
+            .get_unsent_finalization_txs()
+            .await
+            .context("get_unsent_finalization_txs()")?;
+        if unsent_txs.is_empty() {
+            return Ok(());
+        }
+
+        let block_number = self.eth_client.block_number(COMPONENT).await?.as_u64();
+        for (tx_hash, raw_tx) in unsent_txs {
+            tracing::info!("Resending finalization transaction {tx_hash:?}");
+            self.send_tx(&mut storage, tx_hash, raw_tx, block_number)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Checks the status of sent finalization transactions, bumps fees for stuck transactions
+    /// and returns the number of transactions still in flight.
+    async fn check_sent_withdrawals(&self) -> anyhow::Result<usize> {
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let sent_withdrawals = storage
+            .withdrawal_finalizer_dal()
+            .get_sent_withdrawals()
+            .await
+            .context("get_sent_withdrawals()")?;
+
+        let mut txs_in_flight = 0;
+        if !sent_withdrawals.is_empty() {
+            // The nonce must be fetched before checking transaction statuses; otherwise, a finalization transaction
+            // included into a block after its status was checked could be mistaken for a third-party transaction.
+            let l1_nonce = self.eth_client.current_nonce(COMPONENT).await?.as_u64();
+            let block_number = self.eth_client.block_number(COMPONENT).await?.as_u64();
+            for withdrawal in sent_withdrawals {
+                let is_in_flight = self
+                    .check_sent_withdrawal(&mut storage, &withdrawal, l1_nonce, block_number)
+                    .await?;
+                txs_in_flight += usize::from(is_in_flight);
+            }
+        }
+
+        metrics::gauge!(
+            "server.withdrawal_finalizer.txs_in_flight",
+            txs_in_flight as f64
+        );
+        Ok(txs_in_flight)
+    }
+
+    /// Returns `true` if the finalization transaction for the withdrawal is still in flight.
+    async fn check_sent_withdrawal(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        withdrawal: &SentWithdrawal,
+        l1_nonce: u64,
+        block_number: u64,
+    ) -> anyhow::Result<bool> {
+        let mut executed_status = None;
+        // All transactions have the same nonce, so at most one of them can be executed.
+        for &tx_hash in &withdrawal.tx_hashes {
+            executed_status = self.eth_client.get_tx_status(tx_hash, COMPONENT).await?;
+            if executed_status.is_some() {
+                break;
+            }
+        }
+
+        let Some(status) = executed_status else {
+            if withdrawal.nonce < l1_nonce {
+                // None of the finalization transactions can be executed anymore. The withdrawal can be safely
+                // finalized again; the contracts check that a withdrawal is not finalized twice anyway.
+                tracing::warn!(
+                    "Nonce {} of finalization transactions for withdrawal #{} in L1 batch #{} was used \
+                     by another transaction; the withdrawal will be finalized again",
+                    withdrawal.nonce,
+                    withdrawal.l2_message_index,
+                    withdrawal.l1_batch_number
+                );
+                storage
+                    .withdrawal_finalizer_dal()
+                    .set_withdrawal_outcome(
+                        withdrawal.l1_batch_number,
+                        withdrawal.l2_message_index,
+                        WithdrawalStatus::Pending,
+                        None,
+                        None,
+                    )
+                    .await
+                    .context("set_withdrawal_outcome()")?;
+                return Ok(false);
+            }
+
+            if block_number >= withdrawal.created_at_block + self.stuck_tx_timeout_blocks {
+                self.bump_fees(storage, withdrawal, block_number).await?;
+            }
+            return Ok(true);
+        };
+
+        let tx_hash = status.tx_hash;
+        let gas_used = status.receipt.gas_used.unwrap_or_default();
+        let fee_paid = gas_used * status.receipt.effective_gas_price.unwrap_or_default();
+        let outcome = if status.success {
+            WithdrawalStatus::Finalized
+        } else {
+            tracing::warn!(
+                "Finalization transaction {tx_hash:?} for withdrawal #{} in L1 batch #{} has failed",
+                withdrawal.l2_message_index,
+                withdrawal.l1_batch_number
+            );
+            WithdrawalStatus::Failed
+        };
+        metrics::increment_counter!(
+            "server.withdrawal_finalizer.finalized",
+            "outcome" => outcome.as_str()
+        );
+        storage
+            .withdrawal_finalizer_dal()
+            .set_withdrawal_outcome(
+                withdrawal.l1_batch_number,
+                withdrawal.l2_message_index,
+                outcome,
+                Some(gas_used.as_u64()),
+                Some(fee_paid),
+            )
+            .await
+            .context("set_withdrawal_outcome()")?;
+        Ok(false)
+    }
+
+    /// Replaces a stuck finalization transaction with a transaction with the same nonce and bumped fees.
+    async fn bump_fees(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        sent_withdrawal: &SentWithdrawal,
+        block_number: u64,
+    ) -> anyhow::Result<()> {
+        let max_priority_fee_per_gas = bump_fee(sent_withdrawal.max_priority_fee_per_gas);
+        let base_fee_per_gas = self.pending_base_fee_per_gas().await?;
+        let max_fee_per_gas = bump_fee(sent_withdrawal.max_fee_per_gas).max(
+            base_fee_per_gas
+                .saturating_mul(2)
+                .saturating_add(max_priority_fee_per_gas),
+        );
+        if max_fee_per_gas > self.max_fee_per_gas {
+            tracing::warn!(
+                "Finalization transaction {:?} for withdrawal #{} in L1 batch #{} is stuck, but its fees \
+                 cannot be bumped: max fee per gas {max_fee_per_gas} exceeds the limit {}",
+                sent_withdrawal.finalize_tx_hash,
+                sent_withdrawal.l2_message_index,
+                sent_withdrawal.l1_batch_number,
+                self.max_fee_per_gas
+            );
+            return Ok(());
+        }
+
+        let withdrawal = storage
+            .withdrawal_finalizer_dal()
+            .get_withdrawal(
+                sent_withdrawal.l1_batch_number,
+                sent_withdrawal.l2_message_index,
+            )
+            .await
+            .context("get_withdrawal()")?
+            .with_context(|| {
+                format!(
+                    "withdrawal #{} in L1 batch #{} is missing",
+                    sent_withdrawal.l2_message_index, sent_withdrawal.l1_batch_number
+                )
+            })?;
+        tracing::info!(
+            "Finalization transaction {:?} for withdrawal #{} in L1 batch #{} is stuck; bumping fees",
+            sent_withdrawal.finalize_tx_hash,
+            withdrawal.l2_message_index,
+            withdrawal.l1_batch_number
+        );
+        metrics::increment_counter!("server.withdrawal_finalizer.fee_bumps");
+        let fees = TxFees {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        };
+        self.sign_and_send(
+            storage,
+            &withdrawal,
+            sent_withdrawal.nonce,
+            fees,
+            block_number,
+        )
+        .await
+    }
+
+    async fn send_finalizations(&self, txs_in_flight: usize) -> anyhow::Result<()> {
+        let limit = self.max_txs_in_flight.saturating_sub(txs_in_flight);
+        if limit == 0 {
+            return Ok(());
+        }
+        let mut storage = self.pool.access_storage_tagged(COMPONENT).await?;
+        let pending_withdrawals = storage
+            .withdrawal_finalizer_dal()
+            .get_pending_withdrawals(limit)
+            .await
+            .context("get_pending_withdrawals()")?;
+        if pending_withdrawals.is_empty() {
+            return Ok(());
+        }
+
+        let block_number = self.eth_client.block_number(COMPONENT).await?.as_u64();
+        let base_fee_per_gas = self.pending_base_fee_per_gas().await?;
+        let max_fee_per_gas = base_fee_per_gas
+            .saturating_mul(2)
+            .saturating_add(self.priority_fee_per_gas)
+            .min(self.max_fee_per_gas)
+            .max(self.priority_fee_per_gas);
+        let fees = TxFees {
+            max_fee_per_gas,
+            max_priority_fee_per_gas: self.priority_fee_per_gas,
+        };
+        let mut next_nonce = None;
+
+        for withdrawal in pending_withdrawals {
+            let (contract_addr, contract) = self.finalization_contract(&withdrawal);
+            let is_finalized_fn = if withdrawal.l1_token == Address::zero() {
+                "isEthWithdrawalFinalized"
+            } else {
+                "isWithdrawalFinalized"
+            };
+            let is_finalized: bool = self
+                .eth_client
+                .call_contract_function(
+                    is_finalized_fn,
+                    (
+                        U256::from(withdrawal.l1_batch_number.0),
+                        U256::from(withdrawal.l2_message_index),
+                    ),
+                    None,
+                    Options::default(),
+                    None,
+                    contract_addr,
+                    contract.clone(),
+                )
+                .await?;
+            if is_finalized {
+                tracing::info!(
+                    "Withdrawal #{} in L1 batch #{} is already finalized",
+                    withdrawal.l2_message_index,
+                    withdrawal.l1_batch_number
+                );
+                storage
+                    .withdrawal_finalizer_dal()
+                    .set_withdrawal_outcome(
+                        withdrawal.l1_batch_number,
+                        withdrawal.l2_message_index,
+                        WithdrawalStatus::FinalizedExternally,
+                        None,
+                        None,
+                    )
+                    .await
+                    .context("set_withdrawal_outcome()")?;
+                continue;
+            }
+
+            let nonce = match next_nonce {
+                Some(nonce) => nonce,
+                None => self.next_nonce(&mut storage).await?,
+            };
+            next_nonce = Some(nonce + 1);
+            self.sign_and_send(&mut storage, &withdrawal, nonce, fees, block_number)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the nonce for the next finalization transaction. Nonces of transactions persisted in Postgres
+    /// take precedence, since some of these transactions may be not sent to L1 yet.
+    async fn next_nonce(&self, storage: &mut StorageProcessor<'_>) -> anyhow::Result<u64> {
+        let stored_nonce = storage
+            .withdrawal_finalizer_dal()
+            .get_next_nonce()
+            .await
+            .context("get_next_nonce()")?;
+        let l1_nonce = self.eth_client.pending_nonce(COMPONENT).await?.as_u64();
+        Ok(stored_nonce.map_or(l1_nonce, |nonce| nonce.max(l1_nonce)))
+    }
+
+    async fn pending_base_fee_per_gas(&self) -> anyhow::Result<u64> {
+        let base_fee_per_gas = self
+            .eth_client
+            .get_pending_block_base_fee_per_gas(COMPONENT)
+            .await?;
+        // Base fee can theoretically exceed `u64`; saturate it rather than panic.
+        Ok(base_fee_per_gas.try_into().unwrap_or(u64::MAX))
+    }
+
+    fn finalization_contract(&self, withdrawal: &Withdrawal) -> (Address, &Contract) {
+        if withdrawal.l1_token == Address::zero() {
+            (self.diamond_proxy_addr, &self.zksync_contract)
+        } else {
+            (self.l1_erc20_bridge_addr, &self.l1_bridge_contract)
+        }
+    }
+
+    /// Signs a finalization transaction for the withdrawal, persists it and sends it to L1. The transaction
+    /// is persisted before sending, so that a crash or an error after sending doesn't lead to a transaction
+    /// with another nonce being sent for the same withdrawal.
+    async fn sign_and_send(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        withdrawal: &Withdrawal,
+        nonce: u64,
+        fees: TxFees,
+        block_number: u64,
+    ) -> anyhow::Result<()> {
+        let (contract_addr, contract) = self.finalization_contract(withdrawal);
+        let finalize_fn = if withdrawal.l1_token == Address::zero() {
+            "finalizeEthWithdrawal"
+        } else {
+            "finalizeWithdrawal"
+        };
+        let data = contract
+            .function(finalize_fn)
+            .and_then(|function| function.encode_input(&finalize_args(withdrawal)))
+            .with_context(|| format!("failed encoding `{finalize_fn}` call"))?;
+        let options = Options {
+            gas: Some(self.finalize_tx_gas_limit),
+            nonce: Some(nonce.into()),
+            max_fee_per_gas: Some(fees.max_fee_per_gas.into()),
+            max_priority_fee_per_gas: Some(fees.max_priority_fee_per_gas.into()),
+            ..Options::default()
+        };
+        let signed_tx = self
+            .eth_client
+            .sign_prepared_tx_for_addr(data, contract_addr, options, COMPONENT)
+            .await?;
+
+        let tx = FinalizationTx {
+            hash: signed_tx.hash,
+            nonce,
+            raw_tx: signed_tx.raw_tx,
+            max_fee_per_gas: fees.max_fee_per_gas,
+            max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+            created_at_block: block_number,
+        };
+        storage
+            .withdrawal_finalizer_dal()
+            .save_finalization_tx(withdrawal.l1_batch_number, withdrawal.l2_message_index, &tx)
+            .await
+            .context("save_finalization_tx()")?;
+        tracing::info!(
+            "Sending finalization transaction {:?} with nonce {nonce} for withdrawal #{} in L1 batch #{}",
+            tx.hash,
+            withdrawal.l2_message_index,
+            withdrawal.l1_batch_number
+        );
+        self.send_tx(storage, tx.hash, tx.raw_tx, block_number)
+            .await
+    }
+
+    async fn send_tx(
+        &self,
+        storage: &mut StorageProcessor<'_>,
+        tx_hash: H256,
+        raw_tx: Vec<u8>,
+        block_number: u64,
+    ) -> anyhow::Result<()> {
+        if let Err(err) = self.eth_client.send_raw_tx(raw_tx).await {
+            // The transaction is persisted, so it will be resent on the next iteration.
+            tracing::warn!("Error sending finalization transaction {tx_hash:?}: {err}");
+            metrics::increment_counter!("server.withdrawal_finalizer.send_errors");
+            return Ok(());
+        }
+        storage
+            .withdrawal_finalizer_dal()
+            .mark_finalization_tx_as_sent(tx_hash, block_number)
+            .await
+            .context("mark_finalization_tx_as_sent()")?;
+        Ok(())
+    }
+}
+
+/// Fees for a finalization transaction, in wei.
+#[derive(Debug, Clone, Copy)]
+struct TxFees {
+    max_fee_per_gas: u64,
+    max_priority_fee_per_gas: u64,
+}
+
+/// Increases a fee of a stuck transaction. Ethereum nodes require fees to be increased by at least 10%
+/// to replace a pending transaction.
+fn bump_fee(fee: u64) -> u64 {
+    let bumped_fee = fee.saturating_add(fee.saturating_mul(FEE_BUMP_PERCENT) / 100);
+    bumped_fee.max(fee.saturating_add(1))
+}
+
+fn finalize_args(withdrawal: &Withdrawal) -> [Token; 5] {
+    let merkle_proof = withdrawal
+        .merkle_proof
+        .iter()
+        .map(|hash| Token::FixedBytes(hash.as_bytes().to_vec()))
+        .collect();
+    [
+        Token::Uint(withdrawal.l1_batch_number.0.into()),
+        Token::Uint(withdrawal.l2_message_index.into()),
+        Token::Uint(withdrawal.l2_tx_number_in_batch.into()),
+        Token::Bytes(withdrawal.message.clone()),
+        Token::Array(merkle_proof),
+    ]
+}
@@ -0,0 +1,368 @@
+use db_test_macro::db_test;
+
+use std::sync::{atomic::Ordering, Arc};
+
+use zksync_eth_client::clients::mock::MockEthereum;
+use zksync_utils::address_to_h256;
+
+use super::*;
+
+const L2_ERC20_BRIDGE_ADDR: Address = Address::repeat_byte(0xbb);
+
+fn eth_withdrawal_message(receiver: Address, amount: u64) -> Vec<u8> {
+    let mut message = FINALIZE_ETH_WITHDRAWAL_SELECTOR.to_vec();
+    message.extend_from_slice(receiver.as_bytes());
+    message.extend_from_slice(&<[u8; 32]>::from(U256::from(amount)));
+    message
+}
+
+fn erc20_withdrawal_message(receiver: Address, token: Address, amount: u64) -> Vec<u8> {
+    let mut message = FINALIZE_WITHDRAWAL_SELECTOR.to_vec();
+    message.extend_from_slice(receiver.as_bytes());
+    message.extend_from_slice(token.as_bytes());
+    message.extend_from_slice(&<[u8; 32]>::from(U256::from(amount)));
+    message
+}
+
+fn messenger_log(tx_number_in_block: u16, sender: Address, message: &[u8]) -> L2ToL1Log {
+    L2ToL1Log {
+        shard_id: 0,
+        is_service: true,
+        tx_number_in_block,
+        sender: L1_MESSENGER_ADDRESS,
+        key: address_to_h256(&sender),
+        value: H256(keccak256(message)),
+    }
+}
+
+#[test]
+fn parsing_withdrawal_messages() {
+    let receiver = Address::repeat_byte(1);
+    let token = Address::repeat_byte(2);
+
+    let message = eth_withdrawal_message(receiver, 1_000);
+    let parsed = WithdrawalMessage::parse(L2_ETH_TOKEN_ADDRESS, &message, L2_ERC20_BRIDGE_ADDR);
+    assert_eq!(
+        parsed.unwrap(),
+        WithdrawalMessage {
+            l1_receiver: receiver,
+            l1_token: Address::zero(),
+            amount: 1_000.into(),
+        }
+    );
+    // Messages from other senders are not withdrawals.
+    let parsed = WithdrawalMessage::parse(Address::repeat_byte(3), &message, L2_ERC20_BRIDGE_ADDR);
+    assert!(parsed.is_none());
+
+    let message = erc20_withdrawal_message(receiver, token, 500);
+    let parsed = WithdrawalMessage::parse(L2_ERC20_BRIDGE_ADDR, &message, L2_ERC20_BRIDGE_ADDR);
+    assert_eq!(
+        parsed.unwrap(),
+        WithdrawalMessage {
+            l1_receiver: receiver,
+            l1_token: token,
+            amount: 500.into(),
+        }
+    );
+    let parsed = WithdrawalMessage::parse(L2_ETH_TOKEN_ADDRESS, &message, L2_ERC20_BRIDGE_ADDR);
+    assert!(parsed.is_none());
+    let parsed = WithdrawalMessage::parse(
+        L2_ERC20_BRIDGE_ADDR,
+        &message[..message.len() - 1],
+        L2_ERC20_BRIDGE_ADDR,
+    );
+    assert!(parsed.is_none());
+}
+
+#[test]
+fn extracting_withdrawals_from_l1_batch() {
+    let receiver = Address::repeat_byte(1);
+    let other_receiver = Address::repeat_byte(2);
+    let token = Address::repeat_byte(3);
+    let messages = vec![
+        eth_withdrawal_message(receiver, 1_000),
+        b"arbitrary message".to_vec(),
+        erc20_withdrawal_message(other_receiver, token, 500),
+        erc20_withdrawal_message(receiver, token, 100),
+    ];
+    let system_log = L2ToL1Log {
+        sender: Address::repeat_byte(0x80),
+        ..L2ToL1Log::default()
+    };
+    let l2_to_l1_logs = vec![
+        system_log,
+        messenger_log(0, L2_ETH_TOKEN_ADDRESS, &messages[0]),
+        messenger_log(1, Address::repeat_byte(0x10), &messages[1]),
+        messenger_log(1, L2_ERC20_BRIDGE_ADDR, &messages[2]),
+        messenger_log(2, L2_ERC20_BRIDGE_ADDR, &messages[3]),
+    ];
+    let tx_hashes = (0..3).map(|i| (i, H256::repeat_byte(i as u8))).collect();
+    let data = L1BatchWithdrawalData {
+        number: L1BatchNumber(1),
+        l2_to_l1_logs: l2_to_l1_logs.clone(),
+        l2_to_l1_messages: messages.clone(),
+        tx_hashes,
+    };
+
+    let withdrawals = data
+        .extract_withdrawals(&WithdrawalFilter::default(), L2_ERC20_BRIDGE_ADDR)
+        .unwrap();
+    let indices: Vec<_> = withdrawals.iter().map(|w| w.l2_message_index).collect();
+    assert_eq!(indices, [1, 3, 4]);
+    assert_eq!(withdrawals[1].l2_tx_hash, H256::repeat_byte(1));
+    assert_eq!(withdrawals[1].message, messages[2]);
+    let leaves = l2_to_l1_logs.iter().map(L2ToL1Log::to_bytes);
+    let (_, expected_proof) =
+        MiniMerkleTree::new(leaves, L2ToL1Log::LIMIT_PER_L1_BATCH).merkle_root_and_path(3);
+    assert_eq!(withdrawals[1].merkle_proof, expected_proof);
+
+    let filter = WithdrawalFilter {
+        receivers: Some(HashSet::from([receiver])),
+        tokens: Some(HashSet::from([token])),
+    };
+    let withdrawals = data
+        .extract_withdrawals(&filter, L2_ERC20_BRIDGE_ADDR)
+        .unwrap();
+    assert_eq!(withdrawals.len(), 1);
+    assert_eq!(withdrawals[0].l2_message_index, 4);
+    assert_eq!(withdrawals[0].amount, 100.into());
+
+    let encoded_args = finalize_args(&withdrawals[0]);
+    let data = ethabi::encode(&encoded_args);
+    assert_eq!(
+        ethabi::decode(&finalize_params(), &data).unwrap(),
+        encoded_args
+    );
+
+    // Logs inconsistent with messages should be detected.
+    let mut inconsistent_data = L1BatchWithdrawalData {
+        number: L1BatchNumber(1),
+        l2_to_l1_logs,
+        l2_to_l1_messages: messages,
+        tx_hashes: vec![],
+    };
+    inconsistent_data.l2_to_l1_messages.swap(0, 1);
+    inconsistent_data
+        .extract_withdrawals(&WithdrawalFilter::default(), L2_ERC20_BRIDGE_ADDR)
+        .unwrap_err();
+}
+
+const STUCK_TX_TIMEOUT_BLOCKS: u64 = 10;
+
+fn mock_finalizer(
+    pool: ConnectionPool,
+) -> (WithdrawalFinalizer<Arc<MockEthereum>>, Arc<MockEthereum>) {
+    let eth_client = Arc::new(MockEthereum::default().with_fee_history(vec![10]));
+    for function in ["isEthWithdrawalFinalized", "isWithdrawalFinalized"] {
+        eth_client
+            .contract_call_results
+            .write()
+            .unwrap()
+            .insert(function.to_owned(), Token::Bool(false));
+    }
+    let config = WithdrawalFinalizerConfig {
+        receivers: None,
+        tokens: None,
+        start_from_l1_batch: None,
+        poll_interval_ms: 10,
+        max_l1_batches_per_iteration: 10,
+        max_txs_in_flight: 10,
+        finalize_tx_gas_limit: 500_000,
+        priority_fee_per_gas: 1,
+        stuck_tx_timeout_blocks: STUCK_TX_TIMEOUT_BLOCKS,
+        max_fee_per_gas: 1_000,
+    };
+    let contracts_config = ContractsConfig::from_env().unwrap();
+    let finalizer = WithdrawalFinalizer::new(&config, &contracts_config, pool, eth_client.clone());
+    (finalizer, eth_client)
+}
+
+fn mock_withdrawal(l2_message_index: u32) -> Withdrawal {
+    let receiver = Address::repeat_byte(1);
+    Withdrawal {
+        l1_batch_number: L1BatchNumber(1),
+        l2_message_index,
+        l2_tx_number_in_batch: l2_message_index as u16,
+        l2_tx_hash: H256::repeat_byte(l2_message_index as u8),
+        l1_receiver: receiver,
+        l1_token: Address::zero(),
+        amount: 1_000.into(),
+        message: eth_withdrawal_message(receiver, 1_000),
+        merkle_proof: vec![H256::zero(); 3],
+    }
+}
+
+#[db_test]
+async fn sending_and_checking_finalization_txs(pool: ConnectionPool) {
+    let (finalizer, eth_client) = mock_finalizer(pool.clone());
+    let withdrawals: Vec<_> = (0..2).map(mock_withdrawal).collect();
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .withdrawal_finalizer_dal()
+        .insert_withdrawals(L1BatchNumber(1), &withdrawals)
+        .await
+        .unwrap();
+
+    finalizer.send_finalizations(0).await.unwrap();
+    let sent_withdrawals = storage
+        .withdrawal_finalizer_dal()
+        .get_sent_withdrawals()
+        .await
+        .unwrap();
+    assert_eq!(sent_withdrawals.len(), 2);
+    let nonces: Vec<_> = sent_withdrawals.iter().map(|w| w.nonce).collect();
+    assert_eq!(nonces, [0, 1]);
+    for withdrawal in &sent_withdrawals {
+        assert_eq!(withdrawal.max_fee_per_gas, 21); // 2 * base fee + priority fee
+        assert_eq!(withdrawal.max_priority_fee_per_gas, 1);
+        assert!(eth_client
+            .sent_txs
+            .read()
+            .unwrap()
+            .contains_key(&withdrawal.finalize_tx_hash));
+    }
+    let unsent_txs = storage
+        .withdrawal_finalizer_dal()
+        .get_unsent_finalization_txs()
+        .await
+        .unwrap();
+    assert!(unsent_txs.is_empty());
+
+    // Both transactions are in flight, so no transactions are resent.
+    let txs_in_flight = finalizer.check_sent_withdrawals().await.unwrap();
+    assert_eq!(txs_in_flight, 2);
+    assert_eq!(eth_client.sent_txs.read().unwrap().len(), 2);
+
+    eth_client
+        .execute_tx(sent_withdrawals[0].finalize_tx_hash, true, 1)
+        .unwrap();
+    let txs_in_flight = finalizer.check_sent_withdrawals().await.unwrap();
+    assert_eq!(txs_in_flight, 1);
+
+    // The second transaction gets stuck, so its fees should be bumped.
+    eth_client.advance_block_number(STUCK_TX_TIMEOUT_BLOCKS);
+    let txs_in_flight = finalizer.check_sent_withdrawals().await.unwrap();
+    assert_eq!(txs_in_flight, 1);
+    let sent_withdrawals = storage
+        .withdrawal_finalizer_dal()
+        .get_sent_withdrawals()
+        .await
+        .unwrap();
+    assert_eq!(sent_withdrawals.len(), 1);
+    let bumped = &sent_withdrawals[0];
+    assert_eq!(bumped.l2_message_index, 1);
+    assert_eq!(bumped.nonce, 1);
+    assert_eq!(bumped.tx_hashes.len(), 2);
+    assert_eq!(bumped.tx_hashes[0], bumped.finalize_tx_hash);
+    assert_eq!(bumped.max_priority_fee_per_gas, 2);
+    assert_eq!(bumped.max_fee_per_gas, 25);
+    assert!(eth_client
+        .sent_txs
+        .read()
+        .unwrap()
+        .contains_key(&bumped.finalize_tx_hash));
+
+    // The original transaction is executed; this should be detected as well.
+    let original_tx_hash = bumped.tx_hashes[1];
+    eth_client.execute_tx(original_tx_hash, true, 1).unwrap();
+    let txs_in_flight = finalizer.check_sent_withdrawals().await.unwrap();
+    assert_eq!(txs_in_flight, 0);
+    assert!(storage
+        .withdrawal_finalizer_dal()
+        .get_sent_withdrawals()
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(storage
+        .withdrawal_finalizer_dal()
+        .get_pending_withdrawals(10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[db_test]
+async fn resending_persisted_finalization_tx(pool: ConnectionPool) {
+    let (finalizer, eth_client) = mock_finalizer(pool.clone());
+    let withdrawal = mock_withdrawal(0);
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .withdrawal_finalizer_dal()
+        .insert_withdrawals(L1BatchNumber(1), &[withdrawal.clone()])
+        .await
+        .unwrap();
+
+    // Emulate the finalizer crashing after persisting the transaction, but before sending it.
+    let options = Options {
+        nonce: Some(0.into()),
+        ..Options::default()
+    };
+    let signed_tx = eth_client.sign_prepared_tx(vec![1, 2, 3], options).unwrap();
+    let tx = FinalizationTx {
+        hash: signed_tx.hash,
+        nonce: 0,
+        raw_tx: signed_tx.raw_tx,
+        max_fee_per_gas: 21,
+        max_priority_fee_per_gas: 1,
+        created_at_block: 0,
+    };
+    storage
+        .withdrawal_finalizer_dal()
+        .save_finalization_tx(L1BatchNumber(1), 0, &tx)
+        .await
+        .unwrap();
+
+    finalizer.resend_unsent_txs().await.unwrap();
+    assert!(eth_client.sent_txs.read().unwrap().contains_key(&tx.hash));
+    let unsent_txs = storage
+        .withdrawal_finalizer_dal()
+        .get_unsent_finalization_txs()
+        .await
+        .unwrap();
+    assert!(unsent_txs.is_empty());
+    // The withdrawal is not finalized with another nonce.
+    assert_eq!(finalizer.check_sent_withdrawals().await.unwrap(), 1);
+    finalizer.send_finalizations(1).await.unwrap();
+    assert_eq!(eth_client.sent_txs.read().unwrap().len(), 1);
+    assert_eq!(finalizer.next_nonce(&mut storage).await.unwrap(), 1);
+}
+
+#[db_test]
+async fn withdrawal_with_nonce_used_by_other_tx_is_finalized_again(pool: ConnectionPool) {
+    let (finalizer, eth_client) = mock_finalizer(pool.clone());
+    let mut storage = pool.access_storage().await.unwrap();
+    storage
+        .withdrawal_finalizer_dal()
+        .insert_withdrawals(L1BatchNumber(1), &[mock_withdrawal(0)])
+        .await
+        .unwrap();
+    finalizer.send_finalizations(0).await.unwrap();
+
+    // Emulate a third-party transaction with the same nonce being executed.
+    eth_client.current_nonce.fetch_add(1, Ordering::SeqCst);
+    let txs_in_flight = finalizer.check_sent_withdrawals().await.unwrap();
+    assert_eq!(txs_in_flight, 0);
+    let pending_withdrawals = storage
+        .withdrawal_finalizer_dal()
+        .get_pending_withdrawals(10)
+        .await
+        .unwrap();
+    assert_eq!(pending_withdrawals, [mock_withdrawal(0)]);
+
+    finalizer.send_finalizations(0).await.unwrap();
+    let sent_withdrawals = storage
+        .withdrawal_finalizer_dal()
+        .get_sent_withdrawals()
+        .await
+        .unwrap();
+    assert_eq!(sent_withdrawals.len(), 1);
+    assert_eq!(sent_withdrawals[0].nonce, 1);
+}
+
+#[test]
+fn bumping_fees() {
+    assert_eq!(bump_fee(0), 1);
+    assert_eq!(bump_fee(1), 2);
+    assert_eq!(bump_fee(100), 120);
+    assert_eq!(bump_fee(u64::MAX), u64::MAX);
+}
@@ -0,0 +1,103 @@
+/**
+ * This suite contains tests for the developer mode (`CHAIN_STATE_KEEPER_DEV_MODE=true`): account impersonation,
+ * balance / bytecode overrides, state snapshots and instant finalization of sealed L1 batches.
+ */
+import { TestMaster } from '../../src';
+import * as zksync from 'zksync-web3';
+import { ethers } from 'ethers';
+import { waitUntilBlockFinalized } from '../../src/helpers';
+
+describe('Developer mode tests', () => {
+    let testMaster: TestMaster;
+    let alice: zksync.Wallet;
+    let provider: zksync.Provider;
+
+    if (process.env.CHAIN_STATE_KEEPER_DEV_MODE != 'true') {
+        test('Developer mode is not enabled', () => {
+            return;
+        });
+    } else {
+        beforeAll(() => {
+            testMaster = TestMaster.getInstance(__filename);
+            alice = testMaster.mainAccount();
+            provider = alice.provider;
+        });
+
+        test('Should increase and decrease balances', async () => {
+            const account = testMaster.newEmptyAccount().address;
+
+            const increased = ethers.utils.parseEther('10');
+            await provider.send('hardhat_setBalance', [account, ethers.utils.hexValue(increased)]);
+            expect(await provider.getBalance(account)).bnToBeEq(increased);
+
+            const decreased = ethers.utils.parseEther('1');
+            await provider.send('hardhat_setBalance', [account, ethers.utils.hexValue(decreased)]);
+            expect(await provider.getBalance(account)).bnToBeEq(decreased);
+
+            await provider.send('hardhat_setBalance', [account, '0x0']);
+            expect(await provider.getBalance(account)).bnToBeEq(0);
+        });
+
+        test('Should send value from impersonated accounts', async () => {
+            const sender = testMaster.newEmptyAccount().address;
+            const recipient = testMaster.newEmptyAccount().address;
+            const value = ethers.utils.parseEther('1');
+            await provider.send('hardhat_setBalance', [sender, ethers.utils.hexValue(value.mul(2))]);
+
+            const request = { from: sender, to: recipient, value: ethers.utils.hexValue(value) };
+            await expect(provider.send('eth_sendTransaction', [request])).rejects.toThrow('is not impersonated');
+
+            await provider.send('hardhat_impersonateAccount', [sender]);
+            const txHash = await provider.send('eth_sendTransaction', [request]);
+            const receipt = await provider.getTransactionReceipt(txHash);
+            expect(receipt.status).toEqual(1);
+            expect(await provider.getBalance(recipient)).bnToBeEq(value);
+            expect(await provider.getBalance(sender)).bnToBeEq(value);
+
+            await provider.send('hardhat_stopImpersonatingAccount', [sender]);
+            await expect(provider.send('eth_sendTransaction', [request])).rejects.toThrow('is not impersonated');
+        });
+
+        test('Should seal each transaction in a separate finalized L1 batch', async () => {
+            const firstReceipt = await (await alice.transfer({ to: alice.address, amount: 1 })).wait();
+            const secondReceipt = await (await alice.transfer({ to: alice.address, amount: 1 })).wait();
+            expect(secondReceipt.l1BatchNumber).toBeGreaterThan(firstReceipt.l1BatchNumber);
+
+            await waitUntilBlockFinalized(alice, secondReceipt.blockNumber);
+            const details = await provider.getL1BatchDetails(secondReceipt.l1BatchNumber);
+            expect(details.commitTxHash).toBeDefined();
+            expect(details.proveTxHash).toBeDefined();
+            expect(details.executeTxHash).toBeDefined();
+        });
+
+        test('Should revert to snapshots', async () => {
+            const account = testMaster.newEmptyAccount().address;
+            const initialBalance = ethers.utils.parseEther('1');
+            await provider.send('hardhat_setBalance', [account, ethers.utils.hexValue(initialBalance)]);
+
+            const snapshotId = await provider.send('evm_snapshot', []);
+            const snapshotBlock = await provider.getBlockNumber();
+            const laterSnapshotId = await provider.send('evm_snapshot', []);
+            await provider.send('hardhat_setBalance', [account, ethers.utils.hexValue(initialBalance.mul(3))]);
+            // Read the overridden balance, so that it gets into the API server caches.
+            expect(await provider.getBalance(account)).bnToBeEq(initialBalance.mul(3));
+            expect(await provider.getBlockNumber()).toBeGreaterThan(snapshotBlock);
+
+            await expect(provider.send('evm_revert', [snapshotId])).resolves.toEqual(true);
+            expect(await provider.getBlockNumber()).toEqual(snapshotBlock);
+            expect(await provider.getBalance(account)).bnToBeEq(initialBalance);
+            // The snapshot is discarded together with all later snapshots.
+            await expect(provider.send('evm_revert', [snapshotId])).resolves.toEqual(false);
+            await expect(provider.send('evm_revert', [laterSnapshotId])).resolves.toEqual(false);
+
+            // The node should produce new blocks after the revert.
+            const receipt = await (await alice.transfer({ to: account, amount: 1 })).wait();
+            expect(receipt.blockNumber).toBeGreaterThan(snapshotBlock);
+            expect(await provider.getBalance(account)).bnToBeEq(initialBalance.add(1));
+        });
+
+        afterAll(async () => {
+            await testMaster.deinitialize();
+        });
+    }
+});
@@ -757,6 +757,17 @@ describe('web3 API compatibility tests', () => {
         }
     });
 
+    test('Should check eth_maxPriorityFeePerGas', async () => {
+        await anyTransaction(alice);
+        // The operator only charges the base fee, so no priority fee is effectively paid.
+        const priorityFee = await alice.provider.send('eth_maxPriorityFeePerGas', []);
+        expect(ethers.BigNumber.from(priorityFee)).bnToBeEq(0);
+
+        const gasPrice = await alice.provider.getGasPrice();
+        const latestBlock = await alice.provider.getBlock('latest');
+        expect(gasPrice).bnToBeGte(latestBlock.baseFeePerGas!);
+    });
+
     test('Should check zks_getProtocolVersion endpoint', async () => {
         const latestProtocolVersion = await alice.provider.send('zks_getProtocolVersion', []);
         let expectedSysContractsHashes = {
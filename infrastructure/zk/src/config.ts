@@ -30,7 +30,12 @@ const CONFIG_FILES = [
     'proof_data_handler.toml',
     'fri_witness_vector_generator.toml',
     'fri_prover_gateway.toml',
-    'fri_proof_compressor.toml'
+    'fri_proof_compressor.toml',
+    'webhook_notifier.toml',
+    'stream_publisher.toml',
+    'withdrawal_finalizer.toml',
+    'token_registry.toml',
+    'multi_chain.toml'
 ];
 
 function loadConfigFile(path: string) {
@@ -0,0 +1,339 @@
+//! Background verification of circuit commitments (the events queue and the initial bootloader memory)
+//! for L1 batches that are proven, but not yet executed on L1.
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+
+use zkevm_test_harness::ethereum_types::{Address as CircuitAddress, U256 as CircuitU256};
+use zkevm_test_harness::witness::utils::{
+    events_queue_commitment_fixed, initial_heap_content_commitment_fixed,
+};
+use zksync_config::constants::USED_BOOTLOADER_MEMORY_BYTES;
+use zksync_dal::ConnectionPool;
+use zksync_object_store::ObjectStore;
+use zksync_prover_fri_types::circuit_definitions::zk_evm::aux_structures::{
+    LogQuery as CircuitLogQuery, Timestamp as CircuitTimestamp,
+};
+use zksync_prover_fri_types::AuxOutputWitnessWrapper;
+use zksync_prover_utils::periodic_job::PeriodicJob;
+use zksync_types::{L1BatchNumber, LogQuery, H256, U256};
+
+/// Maximum number of L1 batches verified during a single iteration.
+const MAX_L1_BATCHES_PER_ITERATION: usize = 10;
+
+/// Commitments from the auxiliary output of the scheduler circuit that can be recomputed
+/// from the data in Postgres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CircuitCommitments {
+    bootloader_heap_initial_content: H256,
+    events_queue_state: H256,
+}
+
+impl CircuitCommitments {
+    fn recompute(initial_heap_content: &[(usize, U256)], events_queue: &[LogQuery]) -> Self {
+        let heap = expand_bootloader_contents(initial_heap_content);
+        let events_queue = events_queue.iter().map(to_circuit_log_query).collect();
+        Self {
+            bootloader_heap_initial_content: H256(initial_heap_content_commitment_fixed(&heap)),
+            events_queue_state: H256(events_queue_commitment_fixed(&events_queue)),
+        }
+    }
+
+    fn from_public_inputs(public_inputs: &AuxOutputWitnessWrapper) -> Self {
+        Self {
+            bootloader_heap_initial_content: H256(public_inputs.0.bootloader_heap_initial_content),
+            events_queue_state: H256(public_inputs.0.events_queue_state),
+        }
+    }
+
+    /// Returns names of the commitments that differ from the ones in `expected`.
+    fn mismatches(&self, expected: &Self) -> Vec<&'static str> {
+        let mut mismatches = vec![];
+        if self.bootloader_heap_initial_content != expected.bootloader_heap_initial_content {
+            mismatches.push("bootloader_heap_initial_content");
+        }
+        if self.events_queue_state != expected.events_queue_state {
+            mismatches.push("events_queue_state");
+        }
+        mismatches
+    }
+}
+
+/// Outcome of verifying commitments for a single L1 batch.
+#[derive(Debug, PartialEq)]
+enum Verification {
+    /// All recomputed commitments match the prover public inputs.
+    Matched,
+    /// Names of the recomputed commitments that don't match the prover public inputs.
+    Mismatched(Vec<&'static str>),
+    /// Prover public inputs are not available yet; the L1 batch must be verified later.
+    Postponed,
+}
+
+impl Verification {
+    fn new(recomputed: &CircuitCommitments, public_inputs: Option<&CircuitCommitments>) -> Self {
+        let Some(public_inputs) = public_inputs else {
+            return Self::Postponed;
+        };
+        let mismatches = recomputed.mismatches(public_inputs);
+        if mismatches.is_empty() {
+            Self::Matched
+        } else {
+            Self::Mismatched(mismatches)
+        }
+    }
+}
+
+/// Loads commitments from the prover public inputs for the specified L1 batch. Returns `None` if the inputs
+/// cannot be loaded (e.g., they are not uploaded yet, or the object store is temporarily unavailable).
+async fn load_public_inputs(
+    blob_store: &dyn ObjectStore,
+    l1_batch_number: L1BatchNumber,
+) -> Option<CircuitCommitments> {
+    match blob_store
+        .get::<AuxOutputWitnessWrapper>(l1_batch_number)
+        .await
+    {
+        Ok(public_inputs) => Some(CircuitCommitments::from_public_inputs(&public_inputs)),
+        Err(err) => {
+            tracing::warn!(
+                "Cannot verify commitments for L1 batch #{l1_batch_number} yet: \
+                 failed loading prover public inputs: {err}"
+            );
+            None
+        }
+    }
+}
+
+fn expand_bootloader_contents(packed: &[(usize, U256)]) -> Vec<u8> {
+    let mut result = vec![0_u8; USED_BOOTLOADER_MEMORY_BYTES];
+    for (offset, value) in packed {
+        value.to_big_endian(&mut result[(offset * 32)..(offset + 1) * 32]);
+    }
+    result
+}
+
+fn to_circuit_log_query(log: &LogQuery) -> CircuitLogQuery {
+    CircuitLogQuery {
+        timestamp: CircuitTimestamp(log.timestamp.0),
+        tx_number_in_block: log.tx_number_in_block,
+        aux_byte: log.aux_byte,
+        shard_id: log.shard_id,
+        address: CircuitAddress::from_slice(log.address.as_bytes()),
+        key: CircuitU256(log.key.0),
+        read_value: CircuitU256(log.read_value.0),
+        written_value: CircuitU256(log.written_value.0),
+        rw_flag: log.rw_flag,
+        rollback: log.rollback,
+        is_service: log.is_service,
+    }
+}
+
+/// Recomputes the events queue commitment and the initial bootloader memory commitment for proven,
+/// but not yet executed L1 batches, and cross-checks them against the prover public inputs.
+/// This allows catching commitment bugs before the batches are executed on L1.
+pub struct CommitmentVerifier {
+    pool: ConnectionPool,
+    blob_store: Box<dyn ObjectStore>,
+    last_verified_l1_batch: L1BatchNumber,
+    polling_interval_ms: u64,
+}
+
+impl CommitmentVerifier {
+    pub fn new(
+        pool: ConnectionPool,
+        blob_store: Box<dyn ObjectStore>,
+        polling_interval_ms: u64,
+    ) -> Self {
+        Self {
+            pool,
+            blob_store,
+            last_verified_l1_batch: L1BatchNumber(0),
+            polling_interval_ms,
+        }
+    }
+
+    async fn verify_l1_batch(
+        &self,
+        l1_batch_number: L1BatchNumber,
+    ) -> anyhow::Result<Verification> {
+        let public_inputs = load_public_inputs(self.blob_store.as_ref(), l1_batch_number).await;
+
+        let mut storage = self.pool.access_storage().await?;
+        let initial_heap_content = storage
+            .blocks_dal()
+            .get_initial_bootloader_heap(l1_batch_number)
+            .await
+            .context("get_initial_bootloader_heap()")?
+            .with_context(|| format!("L1 batch #{l1_batch_number} is not in the DB"))?;
+        // The events queue is persisted together with the L1 batch, so it cannot appear later.
+        let events_queue = storage
+            .blocks_dal()
+            .get_events_queue(l1_batch_number)
+            .await
+            .context("get_events_queue()")?
+            .with_context(|| {
+                format!("events queue for L1 batch #{l1_batch_number} is not persisted")
+            })?;
+        drop(storage);
+
+        let recomputed = CircuitCommitments::recompute(&initial_heap_content, &events_queue);
+        let verification = Verification::new(&recomputed, public_inputs.as_ref());
+        match &verification {
+            Verification::Matched => {
+                tracing::info!("Verified circuit commitments for L1 batch #{l1_batch_number}");
+            }
+            Verification::Mismatched(mismatches) => {
+                for &commitment in mismatches {
+                    tracing::error!(
+                        "Commitment `{commitment}` recomputed for L1 batch #{l1_batch_number} doesn't match \
+                         the prover public inputs; recomputed commitments: {recomputed:?}"
+                    );
+                    metrics::counter!(
+                        "prover_fri.proof_fri_compressor.commitment_mismatch",
+                        1,
+                        "commitment" => commitment
+                    );
+                }
+            }
+            Verification::Postponed => { /* already logged */ }
+        }
+        Ok(verification)
+    }
+}
+
+#[async_trait]
+impl PeriodicJob for CommitmentVerifier {
+    const SERVICE_NAME: &'static str = "CommitmentVerifier";
+
+    async fn run_routine_task(&mut self) -> anyhow::Result<()> {
+        let l1_batch_numbers = self
+            .pool
+            .access_storage()
+            .await?
+            .proof_generation_dal()
+            .get_proven_unexecuted_l1_batches(
+                self.last_verified_l1_batch,
+                MAX_L1_BATCHES_PER_ITERATION,
+            )
+            .await;
+        for l1_batch_number in l1_batch_numbers {
+            let verification = self
+                .verify_l1_batch(l1_batch_number)
+                .await
+                .with_context(|| {
+                    format!("failed verifying commitments for L1 batch #{l1_batch_number}")
+                })?;
+            if verification == Verification::Postponed {
+                // Batches are verified in order, so that a postponed batch is retried on the next iteration.
+                break;
+            }
+            self.last_verified_l1_batch = l1_batch_number;
+        }
+        Ok(())
+    }
+
+    fn polling_interval_ms(&self) -> u64 {
+        self.polling_interval_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_object_store::ObjectStoreFactory;
+    use zksync_types::{Address, Timestamp};
+
+    use super::*;
+
+    fn mock_events_queue() -> Vec<LogQuery> {
+        let event = LogQuery {
+            timestamp: Timestamp(1),
+            tx_number_in_block: 0,
+            aux_byte: 0,
+            shard_id: 0,
+            address: Address::repeat_byte(1),
+            key: U256::from(1),
+            read_value: U256::zero(),
+            written_value: U256::from(2),
+            rw_flag: true,
+            rollback: false,
+            is_service: false,
+        };
+        vec![event]
+    }
+
+    fn mock_commitments() -> CircuitCommitments {
+        CircuitCommitments::recompute(&[(0, U256::from(1)), (5, U256::MAX)], &mock_events_queue())
+    }
+
+    #[test]
+    fn recomputed_commitments_depend_on_inputs() {
+        let commitments = mock_commitments();
+        let other_heap = CircuitCommitments::recompute(&[(0, U256::from(2))], &mock_events_queue());
+        assert_eq!(
+            commitments.events_queue_state,
+            other_heap.events_queue_state
+        );
+        assert_ne!(
+            commitments.bootloader_heap_initial_content,
+            other_heap.bootloader_heap_initial_content
+        );
+
+        let other_events =
+            CircuitCommitments::recompute(&[(0, U256::from(1)), (5, U256::MAX)], &[]);
+        assert_eq!(
+            commitments.bootloader_heap_initial_content,
+            other_events.bootloader_heap_initial_content
+        );
+        assert_ne!(
+            commitments.events_queue_state,
+            other_events.events_queue_state
+        );
+    }
+
+    #[test]
+    fn matching_commitments() {
+        let recomputed = mock_commitments();
+        let public_inputs = mock_commitments();
+        assert_eq!(
+            Verification::new(&recomputed, Some(&public_inputs)),
+            Verification::Matched
+        );
+    }
+
+    #[test]
+    fn mismatched_commitments() {
+        let recomputed = mock_commitments();
+        let public_inputs = CircuitCommitments {
+            events_queue_state: H256::repeat_byte(1),
+            ..recomputed
+        };
+        assert_eq!(
+            Verification::new(&recomputed, Some(&public_inputs)),
+            Verification::Mismatched(vec!["events_queue_state"])
+        );
+
+        let public_inputs = CircuitCommitments {
+            bootloader_heap_initial_content: H256::zero(),
+            events_queue_state: H256::zero(),
+        };
+        assert_eq!(
+            Verification::new(&recomputed, Some(&public_inputs)),
+            Verification::Mismatched(vec![
+                "bootloader_heap_initial_content",
+                "events_queue_state"
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn verification_is_postponed_without_public_inputs() {
+        let blob_store = ObjectStoreFactory::mock().create_store().await;
+        let public_inputs = load_public_inputs(blob_store.as_ref(), L1BatchNumber(1)).await;
+        assert_eq!(public_inputs, None);
+        assert_eq!(
+            Verification::new(&mock_commitments(), public_inputs.as_ref()),
+            Verification::Postponed
+        );
+    }
+}
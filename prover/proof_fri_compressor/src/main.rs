@@ -10,11 +10,14 @@ use zksync_config::configs::FriProofCompressorConfig;
 use zksync_dal::connection::DbVariant;
 use zksync_dal::ConnectionPool;
 use zksync_object_store::ObjectStoreFactory;
+use zksync_prover_utils::periodic_job::PeriodicJob;
 use zksync_queued_job_processor::JobProcessor;
 use zksync_utils::wait_for_tasks::wait_for_tasks;
 
+use crate::commitment_verifier::CommitmentVerifier;
 use crate::compressor::ProofCompressor;
 
+mod commitment_verifier;
 mod compressor;
 
 #[derive(Debug, StructOpt)]
@@ -86,10 +89,24 @@ async fn main() -> anyhow::Result<()> {
         config.prometheus_pushgateway_url,
         Duration::from_millis(config.prometheus_push_interval_ms.unwrap_or(100)),
     );
-    let tasks = vec![
+    let mut tasks = vec![
         tokio::spawn(prometheus_config.run(stop_receiver.clone())),
         tokio::spawn(proof_compressor.run(stop_receiver, opt.number_of_iterations)),
     ];
+    if let Some(interval_ms) = config.commitment_verification_interval_ms {
+        // Batch data necessary to recompute commitments is stored in the main DB.
+        let master_pool = ConnectionPool::builder(DbVariant::Master)
+            .build()
+            .await
+            .context("failed to build a master connection pool")?;
+        let verifier_blob_store = ObjectStoreFactory::prover_from_env()
+            .context("ObjectStoreFactory::prover_from_env()")?
+            .create_store()
+            .await;
+        let commitment_verifier =
+            CommitmentVerifier::new(master_pool, verifier_blob_store, interval_ms);
+        tasks.push(tokio::spawn(commitment_verifier.run()));
+    }
 
     let graceful_shutdown = None::<futures::future::Ready<()>>;
     let tasks_allowed_to_finish = false;